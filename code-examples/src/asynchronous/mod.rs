@@ -38,12 +38,8 @@ pub async fn example_async_retry() {
 pub async fn example_async_exponential_with_condition() {
     let should_retry = |error: &Error| error.to_string().contains("not found");
 
-    let retry_config = RetryConfig {
-        max_attempts: 4,
-        delay: Duration::from_millis(100),
-        retry_condition: Some(should_retry),
-        strategy: ExponentialBackoff,
-    };
+    let retry_config = RetryConfig::new(4, Duration::from_millis(100), ExponentialBackoff)
+        .with_retry_condition(should_retry);
 
     let result = retry(|| async { send().await }, &retry_config).await;
 
@@ -54,7 +50,7 @@ pub async fn example_async_exponential_with_condition() {
 }
 
 // Define slow_operation as a reusable async function
-async fn slow_operation() -> Result<String, Box<dyn std::error::Error>> {
+async fn slow_operation() -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
     sleep(Duration::from_millis(100)).await;
     Ok("Success".to_string())
 }
@@ -62,16 +58,11 @@ async fn slow_operation() -> Result<String, Box<dyn std::error::Error>> {
 // Example 3: Execute with timeout and optional fallback
 pub async fn example_execute_with_fallback() {
     // Config with fallback
-    let config_with_fallback = ExecConfig {
-        timeout_duration: Duration::from_millis(50),
-        fallback: Some(|| Ok("Fallback result".to_string())),
-    };
+    let config_with_fallback = ExecConfig::new(Duration::from_millis(50))
+        .with_fallback(|_| Ok("Fallback result".to_string()));
 
     // Config without fallback
-    let config_without_fallback = ExecConfig {
-        timeout_duration: Duration::from_millis(50),
-        fallback: None::<fn() -> Result<String, Box<dyn std::error::Error>>>,
-    };
+    let config_without_fallback: ExecConfig<String> = ExecConfig::new(Duration::from_millis(50));
 
     // Test with fallback
     let result_with_fallback = execute_with_fallback(slow_operation(), &config_with_fallback).await;
@@ -90,23 +81,23 @@ pub async fn example_execute_with_fallback() {
 }
 
 // Example 4: Circuit Breaker
-async fn dangerous_call() -> Result<(), Box<dyn std::error::Error>> {
+async fn dangerous_call() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     sleep(Duration::from_millis(100)).await;
     if rng().random_range(0..2) == 0 {
         // Updated rng() to thread_rng()
-        return Err("Operation failed".into()); // Convert string to Box<dyn Error>
+        return Err("Operation failed".into()); // Convert string to Box<dyn Error + Send + Sync>
     }
     Ok(())
 }
 
-pub async fn circuit_breaker() -> Result<(), Box<dyn std::error::Error>> {
+pub async fn circuit_breaker() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let circuit_breaker_conf = CircuitBreakerConfig::new(
         2,                          // max failures
         3,                          // reset attempts
         Duration::from_millis(300), // timeout
     );
 
-    let mut cb = CircuitBreaker::new(circuit_breaker_conf);
+    let cb = CircuitBreaker::new(circuit_breaker_conf);
 
     for n in 1..10 {
         let result = cb.run(|| async { dangerous_call().await }).await;
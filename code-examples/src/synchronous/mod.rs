@@ -35,12 +35,7 @@ pub fn example_simple_retry() {
 // Example 2: Using retry_with_exponential_backoff() with a counter
 pub fn example_exponential_backoff() {
     // Configure retry with 4 attempts and initial 100ms delay
-    let retry_config = RetryConfig {
-        max_attempts: 4,
-        delay: Duration::from_millis(100),
-        retry_condition: None,
-        strategy: ExponentialBackoff,
-    };
+    let retry_config = RetryConfig::new(4, Duration::from_millis(100), ExponentialBackoff);
 
     let mut counter = 0;
 
@@ -67,12 +62,8 @@ pub fn example_retry_with_condition() {
     let should_retry = |error: &String| error.contains("401") || error.contains("404");
 
     // Configure retry with condition
-    let retry_config = RetryConfig {
-        max_attempts: 4,
-        delay: Duration::from_millis(300),
-        retry_condition: Some(should_retry),
-        strategy: Linear,
-    };
+    let retry_config =
+        RetryConfig::new(4, Duration::from_millis(300), Linear).with_retry_condition(should_retry);
 
     let mut attempt_count = 0;
 
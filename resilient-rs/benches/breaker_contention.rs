@@ -0,0 +1,76 @@
+//! Measures the throughput of the mutex-based and lock-free circuit breakers when many threads
+//! call through the same breaker.
+//!
+//! Run with `cargo bench --bench breaker_contention`.
+
+use resilient_rs::config::{CircuitBreakerConfig, FailureWindow};
+use resilient_rs::lockfree::LockFreeCircuitBreaker;
+use resilient_rs::synchronous::CircuitBreaker;
+use std::hint::black_box;
+use std::sync::{Arc, Barrier};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const CALLS_PER_THREAD: u64 = 200_000;
+const THREADS: [usize; 4] = [1, 2, 4, 8];
+
+/// Runs `CALLS_PER_THREAD` calls on each of `threads` threads, one in a thousand failing, and
+/// returns the number of calls per second.
+fn measure<F>(threads: usize, call: F) -> f64
+where
+    F: Fn(u64) + Send + Sync + 'static,
+{
+    let call = Arc::new(call);
+    let barrier = Arc::new(Barrier::new(threads + 1));
+    let workers: Vec<_> = (0..threads)
+        .map(|_| {
+            let call = Arc::clone(&call);
+            let barrier = Arc::clone(&barrier);
+            thread::spawn(move || {
+                barrier.wait();
+                for i in 0..CALLS_PER_THREAD {
+                    call(i);
+                }
+            })
+        })
+        .collect();
+    barrier.wait();
+    let start = Instant::now();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+    (threads as u64 * CALLS_PER_THREAD) as f64 / start.elapsed().as_secs_f64()
+}
+
+fn bench(name: &str, config: CircuitBreakerConfig) {
+    println!("{name}");
+    println!(
+        "{:>8} {:>20} {:>20}",
+        "threads", "mutex (calls/s)", "lock-free (calls/s)"
+    );
+    for threads in THREADS {
+        let mutex = Arc::new(CircuitBreaker::<u64>::with_config(config));
+        let mutex = measure(threads, move |i| {
+            let _ = black_box(mutex.run(|| if i % 1_000 == 0 { Err(i) } else { Ok(i) }));
+        });
+        let lock_free = Arc::new(LockFreeCircuitBreaker::<u64>::with_config(config));
+        let lock_free = measure(threads, move |i| {
+            let _ = black_box(lock_free.run(|| if i % 1_000 == 0 { Err(i) } else { Ok(i) }));
+        });
+        println!("{threads:>8} {mutex:>20.0} {lock_free:>20.0}");
+    }
+    println!();
+}
+
+fn main() {
+    let config = CircuitBreakerConfig::new(5, 2, Duration::from_secs(30));
+    bench("consecutive failures", config);
+    bench(
+        "count window",
+        config.with_window(FailureWindow::count(100, 50.0, 20)),
+    );
+    bench(
+        "time window",
+        config.with_window(FailureWindow::time(Duration::from_secs(10), 50.0, 20)),
+    );
+}
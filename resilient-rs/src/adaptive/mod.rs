@@ -0,0 +1,244 @@
+use crate::bulkhead;
+use crate::config::AimdConfig;
+use crate::logging::Level;
+use crate::logging::log_with;
+use crate::time;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+pub use crate::bulkhead::BulkheadError;
+
+/// A concurrency limiter adjusting its limit to the observed latency and errors of the calls it
+/// admits, using the AIMD (additive increase, multiplicative decrease) algorithm.
+///
+/// Like a `Bulkhead`, the limiter rejects calls with `BulkheadError::Full` once `limit` calls are
+/// running, but the limit is not hand-tuned:
+/// - A call that succeeds within `latency_threshold` while the limiter is at least half used
+///   raises the limit by one, up to `max_limit`.
+/// - A call that fails, or succeeds slower than `latency_threshold`, multiplies the limit by
+///   `backoff_ratio`, down to `min_limit`.
+///
+/// A slowing dependency thus quickly receives less concurrent traffic, and the limit probes its
+/// way back up once it recovers. The limiter never waits for a slot, since queueing would hide
+/// the very latency it reacts to.
+///
+/// The limiter takes `&self` and can be shared through an `Arc` or a static. `call` supervises a
+/// blocking operation and `call_async` an asynchronous one; `try_acquire` lets callers report
+/// outcomes themselves.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::adaptive::AdaptiveLimiter;
+/// use resilient_rs::config::AimdConfig;
+///
+/// let limiter = AdaptiveLimiter::new(AimdConfig::default().with_limits(10, 1, 100));
+/// let _ = limiter.call(|| Err::<(), _>("overloaded"));
+/// assert_eq!(limiter.limit(), 9);
+/// ```
+pub struct AdaptiveLimiter {
+    config: AimdConfig,
+    state: Mutex<AimdState>,
+}
+
+/// The current limit of an `AdaptiveLimiter` and the number of calls it admitted.
+struct AimdState {
+    limit: usize,
+    in_flight: usize,
+}
+
+/// A call admitted by an `AdaptiveLimiter`.
+///
+/// Report its outcome with `success` or `failure`; a permit dropped without an outcome, e.g.
+/// because the call was cancelled, frees its slot without adjusting the limit.
+pub struct AdaptivePermit<'a> {
+    limiter: &'a AdaptiveLimiter,
+    started: Instant,
+    released: bool,
+}
+
+impl AdaptivePermit<'_> {
+    /// Reports that the call succeeded, raising the limit unless the call was too slow.
+    pub fn success(mut self) {
+        let latency = time::elapsed(self.started);
+        self.released = true;
+        self.limiter.release(Some(latency));
+    }
+
+    /// Reports that the call failed, lowering the limit.
+    pub fn failure(mut self) {
+        self.released = true;
+        self.limiter.release(None);
+    }
+}
+
+impl Drop for AdaptivePermit<'_> {
+    fn drop(&mut self) {
+        if !self.released {
+            self.limiter.state().in_flight -= 1;
+        }
+    }
+}
+
+impl AdaptiveLimiter {
+    /// Creates a limiter starting at `config.initial_limit`.
+    ///
+    /// # Arguments
+    /// * `config` - The AIMD settings; check them with `AimdConfig::validate`.
+    pub fn new(config: AimdConfig) -> Self {
+        AdaptiveLimiter {
+            config,
+            state: Mutex::new(AimdState {
+                limit: config.initial_limit,
+                in_flight: 0,
+            }),
+        }
+    }
+
+    /// Admits a call if fewer than `limit` calls are running.
+    ///
+    /// # Returns
+    /// - `Ok(permit)` if the call may run; report its outcome through the permit.
+    /// - `Err(BulkheadError::Full)` if the limit is reached.
+    pub fn try_acquire<E>(&self) -> Result<AdaptivePermit<'_>, BulkheadError<E>> {
+        let mut state = self.state();
+        if state.in_flight >= state.limit {
+            let limit = state.limit;
+            drop(state);
+            return Err(bulkhead::rejected(&self.config.log, limit));
+        }
+        state.in_flight += 1;
+        Ok(AdaptivePermit {
+            limiter: self,
+            started: time::now(),
+            released: false,
+        })
+    }
+
+    /// Executes a blocking operation if the limit allows it, and adjusts the limit to its outcome.
+    ///
+    /// # Returns
+    /// - `Ok(T)` if the operation succeeds.
+    /// - `Err(BulkheadError::Inner(E))` if the operation fails.
+    /// - `Err(BulkheadError::Full)` if the limit is reached.
+    pub fn call<F, T, E>(&self, operation: F) -> Result<T, BulkheadError<E>>
+    where
+        F: FnOnce() -> Result<T, E>,
+    {
+        let permit = self.try_acquire()?;
+        Self::report(permit, operation())
+    }
+
+    /// Executes an asynchronous operation if the limit allows it, and adjusts the limit to its
+    /// outcome.
+    ///
+    /// # Returns
+    /// The same as `call`. If the returned future is dropped before the operation completes, the
+    /// slot is freed without adjusting the limit.
+    pub async fn call_async<F, Fut, T, E>(&self, operation: F) -> Result<T, BulkheadError<E>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let permit = self.try_acquire()?;
+        Self::report(permit, operation().await)
+    }
+
+    /// Returns the current concurrency limit.
+    pub fn limit(&self) -> usize {
+        self.state().limit
+    }
+
+    /// Returns the number of calls currently running.
+    pub fn in_flight(&self) -> usize {
+        self.state().in_flight
+    }
+
+    fn report<T, E>(
+        permit: AdaptivePermit<'_>,
+        result: Result<T, E>,
+    ) -> Result<T, BulkheadError<E>> {
+        match result {
+            Ok(output) => {
+                permit.success();
+                Ok(output)
+            }
+            Err(err) => {
+                permit.failure();
+                Err(BulkheadError::Inner(err))
+            }
+        }
+    }
+
+    /// Frees a slot and adjusts the limit; `latency` is `None` for a failed call.
+    fn release(&self, latency: Option<Duration>) {
+        let mut state = self.state();
+        let utilized = state.in_flight * 2 >= state.limit;
+        state.in_flight -= 1;
+        match latency {
+            Some(latency) if latency <= self.config.latency_threshold => {
+                if utilized && state.limit < self.config.max_limit {
+                    state.limit += 1;
+                }
+            }
+            _ => {
+                let decreased = (state.limit as f64 * self.config.backoff_ratio) as usize;
+                let decreased = decreased.max(self.config.min_limit);
+                if decreased < state.limit {
+                    log_with!(
+                        self.config.log,
+                        Level::Info,
+                        "Adaptive limiter decreasing its limit from {} to {}",
+                        state.limit,
+                        decreased
+                    );
+                    state.limit = decreased;
+                }
+            }
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, AimdState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread::sleep;
+
+    #[test]
+    fn test_aimd_adjusts_limit_to_outcomes() {
+        let limiter = AdaptiveLimiter::new(
+            AimdConfig::default()
+                .with_limits(2, 1, 3)
+                .with_backoff_ratio(0.5)
+                .with_latency_threshold(Duration::from_millis(20)),
+        );
+
+        // Successes while at least half of the limit is used raise it, up to `max_limit`.
+        for _ in 0..3 {
+            assert_eq!(limiter.call(|| Ok::<_, &str>(())), Ok(()));
+        }
+        assert_eq!(limiter.limit(), 3);
+
+        // Slow successes and failures halve it, down to `min_limit`.
+        let slow = limiter.call(|| {
+            sleep(Duration::from_millis(30));
+            Ok::<_, &str>(())
+        });
+        assert!(slow.is_ok());
+        assert_eq!(limiter.limit(), 1);
+        assert_eq!(
+            limiter.call(|| Err::<(), _>("down")),
+            Err(BulkheadError::Inner("down"))
+        );
+        assert_eq!(limiter.limit(), 1);
+
+        let permit = limiter.try_acquire::<&str>().unwrap();
+        assert_eq!(limiter.call(|| Ok::<_, &str>(())), Err(BulkheadError::Full));
+        drop(permit);
+        assert_eq!((limiter.in_flight(), limiter.limit()), (0, 1));
+    }
+}
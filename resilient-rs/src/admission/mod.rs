@@ -0,0 +1,374 @@
+use crate::breaker::CircuitBreakerState;
+use crate::config::{AdmissionConfig, ConfigError};
+use crate::events::{self, ResilienceEvent};
+use crate::logging::log_with;
+use crate::metrics;
+use crate::registry::CircuitBreakerRegistry;
+use crate::shedding::LoadShedder;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// The priority of a call submitted to an `AdmissionController`, from the first to be rejected
+/// to the last.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Priority {
+    /// Work that can be dropped first, such as prefetching, analytics or batch jobs.
+    Low,
+    /// Regular user-facing work.
+    Normal,
+    /// Work that should keep flowing during a brown-out, such as checkouts or logins.
+    High,
+    /// Work that must not be shed, such as health checks; only rejected at full capacity.
+    Critical,
+}
+
+impl Priority {
+    /// Returns the lowercase name of the priority.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Priority::Low => "low",
+            Priority::Normal => "normal",
+            Priority::High => "high",
+            Priority::Critical => "critical",
+        }
+    }
+}
+
+impl fmt::Display for Priority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A source of load considered by an `AdmissionController`, reporting how overloaded the
+/// service is between 0 (idle) and 1 (saturated).
+///
+/// It is implemented for:
+/// - `LoadShedder`, reporting the fraction of recent calls it shed,
+/// - `CircuitBreakerRegistry`, reporting the fraction of its breakers that are open,
+/// - any `Fn() -> f64` closure, e.g. reading a queue depth or the CPU usage.
+///
+/// Values outside `0.0..=1.0` are clamped.
+pub trait LoadSignal: Send + Sync {
+    /// Returns the current load.
+    fn load(&self) -> f64;
+}
+
+impl<F> LoadSignal for F
+where
+    F: Fn() -> f64 + Send + Sync,
+{
+    fn load(&self) -> f64 {
+        self()
+    }
+}
+
+impl LoadSignal for LoadShedder {
+    fn load(&self) -> f64 {
+        self.shed_rate()
+    }
+}
+
+impl LoadSignal for CircuitBreakerRegistry {
+    /// Returns the fraction of the breakers created so far that are open or forced open.
+    fn load(&self) -> f64 {
+        let breakers = self.breakers();
+        if breakers.is_empty() {
+            return 0.0;
+        }
+        let open = breakers
+            .iter()
+            .filter(|breaker| {
+                matches!(
+                    breaker.state(),
+                    CircuitBreakerState::Open | CircuitBreakerState::ForcedOpen
+                )
+            })
+            .count();
+        open as f64 / breakers.len() as f64
+    }
+}
+
+/// The error returned by `AdmissionController::call`.
+///
+/// Like a shed call, a rejected call should not be retried locally: the retries would add to the
+/// load that caused the rejection.
+#[derive(Debug, PartialEq)]
+pub enum AdmissionError<E> {
+    /// The call was rejected without running the operation, because the `load` had reached the
+    /// threshold of its `priority`.
+    Rejected { priority: Priority, load: f64 },
+    /// The operation ran and failed with this error.
+    Inner(E),
+}
+
+impl<E> AdmissionError<E> {
+    /// Returns `true` if the call was rejected.
+    pub fn is_rejected(&self) -> bool {
+        matches!(self, AdmissionError::Rejected { .. })
+    }
+
+    /// Returns the error of the operation, or `None` if the call was rejected.
+    pub fn into_inner(self) -> Option<E> {
+        match self {
+            AdmissionError::Inner(err) => Some(err),
+            AdmissionError::Rejected { .. } => None,
+        }
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for AdmissionError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AdmissionError::Rejected { priority, load } => write!(
+                f,
+                "Service is browned out (load {:.2}), {} priority work is rejected. Please try later..!",
+                load, priority
+            ),
+            AdmissionError::Inner(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> Error for AdmissionError<E> {}
+
+/// A guard admitting calls by priority, rejecting low-priority work first as the load rises so
+/// that high-priority work keeps flowing during a brown-out.
+///
+/// The load is the highest of the controller's in-flight utilization and of its `LoadSignal`s,
+/// such as the shed rate of a `LoadShedder` or the share of open circuit breakers. A call is
+/// rejected with `AdmissionError::Rejected` once the load reaches the threshold of its priority
+/// in the `AdmissionConfig`. `Critical` calls ignore the signals and are only rejected once
+/// `max_in_flight` calls are running.
+///
+/// The controller takes `&self` and can be shared through an `Arc` or a static.
+///
+/// # Example
+/// ```
+/// use resilient_rs::admission::{AdmissionController, Priority};
+/// use resilient_rs::config::AdmissionConfig;
+///
+/// // Every dependency is down: only critical work is admitted.
+/// let controller = AdmissionController::new(AdmissionConfig::new(10)).with_signal(|| 1.0);
+/// assert!(controller.call(Priority::High, || Ok::<_, &str>(())).unwrap_err().is_rejected());
+/// assert_eq!(controller.call(Priority::Critical, || Ok::<_, &str>(42)), Ok(42));
+/// ```
+pub struct AdmissionController {
+    config: AdmissionConfig,
+    signals: Vec<Arc<dyn LoadSignal>>,
+    in_flight: Mutex<usize>,
+}
+
+/// A call admitted by an `AdmissionController`, freeing its slot when dropped.
+struct AdmissionPermit<'a> {
+    controller: &'a AdmissionController,
+}
+
+impl Drop for AdmissionPermit<'_> {
+    fn drop(&mut self) {
+        *self.controller.in_flight() -= 1;
+    }
+}
+
+impl AdmissionController {
+    /// Creates a controller with no call in flight and no load signal.
+    ///
+    /// # Arguments
+    /// * `config` - The capacity and the rejection thresholds of every priority.
+    ///
+    /// # Panics
+    /// Panics if `config` is invalid, e.g. a literal with a `max_in_flight` of 0. Use `try_new`
+    /// to get a `ConfigError` instead.
+    pub fn new(config: AdmissionConfig) -> Self {
+        Self::try_new(config).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates a controller, returning an error instead of panicking on an invalid `config`.
+    ///
+    /// # Returns
+    /// The controller, or the `ConfigError` reported by `AdmissionConfig::validate`.
+    pub fn try_new(config: AdmissionConfig) -> Result<Self, ConfigError> {
+        config.validate()?;
+        Ok(AdmissionController {
+            config,
+            signals: Vec::new(),
+            in_flight: Mutex::new(0),
+        })
+    }
+
+    /// Adds a load signal and returns the modified controller.
+    ///
+    /// # Arguments
+    /// * `signal` - A source of load, e.g. a closure returning a value between 0 and 1.
+    pub fn with_signal(mut self, signal: impl LoadSignal + 'static) -> Self {
+        self.signals.push(Arc::new(signal));
+        self
+    }
+
+    /// Adds a shared load signal, such as a `LoadShedder` or a `CircuitBreakerRegistry` also
+    /// used elsewhere, and returns the modified controller.
+    pub fn with_shared_signal(mut self, signal: Arc<dyn LoadSignal>) -> Self {
+        self.signals.push(signal);
+        self
+    }
+
+    /// Executes a blocking operation if the current load admits its priority.
+    ///
+    /// # Returns
+    /// - `Ok(T)` if the operation succeeds.
+    /// - `Err(AdmissionError::Inner(E))` if the operation fails.
+    /// - `Err(AdmissionError::Rejected)` if the call was rejected.
+    pub fn call<F, T, E>(&self, priority: Priority, operation: F) -> Result<T, AdmissionError<E>>
+    where
+        F: FnOnce() -> Result<T, E>,
+    {
+        let _permit = self.try_admit(priority)?;
+        operation().map_err(AdmissionError::Inner)
+    }
+
+    /// Executes an asynchronous operation if the current load admits its priority.
+    ///
+    /// # Returns
+    /// The same as `call`. The slot is freed when the operation completes or the returned future
+    /// is dropped.
+    pub async fn call_async<F, Fut, T, E>(
+        &self,
+        priority: Priority,
+        operation: F,
+    ) -> Result<T, AdmissionError<E>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let _permit = self.try_admit(priority)?;
+        operation().await.map_err(AdmissionError::Inner)
+    }
+
+    /// Returns the current load between 0 and 1, the highest of the in-flight utilization and of
+    /// the load signals.
+    pub fn load(&self) -> f64 {
+        let utilization = *self.in_flight() as f64 / self.config.max_in_flight as f64;
+        self.signal_load().max(utilization)
+    }
+
+    /// Returns the number of calls currently running.
+    pub fn in_flight_calls(&self) -> usize {
+        *self.in_flight()
+    }
+
+    fn signal_load(&self) -> f64 {
+        self.signals
+            .iter()
+            .map(|signal| signal.load().clamp(0.0, 1.0))
+            .fold(0.0, f64::max)
+    }
+
+    fn threshold(&self, priority: Priority) -> f64 {
+        match priority {
+            Priority::Low => self.config.low_threshold,
+            Priority::Normal => self.config.normal_threshold,
+            Priority::High => self.config.high_threshold,
+            Priority::Critical => 1.0,
+        }
+    }
+
+    fn try_admit<E>(&self, priority: Priority) -> Result<AdmissionPermit<'_>, AdmissionError<E>> {
+        // The signals are read before locking, since they may take locks of their own.
+        let signal_load = match priority {
+            Priority::Critical => 0.0,
+            _ => self.signal_load(),
+        };
+        let mut in_flight = self.in_flight();
+        let utilization = *in_flight as f64 / self.config.max_in_flight as f64;
+        let load = signal_load.max(utilization);
+        if load >= self.threshold(priority) {
+            drop(in_flight);
+            log_with!(
+                self.config.log,
+                self.config.log.level,
+                "Admission controller is at load {:.2}.. {} priority request is rejected",
+                load,
+                priority
+            );
+            metrics::increment(&metrics::LOAD_SHED);
+            events::emit(ResilienceEvent::LoadShed);
+            return Err(AdmissionError::Rejected { priority, load });
+        }
+        *in_flight += 1;
+        Ok(AdmissionPermit { controller: self })
+    }
+
+    fn in_flight(&self) -> MutexGuard<'_, usize> {
+        self.in_flight
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl fmt::Debug for AdmissionController {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AdmissionController")
+            .field("config", &self.config)
+            .field("signals", &self.signals.len())
+            .field("in_flight", &self.in_flight_calls())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_low_priority_work_is_rejected_first_as_calls_pile_up() {
+        let controller = AdmissionController::new(AdmissionConfig::new(4));
+        let admitted = |priority| controller.call(priority, || Ok::<_, ()>(())).is_ok();
+
+        // Two calls in flight put the load at 0.5, the default threshold of `Low` work.
+        let outcome = controller.call(Priority::Normal, || {
+            controller.call(Priority::Normal, || {
+                Ok::<_, ()>([
+                    admitted(Priority::Low),
+                    admitted(Priority::Normal),
+                    admitted(Priority::High),
+                    admitted(Priority::Critical),
+                ])
+            })
+        });
+        assert_eq!(outcome, Ok([false, true, true, true]));
+        assert_eq!(controller.in_flight_calls(), 0);
+        assert!(
+            AdmissionConfig::default()
+                .with_thresholds(0.9, 0.5, 1.0)
+                .validate()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_invalid_config_is_rejected_by_the_controller() {
+        let config = AdmissionConfig {
+            max_in_flight: 0,
+            ..AdmissionConfig::default()
+        };
+        let err = AdmissionController::try_new(config).unwrap_err();
+        assert!(err.to_string().contains("max_in_flight"));
+    }
+
+    #[test]
+    fn test_open_breakers_brown_out_everything_but_critical_work() {
+        let breakers = Arc::new(CircuitBreakerRegistry::default());
+        let controller = AdmissionController::new(AdmissionConfig::new(100))
+            .with_shared_signal(breakers.clone());
+        let admitted = |priority| controller.call(priority, || Ok::<_, ()>(())).is_ok();
+        breakers.get("payments");
+        assert!(admitted(Priority::Low));
+
+        breakers.get("payments").force_open();
+        assert_eq!(controller.load(), 1.0);
+        assert!(!admitted(Priority::Low));
+        assert!(!admitted(Priority::High));
+        assert!(admitted(Priority::Critical));
+    }
+}
@@ -1,9 +1,40 @@
-use crate::config::{CircuitBreakerConfig, ExecConfig, RetryConfig};
-use async_std::future::timeout;
-use async_std::task::sleep;
-use log::{debug, error, info, warn};
-use std::error::Error;
-use std::time::Instant;
+use crate::breaker::{self, BreakerCore, CallPermit, Generation};
+use crate::bulkhead;
+use crate::classifier::{ErrorClass, ErrorClassifier};
+use crate::config::{
+    BoxError, BulkheadConfig, CircuitBreakerConfig, ExecConfig, ExecOutcome, FallbackCause,
+    FallbackChain, LogConfig, RetryConfig, ServedBy, SharedCircuitBreakerConfig, SharedRetryConfig,
+};
+use crate::defaults;
+use crate::error;
+use crate::events::{self, ResilienceEvent};
+use crate::idempotency::IdempotencyKey;
+use crate::logging::Level;
+use crate::logging::{log_sampled, log_with};
+use crate::metrics;
+use crate::shutdown::{ShutdownError, ShutdownHandle};
+use crate::stats::Stats;
+use crate::store::StateStore;
+use crate::time::{self, sleep, timeout};
+use crate::timeline::{AttemptOutcome, Timeline};
+use crate::wheel::TimerWheel;
+use async_std::channel::{self, Receiver, Sender};
+use async_std::stream::Stream;
+use async_std::task::{self, JoinHandle};
+use std::fmt;
+use std::future::poll_fn;
+use std::ops::Deref;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::{Pin, pin};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::Poll;
+use std::time::{Duration, Instant};
+
+pub use crate::breaker::{
+    CircuitBreakerError, CircuitBreakerMetrics, CircuitBreakerState, TransitionEvent,
+};
+pub use crate::bulkhead::BulkheadError;
 
 /// Retries a given asynchronous operation based on the specified retry configuration.
 ///
@@ -13,7 +44,8 @@ use std::time::Instant;
 ///
 /// # Returns
 /// * `Ok(T)` if the operation succeeds within the allowed attempts.
-/// * `Err(E)` if the operation fails after all retry attempts.
+/// * `Err(E)` if the operation fails after all retry attempts, or with an error classified as
+///   `Permanent` or `Fatal`.
 ///
 /// # Example
 /// ```rust
@@ -46,337 +78,2316 @@ use std::time::Instant;
 /// }
 /// ```
 ///
+/// For a long-lived connection that should survive failures between calls, see
+/// `tcp::ResilientTcpStream`, which re-establishes itself instead of dialing per call.
+///
 /// # Notes
 /// - The function logs warnings for failed attempts and final failure.
-pub async fn retry<F, Fut, T, E>(mut operation: F, retry_config: &RetryConfig<E>) -> Result<T, E>
+pub async fn retry<F, Fut, T, E>(operation: F, retry_config: &RetryConfig<E>) -> Result<T, E>
 where
     F: FnMut() -> Fut,
     Fut: Future<Output = Result<T, E>>,
 {
-    let mut attempts = 0;
-    let mut delay = retry_config.delay;
+    retry_with_hook(operation, |_: &E| async {}, retry_config).await
+}
 
-    loop {
-        match operation().await {
-            Ok(output) => {
-                info!("Operation succeeded after {} attempts", attempts + 1);
-                return Ok(output);
-            }
-            Err(err) if attempts + 1 < retry_config.max_attempts => {
-                let should_retry = retry_config.retry_condition.map_or(true, |f| f(&err));
-                if should_retry {
-                    warn!(
-                        "Operation failed (attempt {}/{}), retrying after {:?} with {:?} strategy...",
-                        attempts + 1,
-                        retry_config.max_attempts,
-                        delay,
-                        retry_config.strategy
-                    );
-                    sleep(delay).await;
-                    delay = retry_config.strategy.calculate_delay(delay, attempts + 1);
-                } else {
-                    warn!(
-                        "Operation failed (attempt {}/{}), not retryable, giving up.",
-                        attempts + 1,
-                        retry_config.max_attempts
-                    );
-                    return Err(err);
-                }
-            }
-            Err(err) => {
-                warn!(
-                    "Operation failed after {} attempts, giving up.",
-                    attempts + 1
-                );
-                return Err(err);
-            }
-        }
+/// Retries an asynchronous operation with the process-wide retry policy.
+///
+/// The policy is the one set with `set_global_retry_config`, or `RetryConfig::default()` if
+/// none was set, so that small applications can configure retries once at startup.
+///
+/// # Arguments
+/// * `operation` - A closure that returns a `Future` resolving to a `Result<T, E>`.
+///
+/// # Returns
+/// The same as `retry`.
+///
+/// # Example
+/// ```
+/// use async_std::task::block_on;
+/// use resilient_rs::asynchronous::retry_with_defaults;
+///
+/// let result = block_on(retry_with_defaults(|| async { Ok::<_, String>("connected") }));
+/// assert_eq!(result, Ok("connected"));
+/// ```
+pub async fn retry_with_defaults<F, Fut, T, E>(operation: F) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    retry(operation, &defaults::global_retry_config()).await
+}
 
-        attempts += 1;
-    }
+/// Retries a non-idempotent asynchronous operation, passing the same idempotency key to every
+/// attempt.
+///
+/// A key is generated once, before the first attempt, and a clone of it is handed to the
+/// operation each time it runs, so that it can be sent along with the request (typically in an
+/// `Idempotency-Key` header). A server deduplicating requests by key then executes the operation
+/// at most once, even when an attempt timed out after the server had processed it.
+///
+/// # Arguments
+/// * `operation` - A closure receiving the idempotency key and returning a `Future` resolving to a `Result<T, E>`.
+/// * `retry_config` - A reference to `RetryConfig` specifying the maximum attempts and delay between retries.
+///
+/// # Returns
+/// The same as `retry`.
+///
+/// # Example
+/// ```
+/// use async_std::task;
+/// use resilient_rs::asynchronous::retry_idempotent;
+/// use resilient_rs::config::RetryConfig;
+///
+/// async fn create_payment(idempotency_key: String) -> Result<String, String> {
+///     // Send the key along with the request, e.g. as an `Idempotency-Key` header.
+///     Ok(format!("payment created with key {}", idempotency_key))
+/// }
+///
+/// let result = task::block_on(retry_idempotent(
+///     |key| create_payment(key.to_string()),
+///     &RetryConfig::default(),
+/// ));
+/// assert!(result.is_ok());
+/// ```
+pub async fn retry_idempotent<F, Fut, T, E>(
+    mut operation: F,
+    retry_config: &RetryConfig<E>,
+) -> Result<T, E>
+where
+    F: FnMut(IdempotencyKey) -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let key = IdempotencyKey::generate();
+    retry(|| operation(key.clone()), retry_config).await
 }
 
-#[deprecated(
-    since = "0.4.7",
-    note = "use `retry` with `ExponentialBackoff` this will be removed in upcoming versions"
-)]
-pub async fn retry_with_exponential_backoff<F, Fut, T, E>(
+/// Retries an asynchronous operation consuming an owned input, such as a request body, cloning
+/// the input for every attempt.
+///
+/// The operation receives its input by value, so it does not have to clone it itself. The input
+/// is cloned for every attempt except the last one the configuration allows, which receives the
+/// original.
+///
+/// # Arguments
+/// * `input` - The input handed to every attempt.
+/// * `operation` - A closure receiving the input and returning a `Future` resolving to a `Result<T, E>`.
+/// * `retry_config` - A reference to `RetryConfig` specifying the maximum attempts and delay between retries.
+///
+/// # Returns
+/// The same as `retry`.
+///
+/// # Example
+/// ```
+/// use async_std::task::block_on;
+/// use resilient_rs::asynchronous::retry_with_input;
+/// use resilient_rs::config::RetryConfig;
+///
+/// async fn upload(body: Vec<u8>) -> Result<usize, String> {
+///     Ok(body.len())
+/// }
+///
+/// let body = b"{\"order\": 42}".to_vec();
+/// let result = block_on(retry_with_input(body, upload, &RetryConfig::default()));
+/// assert_eq!(result, Ok(13));
+/// ```
+pub async fn retry_with_input<I, F, Fut, T, E>(
+    input: I,
     mut operation: F,
     retry_config: &RetryConfig<E>,
 ) -> Result<T, E>
 where
-    F: FnMut() -> Fut,
+    I: Clone,
+    F: FnMut(I) -> Fut,
     Fut: Future<Output = Result<T, E>>,
 {
-    let mut attempts = 0;
-    let mut delay = retry_config.delay;
+    let last_attempt = retry_config.attempt_limit();
+    let mut input = Some(input);
+    let mut attempt = 0;
+    retry(
+        || {
+            attempt += 1;
+            let input = if attempt >= last_attempt {
+                input.take()
+            } else {
+                input.clone()
+            };
+            operation(input.expect("no attempt is made after the last one"))
+        },
+        retry_config,
+    )
+    .await
+}
 
-    loop {
-        match operation().await {
-            Ok(output) => {
-                info!("Operation succeeded after {} attempts", attempts + 1);
-                return Ok(output);
-            }
-            Err(err) if attempts + 1 < retry_config.max_attempts => {
-                let should_retry = retry_config.retry_condition.map_or(true, |f| f(&err));
-                if should_retry {
-                    warn!(
-                        "Operation failed (attempt {}/{}), retrying after {:?}...",
-                        attempts + 1,
-                        retry_config.max_attempts,
-                        delay
-                    );
-                    sleep(delay).await;
-                    delay *= 2;
-                } else {
-                    warn!(
-                        "Operation failed (attempt {}/{}), not retryable, giving up.",
-                        attempts + 1,
-                        retry_config.max_attempts
-                    );
-                    return Err(err);
-                }
-            }
-            Err(err) => {
-                warn!(
-                    "Operation failed after {} attempts, giving up.",
-                    attempts + 1
+/// Retries an asynchronous operation that leaves partial work behind when it fails, undoing that
+/// work after every failed attempt.
+///
+/// An attempt failing halfway, e.g. after writing a temporary file, opening a database
+/// transaction or taking a lock, must not leave its partial work for the next attempt to build
+/// on. `rollback` is awaited with the error of every failed attempt, right after it fails and
+/// before the backoff delay, including the last attempt and errors that are not retried, so the
+/// work is undone whether or not the operation is tried again.
+///
+/// The rollback cannot fail the retry loop: it should handle its own errors, e.g. by logging
+/// them, so that a failed cleanup does not hide the error of the operation.
+///
+/// # Arguments
+/// * `operation` - A closure that returns a `Future` resolving to a `Result<T, E>`.
+/// * `rollback` - A closure receiving the error of a failed attempt and returning a `Future`
+///   that undoes its partial work.
+/// * `retry_config` - A reference to `RetryConfig` specifying the maximum attempts and delay between retries.
+///
+/// # Returns
+/// The same as `retry`.
+///
+/// # Example
+/// ```rust
+/// use std::sync::Mutex;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::time::Duration;
+/// use async_std::task::block_on;
+/// use resilient_rs::asynchronous::retry_transactional;
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::strategies::RetryStrategy;
+///
+/// let staged = Mutex::new(Vec::new());
+/// let attempts = AtomicUsize::new(0);
+/// let config = RetryConfig::new(3, Duration::from_millis(1), RetryStrategy::Linear);
+///
+/// let result = block_on(retry_transactional(
+///     || async {
+///         let mut staged = staged.lock().unwrap();
+///         if !staged.is_empty() {
+///             return Err("export.part already exists");
+///         }
+///         staged.push("export.part");
+///         if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+///             Err("upload interrupted")
+///         } else {
+///             Ok(staged.len())
+///         }
+///     },
+///     |_err: &&str| async {
+///         // Delete the partial file so the next attempt starts from scratch.
+///         staged.lock().unwrap().clear();
+///     },
+///     &config,
+/// ));
+/// assert_eq!(result, Ok(1));
+/// ```
+pub async fn retry_transactional<F, Fut, R, RFut, T, E>(
+    mut operation: F,
+    rollback: R,
+    retry_config: &RetryConfig<E>,
+) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    R: FnMut(&E) -> RFut,
+    RFut: Future<Output = ()>,
+{
+    let rollback = std::sync::Mutex::new(rollback);
+    let rollback = &rollback;
+    retry(
+        || {
+            let attempt = operation();
+            async move {
+                let err = match attempt.await {
+                    Ok(output) => return Ok(output),
+                    Err(err) => err,
+                };
+                log_with!(
+                    retry_config.log,
+                    Level::Debug,
+                    "Attempt failed; rolling back its partial work"
+                );
+                let undo = (rollback
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner))(
+                    &err
                 );
-                return Err(err);
+                undo.await;
+                Err(err)
             }
-        }
-
-        attempts += 1;
-    }
+        },
+        retry_config,
+    )
+    .await
 }
 
-/// Executes an asynchronous operation with a timeout and an optional fallback.
+/// Retries a given asynchronous operation, running a recovery hook before each retry.
 ///
-/// This function runs the provided `operation` future with a specified timeout duration.
-/// If the operation completes within the timeout, its result is returned. If it times out,
-/// a fallback function (if provided) is executed synchronously to produce a result.
+/// This behaves exactly like `retry`, but after the backoff delay has elapsed and before the next
+/// attempt starts, the `before_retry` hook is awaited with a reference to the error of the last
+/// attempt. Use it for effectful recovery actions such as refreshing an OAuth token, re-resolving
+/// a hostname, or reopening a connection. The hook is not called after the final attempt or for
+/// errors that are not retried.
 ///
-/// # Type Parameters
+/// # Arguments
+/// * `operation` - A closure that returns a `Future` resolving to a `Result<T, E>`.
+/// * `before_retry` - A closure receiving the last error and returning a `Future` that performs the recovery action.
+/// * `retry_config` - A reference to `RetryConfig` specifying the maximum attempts and delay between retries.
 ///
-/// * `T` - The type of the successful result returned by the operation or fallback.
+/// # Returns
+/// * `Ok(T)` if the operation succeeds within the allowed attempts.
+/// * `Err(E)` if the operation fails after all retry attempts, or with an error classified as
+///   `Permanent` or `Fatal`.
 ///
-/// # Arguments
+/// # Example
+/// ```rust
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::time::Duration;
+/// use async_std::task::block_on;
+/// use resilient_rs::asynchronous::retry_with_hook;
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::strategies::RetryStrategy;
 ///
-/// * `operation` - An asynchronous operation that returns a `Result<T, Box<dyn Error>>`.
-///                 This is typically an async block or function that performs the primary task.
-/// * `exec_config` - A reference to an `ExecConfig<T>` containing the timeout duration and
-///                   an optional fallback function.
+/// let token_refreshes = AtomicUsize::new(0);
+/// let attempts = AtomicUsize::new(0);
+/// let config = RetryConfig::new(3, Duration::from_millis(10), RetryStrategy::Linear);
 ///
-/// # Returns
+/// let result = block_on(retry_with_hook(
+///     || async {
+///         if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+///             Err("token expired")
+///         } else {
+///             Ok("authorized")
+///         }
+///     },
+///     |_err: &&str| async {
+///         token_refreshes.fetch_add(1, Ordering::SeqCst);
+///     },
+///     &config,
+/// ));
 ///
-/// * `Ok(T)` - If the operation completes successfully within the timeout, or if the
-///             fallback succeeds after a timeout.
-/// * `Err(Box<dyn Error>)` - If the operation times out and no fallback is provided,
-///                           or if the fallback itself fails.
+/// assert_eq!(result, Ok("authorized"));
+/// assert_eq!(token_refreshes.load(Ordering::SeqCst), 1);
+/// ```
+pub async fn retry_with_hook<F, Fut, H, HFut, T, E>(
+    operation: F,
+    before_retry: H,
+    retry_config: &RetryConfig<E>,
+) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    H: FnMut(&E) -> HFut,
+    HFut: Future<Output = ()>,
+{
+    retry_loop(operation, before_retry, || retry_config, None, None)
+        .await
+        .map_err(ShutdownError::into_failed)
+}
+
+/// Retries an asynchronous operation like `retry`, and returns the timeline of its attempts
+/// along with the result.
 ///
-/// # Examples
+/// The timeline records the start, duration and outcome of every attempt, and the delay chosen
+/// before the next one, whether the call succeeds or fails, to explain a slow call after the
+/// fact. Recording costs an allocation per attempt, and the classification of each failure.
 ///
-/// ```rust
-/// use std::time::Duration;
-/// use async_std::task::{sleep, block_on};
-/// use resilient_rs::asynchronous::execute_with_fallback;
-/// use resilient_rs::config::ExecConfig;
+/// # Arguments
+/// * `operation` - A closure that returns a `Future` resolving to a `Result<T, E>`.
+/// * `retry_config` - The retry configuration.
 ///
-/// fn main() {
-/// let config = ExecConfig {
-///         timeout_duration: Duration::from_millis(50),
-///         fallback: Some(|| Ok("fallback result".to_string())),
-///     };
+/// # Returns
+/// The result `retry` would return, and the `Timeline` of the call.
 ///
-///     let operation = async {
-///         sleep(Duration::from_millis(100)).await;
-///         Ok("success".to_string())
-///     };
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use async_std::task::block_on;
+/// use resilient_rs::asynchronous::retry_with_timeline;
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::strategies::RetryStrategy;
 ///
-///     let result = block_on(async { execute_with_fallback(operation, &config).await } );
-///     assert_eq!(result.unwrap(), "fallback result");
+/// let config = RetryConfig::new(2, Duration::from_millis(10), RetryStrategy::Linear);
+/// let (result, timeline) =
+///     block_on(retry_with_timeline(|| async { Err::<(), _>("timed out") }, &config));
+/// assert!(result.is_err());
+/// if timeline.total() > Duration::from_secs(5) {
+///     eprintln!("slow call: {}", timeline);
 /// }
+/// assert_eq!(timeline.attempts().len(), 2);
 /// ```
-pub async fn execute_with_fallback<T>(
-    operation: impl Future<Output = Result<T, Box<dyn Error>>>,
-    exec_config: &ExecConfig<T>,
-) -> Result<T, Box<dyn Error>> {
-    match timeout(exec_config.timeout_duration, operation).await {
-        Ok(result) => {
-            info!("Operation completed before timeout; returning result.");
-            result
-        }
-        Err(e) => {
-            if let Some(fallback) = exec_config.fallback {
-                warn!("Operation timed out; executing fallback.");
-                fallback()
-            } else {
-                error!("Operation timed out; no fallback provided, returning error.");
-                Err(Box::new(e))
-            }
+pub async fn retry_with_timeline<F, Fut, T, E>(
+    operation: F,
+    retry_config: &RetryConfig<E>,
+) -> (Result<T, E>, Timeline)
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let mut timeline = Timeline::start();
+    let result = retry_loop(
+        operation,
+        |_: &E| async {},
+        || retry_config,
+        None,
+        Some(&mut timeline),
+    )
+    .await
+    .map_err(ShutdownError::into_failed);
+    timeline.finish();
+    (result, timeline)
+}
+
+/// Spawns a task retrying an asynchronous operation according to `retry_config`, for
+/// fire-and-forget jobs such as delivering a webhook.
+///
+/// The task runs on the async-std runtime, independently of the caller, with a clone of the
+/// configuration. It retries like `retry_until_shutdown`, and records the timeline of its attempts
+/// like `retry_with_timeline`. The returned `RetryTask` tells whether the task finished, cancels
+/// it, and awaits its result along with the timeline. Dropping the `RetryTask` detaches the task,
+/// which keeps retrying in the background.
+///
+/// # Arguments
+/// * `factory` - A closure returning the `Future` of one attempt.
+/// * `retry_config` - The retry configuration, cloned into the task.
+///
+/// # Returns
+/// The `RetryTask` of the spawned task.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use async_std::task::block_on;
+/// use resilient_rs::asynchronous::spawn_with_retry;
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::strategies::RetryStrategy;
+///
+/// let config = RetryConfig::new(3, Duration::from_millis(10), RetryStrategy::Linear);
+/// let delivery = spawn_with_retry(
+///     || async {
+///         // e.g. POST the event to the subscriber's webhook URL.
+///         Ok::<_, String>(202)
+///     },
+///     &config,
+/// );
+///
+/// let (status, timeline) = block_on(delivery.join());
+/// assert_eq!(status.unwrap(), 202);
+/// assert_eq!(timeline.attempts().len(), 1);
+/// ```
+pub fn spawn_with_retry<F, Fut, T, E>(factory: F, retry_config: &RetryConfig<E>) -> RetryTask<T, E>
+where
+    F: FnMut() -> Fut + Send + 'static,
+    Fut: Future<Output = Result<T, E>> + Send + 'static,
+    T: Send + 'static,
+    E: Send + 'static,
+{
+    let retry_config = retry_config.clone();
+    let shutdown = ShutdownHandle::new();
+    let finished = Arc::new(AtomicBool::new(false));
+    let handle = task::spawn({
+        let shutdown = shutdown.clone();
+        let finished = finished.clone();
+        async move {
+            let mut timeline = Timeline::start();
+            let result = retry_loop(
+                factory,
+                |_: &E| async {},
+                || &retry_config,
+                Some(&shutdown),
+                Some(&mut timeline),
+            )
+            .await;
+            timeline.finish();
+            finished.store(true, Ordering::Release);
+            (result, timeline)
         }
+    });
+    RetryTask {
+        handle,
+        shutdown,
+        finished,
+    }
+}
+
+/// The handle of a task spawned by `spawn_with_retry`.
+pub struct RetryTask<T, E> {
+    handle: JoinHandle<(Result<T, ShutdownError<E>>, Timeline)>,
+    shutdown: ShutdownHandle,
+    finished: Arc<AtomicBool>,
+}
+
+impl<T, E> RetryTask<T, E> {
+    /// Returns `true` once the task succeeded, gave up or was cancelled.
+    pub fn is_finished(&self) -> bool {
+        self.finished.load(Ordering::Acquire)
+    }
+
+    /// Asks the task to stop retrying.
+    ///
+    /// As with `retry_until_shutdown`, an attempt in flight is allowed to finish; if it fails, or
+    /// if the task is waiting out a backoff, the task ends with `ShutdownError::Cancelled`.
+    pub fn cancel(&self) {
+        self.shutdown.shutdown();
+    }
+
+    /// Waits for the task to end.
+    ///
+    /// # Returns
+    /// The result of the task, as `retry_until_shutdown` would return it, and the `Timeline` of
+    /// its attempts.
+    pub async fn join(self) -> (Result<T, ShutdownError<E>>, Timeline) {
+        self.handle.await
+    }
+}
+
+impl<T, E> fmt::Debug for RetryTask<T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryTask")
+            .field("finished", &self.is_finished())
+            .field("cancelled", &self.shutdown.is_shutdown())
+            .finish()
     }
 }
 
-/// Represents the possible states of a circuit breaker.
+/// Retries a given asynchronous operation using a hot-reloadable configuration.
+///
+/// This behaves exactly like `retry`, but the configuration is re-read from the
+/// `SharedRetryConfig` at the start of every attempt, so updates made while the operation is
+/// being retried (for example raising `max_attempts` during an incident) take effect immediately.
+///
+/// # Arguments
+/// * `operation` - A closure that returns a `Future` resolving to a `Result<T, E>`.
+/// * `retry_config` - The shared configuration handle to read before each attempt.
 ///
-/// A circuit breaker can be in one of three states, which determine how it handles operations:
-/// - `Close`: Operations are allowed to proceed normally.
-/// - `Open`: Operations are blocked due to repeated failures, preventing further attempts until a cooldown period elapses.
-/// - `HalfOpen`: A trial state after the cooldown, where operations are tentatively allowed to test if the system has recovered.
+/// # Returns
+/// * `Ok(T)` if the operation succeeds within the allowed attempts.
+/// * `Err(E)` if the operation fails after all retry attempts, or with an error classified as
+///   `Permanent` or `Fatal`.
+///
+/// # Example
+/// ```rust
+/// use std::time::Duration;
+/// use async_std::task::block_on;
+/// use resilient_rs::asynchronous::retry_shared;
+/// use resilient_rs::config::{RetryConfig, SharedRetryConfig};
+/// use resilient_rs::strategies::RetryStrategy;
 ///
-/// This enum is used internally by the `CircuitBreaker` struct to manage its state machine.
-#[derive(Debug, PartialEq)]
-enum CircuitBreakerState {
-    Close,
-    Open,
-    HalfOpen,
+/// let shared = SharedRetryConfig::new(RetryConfig::new(2, Duration::from_millis(1), RetryStrategy::Linear));
+/// let result: Result<i32, &str> = block_on(retry_shared(|| async { Ok(1) }, &shared));
+/// assert_eq!(result, Ok(1));
+/// ```
+pub async fn retry_shared<F, Fut, T, E>(
+    operation: F,
+    retry_config: &SharedRetryConfig<E>,
+) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    retry_loop(
+        operation,
+        |_: &E| async {},
+        || retry_config.load(),
+        None,
+        None,
+    )
+    .await
+    .map_err(ShutdownError::into_failed)
 }
 
-/// A circuit breaker for managing fault tolerance in systems.
+/// Retries a given asynchronous operation like `retry`, but stops scheduling new attempts once
+/// `shutdown` is requested.
 ///
-/// The `CircuitBreaker` struct implements the circuit breaker pattern to prevent cascading failures
-/// by monitoring the success and failure of operations. It uses a provided `CircuitBreakerConfig`
-/// to define thresholds and cooldown behavior, transitioning between states (`Closed`, `Open`, `HalfOpen`)
-/// based on operation outcomes.
+/// An attempt in flight when the shutdown is requested is allowed to finish; if it fails, or if
+/// the loop is waiting out a backoff, the loop returns `ShutdownError::Cancelled` at once instead
+/// of retrying, so in-flight work can drain quickly when the process is stopping.
+///
+/// # Arguments
+/// * `operation` - A closure that returns a `Future` resolving to a `Result<T, E>`.
+/// * `retry_config` - A reference to `RetryConfig` specifying the maximum attempts and delay between retries.
+/// * `shutdown` - The handle signaling the shutdown.
 ///
-/// # Fields
-/// * `config` - Configuration defining thresholds and cooldown period
-/// * `state` - Current state of the circuit breaker (`Closed`, `Open`, or `HalfOpen`)
-/// * `failure_count` - Number of consecutive failures since the last state change
-/// * `success_count` - Number of consecutive successes in the `HalfOpen` state
-/// * `last_failure_time` - Timestamp of the most recent failure (if any), used to enforce cooldown period
+/// # Returns
+/// * `Ok(T)` if the operation succeeds within the allowed attempts.
+/// * `Err(ShutdownError::Cancelled)` if the shutdown was requested before the operation
+///   succeeded, with the number of attempts made and the last error.
+/// * `Err(ShutdownError::Failed(E))` if the operation fails after all retry attempts, or with an
+///   error classified as `Permanent` or `Fatal`.
+///
+/// # Example
+/// ```rust
+/// use async_std::task::block_on;
+/// use resilient_rs::asynchronous::retry_until_shutdown;
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::shutdown::ShutdownHandle;
+///
+/// let shutdown = ShutdownHandle::new();
+/// shutdown.shutdown();
+/// let result: Result<(), _> = block_on(retry_until_shutdown(
+///     || async { Err("unreachable") },
+///     &RetryConfig::default(),
+///     &shutdown,
+/// ));
+/// assert!(result.unwrap_err().is_cancelled());
 /// ```
-pub struct CircuitBreaker {
-    config: CircuitBreakerConfig,
-    state: CircuitBreakerState,
-    failure_count: usize,
-    success_count: usize,
-    last_failure_time: Option<Instant>,
+pub async fn retry_until_shutdown<F, Fut, T, E>(
+    operation: F,
+    retry_config: &RetryConfig<E>,
+    shutdown: &ShutdownHandle,
+) -> Result<T, ShutdownError<E>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    retry_loop(
+        operation,
+        |_: &E| async {},
+        || retry_config,
+        Some(shutdown),
+        None,
+    )
+    .await
 }
 
-impl CircuitBreaker {
-    /// Creates a new `CircuitBreaker` instance with the given configuration.
-    ///
-    /// Initializes the circuit breaker in the `Close` state, ready to handle operations.
-    ///
-    /// # Parameters
-    /// - `config`: A reference to a `CircuitBreakerConfig` defining the failure threshold,
-    ///   success threshold, and cooldown period.
-    ///
-    /// # Returns
-    /// A new `CircuitBreaker` instance configured with the provided `config`.
-    ///
-    /// # Examples
-    /// ```rust
-    /// use std::time::Duration;
-    /// use resilient_rs::asynchronous::CircuitBreaker;
-    /// use resilient_rs::config::CircuitBreakerConfig;
-    ///
-    /// let config = CircuitBreakerConfig::new(2, 3, Duration::from_secs(5));
-    /// let cb = CircuitBreaker::new(config);
-    /// ```
-    pub fn new(config: CircuitBreakerConfig) -> Self {
+/// Retries a given asynchronous operation like `retry`, running `cleanup` if the returned future
+/// is dropped while an attempt is in flight.
+///
+/// Dropping a retry future, e.g. because it lost a `select!` or an outer timeout expired, drops
+/// the attempt in flight with it. The attempt stops being polled, but the work it started may go
+/// on elsewhere: a query keeps running on the database, a job keeps running on a remote worker.
+/// `cleanup` is the place to abort it, such as sending `pg_cancel_backend` or `DELETE /jobs/{id}`.
+/// Since a future cannot be awaited from a destructor, the cleanup future is spawned on the
+/// async-std runtime and runs to completion in the background.
+///
+/// The cleanup runs at most once, and only if the future is dropped during an attempt; dropping
+/// it while it waits out a backoff delay, or after it completed, does not run it. Every retry
+/// function reports an attempt dropped mid-call with a `ResilienceEvent::AttemptCancelled` event,
+/// and circuit breakers record such a call neither as a success nor as a failure.
+///
+/// # Arguments
+/// * `operation` - A closure that returns a `Future` resolving to a `Result<T, E>`.
+/// * `cleanup` - A closure returning the `Future` aborting the in-flight work.
+/// * `retry_config` - A reference to `RetryConfig` specifying the maximum attempts and delay between retries.
+///
+/// # Returns
+/// The same as `retry`.
+///
+/// # Example
+/// ```rust
+/// use std::time::Duration;
+/// use async_std::future::{pending, timeout};
+/// use async_std::task::block_on;
+/// use resilient_rs::asynchronous::retry_with_cleanup;
+/// use resilient_rs::config::RetryConfig;
+///
+/// let (cancelled, cancellation) = async_std::channel::bounded(1);
+/// let retry_config = RetryConfig::default();
+/// let report = retry_with_cleanup(
+///     || pending::<Result<String, String>>(),
+///     move || async move {
+///         // e.g. ask the warehouse to cancel the running query.
+///         let _ = cancelled.send("query cancelled").await;
+///     },
+///     &retry_config,
+/// );
+///
+/// // The caller gives up, dropping the retry future mid-attempt.
+/// assert!(block_on(timeout(Duration::from_millis(10), report)).is_err());
+/// assert_eq!(block_on(cancellation.recv()), Ok("query cancelled"));
+/// ```
+pub async fn retry_with_cleanup<F, Fut, C, CFut, T, E>(
+    mut operation: F,
+    cleanup: C,
+    retry_config: &RetryConfig<E>,
+) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    C: FnOnce() -> CFut,
+    CFut: Future<Output = ()> + Send + 'static,
+{
+    let cleanup = std::sync::Mutex::new(Some(move || {
+        task::spawn(cleanup());
+    }));
+    let cleanup = &cleanup;
+    retry(
+        || {
+            let attempt = operation();
+            async move {
+                let armed = CleanupOnDrop {
+                    cleanup: Some(cleanup),
+                };
+                let result = attempt.await;
+                armed.disarm();
+                result
+            }
+        },
+        retry_config,
+    )
+    .await
+}
+
+async fn retry_loop<F, Fut, H, HFut, T, E, C>(
+    mut operation: F,
+    mut before_retry: H,
+    load: impl Fn() -> C,
+    shutdown: Option<&ShutdownHandle>,
+    mut timeline: Option<&mut Timeline>,
+) -> Result<T, ShutdownError<E>>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    H: FnMut(&E) -> HFut,
+    HFut: Future<Output = ()>,
+    C: Deref<Target = RetryConfig<E>>,
+{
+    let mut attempts = 0;
+    let mut delay = load().delay;
+    let mut last_error = None;
+
+    loop {
+        let retry_config = load();
+        if shutdown.is_some_and(ShutdownHandle::is_shutdown) {
+            log_with!(
+                retry_config.log,
+                Level::Info,
+                { attempt = attempts };
+                "Shutdown requested; not retrying after {} attempts",
+                attempts
+            );
+            return Err(ShutdownError::Cancelled {
+                attempts,
+                last_error,
+            });
+        }
+        events::emit(ResilienceEvent::AttemptStarted {
+            attempt: attempts + 1,
+        });
+        let start = time::now();
+        let in_flight = InFlightAttempt::new(retry_config.log, attempts + 1);
+        let result = if retry_config.catch_panics {
+            catch_attempt_panic(&mut operation).await
+        } else {
+            Ok(operation().await)
+        };
+        in_flight.finish();
+        retry_config.record(|stats| stats.record_attempt(time::elapsed(start)));
+        if let Some(timeline) = timeline.as_deref_mut() {
+            let outcome = match &result {
+                Ok(Ok(_)) => AttemptOutcome::Succeeded,
+                Ok(Err(err)) => AttemptOutcome::Failed(retry_config.classify(err)),
+                Err(_) => AttemptOutcome::Panicked,
+            };
+            timeline.record_attempt(start, outcome);
+        }
+        match result {
+            Ok(Ok(output)) => {
+                log_with!(
+                    retry_config.log,
+                    Level::Info,
+                    { attempt = attempts + 1 };
+                    "Operation succeeded after {} attempts",
+                    attempts + 1
+                );
+                retry_config.record(|stats| stats.record_success(attempts > 0));
+                return Ok(output);
+            }
+            Ok(Err(err)) => {
+                let Some(wait) = schedule_retry(&retry_config, &err, attempts + 1, delay) else {
+                    return Err(ShutdownError::Failed(err));
+                };
+                if let Some(timeline) = timeline.as_deref_mut() {
+                    timeline.record_delay(wait);
+                }
+                if back_off(shutdown, retry_config.timer_wheel.as_ref(), wait).await {
+                    delay = retry_config.next_delay(delay, attempts + 1);
+                    before_retry(&err).await;
+                }
+                last_error = Some(err);
+            }
+            Err(payload) => {
+                log_sampled!(
+                    retry_config.log,
+                    attempts + 1,
+                    retry_config.log.level,
+                    { attempt = attempts + 1 };
+                    "Operation panicked (attempt {}), handling it as a transient failure.",
+                    attempts + 1
+                );
+                let Some(wait) =
+                    schedule_class_retry(&retry_config, ErrorClass::Transient, attempts + 1, delay)
+                else {
+                    panic::resume_unwind(payload);
+                };
+                if let Some(timeline) = timeline.as_deref_mut() {
+                    timeline.record_delay(wait);
+                }
+                if back_off(shutdown, retry_config.timer_wheel.as_ref(), wait).await {
+                    delay = retry_config.next_delay(delay, attempts + 1);
+                }
+            }
+        }
+
+        attempts += 1;
+    }
+}
+
+/// Waits for the backoff delay of a retry loop.
+///
+/// Delays shorter than a millisecond, including zero, skip the timer: the loop only yields to the
+/// executor, so that a tight retry loop cannot starve the other tasks of its thread.
+///
+/// # Returns
+/// `true` if the whole delay elapsed, or `false` if it was cut short by `shutdown`.
+pub(crate) async fn back_off(
+    shutdown: Option<&ShutdownHandle>,
+    wheel: Option<&TimerWheel>,
+    wait: Duration,
+) -> bool {
+    if wait < time::MIN_SLEEP {
+        time::yield_now().await;
+        return !shutdown.is_some_and(ShutdownHandle::is_shutdown);
+    }
+    match shutdown {
+        Some(shutdown) => shutdown.sleep(wheel, wait).await,
+        None => {
+            time::sleep_on(wheel, wait).await;
+            true
+        }
+    }
+}
+
+/// Runs one attempt of `operation`, catching a panic raised while creating or polling its future.
+async fn catch_attempt_panic<F, Fut>(operation: &mut F) -> std::thread::Result<Fut::Output>
+where
+    F: FnMut() -> Fut,
+    Fut: Future,
+{
+    let mut attempt = pin!(panic::catch_unwind(AssertUnwindSafe(operation))?);
+    poll_fn(
+        |cx| match panic::catch_unwind(AssertUnwindSafe(|| attempt.as_mut().poll(cx))) {
+            Ok(poll) => poll.map(Ok),
+            Err(payload) => Poll::Ready(Err(payload)),
+        },
+    )
+    .await
+}
+
+/// An attempt of a retry loop, reported as cancelled if the loop's future is dropped before the
+/// attempt completes.
+struct InFlightAttempt {
+    log: LogConfig,
+    attempt: usize,
+    started: Instant,
+    finished: bool,
+}
+
+impl InFlightAttempt {
+    fn new(log: LogConfig, attempt: usize) -> Self {
+        InFlightAttempt {
+            log,
+            attempt,
+            started: time::now(),
+            finished: false,
+        }
+    }
+
+    fn finish(mut self) {
+        self.finished = true;
+    }
+}
+
+impl Drop for InFlightAttempt {
+    fn drop(&mut self) {
+        if !self.finished {
+            let elapsed = time::elapsed(self.started);
+            log_with!(
+                self.log,
+                self.log.level,
+                "Attempt {} dropped after {:?} before completing; no further attempts will run",
+                self.attempt,
+                elapsed
+            );
+            metrics::increment(&metrics::CANCELLATIONS);
+            events::emit(ResilienceEvent::AttemptCancelled { elapsed });
+        }
+    }
+}
+
+/// Runs the cleanup of `retry_with_cleanup` if dropped while armed.
+struct CleanupOnDrop<'a, C: FnOnce()> {
+    cleanup: Option<&'a std::sync::Mutex<Option<C>>>,
+}
+
+impl<C: FnOnce()> CleanupOnDrop<'_, C> {
+    fn disarm(mut self) {
+        self.cleanup = None;
+    }
+}
+
+impl<C: FnOnce()> Drop for CleanupOnDrop<'_, C> {
+    fn drop(&mut self) {
+        let cleanup = self.cleanup.and_then(|cleanup| {
+            cleanup
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .take()
+        });
+        if let Some(cleanup) = cleanup {
+            cleanup();
+        }
+    }
+}
+
+/// Decides whether a failed attempt is retried, and after which delay.
+///
+/// Classifies the error, applies the attempt budget of its class, and logs and records the
+/// decision.
+///
+/// # Returns
+/// * `Some(wait)` if the operation should be retried after `wait`.
+/// * `None` if the retry loop should give up and return the error.
+fn schedule_retry<E>(
+    retry_config: &RetryConfig<E>,
+    err: &E,
+    attempt: usize,
+    delay: Duration,
+) -> Option<Duration> {
+    schedule_class_retry(retry_config, retry_config.classify(err), attempt, delay)
+}
+
+/// Decides whether an attempt that failed with an error of the given class is retried, like
+/// `schedule_retry`, for failures that are not an `E`, such as a timed out attempt.
+pub(crate) fn schedule_class_retry<E>(
+    retry_config: &RetryConfig<E>,
+    class: ErrorClass,
+    attempt: usize,
+    delay: Duration,
+) -> Option<Duration> {
+    let (max_attempts, wait) = retry_config.budget_for(&class, attempt, delay);
+    if attempt >= max_attempts {
+        log_with!(
+            retry_config.log,
+            Level::Warn,
+            { attempt = attempt, max_attempts = max_attempts, error_class:? = class };
+            "Operation failed after {} attempts, giving up.",
+            attempt
+        );
+        retry_config.record(Stats::record_give_up);
+        metrics::increment(&metrics::GIVE_UPS);
+        events::emit(ResilienceEvent::GaveUp { attempts: attempt });
+        return None;
+    }
+    let wait = match class {
+        ErrorClass::Transient => {
+            log_sampled!(
+                retry_config.log,
+                attempt,
+                retry_config.log.level,
+                {
+                    attempt = attempt,
+                    max_attempts = max_attempts,
+                    delay_ms = wait.as_millis() as u64,
+                    error_class:? = class,
+                };
+                "Operation failed (attempt {}/{}), retrying after {:?} with {:?} strategy...",
+                attempt,
+                max_attempts,
+                wait,
+                retry_config.strategy
+            );
+            wait
+        }
+        ErrorClass::Throttled { retry_after } => {
+            let wait = retry_after.map_or(wait, |hint| retry_config.capped(hint));
+            log_sampled!(
+                retry_config.log,
+                attempt,
+                retry_config.log.level,
+                {
+                    attempt = attempt,
+                    max_attempts = max_attempts,
+                    delay_ms = wait.as_millis() as u64,
+                    error_class:? = class,
+                };
+                "Operation throttled (attempt {}/{}), retrying after {:?}...",
+                attempt,
+                max_attempts,
+                wait
+            );
+            wait
+        }
+        ErrorClass::Permanent | ErrorClass::Fatal => {
+            log_with!(
+                retry_config.log,
+                Level::Warn,
+                { attempt = attempt, max_attempts = max_attempts, error_class:? = class };
+                "Operation failed (attempt {}/{}), not retryable, giving up.",
+                attempt,
+                max_attempts
+            );
+            retry_config.record(Stats::record_give_up);
+            metrics::increment(&metrics::GIVE_UPS);
+            events::emit(ResilienceEvent::GaveUp { attempts: attempt });
+            return None;
+        }
+    };
+    retry_config.record(|stats| stats.record_backoff(wait));
+    metrics::increment(&metrics::RETRIES);
+    events::emit(ResilienceEvent::RetryScheduled {
+        attempt,
+        delay: wait,
+    });
+    Some(wait)
+}
+
+/// Logs and records that a retry loop gave up because its attempt opened the circuit.
+pub(crate) fn give_up_on_open<E>(retry_config: &RetryConfig<E>, attempts: usize) {
+    log_with!(
+        retry_config.log,
+        Level::Warn,
+        "Circuit Breaker opened after {} attempts, giving up.",
+        attempts
+    );
+    retry_config.record(Stats::record_give_up);
+    metrics::increment(&metrics::GIVE_UPS);
+    events::emit(ResilienceEvent::GaveUp { attempts });
+}
+
+/// A retry loop whose attempts are supervised by an optional circuit breaker, shared by the
+/// integrations that run the attempts themselves: the `reqwest` middleware, the `hyper` client,
+/// Kafka deliveries and the `redis` wrapper.
+///
+/// Every attempt is started with `start`, which asks the breaker for a permit, and ended with
+/// `end` or `end_reported`, which report its outcome to the breaker and decide whether to retry.
+/// The caller runs the attempt, classifies its result, and waits with `back_off` in between.
+pub(crate) struct GuardedRetry<'a, R, E> {
+    retry_config: &'a RetryConfig<R>,
+    breaker: Option<&'a Mutex<BreakerCore<E>>>,
+    attempts: usize,
+    delay: Duration,
+}
+
+/// An attempt started by `GuardedRetry::start`.
+pub(crate) struct GuardedAttempt {
+    start: Instant,
+    permit: Option<CallPermit>,
+}
+
+/// What a `GuardedRetry` decided once an attempt ended.
+pub(crate) enum Step<T> {
+    /// The loop is over and the result of the attempt is returned.
+    Done(T),
+    /// The attempt failed and is retried after the delay; its result was dropped.
+    Retry(Duration),
+}
+
+impl<'a, R, E> GuardedRetry<'a, R, E> {
+    pub(crate) fn new(
+        retry_config: &'a RetryConfig<R>,
+        breaker: Option<&'a Mutex<BreakerCore<E>>>,
+    ) -> Self {
+        GuardedRetry {
+            retry_config,
+            breaker,
+            attempts: 0,
+            delay: retry_config.delay,
+        }
+    }
+
+    /// Starts an attempt, asking the breaker for a permit.
+    ///
+    /// # Returns
+    /// The attempt, or `Err(retry_after)` if the breaker rejected it: `retry_after` is the
+    /// remaining cooldown if the circuit is open, or `None` if the breaker's concurrency cap was
+    /// reached.
+    pub(crate) fn start(&self) -> Result<GuardedAttempt, Option<Duration>> {
+        events::emit(ResilienceEvent::AttemptStarted {
+            attempt: self.attempts + 1,
+        });
+        let start = time::now();
+        let permit = match self.breaker {
+            Some(core) => match breaker::acquire(core) {
+                Ok(permit) => Some(permit),
+                Err(CircuitBreakerError::Open { retry_after }) => return Err(Some(retry_after)),
+                Err(_) => return Err(None),
+            },
+            None => None,
+        };
+        Ok(GuardedAttempt { start, permit })
+    }
+
+    /// Ends `attempt`, reporting `outcome` to the breaker, and decides what becomes of `result`.
+    ///
+    /// `class` is the class of a failed attempt, or `None` if it succeeded; `outcome` is what the
+    /// breaker counts, which may differ, e.g. for a `5xx` response returned as is.
+    pub(crate) fn end<T>(
+        &mut self,
+        attempt: GuardedAttempt,
+        result: T,
+        outcome: Result<(), E>,
+        class: Option<ErrorClass>,
+    ) -> Step<T>
+    where
+        E: fmt::Display,
+    {
+        let (generation, elapsed) = self.finish(attempt);
+        if let (Some(core), Some(generation)) = (self.breaker, generation) {
+            let _ = breaker::complete(core, generation, outcome, elapsed);
+        }
+        self.decide(result, class)
+    }
+
+    /// Ends `attempt` like `end`, reporting `result` itself to the breaker, whose error type it
+    /// shares; errors are classified by `classify`.
+    #[cfg(feature = "redis")]
+    pub(crate) fn end_reported<T>(
+        &mut self,
+        attempt: GuardedAttempt,
+        result: Result<T, E>,
+        classify: impl FnOnce(&E) -> ErrorClass,
+    ) -> Step<Result<T, E>>
+    where
+        E: fmt::Display,
+    {
+        let (generation, elapsed) = self.finish(attempt);
+        let result = match (self.breaker, generation) {
+            (Some(core), Some(generation)) => breaker::complete(core, generation, result, elapsed)
+                .map_err(|err| match err {
+                    CircuitBreakerError::Inner(err) => err,
+                    _ => unreachable!("the breaker only rejects calls before they run"),
+                }),
+            _ => result,
+        };
+        let class = result.as_ref().err().map(classify);
+        self.decide(result, class)
+    }
+
+    fn finish(&self, attempt: GuardedAttempt) -> (Option<Generation>, Duration) {
+        let generation = attempt.permit.map(CallPermit::finish);
+        let elapsed = time::elapsed(attempt.start);
+        self.retry_config
+            .record(|stats| stats.record_attempt(elapsed));
+        (generation, elapsed)
+    }
+
+    /// Returns `result` if the attempt succeeded, its class is not retried, or it opened the
+    /// circuit, and schedules the next attempt otherwise.
+    fn decide<T>(&mut self, result: T, class: Option<ErrorClass>) -> Step<T> {
+        let attempt = self.attempts + 1;
+        let Some(class) = class else {
+            let retried = self.attempts > 0;
+            self.retry_config
+                .record(|stats| stats.record_success(retried));
+            return Step::Done(result);
+        };
+        if let Some(core) = self.breaker
+            && breaker::lock(core).state() == CircuitBreakerState::Open
+        {
+            give_up_on_open(self.retry_config, attempt);
+            return Step::Done(result);
+        }
+        let Some(wait) = schedule_class_retry(self.retry_config, class, attempt, self.delay) else {
+            return Step::Done(result);
+        };
+        self.delay = self.retry_config.next_delay(self.delay, attempt);
+        self.attempts = attempt;
+        Step::Retry(wait)
+    }
+}
+
+/// The future returned by a function decorated with `CircuitBreaker::decorate` or
+/// `CircuitBreaker::decorate_with_retry`.
+pub type DecoratedFuture<T, E> =
+    Pin<Box<dyn Future<Output = Result<T, CircuitBreakerError<E>>> + Send + 'static>>;
+
+/// Retries an asynchronous operation with exponential backoff.
+///
+/// This is a shim kept for compatibility: it runs the loop of `retry` with the delay doubling
+/// after every retry (`delay`, `2 * delay`, `4 * delay`, ...) as it always did, so both honor the
+/// same retry conditions, classifiers, per-class policies and statistics.
+#[deprecated(
+    since = "0.4.7",
+    note = "use `retry` with `ExponentialBackoff` this will be removed in upcoming versions"
+)]
+pub async fn retry_with_exponential_backoff<F, Fut, T, E>(
+    operation: F,
+    retry_config: &RetryConfig<E>,
+) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    retry(operation, &retry_config.doubling()).await
+}
+
+/// Executes an asynchronous operation with a timeout and an optional fallback.
+///
+/// This function runs the provided `operation` future with a specified timeout duration.
+/// If the operation completes within the timeout, its result is returned. If it times out,
+/// a fallback function (if provided) is executed synchronously to produce a result, receiving a
+/// `FallbackCause::Timeout`.
+///
+/// # Type Parameters
+///
+/// * `T` - The type of the successful result returned by the operation or fallback.
+///
+/// # Arguments
+///
+/// * `operation` - An asynchronous operation that returns a `Result<T, Box<dyn Error + Send + Sync>>`.
+///   This is typically an async block or function that performs the primary task.
+/// * `exec_config` - A reference to an `ExecConfig<T>` containing the timeout duration and
+///   an optional fallback function.
+///
+/// # Returns
+///
+/// * `Ok(T)` - If the operation completes successfully within the timeout, or if the
+///   fallback succeeds after a timeout.
+/// * `Err(Box<dyn Error + Send + Sync>)` - If the operation times out and no fallback is provided,
+///   or if the fallback itself fails.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::time::Duration;
+/// use async_std::task::{sleep, block_on};
+/// use resilient_rs::asynchronous::execute_with_fallback;
+/// use resilient_rs::config::ExecConfig;
+///
+/// fn main() {
+///     let config = ExecConfig::new(Duration::from_millis(50)).with_fallback(|cause| {
+///         assert!(cause.is_timeout());
+///         Ok("fallback result".to_string())
+///     });
+///
+///     let operation = async {
+///         sleep(Duration::from_millis(100)).await;
+///         Ok("success".to_string())
+///     };
+///
+///     let result = block_on(async { execute_with_fallback(operation, &config).await } );
+///     assert_eq!(result.unwrap(), "fallback result");
+/// }
+/// ```
+pub async fn execute_with_fallback<T>(
+    operation: impl Future<Output = Result<T, BoxError>>,
+    exec_config: &ExecConfig<T>,
+) -> Result<T, BoxError> {
+    execute_with_fallback_outcome(operation, exec_config)
+        .await
+        .map(ExecOutcome::into_inner)
+}
+
+/// Executes an asynchronous operation with a timeout and an optional fallback, telling whether
+/// the result was produced by the fallback.
+///
+/// It behaves like `execute_with_fallback`, but wraps the result in an `ExecOutcome` so that
+/// callers can tell degraded responses apart.
+///
+/// # Arguments
+/// * `operation` - An asynchronous operation that returns a `Result<T, Box<dyn Error + Send + Sync>>`.
+/// * `exec_config` - A reference to an `ExecConfig<T>` containing the timeout duration and
+///   an optional fallback function.
+///
+/// # Returns
+/// * `Ok(ExecOutcome::Primary(T))` - If the operation completes successfully within the timeout.
+/// * `Ok(ExecOutcome::Fallback(T))` - If the operation times out and the fallback succeeds.
+/// * `Err(Box<dyn Error + Send + Sync>)` - The same errors as `execute_with_fallback`.
+///
+/// # Example
+/// ```rust
+/// use std::time::Duration;
+/// use async_std::task::{block_on, sleep};
+/// use resilient_rs::asynchronous::execute_with_fallback_outcome;
+/// use resilient_rs::config::{ExecConfig, ExecOutcome};
+///
+/// let config = ExecConfig::new(Duration::from_millis(50))
+///     .with_fallback(|_| Ok("stale price".to_string()));
+///
+/// let operation = async {
+///     sleep(Duration::from_millis(100)).await;
+///     Ok("fresh price".to_string())
+/// };
+/// let outcome = block_on(execute_with_fallback_outcome(operation, &config)).unwrap();
+/// assert_eq!(outcome, ExecOutcome::Fallback("stale price".to_string()));
+/// ```
+pub async fn execute_with_fallback_outcome<T>(
+    operation: impl Future<Output = Result<T, BoxError>>,
+    exec_config: &ExecConfig<T>,
+) -> Result<ExecOutcome<T>, BoxError> {
+    match timeout(exec_config.timeout_duration, operation).await {
+        Ok(result) => {
+            log_with!(
+                exec_config.log,
+                Level::Info,
+                "Operation completed before timeout; returning result."
+            );
+            result.map(ExecOutcome::Primary)
+        }
+        Err(_) => {
+            metrics::increment(&metrics::TIMEOUTS);
+            events::emit(ResilienceEvent::TimeoutHit {
+                timeout: exec_config.timeout_duration,
+            });
+            if let Some(fallback) = &exec_config.fallback {
+                log_with!(
+                    exec_config.log,
+                    exec_config.log.level,
+                    "Operation timed out; executing fallback."
+                );
+                metrics::increment(&metrics::FALLBACKS);
+                events::emit(ResilienceEvent::FallbackUsed);
+                fallback(FallbackCause::Timeout {
+                    timeout: exec_config.timeout_duration,
+                })
+                .map(ExecOutcome::Fallback)
+            } else {
+                log_with!(
+                    exec_config.log,
+                    Level::Error,
+                    "Operation timed out; no fallback provided, returning error."
+                );
+                Err(Box::new(error::Error::Timeout {
+                    timeout: exec_config.timeout_duration,
+                }))
+            }
+        }
+    }
+}
+
+/// Executes an asynchronous operation with a timeout, falling back through an ordered chain of
+/// fallbacks when it fails or times out.
+///
+/// The fallbacks of `chain` are tried in order until one succeeds, so degraded responses can
+/// come from progressively less accurate sources, e.g. a local cache, then a secondary region,
+/// then a static default.
+///
+/// # Arguments
+/// * `operation` - An asynchronous operation that returns a `Result<T, Box<dyn Error + Send + Sync>>`.
+/// * `chain` - A reference to a `FallbackChain<T>` holding the timeout duration and the fallbacks.
+///
+/// # Returns
+/// * `Ok((T, ServedBy))` - The result of the operation or of the first successful fallback,
+///   along with the level that produced it.
+/// * `Err(Box<dyn Error + Send + Sync>)` - The error of the last fallback if every fallback failed, or the
+///   error (or timeout) of the operation if the chain is empty.
+///
+/// # Examples
+/// ```rust
+/// use std::time::Duration;
+/// use async_std::task::block_on;
+/// use resilient_rs::asynchronous::execute_with_fallback_chain;
+/// use resilient_rs::config::{FallbackChain, ServedBy};
+///
+/// let chain = FallbackChain::new(Duration::from_millis(50))
+///     .with_fallback("cache", |_| Err("cache miss".into()))
+///     .with_fallback("secondary-region", |cause| Ok(format!("eu-west price ({})", cause)))
+///     .with_fallback("default", |_| Ok("list price".to_string()));
+///
+/// let operation = async { Err("primary region down".into()) };
+/// let (price, served_by) = block_on(execute_with_fallback_chain(operation, &chain)).unwrap();
+/// assert_eq!(price, "eu-west price (primary region down)");
+/// assert_eq!(served_by, ServedBy::Fallback { level: 2, name: "secondary-region" });
+/// ```
+pub async fn execute_with_fallback_chain<T>(
+    operation: impl Future<Output = Result<T, BoxError>>,
+    chain: &FallbackChain<T>,
+) -> Result<(T, ServedBy), BoxError> {
+    let mut timed_out = false;
+    let primary_error = match timeout(chain.timeout_duration, operation).await {
+        Ok(Ok(output)) => return Ok((output, ServedBy::Primary)),
+        Ok(Err(err)) => err,
+        Err(_) => {
+            metrics::increment(&metrics::TIMEOUTS);
+            events::emit(ResilienceEvent::TimeoutHit {
+                timeout: chain.timeout_duration,
+            });
+            timed_out = true;
+            Box::new(error::Error::Timeout {
+                timeout: chain.timeout_duration,
+            }) as BoxError
+        }
+    };
+    let cause = if timed_out {
+        FallbackCause::Timeout {
+            timeout: chain.timeout_duration,
+        }
+    } else {
+        FallbackCause::Error(primary_error.as_ref())
+    };
+    let fallbacks = chain.fallbacks();
+    let mut last_error = None;
+    for (index, (name, fallback)) in fallbacks.iter().enumerate() {
+        log_with!(
+            chain.log,
+            chain.log.level,
+            "Operation failed ({}); executing fallback `{}` ({}/{}).",
+            cause,
+            name,
+            index + 1,
+            fallbacks.len()
+        );
+        metrics::increment(&metrics::FALLBACKS);
+        events::emit(ResilienceEvent::FallbackUsed);
+        match fallback(cause) {
+            Ok(output) => {
+                let level = index + 1;
+                return Ok((output, ServedBy::Fallback { level, name }));
+            }
+            Err(err) => last_error = Some(err),
+        }
+    }
+    log_with!(
+        chain.log,
+        Level::Error,
+        "Operation and every fallback failed; returning the last error."
+    );
+    Err(last_error.unwrap_or(primary_error))
+}
+
+/// Retries an asynchronous operation, bounding each attempt by the timeout of an `ExecConfig`.
+///
+/// Every attempt runs the future returned by `op_factory` under `exec_config.timeout_duration`.
+/// An attempt that times out fails with `resilient_rs::Error::Timeout`, which goes through the
+/// `retry_config` like any other error: it is retried by default, and a `retry_condition` or
+/// `error_classifier` can tell it apart by downcasting the error to `resilient_rs::Error`. When
+/// the last attempt timed out, the fallback of `exec_config` (if any) runs with a
+/// `FallbackCause::Timeout`, as in `execute_with_fallback`.
+///
+/// # Arguments
+/// * `op_factory` - A closure returning a new `Future` resolving to a `Result<T, Box<dyn Error + Send + Sync>>` for each attempt.
+/// * `exec_config` - A reference to an `ExecConfig<T>` holding the per-attempt timeout and an
+///   optional fallback.
+/// * `retry_config` - A reference to a `RetryConfig` deciding which failures are retried and how
+///   long to wait between attempts.
+///
+/// # Returns
+/// * `Ok(T)` - The result of the first attempt completing successfully within the timeout, or of
+///   the fallback after the last attempt timed out.
+/// * `Err(Box<dyn Error + Send + Sync>)` - A `resilient_rs::Error::RetriesExhausted` wrapping the
+///   error of the last attempt if it was retryable, the error of the last attempt as is if it
+///   was not, or the error of the fallback.
+///
+/// # Example
+/// ```rust
+/// use std::time::Duration;
+/// use async_std::task::{block_on, sleep};
+/// use resilient_rs::asynchronous::execute_with_retry_and_timeout;
+/// use resilient_rs::config::{ExecConfig, RetryConfig};
+/// use resilient_rs::strategies::RetryStrategy;
+///
+/// let exec_config = ExecConfig::new(Duration::from_millis(50));
+/// let retry_config = RetryConfig::new(3, Duration::from_millis(10), RetryStrategy::Linear);
+/// let mut attempts = 0;
+/// let result = block_on(execute_with_retry_and_timeout(
+///     || {
+///         attempts += 1;
+///         let hangs = attempts == 1;
+///         async move {
+///             if hangs {
+///                 sleep(Duration::from_secs(60)).await;
+///             }
+///             Ok("response".to_string())
+///         }
+///     },
+///     &exec_config,
+///     &retry_config,
+/// ));
+/// assert_eq!(result.unwrap(), "response");
+/// assert_eq!(attempts, 2);
+/// ```
+pub async fn execute_with_retry_and_timeout<F, Fut, T>(
+    mut op_factory: F,
+    exec_config: &ExecConfig<T>,
+    retry_config: &RetryConfig<BoxError>,
+) -> Result<T, BoxError>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, BoxError>>,
+{
+    let timeout_duration = exec_config.timeout_duration;
+    let mut attempts = 0;
+    let result = retry(
+        || {
+            attempts += 1;
+            let attempt = op_factory();
+            async move {
+                match timeout(timeout_duration, attempt).await {
+                    Ok(result) => result,
+                    Err(_) => {
+                        metrics::increment(&metrics::TIMEOUTS);
+                        events::emit(ResilienceEvent::TimeoutHit {
+                            timeout: timeout_duration,
+                        });
+                        log_with!(
+                            exec_config.log,
+                            exec_config.log.level,
+                            "Attempt timed out after {:?}.",
+                            timeout_duration
+                        );
+                        Err(Box::new(error::Error::Timeout {
+                            timeout: timeout_duration,
+                        }) as BoxError)
+                    }
+                }
+            }
+        },
+        retry_config,
+    )
+    .await
+    .map_err(|err| {
+        if retry_config.classify(&err).is_retryable() {
+            Box::new(error::Error::RetriesExhausted {
+                source: err,
+                attempts,
+            })
+        } else {
+            err
+        }
+    });
+    match (result, &exec_config.fallback) {
+        (Err(err), Some(fallback)) if error::Error::caused_by_timeout(&err) => {
+            log_with!(
+                exec_config.log,
+                exec_config.log.level,
+                "Last attempt timed out; executing fallback."
+            );
+            metrics::increment(&metrics::FALLBACKS);
+            events::emit(ResilienceEvent::FallbackUsed);
+            fallback(FallbackCause::Timeout {
+                timeout: timeout_duration,
+            })
+        }
+        (result, _) => result,
+    }
+}
+
+/// A circuit breaker for managing fault tolerance in systems.
+///
+/// The `CircuitBreaker` struct implements the circuit breaker pattern to prevent cascading failures
+/// by monitoring the success and failure of operations. It uses a provided `CircuitBreakerConfig`
+/// to define thresholds and cooldown behavior, transitioning between states (`Closed`, `Open`, `HalfOpen`)
+/// based on operation outcomes.
+///
+/// The state machine is shared with `synchronous::CircuitBreaker`, so both breakers make the same
+/// transitions for the same outcomes. Like the synchronous breaker, it takes `&self` and only locks
+/// its state while a call is admitted and its outcome recorded, so it can be shared between tasks
+/// through an `Arc` without serializing their calls.
+///
+/// # Type Parameters
+/// * `E` - The error type of the supervised operations, `Box<dyn Error + Send + Sync>` by default. Use
+///   `CircuitBreaker::with_config` to supervise operations failing with another error type.
+pub struct CircuitBreaker<E = BoxError> {
+    core: Mutex<BreakerCore<E>>,
+}
+
+impl CircuitBreaker {
+    /// Creates a new `CircuitBreaker` instance with the given configuration.
+    ///
+    /// Initializes the circuit breaker in the `Close` state, ready to handle operations.
+    ///
+    /// # Parameters
+    /// - `config`: A `CircuitBreakerConfig` defining the failure threshold, success threshold, and
+    ///   cooldown period. The configuration is `Copy` and owned by the breaker, so the breaker has
+    ///   no lifetime parameter and can be stored in structs, application state or registries.
+    ///
+    /// # Returns
+    /// A new `CircuitBreaker` instance configured with the provided `config`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::time::Duration;
+    /// use resilient_rs::asynchronous::CircuitBreaker;
+    /// use resilient_rs::config::CircuitBreakerConfig;
+    ///
+    /// let config = CircuitBreakerConfig::new(2, 3, Duration::from_secs(5));
+    /// let cb = CircuitBreaker::new(config);
+    /// ```
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        CircuitBreaker::with_config(config)
+    }
+}
+
+impl<E> CircuitBreaker<E> {
+    /// Creates a new `CircuitBreaker` supervising operations that fail with errors of type `E`.
+    ///
+    /// `CircuitBreaker::new` is the shorthand for operations returning `Box<dyn Error + Send + Sync>`.
+    ///
+    /// # Parameters
+    /// - `config`: A `CircuitBreakerConfig` defining the failure threshold, success threshold, and
+    ///   cooldown period.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::io;
+    /// use resilient_rs::asynchronous::CircuitBreaker;
+    /// use resilient_rs::config::CircuitBreakerConfig;
+    ///
+    /// let cb = CircuitBreaker::<io::Error>::with_config(CircuitBreakerConfig::default());
+    /// ```
+    pub fn with_config(config: CircuitBreakerConfig) -> Self {
         CircuitBreaker {
+            core: Mutex::new(BreakerCore::new(config)),
+        }
+    }
+
+    /// Sets an error classifier and returns the modified `CircuitBreaker`.
+    ///
+    /// Failures classified as `Fatal` open the circuit immediately, regardless of the
+    /// configured `failure_threshold`. All other classes count towards the threshold as usual.
+    ///
+    /// # Parameters
+    /// - `classifier`: Any `ErrorClassifier<E>`, including closures of the form `Fn(&E) -> ErrorClass`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::error::Error;
+    /// use resilient_rs::asynchronous::CircuitBreaker;
+    /// use resilient_rs::classifier::ErrorClass;
+    /// use resilient_rs::config::CircuitBreakerConfig;
+    ///
+    /// let cb = CircuitBreaker::new(CircuitBreakerConfig::default()).with_classifier(|e: &Box<dyn Error + Send + Sync>| {
+    ///     if e.to_string().contains("certificate") {
+    ///         ErrorClass::Fatal
+    ///     } else {
+    ///         ErrorClass::Transient
+    ///     }
+    /// });
+    /// ```
+    pub fn with_classifier(
+        mut self,
+        classifier: impl ErrorClassifier<E> + Send + Sync + 'static,
+    ) -> Self {
+        self.core_mut().classifier = Some(Arc::new(classifier));
+        self
+    }
+
+    /// Sets a predicate deciding which errors count as failures and returns the modified `CircuitBreaker`.
+    ///
+    /// Errors for which the predicate returns `false` (e.g. a 404 or a validation error, which are
+    /// the caller's fault rather than the dependency's) are returned to the caller but neither
+    /// move the failure counter nor affect the sliding window or a `HalfOpen` trial. By default,
+    /// every error counts.
+    ///
+    /// # Parameters
+    /// - `predicate`: A function returning `true` for errors that indicate an unhealthy dependency.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use resilient_rs::asynchronous::CircuitBreaker;
+    /// use resilient_rs::config::CircuitBreakerConfig;
+    ///
+    /// let cb = CircuitBreaker::<u16>::with_config(CircuitBreakerConfig::default())
+    ///     .with_record_failure_if(|status: &u16| *status >= 500);
+    /// ```
+    pub fn with_record_failure_if(
+        mut self,
+        predicate: impl Fn(&E) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.core_mut().record_failure_if = Some(Arc::new(predicate));
+        self
+    }
+
+    /// Sets a statistics handle and returns the modified `CircuitBreaker`.
+    ///
+    /// Every executed call is recorded as an attempt (with its latency), rejected calls are
+    /// recorded as rejections, and every transition to `Open` is recorded as a breaker opening.
+    ///
+    /// # Parameters
+    /// - `stats`: The shared `Stats` handle to update.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use resilient_rs::asynchronous::CircuitBreaker;
+    /// use resilient_rs::config::CircuitBreakerConfig;
+    /// use resilient_rs::stats::Stats;
+    ///
+    /// let stats = Stats::new();
+    /// let cb = CircuitBreaker::new(CircuitBreakerConfig::default()).with_stats(stats.clone());
+    /// ```
+    pub fn with_stats(mut self, stats: Stats) -> Self {
+        self.core_mut().stats = Some(stats);
+        self
+    }
+
+    /// Makes the circuit breaker follow a hot-reloadable configuration.
+    ///
+    /// The configuration is read from the handle at the start of every call to `run`, so updates
+    /// to thresholds and the cooldown period apply to the next call without recreating the breaker.
+    /// The breaker's current state and counters are kept across updates.
+    ///
+    /// # Parameters
+    /// - `config`: The shared configuration handle to follow.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::time::Duration;
+    /// use resilient_rs::asynchronous::CircuitBreaker;
+    /// use resilient_rs::config::{CircuitBreakerConfig, SharedCircuitBreakerConfig};
+    ///
+    /// let shared = SharedCircuitBreakerConfig::new(CircuitBreakerConfig::default());
+    /// let cb = CircuitBreaker::new(CircuitBreakerConfig::default()).with_shared_config(shared.clone());
+    /// shared.update_with(|config| config.with_cooldown_period(Duration::from_secs(30)));
+    /// ```
+    pub fn with_shared_config(mut self, config: SharedCircuitBreakerConfig) -> Self {
+        self.core_mut().set_shared_config(config);
+        self
+    }
+
+    /// Shares the breaker's state with other breakers through a `StateStore`.
+    ///
+    /// Breakers using the same store and `name`, typically one per replica of a service, count
+    /// their consecutive failures together and open as soon as any of them opens, instead of each
+    /// replica tripping on its own. `HalfOpen` trials and sliding `FailureWindow`s stay local.
+    /// If the store fails, the error is logged and the breaker keeps using its local state.
+    ///
+    /// # Parameters
+    /// - `name`: The name the shared state is stored under, e.g. the protected dependency.
+    /// - `store`: The store to share the state through, such as `RedisStateStore` with the
+    ///   `redis` feature.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::sync::Arc;
+    /// use resilient_rs::asynchronous::CircuitBreaker;
+    /// use resilient_rs::config::CircuitBreakerConfig;
+    /// use resilient_rs::store::InMemoryStateStore;
+    ///
+    /// let store = Arc::new(InMemoryStateStore::new());
+    /// let cb = CircuitBreaker::new(CircuitBreakerConfig::default()).with_state_store("payments-api", store);
+    /// ```
+    pub fn with_state_store(mut self, name: impl Into<String>, store: Arc<dyn StateStore>) -> Self {
+        self.core_mut().set_state_store(name.into(), store);
+        self
+    }
+
+    /// Executes an operation under circuit breaker supervision.
+    ///
+    /// This method runs the provided async operation and updates the circuit breaker state based
+    /// on the outcome. If the breaker is `Open` and the cooldown period hasn’t elapsed, it blocks
+    /// the operation. In `HalfOpen`, it tests recovery, and in `Close`, it monitors for failures.
+    ///
+    /// # Parameters
+    /// - `operation`: An async closure or function that returns a `Future` yielding a `Result`.
+    ///   It is called at most once, so it may move captured state such as a request body into
+    ///   the future. Use `call_with_retry` to call it again on failure.
+    ///
+    /// # Returns
+    /// - `Ok(T)` if the operation succeeds, where `T` is the operation’s return type.
+    /// - `Err(CircuitBreakerError::Inner(E))` if the operation fails.
+    /// - `Err(CircuitBreakerError::Open { retry_after })` if the breaker is `Open` and the call was
+    ///   not executed.
+    /// - `Err(CircuitBreakerError::Saturated { .. })` if `max_concurrent_calls` calls were already
+    ///   running and the call was not executed.
+    /// ```
+    pub async fn run<F, Fut, T>(&self, operation: F) -> Result<T, CircuitBreakerError<E>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: fmt::Display,
+    {
+        let permit = self.acquire()?;
+        let start = time::now();
+        let result = operation().await;
+        let generation = permit.finish();
+        self.complete(generation, result, time::elapsed(start))
+    }
+
+    /// Executes an operation under circuit breaker supervision, falling back when the circuit is open.
+    ///
+    /// This behaves like `run`, but when the breaker rejects the call, the `fallback` is awaited
+    /// instead of returning `CircuitBreakerError::Open` or `CircuitBreakerError::Saturated`, e.g. to
+    /// serve cached data. Failures of the
+    /// operation itself are returned as is and are not handled by the fallback.
+    ///
+    /// # Parameters
+    /// - `operation`: An async closure or function that returns a `Future` yielding a `Result`.
+    /// - `fallback`: An async closure producing the degraded result while the circuit is open.
+    ///
+    /// # Returns
+    /// - `Ok(T)` if the operation succeeds, or if the call is rejected and the fallback succeeds.
+    /// - `Err(E)` if the operation fails, or if the call is rejected and the fallback fails.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::time::Duration;
+    /// use async_std::task::block_on;
+    /// use resilient_rs::asynchronous::CircuitBreaker;
+    /// use resilient_rs::config::CircuitBreakerConfig;
+    ///
+    /// let cb = CircuitBreaker::<&str>::with_config(CircuitBreakerConfig::new(1, 1, Duration::from_secs(30)));
+    /// let _ = block_on(cb.run(|| async { Err::<&str, _>("timeout") }));
+    ///
+    /// let price = block_on(cb.run_with_fallback(
+    ///     || async { Ok("live price") },
+    ///     || async { Ok("cached price") },
+    /// ));
+    /// assert_eq!(price, Ok("cached price"));
+    /// ```
+    pub async fn run_with_fallback<F, Fut, FB, FBFut, T>(
+        &self,
+        operation: F,
+        fallback: FB,
+    ) -> Result<T, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        FB: FnOnce() -> FBFut,
+        FBFut: Future<Output = Result<T, E>>,
+        E: fmt::Display,
+    {
+        match self.run(operation).await {
+            Ok(output) => Ok(output),
+            Err(CircuitBreakerError::Inner(err)) => Err(err),
+            Err(_) => {
+                breaker::update(&self.core, BreakerCore::fallback_used);
+                fallback().await
+            }
+        }
+    }
+
+    /// Retries an operation under circuit breaker supervision.
+    ///
+    /// Every attempt goes through the breaker, so each failure counts towards opening the
+    /// circuit, and the backoff of `retry_config` is applied between attempts. The retry loop
+    /// stops as soon as the circuit opens: an attempt that trips the breaker is not retried, and a
+    /// rejected attempt ends the loop instead of waiting for the circuit to close.
+    ///
+    /// # Parameters
+    /// - `operation`: An async closure or function that returns a `Future` yielding a `Result`.
+    /// - `retry_config`: The retry configuration, including its classifier and per-class policies.
+    ///
+    /// # Returns
+    /// - `Ok(T)` if an attempt succeeds.
+    /// - `Err(CircuitBreakerError::Inner(E))` with the error of the last attempt if the retries are
+    ///   exhausted, the error is not retryable, or the attempt opened the circuit.
+    /// - `Err(CircuitBreakerError::Open { retry_after })` if the circuit was open before an attempt.
+    /// - `Err(CircuitBreakerError::Saturated { .. })` if the concurrency cap was reached before an
+    ///   attempt.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::time::Duration;
+    /// use async_std::task::block_on;
+    /// use resilient_rs::asynchronous::{CircuitBreaker, CircuitBreakerError};
+    /// use resilient_rs::config::{CircuitBreakerConfig, RetryConfig};
+    /// use resilient_rs::strategies::RetryStrategy;
+    ///
+    /// let cb = CircuitBreaker::<&str>::with_config(CircuitBreakerConfig::new(1, 2, Duration::from_secs(30)));
+    /// let retry_config = RetryConfig::new(5, Duration::from_millis(1), RetryStrategy::Linear);
+    ///
+    /// // The second failure opens the circuit, so the remaining attempts are skipped.
+    /// let result = block_on(cb.call_with_retry(|| async { Err::<(), _>("down") }, &retry_config));
+    /// assert_eq!(result, Err(CircuitBreakerError::Inner("down")));
+    /// assert!(block_on(cb.call_with_retry(|| async { Ok::<_, &str>(()) }, &retry_config)).unwrap_err().is_open());
+    /// ```
+    pub async fn call_with_retry<F, Fut, T>(
+        &self,
+        mut operation: F,
+        retry_config: &RetryConfig<E>,
+    ) -> Result<T, CircuitBreakerError<E>>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: fmt::Display,
+    {
+        let mut attempts = 0;
+        let mut delay = retry_config.delay;
+
+        loop {
+            events::emit(ResilienceEvent::AttemptStarted {
+                attempt: attempts + 1,
+            });
+            let start = time::now();
+            let result = self.run(&mut operation).await;
+            retry_config.record(|stats| stats.record_attempt(time::elapsed(start)));
+            let err = match result {
+                Ok(output) => {
+                    retry_config.record(|stats| stats.record_success(attempts > 0));
+                    return Ok(output);
+                }
+                Err(CircuitBreakerError::Inner(err)) => err,
+                Err(rejected) => return Err(rejected),
+            };
+            if self.state() == CircuitBreakerState::Open {
+                give_up_on_open(retry_config, attempts + 1);
+                return Err(CircuitBreakerError::Inner(err));
+            }
+            let Some(wait) = schedule_retry(retry_config, &err, attempts + 1, delay) else {
+                return Err(CircuitBreakerError::Inner(err));
+            };
+            time::sleep_on(retry_config.timer_wheel.as_ref(), wait).await;
+            delay = retry_config.next_delay(delay, attempts + 1);
+            attempts += 1;
+        }
+    }
+
+    /// Forces the breaker open, rejecting every call until `reset` is called.
+    ///
+    /// This is meant for maintenance windows: callers get `CircuitBreakerError::Open` (or their
+    /// fallback) without the dependency being called, regardless of the cooldown period.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use async_std::task::block_on;
+    /// use resilient_rs::asynchronous::{CircuitBreaker, CircuitBreakerState};
+    /// use resilient_rs::config::CircuitBreakerConfig;
+    ///
+    /// let cb = CircuitBreaker::<&str>::with_config(CircuitBreakerConfig::default());
+    /// cb.force_open();
+    /// assert_eq!(cb.state(), CircuitBreakerState::ForcedOpen);
+    /// assert!(block_on(cb.run(|| async { Ok::<_, &str>(()) })).unwrap_err().is_open());
+    ///
+    /// cb.reset();
+    /// assert!(block_on(cb.run(|| async { Ok::<_, &str>(()) })).is_ok());
+    /// ```
+    pub fn force_open(&self) {
+        breaker::update(&self.core, BreakerCore::force_open);
+    }
+
+    /// Disables the breaker, letting every call through without recording its outcome until
+    /// `reset` is called.
+    pub fn disable(&self) {
+        breaker::update(&self.core, BreakerCore::disable);
+    }
+
+    /// Closes the breaker and forgets every recorded failure, leaving `ForcedOpen` or `Disabled`
+    /// as well as a tripped `Open` state.
+    pub fn reset(&self) {
+        breaker::update(&self.core, BreakerCore::reset);
+    }
+
+    /// Spawns a background task moving the breaker from `Open` to `HalfOpen` as soon as its
+    /// cooldown elapses.
+    ///
+    /// Without the task, the transition happens when the first call arrives after the cooldown, so
+    /// a quiet service reports `Open` long after the dependency could have been probed. The task
+    /// sleeps until the cooldown of an open breaker elapses, and checks the state every
+    /// `check_interval` otherwise. It only holds a weak reference, and ends once every `Arc` to
+    /// the breaker has been dropped.
+    ///
+    /// # Parameters
+    /// - `breaker`: The shared breaker, e.g. one returned by `CircuitBreakerRegistry::get`.
+    /// - `check_interval`: How often the task checks whether a closed breaker has opened.
+    ///
+    /// # Returns
+    /// The handle of the spawned task.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    /// use resilient_rs::asynchronous::CircuitBreaker;
+    /// use resilient_rs::config::CircuitBreakerConfig;
+    ///
+    /// let breaker = Arc::new(CircuitBreaker::new(CircuitBreakerConfig::default()));
+    /// CircuitBreaker::spawn_half_open_timer(&breaker, Duration::from_millis(100));
+    /// ```
+    pub fn spawn_half_open_timer(
+        breaker: &Arc<CircuitBreaker<E>>,
+        check_interval: Duration,
+    ) -> JoinHandle<()>
+    where
+        E: 'static,
+    {
+        let breaker = Arc::downgrade(breaker);
+        task::spawn(async move {
+            while let Some(breaker) = breaker.upgrade() {
+                breaker::update(&breaker.core, BreakerCore::half_open_if_elapsed);
+                let wait = breaker.time_until_half_open().unwrap_or(check_interval);
+                drop(breaker);
+                sleep(wait).await;
+            }
+        })
+    }
+
+    /// Wraps `f` into a function whose calls are supervised by the shared `breaker`.
+    ///
+    /// The returned function can be cloned and handed to other components, which then call the
+    /// dependency through the breaker without having access to it. As with `run`, concurrent
+    /// calls are not serialized, and `max_concurrent_calls` applies to them.
+    ///
+    /// # Parameters
+    /// - `breaker`: The shared breaker, e.g. one returned by `CircuitBreakerRegistry::get`.
+    /// - `f`: The async function to protect, called with the argument of each call.
+    ///
+    /// # Returns
+    /// A function returning a `DecoratedFuture` that resolves like `run`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    /// use async_std::task::block_on;
+    /// use resilient_rs::asynchronous::CircuitBreaker;
+    /// use resilient_rs::config::CircuitBreakerConfig;
+    ///
+    /// let config = CircuitBreakerConfig::new(1, 1, Duration::from_secs(30));
+    /// let breaker = Arc::new(CircuitBreaker::<String>::with_config(config));
+    /// let fetch_user = CircuitBreaker::decorate(&breaker, |id: u32| async move {
+    ///     if id == 0 { Err("no such user".to_string()) } else { Ok(format!("user-{}", id)) }
+    /// });
+    ///
+    /// assert_eq!(block_on(fetch_user(7)), Ok("user-7".to_string()));
+    /// let _ = block_on(fetch_user(0));
+    /// assert!(block_on(fetch_user(7)).unwrap_err().is_open());
+    /// ```
+    pub fn decorate<F, Fut, A, T>(
+        breaker: &Arc<CircuitBreaker<E>>,
+        f: F,
+    ) -> impl Fn(A) -> DecoratedFuture<T, E> + Clone + Send + Sync + 'static
+    where
+        F: Fn(A) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T, E>> + Send + 'static,
+        A: Send + 'static,
+        T: Send + 'static,
+        E: fmt::Display + Send + 'static,
+    {
+        let breaker = Arc::clone(breaker);
+        let f = Arc::new(f);
+        move |arg| -> DecoratedFuture<T, E> {
+            let breaker = Arc::clone(&breaker);
+            let f = Arc::clone(&f);
+            Box::pin(async move { breaker.run(|| f(arg)).await })
+        }
+    }
+
+    /// Wraps `f` into a function whose calls are retried under the supervision of the shared
+    /// `breaker`, like `call_with_retry`.
+    ///
+    /// The argument of a call is cloned for every attempt.
+    ///
+    /// # Parameters
+    /// - `breaker`: The shared breaker.
+    /// - `f`: The async function to protect, called with a clone of the argument on each attempt.
+    /// - `retry_config`: The retry configuration applied to every call.
+    ///
+    /// # Returns
+    /// A function returning a `DecoratedFuture` that resolves like `call_with_retry`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    /// use async_std::task::block_on;
+    /// use resilient_rs::asynchronous::{CircuitBreaker, CircuitBreakerError};
+    /// use resilient_rs::config::{CircuitBreakerConfig, RetryConfig};
+    /// use resilient_rs::strategies::RetryStrategy;
+    ///
+    /// let config = CircuitBreakerConfig::new(1, 2, Duration::from_secs(30));
+    /// let breaker = Arc::new(CircuitBreaker::<&str>::with_config(config));
+    /// let retry_config = RetryConfig::new(5, Duration::from_millis(1), RetryStrategy::Linear);
+    /// let send = CircuitBreaker::decorate_with_retry(
+    ///     &breaker,
+    ///     |_body: String| async { Err::<(), _>("down") },
+    ///     retry_config,
+    /// );
+    ///
+    /// // The second failure opens the circuit, so the remaining attempts are skipped.
+    /// assert_eq!(block_on(send("ping".to_string())), Err(CircuitBreakerError::Inner("down")));
+    /// ```
+    pub fn decorate_with_retry<F, Fut, A, T>(
+        breaker: &Arc<CircuitBreaker<E>>,
+        f: F,
+        retry_config: RetryConfig<E>,
+    ) -> impl Fn(A) -> DecoratedFuture<T, E> + Clone + Send + Sync + 'static
+    where
+        F: Fn(A) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<T, E>> + Send + 'static,
+        A: Clone + Send + Sync + 'static,
+        T: Send + 'static,
+        E: fmt::Display + Send + 'static,
+    {
+        let breaker = Arc::clone(breaker);
+        let f = Arc::new(f);
+        let retry_config = Arc::new(retry_config);
+        move |arg| -> DecoratedFuture<T, E> {
+            let breaker = Arc::clone(&breaker);
+            let f = Arc::clone(&f);
+            let retry_config = Arc::clone(&retry_config);
+            Box::pin(async move {
+                breaker
+                    .call_with_retry(|| f(arg.clone()), &retry_config)
+                    .await
+            })
+        }
+    }
+
+    /// Returns a stream of the breaker's state transitions.
+    ///
+    /// Every transition made after the call, including those caused by `force_open`, `disable`
+    /// and `reset`, is delivered as a `TransitionEvent`, so dashboards and supervisors can await
+    /// lifecycle changes instead of registering callbacks. Each call returns an independent
+    /// stream; events are buffered until consumed, and a dropped stream is forgotten on the next
+    /// transition.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::time::Duration;
+    /// use async_std::prelude::*;
+    /// use async_std::task::block_on;
+    /// use resilient_rs::asynchronous::{CircuitBreaker, CircuitBreakerState};
+    /// use resilient_rs::config::CircuitBreakerConfig;
+    ///
+    /// let cb = CircuitBreaker::<&str>::with_config(CircuitBreakerConfig::new(1, 1, Duration::from_secs(30)));
+    /// let mut events = cb.events();
+    /// block_on(async {
+    ///     let _ = cb.run(|| async { Err::<(), _>("down") }).await;
+    ///     let event = events.next().await.unwrap();
+    ///     assert_eq!((event.from, event.to), (CircuitBreakerState::Close, CircuitBreakerState::Open));
+    /// });
+    /// ```
+    pub fn events(&self) -> impl Stream<Item = TransitionEvent> + Send + Unpin + 'static {
+        self.core().subscribe()
+    }
+
+    /// Returns the current state of the breaker.
+    ///
+    /// An `Open` breaker whose cooldown has elapsed reports `Open` until the next call moves it to
+    /// `HalfOpen`; check `time_until_half_open` to tell the two apart.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use resilient_rs::asynchronous::{CircuitBreaker, CircuitBreakerState};
+    /// use resilient_rs::config::CircuitBreakerConfig;
+    ///
+    /// let cb = CircuitBreaker::new(CircuitBreakerConfig::default());
+    /// assert_eq!(cb.state(), CircuitBreakerState::Close);
+    /// ```
+    pub fn state(&self) -> CircuitBreakerState {
+        self.core().state()
+    }
+
+    /// Returns the number of consecutive failures counted towards `failure_threshold`.
+    pub fn failure_count(&self) -> usize {
+        self.core().failure_count()
+    }
+
+    /// Returns the number of successful trial calls counted towards `success_threshold`.
+    pub fn success_count(&self) -> usize {
+        self.core().success_count()
+    }
+
+    /// Returns how long the breaker stays `Open` before letting a trial call through.
+    ///
+    /// # Returns
+    /// - `Some(duration)` while `Open`; `Duration::ZERO` once the cooldown has elapsed.
+    /// - `None` if the breaker is `Close` or `HalfOpen`.
+    pub fn time_until_half_open(&self) -> Option<Duration> {
+        self.core().time_until_half_open()
+    }
+
+    /// Returns the percentage of successful calls among the last `metrics_window_size` calls.
+    ///
+    /// Every executed call counts, including errors ignored by `with_record_failure_if`;
+    /// rejected calls do not. Returns `None` until a call has been made.
+    pub fn success_rate(&self) -> Option<f64> {
+        self.core().metrics().success_rate
+    }
+
+    /// Returns the mean latency of the last `metrics_window_size` calls, or `None` until a call
+    /// has been made.
+    pub fn mean_latency(&self) -> Option<Duration> {
+        self.core().metrics().mean_latency
+    }
+
+    /// Returns the latency below which `percentile` percent of the last `metrics_window_size`
+    /// calls completed, e.g. `99.0` for the p99 latency.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::time::Duration;
+    /// use async_std::task::{block_on, sleep};
+    /// use resilient_rs::asynchronous::CircuitBreaker;
+    /// use resilient_rs::config::CircuitBreakerConfig;
+    ///
+    /// let cb = CircuitBreaker::<&str>::with_config(CircuitBreakerConfig::default());
+    /// block_on(cb.run(|| async {
+    ///     sleep(Duration::from_millis(5)).await;
+    ///     Ok::<_, &str>(())
+    /// }))
+    /// .unwrap();
+    /// assert!(cb.latency_percentile(99.0).unwrap() >= Duration::from_millis(5));
+    /// assert_eq!(cb.success_rate(), Some(100.0));
+    /// ```
+    pub fn latency_percentile(&self, percentile: f64) -> Option<Duration> {
+        self.core().latency_percentile(percentile)
+    }
+
+    /// Returns a snapshot of the breaker's state and counters.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use resilient_rs::asynchronous::{CircuitBreaker, CircuitBreakerState};
+    /// use resilient_rs::config::CircuitBreakerConfig;
+    ///
+    /// let cb = CircuitBreaker::new(CircuitBreakerConfig::default());
+    /// let metrics = cb.metrics();
+    /// assert_eq!(metrics.state, CircuitBreakerState::Close);
+    /// assert_eq!(metrics.failure_rate, None);
+    /// ```
+    pub fn metrics(&self) -> CircuitBreakerMetrics {
+        self.core().metrics()
+    }
+
+    pub(crate) fn core(&self) -> MutexGuard<'_, BreakerCore<E>> {
+        breaker::lock(&self.core)
+    }
+
+    /// Returns the state machine of the breaker, e.g. to supervise a `GuardedRetry`.
+    pub(crate) fn state_machine(&self) -> &Mutex<BreakerCore<E>> {
+        &self.core
+    }
+
+    /// Decides whether a call may run; see `breaker::acquire`.
+    pub(crate) fn acquire(&self) -> Result<CallPermit, CircuitBreakerError<E>> {
+        breaker::acquire(&self.core)
+    }
+
+    /// Reports the outcome of an admitted call; see `breaker::complete`.
+    pub(crate) fn complete<T>(
+        &self,
+        generation: Generation,
+        result: Result<T, E>,
+        elapsed: Duration,
+    ) -> Result<T, CircuitBreakerError<E>>
+    where
+        E: fmt::Display,
+    {
+        breaker::complete(&self.core, generation, result, elapsed)
+    }
+
+    /// Records the outcome of a health probe; see `breaker::record_probe`.
+    pub(crate) fn record_probe(&self, healthy: bool) {
+        breaker::record_probe(&self.core, healthy)
+    }
+
+    fn core_mut(&mut self) -> &mut BreakerCore<E> {
+        self.core.get_mut().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A bulkhead limiting the number of concurrent executions of the operations it guards.
+///
+/// Isolating a dependency behind a bulkhead keeps a slow dependency from tying up every task of
+/// the service: once `max_concurrent_calls` operations are running, further calls wait up to
+/// `max_wait` for a slot and are rejected with `BulkheadError::Full` otherwise. Slots are handed
+/// out in the order calls started waiting, and are released when the operation completes or its
+/// future is dropped.
+///
+/// The bulkhead takes `&self`, so it can be shared between tasks through an `Arc` or a static.
+/// It composes with the other patterns by nesting calls: run `bulkhead.call` inside
+/// `CircuitBreaker::run` or `retry`, whose error type is then `BulkheadError<E>`.
+///
+/// # Examples
+/// ```rust
+/// use std::time::Duration;
+/// use async_std::task::block_on;
+/// use resilient_rs::asynchronous::{Bulkhead, BulkheadError, CircuitBreaker};
+/// use resilient_rs::config::{BulkheadConfig, CircuitBreakerConfig};
+///
+/// let bulkhead = Bulkhead::new(BulkheadConfig::new(10).with_max_wait(Duration::from_millis(50)));
+/// // Rejections by the bulkhead say nothing about the dependency's health.
+/// let cb = CircuitBreaker::with_config(CircuitBreakerConfig::default())
+///     .with_record_failure_if(|err: &BulkheadError<&str>| !err.is_full());
+///
+/// let result = block_on(cb.run(|| bulkhead.call(|| async { Ok::<_, &str>("fetched") })));
+/// assert_eq!(result, Ok("fetched"));
+/// ```
+pub struct Bulkhead {
+    config: BulkheadConfig,
+    slots: Receiver<()>,
+    release: Sender<()>,
+}
+
+/// A slot of a `Bulkhead`, handed back when dropped.
+struct BulkheadSlot {
+    release: Sender<()>,
+}
+
+impl Drop for BulkheadSlot {
+    fn drop(&mut self) {
+        let _ = self.release.try_send(());
+    }
+}
+
+impl Bulkhead {
+    /// Creates a bulkhead with every slot free.
+    ///
+    /// # Parameters
+    /// - `config`: The bulkhead configuration.
+    pub fn new(config: BulkheadConfig) -> Self {
+        let (release, slots) = channel::bounded(config.max_concurrent_calls.max(1));
+        for _ in 0..config.max_concurrent_calls {
+            let _ = release.try_send(());
+        }
+        Bulkhead {
             config,
-            state: CircuitBreakerState::Close,
-            failure_count: 0,
-            success_count: 0,
-            last_failure_time: None,
+            slots,
+            release,
         }
     }
 
-    /// Executes an operation under circuit breaker supervision.
-    ///
-    /// This method runs the provided async operation and updates the circuit breaker state based
-    /// on the outcome. If the breaker is `Open` and the cooldown period hasn’t elapsed, it blocks
-    /// the operation. In `HalfOpen`, it tests recovery, and in `Close`, it monitors for failures.
+    /// Executes an operation once a slot of the bulkhead is free.
     ///
     /// # Parameters
-    /// - `operation`: An async closure or function that returns a `Future` yielding a `Result`.
-    ///   The closure must be `FnMut` to allow multiple calls if needed in the future.
+    /// - `operation`: An async closure or function returning a `Future` yielding a `Result`. It is
+    ///   not called if the call is rejected.
     ///
     /// # Returns
-    /// - `Ok(T)` if the operation succeeds, where `T` is the operation’s return type.
-    /// - `Err(Box<dyn Error>)` if the operation fails or the breaker is `Open`.
+    /// - `Ok(T)` if the operation succeeds.
+    /// - `Err(BulkheadError::Inner(E))` if the operation fails.
+    /// - `Err(BulkheadError::Full)` if no slot freed up within `max_wait`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use async_std::task::block_on;
+    /// use resilient_rs::asynchronous::Bulkhead;
+    /// use resilient_rs::config::BulkheadConfig;
+    ///
+    /// let bulkhead = Bulkhead::new(BulkheadConfig::new(2));
+    /// let result = block_on(bulkhead.call(|| async { Ok::<_, &str>(42) }));
+    /// assert_eq!(result, Ok(42));
+    /// assert_eq!(bulkhead.available(), 2);
     /// ```
-    pub async fn run<F, Fut, T>(&mut self, mut operation: F) -> Result<T, Box<dyn Error>>
+    pub async fn call<F, Fut, T, E>(&self, operation: F) -> Result<T, BulkheadError<E>>
     where
-        F: FnMut() -> Fut,
-        Fut: Future<Output = Result<T, Box<dyn Error>>>,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
     {
-        match self.state {
-            CircuitBreakerState::Open => {
-                if let Some(last_failure_time) = self.last_failure_time {
-                    if last_failure_time.elapsed() >= self.config.cooldown_period {
-                        self.state = CircuitBreakerState::HalfOpen;
-                        self.success_count = 0;
-                        warn!("Circuit Breaker transitioning to Half Open State");
-                    } else {
-                        warn!("Circuit Breaker is open.. Requests are blocked for now");
-                        return Err(Box::from(String::from(
-                            "Circuit Breaker is open. Please try later..!",
-                        )));
-                    }
-                }
-            }
-            _ => {}
-        }
-
-        match operation().await {
-            Ok(result) => {
-                debug!("Request Success response");
-                self.on_success();
-                Ok(result)
-            }
-            Err(err) => {
-                error!("Failed with {}", err);
-                self.on_failure();
-                Err(err)
-            }
-        }
+        let slot = self.acquire().await?;
+        let result = operation().await;
+        drop(slot);
+        result.map_err(BulkheadError::Inner)
     }
 
-    /// Handles a successful operation outcome.
-    ///
-    /// Updates the circuit breaker state based on a successful operation:
-    /// - In `HalfOpen`, increments `success_count` and transitions to `Close` if the success threshold is met.
-    /// - In `Close`, resets `failure_count` to 0.
-    /// - In `Open`, does nothing (this method is typically called only after `call`).
-    fn on_success(&mut self) {
-        match self.state {
-            CircuitBreakerState::HalfOpen => {
-                self.success_count += 1;
-                if self.success_count >= self.config.success_threshold {
-                    self.state = CircuitBreakerState::Close;
-                    self.failure_count = 0;
-                    debug!("Circuit breaker transitioning to closed state");
-                }
-            }
-            _ => {
-                self.failure_count = 0;
-            }
-        }
+    /// Returns the number of free slots.
+    pub fn available(&self) -> usize {
+        self.slots.len()
     }
 
-    /// Handles a failed operation outcome.
-    ///
-    /// Updates the circuit breaker state based on a failed operation:
-    /// - Increments `failure_count`.
-    /// - If `failure_count` exceeds the threshold, transitions to `Open` and records the failure time.
-    fn on_failure(&mut self) {
-        self.failure_count += 1;
-        if self.failure_count >= self.config.failure_threshold {
-            self.state = CircuitBreakerState::Open;
-            self.last_failure_time = Some(Instant::now());
-            error!("Circuit Breaker transitioning to open state");
+    async fn acquire<E>(&self) -> Result<BulkheadSlot, BulkheadError<E>> {
+        let acquired = if self.slots.try_recv().is_ok() {
+            true
+        } else if self.config.max_wait.is_zero() {
+            false
+        } else {
+            matches!(
+                timeout(self.config.max_wait, self.slots.recv()).await,
+                Ok(Ok(()))
+            )
+        };
+        if !acquired {
+            return Err(bulkhead::rejected(
+                &self.config.log,
+                self.config.max_concurrent_calls,
+            ));
         }
+        Ok(BulkheadSlot {
+            release: self.release.clone(),
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::strategies::RetryStrategy;
     use async_std::task::{block_on, sleep};
     use std::error::Error;
     use std::sync::{Arc, Mutex};
@@ -395,7 +2406,7 @@ mod tests {
     // Suite for `retry` function
     mod retry_tests {
         use super::*;
-        use crate::strategies::RetryStrategy::Linear;
+        use RetryStrategy::Linear;
 
         #[test]
         fn test_retry_success_first_try_with_block_on() {
@@ -404,6 +2415,7 @@ mod tests {
                 delay: Duration::from_millis(10),
                 retry_condition: None,
                 strategy: Linear,
+                ..RetryConfig::default()
             };
 
             let attempts = Arc::new(Mutex::new(0));
@@ -429,6 +2441,7 @@ mod tests {
                 delay: Duration::from_millis(10),
                 retry_condition: None,
                 strategy: Linear,
+                ..RetryConfig::default()
             };
 
             let attempts = Arc::new(Mutex::new(0));
@@ -458,6 +2471,7 @@ mod tests {
                 delay: Duration::from_millis(10),
                 retry_condition: None,
                 strategy: Linear,
+                ..RetryConfig::default()
             };
 
             let attempts = Arc::new(Mutex::new(0));
@@ -483,6 +2497,7 @@ mod tests {
                 delay: Duration::from_millis(10),
                 retry_condition: Some(|e: &DummyError| e.0.contains("transient")),
                 strategy: Linear,
+                ..RetryConfig::default()
             };
 
             let attempts = Arc::new(Mutex::new(0));
@@ -508,6 +2523,7 @@ mod tests {
                 delay: Duration::from_millis(10),
                 retry_condition: Some(|e: &DummyError| e.0.contains("transient")),
                 strategy: Linear,
+                ..RetryConfig::default()
             };
 
             let attempts = Arc::new(Mutex::new(0));
@@ -525,11 +2541,163 @@ mod tests {
             assert_eq!(result, Err(DummyError("transient")));
             assert_eq!(*attempts.lock().unwrap(), 3);
         }
+
+        #[test]
+        fn test_retry_with_classifier_fatal_gives_up() {
+            let config = RetryConfig::new(5, Duration::from_millis(10), Linear)
+                .with_error_classifier(|e: &DummyError| {
+                    if e.0 == "fatal" {
+                        ErrorClass::Fatal
+                    } else {
+                        ErrorClass::Transient
+                    }
+                });
+
+            let attempts = Arc::new(Mutex::new(0));
+            let op_attempts = attempts.clone();
+            let operation = move || {
+                let op_attempts = op_attempts.clone();
+                async move {
+                    let mut count = op_attempts.lock().unwrap();
+                    *count += 1;
+                    if *count < 2 {
+                        Err(DummyError("transient"))
+                    } else {
+                        Err(DummyError("fatal"))
+                    }
+                }
+            };
+
+            let result: Result<(), DummyError> = block_on(retry(operation, &config));
+            assert_eq!(result, Err(DummyError("fatal")));
+            assert_eq!(*attempts.lock().unwrap(), 2);
+        }
+
+        #[test]
+        fn test_retry_shared_picks_up_updates_during_a_running_loop() {
+            let shared: SharedRetryConfig<DummyError> =
+                SharedRetryConfig::new(RetryConfig::new(2, Duration::from_millis(100), Linear));
+            let attempts = Arc::new(Mutex::new(0));
+            let op_attempts = attempts.clone();
+
+            let result: Result<(), DummyError> = block_on(async {
+                // The update lands while the loop waits out its first backoff.
+                let updater = shared.clone();
+                let update = async_std::task::spawn(async move {
+                    sleep(Duration::from_millis(20)).await;
+                    updater.update(RetryConfig::new(4, Duration::from_millis(1), Linear));
+                });
+                let result = retry_shared(
+                    move || {
+                        *op_attempts.lock().unwrap() += 1;
+                        async { Err(DummyError("unavailable")) }
+                    },
+                    &shared,
+                )
+                .await;
+                update.await;
+                result
+            });
+
+            assert_eq!(result, Err(DummyError("unavailable")));
+            assert_eq!(*attempts.lock().unwrap(), 4);
+        }
+
+        #[test]
+        fn test_zero_delay_yields_to_other_futures_between_attempts() {
+            let config = RetryConfig::new(50, Duration::ZERO, RetryStrategy::Linear);
+            let ready = std::cell::Cell::new(false);
+            let attempts = std::cell::Cell::new(0);
+            let mut retried = pin!(retry(
+                || {
+                    attempts.set(attempts.get() + 1);
+                    let ready = ready.get();
+                    async move {
+                        if ready {
+                            Ok(())
+                        } else {
+                            Err(DummyError("not ready"))
+                        }
+                    }
+                },
+                &config,
+            ));
+            // The other future only runs when the retry loop returns `Pending`.
+            let mut other = pin!(async { ready.set(true) });
+            let result = block_on(poll_fn(|cx| {
+                if let Poll::Ready(result) = retried.as_mut().poll(cx) {
+                    return Poll::Ready(result);
+                }
+                let _ = other.as_mut().poll(cx);
+                Poll::Pending
+            }));
+            assert_eq!(result, Ok(()));
+            assert_eq!(attempts.get(), 2);
+        }
+    }
+
+    // Suite for `retry_with_hook` function
+    mod retry_with_hook_tests {
+        use super::*;
+        use RetryStrategy::Linear;
+
+        #[test]
+        fn test_hook_runs_between_attempts_with_last_error() {
+            let config = RetryConfig::new(4, Duration::from_millis(10), Linear);
+            let attempts = Arc::new(Mutex::new(0));
+            let seen = Arc::new(Mutex::new(Vec::new()));
+
+            let op_attempts = attempts.clone();
+            let operation = move || {
+                let op_attempts = op_attempts.clone();
+                async move {
+                    let mut count = op_attempts.lock().unwrap();
+                    *count += 1;
+                    match *count {
+                        1 => Err(DummyError("expired token")),
+                        2 => Err(DummyError("stale dns")),
+                        _ => Ok("recovered"),
+                    }
+                }
+            };
+            let hook_seen = seen.clone();
+            let hook = move |err: &DummyError| {
+                let hook_seen = hook_seen.clone();
+                let message = err.0;
+                async move { hook_seen.lock().unwrap().push(message) }
+            };
+
+            let result = block_on(retry_with_hook(operation, hook, &config));
+            assert_eq!(result, Ok("recovered"));
+            assert_eq!(*attempts.lock().unwrap(), 3);
+            assert_eq!(*seen.lock().unwrap(), vec!["expired token", "stale dns"]);
+        }
+
+        #[test]
+        fn test_hook_not_called_after_final_attempt() {
+            let config = RetryConfig::new(2, Duration::from_millis(10), Linear);
+            let hook_calls = Arc::new(Mutex::new(0));
+
+            let hook_counter = hook_calls.clone();
+            let hook = move |_: &DummyError| {
+                let hook_counter = hook_counter.clone();
+                async move { *hook_counter.lock().unwrap() += 1 }
+            };
+
+            let result: Result<(), DummyError> = block_on(retry_with_hook(
+                || async { Err(DummyError("down")) },
+                hook,
+                &config,
+            ));
+            assert_eq!(result, Err(DummyError("down")));
+            assert_eq!(*hook_calls.lock().unwrap(), 1);
+        }
     }
 
     // Suite for `retry_with_exponential_backoff` function
     mod retry_with_exponential_backoff_tests {
         use super::*;
+        use crate::config::{ClassPolicy, PolicyTable};
         use crate::strategies::RetryStrategy::ExponentialBackoff;
 
         #[test]
@@ -539,6 +2707,7 @@ mod tests {
                 delay: Duration::from_millis(10),
                 retry_condition: None,
                 strategy: ExponentialBackoff,
+                ..RetryConfig::default()
             };
 
             let attempts = Arc::new(Mutex::new(0));
@@ -564,6 +2733,7 @@ mod tests {
                 delay: Duration::from_millis(10),
                 retry_condition: None,
                 strategy: ExponentialBackoff,
+                ..RetryConfig::default()
             };
 
             let attempts = Arc::new(Mutex::new(0));
@@ -593,6 +2763,7 @@ mod tests {
                 delay: Duration::from_millis(10),
                 retry_condition: None,
                 strategy: ExponentialBackoff,
+                ..RetryConfig::default()
             };
 
             let attempts = Arc::new(Mutex::new(0));
@@ -618,6 +2789,7 @@ mod tests {
                 delay: Duration::from_millis(10),
                 retry_condition: Some(|e: &DummyError| e.0.contains("405")),
                 strategy: ExponentialBackoff,
+                ..RetryConfig::default()
             };
 
             let attempts = Arc::new(Mutex::new(0));
@@ -639,17 +2811,78 @@ mod tests {
             assert_eq!(result, Err(DummyError("temporary fail")));
             assert_eq!(*attempts.lock().unwrap(), 1);
         }
+
+        #[test]
+        #[allow(deprecated)]
+        fn test_deprecated_shim_runs_the_retry_loop_doubling_the_delay() {
+            let clock = crate::sim::VirtualClock::new();
+            let config = RetryConfig::new(4, Duration::from_secs(1), RetryStrategy::Linear)
+                .with_error_classifier(|err: &DummyError| {
+                    if err.0 == "fatal" {
+                        ErrorClass::Fatal
+                    } else {
+                        ErrorClass::Transient
+                    }
+                });
+
+            let attempts = Arc::new(Mutex::new(0));
+            let op_attempts = attempts.clone();
+            let result: Result<(), _> = clock.block_on(retry_with_exponential_backoff(
+                move || {
+                    *op_attempts.lock().unwrap() += 1;
+                    async { Err(DummyError("always fail")) }
+                },
+                &config,
+            ));
+            assert_eq!(result, Err(DummyError("always fail")));
+            assert_eq!(*attempts.lock().unwrap(), 4);
+            // 1s, then 2s, then 4s, as before the shim ran the `retry` loop.
+            assert_eq!(clock.elapsed(), Duration::from_secs(7));
+
+            let result: Result<(), _> = clock.block_on(retry_with_exponential_backoff(
+                || async { Err(DummyError("fatal")) },
+                &config,
+            ));
+            assert_eq!(result, Err(DummyError("fatal")));
+            assert_eq!(clock.elapsed(), Duration::from_secs(7));
+        }
+
+        #[test]
+        #[allow(deprecated)]
+        fn test_deprecated_shim_honors_per_class_policies() {
+            let config = RetryConfig::new(2, Duration::from_millis(1), RetryStrategy::Linear)
+                .with_error_classifier(|_: &DummyError| ErrorClass::Throttled { retry_after: None })
+                .with_policies(PolicyTable::new().on_throttled(ClassPolicy::new(
+                    5,
+                    Duration::from_millis(1),
+                    RetryStrategy::Linear,
+                )));
+
+            let attempts = Arc::new(Mutex::new(0));
+            let op_attempts = attempts.clone();
+            let result: Result<(), _> = block_on(retry_with_exponential_backoff(
+                move || {
+                    *op_attempts.lock().unwrap() += 1;
+                    async { Err(DummyError("rate limited")) }
+                },
+                &config,
+            ));
+            assert_eq!(result, Err(DummyError("rate limited")));
+            assert_eq!(*attempts.lock().unwrap(), 5);
+        }
     }
 
     // Suite for `execute_with_timeout` function
     mod execute_with_timeout_tests {
         use super::*;
+        use crate::config::LogConfig;
 
         #[test]
         fn test_execute_with_timeout_success() {
             let config: ExecConfig<String> = ExecConfig {
                 timeout_duration: Duration::from_millis(100),
                 fallback: None,
+                log: LogConfig::default(),
             };
 
             let operation = || async { Ok("success".to_string()) };
@@ -662,10 +2895,10 @@ mod tests {
             let config: ExecConfig<String> = ExecConfig {
                 timeout_duration: Duration::from_millis(100),
                 fallback: None,
+                log: LogConfig::default(),
             };
 
-            let operation =
-                || async { Err(Box::new(DummyError("immediate failure")) as Box<dyn Error>) };
+            let operation = || async { Err(Box::new(DummyError("immediate failure")) as BoxError) };
             let result = block_on(execute_with_fallback(operation(), &config));
             assert!(result.is_err());
             assert_eq!(result.unwrap_err().to_string(), "immediate failure");
@@ -676,6 +2909,7 @@ mod tests {
             let config: ExecConfig<String> = ExecConfig {
                 timeout_duration: Duration::from_millis(10),
                 fallback: None,
+                log: LogConfig::default(),
             };
 
             let operation = || async {
@@ -684,13 +2918,16 @@ mod tests {
             };
             let result = block_on(execute_with_fallback(operation(), &config));
             assert!(result.is_err());
-            assert_eq!(result.unwrap_err().to_string(), "future has timed out");
+            assert_eq!(
+                result.unwrap_err().to_string(),
+                "Operation timed out after 10ms"
+            );
         }
 
         #[test]
         fn test_execute_with_timeout_timeout_with_fallback_success() {
-            let mut config: ExecConfig<String> = ExecConfig::new(Duration::from_millis(10));
-            config.with_fallback(|| Ok("fallback success".to_string()));
+            let config: ExecConfig<String> = ExecConfig::new(Duration::from_millis(10))
+                .with_fallback(|_| Ok("fallback success".to_string()));
 
             let operation = || async {
                 sleep(Duration::from_millis(50)).await;
@@ -702,8 +2939,8 @@ mod tests {
 
         #[test]
         fn test_execute_with_timeout_timeout_with_fallback_failure() {
-            let mut config: ExecConfig<String> = ExecConfig::new(Duration::from_millis(10));
-            config.with_fallback(|| Err(Box::new(DummyError("fallback failed")) as Box<dyn Error>));
+            let config: ExecConfig<String> = ExecConfig::new(Duration::from_millis(10))
+                .with_fallback(|_| Err(Box::new(DummyError("fallback failed")) as BoxError));
 
             let operation = || async {
                 sleep(Duration::from_millis(50)).await;
@@ -714,11 +2951,61 @@ mod tests {
             assert_eq!(result.unwrap_err().to_string(), "fallback failed");
         }
 
+        #[test]
+        fn test_fallback_chain_walks_levels_in_order() {
+            let chain: FallbackChain<String> = FallbackChain::new(Duration::from_millis(10))
+                .with_fallback("cache", |_| {
+                    Err(Box::new(DummyError("cache miss")) as BoxError)
+                })
+                .with_fallback("default", |cause| {
+                    assert!(cause.is_timeout());
+                    Ok("default".to_string())
+                });
+
+            let fast = block_on(execute_with_fallback_chain(
+                async { Ok("primary".to_string()) },
+                &chain,
+            ));
+            assert_eq!(fast.unwrap(), ("primary".to_string(), ServedBy::Primary));
+
+            let slow = block_on(execute_with_fallback_chain(
+                async {
+                    sleep(Duration::from_millis(50)).await;
+                    Ok("too slow".to_string())
+                },
+                &chain,
+            ));
+            assert_eq!(
+                slow.unwrap(),
+                (
+                    "default".to_string(),
+                    ServedBy::Fallback {
+                        level: 2,
+                        name: "default"
+                    }
+                )
+            );
+
+            let exhausted = FallbackChain::<String>::new(Duration::from_millis(10)).with_fallback(
+                "cache",
+                |cause| {
+                    assert_eq!(cause.to_string(), "primary down");
+                    Err(Box::new(DummyError("cache miss")) as BoxError)
+                },
+            );
+            let failed = block_on(execute_with_fallback_chain(
+                async { Err(Box::new(DummyError("primary down")) as BoxError) },
+                &exhausted,
+            ));
+            assert_eq!(failed.unwrap_err().to_string(), "cache miss");
+        }
+
         #[test]
         fn test_execute_with_timeout_success_near_timeout() {
             let config: ExecConfig<String> = ExecConfig {
                 timeout_duration: Duration::from_millis(50),
                 fallback: None,
+                log: LogConfig::default(),
             };
 
             let operation = || async {
@@ -728,46 +3015,740 @@ mod tests {
             let result = block_on(execute_with_fallback(operation(), &config));
             assert_eq!(result.unwrap(), "just in time");
         }
+
+        #[test]
+        fn test_fallback_closure_serves_captured_value() {
+            let cached = Arc::new(Mutex::new(Some("last known price".to_string())));
+            let cache = cached.clone();
+            let config = ExecConfig::new(Duration::from_millis(10)).with_fallback(move |_| {
+                cache
+                    .lock()
+                    .unwrap()
+                    .clone()
+                    .ok_or_else(|| "cache empty".into())
+            });
+
+            let slow = async {
+                sleep(Duration::from_millis(100)).await;
+                Ok("fresh price".to_string())
+            };
+            assert_eq!(
+                block_on(execute_with_fallback(slow, &config)).unwrap(),
+                "last known price"
+            );
+
+            cached.lock().unwrap().take();
+            let slow = async {
+                sleep(Duration::from_millis(100)).await;
+                Ok("fresh price".to_string())
+            };
+            let failed = block_on(execute_with_fallback(slow, &config));
+            assert_eq!(failed.unwrap_err().to_string(), "cache empty");
+        }
+
+        #[test]
+        fn test_outcome_tells_fallback_from_primary() {
+            let config = ExecConfig::new(Duration::from_millis(50))
+                .with_fallback(|_| Ok("fallback".to_string()));
+
+            let fast = async { Ok("primary".to_string()) };
+            let outcome = block_on(execute_with_fallback_outcome(fast, &config)).unwrap();
+            assert_eq!(outcome, ExecOutcome::Primary("primary".to_string()));
+            assert!(!outcome.is_fallback());
+
+            let slow = async {
+                sleep(Duration::from_millis(200)).await;
+                Ok("primary".to_string())
+            };
+            let outcome = block_on(execute_with_fallback_outcome(slow, &config)).unwrap();
+            assert!(outcome.is_fallback());
+            assert_eq!(outcome.into_inner(), "fallback");
+        }
+
+        #[test]
+        fn test_retry_and_timeout_retries_timed_out_attempts() {
+            let clock = crate::sim::VirtualClock::new();
+            let exec_config: ExecConfig<String> = ExecConfig::new(Duration::from_secs(1));
+            let retry_config = RetryConfig::new(
+                3,
+                Duration::from_millis(100),
+                crate::strategies::RetryStrategy::Linear,
+            );
+            let mut attempts = 0;
+            let result = clock.block_on(execute_with_retry_and_timeout(
+                || {
+                    attempts += 1;
+                    let hangs = attempts < 3;
+                    async move {
+                        if hangs {
+                            sleep(Duration::from_secs(60)).await;
+                        }
+                        Ok("response".to_string())
+                    }
+                },
+                &exec_config,
+                &retry_config,
+            ));
+            assert_eq!(result.unwrap(), "response");
+            assert_eq!(attempts, 3);
+            assert_eq!(clock.elapsed(), Duration::from_millis(2200));
+
+            let retry_config = retry_config.with_retry_condition(|err| {
+                !err.downcast_ref::<crate::Error>()
+                    .is_some_and(crate::Error::is_timeout)
+            });
+            let result = clock.block_on(execute_with_retry_and_timeout(
+                || async {
+                    sleep(Duration::from_secs(60)).await;
+                    Ok("response".to_string())
+                },
+                &exec_config,
+                &retry_config,
+            ));
+            let err = result.unwrap_err();
+            assert!(matches!(
+                err.downcast_ref::<crate::Error>(),
+                Some(crate::Error::Timeout { timeout }) if *timeout == Duration::from_secs(1)
+            ));
+            assert_eq!(clock.elapsed(), Duration::from_millis(3200));
+        }
+
+        #[test]
+        fn test_retry_and_timeout_falls_back_after_last_timeout() {
+            let clock = crate::sim::VirtualClock::new();
+            let exec_config = ExecConfig::new(Duration::from_secs(1)).with_fallback(|cause| {
+                assert!(cause.is_timeout());
+                Ok("cached".to_string())
+            });
+            let retry_config = RetryConfig::new(
+                2,
+                Duration::from_millis(100),
+                crate::strategies::RetryStrategy::Linear,
+            );
+            let result = clock.block_on(execute_with_retry_and_timeout(
+                || async {
+                    sleep(Duration::from_secs(60)).await;
+                    Ok("response".to_string())
+                },
+                &exec_config,
+                &retry_config,
+            ));
+            assert_eq!(result.unwrap(), "cached");
+
+            let result = clock.block_on(execute_with_retry_and_timeout(
+                || async { Err::<String, _>("bad request".into()) },
+                &exec_config,
+                &retry_config,
+            ));
+            let err = result.unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "Operation failed after 2 attempts: bad request"
+            );
+            assert!(matches!(
+                err.downcast_ref::<crate::Error>(),
+                Some(crate::Error::RetriesExhausted { attempts: 2, .. })
+            ));
+        }
     }
 
     mod circuit_breaker_tests {
         use super::*;
+        use crate::config::FailureWindow;
+        use crate::sim::VirtualClock;
+        use crate::strategies::RetryStrategy;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+        use std::time::Instant;
+
+        #[test]
+        fn test_breaker_owns_its_config() {
+            fn assert_storable<T: Send + 'static>(_: &T) {}
+            let cb = {
+                let config = CircuitBreakerConfig::new(1, 1, Duration::from_secs(1));
+                CircuitBreaker::new(config)
+            };
+            assert_storable(&cb);
+        }
 
         #[test]
         fn test_success_keeps_closed() {
             let config = CircuitBreakerConfig::new(2, 3, Duration::from_secs(1));
-            let mut cb = CircuitBreaker::new(config);
-            let result = block_on(async {
-                cb.run(|| async { Ok::<_, Box<dyn Error>>("Success") })
-                    .await
-            });
+            let cb = CircuitBreaker::new(config);
+            let result =
+                block_on(async { cb.run(|| async { Ok::<_, BoxError>("Success") }).await });
             assert!(result.is_ok());
-            assert_eq!(cb.state, CircuitBreakerState::Close);
-            assert_eq!(cb.failure_count, 0);
+            assert_eq!(cb.state(), CircuitBreakerState::Close);
+            assert_eq!(cb.failure_count(), 0);
         }
 
         #[test]
         fn test_half_open_to_close() {
+            let clock = VirtualClock::new();
+            let _guard = clock.enter();
             let config = CircuitBreakerConfig::new(2, 3, Duration::from_millis(100));
-            let mut cb = CircuitBreaker::new(config);
+            let cb = CircuitBreaker::new(config);
             // Trigger Open state
             for _ in 0..3 {
                 let _ =
                     block_on(async { cb.run(|| async { Err::<(), _>(Box::from("Fail")) }).await });
             }
-            assert_eq!(cb.state, CircuitBreakerState::Open);
+            assert_eq!(cb.state(), CircuitBreakerState::Open);
             // Wait for cooldown
-            block_on(sleep(Duration::from_millis(150)));
+            clock.advance(Duration::from_millis(150));
             // Transition to HalfOpen and succeed twice
             for _ in 0..2 {
-                let result = block_on(async {
-                    cb.run(|| async { Ok::<_, Box<dyn Error>>("Success") })
-                        .await
-                });
+                let result =
+                    block_on(async { cb.run(|| async { Ok::<_, BoxError>("Success") }).await });
                 assert!(result.is_ok());
             }
-            assert_eq!(cb.state, CircuitBreakerState::Close);
-            assert_eq!(cb.success_count, 2);
+            assert_eq!(cb.state(), CircuitBreakerState::Close);
+            assert_eq!(cb.success_count(), 2);
+        }
+
+        #[test]
+        fn test_stats_record_opens_and_rejections() {
+            let stats = Stats::new();
+            let config = CircuitBreakerConfig::new(1, 1, Duration::from_secs(5));
+            let cb = CircuitBreaker::new(config).with_stats(stats.clone());
+            for _ in 0..3 {
+                let _ =
+                    block_on(async { cb.run(|| async { Err::<(), _>(Box::from("Fail")) }).await });
+            }
+            assert_eq!(stats.attempts(), 1);
+            assert_eq!(stats.breaker_opens(), 1);
+            assert_eq!(stats.rejections(), 2);
+        }
+
+        #[test]
+        fn test_open_rejection_is_distinguished_from_inner_error() {
+            let config = CircuitBreakerConfig::new(1, 1, Duration::from_secs(10));
+            let cb = CircuitBreaker::<String>::with_config(config);
+            let first = block_on(cb.run(|| async { Err::<(), _>("refused".to_string()) }));
+            assert_eq!(
+                first,
+                Err(CircuitBreakerError::Inner("refused".to_string()))
+            );
+            match block_on(cb.run(|| async { Ok::<(), String>(()) })) {
+                Err(CircuitBreakerError::Open { retry_after }) => {
+                    assert!(retry_after > Duration::from_secs(9));
+                    assert!(retry_after <= Duration::from_secs(10));
+                }
+                other => panic!("expected an open rejection, got {:?}", other),
+            }
+        }
+
+        #[test]
+        fn test_decorated_calls_run_concurrently() {
+            let config = CircuitBreakerConfig::default().with_max_concurrent_calls(1);
+            let breaker = Arc::new(CircuitBreaker::<&str>::with_config(config));
+            let (started_tx, started_rx) = async_std::channel::bounded::<()>(1);
+            let (release_tx, release_rx) = async_std::channel::bounded::<()>(1);
+            let call = CircuitBreaker::decorate(&breaker, move |slow: bool| {
+                let (started_tx, release_rx) = (started_tx.clone(), release_rx.clone());
+                async move {
+                    if slow {
+                        started_tx.send(()).await.unwrap();
+                        release_rx.recv().await.unwrap();
+                    }
+                    Ok::<_, &str>(slow)
+                }
+            });
+
+            block_on(async {
+                let slow = task::spawn(call(true));
+                started_rx.recv().await.unwrap();
+                // The slow call is in flight, so the breaker is saturated.
+                assert!(call(false).await.unwrap_err().is_saturated());
+                release_tx.send(()).await.unwrap();
+                assert_eq!(slow.await, Ok(true));
+                assert_eq!(call(false).await, Ok(false));
+            });
+        }
+
+        #[test]
+        fn test_late_outcomes_do_not_move_an_open_breaker() {
+            let stats = Stats::new();
+            let config = CircuitBreakerConfig::new(1, 1, Duration::from_secs(10));
+            let breaker =
+                Arc::new(CircuitBreaker::<&str>::with_config(config).with_stats(stats.clone()));
+            let (started_tx, started_rx) = async_std::channel::bounded::<()>(2);
+            let (release_tx, release_rx) = async_std::channel::bounded::<()>(2);
+            let slow = |outcome: Result<(), &'static str>| {
+                let breaker = breaker.clone();
+                let (started_tx, release_rx) = (started_tx.clone(), release_rx.clone());
+                task::spawn(async move {
+                    breaker
+                        .run(|| async move {
+                            started_tx.send(()).await.unwrap();
+                            release_rx.recv().await.unwrap();
+                            outcome
+                        })
+                        .await
+                })
+            };
+
+            block_on(async {
+                let late_failure = slow(Err("late"));
+                let late_success = slow(Ok(()));
+                started_rx.recv().await.unwrap();
+                started_rx.recv().await.unwrap();
+                // Both calls were admitted while closed; this one opens the circuit.
+                let _ = breaker.run(|| async { Err::<(), _>("Fail") }).await;
+                assert_eq!(breaker.state(), CircuitBreakerState::Open);
+
+                release_tx.send(()).await.unwrap();
+                release_tx.send(()).await.unwrap();
+                assert_eq!(late_failure.await, Err(CircuitBreakerError::Inner("late")));
+                assert_eq!(late_success.await, Ok(()));
+            });
+            assert_eq!(breaker.state(), CircuitBreakerState::Open);
+            assert_eq!(breaker.failure_count(), 1);
+            assert_eq!(stats.breaker_opens(), 1);
+        }
+
+        #[test]
+        fn test_decorate_with_retry_clones_argument_per_attempt() {
+            let breaker = Arc::new(CircuitBreaker::<String>::with_config(
+                CircuitBreakerConfig::default(),
+            ));
+            let attempts = Arc::new(AtomicUsize::new(0));
+            let retry_config = RetryConfig::new(3, Duration::from_millis(1), RetryStrategy::Linear);
+            let send = {
+                let attempts = attempts.clone();
+                CircuitBreaker::decorate_with_retry(
+                    &breaker,
+                    move |body: String| {
+                        let attempt = attempts.fetch_add(1, Ordering::SeqCst) + 1;
+                        async move {
+                            if attempt < 3 {
+                                Err(format!("attempt {} failed", attempt))
+                            } else {
+                                Ok(body.len())
+                            }
+                        }
+                    },
+                    retry_config,
+                )
+            };
+            assert_eq!(block_on(send("payload".to_string())), Ok(7));
+            assert_eq!(attempts.load(Ordering::SeqCst), 3);
+            assert_eq!(breaker.failure_count(), 0);
+        }
+
+        #[test]
+        fn test_bulkhead_rejects_after_max_wait() {
+            let bulkhead = Arc::new(Bulkhead::new(
+                BulkheadConfig::new(1).with_max_wait(Duration::from_millis(10)),
+            ));
+            let (started_tx, started_rx) = async_std::channel::bounded::<()>(1);
+            let (release_tx, release_rx) = async_std::channel::bounded::<()>(1);
+            block_on(async {
+                let slow = {
+                    let bulkhead = bulkhead.clone();
+                    task::spawn(async move {
+                        bulkhead
+                            .call(|| async {
+                                started_tx.send(()).await.unwrap();
+                                release_rx.recv().await.unwrap();
+                                Ok::<_, &str>("slow")
+                            })
+                            .await
+                    })
+                };
+                started_rx.recv().await.unwrap();
+                let start = Instant::now();
+                let rejected = bulkhead.call(|| async { Ok::<_, &str>("fast") }).await;
+                assert_eq!(rejected, Err(BulkheadError::Full));
+                assert!(start.elapsed() >= Duration::from_millis(10));
+
+                release_tx.send(()).await.unwrap();
+                assert_eq!(slow.await, Ok("slow"));
+                assert_eq!(bulkhead.available(), 1);
+                let fast = bulkhead.call(|| async { Err::<(), _>("down") }).await;
+                assert_eq!(fast, Err(BulkheadError::Inner("down")));
+            });
+        }
+
+        #[test]
+        fn test_run_accepts_fn_once_operation() {
+            let cb = CircuitBreaker::<String>::with_config(CircuitBreakerConfig::default());
+            // The body is moved into the future, which an `FnMut` operation could not do.
+            let body = String::from("payload");
+            let sent = block_on(cb.run(move || async move { Ok::<_, String>(body) }));
+            assert_eq!(sent, Ok("payload".to_string()));
+        }
+
+        #[test]
+        fn test_count_window_opens_on_failure_rate() {
+            let config = CircuitBreakerConfig::new(1, 1, Duration::from_secs(10))
+                .with_window(FailureWindow::count(4, 50.0, 4));
+            let cb = CircuitBreaker::<&str>::with_config(config);
+            for fail in [false, true, false] {
+                let _ = block_on(cb.run(|| async move { if fail { Err("Fail") } else { Ok(()) } }));
+            }
+            // A single failure would trip a consecutive breaker with this failure_threshold.
+            assert_eq!(cb.state(), CircuitBreakerState::Close);
+            let _ = block_on(cb.run(|| async { Err::<(), _>("Fail") }));
+            assert_eq!(cb.state(), CircuitBreakerState::Open);
+        }
+
+        #[test]
+        fn test_inspection_reflects_state() {
+            let config = CircuitBreakerConfig::new(1, 2, Duration::from_secs(10))
+                .with_window(FailureWindow::count(10, 100.0, 10));
+            let cb = CircuitBreaker::<&str>::with_config(config);
+            let _ = block_on(cb.run(|| async { Err::<(), _>("Fail") }));
+            let _ = block_on(cb.run(|| async { Ok::<(), &str>(()) }));
+            let metrics = cb.metrics();
+            assert_eq!(metrics.state, CircuitBreakerState::Close);
+            assert_eq!(metrics.window_calls, 2);
+            assert_eq!(metrics.failure_rate, Some(50.0));
+            assert_eq!(metrics.time_until_half_open, None);
+
+            let cb = CircuitBreaker::<&str>::with_config(CircuitBreakerConfig::new(
+                1,
+                1,
+                Duration::from_secs(10),
+            ));
+            let _ = block_on(cb.run(|| async { Err::<(), _>("Fail") }));
+            assert_eq!(cb.state(), CircuitBreakerState::Open);
+            assert_eq!(cb.failure_count(), 1);
+            assert!(cb.time_until_half_open().unwrap() > Duration::from_secs(9));
+        }
+
+        #[test]
+        fn test_fallback_only_used_when_open() {
+            let config = CircuitBreakerConfig::new(1, 1, Duration::from_secs(10));
+            let cb = CircuitBreaker::<&str>::with_config(config);
+            let first = block_on(
+                cb.run_with_fallback(|| async { Err::<i32, _>("Fail") }, || async { Ok(0) }),
+            );
+            assert_eq!(first, Err("Fail"));
+            let second = block_on(cb.run_with_fallback(|| async { Ok(1) }, || async { Ok(0) }));
+            assert_eq!(second, Ok(0));
+        }
+
+        #[test]
+        fn test_record_failure_if_ignores_caller_errors() {
+            let config = CircuitBreakerConfig::new(1, 2, Duration::from_secs(10));
+            let cb = CircuitBreaker::<u16>::with_config(config)
+                .with_record_failure_if(|status: &u16| *status >= 500);
+            for _ in 0..5 {
+                let result = block_on(cb.run(|| async { Err::<(), _>(404) }));
+                assert_eq!(result, Err(CircuitBreakerError::Inner(404)));
+            }
+            assert_eq!(cb.state(), CircuitBreakerState::Close);
+            assert_eq!(cb.failure_count(), 0);
+            for _ in 0..2 {
+                let _ = block_on(cb.run(|| async { Err::<(), _>(503) }));
+            }
+            assert_eq!(cb.state(), CircuitBreakerState::Open);
+        }
+
+        #[test]
+        fn test_fatal_error_trips_immediately() {
+            let config = CircuitBreakerConfig::new(2, 5, Duration::from_secs(1));
+            let cb = CircuitBreaker::new(config).with_classifier(|_: &_| ErrorClass::Fatal);
+            let _ = block_on(async { cb.run(|| async { Err::<(), _>(Box::from("Fail")) }).await });
+            assert_eq!(cb.state(), CircuitBreakerState::Open);
+        }
+
+        #[test]
+        fn test_failed_trial_after_fatal_trip_reopens() {
+            let clock = VirtualClock::new();
+            let _guard = clock.enter();
+            let config = CircuitBreakerConfig::new(1, 3, Duration::from_millis(100));
+            let cb =
+                CircuitBreaker::<&str>::with_config(config).with_classifier(
+                    |err: &&str| match *err {
+                        "fatal" => ErrorClass::Fatal,
+                        _ => ErrorClass::Transient,
+                    },
+                );
+            let _ = block_on(cb.run(|| async { Err::<(), _>("fatal") }));
+            assert_eq!(cb.state(), CircuitBreakerState::Open);
+            clock.advance(Duration::from_millis(150));
+
+            let trial = block_on(cb.run(|| async { Err::<(), _>("Fail") }));
+            assert_eq!(trial, Err(CircuitBreakerError::Inner("Fail")));
+            assert_eq!(cb.state(), CircuitBreakerState::Open);
+        }
+
+        #[test]
+        fn test_half_open_timer_transitions_without_calls() {
+            let breaker = Arc::new(CircuitBreaker::<&str>::with_config(
+                CircuitBreakerConfig::new(1, 1, Duration::from_millis(20)),
+            ));
+            let timer = CircuitBreaker::spawn_half_open_timer(&breaker, Duration::from_millis(5));
+            block_on(async {
+                let _ = breaker.run(|| async { Err::<(), _>("Fail") }).await;
+                assert_eq!(breaker.state(), CircuitBreakerState::Open);
+                sleep(Duration::from_millis(60)).await;
+                assert_eq!(breaker.state(), CircuitBreakerState::HalfOpen);
+            });
+
+            drop(breaker);
+            let finished = block_on(timeout(Duration::from_secs(1), timer));
+            assert!(
+                finished.is_ok(),
+                "the timer stops once the breaker is dropped"
+            );
+        }
+
+        #[test]
+        fn test_call_with_retry_recovers_within_breaker() {
+            let cb = CircuitBreaker::<&str>::with_config(CircuitBreakerConfig::new(
+                1,
+                3,
+                Duration::from_secs(60),
+            ));
+            let retry_config = RetryConfig::new(4, Duration::from_millis(1), RetryStrategy::Linear);
+            let attempts = AtomicUsize::new(0);
+            let result = block_on(cb.call_with_retry(
+                || async {
+                    if attempts.fetch_add(1, Ordering::SeqCst) < 2 {
+                        Err("flaky")
+                    } else {
+                        Ok("done")
+                    }
+                },
+                &retry_config,
+            ));
+            assert_eq!(result, Ok("done"));
+            assert_eq!(attempts.load(Ordering::SeqCst), 3);
+            assert_eq!(cb.state(), CircuitBreakerState::Close);
+            assert_eq!(cb.failure_count(), 0);
+        }
+
+        #[test]
+        fn test_call_with_retry_stops_when_breaker_opens() {
+            let cb = CircuitBreaker::<&str>::with_config(CircuitBreakerConfig::new(
+                1,
+                2,
+                Duration::from_secs(60),
+            ));
+            let retry_config = RetryConfig::new(5, Duration::from_millis(1), RetryStrategy::Linear);
+            let attempts = AtomicUsize::new(0);
+            let result = block_on(cb.call_with_retry(
+                || async {
+                    attempts.fetch_add(1, Ordering::SeqCst);
+                    Err::<(), _>("down")
+                },
+                &retry_config,
+            ));
+            assert_eq!(result, Err(CircuitBreakerError::Inner("down")));
+            assert_eq!(attempts.load(Ordering::SeqCst), 2);
+            assert_eq!(cb.state(), CircuitBreakerState::Open);
         }
     }
+
+    #[test]
+    fn test_retry_until_shutdown_stops_after_the_attempt_in_flight() {
+        let shutdown = ShutdownHandle::new();
+        let signal = shutdown.clone();
+        let retry_config = RetryConfig::new(5, Duration::from_secs(1), RetryStrategy::Linear);
+        let mut attempts = 0;
+        let result: Result<(), _> = crate::sim::VirtualClock::new().block_on(retry_until_shutdown(
+            || {
+                attempts += 1;
+                if attempts == 2 {
+                    signal.shutdown();
+                }
+                async move { Err(DummyError("unavailable")) }
+            },
+            &retry_config,
+            &shutdown,
+        ));
+        assert_eq!(
+            result,
+            Err(ShutdownError::Cancelled {
+                attempts: 2,
+                last_error: Some(DummyError("unavailable"))
+            })
+        );
+    }
+
+    #[test]
+    fn test_spawned_retry_task_reports_its_attempts() {
+        let retry_config = RetryConfig::new(5, Duration::from_millis(1), RetryStrategy::Linear);
+        let attempts = Arc::new(Mutex::new(0));
+        let task = spawn_with_retry(
+            {
+                let attempts = attempts.clone();
+                move || {
+                    let mut attempts = attempts.lock().unwrap();
+                    *attempts += 1;
+                    let result = if *attempts < 3 {
+                        Err("503")
+                    } else {
+                        Ok("delivered")
+                    };
+                    async move { result }
+                }
+            },
+            &retry_config,
+        );
+
+        let (result, timeline) = block_on(task.join());
+        assert_eq!(result, Ok("delivered"));
+        assert_eq!(*attempts.lock().unwrap(), 3);
+        assert_eq!(timeline.attempts().len(), 3);
+    }
+
+    #[test]
+    fn test_cancelled_retry_task_stops_during_its_backoff() {
+        let retry_config = RetryConfig::new(5, Duration::from_secs(60), RetryStrategy::Linear);
+        let task = spawn_with_retry(
+            || async { Err::<(), _>("connection refused") },
+            &retry_config,
+        );
+        block_on(sleep(Duration::from_millis(20)));
+        assert!(!task.is_finished());
+
+        task.cancel();
+        let (result, timeline) = block_on(task.join());
+        assert_eq!(
+            result,
+            Err(ShutdownError::Cancelled {
+                attempts: 1,
+                last_error: Some("connection refused")
+            })
+        );
+        assert!(timeline.total() < Duration::from_secs(60));
+    }
+
+    #[test]
+    fn test_dropped_call_is_neither_success_nor_failure() {
+        let config =
+            CircuitBreakerConfig::new(1, 1, Duration::from_secs(10)).with_max_concurrent_calls(1);
+        let cb = CircuitBreaker::<DummyError>::with_config(config);
+        let clock = crate::sim::VirtualClock::new();
+        let cancelled = clock.block_on(async {
+            let _ = cb.run(|| async { Err::<(), _>(DummyError("down")) }).await;
+            time::sleep(Duration::from_secs(10)).await;
+            time::timeout(
+                Duration::from_secs(1),
+                cb.run(std::future::pending::<Result<(), DummyError>>),
+            )
+            .await
+        });
+        assert!(cancelled.is_err());
+        assert_eq!(cb.state(), CircuitBreakerState::HalfOpen);
+        assert_eq!(cb.success_count(), 0);
+
+        // The dropped call released its slot, so the next trial call is admitted.
+        let result = block_on(cb.run(|| async { Ok::<_, DummyError>("recovered") }));
+        assert_eq!(result.ok(), Some("recovered"));
+        assert_eq!(cb.state(), CircuitBreakerState::Close);
+    }
+
+    #[test]
+    fn test_retry_with_cleanup_runs_only_when_dropped_mid_attempt() {
+        let (cleaned, cleanups) = async_std::channel::unbounded();
+        let done = cleaned.clone();
+        let result = block_on(retry_with_cleanup(
+            || async { Ok::<_, DummyError>(1) },
+            move || async move {
+                let _ = done.send(()).await;
+            },
+            &RetryConfig::default(),
+        ));
+        assert_eq!(result, Ok(1));
+
+        let result = block_on(async_std::future::timeout(
+            Duration::from_millis(10),
+            retry_with_cleanup(
+                std::future::pending::<Result<(), DummyError>>,
+                move || async move {
+                    let _ = cleaned.send(()).await;
+                },
+                &RetryConfig::default(),
+            ),
+        ));
+        assert!(result.is_err());
+        assert_eq!(block_on(cleanups.recv()), Ok(()));
+        assert!(cleanups.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_retry_transactional_rolls_back_every_failed_attempt() {
+        let retry_config = RetryConfig::new(3, Duration::from_secs(1), RetryStrategy::Linear);
+        let rollbacks = Mutex::new(Vec::new());
+        let result: Result<(), _> = crate::sim::VirtualClock::new().block_on(retry_transactional(
+            || async { Err(DummyError("write conflict")) },
+            |err: &DummyError| {
+                rollbacks.lock().unwrap().push((err.0, time::now()));
+                async {}
+            },
+            &retry_config,
+        ));
+        assert_eq!(result, Err(DummyError("write conflict")));
+        let rollbacks = rollbacks.lock().unwrap();
+        assert_eq!(rollbacks.len(), 3);
+        // Each rollback runs before the backoff delay, not after it.
+        assert_eq!(rollbacks[1].1 - rollbacks[0].1, Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_retry_with_input_moves_input_into_last_attempt() {
+        let retry_config = RetryConfig::new(3, Duration::from_secs(1), RetryStrategy::Linear);
+        let clones = Arc::new(());
+        let attempts = Mutex::new(Vec::new());
+        let result: Result<(), _> = crate::sim::VirtualClock::new().block_on(retry_with_input(
+            clones.clone(),
+            |input: Arc<()>| {
+                attempts.lock().unwrap().push(Arc::strong_count(&input));
+                async { Err(DummyError("payload rejected")) }
+            },
+            &retry_config,
+        ));
+        assert_eq!(result, Err(DummyError("payload rejected")));
+        // The first attempts get clones alongside the kept input; the last one gets the input.
+        assert_eq!(*attempts.lock().unwrap(), [3, 3, 2]);
+    }
+
+    #[test]
+    fn test_panicking_futures_are_retried_when_caught() {
+        let retry_config =
+            RetryConfig::new(3, Duration::from_secs(1), RetryStrategy::Linear).with_catch_panics();
+        let attempts = Mutex::new(0);
+        let result = crate::sim::VirtualClock::new().block_on(retry(
+            || async {
+                let attempt = {
+                    let mut attempts = attempts.lock().unwrap();
+                    *attempts += 1;
+                    *attempts
+                };
+                time::sleep(Duration::from_millis(10)).await;
+                if attempt == 1 {
+                    panic!("decoder hit a truncated frame");
+                }
+                Ok::<_, DummyError>(attempt)
+            },
+            &retry_config,
+        ));
+        assert_eq!(result, Ok(2));
+    }
+
+    #[test]
+    fn test_boxed_error_futures_are_send() {
+        fn assert_send<T: Send>(_: &T) {}
+        let exec_config = ExecConfig::<String>::new(Duration::from_secs(1));
+        assert_send(&execute_with_fallback(
+            async { Ok("cached".to_string()) },
+            &exec_config,
+        ));
+        let chain = FallbackChain::<String>::new(Duration::from_secs(1));
+        assert_send(&execute_with_fallback_chain(
+            async { Err("down".into()) },
+            &chain,
+        ));
+        let breaker = CircuitBreaker::new(CircuitBreakerConfig::default());
+        assert_send(&breaker.run(|| async { Ok::<_, BoxError>(()) }));
+        let retry_config = RetryConfig::<BoxError>::default();
+        assert_send(&retry(
+            || async { Err::<(), _>("down".into()) },
+            &retry_config,
+        ));
+    }
 }
@@ -0,0 +1,169 @@
+use crate::classifier::{ErrorClass, ErrorClassifier};
+use crate::config::RetryConfig;
+use crate::strategies::RetryStrategy;
+use aws_smithy_runtime_api::client::orchestrator::HttpResponse;
+use aws_smithy_runtime_api::client::result::SdkError;
+use aws_smithy_types::error::metadata::ProvideErrorMetadata;
+use std::time::Duration;
+
+/// Error codes AWS services return when a request is throttled.
+const THROTTLING_CODES: [&str; 14] = [
+    "Throttling",
+    "ThrottlingException",
+    "ThrottledException",
+    "RequestThrottledException",
+    "TooManyRequestsException",
+    "ProvisionedThroughputExceededException",
+    "TransactionInProgressException",
+    "RequestLimitExceeded",
+    "BandwidthLimitExceeded",
+    "LimitExceededException",
+    "RequestThrottled",
+    "SlowDown",
+    "PriorRequestNotComplete",
+    "EC2ThrottledException",
+];
+
+/// Error codes AWS services return for failures that go away on their own.
+const TRANSIENT_CODES: [&str; 4] = [
+    "RequestTimeout",
+    "RequestTimeoutException",
+    "InternalError",
+    "ServiceUnavailable",
+];
+
+/// HTTP statuses of service errors worth retrying whatever their error code.
+const TRANSIENT_STATUSES: [u16; 4] = [500, 502, 503, 504];
+
+/// An `ErrorClassifier` for the `SdkError`s returned by the operations of aws-sdk-rust clients,
+/// following the classification of the SDKs' "standard" retry mode.
+///
+/// - Service errors with a throttling code (`ThrottlingException`, `SlowDown`,
+///   `ProvisionedThroughputExceededException`, ...) or a `429` status are `Throttled`.
+/// - Service errors with a transient code (`RequestTimeout`, `InternalError`, ...) or a `500`,
+///   `502`, `503` or `504` status are `Transient`.
+/// - Timeouts, I/O failures while dispatching the request and unparseable responses are
+///   `Transient`.
+/// - Every other error, such as `AccessDenied`, a `ValidationException` or a request that could
+///   not be built, is `Permanent`.
+///
+/// # Example
+/// ```
+/// use aws_smithy_runtime_api::client::orchestrator::HttpResponse;
+/// use aws_smithy_runtime_api::client::result::SdkError;
+/// use aws_smithy_types::error::ErrorMetadata;
+/// use resilient_rs::aws::AwsClassifier;
+/// use resilient_rs::classifier::{ErrorClass, ErrorClassifier};
+///
+/// let err: SdkError<ErrorMetadata, HttpResponse> = SdkError::timeout_error("read timed out");
+/// assert_eq!(AwsClassifier.classify(&err), ErrorClass::Transient);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct AwsClassifier;
+
+impl<E: ProvideErrorMetadata> ErrorClassifier<SdkError<E, HttpResponse>> for AwsClassifier {
+    fn classify(&self, error: &SdkError<E, HttpResponse>) -> ErrorClass {
+        match error {
+            SdkError::ServiceError(err) => {
+                let code = err.err().code().unwrap_or_default();
+                let status = err.raw().status().as_u16();
+                if THROTTLING_CODES.contains(&code) || status == 429 {
+                    ErrorClass::Throttled { retry_after: None }
+                } else if TRANSIENT_CODES.contains(&code) || TRANSIENT_STATUSES.contains(&status) {
+                    ErrorClass::Transient
+                } else {
+                    ErrorClass::Permanent
+                }
+            }
+            SdkError::TimeoutError(_) | SdkError::ResponseError(_) => ErrorClass::Transient,
+            SdkError::DispatchFailure(failure) if failure.is_io() || failure.is_timeout() => {
+                ErrorClass::Transient
+            }
+            _ => ErrorClass::Permanent,
+        }
+    }
+}
+
+/// Returns a `RetryConfig` matching the "standard" retry mode of the AWS SDKs, for operations
+/// that call aws-sdk-rust clients.
+///
+/// Errors are classified by `AwsClassifier`, and failed calls are attempted up to 3 times in total
+/// with exponential backoff starting at 1 second, with jitter, and capped at 20 seconds.
+///
+/// Disable the SDK's own retries (`RetryConfig::disabled()` in the SDK's config) when using it,
+/// otherwise every attempt made here is itself retried by the SDK.
+///
+/// # Example
+/// ```
+/// use aws_smithy_runtime_api::client::orchestrator::HttpResponse;
+/// use aws_smithy_runtime_api::client::result::SdkError;
+/// use aws_smithy_types::error::ErrorMetadata;
+/// use resilient_rs::aws::standard_retry_config;
+/// use resilient_rs::config::RetryConfig;
+///
+/// let config: RetryConfig<SdkError<ErrorMetadata, HttpResponse>> = standard_retry_config();
+/// assert_eq!(config.max_attempts(), 3);
+/// ```
+pub fn standard_retry_config<E>() -> RetryConfig<SdkError<E, HttpResponse>>
+where
+    E: ProvideErrorMetadata + 'static,
+{
+    RetryConfig {
+        max_attempts: 3,
+        delay: Duration::from_secs(1),
+        strategy: RetryStrategy::ExponentialBackoffWithJitter { jitter_factor: 0.5 },
+        ..RetryConfig::default()
+    }
+    .with_max_delay(Duration::from_secs(20))
+    .with_error_classifier(AwsClassifier)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aws_smithy_runtime_api::client::result::ConnectorError;
+    use aws_smithy_runtime_api::http::StatusCode;
+    use aws_smithy_types::body::SdkBody;
+    use aws_smithy_types::error::ErrorMetadata;
+
+    type Error = SdkError<ErrorMetadata, HttpResponse>;
+
+    fn service_error(code: &str, status: u16) -> Error {
+        let raw = HttpResponse::new(StatusCode::try_from(status).unwrap(), SdkBody::empty());
+        SdkError::service_error(ErrorMetadata::builder().code(code).build(), raw)
+    }
+
+    #[test]
+    fn test_classifies_service_errors() {
+        assert_eq!(
+            AwsClassifier.classify(&service_error("ThrottlingException", 400)),
+            ErrorClass::Throttled { retry_after: None }
+        );
+        assert_eq!(
+            AwsClassifier.classify(&service_error("InternalFailure", 503)),
+            ErrorClass::Transient
+        );
+        assert_eq!(
+            AwsClassifier.classify(&service_error("RequestTimeout", 400)),
+            ErrorClass::Transient
+        );
+        assert_eq!(
+            AwsClassifier.classify(&service_error("AccessDenied", 403)),
+            ErrorClass::Permanent
+        );
+        assert_eq!(
+            AwsClassifier.classify(&service_error("ValidationException", 400)),
+            ErrorClass::Permanent
+        );
+    }
+
+    #[test]
+    fn test_classifies_dispatch_failures() {
+        let io: Error = SdkError::dispatch_failure(ConnectorError::io("connection reset".into()));
+        assert_eq!(AwsClassifier.classify(&io), ErrorClass::Transient);
+        let user: Error = SdkError::dispatch_failure(ConnectorError::user("invalid URI".into()));
+        assert_eq!(AwsClassifier.classify(&user), ErrorClass::Permanent);
+        let construction: Error = SdkError::construction_failure("missing bucket");
+        assert_eq!(AwsClassifier.classify(&construction), ErrorClass::Permanent);
+    }
+}
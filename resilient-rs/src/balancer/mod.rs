@@ -0,0 +1,396 @@
+use crate::asynchronous::{CircuitBreaker, CircuitBreakerState};
+use crate::classifier::{ErrorClass, ErrorClassifier};
+use crate::config::{BoxError, CircuitBreakerConfig, LogConfig};
+use crate::events::{self, ResilienceEvent};
+use crate::logging::Level;
+use crate::logging::log_with;
+use crate::time;
+use std::fmt;
+use std::sync::{Arc, MutexGuard, PoisonError};
+
+/// The error returned by `LoadBalancer::call`.
+#[derive(Debug)]
+pub enum BalancerError<E> {
+    /// No endpoint was tried: every breaker rejected the call, or there are no endpoints.
+    NoEndpointAvailable,
+    /// Every endpoint tried failed, or one failed with an error not worth trying elsewhere.
+    ///
+    /// `errors` holds the name of each endpoint tried and its error, in the order they were
+    /// tried.
+    Failed { errors: Vec<(String, E)> },
+}
+
+impl<E> BalancerError<E> {
+    /// Returns the error of the last endpoint tried, if any was tried.
+    pub fn last_error(&self) -> Option<&E> {
+        match self {
+            BalancerError::Failed { errors } => errors.last().map(|(_, err)| err),
+            BalancerError::NoEndpointAvailable => None,
+        }
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for BalancerError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BalancerError::NoEndpointAvailable => {
+                write!(f, "No endpoint is available. Please try later..!")
+            }
+            BalancerError::Failed { errors } => {
+                write!(f, "All {} endpoints tried failed", errors.len())?;
+                if let Some((name, err)) = errors.last() {
+                    write!(f, "; last error from {}: {}", name, err)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> std::error::Error for BalancerError<E> {}
+
+/// The state of an endpoint, returned by `LoadBalancer::endpoints`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EndpointStatus {
+    /// The name of the endpoint.
+    pub name: String,
+    /// The configured weight of the endpoint.
+    pub weight: u32,
+    /// The weight currently used for selection, lowered by recent failures.
+    pub effective_weight: u32,
+    /// The state of the endpoint's circuit breaker.
+    pub state: CircuitBreakerState,
+}
+
+struct Endpoint<T, E> {
+    name: String,
+    target: T,
+    breaker: Arc<CircuitBreaker<E>>,
+}
+
+/// The selection weights of an endpoint.
+struct Weights {
+    weight: u32,
+    effective: u32,
+    current: i64,
+}
+
+/// A client-side load balancer spreading calls over weighted endpoints, trying the next endpoint
+/// when one fails, with a circuit breaker per endpoint.
+///
+/// Endpoints are picked by smooth weighted round-robin: an endpoint of weight 3 receives three
+/// calls for every call of an endpoint of weight 1, interleaved rather than in bursts. Equal
+/// weights give plain round-robin.
+///
+/// Each call is tried on up to `max_attempts` distinct endpoints. Endpoints whose breaker is open
+/// are skipped without counting as an attempt. Errors classified as `Permanent` or `Fatal` by the
+/// classifier, if one is set, are returned without trying another endpoint, since the request
+/// itself is at fault.
+///
+/// Weights follow the health of the endpoints: a failure halves the effective weight of an
+/// endpoint, and every success raises it by one until it is back to the configured weight, so a
+/// degraded endpoint receives less traffic before its breaker opens, and traffic returns to it
+/// gradually once it recovered.
+///
+/// # Example
+/// ```
+/// use async_std::task::block_on;
+/// use resilient_rs::balancer::LoadBalancer;
+/// use resilient_rs::config::CircuitBreakerConfig;
+///
+/// let balancer: LoadBalancer<&str, String> = LoadBalancer::new(CircuitBreakerConfig::default())
+///     .with_endpoint("eu-1", "10.0.0.1", 1)
+///     .with_endpoint("eu-2", "10.0.0.2", 1);
+///
+/// // The first endpoint fails, so the call is tried again on the second one.
+/// let result = block_on(balancer.call(|address| {
+///     let address = address.to_string();
+///     async move {
+///         match address.as_str() {
+///             "10.0.0.1" => Err("connection refused".to_string()),
+///             _ => Ok(address),
+///         }
+///     }
+/// }));
+/// assert_eq!(result.unwrap(), "10.0.0.2");
+/// ```
+pub struct LoadBalancer<T, E = BoxError> {
+    endpoints: Vec<Endpoint<T, E>>,
+    weights: std::sync::Mutex<Vec<Weights>>,
+    breaker_config: CircuitBreakerConfig,
+    classifier: Option<Arc<dyn ErrorClassifier<E> + Send + Sync>>,
+    max_attempts: Option<usize>,
+    log: LogConfig,
+}
+
+impl<T, E> LoadBalancer<T, E> {
+    /// Creates a balancer without endpoints, guarding each endpoint added with a circuit breaker
+    /// configured with `breaker_config`.
+    pub fn new(breaker_config: CircuitBreakerConfig) -> Self {
+        LoadBalancer {
+            endpoints: Vec::new(),
+            weights: std::sync::Mutex::new(Vec::new()),
+            breaker_config,
+            classifier: None,
+            max_attempts: None,
+            log: LogConfig::default(),
+        }
+    }
+
+    /// Adds an endpoint and returns the modified balancer.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the endpoint, reported in errors and logs.
+    /// * `target` - The value passed to the operation to call this endpoint, e.g. its address or
+    ///   a client bound to it.
+    /// * `weight` - The share of calls sent to this endpoint relative to the others; `0` is
+    ///   treated as `1`.
+    pub fn with_endpoint(mut self, name: impl Into<String>, target: T, weight: u32) -> Self {
+        let weight = weight.max(1);
+        self.endpoints.push(Endpoint {
+            name: name.into(),
+            target,
+            breaker: Arc::new(CircuitBreaker::with_config(self.breaker_config)),
+        });
+        self.weights().push(Weights {
+            weight,
+            effective: weight,
+            current: 0,
+        });
+        self
+    }
+
+    /// Sets the classifier deciding which errors are tried on another endpoint, and returns the
+    /// modified balancer.
+    ///
+    /// Errors classified as `Permanent` or `Fatal` are returned at once. Without a classifier,
+    /// every error is tried on another endpoint.
+    pub fn with_classifier(
+        mut self,
+        classifier: impl ErrorClassifier<E> + Send + Sync + 'static,
+    ) -> Self {
+        self.classifier = Some(Arc::new(classifier));
+        self
+    }
+
+    /// Sets the maximum number of endpoints tried per call, and returns the modified balancer.
+    ///
+    /// By default, every endpoint may be tried.
+    pub fn with_max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = Some(max_attempts.max(1));
+        self
+    }
+
+    /// Sets the logging behavior of the balancer and returns the modified balancer.
+    pub fn with_log(mut self, log: LogConfig) -> Self {
+        self.log = log;
+        self
+    }
+
+    /// Returns the circuit breaker of the endpoint named `name`, e.g. to feed it with a
+    /// `HealthChecker` or to force it open during maintenance.
+    pub fn breaker(&self, name: &str) -> Option<Arc<CircuitBreaker<E>>> {
+        self.endpoints
+            .iter()
+            .find(|endpoint| endpoint.name == name)
+            .map(|endpoint| endpoint.breaker.clone())
+    }
+
+    /// Returns the weights and breaker state of every endpoint, in the order they were added.
+    pub async fn endpoints(&self) -> Vec<EndpointStatus> {
+        let mut statuses = Vec::with_capacity(self.endpoints.len());
+        for (index, endpoint) in self.endpoints.iter().enumerate() {
+            let state = endpoint.breaker.state();
+            let weights = &self.weights()[index];
+            statuses.push(EndpointStatus {
+                name: endpoint.name.clone(),
+                weight: weights.weight,
+                effective_weight: weights.effective,
+                state,
+            });
+        }
+        statuses
+    }
+
+    /// Picks the next endpoint not in `tried` by smooth weighted round-robin.
+    fn pick(&self, tried: &[bool]) -> Option<usize> {
+        let mut weights = self.weights();
+        let mut total = 0;
+        let mut best: Option<usize> = None;
+        for index in 0..weights.len() {
+            if tried[index] {
+                continue;
+            }
+            let effective = i64::from(weights[index].effective);
+            weights[index].current += effective;
+            total += effective;
+            if best.is_none_or(|best| weights[index].current > weights[best].current) {
+                best = Some(index);
+            }
+        }
+        let best = best?;
+        weights[best].current -= total;
+        Some(best)
+    }
+
+    /// Adjusts the effective weight of an endpoint after a call.
+    fn adjust(&self, index: usize, succeeded: bool) {
+        let mut weights = self.weights();
+        let endpoint = &mut weights[index];
+        endpoint.effective = if succeeded {
+            (endpoint.effective + 1).min(endpoint.weight)
+        } else {
+            (endpoint.effective / 2).max(1)
+        };
+    }
+
+    fn weights(&self) -> MutexGuard<'_, Vec<Weights>> {
+        self.weights.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T, E: fmt::Display> LoadBalancer<T, E> {
+    /// Calls `operation` on the selected endpoint, trying the next endpoints on failure.
+    ///
+    /// # Arguments
+    /// * `operation` - Calls the endpoint whose target it receives; it may be called once per
+    ///   endpoint tried.
+    ///
+    /// # Returns
+    /// - `Ok(R)` with the result of the first endpoint that succeeded.
+    /// - `Err(BalancerError::NoEndpointAvailable)` if no endpoint could be tried.
+    /// - `Err(BalancerError::Failed)` with the error of every endpoint tried otherwise.
+    pub async fn call<F, Fut, R>(&self, mut operation: F) -> Result<R, BalancerError<E>>
+    where
+        F: FnMut(&T) -> Fut,
+        Fut: Future<Output = Result<R, E>>,
+    {
+        let max_attempts = self.max_attempts.unwrap_or(self.endpoints.len());
+        let mut tried = vec![false; self.endpoints.len()];
+        let mut errors = Vec::new();
+
+        while errors.len() < max_attempts {
+            let Some(index) = self.pick(&tried) else {
+                break;
+            };
+            tried[index] = true;
+            let endpoint = &self.endpoints[index];
+            let Ok(permit) = endpoint.breaker.acquire() else {
+                log_with!(
+                    self.log,
+                    Level::Debug,
+                    "Skipping endpoint {}: its circuit is open",
+                    endpoint.name
+                );
+                continue;
+            };
+            events::emit(ResilienceEvent::AttemptStarted {
+                attempt: errors.len() + 1,
+            });
+            let start = time::now();
+            let result = operation(&endpoint.target).await;
+            let generation = permit.finish();
+            let elapsed = time::elapsed(start);
+            let result = endpoint.breaker.complete(generation, result, elapsed);
+            self.adjust(index, result.is_ok());
+
+            let err = match result {
+                Ok(output) => return Ok(output),
+                Err(err) => err
+                    .into_inner()
+                    .expect("an admitted call fails with its own error"),
+            };
+            let class = self
+                .classifier
+                .as_ref()
+                .map_or(ErrorClass::Transient, |classifier| {
+                    classifier.classify(&err)
+                });
+            log_with!(
+                self.log,
+                self.log.level,
+                "Endpoint {} failed with {}",
+                endpoint.name,
+                err
+            );
+            errors.push((endpoint.name.clone(), err));
+            if matches!(class, ErrorClass::Permanent | ErrorClass::Fatal) {
+                break;
+            }
+        }
+
+        if errors.is_empty() {
+            log_with!(self.log, Level::Error, "No endpoint is available");
+            return Err(BalancerError::NoEndpointAvailable);
+        }
+        Err(BalancerError::Failed { errors })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_std::task::block_on;
+    use std::time::Duration;
+
+    #[test]
+    fn test_weighted_round_robin_interleaves_endpoints() {
+        let balancer: LoadBalancer<&str, String> =
+            LoadBalancer::new(CircuitBreakerConfig::default())
+                .with_endpoint("a", "a", 3)
+                .with_endpoint("b", "b", 1);
+
+        let picks: Vec<&str> = (0..8)
+            .map(|_| {
+                block_on(balancer.call(|target| {
+                    let target = *target;
+                    async move { Ok::<_, String>(target) }
+                }))
+            })
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(picks, ["a", "a", "b", "a", "a", "a", "b", "a"]);
+    }
+
+    #[test]
+    fn test_failing_endpoint_is_failed_over_then_skipped() {
+        let balancer: LoadBalancer<&str, String> =
+            LoadBalancer::new(CircuitBreakerConfig::new(1, 2, Duration::from_secs(30)))
+                .with_endpoint("a", "a", 4)
+                .with_endpoint("b", "b", 1)
+                .with_classifier(|err: &String| match err.as_str() {
+                    "bad request" => ErrorClass::Permanent,
+                    _ => ErrorClass::Transient,
+                });
+        let calls = std::sync::Mutex::new(Vec::new());
+        let call = |fail_a: &'static str| {
+            block_on(balancer.call(|target| {
+                calls.lock().unwrap().push(*target);
+                let result = match *target {
+                    "a" => Err(fail_a.to_string()),
+                    target => Ok(target),
+                };
+                async move { result }
+            }))
+        };
+
+        for _ in 0..3 {
+            assert_eq!(call("unavailable").unwrap(), "b");
+        }
+        assert_eq!(*calls.lock().unwrap(), ["a", "b", "b", "a", "b"]);
+        let statuses = block_on(balancer.endpoints());
+        assert_eq!(statuses[0].state, CircuitBreakerState::Open);
+        assert_eq!(statuses[0].effective_weight, 1);
+        calls.lock().unwrap().clear();
+
+        // The open endpoint is skipped without being called.
+        assert_eq!(call("unavailable").unwrap(), "b");
+        assert_eq!(*calls.lock().unwrap(), ["b"]);
+
+        // Errors of the request itself are not tried on another endpoint.
+        let err = block_on(balancer.call(|_| async { Err::<&str, _>("bad request".to_string()) }))
+            .unwrap_err();
+        assert_eq!(err.last_error().map(String::as_str), Some("bad request"));
+        assert!(matches!(&err, BalancerError::Failed { errors } if errors.len() == 1));
+    }
+}
@@ -0,0 +1,276 @@
+use crate::asynchronous::back_off;
+use crate::classifier::ErrorClass;
+use crate::config::RetryConfig;
+use crate::logging::Level;
+use crate::logging::{log_sampled, log_with};
+use std::time::Duration;
+
+/// The final outcome of one item of a batch.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ItemOutcome<T, E> {
+    /// The result of the item's last attempt.
+    pub result: Result<T, E>,
+    /// The number of batches the item was submitted in.
+    pub attempts: usize,
+}
+
+/// The outcome of every item of a batch, in the order the items were given to `retry_batch`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchOutcome<T, E> {
+    /// The outcome of each item, at the item's position in the input.
+    pub items: Vec<ItemOutcome<T, E>>,
+}
+
+impl<T, E> BatchOutcome<T, E> {
+    /// Returns `true` if every item succeeded.
+    pub fn is_complete(&self) -> bool {
+        self.items.iter().all(|item| item.result.is_ok())
+    }
+
+    /// Returns the position and value of the items that succeeded.
+    pub fn succeeded(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.items
+            .iter()
+            .enumerate()
+            .filter_map(|(index, item)| item.result.as_ref().ok().map(|value| (index, value)))
+    }
+
+    /// Returns the position and last error of the items that still failed.
+    pub fn failed(&self) -> impl Iterator<Item = (usize, &E)> {
+        self.items
+            .iter()
+            .enumerate()
+            .filter_map(|(index, item)| item.result.as_ref().err().map(|err| (index, err)))
+    }
+}
+
+/// Submits a batch to a bulk API and retries only the items that failed, re-batched together,
+/// until they succeed or run out of attempts.
+///
+/// `submit` receives the items to send and returns either one result per item, in the same
+/// order, or an error failing the whole batch, such as a dropped connection. Each failed item is
+/// classified with `retry_config` like a single call would be: items whose error is not
+/// retryable, or that used up the attempts of their class, are given up on, and the others are
+/// submitted again in the next batch. Batches are spaced with the delay of `retry_config`'s
+/// strategy, or with the longest `Throttled` hint of the failed items.
+///
+/// # Arguments
+/// * `items` - The items of the batch.
+/// * `submit` - A closure sending a batch and returning a `Future` resolving to the per-item
+///   results, or to an error failing every item.
+/// * `retry_config` - The retry policy applied to each item.
+///
+/// # Returns
+/// The outcome of every item, with the result of its last attempt.
+///
+/// # Panics
+/// Panics if `submit` returns a number of results different from the number of items it was
+/// given.
+///
+/// # Example
+/// ```
+/// use async_std::task::block_on;
+/// use resilient_rs::batch::retry_batch;
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::strategies::RetryStrategy;
+/// use std::time::Duration;
+///
+/// let mut rejected_once = false;
+/// let retry_config = RetryConfig::new(3, Duration::from_millis(1), RetryStrategy::Linear);
+/// let outcome = block_on(retry_batch(
+///     vec!["a", "b", "c"],
+///     |documents: Vec<&str>| {
+///         // The index rejects "b" on the first bulk request.
+///         let results = documents
+///             .iter()
+///             .map(|&document| {
+///                 if document == "b" && !rejected_once {
+///                     rejected_once = true;
+///                     Err("version conflict".to_string())
+///                 } else {
+///                     Ok(format!("indexed {}", document))
+///                 }
+///             })
+///             .collect();
+///         async move { Ok(results) }
+///     },
+///     &retry_config,
+/// ));
+///
+/// assert!(outcome.is_complete());
+/// assert_eq!(outcome.items[1].attempts, 2);
+/// ```
+pub async fn retry_batch<I, F, Fut, T, E>(
+    items: Vec<I>,
+    mut submit: F,
+    retry_config: &RetryConfig<E>,
+) -> BatchOutcome<T, E>
+where
+    I: Clone,
+    E: Clone,
+    F: FnMut(Vec<I>) -> Fut,
+    Fut: Future<Output = Result<Vec<Result<T, E>>, E>>,
+{
+    let mut results: Vec<Option<Result<T, E>>> = items.iter().map(|_| None).collect();
+    let mut attempts = vec![0; items.len()];
+    let mut pending: Vec<usize> = (0..items.len()).collect();
+    let mut delay = retry_config.delay;
+    let mut round = 1;
+
+    while !pending.is_empty() {
+        let batch = pending.iter().map(|&index| items[index].clone()).collect();
+        let round_results = match submit(batch).await {
+            Ok(round_results) => {
+                assert_eq!(
+                    round_results.len(),
+                    pending.len(),
+                    "a batch must return one result per item"
+                );
+                round_results
+            }
+            Err(err) => pending.iter().map(|_| Err(err.clone())).collect(),
+        };
+
+        let mut retried = Vec::new();
+        let mut given_up = 0;
+        let mut wait = Duration::ZERO;
+        for (index, result) in pending.iter().copied().zip(round_results) {
+            attempts[index] += 1;
+            if let Err(err) = &result {
+                let class = retry_config.classify(err);
+                let (max_attempts, class_wait) =
+                    retry_config.budget_for(&class, attempts[index], delay);
+                if class.is_retryable() && attempts[index] < max_attempts {
+                    wait = wait.max(match class {
+                        ErrorClass::Throttled {
+                            retry_after: Some(retry_after),
+                        } => retry_after,
+                        _ => class_wait,
+                    });
+                    retried.push(index);
+                } else {
+                    given_up += 1;
+                }
+            }
+            results[index] = Some(result);
+        }
+
+        if given_up > 0 {
+            log_with!(
+                retry_config.log,
+                Level::Warn,
+                "{} batch items failed after {} attempts, giving up on them.",
+                given_up,
+                round
+            );
+        }
+        if !retried.is_empty() {
+            log_sampled!(
+                retry_config.log,
+                round,
+                retry_config.log.level,
+                "{} of {} batch items failed (attempt {}), retrying them after {:?}...",
+                retried.len(),
+                pending.len(),
+                round,
+                wait
+            );
+            back_off(None, retry_config.timer_wheel.as_ref(), wait).await;
+            delay = retry_config.next_delay(delay, round);
+        }
+        pending = retried;
+        round += 1;
+    }
+
+    BatchOutcome {
+        items: results
+            .into_iter()
+            .zip(attempts)
+            .map(|(result, attempts)| ItemOutcome {
+                result: result.expect("every item is submitted at least once"),
+                attempts,
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::VirtualClock;
+    use crate::strategies::RetryStrategy;
+
+    #[test]
+    fn test_retries_only_failed_items() {
+        let retry_config = RetryConfig::new(3, Duration::from_secs(1), RetryStrategy::Linear);
+        let mut batches = Vec::new();
+        let clock = VirtualClock::new();
+        let outcome = clock.block_on(retry_batch(
+            (0..5).collect(),
+            |batch: Vec<u32>| {
+                let round = batches.len();
+                batches.push(batch.clone());
+                let results = batch
+                    .into_iter()
+                    .map(|item| match item {
+                        3 => Err(format!("item {} rejected", item)),
+                        _ if item % 2 == 1 && round == 0 => Err("throttled".to_string()),
+                        _ => Ok(item * 10),
+                    })
+                    .collect();
+                async move { Ok(results) }
+            },
+            &retry_config,
+        ));
+
+        assert_eq!(batches, [vec![0, 1, 2, 3, 4], vec![1, 3], vec![3]]);
+        assert_eq!(clock.elapsed(), Duration::from_secs(2));
+        assert!(!outcome.is_complete());
+        assert_eq!(
+            outcome.succeeded().collect::<Vec<_>>(),
+            [(0, &0), (1, &10), (2, &20), (4, &40)]
+        );
+        assert_eq!(outcome.items[1].attempts, 2);
+        assert_eq!(
+            outcome.items[3],
+            ItemOutcome {
+                result: Err("item 3 rejected".to_string()),
+                attempts: 3
+            }
+        );
+    }
+
+    #[test]
+    fn test_whole_batch_errors_and_permanent_items() {
+        let retry_config = RetryConfig::new(3, Duration::from_secs(1), RetryStrategy::Linear)
+            .with_retry_condition(|err: &&str| *err != "invalid");
+        let mut calls = 0;
+        let outcome = VirtualClock::new().block_on(retry_batch(
+            vec!["ok", "bad"],
+            |batch: Vec<&str>| {
+                calls += 1;
+                let result = if calls == 1 {
+                    Err("connection reset")
+                } else {
+                    Ok(batch
+                        .into_iter()
+                        .map(|item| {
+                            if item == "bad" {
+                                Err("invalid")
+                            } else {
+                                Ok(item)
+                            }
+                        })
+                        .collect())
+                };
+                async move { result }
+            },
+            &retry_config,
+        ));
+
+        assert_eq!(calls, 2);
+        assert_eq!(outcome.items[0].result, Ok("ok"));
+        assert_eq!(outcome.failed().collect::<Vec<_>>(), [(1, &"invalid")]);
+        assert_eq!(outcome.items[1].attempts, 2);
+    }
+}
@@ -0,0 +1,995 @@
+use crate::classifier::{ErrorClass, ErrorClassifier};
+use crate::config::{CircuitBreakerConfig, FailureWindow, LogConfig, SharedCircuitBreakerConfig};
+use crate::events::{self, ResilienceEvent};
+use crate::logging::Level;
+use crate::logging::log_with;
+use crate::metrics;
+use crate::stats::Stats;
+use crate::store::{SharedBreakerState, StateStore, StoreError};
+use crate::time;
+use crate::window::{CallHistory, OutcomeWindow};
+use async_std::channel::{self, Receiver, Sender};
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Represents the possible states of a circuit breaker.
+///
+/// A circuit breaker can be in one of three states, which determine how it handles operations:
+/// - `Close`: Operations are allowed to proceed normally.
+/// - `Open`: Operations are blocked due to repeated failures, preventing further attempts until a cooldown period elapses.
+/// - `HalfOpen`: A trial state after the cooldown, where operations are tentatively allowed to test if the system has recovered.
+///
+/// Two additional administrative states are only entered and left through the API
+/// (`force_open`, `disable` and `reset`), never because of call outcomes:
+/// - `ForcedOpen`: Every operation is rejected, e.g. while a dependency is under maintenance.
+/// - `Disabled`: Every operation is allowed and no outcome is recorded, e.g. while debugging.
+///
+/// This enum is used by the `CircuitBreaker` struct to manage its state machine, and is returned by
+/// `CircuitBreaker::state` for inspection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CircuitBreakerState {
+    /// Calls are executed and failures are counted.
+    Close,
+    /// Calls are rejected until the cooldown period elapses.
+    Open,
+    /// Trial calls are executed to test whether the dependency recovered.
+    HalfOpen,
+    /// Calls are rejected until the breaker is reset.
+    ForcedOpen,
+    /// Calls are executed and their outcomes are not recorded until the breaker is reset.
+    Disabled,
+}
+
+/// A change of state of a `CircuitBreaker`, delivered by `CircuitBreaker::events`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransitionEvent {
+    /// The state the breaker left.
+    pub from: CircuitBreakerState,
+    /// The state the breaker entered.
+    pub to: CircuitBreakerState,
+    /// When the transition happened.
+    pub at: Instant,
+}
+
+/// A point-in-time snapshot of a `CircuitBreaker`, returned by `CircuitBreaker::metrics`.
+///
+/// It is meant for dashboards and health endpoints; the values are copied, so the snapshot does
+/// not change as the breaker keeps running.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CircuitBreakerMetrics {
+    /// The current state of the breaker.
+    pub state: CircuitBreakerState,
+    /// Consecutive failures counted towards `failure_threshold`.
+    pub failure_count: usize,
+    /// Successful trial calls counted towards `success_threshold` while `HalfOpen`.
+    pub success_count: usize,
+    /// The number of calls inside the sliding window; 0 with `FailureWindow::Consecutive`.
+    pub window_calls: usize,
+    /// The failure percentage inside the sliding window, if it contains any call.
+    pub failure_rate: Option<f64>,
+    /// The remaining cooldown while `Open`; see `CircuitBreaker::time_until_half_open`.
+    pub time_until_half_open: Option<Duration>,
+    /// The success percentage over the last `metrics_window_size` calls, if any call was made.
+    pub success_rate: Option<f64>,
+    /// The mean latency over the last `metrics_window_size` calls, if any call was made.
+    pub mean_latency: Option<Duration>,
+}
+
+/// A predicate deciding whether an error counts as a circuit breaker failure.
+pub(crate) type FailurePredicate<E> = Arc<dyn Fn(&E) -> bool + Send + Sync>;
+
+/// The error returned by `CircuitBreaker::run`.
+///
+/// It tells apart calls rejected by the breaker from failures of the operation itself, so callers
+/// can branch on the outcome without inspecting error messages.
+///
+/// # Example
+/// ```rust
+/// use std::time::Duration;
+/// use async_std::task::block_on;
+/// use resilient_rs::asynchronous::{CircuitBreaker, CircuitBreakerError};
+/// use resilient_rs::config::CircuitBreakerConfig;
+///
+/// let cb = CircuitBreaker::<&str>::with_config(CircuitBreakerConfig::new(1, 1, Duration::from_secs(30)));
+/// let first = block_on(cb.run(|| async { Err::<(), _>("connection refused") }));
+/// assert!(matches!(first, Err(CircuitBreakerError::Inner("connection refused"))));
+///
+/// let second = block_on(cb.run(|| async { Ok::<_, &str>(()) }));
+/// assert!(matches!(second, Err(CircuitBreakerError::Open { .. })));
+/// ```
+#[derive(Debug, PartialEq)]
+pub enum CircuitBreakerError<E> {
+    /// The call was rejected without running the operation because the breaker is open.
+    ///
+    /// `retry_after` is the remaining cooldown before the breaker lets a trial call through. A
+    /// `ForcedOpen` breaker has no cooldown and reports the configured `cooldown_period` as a hint.
+    Open { retry_after: Duration },
+    /// The call was rejected without running the operation because `max_concurrent_calls` calls
+    /// were already running through the breaker.
+    Saturated { max_concurrent_calls: usize },
+    /// The operation ran and failed with this error.
+    Inner(E),
+}
+
+impl<E> CircuitBreakerError<E> {
+    /// Returns `true` if the call was rejected because the breaker is open.
+    pub fn is_open(&self) -> bool {
+        matches!(self, CircuitBreakerError::Open { .. })
+    }
+
+    /// Returns `true` if the call was rejected because the breaker's concurrency cap was reached.
+    pub fn is_saturated(&self) -> bool {
+        matches!(self, CircuitBreakerError::Saturated { .. })
+    }
+
+    /// Returns the error of the operation, or `None` if the call was rejected.
+    pub fn into_inner(self) -> Option<E> {
+        match self {
+            CircuitBreakerError::Inner(err) => Some(err),
+            CircuitBreakerError::Open { .. } | CircuitBreakerError::Saturated { .. } => None,
+        }
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for CircuitBreakerError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CircuitBreakerError::Open { .. } => {
+                write!(f, "Circuit Breaker is open. Please try later..!")
+            }
+            CircuitBreakerError::Saturated {
+                max_concurrent_calls,
+            } => write!(
+                f,
+                "Circuit Breaker is at its limit of {} concurrent calls. Please try later..!",
+                max_concurrent_calls
+            ),
+            CircuitBreakerError::Inner(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> Error for CircuitBreakerError<E> {}
+
+/// The circuit breaker state machine shared by `asynchronous::CircuitBreaker` and
+/// `synchronous::CircuitBreaker`.
+///
+/// The core never runs the supervised operation itself: a call is admitted with `acquire`, the
+/// wrapper runs the operation in its own execution model, and the outcome is handed back to
+/// `complete`. This keeps the transitions, counters, logging and statistics identical across both
+/// wrappers, and lets the synchronous breaker release its lock while the operation runs.
+///
+/// The wrappers go through the `acquire`, `complete` and `record_probe` functions of this module,
+/// which read and write the `StateStore` outside the lock, so a slow store delays only the call
+/// talking to it.
+///
+/// # Fields
+/// * `config` - Configuration defining thresholds and cooldown period
+/// * `state` - Current state of the circuit breaker (`Closed`, `Open`, or `HalfOpen`)
+/// * `failure_count` - Number of consecutive failures since the last state change
+/// * `outcomes` - Recent call outcomes, used when the config has a sliding `FailureWindow`
+/// * `history` - Outcomes and latencies of the last calls, reported by the metrics
+/// * `success_count` - Number of consecutive successes in the `HalfOpen` state
+/// * `last_failure_time` - Timestamp of the most recent failure (if any), used to enforce cooldown period
+/// * `classifier` - Optional error classifier; errors classified as `Fatal` trip the breaker immediately
+/// * `record_failure_if` - Optional predicate; errors it rejects do not count as failures
+/// * `stats` - Optional statistics handle recording calls, rejections and breaker openings
+/// * `shared_config` - Optional hot-reloadable configuration, re-read at the start of every call
+/// * `store` - Optional `StateStore` sharing the open state and failure count with other breakers
+/// * `pending` - Store writes decided under the lock, performed once it is released
+/// * `notices` - Events and diagnostics raised under the lock, emitted once it is released
+/// * `subscribers` - Channels receiving a `TransitionEvent` on every state change
+/// * `reopens` - Consecutive re-openings counted towards the config's `CooldownEscalation`
+/// * `closed_since` - When the circuit last closed after being open, used to reset the escalation
+/// * `in_flight` - Number of admitted calls still running, checked against `max_concurrent_calls`
+/// * `generation` - Incremented on every transition; outcomes of calls admitted in an earlier
+///   generation are not applied
+pub(crate) struct BreakerCore<E> {
+    config: CircuitBreakerConfig,
+    state: CircuitBreakerState,
+    failure_count: usize,
+    outcomes: OutcomeWindow,
+    history: CallHistory,
+    success_count: usize,
+    last_failure_time: Option<Instant>,
+    pub(crate) classifier: Option<Arc<dyn ErrorClassifier<E> + Send + Sync>>,
+    pub(crate) record_failure_if: Option<FailurePredicate<E>>,
+    pub(crate) stats: Option<Stats>,
+    shared_config: Option<SharedCircuitBreakerConfig>,
+    store: Option<StoreBinding>,
+    pending: Vec<StoreWrite>,
+    notices: Vec<Notice>,
+    subscribers: Vec<Sender<TransitionEvent>>,
+    reopens: u32,
+    closed_since: Option<Instant>,
+    in_flight: Arc<AtomicUsize>,
+    generation: u64,
+}
+
+/// The state generation a call was admitted in, handed back to `BreakerCore::complete`.
+///
+/// Breakers are shared, so a call admitted before a transition can complete after it; its
+/// outcome then describes a state the breaker already left and is not applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Generation(u64);
+
+/// Admission of a call through a `BreakerCore`; counts the call as running until dropped.
+///
+/// The wrappers `finish` it as soon as the operation returns, before reporting the outcome, so
+/// the synchronous breaker can keep the count without holding its lock during the call. A permit
+/// dropped without being finished belongs to a call whose future was dropped mid-call: the call
+/// stops counting as running and is reported as cancelled, but it is neither a success nor a
+/// failure, so a `HalfOpen` breaker keeps waiting for a trial call that completes.
+pub(crate) struct CallPermit {
+    in_flight: Arc<AtomicUsize>,
+    started: Instant,
+    log: LogConfig,
+    finished: bool,
+    generation: Generation,
+}
+
+impl CallPermit {
+    /// Marks the call as completed; its outcome is reported to the breaker by the caller along
+    /// with the returned generation.
+    pub(crate) fn finish(mut self) -> Generation {
+        self.finished = true;
+        self.generation
+    }
+}
+
+impl Drop for CallPermit {
+    fn drop(&mut self) {
+        self.in_flight.fetch_sub(1, Ordering::AcqRel);
+        if !self.finished {
+            let elapsed = time::elapsed(self.started);
+            log_with!(
+                self.log,
+                Level::Debug,
+                "Circuit Breaker call dropped after {:?} before completing; outcome not recorded",
+                elapsed
+            );
+            metrics::increment(&metrics::CANCELLATIONS);
+            events::emit(ResilienceEvent::AttemptCancelled { elapsed });
+        }
+    }
+}
+
+/// A breaker's binding to the `StateStore` it shares its state through.
+struct StoreBinding {
+    handle: StoreHandle,
+    /// The opening time last written to or read from the store, used to detect openings and
+    /// closings made by other breakers.
+    open_since: Option<SystemTime>,
+}
+
+/// The `StateStore` of a breaker and the name it shares its state under, usable without the
+/// breaker's lock.
+#[derive(Clone)]
+struct StoreHandle {
+    name: Arc<str>,
+    store: Arc<dyn StateStore>,
+    log: LogConfig,
+}
+
+impl StoreHandle {
+    /// Runs an operation against the store.
+    ///
+    /// # Returns
+    /// `None` if the operation failed; failures are logged and the breaker keeps using its local
+    /// state.
+    fn run<T>(
+        &self,
+        operation: impl FnOnce(&dyn StateStore, &str) -> Result<T, StoreError>,
+    ) -> Option<T> {
+        match operation(self.store.as_ref(), &self.name) {
+            Ok(value) => Some(value),
+            Err(err) => {
+                log_with!(
+                    self.log,
+                    Level::Warn,
+                    "Circuit Breaker state store failed, using local state: {}",
+                    err
+                );
+                None
+            }
+        }
+    }
+}
+
+/// A write to the `StateStore`, decided under the breaker's lock and performed after releasing it.
+#[derive(Debug, Clone, Copy)]
+enum StoreWrite {
+    RecordFailure,
+    ResetFailures,
+    Open(SystemTime),
+    Close,
+}
+
+/// An event or a diagnostic raised under a breaker's lock, emitted after releasing it.
+///
+/// `on_event` listeners and `Diagnostics` hooks are user code that may call back into the
+/// breaker, e.g. to read its state when it opens, which would deadlock under the lock.
+enum Notice {
+    Event(ResilienceEvent),
+    Log(Level, String),
+}
+
+/// The work a `BreakerCore` queued under its lock, taken by `flush`.
+struct Pending {
+    log: LogConfig,
+    notices: Vec<Notice>,
+    writes: Option<(StoreHandle, Vec<StoreWrite>)>,
+}
+
+/// Logs a message of a `BreakerCore` like `log_with!`, deferring it to after the lock is
+/// released when it goes to a `Diagnostics` hook.
+macro_rules! log_deferred {
+    ($core:expr, $level:expr, $($arg:tt)+) => {{
+        let log = $core.config.log;
+        if log.diagnostics.is_none() {
+            log_with!(log, $level, $($arg)+);
+        } else if !log.quiet {
+            let message = format!($($arg)+);
+            $core.notices.push(Notice::Log($level, message));
+        }
+    }};
+}
+
+/// Locks a core.
+///
+/// The lock is never held while user code or store I/O runs, so a poisoned lock can only come
+/// from a panic inside the breaker itself; its state is still consistent and is used as is.
+pub(crate) fn lock<E>(core: &Mutex<BreakerCore<E>>) -> MutexGuard<'_, BreakerCore<E>> {
+    core.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Runs `f` on the locked `core`, then emits the events and diagnostics it raised and performs
+/// its store writes without holding the lock.
+pub(crate) fn update<E, R>(
+    core: &Mutex<BreakerCore<E>>,
+    f: impl FnOnce(&mut BreakerCore<E>) -> R,
+) -> R {
+    let result = f(&mut lock(core));
+    flush(core);
+    result
+}
+
+/// Decides whether a call may run through `core`; see `BreakerCore::acquire`.
+///
+/// The shared state is loaded from the `StateStore` before taking the lock.
+pub(crate) fn acquire<E>(
+    core: &Mutex<BreakerCore<E>>,
+) -> Result<CallPermit, CircuitBreakerError<E>> {
+    let handle = lock(core).store_to_sync();
+    let shared = handle.and_then(|handle| handle.run(|store, name| store.load(name)));
+    update(core, |core| core.acquire(shared))
+}
+
+/// Updates `core` with the outcome of an admitted call; see `BreakerCore::complete`.
+///
+/// The resulting store writes are performed after releasing the lock.
+pub(crate) fn complete<E: fmt::Display, T>(
+    core: &Mutex<BreakerCore<E>>,
+    generation: Generation,
+    result: Result<T, E>,
+    elapsed: Duration,
+) -> Result<T, CircuitBreakerError<E>> {
+    update(core, |core| core.complete(generation, result, elapsed))
+}
+
+/// Records the outcome of a health probe into `core`; see `BreakerCore::record_probe`.
+pub(crate) fn record_probe<E>(core: &Mutex<BreakerCore<E>>, healthy: bool) {
+    update(core, |core| core.record_probe(healthy))
+}
+
+/// Emits the notices and performs the store writes pending in `core` without holding its lock.
+///
+/// A shared failure count reaching the threshold trips the breaker, which raises more notices
+/// and queues another write, so the pending work is drained until none is left.
+fn flush<E>(core: &Mutex<BreakerCore<E>>) {
+    loop {
+        let Pending {
+            log,
+            notices,
+            writes,
+        } = lock(core).take_pending();
+        if notices.is_empty() && writes.is_none() {
+            return;
+        }
+        for notice in notices {
+            match notice {
+                Notice::Event(event) => events::emit(event),
+                Notice::Log(level, message) => log_with!(log, level, "{}", message),
+            }
+        }
+        let Some((handle, writes)) = writes else {
+            continue;
+        };
+        for write in writes {
+            match write {
+                StoreWrite::RecordFailure => {
+                    if let Some(count) = handle.run(|store, name| store.record_failure(name)) {
+                        lock(core).adopt_failure_count(count);
+                    }
+                }
+                StoreWrite::ResetFailures => {
+                    handle.run(|store, name| store.reset_failures(name));
+                }
+                StoreWrite::Open(at) => {
+                    if handle.run(|store, name| store.open(name, at)).is_some() {
+                        lock(core).shared_open_written(Some(at));
+                    }
+                }
+                StoreWrite::Close => {
+                    if handle.run(|store, name| store.close(name)).is_some() {
+                        lock(core).shared_open_written(None);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl<E> BreakerCore<E> {
+    /// Creates a core in the `Close` state.
+    pub(crate) fn new(config: CircuitBreakerConfig) -> Self {
+        BreakerCore {
+            config,
+            state: CircuitBreakerState::Close,
+            failure_count: 0,
+            outcomes: OutcomeWindow::default(),
+            history: CallHistory::default(),
+            success_count: 0,
+            last_failure_time: None,
+            classifier: None,
+            record_failure_if: None,
+            stats: None,
+            shared_config: None,
+            store: None,
+            pending: Vec::new(),
+            notices: Vec::new(),
+            subscribers: Vec::new(),
+            reopens: 0,
+            closed_since: None,
+            in_flight: Arc::new(AtomicUsize::new(0)),
+            generation: 0,
+        }
+    }
+
+    /// Follows a hot-reloadable configuration, starting with its current value.
+    pub(crate) fn set_shared_config(&mut self, config: SharedCircuitBreakerConfig) {
+        self.config = config.load();
+        self.shared_config = Some(config);
+    }
+
+    /// Shares the breaker's open state and failure count through `store` under `name`.
+    pub(crate) fn set_state_store(&mut self, name: String, store: Arc<dyn StateStore>) {
+        self.store = Some(StoreBinding {
+            handle: StoreHandle {
+                name: name.into(),
+                store,
+                log: self.config.log,
+            },
+            open_since: None,
+        });
+    }
+
+    /// Returns the store to load the shared state from before the next `acquire`, if any.
+    ///
+    /// `Disabled` and `ForcedOpen` breakers ignore the shared state.
+    fn store_to_sync(&self) -> Option<StoreHandle> {
+        match self.state {
+            CircuitBreakerState::Disabled | CircuitBreakerState::ForcedOpen => None,
+            _ => self.store.as_ref().map(|binding| binding.handle.clone()),
+        }
+    }
+
+    /// Queues a write to the store, if one is configured.
+    fn write_store(&mut self, write: StoreWrite) {
+        if self.store.is_some() {
+            self.pending.push(write);
+        }
+    }
+
+    /// Takes the notices and the store writes queued since the last call.
+    fn take_pending(&mut self) -> Pending {
+        let writes = Some(std::mem::take(&mut self.pending))
+            .filter(|writes| !writes.is_empty())
+            .zip(self.store.as_ref())
+            .map(|(writes, binding)| (binding.handle.clone(), writes));
+        Pending {
+            log: self.config.log,
+            notices: std::mem::take(&mut self.notices),
+            writes,
+        }
+    }
+
+    /// Queues an event, emitted once the lock is released.
+    fn notify(&mut self, event: ResilienceEvent) {
+        self.notices.push(Notice::Event(event));
+    }
+
+    /// Adopts the consecutive failure count returned by the store, which includes the failures of
+    /// every breaker sharing it, and opens the circuit if it reaches the threshold.
+    fn adopt_failure_count(&mut self, count: usize) {
+        if self.state != CircuitBreakerState::Close || count <= self.failure_count {
+            return;
+        }
+        self.failure_count = count;
+        if self.failure_count >= self.config.failure_threshold {
+            self.trip();
+        }
+    }
+
+    /// Remembers the opening time written to the store, unless the state moved on meanwhile.
+    fn shared_open_written(&mut self, open_since: Option<SystemTime>) {
+        let expected = match open_since {
+            Some(_) => CircuitBreakerState::Open,
+            None => CircuitBreakerState::Close,
+        };
+        if self.state == expected
+            && let Some(binding) = &mut self.store
+        {
+            binding.open_since = open_since;
+        }
+    }
+
+    /// Decides whether a call may run.
+    ///
+    /// Reloads the shared configuration, adopts the `shared` state loaded from the store, moves an
+    /// `Open` breaker whose cooldown has elapsed to `HalfOpen`, enforces `max_concurrent_calls`,
+    /// and records the rejection otherwise.
+    ///
+    /// # Returns
+    /// - `Ok(permit)` if the operation may run; the call counts as running until `permit` is
+    ///   dropped, and is reported as cancelled unless `permit` is finished first.
+    /// - `Err(CircuitBreakerError::Open { retry_after })` with the remaining cooldown if the circuit
+    ///   is open.
+    /// - `Err(CircuitBreakerError::Saturated { .. })` if the concurrency cap is reached.
+    fn acquire(
+        &mut self,
+        shared: Option<SharedBreakerState>,
+    ) -> Result<CallPermit, CircuitBreakerError<E>> {
+        if let Some(shared) = &self.shared_config {
+            self.config = shared.load();
+        }
+        match self.state {
+            CircuitBreakerState::Disabled => return Ok(self.permit()),
+            CircuitBreakerState::ForcedOpen => {
+                log_deferred!(
+                    self,
+                    self.config.log.level,
+                    "Circuit Breaker is forced open.. Requests are blocked until it is reset"
+                );
+                self.reject();
+                return Err(CircuitBreakerError::Open {
+                    retry_after: self.config.cooldown_period,
+                });
+            }
+            _ => {}
+        }
+        if let Some(shared) = shared {
+            self.sync_from_store(shared);
+        }
+        self.half_open_if_elapsed();
+        if self.state == CircuitBreakerState::Open
+            && let Some(last_failure_time) = self.last_failure_time
+        {
+            log_deferred!(
+                self,
+                self.config.log.level,
+                "Circuit Breaker is open.. Requests are blocked for now"
+            );
+            self.reject();
+            return Err(CircuitBreakerError::Open {
+                retry_after: self
+                    .cooldown()
+                    .saturating_sub(time::elapsed(last_failure_time)),
+            });
+        }
+        if let Some(max_concurrent_calls) = self.config.max_concurrent_calls
+            && self.in_flight.load(Ordering::Acquire) >= max_concurrent_calls
+        {
+            log_deferred!(
+                self,
+                self.config.log.level,
+                "Circuit Breaker is at its concurrency limit.. Request is blocked"
+            );
+            self.reject();
+            return Err(CircuitBreakerError::Saturated {
+                max_concurrent_calls,
+            });
+        }
+        Ok(self.permit())
+    }
+
+    /// Counts a call as running until the returned permit is dropped.
+    fn permit(&self) -> CallPermit {
+        self.in_flight.fetch_add(1, Ordering::AcqRel);
+        CallPermit {
+            in_flight: Arc::clone(&self.in_flight),
+            started: time::now(),
+            log: self.config.log,
+            finished: false,
+            generation: Generation(self.generation),
+        }
+    }
+
+    /// Moves an `Open` breaker whose cooldown has elapsed to `HalfOpen`.
+    pub(crate) fn half_open_if_elapsed(&mut self) {
+        if self.state == CircuitBreakerState::Open
+            && let Some(last_failure_time) = self.last_failure_time
+            && time::elapsed(last_failure_time) >= self.cooldown()
+        {
+            self.half_open();
+        }
+    }
+
+    /// Records the outcome of a health probe of the protected dependency.
+    ///
+    /// A failed probe opens a `Close` or `HalfOpen` circuit, and a successful one moves an `Open`
+    /// circuit to `HalfOpen` without waiting for the cooldown, so that the next calls can close
+    /// it. `Disabled` and `ForcedOpen` breakers are left alone.
+    fn record_probe(&mut self, healthy: bool) {
+        match (self.state, healthy) {
+            (CircuitBreakerState::Close | CircuitBreakerState::HalfOpen, false) => self.trip(),
+            (CircuitBreakerState::Open, true) => self.half_open(),
+            _ => {}
+        }
+    }
+
+    fn half_open(&mut self) {
+        self.transition(CircuitBreakerState::HalfOpen);
+        self.success_count = 0;
+        self.notify(ResilienceEvent::BreakerHalfOpened);
+        log_deferred!(
+            self,
+            Level::Warn,
+            "Circuit Breaker transitioning to Half Open State"
+        );
+    }
+
+    /// Returns a stream of the breaker's future state transitions.
+    pub(crate) fn subscribe(&mut self) -> Receiver<TransitionEvent> {
+        let (sender, receiver) = channel::unbounded();
+        self.subscribers.push(sender);
+        receiver
+    }
+
+    /// Moves to `to`, notifying subscribers if the state changes.
+    ///
+    /// Starts a new generation, so outcomes of calls admitted before are not applied.
+    fn transition(&mut self, to: CircuitBreakerState) {
+        let from = self.state;
+        self.state = to;
+        self.generation = self.generation.wrapping_add(1);
+        if from == to || self.subscribers.is_empty() {
+            return;
+        }
+        let event = TransitionEvent {
+            from,
+            to,
+            at: time::now(),
+        };
+        self.subscribers
+            .retain(|subscriber| subscriber.try_send(event).is_ok());
+    }
+
+    /// Records a rejected call.
+    fn reject(&mut self) {
+        self.record(Stats::record_rejection);
+        metrics::increment(&metrics::BREAKER_REJECTIONS);
+        self.notify(ResilienceEvent::CallRejected);
+    }
+
+    /// Moves to `ForcedOpen`, rejecting every call until `reset`.
+    pub(crate) fn force_open(&mut self) {
+        self.transition(CircuitBreakerState::ForcedOpen);
+        self.notify(ResilienceEvent::BreakerForcedOpen);
+        log_deferred!(self, Level::Warn, "Circuit Breaker forced open");
+    }
+
+    /// Moves to `Disabled`, letting every call through without recording it until `reset`.
+    pub(crate) fn disable(&mut self) {
+        self.transition(CircuitBreakerState::Disabled);
+        self.notify(ResilienceEvent::BreakerDisabled);
+        log_deferred!(self, Level::Warn, "Circuit Breaker disabled");
+    }
+
+    /// Moves to `Close` and forgets every recorded failure.
+    pub(crate) fn reset(&mut self) {
+        self.transition(CircuitBreakerState::Close);
+        self.failure_count = 0;
+        self.success_count = 0;
+        self.outcomes.clear();
+        self.last_failure_time = None;
+        self.reopens = 0;
+        self.closed_since = None;
+        self.notify(ResilienceEvent::BreakerClosed);
+        log_deferred!(self, Level::Debug, "Circuit Breaker reset");
+    }
+
+    /// Updates the state machine with the outcome of an admitted call.
+    ///
+    /// # Arguments
+    /// * `generation` - The generation returned by finishing the call's permit.
+    /// * `result` - The result of the operation.
+    /// * `elapsed` - How long the operation ran, recorded as the attempt's latency.
+    ///
+    /// Outcomes of calls completing while the breaker is `Disabled` or `ForcedOpen`, or of calls
+    /// admitted before the last transition, do not move the state machine.
+    fn complete<T>(
+        &mut self,
+        generation: Generation,
+        result: Result<T, E>,
+        elapsed: Duration,
+    ) -> Result<T, CircuitBreakerError<E>>
+    where
+        E: fmt::Display,
+    {
+        self.record(|stats| stats.record_attempt(elapsed));
+        self.history
+            .record(self.config.metrics_window_size, result.is_ok(), elapsed);
+        if matches!(
+            self.state,
+            CircuitBreakerState::Disabled | CircuitBreakerState::ForcedOpen
+        ) {
+            return result.map_err(CircuitBreakerError::Inner);
+        }
+        if generation != Generation(self.generation) {
+            log_deferred!(
+                self,
+                Level::Debug,
+                "Circuit Breaker call admitted before the last transition; outcome not recorded"
+            );
+            return result.map_err(CircuitBreakerError::Inner);
+        }
+        match result {
+            Ok(result) => {
+                log_deferred!(self, Level::Debug, "Request Success response");
+                self.record(|stats| stats.record_success(false));
+                self.on_success();
+                Ok(result)
+            }
+            Err(err) => {
+                log_deferred!(self, self.config.log.level, "Failed with {}", err);
+                if let Some(predicate) = &self.record_failure_if
+                    && !predicate(&err)
+                {
+                    log_deferred!(
+                        self,
+                        Level::Debug,
+                        "Error not recorded as a failure by record_failure_if"
+                    );
+                    return Err(CircuitBreakerError::Inner(err));
+                }
+                let class = self
+                    .classifier
+                    .as_ref()
+                    .map_or(ErrorClass::Transient, |c| c.classify(&err));
+                if class == ErrorClass::Fatal {
+                    self.trip();
+                } else {
+                    self.on_failure();
+                }
+                Err(CircuitBreakerError::Inner(err))
+            }
+        }
+    }
+
+    /// Logs and counts a fallback served instead of a rejected call.
+    pub(crate) fn fallback_used(&mut self) {
+        log_deferred!(
+            self,
+            self.config.log.level,
+            "Circuit Breaker rejected the call; executing fallback."
+        );
+        metrics::increment(&metrics::FALLBACKS);
+        self.notify(ResilienceEvent::FallbackUsed);
+    }
+
+    pub(crate) fn state(&self) -> CircuitBreakerState {
+        self.state
+    }
+
+    pub(crate) fn failure_count(&self) -> usize {
+        self.failure_count
+    }
+
+    pub(crate) fn success_count(&self) -> usize {
+        self.success_count
+    }
+
+    pub(crate) fn time_until_half_open(&self) -> Option<Duration> {
+        match (self.state, self.last_failure_time) {
+            (CircuitBreakerState::Open, Some(last_failure_time)) => Some(
+                self.cooldown()
+                    .saturating_sub(time::elapsed(last_failure_time)),
+            ),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn metrics(&self) -> CircuitBreakerMetrics {
+        let (window_calls, window_failures) = self.outcomes.totals(&self.config.window);
+        CircuitBreakerMetrics {
+            state: self.state,
+            failure_count: self.failure_count,
+            success_count: self.success_count,
+            window_calls,
+            failure_rate: (window_calls > 0)
+                .then(|| window_failures as f64 * 100.0 / window_calls as f64),
+            time_until_half_open: self.time_until_half_open(),
+            success_rate: self.history.success_rate(),
+            mean_latency: self.history.mean_latency(),
+        }
+    }
+
+    pub(crate) fn latency_percentile(&self, percentile: f64) -> Option<Duration> {
+        self.history.latency_percentile(percentile)
+    }
+
+    /// Handles a successful operation outcome.
+    ///
+    /// Updates the circuit breaker state based on a successful operation:
+    /// - In `HalfOpen`, increments `success_count` and transitions to `Close` if the success threshold is met.
+    /// - In `Close`, resets `failure_count` to 0.
+    /// - In `Open`, does nothing (this method is typically called only after `call`).
+    fn on_success(&mut self) {
+        match self.state {
+            CircuitBreakerState::HalfOpen => {
+                self.success_count += 1;
+                if self.success_count >= self.config.success_threshold {
+                    self.transition(CircuitBreakerState::Close);
+                    self.failure_count = 0;
+                    self.closed_since = Some(time::now());
+                    self.write_store(StoreWrite::Close);
+                    self.notify(ResilienceEvent::BreakerClosed);
+                    log_deferred!(
+                        self,
+                        Level::Debug,
+                        "Circuit breaker transitioning to closed state"
+                    );
+                }
+            }
+            _ => {
+                if self.failure_count > 0 {
+                    self.write_store(StoreWrite::ResetFailures);
+                }
+                self.failure_count = 0;
+                self.outcomes.record(&self.config.window, false);
+            }
+        }
+    }
+
+    /// Handles a failed operation outcome.
+    ///
+    /// Updates the circuit breaker state based on a failed operation:
+    /// - In `HalfOpen`, reopens the circuit immediately, whatever the failure count.
+    /// - With `FailureWindow::Consecutive`, increments `failure_count` and transitions to `Open`
+    ///   once it reaches the threshold. The failure is also counted in the store, whose count
+    ///   is adopted once written.
+    /// - With a sliding window, records the failure and transitions to `Open` once the failure rate
+    ///   reaches the window's threshold.
+    fn on_failure(&mut self) {
+        if self.state == CircuitBreakerState::HalfOpen {
+            self.trip();
+            return;
+        }
+        if self.config.window == FailureWindow::Consecutive {
+            self.failure_count += 1;
+            self.write_store(StoreWrite::RecordFailure);
+            if self.failure_count >= self.config.failure_threshold {
+                self.trip();
+            }
+            return;
+        }
+        self.outcomes.record(&self.config.window, true);
+        if self.outcomes.exceeds(&self.config.window) {
+            self.trip();
+        }
+    }
+
+    /// Opens the circuit and records the failure time used to enforce the cooldown period.
+    fn trip(&mut self) {
+        self.escalate_cooldown();
+        self.transition(CircuitBreakerState::Open);
+        self.outcomes.clear();
+        self.last_failure_time = Some(time::now());
+        self.write_store(StoreWrite::Open(time::system_now()));
+        self.record(Stats::record_breaker_open);
+        metrics::increment(&metrics::BREAKER_OPENS);
+        self.notify(ResilienceEvent::BreakerOpened);
+        log_deferred!(
+            self,
+            Level::Error,
+            "Circuit Breaker transitioning to open state"
+        );
+    }
+
+    /// Counts a re-opening towards the `CooldownEscalation`, if one is configured.
+    ///
+    /// An opening less than `reset_after` after the circuit last closed, or while `HalfOpen`,
+    /// lengthens the next cooldown; an opening after a sustained closed period starts over.
+    fn escalate_cooldown(&mut self) {
+        let Some(escalation) = self.config.cooldown_escalation else {
+            return;
+        };
+        let recently_closed = self.state == CircuitBreakerState::HalfOpen
+            || self
+                .closed_since
+                .is_some_and(|closed_since| time::elapsed(closed_since) < escalation.reset_after);
+        self.reopens = if recently_closed {
+            self.reopens.saturating_add(1)
+        } else {
+            0
+        };
+    }
+
+    /// Returns the cooldown of the current opening, escalated if the circuit keeps re-opening.
+    fn cooldown(&self) -> Duration {
+        self.config
+            .cooldown_escalation
+            .map_or(self.config.cooldown_period, |escalation| {
+                escalation.cooldown(self.config.cooldown_period, self.reopens)
+            })
+    }
+
+    /// Adopts the state shared through the `StateStore`.
+    ///
+    /// An opening or closing made by another breaker is applied locally; the cooldown of an
+    /// adopted opening runs from the time it was opened. With `FailureWindow::Consecutive`, the
+    /// failure count is replaced by the shared count.
+    fn sync_from_store(&mut self, shared: SharedBreakerState) {
+        if let Some(binding) = &mut self.store
+            && binding.open_since != shared.open_since
+        {
+            binding.open_since = shared.open_since;
+            match shared.open_since {
+                Some(open_since) => {
+                    let elapsed = time::system_now()
+                        .duration_since(open_since)
+                        .unwrap_or_default();
+                    self.last_failure_time =
+                        Some(time::now().checked_sub(elapsed).unwrap_or_else(time::now));
+                    // An `Open` breaker may be reading back its own opening before the write
+                    // was acknowledged; only the cooldown start is adopted then.
+                    if self.state != CircuitBreakerState::Open {
+                        self.transition(CircuitBreakerState::Open);
+                        self.outcomes.clear();
+                        self.notify(ResilienceEvent::BreakerOpened);
+                        log_deferred!(
+                            self,
+                            Level::Warn,
+                            "Circuit Breaker opened by another instance"
+                        );
+                    }
+                }
+                None if self.state != CircuitBreakerState::Close => {
+                    self.transition(CircuitBreakerState::Close);
+                    self.closed_since = Some(time::now());
+                    self.notify(ResilienceEvent::BreakerClosed);
+                    log_deferred!(
+                        self,
+                        Level::Debug,
+                        "Circuit Breaker closed by another instance"
+                    );
+                }
+                None => {}
+            }
+        }
+        if self.config.window == FailureWindow::Consecutive {
+            self.failure_count = shared.failure_count;
+        }
+    }
+
+    /// Records into the statistics handle, if one is configured.
+    fn record(&self, record: impl FnOnce(&Stats)) {
+        if let Some(stats) = &self.stats {
+            record(stats);
+        }
+    }
+}
@@ -0,0 +1,68 @@
+use crate::config::LogConfig;
+use crate::events::{self, ResilienceEvent};
+use crate::logging::log_with;
+use crate::metrics;
+use std::error::Error;
+use std::fmt;
+
+/// The error returned by `Bulkhead::call`.
+///
+/// It tells a call rejected by a full bulkhead apart from a failure of the operation itself, so
+/// callers can shed load or fall back without inspecting the operation's error.
+///
+/// # Examples
+/// ```rust
+/// use resilient_rs::config::BulkheadConfig;
+/// use resilient_rs::synchronous::{Bulkhead, BulkheadError};
+///
+/// let bulkhead = Bulkhead::new(BulkheadConfig::new(1));
+/// let nested = bulkhead.call(|| Ok::<_, &str>(bulkhead.call(|| Ok::<_, &str>(()))));
+/// assert!(matches!(nested, Ok(Err(BulkheadError::Full))));
+/// ```
+#[derive(Debug, PartialEq)]
+pub enum BulkheadError<E> {
+    /// The call was rejected without running the operation because `max_concurrent_calls`
+    /// operations were running and no slot freed up within `max_wait`.
+    Full,
+    /// The operation ran and failed with this error.
+    Inner(E),
+}
+
+impl<E> BulkheadError<E> {
+    /// Returns `true` if the call was rejected because the bulkhead is full.
+    pub fn is_full(&self) -> bool {
+        matches!(self, BulkheadError::Full)
+    }
+
+    /// Returns the error of the operation, or `None` if the call was rejected.
+    pub fn into_inner(self) -> Option<E> {
+        match self {
+            BulkheadError::Inner(err) => Some(err),
+            BulkheadError::Full => None,
+        }
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for BulkheadError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BulkheadError::Full => write!(f, "Bulkhead is full. Please try later..!"),
+            BulkheadError::Inner(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> Error for BulkheadError<E> {}
+
+/// Logs and counts a call rejected because `max_concurrent_calls` calls were running.
+pub(crate) fn rejected<E>(log: &LogConfig, max_concurrent_calls: usize) -> BulkheadError<E> {
+    log_with!(
+        *log,
+        log.level,
+        "Bulkhead is full ({} concurrent calls).. Request is blocked",
+        max_concurrent_calls
+    );
+    metrics::increment(&metrics::BULKHEAD_REJECTIONS);
+    events::emit(ResilienceEvent::BulkheadRejected);
+    BulkheadError::Full
+}
@@ -0,0 +1,301 @@
+use crate::config::CacheConfig;
+use crate::events::{self, ResilienceEvent};
+use crate::logging::log_with;
+use crate::metrics;
+use crate::ratelimit::{Clock, SystemClock};
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Where the value returned by a `ResultCache` came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CacheSource {
+    /// The value was cached less than `ttl` ago; the operation was not called.
+    Hit,
+    /// The operation was called and succeeded; its result is now cached.
+    Fetched,
+    /// The operation failed and a result cached `age` ago was served instead.
+    Stale { age: Duration },
+}
+
+/// A value returned by a `ResultCache`, along with where it came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cached<V> {
+    pub value: V,
+    pub source: CacheSource,
+}
+
+impl<V> Cached<V> {
+    /// Returns `true` if the value is a stale result served because the operation failed, so the
+    /// caller can flag the response as degraded.
+    pub fn is_stale(&self) -> bool {
+        matches!(self.source, CacheSource::Stale { .. })
+    }
+
+    /// Returns the value, discarding where it came from.
+    pub fn into_value(self) -> V {
+        self.value
+    }
+}
+
+/// A cached result and the instant it was stored.
+struct Entry<V> {
+    value: V,
+    stored: Instant,
+}
+
+/// A cache of successful results, served as a degraded response when the operation fails.
+///
+/// `get_or_fetch` returns the cached result of a key while it is younger than `ttl`, without
+/// calling the operation. Once it expires, the operation is called again:
+/// - If it succeeds, its result replaces the cached one.
+/// - If it fails, the expired result is served instead, as long as it is not older than
+///   `ttl + max_stale`, and the error is returned only when there is nothing to serve.
+///
+/// Wrapping a `CircuitBreaker` call makes the cache serve the last known result while the circuit
+/// is open, since the breaker's rejection is just another error.
+///
+/// Entries are evicted lazily, when a lookup finds them too old to be served; call
+/// `evict_expired` periodically when keys are not looked up again. The lock is never held while
+/// the operation runs, so concurrent misses on the same key all call the operation.
+///
+/// # Example
+/// ```
+/// use std::thread::sleep;
+/// use std::time::Duration;
+/// use resilient_rs::cache::ResultCache;
+/// use resilient_rs::config::{CacheConfig, CircuitBreakerConfig};
+/// use resilient_rs::synchronous::CircuitBreaker;
+///
+/// let cache = ResultCache::new(CacheConfig::new(Duration::from_millis(10)));
+/// let breaker = CircuitBreaker::<&str>::with_config(CircuitBreakerConfig::new(1, 1, Duration::from_secs(30)));
+///
+/// let rate = cache.get_or_fetch("EURUSD", || breaker.run(|| Ok(1.08))).unwrap();
+/// assert!(!rate.is_stale());
+///
+/// // Once the rate expires, the feed fails and opens the breaker: the last rate is served.
+/// sleep(Duration::from_millis(20));
+/// let rate = cache.get_or_fetch("EURUSD", || breaker.run(|| Err("feed down"))).unwrap();
+/// assert!(rate.is_stale());
+/// let rate = cache.get_or_fetch("EURUSD", || breaker.run(|| Ok(1.09))).unwrap();
+/// assert_eq!((rate.value, rate.is_stale()), (1.08, true));
+/// ```
+pub struct ResultCache<K, V> {
+    config: CacheConfig,
+    clock: Arc<dyn Clock>,
+    entries: Mutex<HashMap<K, Entry<V>>>,
+}
+
+impl<K, V> ResultCache<K, V>
+where
+    K: Eq + Hash,
+    V: Clone,
+{
+    /// Creates an empty cache.
+    ///
+    /// # Arguments
+    /// * `config` - The time-to-live and staleness bound of the cached results.
+    pub fn new(config: CacheConfig) -> Self {
+        ResultCache {
+            config,
+            clock: Arc::new(SystemClock),
+            entries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Reads the time from `clock` instead of the system clock, and returns the modified cache.
+    ///
+    /// # Arguments
+    /// * `clock` - The clock timestamping the results, typically a manual clock in tests.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Returns the result cached for `key`, calling a blocking `operation` if it has expired.
+    ///
+    /// # Arguments
+    /// * `key` - The key the result is cached under.
+    /// * `operation` - The operation producing the result, called only on a miss.
+    ///
+    /// # Returns
+    /// - `Ok(Cached)` with a fresh, fetched or stale value, see `CacheSource`.
+    /// - `Err(E)` if the operation fails and no result young enough is cached.
+    pub fn get_or_fetch<F, E>(&self, key: K, operation: F) -> Result<Cached<V>, E>
+    where
+        F: FnOnce() -> Result<V, E>,
+    {
+        if let Some(value) = self.fresh(&key) {
+            return Ok(Cached {
+                value,
+                source: CacheSource::Hit,
+            });
+        }
+        self.store(key, operation())
+    }
+
+    /// Returns the result cached for `key`, calling an asynchronous `operation` if it has expired.
+    ///
+    /// # Returns
+    /// The same as `get_or_fetch`.
+    pub async fn get_or_fetch_async<F, Fut, E>(&self, key: K, operation: F) -> Result<Cached<V>, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<V, E>>,
+    {
+        if let Some(value) = self.fresh(&key) {
+            return Ok(Cached {
+                value,
+                source: CacheSource::Hit,
+            });
+        }
+        self.store(key, operation().await)
+    }
+
+    /// Removes the result cached for `key`, so the next lookup calls the operation and nothing
+    /// stale can be served for it.
+    pub fn invalidate(&self, key: &K) {
+        self.entries().remove(key);
+    }
+
+    /// Removes every result too old to be served, even as a stale response.
+    ///
+    /// With `max_stale` set to `None`, results are kept until they are replaced or invalidated.
+    pub fn evict_expired(&self) {
+        let now = self.clock.now();
+        self.entries()
+            .retain(|_, entry| self.servable(now.saturating_duration_since(entry.stored)));
+    }
+
+    /// Returns the number of cached results, including expired ones not yet evicted.
+    pub fn len(&self) -> usize {
+        self.entries().len()
+    }
+
+    /// Returns `true` if no result is cached.
+    pub fn is_empty(&self) -> bool {
+        self.entries().is_empty()
+    }
+
+    /// Returns the value cached for `key` if it is younger than `ttl`.
+    fn fresh(&self, key: &K) -> Option<V> {
+        let now = self.clock.now();
+        let entries = self.entries();
+        let entry = entries.get(key)?;
+        (now.saturating_duration_since(entry.stored) < self.config.ttl).then(|| entry.value.clone())
+    }
+
+    /// Caches a successful result, or serves a stale one in place of an error.
+    fn store<E>(&self, key: K, result: Result<V, E>) -> Result<Cached<V>, E> {
+        let now = self.clock.now();
+        let mut entries = self.entries();
+        let err = match result {
+            Ok(value) => {
+                let entry = Entry {
+                    value: value.clone(),
+                    stored: now,
+                };
+                entries.insert(key, entry);
+                return Ok(Cached {
+                    value,
+                    source: CacheSource::Fetched,
+                });
+            }
+            Err(err) => err,
+        };
+
+        let Some(entry) = entries.get(&key) else {
+            return Err(err);
+        };
+        let age = now.saturating_duration_since(entry.stored);
+        if !self.servable(age) {
+            entries.remove(&key);
+            return Err(err);
+        }
+        log_with!(
+            self.config.log,
+            self.config.log.level,
+            "Operation failed; serving a cached result stored {:?} ago",
+            age
+        );
+        metrics::increment(&metrics::STALE_RESPONSES);
+        events::emit(ResilienceEvent::StaleServed { age });
+        Ok(Cached {
+            value: entry.value.clone(),
+            source: CacheSource::Stale { age },
+        })
+    }
+
+    /// Returns `true` if a result stored `age` ago may still be served as a stale response.
+    fn servable(&self, age: Duration) -> bool {
+        self.config
+            .max_stale
+            .is_none_or(|max_stale| age <= self.config.ttl.saturating_add(max_stale))
+    }
+
+    fn entries(&self) -> MutexGuard<'_, HashMap<K, Entry<V>>> {
+        self.entries.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::VirtualClock;
+
+    #[test]
+    fn test_serves_stale_result_within_max_stale() {
+        let clock = Arc::new(VirtualClock::new());
+        let config =
+            CacheConfig::new(Duration::from_secs(10)).with_max_stale(Duration::from_secs(20));
+        let cache = ResultCache::new(config).with_clock(clock.clone());
+
+        let fetched = cache.get_or_fetch("user:1", || Ok::<_, &str>("alice"));
+        assert_eq!(fetched.unwrap().source, CacheSource::Fetched);
+
+        // Within the TTL the operation is not called.
+        clock.advance(Duration::from_secs(5));
+        let hit = cache.get_or_fetch("user:1", || Err("not called"));
+        assert_eq!(hit.unwrap().source, CacheSource::Hit);
+
+        clock.advance(Duration::from_secs(10));
+        let stale = cache.get_or_fetch("user:1", || Err("down")).unwrap();
+        assert_eq!(
+            stale,
+            Cached {
+                value: "alice",
+                source: CacheSource::Stale {
+                    age: Duration::from_secs(15)
+                }
+            }
+        );
+
+        // Past `ttl + max_stale`, the error surfaces and the entry is evicted.
+        clock.advance(Duration::from_secs(16));
+        assert_eq!(cache.get_or_fetch("user:1", || Err("down")), Err("down"));
+        assert!(cache.is_empty());
+        assert_eq!(cache.get_or_fetch("user:2", || Err("down")), Err("down"));
+    }
+
+    #[test]
+    fn test_get_or_fetch_async_refreshes_expired_result() {
+        let clock = Arc::new(VirtualClock::new());
+        let cache =
+            ResultCache::new(CacheConfig::new(Duration::from_secs(1))).with_clock(clock.clone());
+
+        async_std::task::block_on(async {
+            let first = cache.get_or_fetch_async(1, || async { Ok::<_, &str>(10) });
+            assert_eq!(first.await.unwrap().value, 10);
+
+            clock.advance(Duration::from_secs(1));
+            let refreshed = cache.get_or_fetch_async(1, || async { Ok::<_, &str>(11) });
+            assert_eq!(refreshed.await.unwrap().source, CacheSource::Fetched);
+            let hit = cache.get_or_fetch_async(1, || async { Err("not called") });
+            assert_eq!(hit.await.unwrap().value, 11);
+        });
+
+        cache.invalidate(&1);
+        assert!(cache.is_empty());
+    }
+}
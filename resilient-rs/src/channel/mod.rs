@@ -0,0 +1,264 @@
+use crate::strategies::RetryStrategy;
+use crate::time;
+use crate::{asynchronous, synchronous};
+use std::fmt;
+use std::sync::mpsc::{self, SyncSender};
+use std::time::{Duration, Instant};
+
+/// The error returned when a channel stayed full until a `ResilientSender` gave up.
+///
+/// The item that could not be sent is handed back, so the caller can shed it, spill it to disk,
+/// or report the overload upstream.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelOverloaded<T> {
+    /// The item that could not be sent.
+    pub item: T,
+    /// The number of times sending was attempted.
+    pub attempts: usize,
+    /// The time spent waiting for room in the channel.
+    pub waited: Duration,
+}
+
+impl<T> fmt::Display for ChannelOverloaded<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "channel overloaded: still full after {} attempts over {:?}",
+            self.attempts, self.waited
+        )
+    }
+}
+
+impl<T: fmt::Debug> std::error::Error for ChannelOverloaded<T> {}
+
+/// The error returned by the `send` methods of a `ResilientSender`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChannelSendError<T> {
+    /// The channel stayed full until the deadline.
+    Overloaded(ChannelOverloaded<T>),
+    /// The receiving side of the channel was dropped; retrying cannot succeed.
+    Disconnected(T),
+}
+
+impl<T> ChannelSendError<T> {
+    /// Returns the item that could not be sent.
+    pub fn into_inner(self) -> T {
+        match self {
+            ChannelSendError::Overloaded(overloaded) => overloaded.item,
+            ChannelSendError::Disconnected(item) => item,
+        }
+    }
+}
+
+impl<T> fmt::Display for ChannelSendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChannelSendError::Overloaded(overloaded) => overloaded.fmt(f),
+            ChannelSendError::Disconnected(_) => write!(f, "channel disconnected"),
+        }
+    }
+}
+
+impl<T: fmt::Debug> std::error::Error for ChannelSendError<T> {}
+
+impl<T> From<ChannelOverloaded<T>> for ChannelSendError<T> {
+    fn from(overloaded: ChannelOverloaded<T>) -> Self {
+        ChannelSendError::Overloaded(overloaded)
+    }
+}
+
+/// Wraps the sender of a bounded channel so that a send to a full channel backs off and tries
+/// again, instead of failing right away or blocking forever, until an overall deadline.
+///
+/// A full channel means the consumer is momentarily behind. Backing off gives it time to catch
+/// up without the producer spinning, and the deadline bounds how long a producer can be held
+/// up: past it, the send fails with `ChannelOverloaded`, handing the item back. The wrapper
+/// supports `std::sync::mpsc::SyncSender`, with a blocking `send`, and
+/// `async_std::channel::Sender`, with an asynchronous `send`.
+///
+/// The delays start at 1ms and grow with `RetryStrategy::ExponentialBackoff`, up to 100ms,
+/// unless configured with `with_backoff` and `with_max_delay`.
+///
+/// # Example
+/// ```
+/// use std::sync::mpsc;
+/// use std::time::Duration;
+/// use resilient_rs::channel::{ChannelSendError, ResilientSender};
+///
+/// let (sender, receiver) = mpsc::sync_channel(1);
+/// let sender = ResilientSender::new(sender, Duration::from_millis(20));
+///
+/// sender.send("first").unwrap();
+/// match sender.send("second") {
+///     Err(ChannelSendError::Overloaded(overloaded)) => assert_eq!(overloaded.item, "second"),
+///     other => panic!("unexpected {:?}", other),
+/// }
+/// assert_eq!(receiver.recv(), Ok("first"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ResilientSender<S> {
+    sender: S,
+    deadline: Duration,
+    delay: Duration,
+    strategy: RetryStrategy,
+    max_delay: Duration,
+}
+
+impl<S> ResilientSender<S> {
+    /// Wraps `sender`, giving up on a send once the channel stayed full for `deadline`.
+    ///
+    /// # Arguments
+    /// * `sender` - The sender of a bounded channel.
+    /// * `deadline` - The longest time a send waits for room in the channel.
+    pub fn new(sender: S, deadline: Duration) -> Self {
+        ResilientSender {
+            sender,
+            deadline,
+            delay: Duration::from_millis(1),
+            strategy: RetryStrategy::ExponentialBackoff,
+            max_delay: Duration::from_millis(100),
+        }
+    }
+
+    /// Builder-style setter for the delay before the first retry and the strategy growing it.
+    pub fn with_backoff(mut self, delay: Duration, strategy: RetryStrategy) -> Self {
+        self.delay = delay;
+        self.strategy = strategy;
+        self
+    }
+
+    /// Builder-style setter for the longest delay between two attempts.
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Returns the wrapped sender.
+    pub fn get_ref(&self) -> &S {
+        &self.sender
+    }
+
+    /// Consumes the `ResilientSender`, returning the wrapped sender.
+    pub fn into_inner(self) -> S {
+        self.sender
+    }
+
+    /// Returns how long to wait before the next attempt, or `None` once the deadline passed.
+    fn backoff(&self, started: Instant, attempts: usize, delay: &mut Duration) -> Option<Duration> {
+        let remaining = self.deadline.checked_sub(time::elapsed(started))?;
+        if remaining.is_zero() {
+            return None;
+        }
+        let wait = (*delay).min(self.max_delay).min(remaining);
+        *delay = self.strategy.calculate_delay(*delay, attempts);
+        Some(wait)
+    }
+}
+
+impl<T> ResilientSender<SyncSender<T>> {
+    /// Sends `item`, blocking with backoff while the channel is full.
+    ///
+    /// # Returns
+    /// - `Ok(())` once the item is in the channel.
+    /// - `Err(ChannelSendError::Overloaded)` if the channel stayed full until the deadline.
+    /// - `Err(ChannelSendError::Disconnected)` if the receiver was dropped.
+    pub fn send(&self, mut item: T) -> Result<(), ChannelSendError<T>> {
+        let started = time::now();
+        let mut delay = self.delay;
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            item = match self.sender.try_send(item) {
+                Ok(()) => return Ok(()),
+                Err(mpsc::TrySendError::Disconnected(item)) => {
+                    return Err(ChannelSendError::Disconnected(item));
+                }
+                Err(mpsc::TrySendError::Full(item)) => item,
+            };
+            let Some(wait) = self.backoff(started, attempts, &mut delay) else {
+                return Err(overloaded(item, attempts, started));
+            };
+            synchronous::back_off(None, wait);
+        }
+    }
+}
+
+impl<T> ResilientSender<async_std::channel::Sender<T>> {
+    /// Sends `item`, waiting with backoff while the channel is full.
+    ///
+    /// # Returns
+    /// The same as the `send` of a `SyncSender`; a closed channel is `Disconnected`.
+    pub async fn send(&self, mut item: T) -> Result<(), ChannelSendError<T>> {
+        let started = time::now();
+        let mut delay = self.delay;
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            item = match self.sender.try_send(item) {
+                Ok(()) => return Ok(()),
+                Err(async_std::channel::TrySendError::Closed(item)) => {
+                    return Err(ChannelSendError::Disconnected(item));
+                }
+                Err(async_std::channel::TrySendError::Full(item)) => item,
+            };
+            let Some(wait) = self.backoff(started, attempts, &mut delay) else {
+                return Err(overloaded(item, attempts, started));
+            };
+            asynchronous::back_off(None, None, wait).await;
+        }
+    }
+}
+
+fn overloaded<T>(item: T, attempts: usize, started: Instant) -> ChannelSendError<T> {
+    ChannelSendError::Overloaded(ChannelOverloaded {
+        item,
+        attempts,
+        waited: time::elapsed(started),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::VirtualClock;
+
+    #[test]
+    fn test_full_channel_backs_off_until_the_deadline() {
+        let clock = VirtualClock::new();
+        let (sender, receiver) = async_std::channel::bounded(1);
+        let sender = ResilientSender::new(sender, Duration::from_millis(100))
+            .with_backoff(Duration::from_millis(10), RetryStrategy::ExponentialBackoff)
+            .with_max_delay(Duration::from_millis(40));
+
+        assert_eq!(clock.block_on(sender.send(1)), Ok(()));
+        // Waits 10, 10, 20 and 40ms, then the remaining 20ms before giving up.
+        assert_eq!(
+            clock.block_on(sender.send(2)),
+            Err(ChannelSendError::Overloaded(ChannelOverloaded {
+                item: 2,
+                attempts: 6,
+                waited: Duration::from_millis(100),
+            }))
+        );
+
+        drop(receiver);
+        assert_eq!(
+            clock.block_on(sender.send(3)),
+            Err(ChannelSendError::Disconnected(3))
+        );
+    }
+
+    #[test]
+    fn test_blocking_send_succeeds_once_the_consumer_catches_up() {
+        let (sender, receiver) = mpsc::sync_channel(1);
+        let sender = ResilientSender::new(sender, Duration::from_secs(5));
+        sender.send(1).unwrap();
+
+        let consumer = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            receiver.iter().take(2).collect::<Vec<_>>()
+        });
+        assert_eq!(sender.send(2), Ok(()));
+        assert_eq!(consumer.join().unwrap(), [1, 2]);
+    }
+}
@@ -0,0 +1,255 @@
+use crate::time;
+use rand::Rng;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::time::Duration;
+
+/// A fault injected into one call by `Chaos`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fault {
+    /// The operation runs normally.
+    Pass,
+    /// The operation is not run and the injected error is returned.
+    Error,
+    /// The operation runs after the given delay.
+    Latency(Duration),
+    /// The call hangs for the given duration, then returns the injected error without running
+    /// the operation, to trigger the caller's timeouts.
+    Hang(Duration),
+}
+
+/// Wraps operations and injects failures into their calls, to check that retry, circuit breaker
+/// and timeout configurations behave as intended.
+///
+/// Each call first takes the next fault of the script, if any, so that sequences such as "fail 3
+/// times then succeed" are deterministic. Once the script is exhausted, faults are drawn at
+/// random with the configured rates: a hang with the timeout rate, otherwise an error with the
+/// error rate, otherwise added latency with the latency rate. All rates are zero by default, so
+/// an unconfigured `Chaos` passes every call through.
+///
+/// This type is available with the `chaos` feature.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::chaos::Chaos;
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::synchronous::retry;
+///
+/// let chaos = Chaos::new(|| "injected failure").with_failures(3);
+/// let config = RetryConfig::default().with_max_attempts(4).with_delay(Duration::from_millis(1));
+///
+/// let result = retry(|| chaos.call(|| Ok(42)), &config);
+/// assert_eq!(result, Ok(42));
+/// assert_eq!(chaos.calls(), 4);
+/// assert_eq!(chaos.injected(), 3);
+/// ```
+pub struct Chaos<E> {
+    error: Box<dyn Fn() -> E + Send + Sync>,
+    script: Mutex<VecDeque<Fault>>,
+    error_rate: f64,
+    latency: Duration,
+    latency_rate: f64,
+    hang: Duration,
+    timeout_rate: f64,
+    calls: AtomicUsize,
+    injected: AtomicUsize,
+}
+
+impl<E> Chaos<E> {
+    /// Creates a `Chaos` passing every call through.
+    ///
+    /// # Arguments
+    /// * `error` - Creates the error returned by the calls that fail.
+    pub fn new<F>(error: F) -> Self
+    where
+        F: Fn() -> E + Send + Sync + 'static,
+    {
+        Chaos {
+            error: Box::new(error),
+            script: Mutex::new(VecDeque::new()),
+            error_rate: 0.0,
+            latency: Duration::ZERO,
+            latency_rate: 0.0,
+            hang: Duration::ZERO,
+            timeout_rate: 0.0,
+            calls: AtomicUsize::new(0),
+            injected: AtomicUsize::new(0),
+        }
+    }
+
+    /// Fails calls at random with the probability `rate`, between `0.0` and `1.0`, and returns
+    /// the modified `Chaos`.
+    pub fn with_error_rate(mut self, rate: f64) -> Self {
+        self.error_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Delays calls by `latency` at random with the probability `rate`, between `0.0` and `1.0`,
+    /// and returns the modified `Chaos`.
+    pub fn with_latency(mut self, latency: Duration, rate: f64) -> Self {
+        self.latency = latency;
+        self.latency_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Makes calls hang for `hang` then fail, at random with the probability `rate`, between
+    /// `0.0` and `1.0`, and returns the modified `Chaos`.
+    ///
+    /// Choose `hang` longer than the timeout under test.
+    pub fn with_timeouts(mut self, hang: Duration, rate: f64) -> Self {
+        self.hang = hang;
+        self.timeout_rate = rate.clamp(0.0, 1.0);
+        self
+    }
+
+    /// Appends faults to the script played before any random fault, and returns the modified
+    /// `Chaos`.
+    pub fn with_script(self, faults: impl IntoIterator<Item = Fault>) -> Self {
+        self.script().extend(faults);
+        self
+    }
+
+    /// Fails the next `count` calls, then lets the rates decide, and returns the modified
+    /// `Chaos`.
+    pub fn with_failures(self, count: usize) -> Self {
+        self.with_script(std::iter::repeat_n(Fault::Error, count))
+    }
+
+    /// Returns the number of calls made so far.
+    pub fn calls(&self) -> usize {
+        self.calls.load(Ordering::Relaxed)
+    }
+
+    /// Returns the number of calls that failed or hung because of an injected fault.
+    pub fn injected(&self) -> usize {
+        self.injected.load(Ordering::Relaxed)
+    }
+
+    /// Runs a blocking operation, injecting the fault of this call.
+    ///
+    /// # Returns
+    /// The result of `operation`, or the injected error for `Fault::Error` and `Fault::Hang`.
+    pub fn call<F, T>(&self, operation: F) -> Result<T, E>
+    where
+        F: FnOnce() -> Result<T, E>,
+    {
+        match self.next_fault() {
+            Fault::Pass => operation(),
+            Fault::Latency(latency) => {
+                time::sleep_blocking(latency);
+                operation()
+            }
+            Fault::Hang(hang) => {
+                time::sleep_blocking(hang);
+                Err((self.error)())
+            }
+            Fault::Error => Err((self.error)()),
+        }
+    }
+
+    /// Runs an asynchronous operation, injecting the fault of this call.
+    ///
+    /// # Returns
+    /// The same as `call`.
+    pub async fn call_async<F, Fut, T>(&self, operation: F) -> Result<T, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        match self.next_fault() {
+            Fault::Pass => operation().await,
+            Fault::Latency(latency) => {
+                time::sleep(latency).await;
+                operation().await
+            }
+            Fault::Hang(hang) => {
+                time::sleep(hang).await;
+                Err((self.error)())
+            }
+            Fault::Error => Err((self.error)()),
+        }
+    }
+
+    /// Counts a call and picks its fault.
+    fn next_fault(&self) -> Fault {
+        self.calls.fetch_add(1, Ordering::Relaxed);
+        let fault = self.script().pop_front().unwrap_or_else(|| {
+            time::with_rng(|rng| {
+                if rng.random_bool(self.timeout_rate) {
+                    Fault::Hang(self.hang)
+                } else if rng.random_bool(self.error_rate) {
+                    Fault::Error
+                } else if rng.random_bool(self.latency_rate) {
+                    Fault::Latency(self.latency)
+                } else {
+                    Fault::Pass
+                }
+            })
+        });
+        if matches!(fault, Fault::Error | Fault::Hang(_)) {
+            self.injected.fetch_add(1, Ordering::Relaxed);
+        }
+        fault
+    }
+
+    fn script(&self) -> std::sync::MutexGuard<'_, VecDeque<Fault>> {
+        self.script.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{CircuitBreakerConfig, RetryConfig};
+    use crate::synchronous::{CircuitBreaker, CircuitBreakerError, retry};
+    use async_std::task::block_on;
+
+    #[test]
+    fn test_scripted_failures_exercise_retries_and_breakers() {
+        let config = RetryConfig {
+            max_attempts: 3,
+            delay: Duration::from_millis(1),
+            ..RetryConfig::default()
+        };
+        let chaos = Chaos::new(|| "injected").with_failures(3);
+        assert_eq!(retry(|| chaos.call(|| Ok(1)), &config), Err("injected"));
+        assert_eq!(retry(|| chaos.call(|| Ok(2)), &config), Ok(2));
+        assert_eq!((chaos.calls(), chaos.injected()), (4, 3));
+
+        let chaos = Chaos::new(|| "injected").with_error_rate(1.0);
+        let breaker =
+            CircuitBreaker::with_config(CircuitBreakerConfig::new(1, 2, Duration::from_secs(30)));
+        for _ in 0..2 {
+            let result = breaker.run(|| chaos.call(|| Ok(())));
+            assert!(matches!(
+                result,
+                Err(CircuitBreakerError::Inner("injected"))
+            ));
+        }
+        let result = breaker.run(|| chaos.call(|| Ok(())));
+        assert!(matches!(result, Err(CircuitBreakerError::Open { .. })));
+        assert_eq!(chaos.calls(), 2);
+    }
+
+    #[test]
+    fn test_hangs_trip_timeouts_and_latency_delays_calls() {
+        let chaos = Chaos::new(|| "injected").with_script([
+            Fault::Hang(Duration::from_secs(5)),
+            Fault::Latency(Duration::from_millis(20)),
+        ]);
+        let hung = block_on(async_std::future::timeout(
+            Duration::from_millis(10),
+            chaos.call_async(|| async { Ok(()) }),
+        ));
+        assert!(hung.is_err());
+
+        let start = std::time::Instant::now();
+        let delayed = block_on(chaos.call_async(|| async { Ok(7) }));
+        assert_eq!(delayed, Ok(7));
+        assert!(start.elapsed() >= Duration::from_millis(20));
+        assert_eq!(block_on(chaos.call_async(|| async { Ok(8) })), Ok(8));
+        assert_eq!((chaos.calls(), chaos.injected()), (3, 1));
+    }
+}
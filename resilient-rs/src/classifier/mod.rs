@@ -0,0 +1,134 @@
+use std::time::Duration;
+
+/// The classification of an error returned by an operation.
+///
+/// An `ErrorClass` tells the retry loops and the circuit breaker how to react to a failure,
+/// which is more expressive than the boolean `retry_condition`:
+/// - `Transient`: The failure is temporary; the operation is retried using the configured strategy.
+/// - `Permanent`: Retrying will not help; the operation fails immediately.
+/// - `Throttled`: The dependency asked us to slow down; the operation is retried after the
+///   suggested `retry_after` delay (or the strategy delay if no hint was given).
+/// - `Fatal`: The dependency is unusable; retries stop and a circuit breaker trips immediately.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorClass {
+    /// A temporary failure that is worth retrying.
+    Transient,
+    /// A failure that will not go away by retrying, such as a validation error.
+    Permanent,
+    /// The operation was rejected because of rate limiting or overload.
+    ///
+    /// `retry_after` carries the delay suggested by the dependency (e.g. from a `Retry-After`
+    /// header). When it is `None`, the delay computed by the retry strategy is used instead.
+    Throttled { retry_after: Option<Duration> },
+    /// A failure indicating the dependency is broken; no further attempts should be made.
+    Fatal,
+}
+
+impl ErrorClass {
+    /// Returns `true` if an error of this class should be retried.
+    ///
+    /// # Example
+    /// ```
+    /// use resilient_rs::classifier::ErrorClass;
+    /// assert!(ErrorClass::Transient.is_retryable());
+    /// assert!(!ErrorClass::Fatal.is_retryable());
+    /// ```
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, ErrorClass::Transient | ErrorClass::Throttled { .. })
+    }
+}
+
+/// Classifies errors into an `ErrorClass`.
+///
+/// Implement this trait for a type when the classification needs its own state (for example a
+/// list of retryable status codes loaded from configuration). Any closure or function taking a
+/// reference to the error and returning an `ErrorClass` implements it automatically.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::classifier::{ErrorClass, ErrorClassifier};
+///
+/// struct HttpClassifier;
+///
+/// impl ErrorClassifier<u16> for HttpClassifier {
+///     fn classify(&self, status: &u16) -> ErrorClass {
+///         match status {
+///             429 => ErrorClass::Throttled { retry_after: Some(Duration::from_secs(1)) },
+///             500..=599 => ErrorClass::Transient,
+///             _ => ErrorClass::Permanent,
+///         }
+///     }
+/// }
+///
+/// assert_eq!(HttpClassifier.classify(&503), ErrorClass::Transient);
+/// assert_eq!(HttpClassifier.classify(&404), ErrorClass::Permanent);
+/// ```
+pub trait ErrorClassifier<E: ?Sized> {
+    /// Returns the classification of `error`.
+    fn classify(&self, error: &E) -> ErrorClass;
+}
+
+impl<E, F> ErrorClassifier<E> for F
+where
+    E: ?Sized,
+    F: Fn(&E) -> ErrorClass,
+{
+    fn classify(&self, error: &E) -> ErrorClass {
+        self(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error;
+
+    #[test]
+    fn test_closure_classifier() {
+        let classifier = |e: &String| {
+            if e.contains("transient") {
+                ErrorClass::Transient
+            } else {
+                ErrorClass::Permanent
+            }
+        };
+        assert_eq!(
+            classifier.classify(&"transient error".to_string()),
+            ErrorClass::Transient
+        );
+        assert_eq!(
+            classifier.classify(&"bad input".to_string()),
+            ErrorClass::Permanent
+        );
+    }
+
+    #[test]
+    fn test_classifier_over_dyn_error() {
+        let classifier = |e: &(dyn Error + 'static)| {
+            if e.to_string() == "throttled" {
+                ErrorClass::Throttled { retry_after: None }
+            } else {
+                ErrorClass::Fatal
+            }
+        };
+        let err: Box<dyn Error> = Box::from("throttled");
+        assert_eq!(
+            classifier.classify(&*err),
+            ErrorClass::Throttled { retry_after: None }
+        );
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        assert!(ErrorClass::Transient.is_retryable());
+        assert!(
+            ErrorClass::Throttled {
+                retry_after: Some(Duration::from_secs(1))
+            }
+            .is_retryable()
+        );
+        assert!(!ErrorClass::Permanent.is_retryable());
+        assert!(!ErrorClass::Fatal.is_retryable());
+    }
+}
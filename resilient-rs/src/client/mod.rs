@@ -0,0 +1,203 @@
+use crate::breaker::CircuitBreakerState;
+use crate::config::{BulkheadConfig, CircuitBreakerConfig, LogConfig, RetryConfig};
+use crate::pipeline::{Pipeline, PipelineError, PipelineFallback};
+use crate::ratelimit::RateLimiter;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The timeout of every attempt made by a `ResilientClient` created with `new`.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A batteries-included client for a single downstream dependency, owning its retry policy,
+/// circuit breaker, rate limiter, timeout and fallback.
+///
+/// `new` starts from sensible defaults: attempts are bounded by `DEFAULT_TIMEOUT`, retried with
+/// `RetryConfig::default()` and supervised by a circuit breaker created from
+/// `CircuitBreakerConfig::default()`. The `with_*` methods replace a stage or add the optional
+/// ones (rate limiter, bulkhead, fallback), and `run` executes an operation through all of them,
+/// in the order documented on `Pipeline`.
+///
+/// Create one client per dependency and share it (e.g. through an `Arc`) between all the calls
+/// to that dependency, so they share its breaker and limiter.
+///
+/// # Example
+/// ```
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use async_std::task::block_on;
+/// use resilient_rs::client::ResilientClient;
+/// use resilient_rs::config::RateLimitConfig;
+/// use resilient_rs::pipeline::PipelineError;
+/// use resilient_rs::ratelimit::FixedWindowLimiter;
+///
+/// let limiter = FixedWindowLimiter::new(RateLimitConfig::new(1, Duration::from_secs(60)));
+/// let inventory = ResilientClient::new()
+///     .with_timeout(Duration::from_millis(500))
+///     .with_rate_limiter(Arc::new(limiter))
+///     .with_fallback(|err| match err {
+///         PipelineError::RateLimited { .. } => Ok(0),
+///         _ => Err("inventory unavailable"),
+///     });
+///
+/// assert_eq!(block_on(inventory.run(|| async { Ok(42) })), Ok(42));
+/// // The quota of the minute is used up, so the fallback serves the second call.
+/// assert_eq!(block_on(inventory.run(|| async { Ok(41) })), Ok(0));
+/// ```
+pub struct ResilientClient<T, E> {
+    pipeline: Pipeline<T, E>,
+}
+
+impl<T, E> Default for ResilientClient<T, E> {
+    fn default() -> Self {
+        ResilientClient {
+            pipeline: Pipeline::new()
+                .with_timeout(DEFAULT_TIMEOUT)
+                .with_retry(RetryConfig::default())
+                .with_circuit_breaker(CircuitBreakerConfig::default()),
+        }
+    }
+}
+
+impl<T, E> ResilientClient<T, E> {
+    /// Creates a client with the default timeout, retry policy and circuit breaker, and without
+    /// rate limiter, bulkhead or fallback.
+    pub fn new() -> Self {
+        ResilientClient::default()
+    }
+
+    /// Bounds every attempt by `timeout` instead of `DEFAULT_TIMEOUT`, and returns the modified
+    /// client.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.pipeline = self.pipeline.with_timeout(timeout);
+        self
+    }
+
+    /// Retries failed attempts according to `retry_config`, and returns the modified client.
+    pub fn with_retry(mut self, retry_config: RetryConfig<E>) -> Self {
+        self.pipeline = self.pipeline.with_retry(retry_config);
+        self
+    }
+
+    /// Replaces the circuit breaker with one created from `config`, and returns the modified
+    /// client.
+    pub fn with_circuit_breaker(mut self, config: CircuitBreakerConfig) -> Self {
+        self.pipeline = self.pipeline.with_circuit_breaker(config);
+        self
+    }
+
+    /// Admits every attempt through `limiter`, and returns the modified client.
+    pub fn with_rate_limiter(mut self, limiter: Arc<dyn RateLimiter>) -> Self {
+        self.pipeline = self.pipeline.with_rate_limiter(limiter);
+        self
+    }
+
+    /// Caps the concurrent attempts with a bulkhead created from `config`, and returns the
+    /// modified client.
+    pub fn with_bulkhead(mut self, config: BulkheadConfig) -> Self {
+        self.pipeline = self.pipeline.with_bulkhead(config);
+        self
+    }
+
+    /// Produces the result with `fallback` when the other stages fail, and returns the modified
+    /// client.
+    pub fn with_fallback(mut self, fallback: PipelineFallback<T, E>) -> Self {
+        self.pipeline = self.pipeline.with_fallback(fallback);
+        self
+    }
+
+    /// Sets the logging behavior for timeouts and fallbacks, and returns the modified client.
+    pub fn with_log(mut self, log: LogConfig) -> Self {
+        self.pipeline = self.pipeline.with_log(log);
+        self
+    }
+
+    /// Returns the state of the client's circuit breaker.
+    pub fn circuit_state(&self) -> Option<CircuitBreakerState> {
+        self.pipeline.circuit_state()
+    }
+
+    /// Runs an asynchronous operation against the dependency.
+    ///
+    /// # Arguments
+    /// * `operation` - A closure returning a `Future` resolving to a `Result<T, E>`, called once
+    ///   per attempt.
+    ///
+    /// # Returns
+    /// - `Ok(T)` if an attempt, or the fallback, succeeds.
+    /// - `Err(PipelineError)` naming the stage that failed the call otherwise.
+    pub async fn run<F, Fut>(&self, operation: F) -> Result<T, PipelineError<E>>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: fmt::Display,
+    {
+        self.pipeline.execute(operation).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::VirtualClock;
+    use crate::strategies::RetryStrategy;
+    use crate::throttle::Throttle;
+    use crate::time;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_defaults_retry_time_out_and_break() {
+        let clock = VirtualClock::new();
+        let client: ResilientClient<(), &str> = ResilientClient::new();
+        let attempts = Cell::new(0);
+
+        // A hanging attempt times out and is retried.
+        let result = clock.block_on(client.run(|| {
+            attempts.set(attempts.get() + 1);
+            let hang = attempts.get() == 1;
+            async move {
+                if hang {
+                    time::sleep(Duration::from_secs(60)).await;
+                }
+                Ok(())
+            }
+        }));
+        assert_eq!(result, Ok(()));
+        assert_eq!(attempts.get(), 2);
+        assert_eq!(clock.elapsed(), DEFAULT_TIMEOUT + Duration::from_secs(2));
+
+        // Repeated failures open the default breaker, which then rejects calls.
+        for _ in 0..2 {
+            let _ = clock.block_on(client.run(|| async { Err("down") }));
+        }
+        assert_eq!(client.circuit_state(), Some(CircuitBreakerState::Open));
+        let rejected = clock.block_on(client.run(|| async { Ok(()) }));
+        assert!(rejected.unwrap_err().is_rejected());
+    }
+
+    #[test]
+    fn test_rate_limited_attempts_reach_the_fallback() {
+        let clock = VirtualClock::new();
+        let _guard = clock.enter();
+        let client: ResilientClient<&str, &str> = ResilientClient::new()
+            .with_retry(RetryConfig::new(
+                3,
+                Duration::from_millis(100),
+                RetryStrategy::Linear,
+            ))
+            .with_rate_limiter(Arc::new(Throttle::new(Duration::from_secs(1))))
+            .with_fallback(|err| match err {
+                PipelineError::RateLimited { .. } => Ok("queued"),
+                _ => Err("failed"),
+            });
+        let attempts = Cell::new(0);
+
+        // The retry of the failed attempt arrives before the throttle's next slot.
+        let result = clock.block_on(client.run(|| {
+            attempts.set(attempts.get() + 1);
+            async { Err("unavailable") }
+        }));
+        assert_eq!(result, Ok("queued"));
+        assert_eq!(attempts.get(), 1);
+    }
+}
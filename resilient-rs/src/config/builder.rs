@@ -0,0 +1,245 @@
+//! A type-state builder for `RetryConfig`.
+//!
+//! The type parameter `S` of `RetryConfigBuilder` records which retry strategy has been chosen,
+//! so that settings only meaningful for one strategy are only available once that strategy is
+//! selected. For example, `jitter` can only be called after `exponential`, and a strategy can be
+//! chosen at most once:
+//!
+//! ```compile_fail
+//! use resilient_rs::config::RetryConfig;
+//!
+//! // Jitter is only defined for exponential backoff.
+//! let config: RetryConfig<()> = RetryConfig::builder().linear().jitter(0.2).build();
+//! ```
+
+use super::{ConfigError, LogConfig, PolicyTable, RetryConfig};
+use crate::classifier::ErrorClassifier;
+use crate::stats::Stats;
+use crate::strategies::RetryStrategy;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Builder state: no strategy has been chosen yet; `build` uses `Linear`.
+#[derive(Debug)]
+pub enum Unset {}
+
+/// Builder state: the `Linear` strategy has been chosen.
+#[derive(Debug)]
+pub enum Linear {}
+
+/// Builder state: the `ExponentialBackoff` strategy has been chosen; jitter may be added.
+#[derive(Debug)]
+pub enum Exponential {}
+
+/// Builder state: the `ExponentialBackoffWithJitter` strategy has been chosen.
+#[derive(Debug)]
+pub enum Jittered {}
+
+/// Builder state: the `FibonacciBackoff` strategy has been chosen.
+#[derive(Debug)]
+pub enum Fibonacci {}
+
+/// Builder state: the `ArithmeticProgression` strategy has been chosen.
+#[derive(Debug)]
+pub enum Arithmetic {}
+
+/// Builder state: the `Polynomial` strategy has been chosen.
+#[derive(Debug)]
+pub enum Polynomial {}
+
+/// Builder state: the `Harmonic` strategy has been chosen.
+#[derive(Debug)]
+pub enum Harmonic {}
+
+/// A builder for `RetryConfig`, created with `RetryConfig::builder()`.
+///
+/// Settings that apply to every strategy (`max_attempts`, `delay`, `max_delay`, the retry
+/// condition, classifier, policies, statistics and logging) can be set in any state. The strategy
+/// is chosen with `linear`, `exponential`, `fibonacci`, `arithmetic`, `polynomial` or `harmonic`,
+/// and `jitter` is only available after `exponential`. Values that can only be checked at runtime, such as a zero
+/// `max_attempts` or a jitter factor outside `0.0..=1.0`, are reported by `try_build`.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::strategies::RetryStrategy;
+///
+/// let config: RetryConfig<String> = RetryConfig::builder()
+///     .max_attempts(4)
+///     .delay(Duration::from_millis(200))
+///     .arithmetic(3)
+///     .try_build()
+///     .unwrap();
+/// assert!(matches!(config.strategy(), RetryStrategy::ArithmeticProgression { coefficient: 3 }));
+/// ```
+pub struct RetryConfigBuilder<E, S = Unset> {
+    config: RetryConfig<E>,
+    state: PhantomData<S>,
+}
+
+impl<E> RetryConfigBuilder<E> {
+    /// Creates a builder starting from `RetryConfig::default()`.
+    pub fn new() -> Self {
+        RetryConfigBuilder {
+            config: RetryConfig::default(),
+            state: PhantomData,
+        }
+    }
+
+    /// Selects the `Linear` strategy.
+    pub fn linear(self) -> RetryConfigBuilder<E, Linear> {
+        self.with_strategy(RetryStrategy::Linear)
+    }
+
+    /// Selects the `ExponentialBackoff` strategy.
+    pub fn exponential(self) -> RetryConfigBuilder<E, Exponential> {
+        self.with_strategy(RetryStrategy::ExponentialBackoff)
+    }
+
+    /// Selects the `FibonacciBackoff` strategy.
+    pub fn fibonacci(self) -> RetryConfigBuilder<E, Fibonacci> {
+        self.with_strategy(RetryStrategy::FibonacciBackoff)
+    }
+
+    /// Selects the `ArithmeticProgression` strategy with the given coefficient.
+    pub fn arithmetic(self, coefficient: usize) -> RetryConfigBuilder<E, Arithmetic> {
+        self.with_strategy(RetryStrategy::ArithmeticProgression { coefficient })
+    }
+
+    /// Selects the `Polynomial` strategy with the given exponent.
+    pub fn polynomial(self, exponent: u32) -> RetryConfigBuilder<E, Polynomial> {
+        self.with_strategy(RetryStrategy::Polynomial { exponent })
+    }
+
+    /// Selects the `Harmonic` strategy.
+    pub fn harmonic(self) -> RetryConfigBuilder<E, Harmonic> {
+        self.with_strategy(RetryStrategy::Harmonic)
+    }
+}
+
+impl<E> Default for RetryConfigBuilder<E> {
+    fn default() -> Self {
+        RetryConfigBuilder::new()
+    }
+}
+
+impl<E> RetryConfigBuilder<E, Exponential> {
+    /// Adds random jitter to the exponential backoff.
+    ///
+    /// # Arguments
+    /// * `jitter_factor` - The jitter range as a fraction of the delay, typically 0.0 to 0.5.
+    pub fn jitter(self, jitter_factor: f64) -> RetryConfigBuilder<E, Jittered> {
+        self.with_strategy(RetryStrategy::ExponentialBackoffWithJitter { jitter_factor })
+    }
+}
+
+impl<E, S> RetryConfigBuilder<E, S> {
+    fn with_strategy<N>(mut self, strategy: RetryStrategy) -> RetryConfigBuilder<E, N> {
+        self.config.strategy = strategy;
+        RetryConfigBuilder {
+            config: self.config,
+            state: PhantomData,
+        }
+    }
+
+    /// Sets the maximum number of attempts, including the initial attempt.
+    pub fn max_attempts(mut self, max_attempts: usize) -> Self {
+        self.config.max_attempts = max_attempts;
+        self
+    }
+
+    /// Sets the base delay between retry attempts.
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.config.delay = delay;
+        self
+    }
+
+    /// Caps the delay between retry attempts.
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.config.max_delay = Some(max_delay);
+        self
+    }
+
+    /// Sets the function deciding whether an error is retried.
+    pub fn retry_condition(mut self, retry_condition: fn(&E) -> bool) -> Self {
+        self.config.retry_condition = Some(retry_condition);
+        self
+    }
+
+    /// Sets the classifier mapping errors to an `ErrorClass`.
+    pub fn error_classifier(
+        mut self,
+        classifier: impl ErrorClassifier<E> + Send + Sync + 'static,
+    ) -> Self {
+        self.config.error_classifier = Some(Arc::new(classifier));
+        self
+    }
+
+    /// Sets the per-class retry policies.
+    pub fn policies(mut self, policies: PolicyTable) -> Self {
+        self.config.policies = policies;
+        self
+    }
+
+    /// Sets the statistics handle updated by the retry functions.
+    pub fn stats(mut self, stats: Stats) -> Self {
+        self.config.stats = Some(stats);
+        self
+    }
+
+    /// Sets the logging behavior of the retry functions.
+    pub fn log(mut self, log: LogConfig) -> Self {
+        self.config.log = log;
+        self
+    }
+
+    /// Retries panics of the operation as `Transient` failures.
+    pub fn catch_panics(mut self) -> Self {
+        self.config.catch_panics = true;
+        self
+    }
+
+    /// Returns the configured `RetryConfig` without validating it.
+    pub fn build(self) -> RetryConfig<E> {
+        self.config
+    }
+
+    /// Returns the configured `RetryConfig` after checking it with `RetryConfig::validate`.
+    pub fn try_build(self) -> Result<RetryConfig<E>, ConfigError> {
+        self.config.validate()?;
+        Ok(self.config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder_sets_strategy_and_cap() {
+        let config: RetryConfig<()> = RetryConfig::builder()
+            .max_attempts(5)
+            .exponential()
+            .jitter(0.2)
+            .max_delay(Duration::from_secs(3))
+            .build();
+        assert_eq!(config.max_attempts, 5);
+        assert_eq!(config.delay, Duration::from_secs(2));
+        assert_eq!(config.max_delay, Some(Duration::from_secs(3)));
+        assert!(matches!(
+            config.strategy,
+            RetryStrategy::ExponentialBackoffWithJitter { jitter_factor } if jitter_factor == 0.2
+        ));
+    }
+
+    #[test]
+    fn test_try_build_validates() {
+        let result: Result<RetryConfig<()>, _> =
+            RetryConfig::builder().exponential().jitter(4.0).try_build();
+        assert!(result.is_err());
+        let result: Result<RetryConfig<()>, _> = RetryConfig::builder().max_attempts(0).try_build();
+        assert!(result.is_err());
+    }
+}
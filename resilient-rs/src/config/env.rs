@@ -0,0 +1,326 @@
+use super::{CircuitBreakerConfig, ConfigError, ExecConfig, LogConfig, RetryConfig};
+use crate::strategies::RetryStrategy;
+use std::fmt::Display;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Reads a variable named `<prefix>_<key>` through `lookup` and parses it.
+///
+/// Returns `Ok(None)` when the variable is not set, so callers can keep their defaults.
+fn read<T>(
+    prefix: &str,
+    key: &str,
+    lookup: &impl Fn(&str) -> Option<String>,
+) -> Result<Option<T>, ConfigError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    let name = var_name(prefix, key);
+    match lookup(&name) {
+        None => Ok(None),
+        Some(value) => match value.trim().parse() {
+            Ok(parsed) => Ok(Some(parsed)),
+            Err(e) => Err(ConfigError::InvalidEnvVar {
+                reason: e.to_string(),
+                name,
+                value,
+            }),
+        },
+    }
+}
+
+fn var_name(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}_{}", prefix.trim_end_matches('_'), key)
+    }
+}
+
+fn read_millis(
+    prefix: &str,
+    key: &str,
+    lookup: &impl Fn(&str) -> Option<String>,
+) -> Result<Option<Duration>, ConfigError> {
+    Ok(read::<u64>(prefix, key, lookup)?.map(Duration::from_millis))
+}
+
+fn read_strategy(
+    prefix: &str,
+    lookup: &impl Fn(&str) -> Option<String>,
+) -> Result<Option<RetryStrategy>, ConfigError> {
+    let Some(name) = read::<String>(prefix, "STRATEGY", lookup)? else {
+        return Ok(None);
+    };
+    let strategy = match name.to_ascii_lowercase().as_str() {
+        "linear" => RetryStrategy::Linear,
+        "exponential_backoff" => RetryStrategy::ExponentialBackoff,
+        "fibonacci_backoff" => RetryStrategy::FibonacciBackoff,
+        "exponential_backoff_with_jitter" => RetryStrategy::ExponentialBackoffWithJitter {
+            jitter_factor: required(prefix, "JITTER_FACTOR", lookup)?,
+        },
+        "arithmetic_progression" => RetryStrategy::ArithmeticProgression {
+            coefficient: required(prefix, "COEFFICIENT", lookup)?,
+        },
+        "polynomial" => RetryStrategy::Polynomial {
+            exponent: required(prefix, "EXPONENT", lookup)?,
+        },
+        "harmonic" => RetryStrategy::Harmonic,
+        _ => {
+            return Err(ConfigError::InvalidEnvVar {
+                name: var_name(prefix, "STRATEGY"),
+                value: name,
+                reason: "expected one of linear, exponential_backoff, \
+                         exponential_backoff_with_jitter, fibonacci_backoff, arithmetic_progression, \
+                         polynomial, harmonic"
+                    .to_string(),
+            });
+        }
+    };
+    Ok(Some(strategy))
+}
+
+fn required<T>(
+    prefix: &str,
+    key: &str,
+    lookup: &impl Fn(&str) -> Option<String>,
+) -> Result<T, ConfigError>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    read(prefix, key, lookup)?.ok_or_else(|| ConfigError::MissingEnvVar {
+        name: var_name(prefix, key),
+    })
+}
+
+fn read_log(
+    prefix: &str,
+    mut log: LogConfig,
+    lookup: &impl Fn(&str) -> Option<String>,
+) -> Result<LogConfig, ConfigError> {
+    if let Some(level) = read(prefix, "LOG_LEVEL", lookup)? {
+        log.level = level;
+    }
+    if let Some(quiet) = read(prefix, "LOG_QUIET", lookup)? {
+        log.quiet = quiet;
+    }
+    Ok(log)
+}
+
+fn env_lookup(name: &str) -> Option<String> {
+    std::env::var(name).ok()
+}
+
+impl<E> RetryConfig<E> {
+    /// Creates a `RetryConfig` from environment variables.
+    ///
+    /// Every variable is optional; unset variables keep the values of `RetryConfig::default()`.
+    /// The following variables are read, where `PREFIX` is the given prefix:
+    /// - `PREFIX_MAX_ATTEMPTS`: The maximum number of attempts.
+    /// - `PREFIX_DELAY_MS`: The base delay between retries, in milliseconds.
+    /// - `PREFIX_STRATEGY`: One of `linear`, `exponential_backoff`, `exponential_backoff_with_jitter`,
+    ///   `fibonacci_backoff`, `arithmetic_progression`, `polynomial` or `harmonic`.
+    /// - `PREFIX_JITTER_FACTOR`: Required when the strategy is `exponential_backoff_with_jitter`.
+    /// - `PREFIX_COEFFICIENT`: Required when the strategy is `arithmetic_progression`.
+    /// - `PREFIX_EXPONENT`: Required when the strategy is `polynomial`.
+    /// - `PREFIX_LOG_LEVEL`: The level of routine retry messages (e.g. `info`, `warn`).
+    /// - `PREFIX_LOG_QUIET`: `true` to silence retry logging.
+    ///
+    /// Function-valued settings such as `retry_condition` cannot be expressed as variables and
+    /// must be set in code.
+    ///
+    /// # Arguments
+    /// * `prefix` - The prefix of the variable names, e.g. `PAYMENTS_RETRY`.
+    ///
+    /// # Returns
+    /// The configuration, or a `ConfigError` naming the variable that is invalid or missing, or
+    /// the setting rejected by `validate`.
+    ///
+    /// # Example
+    /// ```
+    /// use resilient_rs::config::RetryConfig;
+    ///
+    /// // With PAYMENTS_RETRY_MAX_ATTEMPTS=5 and PAYMENTS_RETRY_STRATEGY=exponential_backoff set,
+    /// // the config picks them up; here nothing is set, so the defaults are used.
+    /// let config: RetryConfig<String> = RetryConfig::from_env("PAYMENTS_RETRY").unwrap();
+    /// assert_eq!(config.max_attempts(), 3);
+    /// ```
+    pub fn from_env(prefix: &str) -> Result<Self, ConfigError> {
+        Self::from_lookup(prefix, env_lookup)
+    }
+
+    fn from_lookup(
+        prefix: &str,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, ConfigError> {
+        let mut config = RetryConfig::default();
+        if let Some(max_attempts) = read(prefix, "MAX_ATTEMPTS", &lookup)? {
+            config.max_attempts = max_attempts;
+        }
+        if let Some(delay) = read_millis(prefix, "DELAY_MS", &lookup)? {
+            config.delay = delay;
+        }
+        if let Some(strategy) = read_strategy(prefix, &lookup)? {
+            config.strategy = strategy;
+        }
+        config.log = read_log(prefix, config.log, &lookup)?;
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+impl<T> ExecConfig<T>
+where
+    T: Clone,
+{
+    /// Creates an `ExecConfig` from environment variables.
+    ///
+    /// The following variables are read, where `PREFIX` is the given prefix:
+    /// - `PREFIX_TIMEOUT_MS`: The timeout in milliseconds. This variable is required.
+    /// - `PREFIX_LOG_LEVEL`: The level of timeout messages (e.g. `info`, `warn`).
+    /// - `PREFIX_LOG_QUIET`: `true` to silence executor logging.
+    ///
+    /// The fallback function cannot be expressed as a variable; set it with `with_fallback`.
+    ///
+    /// # Arguments
+    /// * `prefix` - The prefix of the variable names, e.g. `SEARCH_EXEC`.
+    ///
+    /// # Returns
+    /// The configuration, or a `ConfigError` naming the variable that is invalid or missing.
+    pub fn from_env(prefix: &str) -> Result<Self, ConfigError> {
+        Self::from_lookup(prefix, env_lookup)
+    }
+
+    fn from_lookup(
+        prefix: &str,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, ConfigError> {
+        let timeout: u64 = required(prefix, "TIMEOUT_MS", &lookup)?;
+        let mut config = ExecConfig::new(Duration::from_millis(timeout));
+        config.log = read_log(prefix, config.log, &lookup)?;
+        Ok(config)
+    }
+}
+
+impl CircuitBreakerConfig {
+    /// Creates a `CircuitBreakerConfig` from environment variables.
+    ///
+    /// Every variable is optional; unset variables keep the values of
+    /// `CircuitBreakerConfig::default()`. The following variables are read, where `PREFIX` is the
+    /// given prefix:
+    /// - `PREFIX_FAILURE_THRESHOLD`: Consecutive failures before the breaker opens.
+    /// - `PREFIX_SUCCESS_THRESHOLD`: Successes in `HalfOpen` required to close the breaker.
+    /// - `PREFIX_COOLDOWN_MS`: The time spent `Open` before testing recovery, in milliseconds.
+    /// - `PREFIX_LOG_LEVEL`: The level of routine breaker messages (e.g. `info`, `warn`).
+    /// - `PREFIX_LOG_QUIET`: `true` to silence breaker logging.
+    ///
+    /// # Arguments
+    /// * `prefix` - The prefix of the variable names, e.g. `INVENTORY_BREAKER`.
+    ///
+    /// # Returns
+    /// The configuration, or a `ConfigError` naming the variable that is invalid or the setting
+    /// rejected by `validate`.
+    pub fn from_env(prefix: &str) -> Result<Self, ConfigError> {
+        Self::from_lookup(prefix, env_lookup)
+    }
+
+    fn from_lookup(
+        prefix: &str,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, ConfigError> {
+        let mut config = CircuitBreakerConfig::default();
+        if let Some(threshold) = read(prefix, "FAILURE_THRESHOLD", &lookup)? {
+            config.failure_threshold = threshold;
+        }
+        if let Some(threshold) = read(prefix, "SUCCESS_THRESHOLD", &lookup)? {
+            config.success_threshold = threshold;
+        }
+        if let Some(cooldown) = read_millis(prefix, "COOLDOWN_MS", &lookup)? {
+            config.cooldown_period = cooldown;
+        }
+        config.log = read_log(prefix, config.log, &lookup)?;
+        config.validate()?;
+        Ok(config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::logging::Level;
+    use std::collections::HashMap;
+
+    fn vars(pairs: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+        let map: HashMap<String, String> = pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        move |name| map.get(name).cloned()
+    }
+
+    #[test]
+    fn test_retry_config_from_vars() {
+        let config: RetryConfig<String> = RetryConfig::from_lookup(
+            "APP_RETRY",
+            vars(&[
+                ("APP_RETRY_MAX_ATTEMPTS", "7"),
+                ("APP_RETRY_DELAY_MS", "150"),
+                ("APP_RETRY_STRATEGY", "exponential_backoff_with_jitter"),
+                ("APP_RETRY_JITTER_FACTOR", "0.25"),
+                ("APP_RETRY_LOG_LEVEL", "debug"),
+            ]),
+        )
+        .unwrap();
+        assert_eq!(config.max_attempts, 7);
+        assert_eq!(config.delay, Duration::from_millis(150));
+        assert!(matches!(
+            config.strategy,
+            RetryStrategy::ExponentialBackoffWithJitter { jitter_factor } if jitter_factor == 0.25
+        ));
+        assert_eq!(config.log.level, Level::Debug);
+    }
+
+    #[test]
+    fn test_retry_config_reports_invalid_and_missing_vars() {
+        let err = RetryConfig::<String>::from_lookup("APP", vars(&[("APP_MAX_ATTEMPTS", "many")]))
+            .unwrap_err();
+        assert!(
+            matches!(err, ConfigError::InvalidEnvVar { ref name, .. } if name == "APP_MAX_ATTEMPTS")
+        );
+
+        let err = RetryConfig::<String>::from_lookup(
+            "APP",
+            vars(&[("APP_STRATEGY", "arithmetic_progression")]),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err,
+            ConfigError::MissingEnvVar {
+                name: "APP_COEFFICIENT".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_breaker_and_exec_config_from_vars() {
+        let config = CircuitBreakerConfig::from_lookup(
+            "CB",
+            vars(&[("CB_FAILURE_THRESHOLD", "10"), ("CB_COOLDOWN_MS", "500")]),
+        )
+        .unwrap();
+        assert_eq!(config.failure_threshold, 10);
+        assert_eq!(config.success_threshold, 2);
+        assert_eq!(config.cooldown_period, Duration::from_millis(500));
+        assert!(
+            CircuitBreakerConfig::from_lookup("CB", vars(&[("CB_SUCCESS_THRESHOLD", "0")]))
+                .is_err()
+        );
+
+        let exec: ExecConfig<i32> =
+            ExecConfig::from_lookup("EXEC", vars(&[("EXEC_TIMEOUT_MS", "250")])).unwrap();
+        assert_eq!(exec.timeout_duration, Duration::from_millis(250));
+        assert!(ExecConfig::<i32>::from_lookup("EXEC", vars(&[])).is_err());
+    }
+}
@@ -1,43 +1,144 @@
-use crate::strategies::RetryStrategy;
+use crate::classifier::{ErrorClass, ErrorClassifier};
+use crate::logging::{Diagnostics, Level};
+use crate::stats::Stats;
+use crate::strategies::{Backoff, DelaySchedule, RetryStrategy};
+use crate::wheel::TimerWheel;
 use std::error::Error;
+use std::fmt;
+use std::sync::Arc;
 use std::time::Duration;
 
-#[derive(Debug)]
+pub mod builder;
+mod env;
+mod shared;
+
+pub use builder::RetryConfigBuilder;
+pub use shared::{SharedCircuitBreakerConfig, SharedRetryConfig};
+
+/// Configuration for retrying operations.
+///
+/// This struct defines the parameters for retrying an operation, including
+/// the maximum number of attempts, the delay between retries, and the retry strategy.
+///
+/// The fields are private so the configuration can evolve without breaking callers: build it
+/// with `new`, `builder` or a preset, adjust it with the `with_*` methods, and read it back with
+/// getters such as `max_attempts` and `delay`.
+///
+/// With the `serde` feature, the data fields can be loaded from JSON/TOML/YAML configuration;
+/// durations use humantime syntax (e.g. `"250ms"`, `"2s"`) and missing fields take their default
+/// values. Function-valued fields (`retry_condition`, `error_classifier`) and `stats` are not
+/// serialized and must be set in code.
+///
+/// A configuration can be cloned, e.g. to derive per-endpoint policies from a base one, and
+/// compared: two configurations are equal when their settings are equal and they share the same
+/// `retry_condition`, `error_classifier` and `stats` handle.
+///
+/// # Example
+/// ```
+/// use std::collections::HashMap;
+/// use std::time::Duration;
+/// use resilient_rs::config::RetryConfig;
+///
+/// let base: RetryConfig<String> = RetryConfig::default().with_delay(Duration::from_millis(100));
+/// let mut policies = HashMap::new();
+/// policies.insert("search", base.clone());
+/// policies.insert("payments", base.clone().with_max_attempts(5));
+/// assert_eq!(policies["search"], base);
+/// assert_ne!(policies["payments"], base);
+/// ```
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default, bound = ""))]
 pub struct RetryConfig<E> {
-    /// The maximum number of retry attempts.
-    ///
-    /// This specifies how many times the operation will be retried before
-    /// giving up. For example, if `max_attempts` is set to 3, the operation
-    /// will be attempted up to 3 times (1 initial attempt + 2 retries).
-    pub max_attempts: usize,
+    pub(crate) max_attempts: usize,
+    #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+    pub(crate) delay: Duration,
+    pub(crate) strategy: RetryStrategy,
+    #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+    pub(crate) max_delay: Option<Duration>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) retry_condition: Option<fn(&E) -> bool>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) error_classifier: Option<Arc<dyn ErrorClassifier<E> + Send + Sync>>,
+    pub(crate) policies: PolicyTable,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) stats: Option<Stats>,
+    pub(crate) log: LogConfig,
+    pub(crate) catch_panics: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) schedule: Option<DelaySchedule>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(crate) timer_wheel: Option<TimerWheel>,
+}
 
-    /// The delay between retry attempts.
-    ///
-    /// This specifies the base amount of time to wait between each retry attempt.
-    /// The actual delay may vary depending on the `strategy`. For example, if
-    /// `delay` is set to `Duration::from_secs(2)` and the strategy is `Linear`,
-    /// the program will wait 2 seconds between retries.
-    pub delay: Duration,
+impl<E> fmt::Debug for RetryConfig<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryConfig")
+            .field("max_attempts", &self.max_attempts)
+            .field("delay", &self.delay)
+            .field("strategy", &self.strategy)
+            .field("max_delay", &self.max_delay)
+            .field("retry_condition", &self.retry_condition)
+            .field("error_classifier", &self.error_classifier.is_some())
+            .field("policies", &self.policies)
+            .field("stats", &self.stats)
+            .field("log", &self.log)
+            .field("catch_panics", &self.catch_panics)
+            .field("schedule", &self.schedule)
+            .field("timer_wheel", &self.timer_wheel)
+            .finish()
+    }
+}
 
-    /// The strategy used to calculate delays between retry attempts.
-    ///
-    /// This field determines how the `delay` is applied:
-    /// - `Linear`: Uses a fixed delay between retries.
-    /// - `ExponentialBackoff`: Increases the delay exponentially with each retry.
-    /// - `FibonacciBackoff`: Increases the delay following the Fibonacci sequence with each retry.
-    pub strategy: RetryStrategy,
+impl<E> Clone for RetryConfig<E> {
+    fn clone(&self) -> Self {
+        RetryConfig {
+            max_attempts: self.max_attempts,
+            delay: self.delay,
+            strategy: self.strategy,
+            max_delay: self.max_delay,
+            retry_condition: self.retry_condition,
+            error_classifier: self.error_classifier.clone(),
+            policies: self.policies.clone(),
+            stats: self.stats.clone(),
+            log: self.log,
+            catch_panics: self.catch_panics,
+            schedule: self.schedule.clone(),
+            timer_wheel: self.timer_wheel.clone(),
+        }
+    }
+}
 
-    /// An optional function to determine if a retry should be attempted.
-    ///
-    /// This field allows you to specify a custom condition for retrying based on the error type `E`.
-    /// It takes a reference to the error (`&E`) and returns a `bool`:
-    /// - `true` if the operation should be retried.
-    /// - `false` if the operation should not be retried, causing it to fail immediately.
-    ///
-    /// If set to `None` (the default), all errors will trigger a retry up to `max_attempts`.
-    /// If set to `Some(fn)`, only errors for which the function returns `true` will be retried.
-    /// In this example, only errors containing the word "transient" will trigger retries.
-    pub retry_condition: Option<fn(&E) -> bool>,
+impl<E> PartialEq for RetryConfig<E> {
+    fn eq(&self, other: &Self) -> bool {
+        let same_condition = match (self.retry_condition, other.retry_condition) {
+            (Some(a), Some(b)) => std::ptr::fn_addr_eq(a, b),
+            (a, b) => a.is_none() && b.is_none(),
+        };
+        let same_classifier = match (&self.error_classifier, &other.error_classifier) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (a, b) => a.is_none() && b.is_none(),
+        };
+        let same_stats = match (&self.stats, &other.stats) {
+            (Some(a), Some(b)) => a.ptr_eq(b),
+            (a, b) => a.is_none() && b.is_none(),
+        };
+        let same_wheel = match (&self.timer_wheel, &other.timer_wheel) {
+            (Some(a), Some(b)) => a.ptr_eq(b),
+            (a, b) => a.is_none() && b.is_none(),
+        };
+        self.max_attempts == other.max_attempts
+            && self.delay == other.delay
+            && self.strategy == other.strategy
+            && self.max_delay == other.max_delay
+            && self.policies == other.policies
+            && self.log == other.log
+            && self.catch_panics == other.catch_panics
+            && self.schedule == other.schedule
+            && same_condition
+            && same_classifier
+            && same_stats
+            && same_wheel
+    }
 }
 
 impl<E> Default for RetryConfig<E> {
@@ -47,7 +148,15 @@ impl<E> Default for RetryConfig<E> {
     /// - `max_attempts`: 3 retries
     /// - `delay`: 2 seconds between retries
     /// - `strategy`: `Linear`
+    /// - `max_delay`: `None`, meaning delays are not capped
     /// - `retry_condition`: `None`, meaning all errors trigger retries
+    /// - `error_classifier`: `None`
+    /// - `policies`: empty, meaning every error class uses the settings above
+    /// - `stats`: `None`, meaning no statistics are collected
+    /// - `log`: `LogConfig::default()`, logging retries at `Warn`
+    /// - `catch_panics`: `false`, meaning panics propagate immediately
+    /// - `schedule`: `None`, meaning delays are computed on every retry
+    /// - `timer_wheel`: `None`, meaning every backoff sleep uses its own timer
     ///
     /// This implementation allows you to create a `RetryConfig` with sensible
     /// defaults using `RetryConfig::default()`.
@@ -56,7 +165,15 @@ impl<E> Default for RetryConfig<E> {
             max_attempts: 3,
             delay: Duration::from_secs(2),
             strategy: RetryStrategy::Linear,
+            max_delay: None,
             retry_condition: None,
+            error_classifier: None,
+            policies: PolicyTable::default(),
+            stats: None,
+            log: LogConfig::default(),
+            catch_panics: false,
+            schedule: None,
+            timer_wheel: None,
         }
     }
 }
@@ -88,10 +205,94 @@ impl<E> RetryConfig<E> {
             max_attempts,
             delay,
             strategy,
+            max_delay: None,
             retry_condition: None,
+            error_classifier: None,
+            policies: PolicyTable::default(),
+            stats: None,
+            log: LogConfig::default(),
+            catch_panics: false,
+            schedule: None,
+            timer_wheel: None,
         }
     }
 
+    /// Returns a builder that checks at compile time that the strategy settings make sense.
+    ///
+    /// See `RetryConfigBuilder` for the available settings.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use resilient_rs::config::RetryConfig;
+    ///
+    /// let config: RetryConfig<String> = RetryConfig::builder()
+    ///     .max_attempts(5)
+    ///     .delay(Duration::from_millis(100))
+    ///     .exponential()
+    ///     .jitter(0.2)
+    ///     .max_delay(Duration::from_secs(10))
+    ///     .build();
+    /// assert_eq!(config.max_attempts(), 5);
+    /// ```
+    pub fn builder() -> RetryConfigBuilder<E> {
+        RetryConfigBuilder::new()
+    }
+
+    /// A preset for calls over the network, such as HTTP requests or database queries.
+    ///
+    /// Transient network failures usually clear within a few seconds, and many clients failing
+    /// at once must not retry in lockstep. This preset makes 5 attempts starting at 100ms with
+    /// `ExponentialBackoffWithJitter` and a jitter factor of 0.2, capping every delay at 10
+    /// seconds.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use resilient_rs::config::RetryConfig;
+    ///
+    /// let config: RetryConfig<std::io::Error> = RetryConfig::network_default();
+    /// assert_eq!(config.max_attempts(), 5);
+    /// assert_eq!(config.max_delay(), Some(Duration::from_secs(10)));
+    /// ```
+    pub fn network_default() -> Self {
+        Self::new(
+            5,
+            Duration::from_millis(100),
+            RetryStrategy::ExponentialBackoffWithJitter { jitter_factor: 0.2 },
+        )
+        .with_max_delay(Duration::from_secs(10))
+    }
+
+    /// A preset retrying quickly and often, for cheap, idempotent operations on a latency
+    /// sensitive path, such as reading from a local cache or a replica.
+    ///
+    /// This preset makes 8 attempts starting at 10ms with `ExponentialBackoffWithJitter` and a
+    /// jitter factor of 0.5, capping every delay at 500ms, so a caller gives up within a couple
+    /// of seconds.
+    pub fn aggressive() -> Self {
+        Self::new(
+            8,
+            Duration::from_millis(10),
+            RetryStrategy::ExponentialBackoffWithJitter { jitter_factor: 0.5 },
+        )
+        .with_max_delay(Duration::from_millis(500))
+    }
+
+    /// A preset retrying rarely and patiently, for expensive or rate-limited operations, such as
+    /// calls to a third-party API or batch jobs.
+    ///
+    /// This preset makes 3 attempts starting at 1 second with `ExponentialBackoffWithJitter` and
+    /// a jitter factor of 0.2, capping every delay at 30 seconds.
+    pub fn conservative() -> Self {
+        Self::new(
+            3,
+            Duration::from_secs(1),
+            RetryStrategy::ExponentialBackoffWithJitter { jitter_factor: 0.2 },
+        )
+        .with_max_delay(Duration::from_secs(30))
+    }
+
     /// Sets a custom retry condition and returns the modified `RetryConfig`.
     ///
     /// This method allows you to specify a function that determines whether an operation should
@@ -119,6 +320,39 @@ impl<E> RetryConfig<E> {
         self
     }
 
+    /// Sets the maximum number of attempts and returns the modified `RetryConfig`.
+    ///
+    /// # Arguments
+    /// * `max_attempts` - The maximum number of attempts (including the initial attempt).
+    ///
+    /// # Examples
+    /// ```
+    /// use resilient_rs::config::RetryConfig;
+    /// let config: RetryConfig<()> = RetryConfig::default().with_max_attempts(5);
+    /// assert_eq!(config.max_attempts(), 5);
+    /// ```
+    pub fn with_max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Sets the base delay between attempts and returns the modified `RetryConfig`.
+    ///
+    /// # Arguments
+    /// * `delay` - The base duration to wait between retry attempts.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use resilient_rs::config::RetryConfig;
+    /// let config: RetryConfig<()> = RetryConfig::default().with_delay(Duration::from_millis(250));
+    /// assert_eq!(config.delay(), Duration::from_millis(250));
+    /// ```
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
     /// Sets a custom retry strategy and returns the modified `RetryConfig`.
     ///
     /// This method allows you to specify the retry strategy (`Linear` or `ExponentialBackoff`).
@@ -143,236 +377,3274 @@ impl<E> RetryConfig<E> {
         self.strategy = strategy;
         self
     }
-}
-
-/// Configuration for executable tasks supporting both synchronous and asynchronous operations.
-///
-/// This struct defines execution parameters for tasks that may run either synchronously
-/// or asynchronously, including a timeout duration and an optional fallback function.
-/// It's designed to be passed to functions that handle task execution with support for
-/// both execution models.
-///
-/// # Type Parameters
-/// * `T` - The type of the successful result, must implement `Clone`
-/// * `E` - The type of the error that may occur during execution
-///
-#[derive(Debug)]
-pub struct ExecConfig<T> {
-    /// The maximum duration allowed for task execution before timeout.
-    ///
-    /// This applies to both synchronous and asynchronous operations. For async operations,
-    /// this typically integrates with runtime timeout mechanisms.
-    pub timeout_duration: Duration,
-
-    /// Optional fallback function to execute if the primary task fails or times out.
-    ///
-    /// The fallback must be a synchronous function that returns a `Result`. For async
-    /// contexts, the execution function is responsible for handling the sync-to-async
-    /// transition if needed.
-    pub fallback: Option<fn() -> Result<T, Box<dyn Error>>>,
-}
 
-impl<T> ExecConfig<T>
-where
-    T: Clone,
-{
-    /// Creates a new execution configuration with the specified timeout duration.
+    /// Sets an error classifier and returns the modified `RetryConfig`.
     ///
-    /// Initializes the configuration without a fallback function. This is suitable
-    /// for both synchronous and asynchronous task execution scenarios.
+    /// The classifier decides, per error, whether the operation is retried with the configured
+    /// strategy (`Transient`), retried after a suggested delay (`Throttled`), or abandoned
+    /// (`Permanent` or `Fatal`). It takes precedence over `retry_condition`.
     ///
     /// # Arguments
-    /// * `timeout_duration` - Maximum execution time for sync or async operations
+    /// * `classifier` - Any `ErrorClassifier<E>`, including closures of the form `Fn(&E) -> ErrorClass`.
     ///
     /// # Returns
-    /// A new `ExecConfig` instance configured with the given timeout
-    pub fn new(timeout_duration: Duration) -> Self {
-        ExecConfig {
-            timeout_duration,
-            fallback: None,
-        }
-    }
-
-    /// Sets a fallback function to handle task failure or timeout scenarios.
-    ///
-    /// The fallback is a synchronous function, but can be used in both sync and async
-    /// execution contexts. When used with async operations, the executing function
-    /// should handle any necessary async adaptation.
+    /// The updated `RetryConfig` with the specified error classifier.
     ///
-    /// # Arguments
-    /// * `fallback` - Synchronous function returning a `Result` with matching types
-    pub fn with_fallback(&mut self, fallback: fn() -> Result<T, Box<dyn Error>>) {
-        self.fallback = Some(fallback);
-    }
-}
-
-/// Configuration for a Circuit Breaker.
-///
-/// The `CircuitBreakerConfig` struct holds the static configuration parameters for a circuit breaker.
-/// It defines how the circuit breaker behaves during different states (Closed, Open, and HalfOpen).
-/// These settings determine the thresholds for failures, successes, and the cooldown period for recovery attempts.
-///
-/// Use this struct to configure and initialize a `CircuitBreaker` instance with specific settings.
-///
-/// # Fields
-/// - `failure_threshold`: The maximum number of consecutive failures before the circuit breaker transitions
-///   from `Close` to `Open`. This threshold determines how sensitive the circuit breaker is to failures.
-/// - `success_threshold`: The number of successful operations required in the `HalfOpen` state before
-///   transitioning back to `Close`. This determines how many recovery attempts the system will test before
-///   considering the service restored.
-/// - `cooldown_period`: The duration to wait in the `Open` state before transitioning to `HalfOpen` to test
-///   if the system has recovered. This period allows the failing system time to stabilize and prevents
-///   immediate retries.
-///
-/// # Example
-/// ```
-/// use std::time::Duration;
-/// use resilient_rs::config::CircuitBreakerConfig;
-///
-/// let config = CircuitBreakerConfig::new(3, 5, Duration::from_secs(10));
-/// println!("{:?}", config);
-/// ```
-
-#[derive(Debug, Clone, Copy)]
-pub struct CircuitBreakerConfig {
-    pub failure_threshold: usize,
-    pub success_threshold: usize,
-    pub cooldown_period: Duration,
-}
-
-impl Default for CircuitBreakerConfig {
-    /// # Default Configuration
-    /// The default configuration sets:
-    /// - `failure_threshold` to 5 (max failures before opening the circuit)
-    /// - `success_threshold` to 2 (successes required to close the circuit from HalfOpen)
-    /// - `cooldown_period` to 2 seconds (time to wait before testing recovery)
-    fn default() -> Self {
-        Self {
-            success_threshold: 2,
-            failure_threshold: 5,
-            cooldown_period: Duration::from_secs(2),
-        }
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use resilient_rs::classifier::ErrorClass;
+    /// use resilient_rs::config::RetryConfig;
+    /// let config = RetryConfig::default().with_error_classifier(|e: &String| {
+    ///     if e.contains("429") {
+    ///         ErrorClass::Throttled { retry_after: Some(Duration::from_secs(1)) }
+    ///     } else {
+    ///         ErrorClass::Transient
+    ///     }
+    /// });
+    /// ```
+    pub fn with_error_classifier(
+        mut self,
+        classifier: impl ErrorClassifier<E> + Send + Sync + 'static,
+    ) -> Self {
+        self.error_classifier = Some(Arc::new(classifier));
+        self
     }
-}
 
-impl CircuitBreakerConfig {
-    /// Creates a new `CircuitBreakerConfig` instance with the specified settings.
+    /// Sets the per-class retry policies and returns the modified `RetryConfig`.
     ///
-    /// This method constructs a configuration object for a circuit breaker based on the provided thresholds
-    /// and cooldown period. The configuration defines how the circuit breaker will behave during operation.
-    ///
-    /// # Parameters
-    /// - `success_threshold`: The number of successful operations required in the `HalfOpen` state
-    ///   to transition back to `Close`. This must be greater than 0 for meaningful recovery.
-    /// - `failure_threshold`: The number of consecutive failures in the `Close` state that will trigger
-    ///   a transition to `Open`. This must be greater than 0.
-    /// - `cooldown_period`: The duration to wait in the `Open` state before moving to `HalfOpen` to test
-    ///   recovery. Should be long enough to allow the system to stabilize and prevent immediate retries.
+    /// # Arguments
+    /// * `policies` - A `PolicyTable` mapping error classes to their own retry policy.
     ///
     /// # Returns
-    /// A new `CircuitBreakerConfig` instance with the provided parameters.
+    /// The updated `RetryConfig` with the specified policy table.
     ///
-    /// # Panics
-    /// This function will panic if any parameter is invalid (e.g., zero or negative values for thresholds).
-    ///
-    /// # Example
+    /// # Examples
     /// ```
     /// use std::time::Duration;
-    /// use resilient_rs::config::CircuitBreakerConfig;
-    /// let config = CircuitBreakerConfig::new(3, 5, Duration::from_secs(10));
-    /// assert_eq!(config.failure_threshold, 5);
+    /// use resilient_rs::classifier::ErrorClass;
+    /// use resilient_rs::config::{ClassPolicy, PolicyTable, RetryConfig};
+    /// use resilient_rs::strategies::RetryStrategy;
+    /// let config = RetryConfig::default()
+    ///     .with_error_classifier(|e: &String| {
+    ///         if e.contains("429") {
+    ///             ErrorClass::Throttled { retry_after: None }
+    ///         } else {
+    ///             ErrorClass::Transient
+    ///         }
+    ///     })
+    ///     .with_policies(
+    ///         PolicyTable::new()
+    ///             .on_throttled(ClassPolicy::new(10, Duration::from_secs(1), RetryStrategy::ExponentialBackoff))
+    ///             .on_transient(ClassPolicy::new(3, Duration::from_millis(50), RetryStrategy::Linear)),
+    ///     );
     /// ```
-    pub fn new(
-        success_threshold: usize,
-        failure_threshold: usize,
-        cooldown_period: Duration,
-    ) -> Self {
-        assert!(
-            success_threshold > 0,
-            "success_threshold must be greater than 0"
-        );
-        assert!(
-            failure_threshold > 0,
-            "failure_threshold must be greater than 0"
-        );
-        assert!(
-            cooldown_period > Duration::ZERO,
-            "cooldown_period must be non-zero"
-        );
-
-        Self {
-            failure_threshold,
-            success_threshold,
-            cooldown_period,
-        }
+    pub fn with_policies(mut self, policies: PolicyTable) -> Self {
+        self.policies = policies;
+        self
     }
 
-    /// Builder-style setter for `failure_threshold`.
+    /// Sets a statistics handle and returns the modified `RetryConfig`.
     ///
-    /// This method allows you to modify the `failure_threshold` value after the initial configuration.
-    /// It enables more flexible configuration using a builder pattern.
+    /// The handle is shared: clone it before passing it in to keep a reference for reading.
     ///
-    /// # Parameters
-    /// - `threshold`: The number of failures required to trigger a transition to `Open`. Must be greater than 0.
+    /// # Arguments
+    /// * `stats` - The `Stats` handle to update on every attempt.
     ///
     /// # Returns
-    /// A new `CircuitBreakerConfig` instance with the updated `failure_threshold`.
+    /// The updated `RetryConfig` with the specified statistics handle.
     ///
-    /// # Example
+    /// # Examples
     /// ```
-    /// use resilient_rs::config::CircuitBreakerConfig;
-    /// let config = CircuitBreakerConfig::default().with_failure_threshold(3);
-    /// assert_eq!(config.failure_threshold, 3);
+    /// use resilient_rs::config::RetryConfig;
+    /// use resilient_rs::stats::Stats;
+    /// let stats = Stats::new();
+    /// let config: RetryConfig<()> = RetryConfig::default().with_stats(stats.clone());
     /// ```
-    pub fn with_failure_threshold(mut self, threshold: usize) -> Self {
-        assert!(threshold > 0, "failure_threshold must be greater than 0");
-        self.failure_threshold = threshold;
+    pub fn with_stats(mut self, stats: Stats) -> Self {
+        self.stats = Some(stats);
         self
     }
 
-    /// Builder-style setter for `success_threshold`.
-    ///
-    /// This method allows you to modify the `success_threshold` value after the initial configuration.
-    /// It enables more flexible configuration using a builder pattern.
+    /// Sets the logging behavior and returns the modified `RetryConfig`.
     ///
-    /// # Parameters
-    /// - `threshold`: The number of successes required to close the circuit from the `HalfOpen` state. Must be greater than 0.
+    /// # Arguments
+    /// * `log` - The `LogConfig` controlling level, verbosity and target of retry messages.
     ///
     /// # Returns
-    /// A new `CircuitBreakerConfig` instance with the updated `success_threshold`.
+    /// The updated `RetryConfig` with the specified logging behavior.
     ///
-    /// # Example
+    /// # Examples
     /// ```
-    /// use resilient_rs::config::CircuitBreakerConfig;
-    /// let config = CircuitBreakerConfig::default().with_success_threshold(4);
-    /// assert_eq!(config.success_threshold, 4);
+    /// use resilient_rs::logging::Level;
+    /// use resilient_rs::config::{LogConfig, RetryConfig};
+    /// let config: RetryConfig<()> = RetryConfig::default()
+    ///     .with_log(LogConfig::new(Level::Debug).with_target("payments"));
     /// ```
-    pub fn with_success_threshold(mut self, threshold: usize) -> Self {
-        assert!(threshold > 0, "success_threshold must be greater than 0");
-        self.success_threshold = threshold;
+    pub fn with_log(mut self, log: LogConfig) -> Self {
+        self.log = log;
         self
     }
 
-    /// Builder-style setter for `cooldown_period`.
+    /// Caps the delay between retry attempts and returns the modified `RetryConfig`.
     ///
-    /// This method allows you to modify the `cooldown_period` value after the initial configuration.
-    /// It enables more flexible configuration using a builder pattern.
+    /// # Arguments
+    /// * `max_delay` - The longest delay the strategy, or a `Throttled` error's `retry_after` hint,
+    ///   may produce.
     ///
-    /// # Parameters
-    /// - `period`: The duration to wait before testing the recovery of the system. Must be greater than 0.
+    /// # Returns
+    /// The updated `RetryConfig` with the specified delay cap.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use resilient_rs::config::RetryConfig;
+    /// use resilient_rs::strategies::RetryStrategy;
+    /// let config: RetryConfig<()> = RetryConfig::new(10, Duration::from_millis(100), RetryStrategy::ExponentialBackoff)
+    ///     .with_max_delay(Duration::from_secs(5));
+    /// ```
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = Some(max_delay);
+        self
+    }
+
+    /// Retries panics of the operation as `Transient` failures, and returns the modified
+    /// `RetryConfig`.
+    ///
+    /// The panic of the last attempt is resumed once the attempts are exhausted, so a bug that
+    /// panics every time still surfaces. The operation must not leave shared state broken when it
+    /// panics, since the next attempt observes it.
     ///
     /// # Returns
-    /// A new `CircuitBreakerConfig` instance with the updated `cooldown_period`.
+    /// The updated `RetryConfig` catching panics.
     ///
-    /// # Example
+    /// # Examples
     /// ```
-    /// use resilient_rs::config::CircuitBreakerConfig;
-    /// let config = CircuitBreakerConfig::default().with_cooldown_period(std::time::Duration::from_secs(5));
-    /// assert_eq!(config.cooldown_period, std::time::Duration::from_secs(5));
+    /// use std::time::Duration;
+    /// use resilient_rs::config::RetryConfig;
+    /// use resilient_rs::strategies::RetryStrategy;
+    /// use resilient_rs::synchronous::retry;
+    ///
+    /// let config = RetryConfig::new(3, Duration::from_millis(1), RetryStrategy::Linear)
+    ///     .with_catch_panics();
+    /// let mut calls = 0;
+    /// let result: Result<_, String> = retry(|| {
+    ///     calls += 1;
+    ///     // A third-party parser panics while the file is still being written.
+    ///     assert!(calls > 1, "unexpected end of file");
+    ///     Ok(calls)
+    /// }, &config);
+    /// assert_eq!(result, Ok(2));
     /// ```
-    pub fn with_cooldown_period(mut self, period: Duration) -> Self {
-        assert!(period > Duration::ZERO, "cooldown_period must be non-zero");
-        self.cooldown_period = period;
+    pub fn with_catch_panics(mut self) -> Self {
+        self.catch_panics = true;
+        self
+    }
+
+    /// Precomputes the delays of the retries, so each retry looks its delay up instead of
+    /// computing it.
+    ///
+    /// This is worth it for hot retry paths using strategies with costly math, such as the
+    /// floating-point `ExponentialBackoffWithJitter` or `FibonacciBackoff`, whose delay is
+    /// recomputed from scratch on every retry. The schedule covers `max_attempts` attempts and is
+    /// computed from the current `delay`, `strategy` and `max_delay`, so call this after setting
+    /// them. Jittered delays are drawn once, here, and shared by every retry loop using the
+    /// configuration.
+    ///
+    /// # Returns
+    /// The updated `RetryConfig` with its `schedule` set.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use resilient_rs::config::RetryConfig;
+    /// use resilient_rs::strategies::RetryStrategy;
+    ///
+    /// let config: RetryConfig<()> =
+    ///     RetryConfig::new(5, Duration::from_millis(100), RetryStrategy::FibonacciBackoff)
+    ///         .with_max_delay(Duration::from_millis(250))
+    ///         .with_precomputed_schedule();
+    /// let delays: Vec<u64> = config
+    ///     .schedule()
+    ///     .unwrap()
+    ///     .delays()
+    ///     .iter()
+    ///     .map(|delay| delay.as_millis() as u64)
+    ///     .collect();
+    /// assert_eq!(delays, [100, 100, 200, 250, 250]);
+    /// ```
+    pub fn with_precomputed_schedule(mut self) -> Self {
+        self.schedule = Some(DelaySchedule::compute(
+            self.delay,
+            self.strategy,
+            self.max_delay,
+            self.max_attempts,
+        ));
+        self
+    }
+
+    /// Sets a shared timer wheel for the backoff sleeps of the asynchronous retry loops.
+    ///
+    /// Share one wheel between the configurations of an application retrying many operations at
+    /// once; its sleeps may last up to one tick of the wheel longer than the computed delays.
+    ///
+    /// # Arguments
+    /// * `wheel` - The `TimerWheel` to sleep on.
+    ///
+    /// # Returns
+    /// The updated `RetryConfig` with its `timer_wheel` set.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use resilient_rs::config::RetryConfig;
+    /// use resilient_rs::wheel::TimerWheel;
+    ///
+    /// let wheel = TimerWheel::new(Duration::from_millis(10));
+    /// let config: RetryConfig<String> = RetryConfig::default().with_timer_wheel(wheel.clone());
+    /// ```
+    pub fn with_timer_wheel(mut self, wheel: TimerWheel) -> Self {
+        self.timer_wheel = Some(wheel);
         self
     }
+
+    /// Returns a `Backoff` producing the delays of this configuration one at a time.
+    ///
+    /// The backoff carries over the `delay`, `strategy`, `max_delay`, `max_attempts` and
+    /// `schedule` of the configuration, so a hand-written loop waits what `retry` would have.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use resilient_rs::config::RetryConfig;
+    /// use resilient_rs::strategies::RetryStrategy;
+    ///
+    /// let config: RetryConfig<()> =
+    ///     RetryConfig::new(3, Duration::from_millis(50), RetryStrategy::Linear);
+    /// let mut backoff = config.backoff();
+    /// assert_eq!(backoff.next_delay(), Some(Duration::from_millis(50)));
+    /// assert_eq!(backoff.next_delay(), Some(Duration::from_millis(50)));
+    /// assert_eq!(backoff.next_delay(), None);
+    /// ```
+    pub fn backoff(&self) -> Backoff {
+        let backoff = Backoff::new(self.delay, self.strategy)
+            .with_max_attempts(self.max_attempts)
+            .with_schedule(self.schedule.clone());
+        match self.max_delay {
+            Some(max_delay) => backoff.with_max_delay(max_delay),
+            None => backoff,
+        }
+    }
+
+    /// Turns the configuration into a retrying version of `operation`.
+    ///
+    /// Every call of the returned closure runs `operation` with the retries of this
+    /// configuration, like `synchronous::retry`, so it can be handed to code that expects a
+    /// plain `FnMut() -> Result<T, E>` and knows nothing about retries. Arguments are passed by
+    /// capturing them in `operation`.
+    ///
+    /// # Arguments
+    /// * `operation` - The fallible operation to protect.
+    ///
+    /// # Returns
+    /// A closure retrying `operation` each time it is called.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use resilient_rs::config::RetryConfig;
+    /// use resilient_rs::strategies::RetryStrategy;
+    ///
+    /// fn warm_up(mut load: impl FnMut() -> Result<u32, String>) -> u32 {
+    ///     load().unwrap_or(0)
+    /// }
+    ///
+    /// let mut calls = 0;
+    /// let load = RetryConfig::new(3, Duration::from_millis(1), RetryStrategy::Linear).decorate(|| {
+    ///     calls += 1;
+    ///     if calls < 3 { Err("cache not ready".to_string()) } else { Ok(calls) }
+    /// });
+    /// assert_eq!(warm_up(load), 3);
+    /// ```
+    pub fn decorate<F, T>(self, mut operation: F) -> impl FnMut() -> Result<T, E>
+    where
+        F: FnMut() -> Result<T, E>,
+    {
+        move || crate::synchronous::retry(&mut operation, &self)
+    }
+
+    /// Turns the configuration into a retrying version of an asynchronous `operation`.
+    ///
+    /// Every call of the returned async closure runs `operation` with the retries of this
+    /// configuration, like `asynchronous::retry`, so it can be handed to code that expects an
+    /// `AsyncFnMut() -> Result<T, E>`.
+    ///
+    /// # Arguments
+    /// * `operation` - A closure returning a `Future` resolving to a `Result<T, E>`.
+    ///
+    /// # Returns
+    /// An async closure retrying `operation` each time it is called.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use async_std::task::block_on;
+    /// use resilient_rs::config::RetryConfig;
+    /// use resilient_rs::strategies::RetryStrategy;
+    ///
+    /// let mut ping = RetryConfig::new(3, Duration::from_millis(1), RetryStrategy::Linear)
+    ///     .decorate_async(|| async { Ok::<_, String>("pong") });
+    /// assert_eq!(block_on(ping()), Ok("pong"));
+    /// ```
+    pub fn decorate_async<F, Fut, T>(self, mut operation: F) -> impl AsyncFnMut() -> Result<T, E>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        async move || crate::asynchronous::retry(&mut operation, &self).await
+    }
+
+    /// Creates a new `RetryConfig`, returning an error if the settings are invalid.
+    ///
+    /// This is `new` followed by `validate`, and is the preferred constructor when the values
+    /// come from configuration files or user input.
+    ///
+    /// # Arguments
+    /// * `max_attempts` - The maximum number of attempts (including the initial attempt).
+    /// * `delay` - The base duration to wait between retry attempts.
+    /// * `strategy` - The retry strategy to use.
+    ///
+    /// # Returns
+    /// The configuration, or a `ConfigError::InvalidValue` naming the offending field.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use resilient_rs::config::RetryConfig;
+    /// use resilient_rs::strategies::RetryStrategy;
+    ///
+    /// assert!(RetryConfig::<()>::try_new(0, Duration::from_secs(1), RetryStrategy::Linear).is_err());
+    /// ```
+    pub fn try_new(
+        max_attempts: usize,
+        delay: Duration,
+        strategy: RetryStrategy,
+    ) -> Result<Self, ConfigError> {
+        let config = Self::new(max_attempts, delay, strategy);
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks that the configuration is usable, including every per-class policy.
+    ///
+    /// The following settings are rejected:
+    /// - `max_attempts` of 0, which would never run the operation.
+    /// - A zero `delay` combined with more than 100 attempts, which turns a retry loop into a busy loop.
+    /// - A `jitter_factor` that is negative, greater than 1.0 or not a finite number.
+    /// - An `ArithmeticProgression` `coefficient` of 0, which produces zero delays.
+    /// - A `max_delay` smaller than `delay`.
+    ///
+    /// # Returns
+    /// `Ok(())`, or a `ConfigError::InvalidValue` naming the first offending field.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use resilient_rs::config::RetryConfig;
+    /// use resilient_rs::strategies::RetryStrategy;
+    ///
+    /// let config: RetryConfig<()> = RetryConfig::new(
+    ///     3,
+    ///     Duration::from_millis(100),
+    ///     RetryStrategy::ExponentialBackoffWithJitter { jitter_factor: -0.5 },
+    /// );
+    /// assert!(config.validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        validate_schedule(
+            self.max_attempts,
+            self.delay,
+            &self.strategy,
+            ["max_attempts", "delay", "strategy"],
+        )?;
+        if let Some(max_delay) = self.max_delay
+            && max_delay < self.delay
+        {
+            return Err(ConfigError::invalid(
+                "max_delay",
+                "must not be smaller than delay",
+            ));
+        }
+        if let Some(policy) = &self.policies.transient {
+            validate_schedule(
+                policy.max_attempts,
+                policy.delay,
+                &policy.strategy,
+                [
+                    "policies.transient.max_attempts",
+                    "policies.transient.delay",
+                    "policies.transient.strategy",
+                ],
+            )?;
+        }
+        if let Some(policy) = &self.policies.throttled {
+            validate_schedule(
+                policy.max_attempts,
+                policy.delay,
+                &policy.strategy,
+                [
+                    "policies.throttled.max_attempts",
+                    "policies.throttled.delay",
+                    "policies.throttled.strategy",
+                ],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Returns the maximum number of retry attempts.
+    ///
+    /// This specifies how many times the operation will be retried before
+    /// giving up. For example, if `max_attempts` is set to 3, the operation
+    /// will be attempted up to 3 times (1 initial attempt + 2 retries).
+    pub fn max_attempts(&self) -> usize {
+        self.max_attempts
+    }
+
+    /// Returns the delay between retry attempts.
+    ///
+    /// This specifies the base amount of time to wait between each retry attempt.
+    /// The actual delay may vary depending on the `strategy`. For example, if
+    /// `delay` is set to `Duration::from_secs(2)` and the strategy is `Linear`,
+    /// the program will wait 2 seconds between retries.
+    ///
+    /// Delays shorter than a millisecond, including zero, do not sleep: `asynchronous::retry`
+    /// yields to the executor between the attempts and `synchronous::retry` yields the thread.
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+
+    /// Returns the strategy used to calculate delays between retry attempts.
+    ///
+    /// It determines how the `delay` is applied:
+    /// - `Linear`: Uses a fixed delay between retries.
+    /// - `ExponentialBackoff`: Increases the delay exponentially with each retry.
+    /// - `FibonacciBackoff`: Increases the delay following the Fibonacci sequence with each retry.
+    /// - `Polynomial`: Increases the delay with a power of the retry number.
+    /// - `Harmonic`: Increases the delay with the harmonic numbers, ever more slowly.
+    pub fn strategy(&self) -> RetryStrategy {
+        self.strategy
+    }
+
+    /// Returns the upper bound for the delay between retry attempts, if any.
+    ///
+    /// Delays computed by the `strategy` (including those of per-class policies) are capped at
+    /// this value, which keeps exponential strategies from growing without limit. A `retry_after`
+    /// hint from a `Throttled` classification is honored up to this value too, so a server cannot
+    /// park the caller with a huge hint. If set to `None` (the default), delays are not capped.
+    pub fn max_delay(&self) -> Option<Duration> {
+        self.max_delay
+    }
+
+    /// Returns the function deciding whether an error is retried, if any.
+    ///
+    /// It takes a reference to the error (`&E`) and returns a `bool`:
+    /// - `true` if the operation should be retried.
+    /// - `false` if the operation should not be retried, causing it to fail immediately.
+    ///
+    /// If set to `None` (the default), all errors will trigger a retry up to `max_attempts`.
+    /// If set to `Some(fn)`, only errors for which the function returns `true` will be retried.
+    pub fn retry_condition(&self) -> Option<fn(&E) -> bool> {
+        self.retry_condition
+    }
+
+    /// Returns the classifier mapping errors to an `ErrorClass`, if any.
+    ///
+    /// When set, it takes precedence over `retry_condition` and lets the retry loop react to
+    /// each failure individually:
+    /// - `Transient`: retry using the configured `strategy`.
+    /// - `Throttled`: retry after the suggested `retry_after` delay, if any, capped at `max_delay`.
+    /// - `Permanent` / `Fatal`: give up immediately.
+    ///
+    /// If set to `None` (the default), `retry_condition` decides whether an error is retried.
+    pub fn error_classifier(&self) -> Option<&(dyn ErrorClassifier<E> + Send + Sync)> {
+        self.error_classifier.as_deref()
+    }
+
+    /// Returns the per-class retry policies overriding `max_attempts`, `delay` and `strategy`.
+    ///
+    /// When the error of a failed attempt is classified as an `ErrorClass` that has a policy in
+    /// this table, that policy decides how many attempts are allowed and how long to wait before
+    /// the next one. Classes without a policy use the top-level settings. The table is empty by default.
+    pub fn policies(&self) -> &PolicyTable {
+        &self.policies
+    }
+
+    /// Returns the statistics handle updated by the retry functions, if any.
+    ///
+    /// When set, every attempt, success, give-up and backoff delay is recorded in the shared
+    /// `Stats` handle. If set to `None` (the default), no statistics are collected.
+    pub fn stats(&self) -> Option<&Stats> {
+        self.stats.as_ref()
+    }
+
+    /// Returns the logging behavior of the retry functions for this policy.
+    ///
+    /// Controls the level of the per-attempt retry messages, whether anything is logged at all,
+    /// and the log target. By default, retries are logged at `Warn` under the module's target.
+    pub fn log(&self) -> &LogConfig {
+        &self.log
+    }
+
+    /// Returns whether a panic of the operation counts as a failed attempt.
+    ///
+    /// When `true`, the retry loops of `synchronous::retry` and `asynchronous::retry`, and the
+    /// functions built on them, catch a panicking attempt, retry it like a `Transient` error and
+    /// resume the panic of the last attempt once the attempts are exhausted. When `false` (the
+    /// default), a panic propagates out of the loop immediately.
+    pub fn catch_panics(&self) -> bool {
+        self.catch_panics
+    }
+
+    /// Returns the schedule of precomputed delays set by `with_precomputed_schedule`, if any.
+    ///
+    /// The retry loops look their delays up in the schedule as long as `delay`, `strategy` and
+    /// `max_delay` keep the values it was computed for, and compute them on every retry otherwise,
+    /// or past the end of the schedule. If set to `None` (the default), delays are computed on
+    /// every retry. The schedule is not serialized.
+    pub fn schedule(&self) -> Option<&DelaySchedule> {
+        self.schedule.as_ref()
+    }
+
+    /// Returns the timer wheel the asynchronous retry loops sleep on between attempts, if any.
+    ///
+    /// When set, the backoff sleeps of `asynchronous::retry` and the functions built on it are
+    /// registered with the shared `TimerWheel` instead of creating one runtime timer each. If set
+    /// to `None` (the default), every sleep uses its own timer. The wheel is not serialized.
+    pub fn timer_wheel(&self) -> Option<&TimerWheel> {
+        self.timer_wheel.as_ref()
+    }
+
+    /// Records into the statistics handle, if one is configured.
+    pub(crate) fn record(&self, record: impl FnOnce(&Stats)) {
+        if let Some(stats) = &self.stats {
+            record(stats);
+        }
+    }
+
+    /// Returns the attempt budget and the delay to wait after a failure of the given class.
+    ///
+    /// If the class has a policy in `policies`, the budget is the policy's `max_attempts` and the
+    /// delay is computed from the policy's own `delay` and `strategy` for the given 1-based `retry`.
+    /// Otherwise, the top-level `max_attempts` and the running `delay` are returned.
+    pub(crate) fn budget_for(
+        &self,
+        class: &ErrorClass,
+        retry: usize,
+        delay: Duration,
+    ) -> (usize, Duration) {
+        match self.policies.policy_for(class) {
+            Some(policy) => (
+                policy.max_attempts,
+                self.capped(policy.strategy.calculate_delay(policy.delay, retry)),
+            ),
+            None => (self.max_attempts, self.capped(delay)),
+        }
+    }
+
+    /// Returns a copy of the configuration doubling the delay after every retry, the schedule of
+    /// the deprecated `retry_with_exponential_backoff` functions.
+    pub(crate) fn doubling(&self) -> Self {
+        let mut config = self
+            .clone()
+            .with_strategy(RetryStrategy::ExponentialBackoff);
+        config.schedule = Some(DelaySchedule::doubling(
+            config.delay,
+            config.max_delay,
+            config.attempt_limit(),
+        ));
+        config
+    }
+
+    /// Returns the largest number of attempts any error class may be given.
+    pub(crate) fn attempt_limit(&self) -> usize {
+        [&self.policies.transient, &self.policies.throttled]
+            .into_iter()
+            .flatten()
+            .map(|policy| policy.max_attempts)
+            .fold(self.max_attempts, usize::max)
+    }
+
+    /// Returns the running delay after the given 1-based `retry`, grown from the previous `delay`.
+    ///
+    /// The delay is looked up in the `schedule` when it covers the retry, and computed from the
+    /// `strategy` and capped otherwise.
+    pub(crate) fn next_delay(&self, delay: Duration, retry: usize) -> Duration {
+        self.schedule
+            .as_ref()
+            .and_then(|schedule| schedule.get(self.delay, self.strategy, self.max_delay, retry))
+            .unwrap_or_else(|| self.capped(self.strategy.calculate_delay(delay, retry)))
+    }
+
+    /// Applies `max_delay` to a delay computed by a strategy.
+    pub(crate) fn capped(&self, delay: Duration) -> Duration {
+        match self.max_delay {
+            Some(max_delay) => delay.min(max_delay),
+            None => delay,
+        }
+    }
+
+    /// Classifies an error using the `error_classifier`, falling back to `retry_condition`.
+    ///
+    /// Without a classifier, errors rejected by `retry_condition` are `Permanent` and every
+    /// other error is `Transient`.
+    pub(crate) fn classify(&self, err: &E) -> ErrorClass {
+        if let Some(classifier) = &self.error_classifier {
+            return classifier.classify(err);
+        }
+        match self.retry_condition {
+            Some(condition) if !condition(err) => ErrorClass::Permanent,
+            _ => ErrorClass::Transient,
+        }
+    }
+}
+
+/// A classifier over type-erased errors, used by `RetryPolicy`.
+pub type DynErrorClassifier = Arc<dyn ErrorClassifier<dyn Error + 'static> + Send + Sync>;
+
+/// A retry policy that is not generic over the error type.
+///
+/// A `RetryConfig<E>` is tied to its error type by `retry_condition` and `error_classifier`,
+/// which makes policies for different error types hard to store together or to expose through
+/// `dyn`-friendly interfaces. A `RetryPolicy` holds the same settings with a classifier over
+/// `&dyn Error` instead, so heterogeneous policies fit in one collection or registry. It is
+/// converted from a `RetryConfig<E>` with `From`, and back with `to_config` when an operation is
+/// retried.
+///
+/// # Example
+/// ```
+/// use std::collections::HashMap;
+/// use std::io;
+/// use std::time::Duration;
+/// use resilient_rs::classifier::ErrorClass;
+/// use resilient_rs::config::{RetryConfig, RetryPolicy};
+/// use resilient_rs::strategies::RetryStrategy;
+/// use resilient_rs::synchronous::retry;
+///
+/// let mut policies: HashMap<&str, RetryPolicy> = HashMap::new();
+/// policies.insert(
+///     "disk",
+///     RetryConfig::<io::Error>::new(3, Duration::from_millis(1), RetryStrategy::Linear)
+///         .with_retry_condition(|err| err.kind() == io::ErrorKind::Interrupted)
+///         .into(),
+/// );
+/// policies.insert(
+///     "parse",
+///     RetryConfig::<std::num::ParseIntError>::new(1, Duration::ZERO, RetryStrategy::Linear).into(),
+/// );
+///
+/// let denied = io::Error::from(io::ErrorKind::PermissionDenied);
+/// assert_eq!(policies["disk"].classify(&denied), ErrorClass::Permanent);
+///
+/// let mut attempts = 0;
+/// let result: Result<(), io::Error> = retry(
+///     || {
+///         attempts += 1;
+///         Err(io::Error::from(io::ErrorKind::Interrupted))
+///     },
+///     &policies["disk"].to_config(),
+/// );
+/// assert!(result.is_err());
+/// assert_eq!(attempts, 3);
+/// ```
+#[derive(Clone)]
+pub struct RetryPolicy {
+    /// The maximum number of attempts, as in `RetryConfig::max_attempts`.
+    pub max_attempts: usize,
+    /// The base delay between attempts, as in `RetryConfig::delay`.
+    pub delay: Duration,
+    /// The strategy growing `delay` between attempts, as in `RetryConfig::strategy`.
+    pub strategy: RetryStrategy,
+    /// An optional upper bound for the delay, as in `RetryConfig::max_delay`.
+    pub max_delay: Option<Duration>,
+    /// An optional classifier of type-erased errors; when `None`, every error is `Transient`.
+    pub classifier: Option<DynErrorClassifier>,
+    /// Per-class retry policies, as in `RetryConfig::policies`.
+    pub policies: PolicyTable,
+    /// An optional statistics handle, as in `RetryConfig::stats`.
+    pub stats: Option<Stats>,
+    /// Logging behavior, as in `RetryConfig::log`.
+    pub log: LogConfig,
+    /// Whether a panic counts as a failed attempt, as in `RetryConfig::catch_panics`.
+    pub catch_panics: bool,
+    /// An optional schedule of precomputed delays, as in `RetryConfig::schedule`.
+    pub schedule: Option<DelaySchedule>,
+    /// An optional shared timer wheel, as in `RetryConfig::timer_wheel`.
+    pub timer_wheel: Option<TimerWheel>,
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("delay", &self.delay)
+            .field("strategy", &self.strategy)
+            .field("max_delay", &self.max_delay)
+            .field("classifier", &self.classifier.is_some())
+            .field("policies", &self.policies)
+            .field("stats", &self.stats)
+            .field("log", &self.log)
+            .field("catch_panics", &self.catch_panics)
+            .field("schedule", &self.schedule)
+            .field("timer_wheel", &self.timer_wheel)
+            .finish()
+    }
+}
+
+impl RetryPolicy {
+    /// Classifies a type-erased error with the `classifier`.
+    ///
+    /// # Returns
+    /// The class returned by the classifier, or `Transient` if there is none.
+    pub fn classify(&self, err: &(dyn Error + 'static)) -> ErrorClass {
+        match &self.classifier {
+            Some(classifier) => classifier.classify(err),
+            None => ErrorClass::Transient,
+        }
+    }
+
+    /// Returns a `RetryConfig` for operations failing with errors of type `E`, classifying their
+    /// errors with this policy's `classifier`.
+    pub fn to_config<E: Error + 'static>(&self) -> RetryConfig<E> {
+        let error_classifier = self.classifier.clone().map(|classifier| {
+            Arc::new(move |err: &E| classifier.classify(err))
+                as Arc<dyn ErrorClassifier<E> + Send + Sync>
+        });
+        RetryConfig {
+            max_attempts: self.max_attempts,
+            delay: self.delay,
+            strategy: self.strategy,
+            max_delay: self.max_delay,
+            retry_condition: None,
+            error_classifier,
+            policies: self.policies.clone(),
+            stats: self.stats.clone(),
+            log: self.log,
+            catch_panics: self.catch_panics,
+            schedule: self.schedule.clone(),
+            timer_wheel: self.timer_wheel.clone(),
+        }
+    }
+}
+
+impl<E: Error + 'static> From<RetryConfig<E>> for RetryPolicy {
+    /// Erases the error type of a `RetryConfig`.
+    ///
+    /// The `retry_condition` and `error_classifier` of the configuration become a classifier
+    /// that handles errors of type `E` as the configuration would, and errors of any other type
+    /// as `Transient`.
+    fn from(config: RetryConfig<E>) -> Self {
+        let classifier = if config.retry_condition.is_some() || config.error_classifier.is_some() {
+            let retry_condition = config.retry_condition;
+            let error_classifier = config.error_classifier.clone();
+            let classifier = move |err: &(dyn Error + 'static)| match err.downcast_ref::<E>() {
+                Some(err) => match (&error_classifier, retry_condition) {
+                    (Some(classifier), _) => classifier.classify(err),
+                    (None, Some(condition)) if !condition(err) => ErrorClass::Permanent,
+                    (None, _) => ErrorClass::Transient,
+                },
+                None => ErrorClass::Transient,
+            };
+            Some(Arc::new(classifier) as DynErrorClassifier)
+        } else {
+            None
+        };
+        RetryPolicy {
+            max_attempts: config.max_attempts,
+            delay: config.delay,
+            strategy: config.strategy,
+            max_delay: config.max_delay,
+            classifier,
+            policies: config.policies,
+            stats: config.stats,
+            log: config.log,
+            catch_panics: config.catch_panics,
+            schedule: config.schedule,
+            timer_wheel: config.timer_wheel,
+        }
+    }
+}
+
+/// Logging behavior of a resilience policy.
+///
+/// Every pattern logs routine events (a retry being scheduled, a fallback being used, a call
+/// rejected by an open breaker) at a configurable `level`, so expected transient failures don't
+/// have to flood logs at `Warn`. Outcome events (giving up, a breaker opening) keep their fixed
+/// levels. A `quiet` config suppresses every message, and `target` overrides the log target.
+/// With the `kv` feature, the retry messages also carry the attempt, the delay and the error
+/// class as structured key-value fields, with `target` as their `policy_name`.
+///
+/// # Fields
+/// - `level`: The level used for routine events. Defaults to `Warn`.
+/// - `quiet`: When `true`, nothing is logged for this policy. Defaults to `false`.
+/// - `target`: A custom log target; when `None`, the crate's module path is used. The target
+///   is not serialized with the `serde` feature.
+/// - `sample_every`: Logs only one in this many routine retry messages of a call: the first
+///   failure, then every `sample_every`-th one. Giving up is always logged. Defaults to `1`,
+///   logging every retry.
+/// - `diagnostics`: A `Diagnostics` hook receiving the messages instead of the `log` facade,
+///   set with `with_diagnostics`. Defaults to `None`; not serialized with the `serde` feature.
+///
+/// # Example
+/// ```
+/// use resilient_rs::logging::Level;
+/// use resilient_rs::config::LogConfig;
+///
+/// let verbose = LogConfig::new(Level::Debug).with_target("inventory-client");
+/// let silent = LogConfig::quiet();
+/// assert!(silent.quiet);
+/// assert_eq!(verbose.target, Some("inventory-client"));
+/// ```
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct LogConfig {
+    pub level: Level,
+    pub quiet: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub target: Option<&'static str>,
+    pub sample_every: usize,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub diagnostics: Option<&'static dyn Diagnostics>,
+}
+
+impl fmt::Debug for LogConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LogConfig")
+            .field("level", &self.level)
+            .field("quiet", &self.quiet)
+            .field("target", &self.target)
+            .field("sample_every", &self.sample_every)
+            .field("diagnostics", &self.diagnostics.is_some())
+            .finish()
+    }
+}
+
+impl PartialEq for LogConfig {
+    /// Two configs are equal if their settings are equal and they hand diagnostics to the same
+    /// hook, if any.
+    fn eq(&self, other: &Self) -> bool {
+        self.level == other.level
+            && self.quiet == other.quiet
+            && self.target == other.target
+            && self.sample_every == other.sample_every
+            && match (self.diagnostics, other.diagnostics) {
+                (Some(a), Some(b)) => std::ptr::addr_eq(a, b),
+                (a, b) => a.is_none() && b.is_none(),
+            }
+    }
+}
+
+impl Eq for LogConfig {}
+
+impl Default for LogConfig {
+    /// Logs routine events at `Warn` under the module's own target.
+    fn default() -> Self {
+        LogConfig {
+            level: Level::Warn,
+            quiet: false,
+            target: None,
+            sample_every: 1,
+            diagnostics: None,
+        }
+    }
+}
+
+impl LogConfig {
+    /// Creates a `LogConfig` logging routine events at the given level.
+    pub fn new(level: Level) -> Self {
+        LogConfig {
+            level,
+            ..LogConfig::default()
+        }
+    }
+
+    /// Creates a `LogConfig` that suppresses all log output.
+    pub fn quiet() -> Self {
+        LogConfig {
+            quiet: true,
+            ..LogConfig::default()
+        }
+    }
+
+    /// Builder-style setter for a custom log `target`.
+    pub fn with_target(mut self, target: &'static str) -> Self {
+        self.target = Some(target);
+        self
+    }
+
+    /// Builder-style setter sampling the routine retry messages of a call.
+    ///
+    /// When a dependency is down, every call logs each of its retries, and those lines can
+    /// dominate the log volume. With sampling, a call logs its first failed attempt, then one in
+    /// every `sample_every` of the following ones, and always the final give-up.
+    ///
+    /// # Arguments
+    /// * `sample_every` - Log one in this many retries; `0` and `1` log every retry.
+    ///
+    /// # Example
+    /// ```
+    /// use resilient_rs::config::LogConfig;
+    ///
+    /// let sampled = LogConfig::default().with_sampling(5);
+    /// assert!(sampled.samples(1));
+    /// assert!(!sampled.samples(2));
+    /// assert!(sampled.samples(6));
+    /// ```
+    pub fn with_sampling(mut self, sample_every: usize) -> Self {
+        self.sample_every = sample_every;
+        self
+    }
+
+    /// Builder-style setter for a `Diagnostics` hook receiving the messages instead of `log`.
+    ///
+    /// # Arguments
+    /// * `diagnostics` - The hook; a `static` or a leaked value, since configs are `Copy`.
+    ///
+    /// # Example
+    /// ```
+    /// use resilient_rs::config::LogConfig;
+    /// use resilient_rs::logging::Diagnostic;
+    ///
+    /// fn to_console(diagnostic: &Diagnostic<'_>) {
+    ///     eprintln!("[{}] {}", diagnostic.level(), diagnostic);
+    /// }
+    ///
+    /// let log = LogConfig::default().with_diagnostics(&to_console);
+    /// assert!(log.diagnostics.is_some());
+    /// ```
+    pub fn with_diagnostics(mut self, diagnostics: &'static dyn Diagnostics) -> Self {
+        self.diagnostics = Some(diagnostics);
+        self
+    }
+
+    /// Returns `true` if the routine message of the given 1-based attempt is logged.
+    pub fn samples(&self, attempt: usize) -> bool {
+        self.sample_every <= 1 || attempt.saturating_sub(1).is_multiple_of(self.sample_every)
+    }
+}
+
+/// A retry policy applied to errors of a single `ErrorClass`.
+///
+/// # Fields
+/// - `max_attempts`: The maximum number of attempts (including the initial attempt) while
+///   failing with this class of error.
+/// - `delay`: The base delay between retries for this class.
+/// - `strategy`: The strategy used to grow `delay` between retries for this class.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClassPolicy {
+    pub max_attempts: usize,
+    #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+    pub delay: Duration,
+    pub strategy: RetryStrategy,
+}
+
+impl ClassPolicy {
+    /// Creates a new `ClassPolicy` with the specified maximum attempts, delay, and strategy.
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use resilient_rs::config::ClassPolicy;
+    /// use resilient_rs::strategies::RetryStrategy;
+    /// let policy = ClassPolicy::new(3, Duration::from_millis(100), RetryStrategy::Linear);
+    /// assert_eq!(policy.max_attempts, 3);
+    /// ```
+    pub fn new(max_attempts: usize, delay: Duration, strategy: RetryStrategy) -> Self {
+        ClassPolicy {
+            max_attempts,
+            delay,
+            strategy,
+        }
+    }
+}
+
+/// The largest number of attempts allowed without a delay between them.
+const MAX_ATTEMPTS_WITHOUT_DELAY: usize = 100;
+
+/// Validates a retry schedule; `fields` names the attempts, delay and strategy settings.
+fn validate_schedule(
+    max_attempts: usize,
+    delay: Duration,
+    strategy: &RetryStrategy,
+    fields: [&'static str; 3],
+) -> Result<(), ConfigError> {
+    let [attempts_field, delay_field, strategy_field] = fields;
+    if max_attempts == 0 {
+        return Err(ConfigError::invalid(
+            attempts_field,
+            "must be greater than 0",
+        ));
+    }
+    if delay == Duration::ZERO && max_attempts > MAX_ATTEMPTS_WITHOUT_DELAY {
+        return Err(ConfigError::invalid(
+            delay_field,
+            format!(
+                "must be non-zero when more than {} attempts are allowed",
+                MAX_ATTEMPTS_WITHOUT_DELAY
+            ),
+        ));
+    }
+    match strategy {
+        RetryStrategy::ExponentialBackoffWithJitter { jitter_factor }
+            if !(0.0..=1.0).contains(jitter_factor) =>
+        {
+            Err(ConfigError::invalid(
+                strategy_field,
+                format!(
+                    "jitter_factor must be between 0.0 and 1.0, got {}",
+                    jitter_factor
+                ),
+            ))
+        }
+        RetryStrategy::ArithmeticProgression { coefficient: 0 } => Err(ConfigError::invalid(
+            strategy_field,
+            "coefficient must be greater than 0",
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// A table of retry policies keyed by the `ErrorClass` returned by the error classifier.
+///
+/// Only retryable classes (`Transient` and `Throttled`) can carry a policy; `Permanent` and
+/// `Fatal` errors are never retried. For `Throttled` errors, a `retry_after` hint from the
+/// classifier still takes precedence over the delay computed by the policy.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::classifier::ErrorClass;
+/// use resilient_rs::config::{ClassPolicy, PolicyTable};
+/// use resilient_rs::strategies::RetryStrategy;
+///
+/// let table = PolicyTable::new()
+///     .on_throttled(ClassPolicy::new(10, Duration::from_secs(1), RetryStrategy::ExponentialBackoff));
+/// assert!(table.policy_for(&ErrorClass::Throttled { retry_after: None }).is_some());
+/// assert!(table.policy_for(&ErrorClass::Transient).is_none());
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct PolicyTable {
+    /// The policy used for errors classified as `Transient`.
+    pub transient: Option<ClassPolicy>,
+    /// The policy used for errors classified as `Throttled`.
+    pub throttled: Option<ClassPolicy>,
+}
+
+impl PolicyTable {
+    /// Creates an empty `PolicyTable`.
+    pub fn new() -> Self {
+        PolicyTable::default()
+    }
+
+    /// Builder-style setter for the policy applied to `Transient` errors.
+    pub fn on_transient(mut self, policy: ClassPolicy) -> Self {
+        self.transient = Some(policy);
+        self
+    }
+
+    /// Builder-style setter for the policy applied to `Throttled` errors.
+    pub fn on_throttled(mut self, policy: ClassPolicy) -> Self {
+        self.throttled = Some(policy);
+        self
+    }
+
+    /// Returns the policy registered for the given error class, if any.
+    pub fn policy_for(&self, class: &ErrorClass) -> Option<&ClassPolicy> {
+        match class {
+            ErrorClass::Transient => self.transient.as_ref(),
+            ErrorClass::Throttled { .. } => self.throttled.as_ref(),
+            ErrorClass::Permanent | ErrorClass::Fatal => None,
+        }
+    }
+}
+
+/// Why a fallback is running, passed to every `Fallback`.
+///
+/// It lets a fallback tailor the degraded response, e.g. serve a stale cached value after a
+/// timeout but a static default after a specific error.
+#[derive(Debug, Clone, Copy)]
+pub enum FallbackCause<'a> {
+    /// The primary operation did not complete within `timeout`.
+    Timeout { timeout: Duration },
+    /// The primary operation failed with this error.
+    Error(&'a dyn Error),
+}
+
+impl FallbackCause<'_> {
+    /// Returns `true` if the primary operation timed out.
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, FallbackCause::Timeout { .. })
+    }
+}
+
+impl fmt::Display for FallbackCause<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FallbackCause::Timeout { timeout } => write!(f, "timed out after {:?}", timeout),
+            FallbackCause::Error(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+/// The boxed error used by the APIs that do not take an error type parameter.
+///
+/// It is `Send + Sync`, so the futures of these APIs can be spawned on multi-threaded runtimes
+/// and their errors moved across threads.
+pub type BoxError = Box<dyn Error + Send + Sync>;
+
+/// A fallback function used by `ExecConfig` and `FallbackChain`.
+///
+/// The fallback receives the `FallbackCause` of the primary operation's failure. It is a shared
+/// closure, so it can capture a cached value, a client handle or a default loaded at startup.
+pub type Fallback<T> = Arc<dyn Fn(FallbackCause<'_>) -> Result<T, BoxError> + Send + Sync>;
+
+/// Configuration for executable tasks supporting both synchronous and asynchronous operations.
+///
+/// This struct defines execution parameters for tasks that may run either synchronously
+/// or asynchronously, including a timeout duration and an optional fallback function.
+/// It's designed to be passed to functions that handle task execution with support for
+/// both execution models.
+///
+/// The configuration is created with `new` and refined with the `with_*` builder methods, so
+/// that fields added in later versions do not break existing code.
+///
+/// # Type Parameters
+/// * `T` - The type of the successful result, must implement `Clone`
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::logging::Level;
+/// use resilient_rs::config::{ExecConfig, LogConfig};
+///
+/// let config = ExecConfig::new(Duration::from_millis(200))
+///     .with_fallback(|_| Ok("cached".to_string()))
+///     .with_log(LogConfig::new(Level::Debug));
+/// assert!(config.fallback.is_some());
+/// ```
+#[non_exhaustive]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(bound = ""))]
+pub struct ExecConfig<T> {
+    /// The maximum duration allowed for task execution before timeout.
+    ///
+    /// This applies to both synchronous and asynchronous operations. For async operations,
+    /// this typically integrates with runtime timeout mechanisms.
+    #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+    pub timeout_duration: Duration,
+
+    /// Optional fallback function to execute if the primary task fails or times out.
+    ///
+    /// The fallback must be a synchronous function that returns a `Result`. For async
+    /// contexts, the execution function is responsible for handling the sync-to-async
+    /// transition if needed. It receives the `FallbackCause` of the failure.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub fallback: Option<Fallback<T>>,
+
+    /// Logging behavior of the executor; timeouts handled by a fallback are logged at `log.level`.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub log: LogConfig,
+}
+
+impl<T> fmt::Debug for ExecConfig<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExecConfig")
+            .field("timeout_duration", &self.timeout_duration)
+            .field("fallback", &self.fallback.is_some())
+            .field("log", &self.log)
+            .finish()
+    }
+}
+
+impl<T> Clone for ExecConfig<T> {
+    fn clone(&self) -> Self {
+        ExecConfig {
+            timeout_duration: self.timeout_duration,
+            fallback: self.fallback.clone(),
+            log: self.log,
+        }
+    }
+}
+
+impl<T> ExecConfig<T>
+where
+    T: Clone,
+{
+    /// Creates a new execution configuration with the specified timeout duration.
+    ///
+    /// Initializes the configuration without a fallback function. This is suitable
+    /// for both synchronous and asynchronous task execution scenarios.
+    ///
+    /// # Arguments
+    /// * `timeout_duration` - Maximum execution time for sync or async operations
+    ///
+    /// # Returns
+    /// A new `ExecConfig` instance configured with the given timeout
+    pub fn new(timeout_duration: Duration) -> Self {
+        ExecConfig {
+            timeout_duration,
+            fallback: None,
+            log: LogConfig::default(),
+        }
+    }
+
+    /// Sets a fallback function to handle task failure or timeout scenarios, and returns the
+    /// modified configuration.
+    ///
+    /// The fallback is a synchronous function, but can be used in both sync and async
+    /// execution contexts. When used with async operations, the executing function
+    /// should handle any necessary async adaptation.
+    ///
+    /// # Arguments
+    /// * `fallback` - Synchronous closure returning a `Result` with matching types
+    ///
+    /// # Examples
+    /// ```
+    /// use std::time::Duration;
+    /// use resilient_rs::config::ExecConfig;
+    ///
+    /// let default_locale = String::from("en-US");
+    /// let config = ExecConfig::new(Duration::from_millis(200)).with_fallback(move |_| Ok(default_locale.clone()));
+    /// ```
+    pub fn with_fallback(
+        mut self,
+        fallback: impl Fn(FallbackCause<'_>) -> Result<T, BoxError> + Send + Sync + 'static,
+    ) -> Self {
+        self.fallback = Some(Arc::new(fallback));
+        self
+    }
+
+    /// Sets the logging behavior of the executor, and returns the modified configuration.
+    ///
+    /// # Arguments
+    /// * `log` - The `LogConfig` controlling level, verbosity and target of executor messages
+    pub fn with_log(mut self, log: LogConfig) -> Self {
+        self.log = log;
+        self
+    }
+}
+
+/// The result of an operation run with an `ExecConfig`, telling whether it was produced by the
+/// operation itself or by the fallback.
+///
+/// Returned by `asynchronous::execute_with_fallback_outcome`, it lets callers record degraded
+/// responses, e.g. serve them with a shorter cache lifetime or count them in a metric.
+///
+/// # Example
+/// ```
+/// use resilient_rs::config::ExecOutcome;
+///
+/// let outcome = ExecOutcome::Fallback("cached".to_string());
+/// assert!(outcome.is_fallback());
+/// assert_eq!(outcome.into_inner(), "cached");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecOutcome<T> {
+    /// The operation completed within the timeout.
+    Primary(T),
+    /// The operation timed out and the fallback produced the result.
+    Fallback(T),
+}
+
+impl<T> ExecOutcome<T> {
+    /// Returns `true` if the result was produced by the fallback.
+    pub fn is_fallback(&self) -> bool {
+        matches!(self, ExecOutcome::Fallback(_))
+    }
+
+    /// Returns the result, whichever produced it.
+    pub fn into_inner(self) -> T {
+        match self {
+            ExecOutcome::Primary(output) | ExecOutcome::Fallback(output) => output,
+        }
+    }
+}
+
+/// Which level of a `FallbackChain` produced a result.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ServedBy {
+    /// The primary operation succeeded within the timeout.
+    Primary,
+    /// The fallback registered at position `level` (1 for the first fallback) succeeded.
+    Fallback { level: usize, name: &'static str },
+}
+
+/// Configuration for executing an operation with a timeout and an ordered chain of fallbacks.
+///
+/// When the primary operation fails or times out, the executor tries the fallbacks in the order
+/// they were registered, e.g. a local cache, then a secondary region, then a static default, and
+/// returns the first successful result along with the `ServedBy` level that produced it. If every
+/// fallback fails, the error of the last one is returned. Every fallback receives the
+/// `FallbackCause` of the primary operation's failure.
+///
+/// # Type Parameters
+/// * `T` - The type of the successful result
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::config::FallbackChain;
+///
+/// let chain: FallbackChain<String> = FallbackChain::new(Duration::from_millis(200))
+///     .with_fallback("cache", |_| Err("cache miss".into()))
+///     .with_fallback("default", |_| Ok("default".to_string()));
+/// assert_eq!(chain.len(), 2);
+/// ```
+pub struct FallbackChain<T> {
+    /// The maximum duration allowed for the primary operation.
+    pub timeout_duration: Duration,
+
+    /// The fallbacks, tried in order, along with their names.
+    fallbacks: Vec<(&'static str, Fallback<T>)>,
+
+    /// Logging behavior of the executor; fallbacks being tried are logged at `log.level`.
+    pub log: LogConfig,
+}
+
+impl<T> fmt::Debug for FallbackChain<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("FallbackChain")
+            .field("timeout_duration", &self.timeout_duration)
+            .field(
+                "fallbacks",
+                &self
+                    .fallbacks
+                    .iter()
+                    .map(|(name, _)| *name)
+                    .collect::<Vec<_>>(),
+            )
+            .field("log", &self.log)
+            .finish()
+    }
+}
+
+impl<T> Clone for FallbackChain<T> {
+    fn clone(&self) -> Self {
+        FallbackChain {
+            timeout_duration: self.timeout_duration,
+            fallbacks: self.fallbacks.clone(),
+            log: self.log,
+        }
+    }
+}
+
+impl<T> FallbackChain<T> {
+    /// Creates a chain without fallbacks.
+    ///
+    /// # Arguments
+    /// * `timeout_duration` - Maximum execution time of the primary operation
+    pub fn new(timeout_duration: Duration) -> Self {
+        FallbackChain {
+            timeout_duration,
+            fallbacks: Vec::new(),
+            log: LogConfig::default(),
+        }
+    }
+
+    /// Appends a fallback to the chain and returns the modified chain.
+    ///
+    /// # Arguments
+    /// * `name` - A name identifying the fallback in logs and in `ServedBy`
+    /// * `fallback` - Synchronous closure returning a `Result` with matching types
+    pub fn with_fallback(
+        mut self,
+        name: &'static str,
+        fallback: impl Fn(FallbackCause<'_>) -> Result<T, BoxError> + Send + Sync + 'static,
+    ) -> Self {
+        self.fallbacks.push((name, Arc::new(fallback)));
+        self
+    }
+
+    /// Sets the logging behavior of the executor and returns the modified chain.
+    ///
+    /// # Arguments
+    /// * `log` - The `LogConfig` controlling level, verbosity and target of executor messages
+    pub fn with_log(mut self, log: LogConfig) -> Self {
+        self.log = log;
+        self
+    }
+
+    /// Returns the number of fallbacks in the chain.
+    pub fn len(&self) -> usize {
+        self.fallbacks.len()
+    }
+
+    /// Returns `true` if the chain has no fallback.
+    pub fn is_empty(&self) -> bool {
+        self.fallbacks.is_empty()
+    }
+
+    /// Returns the fallbacks in the order they are tried, along with their names.
+    pub(crate) fn fallbacks(&self) -> &[(&'static str, Fallback<T>)] {
+        &self.fallbacks
+    }
+}
+
+/// Configuration for a Circuit Breaker.
+///
+/// The `CircuitBreakerConfig` struct holds the static configuration parameters for a circuit breaker.
+/// It defines how the circuit breaker behaves during different states (Closed, Open, and HalfOpen).
+/// These settings determine the thresholds for failures, successes, and the cooldown period for recovery attempts.
+///
+/// Use this struct to configure and initialize a `CircuitBreaker` instance with specific settings.
+///
+/// # Fields
+/// - `failure_threshold`: The maximum number of consecutive failures before the circuit breaker transitions
+///   from `Close` to `Open`. This threshold determines how sensitive the circuit breaker is to failures.
+/// - `success_threshold`: The number of successful operations required in the `HalfOpen` state before
+///   transitioning back to `Close`. This determines how many recovery attempts the system will test before
+///   considering the service restored.
+/// - `cooldown_period`: The duration to wait in the `Open` state before transitioning to `HalfOpen` to test
+///   if the system has recovered. This period allows the failing system time to stabilize and prevents
+///   immediate retries.
+/// - `log`: Logging behavior of the breaker; failed calls and rejections are logged at `log.level`.
+/// - `window`: How failures are counted while `Close`. By default, `failure_threshold` consecutive
+///   failures open the circuit; a sliding window opens it based on the failure rate instead.
+/// - `cooldown_escalation`: Optional growth of the cooldown period when the circuit keeps
+///   re-opening, so a persistently broken dependency is probed less and less often.
+/// - `metrics_window_size`: The number of most recent calls whose outcome and latency are kept
+///   for the breaker's success rate and latency statistics.
+/// - `max_concurrent_calls`: Optional cap on the calls running through the breaker at once. Calls
+///   beyond the cap are rejected even while the circuit is closed, protecting the dependency from
+///   overload before failures even start.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::config::CircuitBreakerConfig;
+///
+/// let config = CircuitBreakerConfig::new(3, 5, Duration::from_secs(10));
+/// println!("{:?}", config);
+/// ```
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct CircuitBreakerConfig {
+    pub failure_threshold: usize,
+    pub success_threshold: usize,
+    #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+    pub cooldown_period: Duration,
+    pub log: LogConfig,
+    pub window: FailureWindow,
+    pub cooldown_escalation: Option<CooldownEscalation>,
+    pub metrics_window_size: usize,
+    pub max_concurrent_calls: Option<usize>,
+}
+
+impl Default for CircuitBreakerConfig {
+    /// # Default Configuration
+    /// The default configuration sets:
+    /// - `failure_threshold` to 5 (max failures before opening the circuit)
+    /// - `success_threshold` to 2 (successes required to close the circuit from HalfOpen)
+    /// - `cooldown_period` to 2 seconds (time to wait before testing recovery)
+    /// - `log` to `LogConfig::default()` (routine events at `Warn`)
+    /// - `window` to `FailureWindow::Consecutive` (open after `failure_threshold` consecutive failures)
+    /// - `cooldown_escalation` to `None` (the same cooldown after every opening)
+    /// - `metrics_window_size` to 100 (latency statistics over the last 100 calls)
+    /// - `max_concurrent_calls` to `None` (no concurrency cap)
+    fn default() -> Self {
+        Self {
+            success_threshold: 2,
+            failure_threshold: 5,
+            cooldown_period: Duration::from_secs(2),
+            log: LogConfig::default(),
+            window: FailureWindow::Consecutive,
+            cooldown_escalation: None,
+            metrics_window_size: 100,
+            max_concurrent_calls: None,
+        }
+    }
+}
+
+impl CircuitBreakerConfig {
+    /// Creates a new `CircuitBreakerConfig` instance with the specified settings.
+    ///
+    /// This method constructs a configuration object for a circuit breaker based on the provided thresholds
+    /// and cooldown period. The configuration defines how the circuit breaker will behave during operation.
+    ///
+    /// # Parameters
+    /// - `success_threshold`: The number of successful operations required in the `HalfOpen` state
+    ///   to transition back to `Close`. This must be greater than 0 for meaningful recovery.
+    /// - `failure_threshold`: The number of consecutive failures in the `Close` state that will trigger
+    ///   a transition to `Open`. This must be greater than 0.
+    /// - `cooldown_period`: The duration to wait in the `Open` state before moving to `HalfOpen` to test
+    ///   recovery. Should be long enough to allow the system to stabilize and prevent immediate retries.
+    ///
+    /// # Returns
+    /// A new `CircuitBreakerConfig` instance with the provided parameters.
+    ///
+    /// # Panics
+    /// This function will panic if any parameter is invalid (e.g., zero or negative values for thresholds).
+    /// Use `try_new` to get a `ConfigError` instead.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use resilient_rs::config::CircuitBreakerConfig;
+    /// let config = CircuitBreakerConfig::new(3, 5, Duration::from_secs(10));
+    /// assert_eq!(config.failure_threshold, 5);
+    /// ```
+    pub fn new(
+        success_threshold: usize,
+        failure_threshold: usize,
+        cooldown_period: Duration,
+    ) -> Self {
+        Self::try_new(success_threshold, failure_threshold, cooldown_period)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates a new `CircuitBreakerConfig`, returning an error instead of panicking on invalid input.
+    ///
+    /// Prefer this constructor when the values come from configuration files or user input.
+    ///
+    /// # Parameters
+    /// - `success_threshold`: The number of successes in `HalfOpen` required to close the circuit. Must be greater than 0.
+    /// - `failure_threshold`: The number of consecutive failures that opens the circuit. Must be greater than 0.
+    /// - `cooldown_period`: The duration to wait in `Open` before testing recovery. Must be non-zero.
+    ///
+    /// # Returns
+    /// The configuration, or a `ConfigError::InvalidValue` naming the offending field.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use resilient_rs::config::{CircuitBreakerConfig, ConfigError};
+    ///
+    /// let err = CircuitBreakerConfig::try_new(2, 0, Duration::from_secs(1)).unwrap_err();
+    /// assert!(matches!(err, ConfigError::InvalidValue { field: "failure_threshold", .. }));
+    /// ```
+    pub fn try_new(
+        success_threshold: usize,
+        failure_threshold: usize,
+        cooldown_period: Duration,
+    ) -> Result<Self, ConfigError> {
+        let config = Self {
+            failure_threshold,
+            success_threshold,
+            cooldown_period,
+            log: LogConfig::default(),
+            window: FailureWindow::Consecutive,
+            cooldown_escalation: None,
+            metrics_window_size: 100,
+            max_concurrent_calls: None,
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// A preset opening the circuit early, for dependencies whose failures are expensive to keep
+    /// calling, such as a payment provider or a database under load.
+    ///
+    /// The circuit opens after 3 consecutive failures and stays open for 10 seconds. Every time
+    /// it opens again within 5 minutes of closing, or a probe fails, the cooldown doubles, up to
+    /// 2 minutes. Closing it takes 3 successful probes.
+    ///
+    /// # Example
+    /// ```
+    /// use resilient_rs::config::CircuitBreakerConfig;
+    ///
+    /// let config = CircuitBreakerConfig::sensitive();
+    /// assert_eq!(config.failure_threshold, 3);
+    /// assert!(config.validate().is_ok());
+    /// ```
+    pub fn sensitive() -> Self {
+        Self::new(3, 3, Duration::from_secs(10)).with_cooldown_escalation(CooldownEscalation::new(
+            2.0,
+            Duration::from_secs(120),
+            Duration::from_secs(300),
+        ))
+    }
+
+    /// A preset tolerating occasional failures, for high-traffic dependencies where a few errors
+    /// are expected and opening the circuit is costly.
+    ///
+    /// The circuit opens when at least half of the last 50 calls failed, once 20 calls were
+    /// made, and stays open for 30 seconds. Closing it takes 3 successful probes.
+    pub fn tolerant() -> Self {
+        Self::new(3, 5, Duration::from_secs(30)).with_window(FailureWindow::count(50, 50.0, 20))
+    }
+
+    /// Checks that the configuration is usable.
+    ///
+    /// This is useful after setting fields directly or deserializing a configuration, since those
+    /// paths bypass the checks performed by `new` and the builder setters.
+    ///
+    /// # Returns
+    /// `Ok(())`, or a `ConfigError::InvalidValue` naming the first offending field.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use resilient_rs::config::CircuitBreakerConfig;
+    ///
+    /// let mut config = CircuitBreakerConfig::default();
+    /// config.cooldown_period = Duration::ZERO;
+    /// assert!(config.validate().is_err());
+    /// ```
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.success_threshold == 0 {
+            return Err(ConfigError::invalid(
+                "success_threshold",
+                "must be greater than 0",
+            ));
+        }
+        if self.failure_threshold == 0 {
+            return Err(ConfigError::invalid(
+                "failure_threshold",
+                "must be greater than 0",
+            ));
+        }
+        if self.cooldown_period == Duration::ZERO {
+            return Err(ConfigError::invalid("cooldown_period", "must be non-zero"));
+        }
+        if let Some(escalation) = &self.cooldown_escalation {
+            escalation.validate(self.cooldown_period)?;
+        }
+        if self.max_concurrent_calls == Some(0) {
+            return Err(ConfigError::invalid(
+                "max_concurrent_calls",
+                "must be greater than 0",
+            ));
+        }
+        self.window.validate()
+    }
+
+    /// Builder-style setter for `failure_threshold`.
+    ///
+    /// This method allows you to modify the `failure_threshold` value after the initial configuration.
+    /// It enables more flexible configuration using a builder pattern.
+    ///
+    /// # Parameters
+    /// - `threshold`: The number of failures required to trigger a transition to `Open`. Must be greater than 0;
+    ///   a zero is reported by `validate` rather than panicking, so values read from a config file can be
+    ///   checked before use.
+    ///
+    /// # Returns
+    /// A new `CircuitBreakerConfig` instance with the updated `failure_threshold`.
+    ///
+    /// # Example
+    /// ```
+    /// use resilient_rs::config::CircuitBreakerConfig;
+    /// let config = CircuitBreakerConfig::default().with_failure_threshold(3);
+    /// assert_eq!(config.failure_threshold, 3);
+    /// ```
+    pub fn with_failure_threshold(mut self, threshold: usize) -> Self {
+        self.failure_threshold = threshold;
+        self
+    }
+
+    /// Builder-style setter for `success_threshold`.
+    ///
+    /// This method allows you to modify the `success_threshold` value after the initial configuration.
+    /// It enables more flexible configuration using a builder pattern.
+    ///
+    /// # Parameters
+    /// - `threshold`: The number of successes required to close the circuit from the `HalfOpen` state. Must be greater than 0;
+    ///   a zero is reported by `validate`.
+    ///
+    /// # Returns
+    /// A new `CircuitBreakerConfig` instance with the updated `success_threshold`.
+    ///
+    /// # Example
+    /// ```
+    /// use resilient_rs::config::CircuitBreakerConfig;
+    /// let config = CircuitBreakerConfig::default().with_success_threshold(4);
+    /// assert_eq!(config.success_threshold, 4);
+    /// ```
+    pub fn with_success_threshold(mut self, threshold: usize) -> Self {
+        self.success_threshold = threshold;
+        self
+    }
+
+    /// Builder-style setter for `cooldown_period`.
+    ///
+    /// This method allows you to modify the `cooldown_period` value after the initial configuration.
+    /// It enables more flexible configuration using a builder pattern.
+    ///
+    /// # Parameters
+    /// - `period`: The duration to wait before testing the recovery of the system. Must be greater than 0; a zero
+    ///   duration is reported by `validate`.
+    ///
+    /// # Returns
+    /// A new `CircuitBreakerConfig` instance with the updated `cooldown_period`.
+    ///
+    /// # Example
+    /// ```
+    /// use resilient_rs::config::CircuitBreakerConfig;
+    /// let config = CircuitBreakerConfig::default().with_cooldown_period(std::time::Duration::from_secs(5));
+    /// assert_eq!(config.cooldown_period, std::time::Duration::from_secs(5));
+    /// ```
+    pub fn with_cooldown_period(mut self, period: Duration) -> Self {
+        self.cooldown_period = period;
+        self
+    }
+
+    /// Builder-style setter for `log`.
+    ///
+    /// # Parameters
+    /// - `log`: The `LogConfig` controlling level, verbosity and target of breaker messages.
+    ///
+    /// # Returns
+    /// A new `CircuitBreakerConfig` instance with the updated logging behavior.
+    ///
+    /// # Example
+    /// ```
+    /// use resilient_rs::config::{CircuitBreakerConfig, LogConfig};
+    /// let config = CircuitBreakerConfig::default().with_log(LogConfig::quiet());
+    /// assert!(config.log.quiet);
+    /// ```
+    pub fn with_log(mut self, log: LogConfig) -> Self {
+        self.log = log;
+        self
+    }
+
+    /// Builder-style setter for `window`.
+    ///
+    /// # Parameters
+    /// - `window`: How failures are counted before the circuit opens.
+    ///
+    /// # Returns
+    /// A new `CircuitBreakerConfig` instance with the updated failure window.
+    ///
+    /// # Example
+    /// ```
+    /// use resilient_rs::config::{CircuitBreakerConfig, FailureWindow};
+    /// // Open when at least half of the last 20 calls failed, once 10 calls were seen.
+    /// let config = CircuitBreakerConfig::default().with_window(FailureWindow::count(20, 50.0, 10));
+    /// ```
+    pub fn with_window(mut self, window: FailureWindow) -> Self {
+        self.window = window;
+        self
+    }
+
+    /// Builder-style setter for `cooldown_escalation`.
+    ///
+    /// # Parameters
+    /// - `escalation`: How the cooldown period grows when the circuit keeps re-opening.
+    ///
+    /// # Returns
+    /// A new `CircuitBreakerConfig` instance with the updated cooldown escalation.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use resilient_rs::config::{CircuitBreakerConfig, CooldownEscalation};
+    /// // 2s, 4s, 8s, ... up to 1 minute; back to 2s after 5 minutes without opening.
+    /// let config = CircuitBreakerConfig::default().with_cooldown_escalation(CooldownEscalation::new(
+    ///     2.0,
+    ///     Duration::from_secs(60),
+    ///     Duration::from_secs(300),
+    /// ));
+    /// assert_eq!(config.cooldown_escalation.unwrap().multiplier, 2.0);
+    /// ```
+    pub fn with_cooldown_escalation(mut self, escalation: CooldownEscalation) -> Self {
+        self.cooldown_escalation = Some(escalation);
+        self
+    }
+
+    /// Builder-style setter for `metrics_window_size`.
+    ///
+    /// # Parameters
+    /// - `size`: The number of most recent calls used for the success rate and latency
+    ///   statistics; 0 disables them.
+    ///
+    /// # Returns
+    /// A new `CircuitBreakerConfig` instance with the updated metrics window size.
+    ///
+    /// # Example
+    /// ```
+    /// use resilient_rs::config::CircuitBreakerConfig;
+    /// let config = CircuitBreakerConfig::default().with_metrics_window_size(1_000);
+    /// assert_eq!(config.metrics_window_size, 1_000);
+    /// ```
+    pub fn with_metrics_window_size(mut self, size: usize) -> Self {
+        self.metrics_window_size = size;
+        self
+    }
+
+    /// Builder-style setter for `max_concurrent_calls`.
+    ///
+    /// Calls started while `max` calls are already running through the breaker are rejected with
+    /// `CircuitBreakerError::Saturated`, whatever the state of the circuit. Rejected calls are not
+    /// counted as failures.
+    ///
+    /// # Parameters
+    /// - `max`: The maximum number of calls running at once. Must be greater than 0.
+    ///
+    /// # Returns
+    /// A new `CircuitBreakerConfig` instance with the updated concurrency cap.
+    ///
+    /// # Example
+    /// ```
+    /// use resilient_rs::config::CircuitBreakerConfig;
+    /// let config = CircuitBreakerConfig::default().with_max_concurrent_calls(32);
+    /// assert_eq!(config.max_concurrent_calls, Some(32));
+    /// ```
+    pub fn with_max_concurrent_calls(mut self, max: usize) -> Self {
+        self.max_concurrent_calls = Some(max);
+        self
+    }
+}
+
+/// How a circuit breaker lengthens its cooldown period when the circuit keeps re-opening.
+///
+/// Every time the circuit opens less than `reset_after` after it last closed (including when a
+/// `HalfOpen` trial fails), the cooldown is multiplied by `multiplier`, up to `max_cooldown`.
+/// Once the circuit has stayed closed for `reset_after`, the next opening uses the configured
+/// `cooldown_period` again.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CooldownEscalation {
+    /// The factor applied to the cooldown on every re-opening; must be at least 1.0.
+    pub multiplier: f64,
+    /// The longest cooldown; must not be shorter than `cooldown_period`.
+    #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+    pub max_cooldown: Duration,
+    /// How long the circuit must stay closed for the escalation to be forgotten.
+    #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+    pub reset_after: Duration,
+}
+
+impl CooldownEscalation {
+    /// Creates a new `CooldownEscalation`.
+    ///
+    /// # Arguments
+    /// * `multiplier` - The factor applied to the cooldown on every re-opening, e.g. `2.0`.
+    /// * `max_cooldown` - The longest cooldown.
+    /// * `reset_after` - How long the circuit must stay closed for the escalation to be forgotten.
+    pub fn new(multiplier: f64, max_cooldown: Duration, reset_after: Duration) -> Self {
+        CooldownEscalation {
+            multiplier,
+            max_cooldown,
+            reset_after,
+        }
+    }
+
+    /// Returns the cooldown after `reopens` consecutive re-openings.
+    pub(crate) fn cooldown(&self, cooldown_period: Duration, reopens: u32) -> Duration {
+        let factor = self
+            .multiplier
+            .powi(i32::try_from(reopens).unwrap_or(i32::MAX));
+        let cooldown =
+            (cooldown_period.as_secs_f64() * factor).min(self.max_cooldown.as_secs_f64());
+        Duration::try_from_secs_f64(cooldown).unwrap_or(self.max_cooldown)
+    }
+
+    fn validate(&self, cooldown_period: Duration) -> Result<(), ConfigError> {
+        if !(self.multiplier >= 1.0 && self.multiplier.is_finite()) {
+            return Err(ConfigError::invalid(
+                "cooldown_escalation.multiplier",
+                "must be a finite number of at least 1.0",
+            ));
+        }
+        if self.max_cooldown < cooldown_period {
+            return Err(ConfigError::invalid(
+                "cooldown_escalation.max_cooldown",
+                "must not be shorter than cooldown_period",
+            ));
+        }
+        if self.reset_after == Duration::ZERO {
+            return Err(ConfigError::invalid(
+                "cooldown_escalation.reset_after",
+                "must be non-zero",
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// How a circuit breaker counts failures while it is `Close`.
+///
+/// - `Consecutive`: The circuit opens after `failure_threshold` consecutive failures; a success
+///   resets the count. This is the default.
+/// - `Count`: The breaker remembers the outcome of the last `size` calls and opens when the
+///   percentage of failures among them reaches `failure_rate_threshold`, as long as at least
+///   `minimum_calls` calls were recorded. This copes better with mixed traffic, where occasional
+///   successes would otherwise hide a mostly failing dependency.
+/// - `Time`: Like `Count`, but over the calls made during the last `duration`. Outcomes are
+///   aggregated into `buckets` slices of the duration, so memory stays bounded under high call rates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum FailureWindow {
+    /// Count consecutive failures against `failure_threshold`.
+    Consecutive,
+    /// Track the failure rate over the last `size` calls.
+    Count {
+        size: usize,
+        failure_rate_threshold: f64,
+        minimum_calls: usize,
+    },
+    /// Track the failure rate over the calls made during the last `duration`.
+    Time {
+        #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+        duration: Duration,
+        buckets: usize,
+        failure_rate_threshold: f64,
+        minimum_calls: usize,
+    },
+}
+
+impl FailureWindow {
+    /// Creates a count-based sliding window.
+    ///
+    /// # Arguments
+    /// * `size` - The number of most recent calls taken into account.
+    /// * `failure_rate_threshold` - The failure percentage (`0.0` to `100.0`) at which the circuit opens.
+    /// * `minimum_calls` - The number of calls required before the failure rate is evaluated.
+    pub fn count(size: usize, failure_rate_threshold: f64, minimum_calls: usize) -> Self {
+        FailureWindow::Count {
+            size,
+            failure_rate_threshold,
+            minimum_calls,
+        }
+    }
+
+    /// Creates a time-based sliding window split into 10 buckets.
+    ///
+    /// # Arguments
+    /// * `duration` - The period of time taken into account, e.g. the last 30 seconds.
+    /// * `failure_rate_threshold` - The failure percentage (`0.0` to `100.0`) at which the circuit opens.
+    /// * `minimum_calls` - The number of calls within `duration` required before the failure rate is evaluated.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use resilient_rs::config::{CircuitBreakerConfig, FailureWindow};
+    /// // Open if more than half of the calls in the last 30s failed.
+    /// let config = CircuitBreakerConfig::default()
+    ///     .with_window(FailureWindow::time(Duration::from_secs(30), 50.0, 20));
+    /// ```
+    pub fn time(duration: Duration, failure_rate_threshold: f64, minimum_calls: usize) -> Self {
+        FailureWindow::Time {
+            duration,
+            buckets: 10,
+            failure_rate_threshold,
+            minimum_calls,
+        }
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        match *self {
+            FailureWindow::Consecutive => Ok(()),
+            FailureWindow::Time {
+                duration,
+                buckets,
+                failure_rate_threshold,
+                ..
+            } => {
+                if buckets == 0 || u32::try_from(buckets).is_err() {
+                    return Err(ConfigError::invalid(
+                        "window.buckets",
+                        "must be greater than 0 and fit in a u32",
+                    ));
+                }
+                if duration < Duration::from_nanos(buckets as u64) {
+                    return Err(ConfigError::invalid(
+                        "window.duration",
+                        "must be at least one nanosecond per bucket",
+                    ));
+                }
+                validate_failure_rate(failure_rate_threshold)
+            }
+            FailureWindow::Count {
+                size,
+                failure_rate_threshold,
+                minimum_calls,
+            } => {
+                if size == 0 {
+                    return Err(ConfigError::invalid(
+                        "window.size",
+                        "must be greater than 0",
+                    ));
+                }
+                if minimum_calls > size {
+                    return Err(ConfigError::invalid(
+                        "window.minimum_calls",
+                        "must not be greater than window.size",
+                    ));
+                }
+                validate_failure_rate(failure_rate_threshold)
+            }
+        }
+    }
+}
+
+fn validate_failure_rate(failure_rate_threshold: f64) -> Result<(), ConfigError> {
+    if failure_rate_threshold > 0.0 && failure_rate_threshold <= 100.0 {
+        Ok(())
+    } else {
+        Err(ConfigError::invalid(
+            "window.failure_rate_threshold",
+            format!(
+                "must be greater than 0.0 and at most 100.0, got {}",
+                failure_rate_threshold
+            ),
+        ))
+    }
+}
+
+/// Configuration for a `Bulkhead`, which caps the number of concurrent executions of the
+/// operations it guards.
+///
+/// # Fields
+/// - `max_concurrent_calls`: The maximum number of operations running through the bulkhead at
+///   once.
+/// - `max_wait`: How long a call waits for a free slot when all of them are taken before being
+///   rejected. Zero, the default, rejects it immediately.
+/// - `log`: Logging behavior of the bulkhead; rejections are logged at `log.level`.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::config::BulkheadConfig;
+///
+/// let config = BulkheadConfig::new(10).with_max_wait(Duration::from_millis(50));
+/// assert_eq!(config.max_concurrent_calls, 10);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct BulkheadConfig {
+    pub max_concurrent_calls: usize,
+    #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+    pub max_wait: Duration,
+    pub log: LogConfig,
+}
+
+impl Default for BulkheadConfig {
+    /// # Default Configuration
+    /// The default configuration sets:
+    /// - `max_concurrent_calls` to 25
+    /// - `max_wait` to zero (calls are rejected as soon as the bulkhead is full)
+    /// - `log` to `LogConfig::default()` (rejections at `Warn`)
+    fn default() -> Self {
+        BulkheadConfig {
+            max_concurrent_calls: 25,
+            max_wait: Duration::ZERO,
+            log: LogConfig::default(),
+        }
+    }
+}
+
+impl BulkheadConfig {
+    /// Creates a new `BulkheadConfig` rejecting calls as soon as `max_concurrent_calls` operations
+    /// are running.
+    ///
+    /// # Parameters
+    /// - `max_concurrent_calls`: The maximum number of concurrent executions. Must be greater than 0.
+    ///
+    /// # Panics
+    /// This function will panic if `max_concurrent_calls` is 0. Use `try_new` to get a
+    /// `ConfigError` instead.
+    pub fn new(max_concurrent_calls: usize) -> Self {
+        Self::try_new(max_concurrent_calls).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates a new `BulkheadConfig`, returning an error instead of panicking on invalid input.
+    ///
+    /// # Parameters
+    /// - `max_concurrent_calls`: The maximum number of concurrent executions. Must be greater than 0.
+    ///
+    /// # Returns
+    /// The configuration, or a `ConfigError::InvalidValue` naming the offending field.
+    pub fn try_new(max_concurrent_calls: usize) -> Result<Self, ConfigError> {
+        let config = BulkheadConfig {
+            max_concurrent_calls,
+            ..BulkheadConfig::default()
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks that the configuration is usable.
+    ///
+    /// # Returns
+    /// `Ok(())`, or a `ConfigError::InvalidValue` naming the first offending field.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.max_concurrent_calls == 0 {
+            return Err(ConfigError::invalid(
+                "max_concurrent_calls",
+                "must be greater than 0",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Builder-style setter for `max_wait`.
+    ///
+    /// # Parameters
+    /// - `max_wait`: How long a call waits for a free slot before being rejected.
+    ///
+    /// # Returns
+    /// A new `BulkheadConfig` instance with the updated maximum wait.
+    pub fn with_max_wait(mut self, max_wait: Duration) -> Self {
+        self.max_wait = max_wait;
+        self
+    }
+
+    /// Builder-style setter for `log`.
+    ///
+    /// # Parameters
+    /// - `log`: The logging behavior of the bulkhead.
+    ///
+    /// # Returns
+    /// A new `BulkheadConfig` instance with the updated logging behavior.
+    pub fn with_log(mut self, log: LogConfig) -> Self {
+        self.log = log;
+        self
+    }
+}
+
+/// Configuration for an `AdaptiveLimiter` using the AIMD (additive increase, multiplicative
+/// decrease) algorithm.
+///
+/// # Fields
+/// - `initial_limit`: The concurrency limit the limiter starts with.
+/// - `min_limit`: The lowest the limit can be decreased to.
+/// - `max_limit`: The highest the limit can be increased to.
+/// - `backoff_ratio`: The factor applied to the limit when a call fails or is too slow, between 0
+///   and 1 (exclusive).
+/// - `latency_threshold`: Calls slower than this are treated as a sign of overload, like failures.
+/// - `log`: Logging behavior of the limiter; rejections are logged at `log.level`.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::config::AimdConfig;
+///
+/// let config = AimdConfig::default()
+///     .with_limits(4, 1, 64)
+///     .with_latency_threshold(Duration::from_millis(250));
+/// assert!(config.validate().is_ok());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct AimdConfig {
+    pub initial_limit: usize,
+    pub min_limit: usize,
+    pub max_limit: usize,
+    pub backoff_ratio: f64,
+    #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+    pub latency_threshold: Duration,
+    pub log: LogConfig,
+}
+
+impl Default for AimdConfig {
+    /// # Default Configuration
+    /// The default configuration sets:
+    /// - `initial_limit` to 20, `min_limit` to 1 and `max_limit` to 200
+    /// - `backoff_ratio` to 0.9 (the limit shrinks by 10% on every overload signal)
+    /// - `latency_threshold` to 5 seconds
+    /// - `log` to `LogConfig::default()` (rejections at `Warn`)
+    fn default() -> Self {
+        AimdConfig {
+            initial_limit: 20,
+            min_limit: 1,
+            max_limit: 200,
+            backoff_ratio: 0.9,
+            latency_threshold: Duration::from_secs(5),
+            log: LogConfig::default(),
+        }
+    }
+}
+
+impl AimdConfig {
+    /// Checks that the configuration is usable.
+    ///
+    /// # Returns
+    /// `Ok(())`, or a `ConfigError::InvalidValue` naming the first offending field.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.min_limit == 0 {
+            return Err(ConfigError::invalid("min_limit", "must be greater than 0"));
+        }
+        if self.initial_limit < self.min_limit {
+            return Err(ConfigError::invalid(
+                "initial_limit",
+                "must not be lower than min_limit",
+            ));
+        }
+        if self.max_limit < self.initial_limit {
+            return Err(ConfigError::invalid(
+                "max_limit",
+                "must not be lower than initial_limit",
+            ));
+        }
+        if !(self.backoff_ratio > 0.0 && self.backoff_ratio < 1.0) {
+            return Err(ConfigError::invalid(
+                "backoff_ratio",
+                "must be between 0 and 1 (exclusive)",
+            ));
+        }
+        if self.latency_threshold == Duration::ZERO {
+            return Err(ConfigError::invalid(
+                "latency_threshold",
+                "must be non-zero",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Builder-style setter for `initial_limit`, `min_limit` and `max_limit`.
+    ///
+    /// # Parameters
+    /// - `initial`: The concurrency limit to start with.
+    /// - `min`: The lowest limit. Must be greater than 0.
+    /// - `max`: The highest limit.
+    ///
+    /// # Returns
+    /// A new `AimdConfig` instance with the updated limits.
+    pub fn with_limits(mut self, initial: usize, min: usize, max: usize) -> Self {
+        self.initial_limit = initial;
+        self.min_limit = min;
+        self.max_limit = max;
+        self
+    }
+
+    /// Builder-style setter for `backoff_ratio`.
+    ///
+    /// # Parameters
+    /// - `ratio`: The factor applied to the limit on overload, between 0 and 1 (exclusive).
+    ///
+    /// # Returns
+    /// A new `AimdConfig` instance with the updated backoff ratio.
+    pub fn with_backoff_ratio(mut self, ratio: f64) -> Self {
+        self.backoff_ratio = ratio;
+        self
+    }
+
+    /// Builder-style setter for `latency_threshold`.
+    ///
+    /// # Parameters
+    /// - `threshold`: The latency above which a successful call counts as an overload signal.
+    ///
+    /// # Returns
+    /// A new `AimdConfig` instance with the updated latency threshold.
+    pub fn with_latency_threshold(mut self, threshold: Duration) -> Self {
+        self.latency_threshold = threshold;
+        self
+    }
+
+    /// Builder-style setter for `log`.
+    ///
+    /// # Parameters
+    /// - `log`: The logging behavior of the limiter.
+    ///
+    /// # Returns
+    /// A new `AimdConfig` instance with the updated logging behavior.
+    pub fn with_log(mut self, log: LogConfig) -> Self {
+        self.log = log;
+        self
+    }
+}
+
+/// Configuration for a `LoadShedder`.
+///
+/// # Fields
+/// - `max_in_flight`: The number of calls running at once above which new calls are shed.
+/// - `max_latency`: The average latency of recent calls above which the shedder considers the
+///   service overloaded and halves `max_in_flight` until the average recovers.
+/// - `smoothing`: The weight of each new latency sample in the moving average, between 0
+///   (exclusive) and 1 (inclusive). Higher values react faster to latency changes.
+/// - `log`: Logging behavior of the shedder; shed calls are logged at `log.level`.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::config::LoadShedConfig;
+///
+/// let config = LoadShedConfig::new(64, Duration::from_millis(500)).with_smoothing(0.5);
+/// assert_eq!(config.max_in_flight, 64);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct LoadShedConfig {
+    pub max_in_flight: usize,
+    #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+    pub max_latency: Duration,
+    pub smoothing: f64,
+    pub log: LogConfig,
+}
+
+impl Default for LoadShedConfig {
+    /// # Default Configuration
+    /// The default configuration sets:
+    /// - `max_in_flight` to 100
+    /// - `max_latency` to 1 second
+    /// - `smoothing` to 0.2
+    /// - `log` to `LogConfig::default()` (shed calls at `Warn`)
+    fn default() -> Self {
+        LoadShedConfig {
+            max_in_flight: 100,
+            max_latency: Duration::from_secs(1),
+            smoothing: 0.2,
+            log: LogConfig::default(),
+        }
+    }
+}
+
+impl LoadShedConfig {
+    /// Creates a new `LoadShedConfig` shedding calls above `max_in_flight` concurrent calls or
+    /// `max_latency` average latency.
+    ///
+    /// # Parameters
+    /// - `max_in_flight`: The maximum number of concurrent calls. Must be greater than 0.
+    /// - `max_latency`: The highest acceptable average latency. Must be non-zero.
+    ///
+    /// # Panics
+    /// This function will panic if a parameter is invalid. Use `try_new` to get a `ConfigError`
+    /// instead.
+    pub fn new(max_in_flight: usize, max_latency: Duration) -> Self {
+        Self::try_new(max_in_flight, max_latency).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates a new `LoadShedConfig`, returning an error instead of panicking on invalid input.
+    ///
+    /// # Parameters
+    /// - `max_in_flight`: The maximum number of concurrent calls. Must be greater than 0.
+    /// - `max_latency`: The highest acceptable average latency. Must be non-zero.
+    ///
+    /// # Returns
+    /// The configuration, or a `ConfigError::InvalidValue` naming the offending field.
+    pub fn try_new(max_in_flight: usize, max_latency: Duration) -> Result<Self, ConfigError> {
+        let config = LoadShedConfig {
+            max_in_flight,
+            max_latency,
+            ..LoadShedConfig::default()
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks that the configuration is usable.
+    ///
+    /// # Returns
+    /// `Ok(())`, or a `ConfigError::InvalidValue` naming the first offending field.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.max_in_flight == 0 {
+            return Err(ConfigError::invalid(
+                "max_in_flight",
+                "must be greater than 0",
+            ));
+        }
+        if self.max_latency == Duration::ZERO {
+            return Err(ConfigError::invalid("max_latency", "must be non-zero"));
+        }
+        if !(self.smoothing > 0.0 && self.smoothing <= 1.0) {
+            return Err(ConfigError::invalid(
+                "smoothing",
+                "must be greater than 0 and at most 1",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Builder-style setter for `smoothing`.
+    ///
+    /// # Parameters
+    /// - `smoothing`: The weight of each new latency sample in the moving average.
+    ///
+    /// # Returns
+    /// A new `LoadShedConfig` instance with the updated smoothing factor.
+    pub fn with_smoothing(mut self, smoothing: f64) -> Self {
+        self.smoothing = smoothing;
+        self
+    }
+
+    /// Builder-style setter for `log`.
+    ///
+    /// # Parameters
+    /// - `log`: The logging behavior of the shedder.
+    ///
+    /// # Returns
+    /// A new `LoadShedConfig` instance with the updated logging behavior.
+    pub fn with_log(mut self, log: LogConfig) -> Self {
+        self.log = log;
+        self
+    }
+}
+
+/// Configuration for an `AdmissionController`.
+///
+/// The controller computes a load between 0 and 1, the highest of its in-flight utilization
+/// (`in_flight / max_in_flight`) and of its load signals, and rejects work of a priority once the
+/// load reaches the priority's threshold. `Critical` work ignores the signals and is only
+/// rejected once `max_in_flight` calls are running.
+///
+/// # Fields
+/// - `max_in_flight`: The number of calls running at once at which the load reaches 1.
+/// - `low_threshold`: The load from which `Low` priority work is rejected.
+/// - `normal_threshold`: The load from which `Normal` priority work is rejected.
+/// - `high_threshold`: The load from which `High` priority work is rejected.
+/// - `log`: Logging behavior of the controller; rejected calls are logged at `log.level`.
+///
+/// # Example
+/// ```
+/// use resilient_rs::config::AdmissionConfig;
+///
+/// let config = AdmissionConfig::new(200).with_thresholds(0.4, 0.7, 0.95);
+/// assert_eq!(config.low_threshold, 0.4);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct AdmissionConfig {
+    pub max_in_flight: usize,
+    pub low_threshold: f64,
+    pub normal_threshold: f64,
+    pub high_threshold: f64,
+    pub log: LogConfig,
+}
+
+impl Default for AdmissionConfig {
+    /// # Default Configuration
+    /// The default configuration sets:
+    /// - `max_in_flight` to 100
+    /// - `low_threshold` to 0.5
+    /// - `normal_threshold` to 0.75
+    /// - `high_threshold` to 0.9
+    /// - `log` to `LogConfig::default()` (rejected calls at `Warn`)
+    fn default() -> Self {
+        AdmissionConfig {
+            max_in_flight: 100,
+            low_threshold: 0.5,
+            normal_threshold: 0.75,
+            high_threshold: 0.9,
+            log: LogConfig::default(),
+        }
+    }
+}
+
+impl AdmissionConfig {
+    /// Creates a new `AdmissionConfig` with the default thresholds and `max_in_flight`
+    /// concurrent calls.
+    ///
+    /// # Parameters
+    /// - `max_in_flight`: The capacity of the service in concurrent calls. Must be greater than 0.
+    ///
+    /// # Panics
+    /// This function will panic if `max_in_flight` is 0. Use `try_new` to get a `ConfigError`
+    /// instead.
+    pub fn new(max_in_flight: usize) -> Self {
+        Self::try_new(max_in_flight).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates a new `AdmissionConfig`, returning an error instead of panicking on invalid input.
+    ///
+    /// # Parameters
+    /// - `max_in_flight`: The capacity of the service in concurrent calls. Must be greater than 0.
+    ///
+    /// # Returns
+    /// The configuration, or a `ConfigError::InvalidValue` naming the offending field.
+    pub fn try_new(max_in_flight: usize) -> Result<Self, ConfigError> {
+        let config = AdmissionConfig {
+            max_in_flight,
+            ..AdmissionConfig::default()
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks that the configuration is usable.
+    ///
+    /// # Returns
+    /// `Ok(())`, or a `ConfigError::InvalidValue` naming the first offending field.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.max_in_flight == 0 {
+            return Err(ConfigError::invalid(
+                "max_in_flight",
+                "must be greater than 0",
+            ));
+        }
+        let thresholds = [
+            ("low_threshold", self.low_threshold),
+            ("normal_threshold", self.normal_threshold),
+            ("high_threshold", self.high_threshold),
+        ];
+        for (field, threshold) in thresholds {
+            if !(threshold > 0.0 && threshold <= 1.0) {
+                return Err(ConfigError::invalid(
+                    field,
+                    "must be greater than 0 and at most 1",
+                ));
+            }
+        }
+        if self.low_threshold > self.normal_threshold || self.normal_threshold > self.high_threshold
+        {
+            return Err(ConfigError::invalid(
+                "low_threshold",
+                "thresholds must not decrease with the priority",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Builder-style setter for the rejection thresholds.
+    ///
+    /// # Parameters
+    /// - `low`: The load from which `Low` priority work is rejected.
+    /// - `normal`: The load from which `Normal` priority work is rejected.
+    /// - `high`: The load from which `High` priority work is rejected.
+    ///
+    /// # Returns
+    /// A new `AdmissionConfig` instance with the updated thresholds.
+    pub fn with_thresholds(mut self, low: f64, normal: f64, high: f64) -> Self {
+        self.low_threshold = low;
+        self.normal_threshold = normal;
+        self.high_threshold = high;
+        self
+    }
+
+    /// Builder-style setter for `log`.
+    ///
+    /// # Parameters
+    /// - `log`: The logging behavior of the controller.
+    ///
+    /// # Returns
+    /// A new `AdmissionConfig` instance with the updated logging behavior.
+    pub fn with_log(mut self, log: LogConfig) -> Self {
+        self.log = log;
+        self
+    }
+}
+
+/// Configuration for the rate limiters of the `ratelimit` module.
+///
+/// # Fields
+/// - `limit`: The maximum number of calls admitted per `window`.
+/// - `window`: The interval the limit applies to, e.g. one minute for "at most N calls per minute".
+/// - `log`: Logging behavior of the limiter; rejections are logged at `log.level`.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::config::RateLimitConfig;
+///
+/// let config = RateLimitConfig::new(600, Duration::from_secs(60));
+/// assert_eq!(config.limit, 600);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct RateLimitConfig {
+    pub limit: usize,
+    #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+    pub window: Duration,
+    pub log: LogConfig,
+}
+
+impl Default for RateLimitConfig {
+    /// # Default Configuration
+    /// The default configuration sets:
+    /// - `limit` to 100
+    /// - `window` to 1 second
+    /// - `log` to `LogConfig::default()` (rejections at `Warn`)
+    fn default() -> Self {
+        RateLimitConfig {
+            limit: 100,
+            window: Duration::from_secs(1),
+            log: LogConfig::default(),
+        }
+    }
+}
+
+impl RateLimitConfig {
+    /// Creates a new `RateLimitConfig` admitting `limit` calls per `window`.
+    ///
+    /// # Parameters
+    /// - `limit`: The maximum number of calls per window. Must be greater than 0.
+    /// - `window`: The interval the limit applies to. Must be non-zero.
+    ///
+    /// # Panics
+    /// This function will panic if a parameter is invalid. Use `try_new` to get a `ConfigError`
+    /// instead.
+    pub fn new(limit: usize, window: Duration) -> Self {
+        Self::try_new(limit, window).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates a new `RateLimitConfig`, returning an error instead of panicking on invalid input.
+    ///
+    /// # Parameters
+    /// - `limit`: The maximum number of calls per window. Must be greater than 0.
+    /// - `window`: The interval the limit applies to. Must be non-zero.
+    ///
+    /// # Returns
+    /// The configuration, or a `ConfigError::InvalidValue` naming the offending field.
+    pub fn try_new(limit: usize, window: Duration) -> Result<Self, ConfigError> {
+        let config = RateLimitConfig {
+            limit,
+            window,
+            log: LogConfig::default(),
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks that the configuration is usable.
+    ///
+    /// # Returns
+    /// `Ok(())`, or a `ConfigError::InvalidValue` naming the first offending field.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.limit == 0 {
+            return Err(ConfigError::invalid("limit", "must be greater than 0"));
+        }
+        if self.window == Duration::ZERO {
+            return Err(ConfigError::invalid("window", "must be non-zero"));
+        }
+        Ok(())
+    }
+
+    /// Builder-style setter for `log`.
+    ///
+    /// # Parameters
+    /// - `log`: The logging behavior of the limiter.
+    ///
+    /// # Returns
+    /// A new `RateLimitConfig` instance with the updated logging behavior.
+    pub fn with_log(mut self, log: LogConfig) -> Self {
+        self.log = log;
+        self
+    }
+}
+
+/// Configuration for a `ResultCache`.
+///
+/// # Fields
+/// - `ttl`: How long a successful result is served from the cache without calling the operation.
+/// - `max_stale`: How long past its `ttl` a cached result may still be served when the operation
+///   fails. `None`, the default, serves it however old it is; `Some(Duration::ZERO)` disables
+///   stale responses.
+/// - `log`: Logging behavior of the cache; stale responses are logged at `log.level`.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::config::CacheConfig;
+///
+/// let config = CacheConfig::new(Duration::from_secs(30)).with_max_stale(Duration::from_secs(600));
+/// assert_eq!(config.max_stale, Some(Duration::from_secs(600)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct CacheConfig {
+    #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+    pub ttl: Duration,
+    #[cfg_attr(feature = "serde", serde(with = "humantime_serde"))]
+    pub max_stale: Option<Duration>,
+    pub log: LogConfig,
+}
+
+impl Default for CacheConfig {
+    /// # Default Configuration
+    /// The default configuration sets:
+    /// - `ttl` to 60 seconds
+    /// - `max_stale` to `None` (stale results are served however old they are)
+    /// - `log` to `LogConfig::default()` (stale responses at `Warn`)
+    fn default() -> Self {
+        CacheConfig {
+            ttl: Duration::from_secs(60),
+            max_stale: None,
+            log: LogConfig::default(),
+        }
+    }
+}
+
+impl CacheConfig {
+    /// Creates a new `CacheConfig` caching successful results for `ttl`.
+    ///
+    /// # Parameters
+    /// - `ttl`: How long a result is served without calling the operation. Must be non-zero.
+    ///
+    /// # Panics
+    /// This function will panic if `ttl` is zero. Use `try_new` to get a `ConfigError` instead.
+    pub fn new(ttl: Duration) -> Self {
+        Self::try_new(ttl).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates a new `CacheConfig`, returning an error instead of panicking on invalid input.
+    ///
+    /// # Parameters
+    /// - `ttl`: How long a result is served without calling the operation. Must be non-zero.
+    ///
+    /// # Returns
+    /// The configuration, or a `ConfigError::InvalidValue` naming the offending field.
+    pub fn try_new(ttl: Duration) -> Result<Self, ConfigError> {
+        let config = CacheConfig {
+            ttl,
+            ..CacheConfig::default()
+        };
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Checks that the configuration is usable.
+    ///
+    /// # Returns
+    /// `Ok(())`, or a `ConfigError::InvalidValue` naming the first offending field.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.ttl == Duration::ZERO {
+            return Err(ConfigError::invalid("ttl", "must be non-zero"));
+        }
+        Ok(())
+    }
+
+    /// Builder-style setter for `max_stale`.
+    ///
+    /// # Parameters
+    /// - `max_stale`: How long past its `ttl` a result may be served when the operation fails.
+    ///
+    /// # Returns
+    /// A new `CacheConfig` instance with the updated staleness bound.
+    pub fn with_max_stale(mut self, max_stale: Duration) -> Self {
+        self.max_stale = Some(max_stale);
+        self
+    }
+
+    /// Builder-style setter for `log`.
+    ///
+    /// # Parameters
+    /// - `log`: The logging behavior of the cache.
+    ///
+    /// # Returns
+    /// A new `CacheConfig` instance with the updated logging behavior.
+    pub fn with_log(mut self, log: LogConfig) -> Self {
+        self.log = log;
+        self
+    }
+}
+
+/// An error produced while building or validating a configuration.
+///
+/// The variants name the offending setting so that misconfigurations can be reported at startup
+/// instead of surfacing as surprising runtime behavior.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigError {
+    /// A required environment variable is not set.
+    MissingEnvVar { name: String },
+    /// An environment variable is set but its value cannot be used.
+    InvalidEnvVar {
+        name: String,
+        value: String,
+        reason: String,
+    },
+    /// A configuration field holds a value that would make the pattern misbehave.
+    InvalidValue { field: &'static str, reason: String },
+    /// A resilience profile cannot be read, or is invalid at the given line.
+    InvalidProfile { line: Option<usize>, reason: String },
+}
+
+impl ConfigError {
+    fn invalid(field: &'static str, reason: impl Into<String>) -> Self {
+        ConfigError::InvalidValue {
+            field,
+            reason: reason.into(),
+        }
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::MissingEnvVar { name } => {
+                write!(f, "environment variable `{}` is required", name)
+            }
+            ConfigError::InvalidEnvVar {
+                name,
+                value,
+                reason,
+            } => write!(
+                f,
+                "environment variable `{}` has invalid value `{}`: {}",
+                name, value, reason
+            ),
+            ConfigError::InvalidValue { field, reason } => write!(f, "{} {}", field, reason),
+            ConfigError::InvalidProfile {
+                line: Some(line),
+                reason,
+            } => write!(f, "resilience profile, line {}: {}", line, reason),
+            ConfigError::InvalidProfile { line: None, reason } => {
+                write!(f, "resilience profile: {}", reason)
+            }
+        }
+    }
+}
+
+impl Error for ConfigError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::VirtualClock;
+    use std::cell::Cell;
+
+    #[test]
+    fn test_circuit_breaker_try_new_reports_field() {
+        assert_eq!(
+            CircuitBreakerConfig::try_new(0, 1, Duration::from_secs(1)).unwrap_err(),
+            ConfigError::InvalidValue {
+                field: "success_threshold",
+                reason: "must be greater than 0".to_string()
+            }
+        );
+        assert!(CircuitBreakerConfig::try_new(1, 1, Duration::ZERO).is_err());
+        assert!(CircuitBreakerConfig::try_new(1, 1, Duration::from_millis(1)).is_ok());
+    }
+
+    #[test]
+    fn test_circuit_breaker_rejects_zero_concurrency_cap() {
+        let config = CircuitBreakerConfig::default().with_max_concurrent_calls(0);
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidValue {
+                field: "max_concurrent_calls",
+                ..
+            })
+        ));
+        assert!(config.with_max_concurrent_calls(1).validate().is_ok());
+    }
+
+    #[test]
+    fn test_bulkhead_try_new_reports_field() {
+        assert_eq!(
+            BulkheadConfig::try_new(0).unwrap_err(),
+            ConfigError::InvalidValue {
+                field: "max_concurrent_calls",
+                reason: "must be greater than 0".to_string()
+            }
+        );
+        assert_eq!(BulkheadConfig::try_new(3).unwrap().max_wait, Duration::ZERO);
+    }
+
+    #[test]
+    fn test_rate_limit_try_new_reports_field() {
+        assert!(matches!(
+            RateLimitConfig::try_new(0, Duration::from_secs(1)),
+            Err(ConfigError::InvalidValue { field: "limit", .. })
+        ));
+        assert!(matches!(
+            RateLimitConfig::try_new(1, Duration::ZERO),
+            Err(ConfigError::InvalidValue {
+                field: "window",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_aimd_config_validation() {
+        assert!(AimdConfig::default().validate().is_ok());
+        assert!(matches!(
+            AimdConfig::default().with_limits(1, 2, 10).validate(),
+            Err(ConfigError::InvalidValue {
+                field: "initial_limit",
+                ..
+            })
+        ));
+        assert!(matches!(
+            AimdConfig::default().with_backoff_ratio(1.0).validate(),
+            Err(ConfigError::InvalidValue {
+                field: "backoff_ratio",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_cooldown_escalation() {
+        let escalation =
+            CooldownEscalation::new(2.0, Duration::from_secs(10), Duration::from_secs(60));
+        let cooldown = Duration::from_secs(2);
+        assert_eq!(escalation.cooldown(cooldown, 0), cooldown);
+        assert_eq!(escalation.cooldown(cooldown, 2), Duration::from_secs(8));
+        assert_eq!(escalation.cooldown(cooldown, 40), Duration::from_secs(10));
+
+        let config = CircuitBreakerConfig::new(1, 1, cooldown);
+        assert!(
+            config
+                .with_cooldown_escalation(escalation)
+                .validate()
+                .is_ok()
+        );
+        let err = config
+            .with_cooldown_escalation(CooldownEscalation::new(
+                0.5,
+                Duration::from_secs(10),
+                Duration::from_secs(60),
+            ))
+            .validate()
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::InvalidValue {
+                field: "cooldown_escalation.multiplier",
+                ..
+            }
+        ));
+        assert!(
+            config
+                .with_cooldown_escalation(CooldownEscalation::new(
+                    2.0,
+                    Duration::from_secs(1),
+                    Duration::from_secs(60),
+                ))
+                .validate()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_failure_window_validation() {
+        let config = CircuitBreakerConfig::default();
+        assert!(
+            config
+                .with_window(FailureWindow::count(10, 50.0, 5))
+                .validate()
+                .is_ok()
+        );
+        assert!(
+            config
+                .with_window(FailureWindow::count(0, 50.0, 0))
+                .validate()
+                .is_err()
+        );
+        assert!(
+            config
+                .with_window(FailureWindow::count(10, 150.0, 5))
+                .validate()
+                .is_err()
+        );
+        assert!(
+            config
+                .with_window(FailureWindow::count(10, 50.0, 20))
+                .validate()
+                .is_err()
+        );
+        assert!(
+            config
+                .with_window(FailureWindow::time(Duration::from_secs(30), 50.0, 100))
+                .validate()
+                .is_ok()
+        );
+        assert!(
+            config
+                .with_window(FailureWindow::time(Duration::ZERO, 50.0, 1))
+                .validate()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_retry_config_validation() {
+        let invalid: [RetryConfig<()>; 4] = [
+            RetryConfig::new(0, Duration::from_millis(1), RetryStrategy::Linear),
+            RetryConfig::new(1000, Duration::ZERO, RetryStrategy::Linear),
+            RetryConfig::new(
+                3,
+                Duration::from_millis(1),
+                RetryStrategy::ExponentialBackoffWithJitter {
+                    jitter_factor: f64::NAN,
+                },
+            ),
+            RetryConfig::new(
+                3,
+                Duration::from_millis(1),
+                RetryStrategy::ArithmeticProgression { coefficient: 0 },
+            ),
+        ];
+        for config in invalid {
+            assert!(config.validate().is_err(), "{:?}", config);
+        }
+        assert!(
+            RetryConfig::<()>::new(5, Duration::ZERO, RetryStrategy::Linear)
+                .validate()
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_max_delay_caps_strategy_delays() {
+        let config: RetryConfig<()> = RetryConfig::new(
+            10,
+            Duration::from_millis(100),
+            RetryStrategy::ExponentialBackoff,
+        )
+        .with_max_delay(Duration::from_millis(250))
+        .with_policies(PolicyTable::new().on_transient(ClassPolicy::new(
+            10,
+            Duration::from_millis(100),
+            RetryStrategy::ExponentialBackoff,
+        )));
+        let (_, wait) = config.budget_for(&ErrorClass::Transient, 4, config.delay);
+        assert_eq!(wait, Duration::from_millis(250));
+        let (_, wait) = config.budget_for(&ErrorClass::Permanent, 1, Duration::from_secs(5));
+        assert_eq!(wait, Duration::from_millis(250));
+    }
+
+    #[test]
+    fn test_retry_config_validates_class_policies() {
+        let config: RetryConfig<()> =
+            RetryConfig::default().with_policies(PolicyTable::new().on_throttled(
+                ClassPolicy::new(0, Duration::from_secs(1), RetryStrategy::Linear),
+            ));
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidValue {
+                field: "policies.throttled.max_attempts",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_retry_config_getters_expose_the_builder_settings() {
+        let config = RetryConfig::<String>::builder()
+            .exponential()
+            .max_attempts(5)
+            .delay(Duration::from_millis(50))
+            .max_delay(Duration::from_secs(2))
+            .retry_condition(|err| err.contains("timeout"))
+            .catch_panics()
+            .build();
+        assert_eq!(config.max_attempts(), 5);
+        assert_eq!(config.delay(), Duration::from_millis(50));
+        assert_eq!(config.strategy(), RetryStrategy::ExponentialBackoff);
+        assert_eq!(config.max_delay(), Some(Duration::from_secs(2)));
+        assert!(config.retry_condition().unwrap()(&"timeout".to_string()));
+        assert!(config.error_classifier().is_none());
+        assert_eq!(config.policies(), &PolicyTable::default());
+        assert!(config.stats().is_none());
+        assert!(config.catch_panics());
+        assert!(config.schedule().is_none() && config.timer_wheel().is_none());
+    }
+
+    #[test]
+    fn test_circuit_breaker_setters_leave_validation_to_validate() {
+        let config = CircuitBreakerConfig::default().with_failure_threshold(0);
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidValue {
+                field: "failure_threshold",
+                ..
+            })
+        ));
+        let config = CircuitBreakerConfig::default()
+            .with_success_threshold(0)
+            .with_cooldown_period(Duration::ZERO);
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidValue {
+                field: "success_threshold",
+                ..
+            })
+        ));
+        let config = CircuitBreakerConfig::default().with_cooldown_period(Duration::ZERO);
+        assert!(matches!(
+            config.validate(),
+            Err(ConfigError::InvalidValue {
+                field: "cooldown_period",
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_decorated_closures_retry_every_call() {
+        let clock = VirtualClock::new();
+        let _guard = clock.enter();
+        let calls = Cell::new(0);
+        let mut fetch = RetryConfig::new(3, Duration::from_secs(1), RetryStrategy::Linear)
+            .decorate(|| {
+                calls.set(calls.get() + 1);
+                if calls.get() % 2 == 1 {
+                    Err("flaky")
+                } else {
+                    Ok(calls.get())
+                }
+            });
+        assert_eq!(fetch(), Ok(2));
+        assert_eq!(fetch(), Ok(4));
+        assert_eq!(clock.elapsed(), Duration::from_secs(2));
+
+        let attempts = Cell::new(0);
+        let mut publish = RetryConfig::new(2, Duration::from_secs(1), RetryStrategy::Linear)
+            .decorate_async(|| {
+                attempts.set(attempts.get() + 1);
+                async { Err::<(), _>("broker down") }
+            });
+        assert_eq!(clock.block_on(publish()), Err("broker down"));
+        assert_eq!(clock.block_on(publish()), Err("broker down"));
+        assert_eq!(attempts.get(), 4);
+    }
+
+    #[test]
+    fn test_cloned_retry_configs_compare_settings_and_shared_handles() {
+        let stats = Stats::new();
+        let base: RetryConfig<String> = RetryConfig::new(
+            3,
+            Duration::from_millis(100),
+            RetryStrategy::ExponentialBackoff,
+        )
+        .with_retry_condition(|err: &String| err.contains("timeout"))
+        .with_stats(stats.clone());
+        let copy = base.clone();
+        assert_eq!(copy, base);
+        assert!(copy.stats.as_ref().unwrap().ptr_eq(&stats));
+        assert_ne!(base.clone().with_delay(Duration::from_millis(50)), base);
+        assert_ne!(base.clone().with_stats(Stats::new()), base);
+
+        let chain = FallbackChain::new(Duration::from_secs(1))
+            .with_fallback("default", |_| Ok("default".to_string()));
+        assert_eq!(chain.clone().len(), 1);
+        assert_eq!(
+            CircuitBreakerConfig::default().with_failure_threshold(3),
+            CircuitBreakerConfig::default().with_failure_threshold(3)
+        );
+    }
+
+    #[test]
+    fn test_retry_policy_erases_and_restores_classification() {
+        let config: RetryConfig<fmt::Error> =
+            RetryConfig::new(4, Duration::from_millis(10), RetryStrategy::Linear)
+                .with_error_classifier(|_: &fmt::Error| ErrorClass::Fatal);
+        let policy = RetryPolicy::from(config.clone());
+        assert_eq!(policy.max_attempts, 4);
+        assert_eq!(policy.classify(&fmt::Error), ErrorClass::Fatal);
+        let other = ConfigError::invalid("delay", "too short");
+        assert_eq!(policy.classify(&other), ErrorClass::Transient);
+
+        let restored = policy.to_config::<fmt::Error>();
+        assert_eq!(restored.classify(&fmt::Error), ErrorClass::Fatal);
+        assert_eq!(restored.delay, config.delay);
+        let unclassified = RetryPolicy::from(RetryConfig::<fmt::Error>::default());
+        assert!(unclassified.classifier.is_none());
+    }
+
+    #[test]
+    fn test_presets_are_valid_and_capped() {
+        for config in [
+            RetryConfig::<&str>::network_default(),
+            RetryConfig::aggressive(),
+            RetryConfig::conservative(),
+        ] {
+            assert_eq!(config.validate(), Ok(()));
+            let max_delay = config.max_delay.unwrap();
+            let mut backoff = config.backoff();
+            while let Some(delay) = backoff.next_delay() {
+                assert!(delay <= max_delay, "{:?} > {:?}", delay, max_delay);
+            }
+            assert_eq!(backoff.attempts(), config.max_attempts - 1);
+        }
+        assert_eq!(CircuitBreakerConfig::sensitive().validate(), Ok(()));
+        assert_eq!(CircuitBreakerConfig::tolerant().validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_precomputed_schedule_matches_computed_delays() {
+        let computed =
+            RetryConfig::<&str>::new(5, Duration::from_secs(1), RetryStrategy::FibonacciBackoff)
+                .with_max_delay(Duration::from_secs(4));
+        let precomputed = computed.clone().with_precomputed_schedule();
+        let mut delay = computed.delay;
+        for retry in 1..5 {
+            let next = computed.next_delay(delay, retry);
+            assert_eq!(precomputed.next_delay(delay, retry), next);
+            delay = next;
+        }
+        assert_eq!(
+            precomputed.schedule.as_ref().unwrap().delays(),
+            [1, 1, 2, 4, 4].map(Duration::from_secs)
+        );
+
+        let clock = VirtualClock::new();
+        let _guard = clock.enter();
+        let result: Result<(), _> = crate::synchronous::retry(|| Err("down"), &precomputed);
+        assert_eq!(result, Err("down"));
+        assert_eq!(clock.elapsed(), Duration::from_secs(8));
+
+        // A schedule computed for other settings is ignored.
+        let mut changed = precomputed;
+        changed.delay = Duration::from_secs(2);
+        assert_eq!(
+            changed.next_delay(Duration::from_secs(2), 1),
+            Duration::from_secs(2)
+        );
+        assert_eq!(
+            changed.next_delay(Duration::from_secs(2), 2),
+            Duration::from_secs(4)
+        );
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod serde_tests {
+    use super::*;
+
+    #[test]
+    fn test_retry_config_from_json_with_humantime_durations() {
+        let config: RetryConfig<String> = serde_json::from_str(
+            r#"{
+                "max_attempts": 5,
+                "delay": "250ms",
+                "strategy": { "exponential_backoff_with_jitter": { "jitter_factor": 0.2 } },
+                "policies": { "throttled": { "max_attempts": 10, "delay": "1s", "strategy": "exponential_backoff" } },
+                "log": { "level": "DEBUG" }
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(config.max_attempts, 5);
+        assert_eq!(config.delay, Duration::from_millis(250));
+        assert!(matches!(
+            config.strategy,
+            RetryStrategy::ExponentialBackoffWithJitter { jitter_factor } if jitter_factor == 0.2
+        ));
+        let throttled = config.policies.throttled.unwrap();
+        assert_eq!(throttled.max_attempts, 10);
+        assert_eq!(throttled.delay, Duration::from_secs(1));
+        assert_eq!(config.log.level, Level::Debug);
+        assert!(config.retry_condition.is_none());
+    }
+
+    #[test]
+    fn test_retry_config_missing_fields_use_defaults() {
+        let config: RetryConfig<String> =
+            serde_json::from_str(r#"{ "strategy": "linear" }"#).unwrap();
+        assert_eq!(config.max_attempts, 3);
+        assert_eq!(config.delay, Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_circuit_breaker_config_round_trip() {
+        let config = CircuitBreakerConfig::new(2, 4, Duration::from_secs(30));
+        let json = serde_json::to_string(&config).unwrap();
+        assert!(json.contains("\"cooldown_period\":\"30s\""));
+        let parsed: CircuitBreakerConfig = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.failure_threshold, 4);
+        assert_eq!(parsed.cooldown_period, Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_exec_config_from_json() {
+        let config: ExecConfig<String> =
+            serde_json::from_str(r#"{ "timeout_duration": "1m 30s" }"#).unwrap();
+        assert_eq!(config.timeout_duration, Duration::from_secs(90));
+        assert!(config.fallback.is_none());
+    }
 }
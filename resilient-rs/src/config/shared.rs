@@ -0,0 +1,156 @@
+use super::{CircuitBreakerConfig, RetryConfig};
+use std::fmt;
+use std::sync::{Arc, RwLock};
+
+/// A hot-reloadable handle to a `RetryConfig`.
+///
+/// Retry loops started with `retry_shared` read the current configuration at the start of every
+/// attempt, so calling `update` takes effect for operations that are already running. Cloning the
+/// handle is cheap and every clone observes the same configuration, which makes it easy to hand
+/// one clone to an admin endpoint or a config watcher and the others to the code doing the work.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::config::{RetryConfig, SharedRetryConfig};
+/// use resilient_rs::strategies::RetryStrategy;
+///
+/// let shared: SharedRetryConfig<String> =
+///     SharedRetryConfig::new(RetryConfig::new(3, Duration::from_millis(100), RetryStrategy::Linear));
+///
+/// // During an incident, back off harder without restarting the service.
+/// shared.update(RetryConfig::new(5, Duration::from_secs(1), RetryStrategy::ExponentialBackoff));
+/// assert_eq!(shared.load().max_attempts(), 5);
+/// ```
+pub struct SharedRetryConfig<E> {
+    current: Arc<RwLock<Arc<RetryConfig<E>>>>,
+}
+
+impl<E> SharedRetryConfig<E> {
+    /// Creates a new handle holding `config`.
+    pub fn new(config: RetryConfig<E>) -> Self {
+        SharedRetryConfig {
+            current: Arc::new(RwLock::new(Arc::new(config))),
+        }
+    }
+
+    /// Returns a snapshot of the current configuration.
+    ///
+    /// The snapshot is unaffected by later updates, so it can be held for as long as needed.
+    pub fn load(&self) -> Arc<RetryConfig<E>> {
+        self.current.read().unwrap().clone()
+    }
+
+    /// Replaces the configuration observed by every clone of this handle.
+    ///
+    /// # Arguments
+    /// * `config` - The new configuration, used from the next attempt of running retry loops.
+    pub fn update(&self, config: RetryConfig<E>) {
+        *self.current.write().unwrap() = Arc::new(config);
+    }
+
+    /// Replaces the configuration with one derived from the current configuration.
+    ///
+    /// # Arguments
+    /// * `f` - A function receiving the current configuration and returning the new one.
+    pub fn update_with(&self, f: impl FnOnce(&RetryConfig<E>) -> RetryConfig<E>) {
+        let mut current = self.current.write().unwrap();
+        *current = Arc::new(f(&current));
+    }
+}
+
+impl<E> Clone for SharedRetryConfig<E> {
+    fn clone(&self) -> Self {
+        SharedRetryConfig {
+            current: self.current.clone(),
+        }
+    }
+}
+
+impl<E> fmt::Debug for SharedRetryConfig<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SharedRetryConfig")
+            .field(&self.load())
+            .finish()
+    }
+}
+
+/// A hot-reloadable handle to a `CircuitBreakerConfig`.
+///
+/// A `CircuitBreaker` created with `with_shared_config` reads the current configuration on every
+/// call, so thresholds and the cooldown period can be tightened or loosened at runtime. Cloning the
+/// handle is cheap and every clone observes the same configuration.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::config::{CircuitBreakerConfig, SharedCircuitBreakerConfig};
+///
+/// let shared = SharedCircuitBreakerConfig::new(CircuitBreakerConfig::default());
+/// shared.update_with(|config| config.with_failure_threshold(20));
+/// assert_eq!(shared.load().failure_threshold, 20);
+/// ```
+#[derive(Debug, Clone)]
+pub struct SharedCircuitBreakerConfig {
+    current: Arc<RwLock<CircuitBreakerConfig>>,
+}
+
+impl SharedCircuitBreakerConfig {
+    /// Creates a new handle holding `config`.
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        SharedCircuitBreakerConfig {
+            current: Arc::new(RwLock::new(config)),
+        }
+    }
+
+    /// Returns a copy of the current configuration.
+    pub fn load(&self) -> CircuitBreakerConfig {
+        *self.current.read().unwrap()
+    }
+
+    /// Replaces the configuration observed by every clone of this handle.
+    ///
+    /// # Arguments
+    /// * `config` - The new configuration, used from the next call of the breakers sharing it.
+    pub fn update(&self, config: CircuitBreakerConfig) {
+        *self.current.write().unwrap() = config;
+    }
+
+    /// Replaces the configuration with one derived from the current configuration.
+    ///
+    /// # Arguments
+    /// * `f` - A function receiving a copy of the current configuration and returning the new one.
+    pub fn update_with(&self, f: impl FnOnce(CircuitBreakerConfig) -> CircuitBreakerConfig) {
+        let mut current = self.current.write().unwrap();
+        *current = f(*current);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::RetryStrategy;
+    use std::time::Duration;
+
+    #[test]
+    fn test_clones_observe_updates() {
+        let shared: SharedRetryConfig<()> = SharedRetryConfig::new(RetryConfig::default());
+        let clone = shared.clone();
+        let snapshot = shared.load();
+        clone.update_with(|current| {
+            RetryConfig::new(
+                current.max_attempts + 2,
+                current.delay,
+                RetryStrategy::Linear,
+            )
+        });
+        assert_eq!(shared.load().max_attempts(), 5);
+        assert_eq!(snapshot.max_attempts, 3);
+
+        let breaker = SharedCircuitBreakerConfig::new(CircuitBreakerConfig::default());
+        breaker
+            .clone()
+            .update(CircuitBreakerConfig::new(1, 1, Duration::from_millis(10)));
+        assert_eq!(breaker.load().failure_threshold, 1);
+    }
+}
@@ -0,0 +1,136 @@
+use crate::config::{CircuitBreakerConfig, RetryConfig};
+use std::sync::OnceLock;
+
+// The error-independent settings of the global retry policy; `retry_condition` and
+// `error_classifier` are left unset.
+static GLOBAL_RETRY_CONFIG: OnceLock<RetryConfig<()>> = OnceLock::new();
+static GLOBAL_CIRCUIT_BREAKER_DEFAULTS: OnceLock<CircuitBreakerConfig> = OnceLock::new();
+
+/// Sets the process-wide retry policy used by `retry_with_defaults`.
+///
+/// Small applications can configure their retry behavior once at startup instead of passing a
+/// `RetryConfig` through every function signature. The policy can be set only once, so that every
+/// caller observes the same settings.
+///
+/// The policy applies to operations failing with any error type, so its `retry_condition` and
+/// `error_classifier`, which are tied to the error type `E`, are not kept: every error is
+/// retried up to `max_attempts`.
+///
+/// # Arguments
+/// * `config` - The retry configuration to use by default.
+///
+/// # Returns
+/// `true` if the policy was set, or `false` if a policy was already set.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::strategies::RetryStrategy;
+///
+/// let config = RetryConfig::<()>::new(5, Duration::from_millis(1), RetryStrategy::Linear);
+/// assert!(resilient_rs::set_global_retry_config(config));
+///
+/// let config = resilient_rs::global_retry_config::<std::io::Error>();
+/// assert_eq!(config.max_attempts(), 5);
+/// assert!(!resilient_rs::set_global_retry_config(RetryConfig::<()>::default()));
+/// ```
+pub fn set_global_retry_config<E>(config: RetryConfig<E>) -> bool {
+    GLOBAL_RETRY_CONFIG.set(copy_settings(&config)).is_ok()
+}
+
+/// Returns the process-wide retry policy for operations failing with errors of type `E`.
+///
+/// # Returns
+/// The policy set with `set_global_retry_config`, or `RetryConfig::default()` if none was set.
+pub fn global_retry_config<E>() -> RetryConfig<E> {
+    GLOBAL_RETRY_CONFIG
+        .get()
+        .map(copy_settings)
+        .unwrap_or_default()
+}
+
+/// Sets the process-wide circuit breaker configuration returned by
+/// `global_circuit_breaker_defaults`.
+///
+/// The configuration can be set only once, typically at startup.
+///
+/// # Arguments
+/// * `config` - The circuit breaker configuration to use by default.
+///
+/// # Returns
+/// `true` if the configuration was set, or `false` if a configuration was already set.
+pub fn set_global_circuit_breaker_defaults(config: CircuitBreakerConfig) -> bool {
+    GLOBAL_CIRCUIT_BREAKER_DEFAULTS.set(config).is_ok()
+}
+
+/// Returns the process-wide circuit breaker configuration.
+///
+/// # Returns
+/// The configuration set with `set_global_circuit_breaker_defaults`, or
+/// `CircuitBreakerConfig::default()` if none was set.
+///
+/// # Example
+/// ```
+/// use resilient_rs::asynchronous::CircuitBreaker;
+///
+/// let breaker: CircuitBreaker = CircuitBreaker::new(resilient_rs::global_circuit_breaker_defaults());
+/// ```
+pub fn global_circuit_breaker_defaults() -> CircuitBreakerConfig {
+    GLOBAL_CIRCUIT_BREAKER_DEFAULTS
+        .get()
+        .copied()
+        .unwrap_or_default()
+}
+
+/// Copies the error-independent settings of a `RetryConfig` to a `RetryConfig` for another
+/// error type.
+fn copy_settings<A, B>(config: &RetryConfig<A>) -> RetryConfig<B> {
+    RetryConfig {
+        max_attempts: config.max_attempts,
+        delay: config.delay,
+        strategy: config.strategy,
+        max_delay: config.max_delay,
+        retry_condition: None,
+        error_classifier: None,
+        policies: config.policies.clone(),
+        stats: config.stats.clone(),
+        log: config.log,
+        catch_panics: config.catch_panics,
+        schedule: config.schedule.clone(),
+        timer_wheel: config.timer_wheel.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::RetryStrategy;
+    use crate::synchronous::retry_with_defaults;
+    use std::time::Duration;
+
+    #[test]
+    fn test_global_defaults_are_set_once_and_used_by_retry_with_defaults() {
+        let config = RetryConfig::<String>::new(4, Duration::from_millis(1), RetryStrategy::Linear)
+            .with_retry_condition(|err| err != "fatal");
+        assert!(set_global_retry_config(config));
+        let rejected = RetryConfig::<()>::new(9, Duration::ZERO, RetryStrategy::Linear);
+        assert!(!set_global_retry_config(rejected));
+
+        let mut attempts = 0;
+        let result: Result<(), _> = retry_with_defaults(|| {
+            attempts += 1;
+            Err("fatal")
+        });
+        assert_eq!(result, Err("fatal"));
+        assert_eq!(attempts, 4);
+        assert!(global_retry_config::<String>().retry_condition.is_none());
+
+        let breaker = CircuitBreakerConfig::new(1, 1, Duration::from_secs(30));
+        assert!(set_global_circuit_breaker_defaults(breaker));
+        assert!(!set_global_circuit_breaker_defaults(
+            CircuitBreakerConfig::default()
+        ));
+        assert_eq!(global_circuit_breaker_defaults().failure_threshold, 1);
+    }
+}
@@ -0,0 +1,350 @@
+use crate::asynchronous::{self, schedule_class_retry};
+use crate::config::RetryConfig;
+use crate::events::{self, ResilienceEvent};
+use crate::logging::Level;
+use crate::logging::log_with;
+use crate::synchronous;
+use crate::time;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant, SystemTime};
+
+/// A message whose processing failed on every attempt, handed to a `DeadLetterSink`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeadLetter<M, E> {
+    /// The message that could not be processed.
+    pub message: M,
+    /// The error of every attempt, oldest first.
+    pub errors: Vec<E>,
+    /// When the first attempt started.
+    pub first_attempt_at: SystemTime,
+    /// The time spent processing the message, including the delays between attempts.
+    pub elapsed: Duration,
+}
+
+impl<M, E> DeadLetter<M, E> {
+    /// Returns the number of attempts made.
+    pub fn attempts(&self) -> usize {
+        self.errors.len()
+    }
+
+    /// Returns the error of the last attempt.
+    pub fn last_error(&self) -> &E {
+        self.errors
+            .last()
+            .expect("a dead letter has at least one error")
+    }
+}
+
+/// A destination for the messages that could not be processed, such as a dead-letter topic or
+/// table, to inspect and replay them later.
+///
+/// Any `Fn(DeadLetter<M, E>)` closure implements this trait, e.g. one sending the letter into a
+/// channel drained by an asynchronous publisher.
+pub trait DeadLetterSink<M, E>: Send + Sync {
+    /// Stores a dead letter.
+    fn dead_letter(&self, letter: DeadLetter<M, E>);
+}
+
+impl<M, E, F> DeadLetterSink<M, E> for F
+where
+    F: Fn(DeadLetter<M, E>) + Send + Sync,
+{
+    fn dead_letter(&self, letter: DeadLetter<M, E>) {
+        self(letter)
+    }
+}
+
+/// A `DeadLetterSink` keeping dead letters in memory, for a single process and for tests.
+pub struct InMemoryDeadLetters<M, E> {
+    letters: Mutex<Vec<DeadLetter<M, E>>>,
+}
+
+impl<M, E> InMemoryDeadLetters<M, E> {
+    /// Creates an empty sink.
+    pub fn new() -> Self {
+        InMemoryDeadLetters {
+            letters: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Returns the number of dead letters stored.
+    pub fn len(&self) -> usize {
+        self.letters().len()
+    }
+
+    /// Returns `true` if no dead letter is stored.
+    pub fn is_empty(&self) -> bool {
+        self.letters().is_empty()
+    }
+
+    /// Removes and returns every dead letter stored, oldest first, e.g. to replay them.
+    pub fn drain(&self) -> Vec<DeadLetter<M, E>> {
+        std::mem::take(&mut *self.letters())
+    }
+
+    fn letters(&self) -> MutexGuard<'_, Vec<DeadLetter<M, E>>> {
+        self.letters.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<M, E> Default for InMemoryDeadLetters<M, E> {
+    fn default() -> Self {
+        InMemoryDeadLetters::new()
+    }
+}
+
+impl<M: Send, E: Send> DeadLetterSink<M, E> for InMemoryDeadLetters<M, E> {
+    fn dead_letter(&self, letter: DeadLetter<M, E>) {
+        self.letters().push(letter);
+    }
+}
+
+/// The outcome of a message handled by a `DeadLetterQueue`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Handled<T> {
+    /// The message was processed, after `attempts` attempts.
+    Processed { output: T, attempts: usize },
+    /// Every attempt failed and the message was handed to the dead-letter sink.
+    DeadLettered { attempts: usize },
+}
+
+impl<T> Handled<T> {
+    /// Returns `true` if the message was handed to the dead-letter sink.
+    pub fn is_dead_lettered(&self) -> bool {
+        matches!(self, Handled::DeadLettered { .. })
+    }
+
+    /// Returns the output of the handler, or `None` if the message was dead-lettered.
+    pub fn output(self) -> Option<T> {
+        match self {
+            Handled::Processed { output, .. } => Some(output),
+            Handled::DeadLettered { .. } => None,
+        }
+    }
+}
+
+/// Processes messages with a per-message retry policy, sending the messages that still fail to a
+/// dead-letter sink instead of returning the error.
+///
+/// Every message is attempted according to the `RetryConfig`: errors classified as retryable are
+/// retried with its delays and strategy, and the message is dead-lettered, along with the error
+/// of every attempt, once the attempts are exhausted or an error is not retryable. A consumer can
+/// then commit the message's offset in both cases and move on, so a poison message never blocks
+/// its partition.
+///
+/// # Example
+/// ```
+/// use std::num::ParseIntError;
+/// use std::sync::Arc;
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::dlq::{DeadLetterQueue, InMemoryDeadLetters};
+///
+/// // Malformed messages are not worth retrying.
+/// let config = RetryConfig::default().with_retry_condition(|_: &ParseIntError| false);
+/// let dead_letters = Arc::new(InMemoryDeadLetters::new());
+/// let queue = DeadLetterQueue::new(config, dead_letters.clone());
+///
+/// let handled = queue.handle("not a number".to_string(), |message| message.parse::<u32>());
+/// assert!(handled.is_dead_lettered());
+/// assert_eq!(dead_letters.drain()[0].message, "not a number");
+/// ```
+pub struct DeadLetterQueue<M, E> {
+    retry: RetryConfig<E>,
+    sink: Arc<dyn DeadLetterSink<M, E>>,
+}
+
+impl<M, E> DeadLetterQueue<M, E> {
+    /// Creates a queue retrying messages according to `retry` and dead-lettering them to `sink`.
+    ///
+    /// # Arguments
+    /// * `retry` - The attempts, delays and strategy of the retries of every message.
+    /// * `sink` - The destination of the messages that could not be processed.
+    pub fn new(retry: RetryConfig<E>, sink: Arc<dyn DeadLetterSink<M, E>>) -> Self {
+        DeadLetterQueue { retry, sink }
+    }
+
+    /// Processes a message with a blocking handler.
+    ///
+    /// # Returns
+    /// - `Handled::Processed` with the output of the first successful attempt.
+    /// - `Handled::DeadLettered` once the message was handed to the sink.
+    pub fn handle<F, T>(&self, message: M, mut handler: F) -> Handled<T>
+    where
+        F: FnMut(&M) -> Result<T, E>,
+    {
+        let mut attempt = self.start();
+        loop {
+            attempt.begin();
+            match attempt.end(&self.retry, handler(&message)) {
+                Step::Done(output) => return attempt.processed(output),
+                Step::Retry(wait) => {
+                    synchronous::back_off(None, wait);
+                }
+                Step::GiveUp => return attempt.dead_letter(self, message),
+            }
+        }
+    }
+
+    /// Processes a message with an asynchronous handler.
+    ///
+    /// # Returns
+    /// The same as `handle`.
+    pub async fn handle_async<F, Fut, T>(&self, message: M, mut handler: F) -> Handled<T>
+    where
+        F: FnMut(&M) -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut attempt = self.start();
+        loop {
+            attempt.begin();
+            match attempt.end(&self.retry, handler(&message).await) {
+                Step::Done(output) => return attempt.processed(output),
+                Step::Retry(wait) => {
+                    asynchronous::back_off(None, self.retry.timer_wheel.as_ref(), wait).await;
+                }
+                Step::GiveUp => return attempt.dead_letter(self, message),
+            }
+        }
+    }
+
+    fn start(&self) -> Attempts<E> {
+        Attempts {
+            errors: Vec::new(),
+            delay: self.retry.delay,
+            first_attempt_at: time::system_now(),
+            started: time::now(),
+            attempt_started: time::now(),
+        }
+    }
+}
+
+/// What to do after an attempt.
+enum Step<T> {
+    Done(T),
+    Retry(Duration),
+    GiveUp,
+}
+
+/// The state of the attempts at processing one message.
+struct Attempts<E> {
+    errors: Vec<E>,
+    delay: Duration,
+    first_attempt_at: SystemTime,
+    started: Instant,
+    attempt_started: Instant,
+}
+
+impl<E> Attempts<E> {
+    fn begin(&mut self) {
+        events::emit(ResilienceEvent::AttemptStarted {
+            attempt: self.errors.len() + 1,
+        });
+        self.attempt_started = time::now();
+    }
+
+    fn end<T>(&mut self, retry: &RetryConfig<E>, result: Result<T, E>) -> Step<T> {
+        let elapsed = time::elapsed(self.attempt_started);
+        retry.record(|stats| stats.record_attempt(elapsed));
+        let err = match result {
+            Ok(output) => {
+                let retried = !self.errors.is_empty();
+                retry.record(|stats| stats.record_success(retried));
+                return Step::Done(output);
+            }
+            Err(err) => err,
+        };
+        let attempt = self.errors.len() + 1;
+        let class = retry.classify(&err);
+        self.errors.push(err);
+        match schedule_class_retry(retry, class, attempt, self.delay) {
+            Some(wait) => {
+                self.delay = retry.next_delay(self.delay, attempt);
+                Step::Retry(wait)
+            }
+            None => Step::GiveUp,
+        }
+    }
+
+    fn processed<T>(self, output: T) -> Handled<T> {
+        Handled::Processed {
+            output,
+            attempts: self.errors.len() + 1,
+        }
+    }
+
+    fn dead_letter<M, T>(self, queue: &DeadLetterQueue<M, E>, message: M) -> Handled<T> {
+        let attempts = self.errors.len();
+        log_with!(
+            queue.retry.log,
+            Level::Warn,
+            "Message failed after {} attempts, sending it to the dead-letter sink",
+            attempts
+        );
+        queue.sink.dead_letter(DeadLetter {
+            message,
+            errors: self.errors,
+            first_attempt_at: self.first_attempt_at,
+            elapsed: time::elapsed(self.started),
+        });
+        Handled::DeadLettered { attempts }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classifier::ErrorClass;
+    use crate::strategies::RetryStrategy;
+
+    fn retry_config() -> RetryConfig<String> {
+        RetryConfig::new(3, Duration::from_millis(1), RetryStrategy::Linear)
+    }
+
+    #[test]
+    fn test_exhausted_messages_are_dead_lettered_with_their_history() {
+        let dead_letters = Arc::new(InMemoryDeadLetters::new());
+        let queue = DeadLetterQueue::new(retry_config(), dead_letters.clone());
+
+        let mut calls = 0;
+        let handled = queue.handle("order-1", |_| {
+            calls += 1;
+            match calls {
+                1 => Err("timeout".to_string()),
+                _ => Ok(calls),
+            }
+        });
+        assert_eq!(
+            handled,
+            Handled::Processed {
+                output: 2,
+                attempts: 2
+            }
+        );
+        assert!(dead_letters.is_empty());
+
+        let mut calls = 0;
+        let handled: Handled<()> = queue.handle("order-2", |_| {
+            calls += 1;
+            Err(format!("timeout {}", calls))
+        });
+        assert_eq!(handled, Handled::DeadLettered { attempts: 3 });
+        let letters = dead_letters.drain();
+        assert_eq!(letters.len(), 1);
+        assert_eq!(letters[0].message, "order-2");
+        assert_eq!(letters[0].errors, ["timeout 1", "timeout 2", "timeout 3"]);
+        assert_eq!(letters[0].last_error(), "timeout 3");
+    }
+
+    #[test]
+    fn test_permanent_errors_are_dead_lettered_immediately() {
+        let dead_letters = Arc::new(InMemoryDeadLetters::new());
+        let config = retry_config().with_error_classifier(|_: &String| ErrorClass::Permanent);
+        let queue = DeadLetterQueue::new(config, dead_letters.clone());
+
+        let handled: Handled<()> = async_std::task::block_on(
+            queue.handle_async(42, |_| async { Err("malformed payload".to_string()) }),
+        );
+        assert_eq!(handled, Handled::DeadLettered { attempts: 1 });
+        assert_eq!(dead_letters.drain()[0].attempts(), 1);
+    }
+}
@@ -0,0 +1,138 @@
+use crate::config::BoxError;
+use crate::ratelimit::RateLimited;
+use std::fmt;
+use std::time::Duration;
+
+/// The failure causes reported by the crate's APIs returning `Box<dyn Error + Send + Sync>`.
+///
+/// The executors that cannot return a typed error (`execute_with_fallback`,
+/// `execute_with_retry_and_timeout`, `synchronous::execute_with_timeout`, the `tower` layers, ...)
+/// box an `Error` when the failure comes from a resilience mechanism rather than from the
+/// operation, so downstream code can downcast and match on the cause instead of parsing
+/// messages. Errors of the operation itself are returned as is.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use async_std::task::{block_on, sleep};
+/// use resilient_rs::asynchronous::execute_with_fallback;
+/// use resilient_rs::config::ExecConfig;
+///
+/// let config: ExecConfig<()> = ExecConfig::new(Duration::from_millis(10));
+/// let operation = async {
+///     sleep(Duration::from_millis(100)).await;
+///     Ok(())
+/// };
+/// let err = block_on(execute_with_fallback(operation, &config)).unwrap_err();
+/// match err.downcast_ref::<resilient_rs::Error>() {
+///     Some(resilient_rs::Error::Timeout { timeout }) => {
+///         assert_eq!(*timeout, Duration::from_millis(10))
+///     }
+///     other => panic!("unexpected error: {:?}", other),
+/// }
+/// ```
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The operation did not complete within `timeout`.
+    Timeout { timeout: Duration },
+    /// The call was rejected because a circuit breaker is open; `retry_after` is the remaining
+    /// cooldown.
+    CircuitOpen { retry_after: Duration },
+    /// The operation still failed with a retryable error after `attempts` attempts.
+    RetriesExhausted { source: BoxError, attempts: usize },
+    /// The call was rejected by a rate limiter; `retry_after` is the time until a call would be
+    /// admitted.
+    RateLimited { retry_after: Duration },
+    /// The operation was cancelled by a shutdown after `attempts` attempts.
+    Cancelled { attempts: usize },
+}
+
+impl Error {
+    /// Returns `true` if the operation timed out.
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, Error::Timeout { .. })
+    }
+
+    /// Returns `true` if the error was caused by a boxed `Error::Timeout`, either directly or as
+    /// the last failure of `Error::RetriesExhausted`.
+    pub(crate) fn caused_by_timeout(err: &BoxError) -> bool {
+        match err.downcast_ref::<Error>() {
+            Some(Error::Timeout { .. }) => true,
+            Some(Error::RetriesExhausted { source, .. }) => Error::caused_by_timeout(source),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Timeout { timeout } => write!(f, "Operation timed out after {:?}", timeout),
+            Error::CircuitOpen { .. } => write!(f, "Circuit Breaker is open. Please try later..!"),
+            Error::RetriesExhausted { source, attempts } => write!(
+                f,
+                "Operation failed after {} attempts: {}",
+                attempts, source
+            ),
+            Error::RateLimited { retry_after } => write!(
+                f,
+                "Rate limit reached. Please retry after {:?}..!",
+                retry_after
+            ),
+            Error::Cancelled { attempts } => write!(
+                f,
+                "Retries cancelled by shutdown after {} attempts",
+                attempts
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::RetriesExhausted { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<RateLimited> for Error {
+    fn from(rejected: RateLimited) -> Self {
+        Error::RateLimited {
+            retry_after: rejected.retry_after,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::error::Error as _;
+
+    #[test]
+    fn test_exhausted_retries_expose_their_last_error() {
+        let timeout: BoxError = Box::new(Error::Timeout {
+            timeout: Duration::from_secs(1),
+        });
+        let exhausted = Error::RetriesExhausted {
+            source: timeout,
+            attempts: 3,
+        };
+        assert_eq!(
+            exhausted.to_string(),
+            "Operation failed after 3 attempts: Operation timed out after 1s"
+        );
+        assert!(exhausted.source().unwrap().is::<Error>());
+        assert!(Error::caused_by_timeout(&(Box::new(exhausted) as BoxError)));
+
+        let rejected = Error::from(RateLimited {
+            retry_after: Duration::from_millis(250),
+        });
+        assert!(
+            matches!(rejected, Error::RateLimited { retry_after } if retry_after == Duration::from_millis(250))
+        );
+        assert!(!rejected.is_timeout());
+    }
+}
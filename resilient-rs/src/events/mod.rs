@@ -0,0 +1,200 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{Receiver, SyncSender, TrySendError, sync_channel};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+/// A structured event emitted by the crate's resilience patterns.
+///
+/// Events are delivered to every subscriber registered with `on_event` or `subscribe`, which
+/// makes it possible to build auditing and alerting without parsing log lines.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResilienceEvent {
+    /// A retry loop is about to execute an attempt (1-based).
+    AttemptStarted { attempt: usize },
+    /// An attempt failed and the next one is scheduled after `delay`.
+    RetryScheduled { attempt: usize, delay: Duration },
+    /// A retry loop gave up after `attempts` attempts.
+    GaveUp { attempts: usize },
+    /// A circuit breaker transitioned to the open state.
+    BreakerOpened,
+    /// A circuit breaker transitioned to the half-open state to test recovery.
+    BreakerHalfOpened,
+    /// A circuit breaker transitioned back to the closed state.
+    BreakerClosed,
+    /// A circuit breaker was forced open for maintenance.
+    BreakerForcedOpen,
+    /// A circuit breaker was disabled and lets every call through.
+    BreakerDisabled,
+    /// A call was rejected because the circuit breaker is open.
+    CallRejected,
+    /// A call was rejected because a bulkhead was full.
+    BulkheadRejected,
+    /// A call was rejected because a rate limiter's limit was reached.
+    RateLimited { retry_after: Duration },
+    /// A call was shed because a load shedder considered the service overloaded.
+    LoadShed,
+    /// An operation exceeded its timeout.
+    TimeoutHit { timeout: Duration },
+    /// A fallback was executed instead of the primary operation.
+    FallbackUsed,
+    /// A cached result `age` old was served because the operation failed.
+    StaleServed { age: Duration },
+    /// An attempt or a circuit breaker call was dropped before completing, after running for
+    /// `elapsed`, e.g. because the future awaiting it was cancelled by a timeout. Its outcome is
+    /// not recorded as a success or a failure.
+    AttemptCancelled { elapsed: Duration },
+}
+
+/// Identifies a subscription created with `on_event`, used to remove it with `unsubscribe`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionId(u64);
+
+#[derive(Clone)]
+enum Sink {
+    Callback(Arc<dyn Fn(&ResilienceEvent) + Send + Sync>),
+    Channel(SyncSender<ResilienceEvent>),
+}
+
+static SUBSCRIBERS: RwLock<Vec<(SubscriptionId, Sink)>> = RwLock::new(Vec::new());
+static HAS_SUBSCRIBERS: AtomicBool = AtomicBool::new(false);
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+/// Registers a callback invoked synchronously for every `ResilienceEvent`.
+///
+/// The callback runs on the thread (or task) that produced the event, so it should be cheap;
+/// forward the event to a channel or queue if heavy processing is needed.
+///
+/// # Arguments
+/// * `listener` - A function receiving a reference to each event.
+///
+/// # Returns
+/// A `SubscriptionId` that can be passed to `unsubscribe`.
+///
+/// # Example
+/// ```
+/// use resilient_rs::events::{on_event, unsubscribe, ResilienceEvent};
+///
+/// let id = on_event(|event| {
+///     if let ResilienceEvent::BreakerOpened = event {
+///         eprintln!("a circuit breaker opened");
+///     }
+/// });
+/// unsubscribe(id);
+/// ```
+pub fn on_event(listener: impl Fn(&ResilienceEvent) + Send + Sync + 'static) -> SubscriptionId {
+    register(Sink::Callback(Arc::new(listener)))
+}
+
+/// Subscribes to events through a bounded channel.
+///
+/// Events are sent without blocking; when the channel is full, new events are dropped for this
+/// subscriber. The subscription is removed automatically once the `Receiver` is dropped.
+///
+/// # Arguments
+/// * `capacity` - The maximum number of undelivered events buffered for this subscriber.
+///
+/// # Returns
+/// The receiving half of the channel.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::events::{subscribe, ResilienceEvent};
+/// use resilient_rs::strategies::RetryStrategy;
+/// use resilient_rs::synchronous::retry;
+///
+/// let events = subscribe(64);
+/// let config = RetryConfig::new(2, Duration::from_millis(1), RetryStrategy::Linear);
+/// let _: Result<(), &str> = retry(|| Err("down"), &config);
+///
+/// let received: Vec<ResilienceEvent> = events.try_iter().collect();
+/// assert!(received.contains(&ResilienceEvent::GaveUp { attempts: 2 }));
+/// ```
+pub fn subscribe(capacity: usize) -> Receiver<ResilienceEvent> {
+    let (sender, receiver) = sync_channel(capacity);
+    register(Sink::Channel(sender));
+    receiver
+}
+
+/// Removes a subscription created with `on_event`.
+///
+/// # Returns
+/// `true` if the subscription existed and was removed.
+pub fn unsubscribe(id: SubscriptionId) -> bool {
+    let mut subscribers = SUBSCRIBERS.write().unwrap();
+    let before = subscribers.len();
+    subscribers.retain(|(existing, _)| *existing != id);
+    HAS_SUBSCRIBERS.store(!subscribers.is_empty(), Ordering::Release);
+    subscribers.len() != before
+}
+
+fn register(sink: Sink) -> SubscriptionId {
+    let id = SubscriptionId(NEXT_ID.fetch_add(1, Ordering::Relaxed));
+    let mut subscribers = SUBSCRIBERS.write().unwrap();
+    subscribers.push((id, sink));
+    HAS_SUBSCRIBERS.store(true, Ordering::Release);
+    id
+}
+
+/// Delivers an event to every subscriber.
+///
+/// This is a single atomic load when nobody is subscribed. Subscribers are invoked outside the
+/// registry lock, so callbacks may themselves subscribe or unsubscribe.
+pub(crate) fn emit(event: ResilienceEvent) {
+    if !HAS_SUBSCRIBERS.load(Ordering::Acquire) {
+        return;
+    }
+    let subscribers = SUBSCRIBERS.read().unwrap().clone();
+    for (id, sink) in subscribers {
+        match sink {
+            Sink::Callback(listener) => listener(&event),
+            Sink::Channel(sender) => {
+                if let Err(TrySendError::Disconnected(_)) = sender.try_send(event.clone()) {
+                    unsubscribe(id);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_callback_receives_events_until_unsubscribed() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let sink = seen.clone();
+        let id = on_event(move |event| {
+            if let ResilienceEvent::TimeoutHit { timeout } = event {
+                sink.lock().unwrap().push(*timeout);
+            }
+        });
+
+        emit(ResilienceEvent::TimeoutHit {
+            timeout: Duration::from_millis(7331),
+        });
+        assert!(unsubscribe(id));
+        emit(ResilienceEvent::TimeoutHit {
+            timeout: Duration::from_millis(7331),
+        });
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(
+            seen.iter()
+                .filter(|t| **t == Duration::from_millis(7331))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_channel_subscription_is_bounded() {
+        let receiver = subscribe(1);
+        emit(ResilienceEvent::FallbackUsed);
+        emit(ResilienceEvent::FallbackUsed);
+        assert_eq!(receiver.try_iter().count(), 1);
+    }
+}
@@ -0,0 +1,372 @@
+use crate::asynchronous::{CircuitBreaker, CircuitBreakerState};
+use crate::config::LogConfig;
+use crate::logging::Level;
+use crate::logging::log_with;
+use crate::time;
+use async_std::task::{self, JoinHandle};
+use std::collections::HashMap;
+use std::error::Error;
+use std::pin::Pin;
+use std::sync::{Arc, MutexGuard, PoisonError};
+use std::time::{Duration, SystemTime};
+
+/// The error of a failed health probe.
+pub type ProbeError = Box<dyn Error + Send + Sync>;
+
+/// The future returned by a health probe.
+type ProbeFuture = Pin<Box<dyn Future<Output = Result<(), ProbeError>> + Send>>;
+
+/// The health of one dependency, as seen by its latest probes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DependencyHealth {
+    /// The name of the dependency.
+    pub name: String,
+    /// `true` until the number of consecutive failed probes reaches the failure threshold.
+    pub healthy: bool,
+    /// The number of failed probes since the last successful one.
+    pub consecutive_failures: usize,
+    /// When the dependency was last probed, or `None` if it was never probed.
+    pub last_checked: Option<SystemTime>,
+    /// The error of the last probe, if it failed.
+    pub last_error: Option<String>,
+    /// The state of the dependency's circuit breaker after the last probe.
+    pub breaker_state: CircuitBreakerState,
+}
+
+/// The health of every dependency of a `HealthChecker`, e.g. for a readiness endpoint.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HealthSnapshot {
+    /// The health of each dependency, in the order the probes were registered.
+    pub dependencies: Vec<DependencyHealth>,
+}
+
+impl HealthSnapshot {
+    /// Returns `true` if every dependency is healthy.
+    pub fn is_healthy(&self) -> bool {
+        self.dependencies
+            .iter()
+            .all(|dependency| dependency.healthy)
+    }
+
+    /// Returns the dependencies that are not healthy.
+    pub fn unhealthy(&self) -> impl Iterator<Item = &DependencyHealth> {
+        self.dependencies
+            .iter()
+            .filter(|dependency| !dependency.healthy)
+    }
+
+    /// Returns the health of the dependency named `name`, if it is probed.
+    pub fn get(&self, name: &str) -> Option<&DependencyHealth> {
+        self.dependencies
+            .iter()
+            .find(|dependency| dependency.name == name)
+    }
+}
+
+/// A probe of one dependency and the circuit breaker it feeds.
+struct Probe {
+    name: String,
+    breaker: Arc<CircuitBreaker>,
+    check: Box<dyn Fn() -> ProbeFuture + Send + Sync>,
+}
+
+/// Periodically probes dependencies and feeds the results into their circuit breakers.
+///
+/// Each probe is a cheap request to a dependency, such as a `SELECT 1` or a `GET /health`, run
+/// at every interval regardless of the state of the dependency's breaker:
+/// - Once `failure_threshold` probes in a row failed or timed out, the dependency is reported
+///   unhealthy and its breaker is opened, so callers fail fast before their own calls time out.
+/// - A successful probe reports the dependency healthy again and moves an open breaker to
+///   `HalfOpen` without waiting for its cooldown, so that the next calls can close it.
+///
+/// Breakers that were forced open or disabled are left alone. The breakers are typically those
+/// of a `CircuitBreakerRegistry`, shared with the code calling the dependencies.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use async_std::task::block_on;
+/// use resilient_rs::config::CircuitBreakerConfig;
+/// use resilient_rs::health::HealthChecker;
+/// use resilient_rs::registry::CircuitBreakerRegistry;
+///
+/// let registry = CircuitBreakerRegistry::new(CircuitBreakerConfig::default());
+/// let checker = HealthChecker::new(Duration::from_secs(10))
+///     .with_probe("postgres", registry.get("postgres"), || async { Ok(()) })
+///     .with_probe("search", registry.get("search"), || async { Err("connection refused".into()) });
+///
+/// let snapshot = block_on(checker.check());
+/// assert!(!snapshot.is_healthy());
+/// assert_eq!(snapshot.unhealthy().next().unwrap().name, "search");
+/// ```
+pub struct HealthChecker {
+    probes: Vec<Probe>,
+    interval: Duration,
+    probe_timeout: Duration,
+    failure_threshold: usize,
+    log: LogConfig,
+    health: std::sync::Mutex<HashMap<String, DependencyHealth>>,
+}
+
+impl HealthChecker {
+    /// Creates a checker without probes, probing every `interval` once spawned.
+    ///
+    /// Probes time out after 5 seconds, and a single failed probe marks a dependency unhealthy.
+    pub fn new(interval: Duration) -> Self {
+        HealthChecker {
+            probes: Vec::new(),
+            interval,
+            probe_timeout: Duration::from_secs(5),
+            failure_threshold: 1,
+            log: LogConfig::default(),
+            health: std::sync::Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Registers a probe and returns the modified checker.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the dependency, reported in the `HealthSnapshot`.
+    /// * `breaker` - The breaker fed with the results of the probe, e.g. `registry.get(name)`.
+    /// * `probe` - Checks the dependency, returning an error if it is unavailable.
+    pub fn with_probe<F, Fut>(
+        mut self,
+        name: impl Into<String>,
+        breaker: Arc<CircuitBreaker>,
+        probe: F,
+    ) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), ProbeError>> + Send + 'static,
+    {
+        self.probes.push(Probe {
+            name: name.into(),
+            breaker,
+            check: Box::new(move || Box::pin(probe())),
+        });
+        self
+    }
+
+    /// Sets how long a probe may run before it counts as failed, and returns the modified
+    /// checker.
+    pub fn with_probe_timeout(mut self, probe_timeout: Duration) -> Self {
+        self.probe_timeout = probe_timeout;
+        self
+    }
+
+    /// Sets the number of consecutive failed probes marking a dependency unhealthy and opening
+    /// its breaker, and returns the modified checker.
+    pub fn with_failure_threshold(mut self, failure_threshold: usize) -> Self {
+        self.failure_threshold = failure_threshold.max(1);
+        self
+    }
+
+    /// Sets the logging behavior of the failed probes and returns the modified checker.
+    pub fn with_log(mut self, log: LogConfig) -> Self {
+        self.log = log;
+        self
+    }
+
+    /// Runs every probe once, feeds the results into the breakers, and returns the resulting
+    /// health.
+    pub async fn check(&self) -> HealthSnapshot {
+        for probe in &self.probes {
+            self.run_probe(probe).await;
+        }
+        self.snapshot()
+    }
+
+    /// Returns the health recorded by the latest probes, without probing.
+    ///
+    /// Dependencies that were never probed are reported healthy.
+    pub fn snapshot(&self) -> HealthSnapshot {
+        let health = self.health();
+        let dependencies = self
+            .probes
+            .iter()
+            .map(|probe| {
+                health
+                    .get(&probe.name)
+                    .cloned()
+                    .unwrap_or_else(|| DependencyHealth {
+                        name: probe.name.clone(),
+                        healthy: true,
+                        consecutive_failures: 0,
+                        last_checked: None,
+                        last_error: None,
+                        breaker_state: CircuitBreakerState::Close,
+                    })
+            })
+            .collect();
+        HealthSnapshot { dependencies }
+    }
+
+    /// Spawns a task running every probe at each interval, until the checker is dropped.
+    ///
+    /// # Returns
+    /// The handle of the spawned task.
+    pub fn spawn(checker: &Arc<HealthChecker>) -> JoinHandle<()> {
+        let checker = Arc::downgrade(checker);
+        task::spawn(async move {
+            while let Some(checker) = checker.upgrade() {
+                checker.check().await;
+                let interval = checker.interval;
+                drop(checker);
+                time::sleep(interval).await;
+            }
+        })
+    }
+
+    async fn run_probe(&self, probe: &Probe) {
+        let error = match time::timeout(self.probe_timeout, (probe.check)()).await {
+            Ok(Ok(())) => None,
+            Ok(Err(err)) => Some(err.to_string()),
+            Err(_) => Some(format!("probe timed out after {:?}", self.probe_timeout)),
+        };
+        let consecutive_failures = {
+            let health = self.health();
+            let previous = health
+                .get(&probe.name)
+                .map_or(0, |health| health.consecutive_failures);
+            match error {
+                Some(_) => previous + 1,
+                None => 0,
+            }
+        };
+        let healthy = consecutive_failures < self.failure_threshold;
+        if let Some(error) = &error {
+            log_with!(
+                self.log,
+                Level::Warn,
+                "Health probe of {} failed: {}",
+                probe.name,
+                error
+            );
+        }
+
+        match (&error, healthy) {
+            (None, _) => probe.breaker.record_probe(true),
+            (Some(_), false) => probe.breaker.record_probe(false),
+            (Some(_), true) => {}
+        }
+        let breaker_state = probe.breaker.state();
+        self.health().insert(
+            probe.name.clone(),
+            DependencyHealth {
+                name: probe.name.clone(),
+                healthy,
+                consecutive_failures,
+                last_checked: Some(time::system_now()),
+                last_error: error,
+                breaker_state,
+            },
+        );
+    }
+
+    fn health(&self) -> MutexGuard<'_, HashMap<String, DependencyHealth>> {
+        self.health.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::CircuitBreakerConfig;
+    use crate::sim::VirtualClock;
+    use async_std::task::block_on;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    #[test]
+    fn test_probes_open_breakers_and_allow_recovery() {
+        let breaker = Arc::new(CircuitBreaker::new(CircuitBreakerConfig::new(
+            1,
+            5,
+            Duration::from_secs(60),
+        )));
+        let up = Arc::new(AtomicBool::new(false));
+        let status = up.clone();
+        let checker = HealthChecker::new(Duration::from_secs(10))
+            .with_failure_threshold(2)
+            .with_probe("orders-db", breaker.clone(), move || {
+                let up = status.load(Ordering::SeqCst);
+                async move {
+                    if up {
+                        Ok(())
+                    } else {
+                        Err("connection refused".into())
+                    }
+                }
+            });
+        assert!(checker.snapshot().is_healthy());
+
+        let snapshot = block_on(checker.check());
+        let health = snapshot.get("orders-db").unwrap();
+        assert!(health.healthy);
+        assert_eq!(health.consecutive_failures, 1);
+        assert_eq!(health.breaker_state, CircuitBreakerState::Close);
+
+        let snapshot = block_on(checker.check());
+        assert!(!snapshot.is_healthy());
+        assert_eq!(
+            snapshot.get("orders-db").unwrap().last_error.as_deref(),
+            Some("connection refused")
+        );
+        assert_eq!(breaker.state(), CircuitBreakerState::Open);
+
+        up.store(true, Ordering::SeqCst);
+        let snapshot = block_on(checker.check());
+        assert!(snapshot.is_healthy());
+        assert_eq!(
+            snapshot.get("orders-db").unwrap().breaker_state,
+            CircuitBreakerState::HalfOpen
+        );
+        let result = block_on(breaker.run(|| async { Ok::<_, ProbeError>(()) }));
+        assert!(result.is_ok());
+        assert_eq!(breaker.state(), CircuitBreakerState::Close);
+    }
+
+    #[test]
+    fn test_failed_trial_after_probe_trip_reopens() {
+        let clock = VirtualClock::new();
+        let _guard = clock.enter();
+        let breaker = Arc::new(CircuitBreaker::new(CircuitBreakerConfig::new(
+            1,
+            5,
+            Duration::from_secs(60),
+        )));
+        let checker = HealthChecker::new(Duration::from_secs(10))
+            .with_failure_threshold(1)
+            .with_probe("orders-db", breaker.clone(), || async {
+                Err("connection refused".into())
+            });
+        block_on(checker.check());
+        assert_eq!(breaker.state(), CircuitBreakerState::Open);
+
+        clock.advance(Duration::from_secs(61));
+        let trial = block_on(breaker.run(|| async { Err::<(), ProbeError>("timeout".into()) }));
+        assert!(trial.is_err());
+        assert_eq!(breaker.state(), CircuitBreakerState::Open);
+    }
+
+    #[test]
+    fn test_hung_probes_time_out() {
+        let breaker = Arc::new(CircuitBreaker::new(CircuitBreakerConfig::default()));
+        let checker = HealthChecker::new(Duration::from_secs(10))
+            .with_probe_timeout(Duration::from_secs(2))
+            .with_probe("search", breaker.clone(), || async {
+                time::sleep(Duration::from_secs(3600)).await;
+                Ok(())
+            });
+
+        let clock = VirtualClock::new();
+        let snapshot = clock.block_on(checker.check());
+        assert_eq!(clock.elapsed(), Duration::from_secs(2));
+        let health = snapshot.get("search").unwrap();
+        assert!(!health.healthy);
+        assert_eq!(
+            health.last_error.as_deref(),
+            Some("probe timed out after 2s")
+        );
+        assert_eq!(health.breaker_state, CircuitBreakerState::Open);
+    }
+}
@@ -0,0 +1,274 @@
+use crate::classifier::ErrorClass;
+use crate::time;
+use ::http::{HeaderMap, StatusCode};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Epoch timestamps below this value are read as delays in seconds by `suggested_delay`; no
+/// rate limit resets 30 years from now.
+const EPOCH_THRESHOLD: u64 = 1_000_000_000;
+
+/// Returns `true` if a request that received `status` is worth sending again.
+///
+/// `408 Request Timeout`, `429 Too Many Requests`, `500 Internal Server Error`,
+/// `502 Bad Gateway`, `503 Service Unavailable` and `504 Gateway Timeout` are retryable. Other
+/// statuses are either successes or failures that the same request will meet again, such as
+/// `400 Bad Request` or `501 Not Implemented`.
+///
+/// # Example
+/// ```
+/// use http::StatusCode;
+/// use resilient_rs::http::is_retryable;
+///
+/// assert!(is_retryable(StatusCode::SERVICE_UNAVAILABLE));
+/// assert!(!is_retryable(StatusCode::NOT_FOUND));
+/// ```
+pub fn is_retryable(status: StatusCode) -> bool {
+    matches!(status.as_u16(), 408 | 429 | 500 | 502 | 503 | 504)
+}
+
+/// Classifies an HTTP response from its status and headers.
+///
+/// # Returns
+/// - `None` for informational, successful and redirection statuses.
+/// - `Some(ErrorClass::Throttled)` for `429`, and for `503` with a delay hint, carrying the delay
+///   returned by `suggested_delay`.
+/// - `Some(ErrorClass::Transient)` for the other statuses accepted by `is_retryable`.
+/// - `Some(ErrorClass::Permanent)` for every other error status.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use http::{HeaderMap, StatusCode};
+/// use resilient_rs::classifier::ErrorClass;
+/// use resilient_rs::http::classify;
+///
+/// let mut headers = HeaderMap::new();
+/// headers.insert("retry-after", "3".parse().unwrap());
+/// assert_eq!(
+///     classify(StatusCode::TOO_MANY_REQUESTS, &headers),
+///     Some(ErrorClass::Throttled { retry_after: Some(Duration::from_secs(3)) })
+/// );
+/// assert_eq!(classify(StatusCode::OK, &headers), None);
+/// ```
+pub fn classify(status: StatusCode, headers: &HeaderMap) -> Option<ErrorClass> {
+    if !status.is_client_error() && !status.is_server_error() {
+        return None;
+    }
+    let retry_after = suggested_delay(headers);
+    Some(match status {
+        StatusCode::TOO_MANY_REQUESTS => ErrorClass::Throttled { retry_after },
+        StatusCode::SERVICE_UNAVAILABLE if retry_after.is_some() => {
+            ErrorClass::Throttled { retry_after }
+        }
+        status if is_retryable(status) => ErrorClass::Transient,
+        _ => ErrorClass::Permanent,
+    })
+}
+
+/// Returns the delay the server asked clients to wait before retrying, if any.
+///
+/// The headers are read in order of precedence:
+/// - `Retry-After`, either in seconds or as an HTTP-date.
+/// - `RateLimit-Reset`, in seconds until the rate limit window resets.
+/// - `X-RateLimit-Reset`, in seconds, or as a Unix timestamp as sent by GitHub and others.
+///
+/// Dates in the past yield a zero delay. Unparseable values, and timestamps too far in the future
+/// to be represented, are ignored. Delays are returned as sent; `RetryConfig::max_delay` bounds
+/// how long a retry loop honors them.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use http::HeaderMap;
+/// use resilient_rs::http::suggested_delay;
+///
+/// let mut headers = HeaderMap::new();
+/// headers.insert("ratelimit-reset", "30".parse().unwrap());
+/// assert_eq!(suggested_delay(&headers), Some(Duration::from_secs(30)));
+/// ```
+pub fn suggested_delay(headers: &HeaderMap) -> Option<Duration> {
+    let header = |name: &str| headers.get(name).and_then(|value| value.to_str().ok());
+    let now = time::system_now();
+    if let Some(delay) = header("retry-after").and_then(|value| parse_retry_after(value, now)) {
+        return Some(delay);
+    }
+    if let Some(seconds) = header("ratelimit-reset").and_then(parse_seconds) {
+        return Some(Duration::from_secs(seconds));
+    }
+    let reset = header("x-ratelimit-reset").and_then(parse_seconds)?;
+    if reset < EPOCH_THRESHOLD {
+        return Some(Duration::from_secs(reset));
+    }
+    let reset = UNIX_EPOCH.checked_add(Duration::from_secs(reset))?;
+    Some(reset.duration_since(now).unwrap_or_default())
+}
+
+/// Parses the value of a `Retry-After` header into the delay to wait from `now`.
+///
+/// # Arguments
+/// * `value` - The header value, either delay-seconds (`"120"`) or an HTTP-date
+///   (`"Wed, 21 Oct 2015 07:28:00 GMT"`).
+/// * `now` - The time the response was received.
+///
+/// # Returns
+/// The delay, zero for a date in the past, or `None` if `value` is neither form.
+///
+/// # Example
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use resilient_rs::http::parse_retry_after;
+///
+/// let now = UNIX_EPOCH + Duration::from_secs(1445412470);
+/// assert_eq!(parse_retry_after("Wed, 21 Oct 2015 07:28:00 GMT", now), Some(Duration::from_secs(10)));
+/// assert_eq!(parse_retry_after("120", now), Some(Duration::from_secs(120)));
+/// ```
+pub fn parse_retry_after(value: &str, now: SystemTime) -> Option<Duration> {
+    if let Some(seconds) = parse_seconds(value) {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = parse_http_date(value)?;
+    Some(date.duration_since(now).unwrap_or_default())
+}
+
+/// Parses an HTTP-date in the preferred IMF-fixdate format, e.g.
+/// `"Sun, 06 Nov 1994 08:49:37 GMT"`.
+///
+/// The obsolete RFC 850 and asctime formats are not supported.
+pub fn parse_http_date(value: &str) -> Option<SystemTime> {
+    let mut parts = value.trim().split(' ');
+    let (_weekday, day, month, year, time, zone) = (
+        parts.next()?.strip_suffix(',')?,
+        parts.next()?,
+        parts.next()?,
+        parts.next()?,
+        parts.next()?,
+        parts.next()?,
+    );
+    if zone != "GMT" || parts.next().is_some() || day.len() != 2 || year.len() != 4 {
+        return None;
+    }
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let month = MONTHS.iter().position(|name| *name == month)? as u64 + 1;
+    let day: u64 = day.parse().ok()?;
+    let year: u64 = year.parse().ok()?;
+    let mut clock = time.split(':').map(|part| match part.len() {
+        2 => part.parse::<u64>().ok(),
+        _ => None,
+    });
+    let (hours, minutes, seconds) = (clock.next()??, clock.next()??, clock.next()??);
+    if clock.next().is_some()
+        || year < 1970
+        || !(1..=31).contains(&day)
+        || hours > 23
+        || minutes > 59
+        || seconds > 60
+    {
+        return None;
+    }
+    let days = days_since_epoch(year, month, day);
+    Some(UNIX_EPOCH + Duration::from_secs(days * 86_400 + hours * 3_600 + minutes * 60 + seconds))
+}
+
+/// Parses a non-negative number of seconds.
+fn parse_seconds(value: &str) -> Option<u64> {
+    let value = value.trim();
+    if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    value.parse().ok()
+}
+
+/// Returns the number of days from 1970-01-01 to the given date of the proleptic Gregorian
+/// calendar, for years from 1970.
+fn days_since_epoch(year: u64, month: u64, day: u64) -> u64 {
+    // Shift the year to start in March, so that the leap day is the last day of the year.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let month_from_march = (month + 9) % 12;
+    let day_of_year = (153 * month_from_march + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RetryConfig;
+    use crate::strategies::RetryStrategy;
+    use crate::synchronous;
+
+    #[test]
+    fn test_parse_http_date() {
+        assert_eq!(
+            parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"),
+            Some(UNIX_EPOCH + Duration::from_secs(784_111_777))
+        );
+        assert_eq!(
+            parse_http_date("Thu, 29 Feb 2024 00:00:00 GMT"),
+            Some(UNIX_EPOCH + Duration::from_secs(1_709_164_800))
+        );
+        assert_eq!(parse_http_date("Sunday, 06-Nov-94 08:49:37 GMT"), None);
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 PST"), None);
+        assert_eq!(parse_http_date("Sun, 06 Foo 1994 08:49:37 GMT"), None);
+    }
+
+    #[test]
+    fn test_suggested_delay_precedence() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(suggested_delay(&headers), None);
+
+        let reset = SystemTime::now().duration_since(UNIX_EPOCH).unwrap() + Duration::from_secs(60);
+        headers.insert(
+            "x-ratelimit-reset",
+            reset.as_secs().to_string().parse().unwrap(),
+        );
+        let delay = suggested_delay(&headers).unwrap();
+        assert!(delay > Duration::from_secs(58) && delay <= Duration::from_secs(60));
+
+        headers.insert("ratelimit-reset", "20".parse().unwrap());
+        assert_eq!(suggested_delay(&headers), Some(Duration::from_secs(20)));
+
+        headers.insert(
+            "retry-after",
+            "Thu, 01 Jan 1970 00:00:00 GMT".parse().unwrap(),
+        );
+        assert_eq!(suggested_delay(&headers), Some(Duration::ZERO));
+
+        assert_eq!(
+            classify(StatusCode::SERVICE_UNAVAILABLE, &headers),
+            Some(ErrorClass::Throttled {
+                retry_after: Some(Duration::ZERO)
+            })
+        );
+        assert_eq!(
+            classify(StatusCode::NOT_IMPLEMENTED, &headers),
+            Some(ErrorClass::Permanent)
+        );
+    }
+
+    #[test]
+    fn test_huge_delay_hints_do_not_crash_or_park_the_caller() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-ratelimit-reset", u64::MAX.to_string().parse().unwrap());
+        assert_eq!(suggested_delay(&headers), None);
+
+        headers.insert("retry-after", u64::MAX.to_string().parse().unwrap());
+        assert_eq!(
+            suggested_delay(&headers),
+            Some(Duration::from_secs(u64::MAX))
+        );
+        let retry_config = RetryConfig::new(2, Duration::from_millis(1), RetryStrategy::Linear)
+            .with_max_delay(Duration::from_millis(1))
+            .with_error_classifier(move |status: &StatusCode| {
+                classify(*status, &headers).unwrap_or(ErrorClass::Permanent)
+            });
+        let result = synchronous::retry(
+            || Err::<(), _>(StatusCode::TOO_MANY_REQUESTS),
+            &retry_config,
+        );
+        assert_eq!(result, Err(StatusCode::TOO_MANY_REQUESTS));
+    }
+}
@@ -0,0 +1,308 @@
+use crate::asynchronous::{CircuitBreaker, GuardedRetry, Step, back_off};
+use crate::classifier::ErrorClass;
+use crate::config::{CircuitBreakerConfig, RetryConfig};
+use crate::registry::CircuitBreakerRegistry;
+use http_body::Body;
+use hyper::body::Incoming;
+use hyper::{Request, Response};
+use hyper_util::client::legacy::connect::Connect;
+use hyper_util::client::legacy::{Client, Error};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The error returned by `ResilientHyperClient::request`.
+#[derive(Debug)]
+pub enum HyperClientError {
+    /// The circuit breaker of the request's host rejected the request, which was not sent.
+    CircuitOpen {
+        host: String,
+        retry_after: Option<Duration>,
+    },
+    /// The request failed after the retries allowed for it.
+    Request(Error),
+}
+
+impl HyperClientError {
+    /// Returns `true` if the request was rejected by the circuit breaker without being sent.
+    pub fn is_circuit_open(&self) -> bool {
+        matches!(self, HyperClientError::CircuitOpen { .. })
+    }
+
+    /// Returns `true` if the request failed while connecting to the host.
+    pub fn is_connect(&self) -> bool {
+        matches!(self, HyperClientError::Request(err) if err.is_connect())
+    }
+}
+
+impl fmt::Display for HyperClientError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HyperClientError::CircuitOpen { host, .. } => write!(
+                f,
+                "Circuit Breaker for {} rejected the request. Please try later..!",
+                host
+            ),
+            HyperClientError::Request(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for HyperClientError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            HyperClientError::Request(err) => Some(err),
+            HyperClientError::CircuitOpen { .. } => None,
+        }
+    }
+}
+
+/// A thin wrapper around a `hyper_util` client retrying connection failures and, optionally,
+/// supervising every host with its own circuit breaker.
+///
+/// Only failures to connect (refused or reset connections, DNS errors, connect timeouts of the
+/// connector) are retried, following the `RetryConfig`'s delays and strategy. They happen before
+/// any byte of the request is written, so the body of a retried request was never polled; a
+/// request failing after its connection was established is never retried, since its body may be
+/// partially consumed. An `error_classifier` or `retry_condition` of the `RetryConfig` can narrow
+/// the connection failures that are retried.
+///
+/// With `with_circuit_breaker`, every failure counts against the request's host and every
+/// response, whatever its status, counts as a success. Requests to a host whose circuit is open
+/// fail with `HyperClientError::CircuitOpen` without being sent, and retries stop as soon as an
+/// attempt opens the circuit.
+///
+/// # Example
+/// ```no_run
+/// use std::time::Duration;
+/// use hyper_util::client::legacy::Client;
+/// use hyper_util::client::legacy::connect::HttpConnector;
+/// use resilient_rs::config::{CircuitBreakerConfig, RetryConfig};
+/// use resilient_rs::hyper::ResilientHyperClient;
+///
+/// async fn fetch_items(client: Client<HttpConnector, String>) -> Result<(), Box<dyn std::error::Error>> {
+///     let client = ResilientHyperClient::new(client, RetryConfig::default())
+///         .with_circuit_breaker(CircuitBreakerConfig::new(1, 5, Duration::from_secs(30)));
+///
+///     let request = hyper::Request::get("http://inventory.internal/items").body(String::new())?;
+///     let response = client.request(request).await?;
+///     println!("{}", response.status());
+///     Ok(())
+/// }
+/// ```
+pub struct ResilientHyperClient<C, B> {
+    client: Client<C, B>,
+    retry: RetryConfig<Error>,
+    breakers: Option<Arc<CircuitBreakerRegistry>>,
+}
+
+impl<C, B> ResilientHyperClient<C, B>
+where
+    C: Connect + Clone + Send + Sync + 'static,
+    B: Body + Clone + Send + Unpin + 'static,
+    B::Data: Send,
+    B::Error: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    /// Wraps `client`, retrying its connection failures according to `retry`.
+    ///
+    /// # Arguments
+    /// * `client` - The client sending the requests.
+    /// * `retry` - The attempts, delays and strategy of the retries.
+    pub fn new(client: Client<C, B>, retry: RetryConfig<Error>) -> Self {
+        ResilientHyperClient {
+            client,
+            retry,
+            breakers: None,
+        }
+    }
+
+    /// Supervises every host with its own breaker configured with `config`, and returns the
+    /// modified client.
+    pub fn with_circuit_breaker(self, config: CircuitBreakerConfig) -> Self {
+        self.with_breaker_registry(Arc::new(CircuitBreakerRegistry::new(config)))
+    }
+
+    /// Supervises every host with the breaker of `registry` named after the host, and returns
+    /// the modified client.
+    pub fn with_breaker_registry(mut self, registry: Arc<CircuitBreakerRegistry>) -> Self {
+        self.breakers = Some(registry);
+        self
+    }
+
+    /// Returns the wrapped client, e.g. to send a request without retries.
+    pub fn inner(&self) -> &Client<C, B> {
+        &self.client
+    }
+
+    /// Sends `request`, retrying it while the connection to its host fails.
+    ///
+    /// The request is cloned for every attempt, so its body must be `Clone`, e.g. `Full` or
+    /// `Empty` from `http-body-util`; streaming bodies should be sent with `inner`.
+    ///
+    /// # Returns
+    /// - `Ok(Response)` once a connection was established and a response received, whatever its
+    ///   status.
+    /// - `Err(HyperClientError::CircuitOpen)` if the host's breaker rejected the request.
+    /// - `Err(HyperClientError::Request)` with the last error otherwise.
+    pub async fn request(
+        &self,
+        request: Request<B>,
+    ) -> Result<Response<Incoming>, HyperClientError> {
+        let host = request.uri().host().unwrap_or_default().to_string();
+        let breaker = self.breakers.as_ref().map(|registry| registry.get(&host));
+        let mut retry = GuardedRetry::new(
+            &self.retry,
+            breaker.as_deref().map(CircuitBreaker::state_machine),
+        );
+
+        loop {
+            let attempt = match retry.start() {
+                Ok(attempt) => attempt,
+                Err(retry_after) => {
+                    return Err(HyperClientError::CircuitOpen { host, retry_after });
+                }
+            };
+            let result = self.client.request(request.clone()).await;
+            let outcome = match &result {
+                Ok(_) => Ok(()),
+                Err(err) => Err(err.to_string().into()),
+            };
+            let class = result.as_ref().err().map(|err| match err.is_connect() {
+                true => self.retry.classify(err),
+                false => ErrorClass::Permanent,
+            });
+            let wait = match retry.end(attempt, result, outcome, class) {
+                Step::Done(result) => return result.map_err(HyperClientError::Request),
+                Step::Retry(wait) => wait,
+            };
+            back_off(None, self.retry.timer_wheel.as_ref(), wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::RetryStrategy;
+    use async_std::task::block_on;
+    use hyper::Uri;
+    use hyper::rt::{Executor, Read, ReadBufCursor, Write};
+    use hyper_util::client::legacy::connect::{Connected, Connection};
+    use std::future::{Future, Ready, ready};
+    use std::io;
+    use std::pin::Pin;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::task::{Context, Poll};
+
+    #[derive(Clone)]
+    struct AsyncStdExecutor;
+
+    impl<F> Executor<F> for AsyncStdExecutor
+    where
+        F: Future + Send + 'static,
+        F::Output: Send + 'static,
+    {
+        fn execute(&self, future: F) {
+            async_std::task::spawn(future);
+        }
+    }
+
+    /// A connection that is never established.
+    struct NoStream;
+
+    impl Read for NoStream {
+        fn poll_read(
+            self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+            _: ReadBufCursor<'_>,
+        ) -> Poll<io::Result<()>> {
+            unreachable!()
+        }
+    }
+
+    impl Write for NoStream {
+        fn poll_write(
+            self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+            _: &[u8],
+        ) -> Poll<io::Result<usize>> {
+            unreachable!()
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            unreachable!()
+        }
+
+        fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+            unreachable!()
+        }
+    }
+
+    impl Connection for NoStream {
+        fn connected(&self) -> Connected {
+            Connected::new()
+        }
+    }
+
+    /// A connector whose connections are always refused.
+    #[derive(Clone)]
+    struct Refused(Arc<AtomicUsize>);
+
+    impl tower_service::Service<Uri> for Refused {
+        type Response = NoStream;
+        type Error = io::Error;
+        type Future = Ready<Result<NoStream, io::Error>>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), io::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, _: Uri) -> Self::Future {
+            self.0.fetch_add(1, Ordering::SeqCst);
+            ready(Err(io::ErrorKind::ConnectionRefused.into()))
+        }
+    }
+
+    fn client(
+        retry: RetryConfig<Error>,
+    ) -> (ResilientHyperClient<Refused, String>, Arc<AtomicUsize>) {
+        let connects = Arc::new(AtomicUsize::new(0));
+        let client = Client::builder(AsyncStdExecutor).build(Refused(connects.clone()));
+        (ResilientHyperClient::new(client, retry), connects)
+    }
+
+    fn request(uri: &str) -> Request<String> {
+        Request::post(uri).body("payload".to_string()).unwrap()
+    }
+
+    fn retry_config() -> RetryConfig<Error> {
+        RetryConfig::new(3, Duration::from_millis(1), RetryStrategy::Linear)
+    }
+
+    #[test]
+    fn test_retries_connection_failures() {
+        let (client, connects) = client(retry_config());
+
+        let err = block_on(client.request(request("http://inventory.test/items"))).unwrap_err();
+        assert!(err.is_connect());
+        assert_eq!(connects.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn test_connection_failures_open_the_host_circuit() {
+        let (client, connects) = client(retry_config());
+        let client =
+            client.with_circuit_breaker(CircuitBreakerConfig::new(1, 2, Duration::from_secs(30)));
+
+        let err = block_on(client.request(request("http://payments.test/charge"))).unwrap_err();
+        assert!(err.is_connect());
+        assert_eq!(connects.load(Ordering::SeqCst), 2);
+
+        let err = block_on(client.request(request("http://payments.test/charge"))).unwrap_err();
+        assert!(err.is_circuit_open());
+        assert_eq!(connects.load(Ordering::SeqCst), 2);
+
+        let err = block_on(client.request(request("http://inventory.test/items"))).unwrap_err();
+        assert!(err.is_connect());
+    }
+}
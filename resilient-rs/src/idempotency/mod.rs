@@ -0,0 +1,282 @@
+use crate::config::LogConfig;
+use crate::logging::Level;
+use crate::logging::log_with;
+use crate::store::StoreError;
+use rand::{Rng, rng};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+/// A key identifying one logical operation across all of its retry attempts.
+///
+/// Sending the same key with every attempt of a non-idempotent request (typically in an
+/// `Idempotency-Key` header) lets the server recognize a retry of a request it already executed,
+/// e.g. when the response was lost to a timeout, and return the original result instead of
+/// executing it twice.
+///
+/// # Example
+/// ```
+/// use resilient_rs::idempotency::IdempotencyKey;
+///
+/// let key = IdempotencyKey::generate();
+/// assert_eq!(key.as_str().len(), 36);
+/// assert_ne!(key, IdempotencyKey::generate());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IdempotencyKey(String);
+
+impl IdempotencyKey {
+    /// Generates a random key formatted as a version 4 UUID.
+    pub fn generate() -> Self {
+        let bits = rng().random::<u128>();
+        // Set the version (4) and variant (RFC 4122) bits.
+        let bits = (bits & !(0xf << 76) | (0x4 << 76)) & !(0x3 << 62) | (0x2 << 62);
+        let hex = format!("{:032x}", bits);
+        IdempotencyKey(format!(
+            "{}-{}-{}-{}-{}",
+            &hex[..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..]
+        ))
+    }
+
+    /// Returns the key as a string, e.g. to set it as a request header.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for IdempotencyKey {
+    /// Wraps a key received from a client or derived from the request, such as an order id.
+    fn from(key: String) -> Self {
+        IdempotencyKey(key)
+    }
+}
+
+impl From<&str> for IdempotencyKey {
+    fn from(key: &str) -> Self {
+        IdempotencyKey(key.to_string())
+    }
+}
+
+impl fmt::Display for IdempotencyKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// A backend remembering the results of operations by idempotency key.
+///
+/// Implement it over a shared database or cache so that every replica of a service recognizes
+/// keys already executed by the others. Results should be kept at least as long as clients may
+/// retry.
+pub trait IdempotencyStore<T>: Send + Sync {
+    /// Returns the result recorded for `key`, or `None` if the key was never executed.
+    fn get(&self, key: &IdempotencyKey) -> Result<Option<T>, StoreError>;
+
+    /// Records the result of the operation executed for `key`.
+    fn put(&self, key: &IdempotencyKey, result: &T) -> Result<(), StoreError>;
+}
+
+/// An `IdempotencyStore` keeping results in memory, for a single process and for tests.
+///
+/// Results are kept until they are removed with `remove`.
+pub struct InMemoryIdempotencyStore<T> {
+    results: Mutex<HashMap<IdempotencyKey, T>>,
+}
+
+impl<T> InMemoryIdempotencyStore<T> {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        InMemoryIdempotencyStore {
+            results: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Forgets the result recorded for `key`, so that the key executes again.
+    pub fn remove(&self, key: &IdempotencyKey) {
+        self.results().remove(key);
+    }
+
+    fn results(&self) -> MutexGuard<'_, HashMap<IdempotencyKey, T>> {
+        self.results.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl<T> Default for InMemoryIdempotencyStore<T> {
+    fn default() -> Self {
+        InMemoryIdempotencyStore::new()
+    }
+}
+
+impl<T: Clone + Send> IdempotencyStore<T> for InMemoryIdempotencyStore<T> {
+    fn get(&self, key: &IdempotencyKey) -> Result<Option<T>, StoreError> {
+        Ok(self.results().get(key).cloned())
+    }
+
+    fn put(&self, key: &IdempotencyKey, result: &T) -> Result<(), StoreError> {
+        self.results().insert(key.clone(), result.clone());
+        Ok(())
+    }
+}
+
+/// Executes operations at most once per idempotency key, the receiving side of retried writes.
+///
+/// `run_once` returns the result recorded for a key without running the operation again, and
+/// records the result of successful operations; failed ones are not recorded, so the client's
+/// next retry executes them again. When the store fails, the failure is logged and the operation
+/// runs as if the key was new, so an unavailable store never blocks traffic.
+///
+/// Concurrent calls with the same key may both execute the operation; use a store that rejects
+/// duplicate keys atomically when that matters.
+///
+/// # Example
+/// ```
+/// use std::sync::Arc;
+/// use resilient_rs::idempotency::{Deduplicator, IdempotencyKey, InMemoryIdempotencyStore};
+///
+/// let dedup = Deduplicator::new(Arc::new(InMemoryIdempotencyStore::new()));
+/// let key = IdempotencyKey::from("order-42");
+///
+/// let mut charges = 0;
+/// let first = dedup.run_once(&key, || { charges += 1; Ok::<_, String>(charges) });
+/// let retried = dedup.run_once(&key, || { charges += 1; Ok::<_, String>(charges) });
+/// assert_eq!((first, retried, charges), (Ok(1), Ok(1), 1));
+/// ```
+pub struct Deduplicator<T> {
+    store: Arc<dyn IdempotencyStore<T>>,
+    log: LogConfig,
+}
+
+impl<T> Deduplicator<T> {
+    /// Creates a deduplicator recording results in `store`.
+    ///
+    /// # Arguments
+    /// * `store` - The store shared by every deduplicator that should recognize the same keys.
+    pub fn new(store: Arc<dyn IdempotencyStore<T>>) -> Self {
+        Deduplicator {
+            store,
+            log: LogConfig::default(),
+        }
+    }
+
+    /// Sets the logging behavior for replayed keys and store failures, and returns the modified
+    /// deduplicator.
+    pub fn with_log(mut self, log: LogConfig) -> Self {
+        self.log = log;
+        self
+    }
+
+    /// Executes a blocking operation unless `key` was already executed.
+    ///
+    /// # Returns
+    /// - `Ok(T)` with the recorded result if `key` was already executed.
+    /// - The result of the operation otherwise; successful results are recorded.
+    pub fn run_once<F, E>(&self, key: &IdempotencyKey, operation: F) -> Result<T, E>
+    where
+        F: FnOnce() -> Result<T, E>,
+    {
+        if let Some(result) = self.recorded(key) {
+            return Ok(result);
+        }
+        let result = operation();
+        self.record(key, &result);
+        result
+    }
+
+    /// Executes an asynchronous operation unless `key` was already executed.
+    ///
+    /// # Returns
+    /// The same as `run_once`.
+    pub async fn run_once_async<F, Fut, E>(
+        &self,
+        key: &IdempotencyKey,
+        operation: F,
+    ) -> Result<T, E>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        if let Some(result) = self.recorded(key) {
+            return Ok(result);
+        }
+        let result = operation().await;
+        self.record(key, &result);
+        result
+    }
+
+    fn recorded(&self, key: &IdempotencyKey) -> Option<T> {
+        match self.store.get(key) {
+            Ok(Some(result)) => {
+                log_with!(
+                    self.log,
+                    Level::Info,
+                    "Idempotency key {} was already executed, returning its recorded result",
+                    key
+                );
+                Some(result)
+            }
+            Ok(None) => None,
+            Err(err) => {
+                log_with!(
+                    self.log,
+                    Level::Warn,
+                    "Idempotency store failed to look up key {}, executing the operation: {}",
+                    key,
+                    err
+                );
+                None
+            }
+        }
+    }
+
+    fn record<E>(&self, key: &IdempotencyKey, result: &Result<T, E>) {
+        let Ok(result) = result else {
+            return;
+        };
+        if let Err(err) = self.store.put(key, result) {
+            log_with!(
+                self.log,
+                Level::Warn,
+                "Idempotency store failed to record key {}: {}",
+                key,
+                err
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generated_keys_are_v4_uuids() {
+        let key = IdempotencyKey::generate();
+        let parts: Vec<&str> = key.as_str().split('-').collect();
+        assert_eq!(
+            parts.iter().map(|part| part.len()).collect::<Vec<_>>(),
+            [8, 4, 4, 4, 12]
+        );
+        assert!(parts[2].starts_with('4'));
+        assert!(matches!(parts[3].as_bytes()[0], b'8' | b'9' | b'a' | b'b'));
+    }
+
+    #[test]
+    fn test_failed_operations_are_not_recorded() {
+        let store = Arc::new(InMemoryIdempotencyStore::new());
+        let dedup = Deduplicator::new(store.clone());
+        let key = IdempotencyKey::generate();
+
+        assert_eq!(dedup.run_once(&key, || Err("timeout")), Err("timeout"));
+        assert_eq!(dedup.run_once(&key, || Ok::<_, &str>(7)), Ok(7));
+        assert_eq!(dedup.run_once(&key, || Ok::<_, &str>(8)), Ok(7));
+
+        store.remove(&key);
+        let replayed =
+            async_std::task::block_on(dedup.run_once_async(&key, || async { Ok::<_, &str>(9) }));
+        assert_eq!(replayed, Ok(9));
+    }
+}
@@ -0,0 +1,338 @@
+use crate::asynchronous::{CircuitBreaker, GuardedRetry, Step, back_off};
+use crate::classifier::{ErrorClass, ErrorClassifier};
+use crate::config::{CircuitBreakerConfig, RetryConfig};
+use crate::registry::CircuitBreakerRegistry;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Kafka protocol and librdkafka error codes after which publishing again succeeds, typically once
+/// a partition leader was elected or the broker became reachable again.
+const RETRIABLE_CODES: [i32; 18] = [
+    2,    // CORRUPT_MESSAGE
+    3,    // UNKNOWN_TOPIC_OR_PARTITION, while a topic is being created
+    5,    // LEADER_NOT_AVAILABLE
+    6,    // NOT_LEADER_OR_FOLLOWER
+    7,    // REQUEST_TIMED_OUT
+    8,    // BROKER_NOT_AVAILABLE
+    9,    // REPLICA_NOT_AVAILABLE
+    13,   // NETWORK_EXCEPTION
+    14,   // COORDINATOR_LOAD_IN_PROGRESS
+    15,   // COORDINATOR_NOT_AVAILABLE
+    16,   // NOT_COORDINATOR
+    19,   // NOT_ENOUGH_REPLICAS
+    20,   // NOT_ENOUGH_REPLICAS_AFTER_APPEND
+    56,   // KAFKA_STORAGE_ERROR
+    -185, // librdkafka _TIMED_OUT
+    -187, // librdkafka _ALL_BROKERS_DOWN
+    -192, // librdkafka _MSG_TIMED_OUT
+    -195, // librdkafka _TRANSPORT
+];
+
+/// Error codes asking the producer to slow down.
+const THROTTLING_CODES: [i32; 2] = [
+    89,   // THROTTLING_QUOTA_EXCEEDED
+    -184, // librdkafka _QUEUE_FULL
+];
+
+/// Error codes of misconfigured producers, which fail every attempt until fixed.
+const FATAL_CODES: [i32; 3] = [
+    29, // TOPIC_AUTHORIZATION_FAILED
+    31, // CLUSTER_AUTHORIZATION_FAILED
+    58, // SASL_AUTHENTICATION_FAILED
+];
+
+/// An error returned while publishing to Kafka, exposing its error code.
+///
+/// Implement it for the error type of the Kafka client in use. With `rdkafka`, the code of a
+/// `KafkaError` is `err.rdkafka_error_code().map(|code| code as i32)`.
+pub trait BrokerError {
+    /// Returns the Kafka protocol error code (positive), the librdkafka local error code
+    /// (negative), or `None` if the error has no code.
+    fn error_code(&self) -> Option<i32>;
+}
+
+/// An `ErrorClassifier` for errors implementing `BrokerError`.
+///
+/// - Leader elections (`NOT_LEADER_OR_FOLLOWER`, `LEADER_NOT_AVAILABLE`), request and delivery
+///   timeouts, unreachable brokers and missing in-sync replicas are `Transient`.
+/// - A full local producer queue (`_QUEUE_FULL`) and exceeded quotas are `Throttled`.
+/// - Authorization and authentication failures are `Fatal`.
+/// - Every other error, such as `MESSAGE_TOO_LARGE` or an invalid topic, and errors without a
+///   code, are `Permanent`.
+///
+/// # Example
+/// ```
+/// use resilient_rs::classifier::{ErrorClass, ErrorClassifier};
+/// use resilient_rs::kafka::{BrokerError, KafkaClassifier};
+///
+/// struct ProduceError(i32);
+///
+/// impl BrokerError for ProduceError {
+///     fn error_code(&self) -> Option<i32> {
+///         Some(self.0)
+///     }
+/// }
+///
+/// assert_eq!(KafkaClassifier.classify(&ProduceError(6)), ErrorClass::Transient);
+/// assert_eq!(KafkaClassifier.classify(&ProduceError(10)), ErrorClass::Permanent);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct KafkaClassifier;
+
+impl<E: BrokerError> ErrorClassifier<E> for KafkaClassifier {
+    fn classify(&self, error: &E) -> ErrorClass {
+        match error.error_code() {
+            Some(code) if RETRIABLE_CODES.contains(&code) => ErrorClass::Transient,
+            Some(code) if THROTTLING_CODES.contains(&code) => {
+                ErrorClass::Throttled { retry_after: None }
+            }
+            Some(code) if FATAL_CODES.contains(&code) => ErrorClass::Fatal,
+            _ => ErrorClass::Permanent,
+        }
+    }
+}
+
+/// The error returned by `DeliveryRetry::send`.
+#[derive(Debug, PartialEq)]
+pub enum DeliveryError<E> {
+    /// The circuit breaker of the topic rejected the message, which was not published.
+    CircuitOpen {
+        topic: String,
+        retry_after: Option<Duration>,
+    },
+    /// Publishing failed after the retries allowed for it.
+    Failed(E),
+}
+
+impl<E> DeliveryError<E> {
+    /// Returns `true` if the message was rejected by the circuit breaker without being published.
+    pub fn is_circuit_open(&self) -> bool {
+        matches!(self, DeliveryError::CircuitOpen { .. })
+    }
+
+    /// Returns the error of the last attempt, or `None` if the message was rejected.
+    pub fn into_inner(self) -> Option<E> {
+        match self {
+            DeliveryError::Failed(err) => Some(err),
+            DeliveryError::CircuitOpen { .. } => None,
+        }
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for DeliveryError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DeliveryError::CircuitOpen { topic, .. } => write!(
+                f,
+                "Circuit Breaker for topic {} rejected the message. Please try later..!",
+                topic
+            ),
+            DeliveryError::Failed(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> std::error::Error for DeliveryError<E> {}
+
+/// Retries the publishing of messages to Kafka and, optionally, guards every topic with its own
+/// circuit breaker.
+///
+/// `DeliveryRetry` does not own the producer: `send` takes a closure publishing the message and
+/// awaiting its delivery report, so it works with any Kafka client whose errors implement
+/// `BrokerError`.
+///
+/// Errors are classified by the `error_classifier` or `retry_condition` of the `RetryConfig` when
+/// one is set, and by `KafkaClassifier` otherwise. With `with_circuit_breaker`, only the errors
+/// that are not `Permanent` count as failures of the topic, so an oversized message never opens
+/// its circuit; once a topic's circuit is open, messages to it fail fast with
+/// `DeliveryError::CircuitOpen` until the cooldown elapses.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::config::{CircuitBreakerConfig, RetryConfig};
+/// use resilient_rs::kafka::{BrokerError, DeliveryRetry};
+///
+/// #[derive(Debug)]
+/// struct ProduceError(i32);
+///
+/// impl std::fmt::Display for ProduceError {
+///     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+///         write!(f, "produce failed with code {}", self.0)
+///     }
+/// }
+///
+/// impl BrokerError for ProduceError {
+///     fn error_code(&self) -> Option<i32> {
+///         Some(self.0)
+///     }
+/// }
+///
+/// async fn publish(payload: &[u8]) -> Result<(i32, i64), ProduceError> {
+///     Ok((0, 42))
+/// }
+///
+/// # async_std::task::block_on(async {
+/// let delivery = DeliveryRetry::new(RetryConfig::default())
+///     .with_circuit_breaker(CircuitBreakerConfig::new(1, 5, Duration::from_secs(30)));
+/// let (partition, offset) = delivery.send("orders", || publish(b"order-42")).await.unwrap();
+/// # });
+/// ```
+pub struct DeliveryRetry<E> {
+    retry: RetryConfig<E>,
+    breakers: Option<Arc<CircuitBreakerRegistry>>,
+}
+
+impl<E: BrokerError + fmt::Display> DeliveryRetry<E> {
+    /// Creates a `DeliveryRetry` retrying according to `retry`, without circuit breaking.
+    ///
+    /// # Arguments
+    /// * `retry` - The attempts, delays and strategy of the retries.
+    pub fn new(retry: RetryConfig<E>) -> Self {
+        DeliveryRetry {
+            retry,
+            breakers: None,
+        }
+    }
+
+    /// Guards every topic with its own breaker configured with `config`, and returns the modified
+    /// `DeliveryRetry`.
+    pub fn with_circuit_breaker(self, config: CircuitBreakerConfig) -> Self {
+        self.with_breaker_registry(Arc::new(CircuitBreakerRegistry::new(config)))
+    }
+
+    /// Guards every topic with the breaker of `registry` named after the topic, and returns the
+    /// modified `DeliveryRetry`.
+    ///
+    /// Name breakers after brokers instead by passing the broker to `send` as the `topic`.
+    pub fn with_breaker_registry(mut self, registry: Arc<CircuitBreakerRegistry>) -> Self {
+        self.breakers = Some(registry);
+        self
+    }
+
+    /// Publishes a message to `topic`, retrying on retryable errors.
+    ///
+    /// `publish` is called once per attempt and should resolve once the broker acknowledged the
+    /// message, so that delivery timeouts are retried too. Retried messages may be delivered
+    /// twice; enable the idempotent producer of the client to deduplicate them.
+    ///
+    /// # Returns
+    /// - `Ok(T)` with the delivery report of the first successful attempt.
+    /// - `Err(DeliveryError::CircuitOpen)` if the topic's breaker rejected the message.
+    /// - `Err(DeliveryError::Failed)` with the last error otherwise.
+    pub async fn send<F, Fut, T>(&self, topic: &str, mut publish: F) -> Result<T, DeliveryError<E>>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let breaker = self.breakers.as_ref().map(|registry| registry.get(topic));
+        let mut retry = GuardedRetry::new(
+            &self.retry,
+            breaker.as_deref().map(CircuitBreaker::state_machine),
+        );
+
+        loop {
+            let attempt = match retry.start() {
+                Ok(attempt) => attempt,
+                Err(retry_after) => {
+                    return Err(DeliveryError::CircuitOpen {
+                        topic: topic.to_string(),
+                        retry_after,
+                    });
+                }
+            };
+            let result = publish().await;
+            let class = result.as_ref().err().map(|err| {
+                if self.retry.error_classifier.is_some() || self.retry.retry_condition.is_some() {
+                    self.retry.classify(err)
+                } else {
+                    KafkaClassifier.classify(err)
+                }
+            });
+            let outcome = match (&result, class) {
+                (Err(err), Some(class)) if class != ErrorClass::Permanent => {
+                    Err(err.to_string().into())
+                }
+                _ => Ok(()),
+            };
+            let wait = match retry.end(attempt, result, outcome, class) {
+                Step::Done(result) => return result.map_err(DeliveryError::Failed),
+                Step::Retry(wait) => wait,
+            };
+            back_off(None, self.retry.timer_wheel.as_ref(), wait).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::RetryStrategy;
+    use async_std::task::block_on;
+    use std::cell::Cell;
+
+    #[derive(Debug, PartialEq)]
+    struct ProduceError(i32);
+
+    impl fmt::Display for ProduceError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "produce failed with code {}", self.0)
+        }
+    }
+
+    impl BrokerError for ProduceError {
+        fn error_code(&self) -> Option<i32> {
+            Some(self.0)
+        }
+    }
+
+    fn retry_config() -> RetryConfig<ProduceError> {
+        RetryConfig::new(3, Duration::from_millis(1), RetryStrategy::Linear)
+    }
+
+    #[test]
+    fn test_retries_leader_elections() {
+        let delivery = DeliveryRetry::new(retry_config());
+        let attempts = Cell::new(0);
+
+        let result = block_on(delivery.send("orders", || {
+            attempts.set(attempts.get() + 1);
+            let attempt = attempts.get();
+            async move {
+                match attempt {
+                    1 => Err(ProduceError(6)),
+                    2 => Err(ProduceError(-184)),
+                    _ => Ok(7),
+                }
+            }
+        }));
+        assert_eq!(result, Ok(7));
+        assert_eq!(attempts.get(), 3);
+
+        let result = block_on(delivery.send("orders", || async { Err::<(), _>(ProduceError(10)) }));
+        assert_eq!(result, Err(DeliveryError::Failed(ProduceError(10))));
+    }
+
+    #[test]
+    fn test_circuit_breaker_per_topic() {
+        let delivery = DeliveryRetry::new(retry_config())
+            .with_circuit_breaker(CircuitBreakerConfig::new(1, 2, Duration::from_secs(30)));
+        let attempts = Cell::new(0);
+        let failing = || {
+            attempts.set(attempts.get() + 1);
+            async { Err::<(), _>(ProduceError(-187)) }
+        };
+
+        let result = block_on(delivery.send("orders", failing));
+        assert_eq!(result, Err(DeliveryError::Failed(ProduceError(-187))));
+        assert_eq!(attempts.get(), 2);
+
+        let result = block_on(delivery.send("orders", failing));
+        assert!(result.unwrap_err().is_circuit_open());
+        assert_eq!(attempts.get(), 2);
+
+        let result = block_on(delivery.send("payments", || async { Ok::<_, ProduceError>(1) }));
+        assert_eq!(result, Ok(1));
+    }
+}
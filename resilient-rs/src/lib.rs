@@ -1,20 +1,291 @@
+pub use error::Error;
+
+pub use defaults::{
+    global_circuit_breaker_defaults, global_retry_config, set_global_circuit_breaker_defaults,
+    set_global_retry_config,
+};
+
+/// The `admission` module provides the `AdmissionController`, which tags work with a `Priority`
+/// and, as the load measured from calls in flight, shed rates and open circuit breakers rises,
+/// rejects low-priority work first so that high-priority work keeps flowing.
+pub mod admission;
+
+/// The `adaptive` module provides the `AdaptiveLimiter`, a concurrency limiter that adjusts its
+/// limit to the latency and errors of the calls it admits (AIMD), instead of relying on a
+/// hand-tuned static limit.
+pub mod adaptive;
+
 /// The `asynchronous` module provides utilities for handling retries and resilience
 /// in asynchronous contexts. This includes retry logic and other resilience patterns
 /// that are compatible with async/await.
 pub mod asynchronous;
 
+/// The `aws` module provides the `AwsClassifier`, which classifies the errors of aws-sdk-rust
+/// operations (throttling, `5xx`, timeouts versus access denied or validation errors), and
+/// `standard_retry_config`, a `RetryConfig` matching the SDKs' "standard" retry mode. It is
+/// available with the `aws` feature.
+#[cfg(feature = "aws")]
+pub mod aws;
+
+/// The `balancer` module provides the `LoadBalancer`, a client-side load balancer spreading calls
+/// over weighted endpoints, trying the next endpoint on failure, with a circuit breaker per
+/// endpoint and weights lowered while an endpoint fails.
+pub mod balancer;
+
+/// The `batch` module provides `retry_batch`, which submits a batch to a bulk API and retries
+/// only the items that failed, re-batched together with backoff, reporting the outcome of every
+/// item.
+pub mod batch;
+
+/// The `breaker` module holds the circuit breaker state machine shared by the asynchronous and
+/// synchronous `CircuitBreaker`s, along with their public state, metrics and error types.
+pub(crate) mod breaker;
+
+/// The `bulkhead` module holds the error type and rejection handling shared by the asynchronous
+/// and synchronous `Bulkhead`s, which cap the number of concurrent executions of an operation.
+pub(crate) mod bulkhead;
+
+/// The `cache` module provides the `ResultCache`, which caches successful results for a TTL and
+/// serves the stale cached value as a degraded response when the operation fails or its circuit
+/// breaker is open.
+pub mod cache;
+
+/// The `channel` module provides the `ResilientSender`, which backs off and retries sends to a
+/// full bounded channel until a deadline, then fails with a typed `ChannelOverloaded` error.
+pub mod channel;
+
+/// The `chaos` module provides `Chaos`, which injects errors, latency, hangs and scripted failure
+/// sequences into the calls of an operation, to test that retry, circuit breaker and timeout
+/// configurations behave as intended. It is available with the `chaos` feature.
+#[cfg(any(test, feature = "chaos"))]
+pub mod chaos;
+
+/// The `classifier` module provides the `ErrorClassifier` trait and the `ErrorClass` outcomes
+/// (`Transient`, `Permanent`, `Throttled`, `Fatal`) used by the retry loops and the circuit
+/// breaker to decide how to react to a failure.
+pub mod classifier;
+
+/// The `client` module provides the `ResilientClient`, a batteries-included client owning the
+/// retry policy, circuit breaker, rate limiter, timeout and fallback of a single downstream
+/// dependency behind one `run` method.
+pub mod client;
+
 /// The `config` module provides configuration structures for retry logic and other
 /// resilience patterns. This includes settings like the maximum number of attempts
 /// and delay between retries.
 pub mod config;
 
+/// The `defaults` module holds the process-wide retry policy and circuit breaker configuration,
+/// set once at startup and used by the `retry_with_defaults` functions, so that small
+/// applications do not have to pass configurations through every function signature.
+pub mod defaults;
+
+/// The `dlq` module provides the `DeadLetterQueue`, which processes messages with a per-message
+/// retry policy and hands the messages that still fail, along with the error of every attempt,
+/// to a `DeadLetterSink`.
+pub mod dlq;
+
+/// The `error` module provides the crate-level `Error`, the failure causes (timeouts, open
+/// circuits, exhausted retries, rate limiting, cancellation) boxed by the APIs returning
+/// `Box<dyn Error + Send + Sync>`, so callers can match on them.
+pub mod error;
+
+/// The `events` module provides a crate-wide subscription bus for structured
+/// `ResilienceEvent`s (attempts, scheduled retries, breaker transitions, timeouts and
+/// fallbacks), delivered to callbacks or bounded channels.
+pub mod events;
+
+/// The `health` module provides the `HealthChecker`, which periodically probes dependencies,
+/// opens their circuit breakers when the probes fail, lets them close again on recovery, and
+/// reports an aggregate `HealthSnapshot`.
+pub mod health;
+
+/// The `http` module provides helpers deciding whether an HTTP response is worth retrying and
+/// how long to wait, from its status and its `Retry-After` or `RateLimit-Reset` headers, for use
+/// with any HTTP client. It is available with the `http` feature.
+#[cfg(feature = "http")]
+pub mod http;
+
+/// The `hyper` module provides the `ResilientHyperClient`, a wrapper around a `hyper_util`
+/// client retrying connection failures with backoff and, optionally, breaking the circuit per
+/// host. It is available with the `hyper` feature.
+#[cfg(feature = "hyper")]
+pub mod hyper;
+
+/// The `idempotency` module provides the `IdempotencyKey` threaded through the attempts of
+/// `retry_idempotent`, and the `Deduplicator`, which executes an operation at most once per key
+/// through a pluggable `IdempotencyStore`, making retried writes safe.
+pub mod idempotency;
+
+/// The `kafka` module provides the `KafkaClassifier`, which separates retryable broker errors
+/// (queue full, not leader, timeouts) from permanent ones, and `DeliveryRetry`, which retries the
+/// publishing of messages with an optional circuit breaker per topic, for any Kafka client.
+pub mod kafka;
+
+/// The `lockfree` module provides the `LockFreeCircuitBreaker`, a circuit breaker whose state and
+/// failure windows live in atomics instead of behind a lock, for call paths shared by many threads.
+pub mod lockfree;
+
+/// The `logging` module provides the `Level` of log messages and the `Diagnostics` hook routing
+/// them to an embedder's own channel, along with the internal `log_with!` macro used by every
+/// pattern to log through the `log` facade while honoring a policy's `LogConfig`. The `log`
+/// facade is used with the `log` feature, enabled by default.
+pub mod logging;
+
+/// The `metrics` module holds the crate-wide counters (retries, give-ups, breaker openings,
+/// rejections, timeouts and fallbacks) that are reported by the exposition helpers.
+pub(crate) mod metrics;
+
+/// The `otel` module reports retries, breaker transitions, timeouts and fallbacks as
+/// OpenTelemetry metrics and span events, and traces every retry attempt as a child span. It is
+/// available with the `otel` feature.
+#[cfg(feature = "otel")]
+pub mod otel;
+
+/// The `pipeline` module provides the `Pipeline`, a single executor combining a timeout, retries,
+/// a rate limiter, a circuit breaker, a bulkhead and a fallback in a fixed, documented order.
+pub mod pipeline;
+
+/// The `pool` module provides the `ConnectionPool`, a small generic connection pool whose checkout
+/// retries failed connects, evicts broken connections and guards every backend with a circuit
+/// breaker.
+pub mod pool;
+
+/// The `prometheus` module renders the crate-wide counters in the Prometheus text format
+/// through a single `gather()` function. It is available with the `prometheus` feature.
+#[cfg(feature = "prometheus")]
+pub mod prometheus;
+
+/// The `queue` module provides the `RetryQueue`, which persists failed operations in a
+/// `QueueStore` and retries them from a worker with backoff, resuming their attempts across
+/// process restarts. The `SqliteQueueStore` is available with the `queue-sqlite` feature.
+pub mod queue;
+
+/// The `ratelimit` module provides rate limiters capping the number of calls admitted per time
+/// window: the `FixedWindowLimiter`, for callers whose upstream quotas are expressed that way,
+/// and the `SlidingLogLimiter`, which tracks individual calls to avoid bursts at window
+/// boundaries.
+pub mod ratelimit;
+
+/// The `redis` module provides the `RedisClassifier`, separating retryable Redis errors
+/// (`LOADING`, `MOVED`, dropped connections) from permanent ones, and `ResilientRedis`, which
+/// retries commands with backoff, reconnecting between attempts, optionally behind a circuit
+/// breaker. It is available with the `redis` feature.
+#[cfg(feature = "redis")]
+pub mod redis;
+
+/// The `registry` module provides the `PolicyRegistry`, which stores retry, circuit breaker,
+/// timeout and rate limit policies under names so they can be looked up anywhere in an
+/// application, and the `CircuitBreakerRegistry`, which creates one shared breaker per dependency
+/// name on first use. With the `config-file` feature, the `PolicyRegistry` loads its policies
+/// from a `resilience.toml` profile.
+pub mod registry;
+
+/// The `reqwest` module provides the `ResilienceMiddleware`, a `reqwest_middleware::Middleware`
+/// retrying `5xx`, `429` and connection failures with HTTP-aware classification and, optionally,
+/// breaking the circuit per host. It is available with the `reqwest-middleware` feature.
+#[cfg(feature = "reqwest-middleware")]
+pub mod reqwest;
+
+/// The `saga` module provides the `Saga`, which runs a sequence of steps each retried with its
+/// own policy and, when a step still fails, undoes the completed ones by running their
+/// compensations in reverse order.
+pub mod saga;
+
+/// The `scatter` module provides `scatter_gather`, which fans an operation out over a set of
+/// inputs with a concurrency limit and per-item retries, and gathers the successes and failures
+/// according to a fail-fast, best-effort or minimum-success-count `GatherPolicy`.
+pub mod scatter;
+
+/// The `sim` module provides the `VirtualClock`, which runs the crate's sleeps, timeouts,
+/// breaker cooldowns, rate limiter windows and jitter against virtual time that tests advance
+/// instantly and deterministically. It is available with the `sim` feature.
+#[cfg(any(test, feature = "sim"))]
+pub mod sim;
+
+/// The `shedding` module provides the `LoadShedder`, which rejects excess work with an
+/// `Overloaded` error once too many calls are in flight or recent calls became too slow.
+pub mod shedding;
+
+/// The `shutdown` module provides the `ShutdownHandle`, which tells retry loops that the process
+/// is shutting down so they stop scheduling new attempts and return a distinct `Cancelled`
+/// outcome.
+pub mod shutdown;
+
+/// The `sink` module provides the `RetrySink`, wrapping a `futures::Sink` so that failed sends
+/// and flushes are retried with a `RetryConfig`, optionally re-creating the sink through a
+/// factory. It is available with the `sink` feature.
+#[cfg(feature = "sink")]
+pub mod sink;
+
+/// The `sqlx` module provides the `SqlxClassifier`, separating retryable database errors
+/// (serialization failures, deadlocks, dropped connections) from permanent ones, and `retry_tx`,
+/// which runs a transaction again on retryable failures. It is available with the `sqlx` feature.
+#[cfg(feature = "sqlx")]
+pub mod sqlx;
+
+/// The `stats` module provides the opt-in `Stats` handle, a shared collector of attempts,
+/// successes, give-ups, backoff time and per-attempt latencies updated by the retry functions
+/// and the circuit breaker.
+pub mod stats;
+
+/// The `store` module provides the `StateStore` trait, through which circuit breakers share their
+/// open state and failure count across the replicas of a service, along with an in-memory store
+/// and, with the `redis` feature, a Redis-backed store.
+pub mod store;
+
 /// The `strategies` module defines different retry strategies used for handling
 /// transient failures. It provides mechanisms to calculate appropriate delay
 /// durations between retry attempts, supporting both linear and exponential backoff approaches.
 ///
 /// This module is utilized by both synchronous and asynchronous retry mechanisms.
 pub mod strategies;
+
 /// The `synchronous` module provides utilities for handling retries and resilience
 /// in synchronous contexts. This includes retry logic and other resilience patterns
 /// for blocking operations.
 pub mod synchronous;
+
+/// The `time` module is the crate's single source of time: it reads the clock, sleeps, applies
+/// timeouts and draws jitter, against the `sim` module's virtual clock when one is installed.
+pub(crate) mod time;
+
+/// The `tcp` module provides the `ResilientTcpStream`, which dials with retry and backoff and
+/// re-establishes its connection, running a handshake hook, when a read or write fails.
+pub mod tcp;
+
+/// The `throttle` module provides the `Debouncer`, which coalesces bursts of triggers into
+/// batches handed over once they quiet down, and the `Throttle`, which spaces asynchronous or
+/// blocking calls a minimum interval apart.
+pub mod throttle;
+
+/// The `timeline` module provides the `Timeline` of a retried call, recording the start,
+/// duration and outcome of every attempt and the delays between them, as returned by the
+/// `retry_with_timeline` functions.
+pub mod timeline;
+
+/// The `tower` module provides `tower::Layer` implementations (`RetryLayer`,
+/// `CircuitBreakerLayer`, `TimeoutFallbackLayer` and `RateLimitLayer`) backed by the crate's
+/// configurations, for hyper, axum and tonic service stacks. It is available with the `tower`
+/// feature.
+#[cfg(feature = "tower")]
+pub mod tower;
+
+/// The `watchdog` module provides the `Watchdog`, which detects stuck work: a task must pet it
+/// within an interval, or a callback fires to restart the task, alert or open a breaker.
+pub mod watchdog;
+
+/// The `websocket` module provides the `ReconnectingWebSocket`, which owns a connection factory
+/// and transparently reconnects with backoff, resubscribing through a hook, when its connection
+/// drops. It is available with the `websocket` feature.
+#[cfg(feature = "websocket")]
+pub mod websocket;
+
+/// The `wheel` module provides the `TimerWheel`, a hierarchical timer wheel that the retry loops
+/// can share for their backoff sleeps, so that mass concurrent retries do not each register a
+/// runtime timer.
+pub mod wheel;
+
+/// The `window` module tracks the recent call outcomes used by circuit breakers configured with a
+/// sliding `FailureWindow`.
+pub(crate) mod window;
@@ -0,0 +1,627 @@
+use crate::breaker::{CircuitBreakerError, CircuitBreakerState};
+use crate::classifier::{ErrorClass, ErrorClassifier};
+use crate::config::{BoxError, CircuitBreakerConfig, FailureWindow};
+use crate::events::{self, ResilienceEvent};
+use crate::logging::Level;
+use crate::logging::log_with;
+use crate::metrics;
+use crate::time;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU8, AtomicU64, AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+const CLOSE: u64 = 0;
+const OPEN: u64 = 1;
+const HALF_OPEN: u64 = 2;
+const STATE_BITS: u32 = 2;
+const STATE_MASK: u64 = (1 << STATE_BITS) - 1;
+
+/// A circuit breaker whose state and counters live in atomics, for high-throughput call paths.
+///
+/// `synchronous::CircuitBreaker` guards its state machine with a `Mutex`, which becomes a
+/// contention point once many threads go through the same breaker hundreds of thousands of times
+/// per second. This breaker makes the same `Close` → `Open` → `HalfOpen` → `Close` transitions
+/// without any lock: the state and the opening time are packed in one `AtomicU64` updated with
+/// compare-and-swap, consecutive failures are an atomic counter, a `FailureWindow::Count` window
+/// is a ring of atomic outcome slots, and a `FailureWindow::Time` window is split into buckets
+/// stamped with the epoch (the index of the bucket-sized slice of time) they currently count.
+///
+/// It honors `failure_threshold`, `success_threshold`, `cooldown_period`, `window` and
+/// `max_concurrent_calls`, and supports an error classifier. To keep every call on the fast path,
+/// it does not support cooldown escalation, latency metrics, statistics, state stores, forced
+/// states or transition subscriptions, and it only logs state transitions. Counts taken while
+/// the state changes concurrently are approximate, e.g. a few calls may be admitted as trial
+/// calls while the circuit is `HalfOpen`. Use it by constructing it instead of a
+/// `synchronous::CircuitBreaker`; it runs blocking operations with `run` and asynchronous ones
+/// with `run_async`.
+///
+/// # Type Parameters
+/// * `E` - The error type of the supervised operations, `Box<dyn Error + Send + Sync>` by default. Use
+///   `LockFreeCircuitBreaker::with_config` to supervise operations failing with another error type.
+///
+/// # Example
+/// ```rust
+/// use std::sync::Arc;
+/// use std::thread;
+/// use std::time::Duration;
+/// use resilient_rs::config::CircuitBreakerConfig;
+/// use resilient_rs::lockfree::LockFreeCircuitBreaker;
+/// use resilient_rs::synchronous::CircuitBreakerState;
+///
+/// let cb = Arc::new(LockFreeCircuitBreaker::<String>::with_config(CircuitBreakerConfig::new(
+///     4,
+///     2,
+///     Duration::from_secs(30),
+/// )));
+/// let workers: Vec<_> = (0..4)
+///     .map(|_| {
+///         let cb = Arc::clone(&cb);
+///         thread::spawn(move || cb.run(|| Err::<(), _>("connection refused".to_string())))
+///     })
+///     .collect();
+/// for worker in workers {
+///     let _ = worker.join();
+/// }
+/// assert_eq!(cb.state(), CircuitBreakerState::Open);
+/// assert!(cb.run(|| Ok::<_, String>(())).unwrap_err().is_open());
+/// ```
+pub struct LockFreeCircuitBreaker<E = BoxError> {
+    config: CircuitBreakerConfig,
+    /// The instant the times packed in `state` are measured from.
+    origin: Instant,
+    /// The state in the low `STATE_BITS` bits, and the time the circuit opened, in nanoseconds
+    /// since `origin`, in the others.
+    state: AtomicU64,
+    /// Consecutive failures while `Close`, with `FailureWindow::Consecutive`.
+    failures: AtomicU64,
+    /// Successful trial calls while `HalfOpen`.
+    successes: AtomicU64,
+    in_flight: AtomicUsize,
+    window: Window,
+    classifier: Option<Arc<dyn ErrorClassifier<E> + Send + Sync>>,
+}
+
+impl LockFreeCircuitBreaker {
+    /// Creates a new `LockFreeCircuitBreaker` for operations failing with
+    /// `Box<dyn Error + Send + Sync>`.
+    ///
+    /// # Parameters
+    /// - `config`: A `CircuitBreakerConfig` defining the failure threshold, success threshold, and
+    ///   cooldown period.
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        LockFreeCircuitBreaker::with_config(config)
+    }
+}
+
+impl<E> LockFreeCircuitBreaker<E> {
+    /// Creates a new `LockFreeCircuitBreaker` for operations failing with errors of type `E`.
+    pub fn with_config(config: CircuitBreakerConfig) -> Self {
+        LockFreeCircuitBreaker {
+            config,
+            origin: time::now(),
+            state: AtomicU64::new(CLOSE),
+            failures: AtomicU64::new(0),
+            successes: AtomicU64::new(0),
+            in_flight: AtomicUsize::new(0),
+            window: Window::new(&config.window),
+            classifier: None,
+        }
+    }
+
+    /// Sets an error classifier; errors classified as `Fatal` open the circuit immediately.
+    pub fn with_classifier(
+        mut self,
+        classifier: impl ErrorClassifier<E> + Send + Sync + 'static,
+    ) -> Self {
+        self.classifier = Some(Arc::new(classifier));
+        self
+    }
+
+    /// Executes a blocking operation under circuit breaker supervision.
+    ///
+    /// # Returns
+    /// - `Ok(T)` if the operation succeeds.
+    /// - `Err(CircuitBreakerError::Open { .. })` or `Err(CircuitBreakerError::Saturated { .. })`
+    ///   if the call was rejected without running the operation.
+    /// - `Err(CircuitBreakerError::Inner(E))` if the operation fails.
+    pub fn run<F, T>(&self, operation: F) -> Result<T, CircuitBreakerError<E>>
+    where
+        F: FnOnce() -> Result<T, E>,
+    {
+        let permit = self.acquire()?;
+        let result = operation();
+        drop(permit);
+        self.complete(result)
+    }
+
+    /// Executes an asynchronous operation under circuit breaker supervision.
+    ///
+    /// # Returns
+    /// The same as `run`.
+    pub async fn run_async<F, Fut, T>(&self, operation: F) -> Result<T, CircuitBreakerError<E>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let permit = self.acquire()?;
+        let result = operation().await;
+        drop(permit);
+        self.complete(result)
+    }
+
+    /// Returns the current state of the circuit.
+    pub fn state(&self) -> CircuitBreakerState {
+        match self.state.load(Ordering::Acquire) & STATE_MASK {
+            OPEN => CircuitBreakerState::Open,
+            HALF_OPEN => CircuitBreakerState::HalfOpen,
+            _ => CircuitBreakerState::Close,
+        }
+    }
+
+    /// Returns the number of consecutive failures recorded while the circuit is `Close`.
+    pub fn failure_count(&self) -> usize {
+        self.failures.load(Ordering::Relaxed) as usize
+    }
+
+    /// Closes the circuit and forgets every recorded failure.
+    pub fn reset(&self) {
+        self.state.store(CLOSE, Ordering::Release);
+        self.failures.store(0, Ordering::Relaxed);
+        self.successes.store(0, Ordering::Relaxed);
+        self.window.clear();
+        events::emit(ResilienceEvent::BreakerClosed);
+        log_with!(self.config.log, Level::Debug, "Circuit Breaker reset");
+    }
+
+    /// Decides whether a call may run, moving an `Open` circuit whose cooldown has elapsed to
+    /// `HalfOpen`.
+    fn acquire(&self) -> Result<InFlight<'_>, CircuitBreakerError<E>> {
+        let word = self.state.load(Ordering::Acquire);
+        if word & STATE_MASK == OPEN {
+            let now = self.now();
+            let open_for = Duration::from_nanos(now.saturating_sub(word >> STATE_BITS));
+            if open_for < self.config.cooldown_period {
+                reject();
+                return Err(CircuitBreakerError::Open {
+                    retry_after: self.config.cooldown_period - open_for,
+                });
+            }
+            // Only the thread winning the exchange reports the transition; the others are
+            // admitted as trial calls of the same `HalfOpen` period.
+            if self
+                .state
+                .compare_exchange(word, HALF_OPEN, Ordering::AcqRel, Ordering::Acquire)
+                .is_ok()
+            {
+                self.successes.store(0, Ordering::Relaxed);
+                events::emit(ResilienceEvent::BreakerHalfOpened);
+                log_with!(
+                    self.config.log,
+                    Level::Warn,
+                    "Circuit Breaker transitioning to Half Open State"
+                );
+            }
+        }
+        let running = self.in_flight.fetch_add(1, Ordering::AcqRel);
+        let permit = InFlight(&self.in_flight);
+        if let Some(max_concurrent_calls) = self.config.max_concurrent_calls
+            && running >= max_concurrent_calls
+        {
+            reject();
+            return Err(CircuitBreakerError::Saturated {
+                max_concurrent_calls,
+            });
+        }
+        Ok(permit)
+    }
+
+    /// Updates the state machine with the outcome of an admitted call.
+    fn complete<T>(&self, result: Result<T, E>) -> Result<T, CircuitBreakerError<E>> {
+        match result {
+            Ok(output) => {
+                self.on_success();
+                Ok(output)
+            }
+            Err(err) => {
+                let class = self
+                    .classifier
+                    .as_ref()
+                    .map_or(ErrorClass::Transient, |c| c.classify(&err));
+                if class == ErrorClass::Fatal {
+                    self.trip();
+                } else {
+                    self.on_failure();
+                }
+                Err(CircuitBreakerError::Inner(err))
+            }
+        }
+    }
+
+    fn on_success(&self) {
+        let word = self.state.load(Ordering::Acquire);
+        if word & STATE_MASK == HALF_OPEN {
+            let successes = self.successes.fetch_add(1, Ordering::AcqRel) + 1;
+            if successes >= self.config.success_threshold as u64
+                && self
+                    .state
+                    .compare_exchange(word, CLOSE, Ordering::AcqRel, Ordering::Acquire)
+                    .is_ok()
+            {
+                self.failures.store(0, Ordering::Relaxed);
+                self.window.clear();
+                events::emit(ResilienceEvent::BreakerClosed);
+                log_with!(
+                    self.config.log,
+                    Level::Debug,
+                    "Circuit breaker transitioning to closed state"
+                );
+            }
+            return;
+        }
+        // Skip the store on the common path, so successes do not bounce the cache line.
+        if self.failures.load(Ordering::Relaxed) > 0 {
+            self.failures.store(0, Ordering::Relaxed);
+        }
+        self.window.record(false, self.now());
+    }
+
+    fn on_failure(&self) {
+        if self.state.load(Ordering::Acquire) & STATE_MASK == HALF_OPEN {
+            self.trip();
+            return;
+        }
+        let now = self.now();
+        let tripped = match &self.window {
+            Window::Consecutive => {
+                let failures = self.failures.fetch_add(1, Ordering::AcqRel) + 1;
+                failures >= self.config.failure_threshold as u64
+            }
+            window => {
+                window.record(true, now);
+                window.exceeds()
+            }
+        };
+        if tripped {
+            self.trip();
+        }
+    }
+
+    /// Opens the circuit, unless another thread already did.
+    fn trip(&self) {
+        let opened = (self.now() << STATE_BITS) | OPEN;
+        let mut word = self.state.load(Ordering::Acquire);
+        while word & STATE_MASK != OPEN {
+            match self.state.compare_exchange_weak(
+                word,
+                opened,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+            ) {
+                Ok(_) => {
+                    self.window.clear();
+                    metrics::increment(&metrics::BREAKER_OPENS);
+                    events::emit(ResilienceEvent::BreakerOpened);
+                    log_with!(
+                        self.config.log,
+                        Level::Warn,
+                        "Circuit Breaker transitioning to Open State"
+                    );
+                    return;
+                }
+                Err(current) => word = current,
+            }
+        }
+    }
+
+    /// Returns the nanoseconds elapsed since `origin`.
+    fn now(&self) -> u64 {
+        time::elapsed(self.origin).as_nanos() as u64
+    }
+}
+
+/// Records a rejected call.
+fn reject() {
+    metrics::increment(&metrics::BREAKER_REJECTIONS);
+    events::emit(ResilienceEvent::CallRejected);
+}
+
+/// Counts a call as running until dropped, even if the operation panics or its future is
+/// dropped.
+struct InFlight<'a>(&'a AtomicUsize);
+
+impl Drop for InFlight<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// The lock-free counterpart of a sliding `FailureWindow`.
+enum Window {
+    Consecutive,
+    Count {
+        /// The outcome of the last calls: `0` for none yet, `1` for a success, `2` for a failure.
+        slots: Box<[AtomicU8]>,
+        next: AtomicU64,
+        calls: AtomicU64,
+        failures: AtomicU64,
+        failure_rate_threshold: f64,
+        minimum_calls: usize,
+    },
+    Time {
+        buckets: Box<[Bucket]>,
+        bucket_nanos: u64,
+        failure_rate_threshold: f64,
+        minimum_calls: usize,
+    },
+}
+
+/// The outcomes of the calls made during one slice of a time window.
+struct Bucket {
+    /// The slice of time counted, `u64::MAX` while unused.
+    epoch: AtomicU64,
+    calls: AtomicU64,
+    failures: AtomicU64,
+}
+
+const SUCCESS: u8 = 1;
+const FAILURE: u8 = 2;
+
+impl Window {
+    fn new(window: &FailureWindow) -> Self {
+        match *window {
+            FailureWindow::Consecutive => Window::Consecutive,
+            FailureWindow::Count {
+                size,
+                failure_rate_threshold,
+                minimum_calls,
+            } => Window::Count {
+                slots: (0..size.max(1)).map(|_| AtomicU8::new(0)).collect(),
+                next: AtomicU64::new(0),
+                calls: AtomicU64::new(0),
+                failures: AtomicU64::new(0),
+                failure_rate_threshold,
+                minimum_calls,
+            },
+            FailureWindow::Time {
+                duration,
+                buckets,
+                failure_rate_threshold,
+                minimum_calls,
+            } => {
+                let buckets = buckets.max(1);
+                Window::Time {
+                    buckets: (0..buckets)
+                        .map(|_| Bucket {
+                            epoch: AtomicU64::new(u64::MAX),
+                            calls: AtomicU64::new(0),
+                            failures: AtomicU64::new(0),
+                        })
+                        .collect(),
+                    bucket_nanos: (duration.as_nanos() as u64 / buckets as u64).max(1),
+                    failure_rate_threshold,
+                    minimum_calls,
+                }
+            }
+        }
+    }
+
+    /// Records the outcome of a call completed `now` nanoseconds after the breaker's origin.
+    fn record(&self, failed: bool, now: u64) {
+        match self {
+            Window::Consecutive => {}
+            Window::Count {
+                slots,
+                next,
+                calls,
+                failures,
+                ..
+            } => {
+                let index = next.fetch_add(1, Ordering::Relaxed) % slots.len() as u64;
+                let outcome = if failed { FAILURE } else { SUCCESS };
+                let evicted = slots[index as usize].swap(outcome, Ordering::AcqRel);
+                if failed {
+                    failures.fetch_add(1, Ordering::AcqRel);
+                }
+                match evicted {
+                    0 => {
+                        calls.fetch_add(1, Ordering::AcqRel);
+                    }
+                    FAILURE => decrement(failures),
+                    _ => {}
+                }
+            }
+            Window::Time {
+                buckets,
+                bucket_nanos,
+                ..
+            } => {
+                let epoch = now / bucket_nanos;
+                let bucket = &buckets[(epoch % buckets.len() as u64) as usize];
+                let seen = bucket.epoch.load(Ordering::Acquire);
+                // The thread moving the bucket to the new epoch resets its counts; outcomes
+                // recorded concurrently with the reset may be lost.
+                if seen != epoch
+                    && bucket
+                        .epoch
+                        .compare_exchange(seen, epoch, Ordering::AcqRel, Ordering::Acquire)
+                        .is_ok()
+                {
+                    bucket.calls.store(0, Ordering::Release);
+                    bucket.failures.store(0, Ordering::Release);
+                }
+                bucket.calls.fetch_add(1, Ordering::AcqRel);
+                if failed {
+                    bucket.failures.fetch_add(1, Ordering::AcqRel);
+                }
+            }
+        }
+    }
+
+    /// Returns `true` once the failure rate of the window reaches its threshold.
+    ///
+    /// Only used by the sliding windows, right after recording a failure, so the epoch of the
+    /// newest bucket of a time window is the one just recorded.
+    fn exceeds(&self) -> bool {
+        let (calls, failures, threshold, minimum_calls) = match self {
+            Window::Consecutive => return false,
+            Window::Count {
+                calls,
+                failures,
+                failure_rate_threshold,
+                minimum_calls,
+                ..
+            } => (
+                calls.load(Ordering::Acquire),
+                failures.load(Ordering::Acquire),
+                *failure_rate_threshold,
+                *minimum_calls,
+            ),
+            Window::Time {
+                buckets,
+                failure_rate_threshold,
+                minimum_calls,
+                ..
+            } => {
+                let newest = buckets
+                    .iter()
+                    .map(|bucket| bucket.epoch.load(Ordering::Acquire))
+                    .filter(|epoch| *epoch != u64::MAX)
+                    .max()
+                    .unwrap_or(0);
+                let (calls, failures) = buckets
+                    .iter()
+                    .filter(|bucket| {
+                        let epoch = bucket.epoch.load(Ordering::Acquire);
+                        epoch != u64::MAX && newest - epoch < buckets.len() as u64
+                    })
+                    .fold((0, 0), |(calls, failures), bucket| {
+                        (
+                            calls + bucket.calls.load(Ordering::Acquire),
+                            failures + bucket.failures.load(Ordering::Acquire),
+                        )
+                    });
+                (calls, failures, *failure_rate_threshold, *minimum_calls)
+            }
+        };
+        calls > 0
+            && calls >= minimum_calls as u64
+            && failures as f64 * 100.0 / calls as f64 >= threshold
+    }
+
+    /// Forgets every recorded outcome.
+    fn clear(&self) {
+        match self {
+            Window::Consecutive => {}
+            Window::Count {
+                slots,
+                calls,
+                failures,
+                ..
+            } => {
+                for slot in slots.iter() {
+                    slot.store(0, Ordering::Release);
+                }
+                calls.store(0, Ordering::Release);
+                failures.store(0, Ordering::Release);
+            }
+            Window::Time { buckets, .. } => {
+                for bucket in buckets.iter() {
+                    bucket.epoch.store(u64::MAX, Ordering::Release);
+                    bucket.calls.store(0, Ordering::Release);
+                    bucket.failures.store(0, Ordering::Release);
+                }
+            }
+        }
+    }
+}
+
+/// Decrements `counter` without wrapping around, as a concurrent `clear` may have zeroed it.
+fn decrement(counter: &AtomicU64) {
+    let _ = counter.fetch_update(Ordering::AcqRel, Ordering::Acquire, |count| {
+        Some(count.saturating_sub(1))
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::VirtualClock;
+    use std::thread;
+
+    #[test]
+    fn test_transitions_match_the_mutex_breaker() {
+        let clock = VirtualClock::new();
+        let _guard = clock.enter();
+        let cb = LockFreeCircuitBreaker::<&str>::with_config(CircuitBreakerConfig::new(
+            2,
+            2,
+            Duration::from_secs(10),
+        ));
+        assert_eq!(
+            cb.run(|| Err::<(), _>("down")),
+            Err(CircuitBreakerError::Inner("down"))
+        );
+        assert_eq!(cb.run(|| Ok::<_, &str>(1)), Ok(1));
+        assert_eq!(cb.failure_count(), 0);
+        let _ = cb.run(|| Err::<(), _>("down"));
+        let _ = cb.run(|| Err::<(), _>("down"));
+        assert_eq!(cb.state(), CircuitBreakerState::Open);
+
+        clock.advance(Duration::from_secs(4));
+        assert_eq!(
+            cb.run(|| Ok::<_, &str>(())),
+            Err(CircuitBreakerError::Open {
+                retry_after: Duration::from_secs(6)
+            })
+        );
+        clock.advance(Duration::from_secs(6));
+        assert_eq!(
+            clock.block_on(cb.run_async(|| async { Ok::<_, &str>(()) })),
+            Ok(())
+        );
+        assert_eq!(cb.state(), CircuitBreakerState::HalfOpen);
+        let _ = cb.run(|| Err::<(), _>("down"));
+        assert_eq!(cb.state(), CircuitBreakerState::Open);
+
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(cb.run(|| Ok::<_, &str>(())), Ok(()));
+        assert_eq!(cb.run(|| Ok::<_, &str>(())), Ok(()));
+        assert_eq!(cb.state(), CircuitBreakerState::Close);
+    }
+
+    #[test]
+    fn test_sliding_windows_open_on_failure_rate_under_contention() {
+        let config = CircuitBreakerConfig::default()
+            .with_window(FailureWindow::count(100, 50.0, 100))
+            .with_cooldown_period(Duration::from_secs(60));
+        let cb = Arc::new(LockFreeCircuitBreaker::<u32>::with_config(config));
+        let workers: Vec<_> = (0..4)
+            .map(|_| {
+                let cb = Arc::clone(&cb);
+                thread::spawn(move || {
+                    for call in 0..1_000 {
+                        let _ = cb.run(|| if call % 4 == 0 { Err(call) } else { Ok(call) });
+                    }
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap();
+        }
+        assert_eq!(cb.state(), CircuitBreakerState::Close);
+
+        let config = CircuitBreakerConfig::default().with_window(FailureWindow::time(
+            Duration::from_secs(60),
+            50.0,
+            10,
+        ));
+        let cb = LockFreeCircuitBreaker::<u32>::with_config(config);
+        for call in 0..9 {
+            let _ = cb.run(|| Err::<(), _>(call));
+        }
+        assert_eq!(cb.state(), CircuitBreakerState::Close);
+        let _ = cb.run(|| Err::<(), _>(9));
+        assert_eq!(cb.state(), CircuitBreakerState::Open);
+        assert!(cb.run(|| Ok::<_, u32>(())).unwrap_err().is_open());
+    }
+}
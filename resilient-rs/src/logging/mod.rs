@@ -0,0 +1,419 @@
+use std::fmt;
+
+#[cfg(feature = "log")]
+pub use log::Level;
+
+/// The level of a diagnostic, mirroring `log::Level` when the `log` feature is disabled.
+#[cfg(not(feature = "log"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "UPPERCASE"))]
+pub enum Level {
+    Error = 1,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+#[cfg(not(feature = "log"))]
+impl Level {
+    /// Returns the name of the level, e.g. `"WARN"`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Level::Error => "ERROR",
+            Level::Warn => "WARN",
+            Level::Info => "INFO",
+            Level::Debug => "DEBUG",
+            Level::Trace => "TRACE",
+        }
+    }
+}
+
+#[cfg(not(feature = "log"))]
+impl fmt::Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad(self.as_str())
+    }
+}
+
+#[cfg(not(feature = "log"))]
+impl std::str::FromStr for Level {
+    type Err = ParseLevelError;
+
+    /// Parses a level name, ignoring case, e.g. `"warn"`.
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        [
+            Level::Error,
+            Level::Warn,
+            Level::Info,
+            Level::Debug,
+            Level::Trace,
+        ]
+        .into_iter()
+        .find(|level| level.as_str().eq_ignore_ascii_case(name))
+        .ok_or(ParseLevelError)
+    }
+}
+
+/// The error returned when parsing a `Level` from an unknown name.
+#[cfg(not(feature = "log"))]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseLevelError;
+
+#[cfg(not(feature = "log"))]
+impl fmt::Display for ParseLevelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected one of error, warn, info, debug, trace")
+    }
+}
+
+#[cfg(not(feature = "log"))]
+impl std::error::Error for ParseLevelError {}
+
+/// A message emitted by a resilience pattern, such as a retry being scheduled or a breaker
+/// opening, handed to a `Diagnostics` hook.
+#[derive(Debug, Clone, Copy)]
+pub struct Diagnostic<'a> {
+    level: Level,
+    target: &'a str,
+    args: fmt::Arguments<'a>,
+}
+
+impl<'a> Diagnostic<'a> {
+    /// Returns the level of the message.
+    pub fn level(&self) -> Level {
+        self.level
+    }
+
+    /// Returns the target of the message: the `LogConfig`'s `target`, or the emitting module's
+    /// path.
+    pub fn target(&self) -> &'a str {
+        self.target
+    }
+
+    /// Returns the message, to be formatted with `Display`.
+    pub fn args(&self) -> &fmt::Arguments<'a> {
+        &self.args
+    }
+}
+
+impl fmt::Display for Diagnostic<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.args.fmt(f)
+    }
+}
+
+/// Receives the diagnostics of the resilience patterns through a channel of the embedder's own,
+/// instead of the global `log` logger.
+///
+/// A hook is set per policy with `LogConfig::with_diagnostics`. Games, plugins hosted in
+/// another process's logger, or builds without the `log` feature can use it to route retry and
+/// breaker messages to an in-game console, a host API or a ring buffer. The hook is called
+/// synchronously on the thread emitting the message, so it must be cheap.
+///
+/// Any `Fn(&Diagnostic<'_>) + Send + Sync` closure is a `Diagnostics` hook.
+///
+/// # Example
+/// ```
+/// use std::sync::Mutex;
+/// use resilient_rs::config::LogConfig;
+/// use resilient_rs::logging::{Diagnostic, Diagnostics};
+///
+/// struct Console(Mutex<Vec<String>>);
+///
+/// impl Diagnostics for Console {
+///     fn record(&self, diagnostic: &Diagnostic<'_>) {
+///         let line = format!("[{}] {}", diagnostic.level(), diagnostic);
+///         self.0.lock().unwrap().push(line);
+///     }
+/// }
+///
+/// static CONSOLE: Console = Console(Mutex::new(Vec::new()));
+/// let log = LogConfig::default().with_diagnostics(&CONSOLE);
+/// assert!(log.diagnostics.is_some());
+/// ```
+pub trait Diagnostics: Send + Sync {
+    /// Handles one diagnostic.
+    fn record(&self, diagnostic: &Diagnostic<'_>);
+}
+
+impl<F> Diagnostics for F
+where
+    F: Fn(&Diagnostic<'_>) + Send + Sync,
+{
+    fn record(&self, diagnostic: &Diagnostic<'_>) {
+        self(diagnostic)
+    }
+}
+
+/// Hands a message to a `Diagnostics` hook; used by `log_with!`.
+pub(crate) fn emit(
+    diagnostics: &dyn Diagnostics,
+    level: Level,
+    target: &str,
+    args: fmt::Arguments<'_>,
+) {
+    diagnostics.record(&Diagnostic {
+        level,
+        target,
+        args,
+    });
+}
+
+/// Logs a message through the `log` facade, honoring a `LogConfig`.
+///
+/// The message is suppressed entirely when the config is `quiet`, and it is emitted under the
+/// config's custom `target` when one is set (the calling module path otherwise). When the config
+/// has a `Diagnostics` hook, the message goes to the hook instead of `log`; without the `log`
+/// feature, messages without a hook are dropped.
+///
+/// Structured fields can be given in braces before the message. With the `kv` feature they are
+/// attached to the record through `log`'s key-value API, along with a `policy_name` field holding
+/// the config's `target`, so log pipelines can index them; without it, or with a `Diagnostics`
+/// hook, only the message is logged.
+///
+/// # Usage
+/// `log_with!(config.log, level, "format string", args...)`
+/// `log_with!(config.log, level, { attempt = n, error_class:? = class }; "format string", args...)`
+macro_rules! log_with {
+    ($log:expr, $level:expr, { $($key:ident $(:$capture:tt)? = $value:expr),+ $(,)? }; $($arg:tt)+) => {{
+        let log_config: &$crate::config::LogConfig = &$log;
+        if !log_config.quiet {
+            if let Some(diagnostics) = log_config.diagnostics {
+                $crate::logging::emit(
+                    diagnostics,
+                    $level,
+                    log_config.target.unwrap_or(module_path!()),
+                    format_args!($($arg)+),
+                );
+            } else {
+                #[cfg(feature = "kv")]
+                log::log!(
+                    target: log_config.target.unwrap_or(module_path!()),
+                    $level,
+                    policy_name = log_config.target,
+                    $($key $(:$capture)? = $value),+;
+                    $($arg)+
+                );
+                #[cfg(all(feature = "log", not(feature = "kv")))]
+                log::log!(
+                    target: log_config.target.unwrap_or(module_path!()),
+                    $level,
+                    $($arg)+
+                );
+            }
+        }
+    }};
+    ($log:expr, $level:expr, $($arg:tt)+) => {{
+        let log_config: &$crate::config::LogConfig = &$log;
+        if !log_config.quiet {
+            if let Some(diagnostics) = log_config.diagnostics {
+                $crate::logging::emit(
+                    diagnostics,
+                    $level,
+                    log_config.target.unwrap_or(module_path!()),
+                    format_args!($($arg)+),
+                );
+            } else {
+                #[cfg(feature = "log")]
+                log::log!(
+                    target: log_config.target.unwrap_or(module_path!()),
+                    $level,
+                    $($arg)+
+                );
+            }
+        }
+    }};
+}
+
+/// Logs the routine message of a retry attempt, honoring the `LogConfig`'s sampling.
+///
+/// # Usage
+/// `log_sampled!(config.log, attempt, level, "format string", args...)`
+macro_rules! log_sampled {
+    ($log:expr, $attempt:expr, $level:expr, $($arg:tt)+) => {{
+        let log_config: &$crate::config::LogConfig = &$log;
+        if log_config.samples($attempt) {
+            $crate::logging::log_with!(*log_config, $level, $($arg)+);
+        }
+    }};
+}
+
+pub(crate) use log_sampled;
+pub(crate) use log_with;
+
+#[cfg(all(test, feature = "log"))]
+mod tests {
+    use crate::config::LogConfig;
+    use log::{Level, LevelFilter, Log, Metadata, Record};
+    use std::sync::{Mutex, Once};
+
+    struct CapturingLogger;
+
+    static RECORDS: Mutex<Vec<(String, Level, String)>> = Mutex::new(Vec::new());
+    static INIT: Once = Once::new();
+    #[cfg(feature = "kv")]
+    type KeyValues = Vec<(String, String)>;
+    #[cfg(feature = "kv")]
+    static KEY_VALUES: Mutex<Vec<(String, KeyValues)>> = Mutex::new(Vec::new());
+
+    #[cfg(feature = "kv")]
+    struct CollectingVisitor(KeyValues);
+
+    #[cfg(feature = "kv")]
+    impl<'kvs> log::kv::VisitSource<'kvs> for CollectingVisitor {
+        fn visit_pair(
+            &mut self,
+            key: log::kv::Key<'kvs>,
+            value: log::kv::Value<'kvs>,
+        ) -> Result<(), log::kv::Error> {
+            self.0.push((key.to_string(), value.to_string()));
+            Ok(())
+        }
+    }
+
+    impl Log for CapturingLogger {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            RECORDS.lock().unwrap().push((
+                record.target().to_string(),
+                record.level(),
+                record.args().to_string(),
+            ));
+            #[cfg(feature = "kv")]
+            {
+                let mut visitor = CollectingVisitor(Vec::new());
+                record.key_values().visit(&mut visitor).unwrap();
+                KEY_VALUES
+                    .lock()
+                    .unwrap()
+                    .push((record.target().to_string(), visitor.0));
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    fn records_for(target: &str) -> Vec<(Level, String)> {
+        RECORDS
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(t, _, _)| t == target)
+            .map(|(_, level, message)| (*level, message.clone()))
+            .collect()
+    }
+
+    fn init() {
+        INIT.call_once(|| {
+            log::set_logger(&CapturingLogger).unwrap();
+            log::set_max_level(LevelFilter::Trace);
+        });
+    }
+
+    #[test]
+    fn test_custom_target_and_level() {
+        init();
+        let config = LogConfig::new(Level::Debug).with_target("logging-test-target");
+        log_with!(config, config.level, "retrying after {}ms", 10);
+        assert_eq!(
+            records_for("logging-test-target"),
+            vec![(Level::Debug, "retrying after 10ms".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_sampling_logs_first_then_one_in_n() {
+        init();
+        let config = LogConfig::new(Level::Info)
+            .with_target("logging-test-sampled")
+            .with_sampling(3);
+        for attempt in 1..=7 {
+            log_sampled!(config, attempt, config.level, "attempt {}", attempt);
+        }
+        let messages: Vec<_> = records_for("logging-test-sampled")
+            .into_iter()
+            .map(|(_, message)| message)
+            .collect();
+        assert_eq!(messages, ["attempt 1", "attempt 4", "attempt 7"]);
+    }
+
+    #[test]
+    #[cfg(feature = "kv")]
+    fn test_structured_fields_are_attached_as_key_values() {
+        init();
+        let config = LogConfig::default().with_target("logging-test-kv");
+        let class = crate::classifier::ErrorClass::Transient;
+        log_with!(
+            config,
+            Level::Warn,
+            { attempt = 2, delay_ms = 250u64, error_class:? = class };
+            "Operation failed (attempt {}), retrying after 250ms",
+            2
+        );
+        let key_values: Vec<_> = KEY_VALUES
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(target, _)| target == "logging-test-kv")
+            .flat_map(|(_, key_values)| key_values.clone())
+            .collect();
+        let expected = [
+            ("policy_name", "logging-test-kv"),
+            ("attempt", "2"),
+            ("delay_ms", "250"),
+            ("error_class", "Transient"),
+        ];
+        assert_eq!(
+            key_values,
+            expected.map(|(key, value)| (key.to_string(), value.to_string()))
+        );
+        assert_eq!(
+            records_for("logging-test-kv"),
+            vec![(
+                Level::Warn,
+                "Operation failed (attempt 2), retrying after 250ms".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_diagnostics_hook_replaces_the_logger() {
+        init();
+        static SEEN: Mutex<Vec<(String, Level, String)>> = Mutex::new(Vec::new());
+        fn hook(diagnostic: &super::Diagnostic<'_>) {
+            SEEN.lock().unwrap().push((
+                diagnostic.target().to_string(),
+                diagnostic.level(),
+                diagnostic.to_string(),
+            ));
+        }
+        let config = LogConfig::new(Level::Info)
+            .with_target("logging-test-diagnostics")
+            .with_diagnostics(&hook);
+        log_with!(config, config.level, { attempt = 3 }; "retry {} scheduled", 3);
+        log_with!(config, Level::Error, "gave up");
+
+        assert!(records_for("logging-test-diagnostics").is_empty());
+        let target = "logging-test-diagnostics".to_string();
+        assert_eq!(
+            *SEEN.lock().unwrap(),
+            [
+                (target.clone(), Level::Info, "retry 3 scheduled".to_string()),
+                (target, Level::Error, "gave up".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_quiet_suppresses_everything() {
+        init();
+        let config = LogConfig::quiet().with_target("logging-test-quiet");
+        log_with!(config, Level::Error, "should not be logged");
+        assert!(records_for("logging-test-quiet").is_empty());
+    }
+}
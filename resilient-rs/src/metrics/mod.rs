@@ -0,0 +1,23 @@
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Crate-wide counters updated by every resilience pattern.
+///
+/// Unlike the opt-in `Stats` handle, these counters are always maintained (a relaxed atomic
+/// increment per event) so that exposition helpers such as the `prometheus` module can report
+/// on all policies in the process without any per-config wiring.
+pub(crate) static RETRIES: AtomicU64 = AtomicU64::new(0);
+pub(crate) static GIVE_UPS: AtomicU64 = AtomicU64::new(0);
+pub(crate) static BREAKER_OPENS: AtomicU64 = AtomicU64::new(0);
+pub(crate) static BREAKER_REJECTIONS: AtomicU64 = AtomicU64::new(0);
+pub(crate) static TIMEOUTS: AtomicU64 = AtomicU64::new(0);
+pub(crate) static FALLBACKS: AtomicU64 = AtomicU64::new(0);
+pub(crate) static BULKHEAD_REJECTIONS: AtomicU64 = AtomicU64::new(0);
+pub(crate) static RATE_LIMIT_REJECTIONS: AtomicU64 = AtomicU64::new(0);
+pub(crate) static LOAD_SHED: AtomicU64 = AtomicU64::new(0);
+pub(crate) static STALE_RESPONSES: AtomicU64 = AtomicU64::new(0);
+pub(crate) static CANCELLATIONS: AtomicU64 = AtomicU64::new(0);
+
+/// Increments one of the crate-wide counters.
+pub(crate) fn increment(counter: &AtomicU64) {
+    counter.fetch_add(1, Ordering::Relaxed);
+}
@@ -0,0 +1,374 @@
+use crate::asynchronous;
+use crate::config::RetryConfig;
+use crate::events::{self, ResilienceEvent, SubscriptionId};
+use crate::synchronous;
+use opentelemetry::context::FutureExt;
+use opentelemetry::metrics::{Counter, Histogram, Meter};
+use opentelemetry::trace::{Status, TraceContextExt, Tracer};
+use opentelemetry::{Context, KeyValue};
+use std::fmt::Display;
+
+/// The OpenTelemetry instruments updated from the crate's events.
+struct Instruments {
+    attempts: Counter<u64>,
+    retries: Counter<u64>,
+    retry_delay: Histogram<f64>,
+    give_ups: Counter<u64>,
+    breaker_transitions: Counter<u64>,
+    rejections: Counter<u64>,
+    timeouts: Counter<u64>,
+    fallbacks: Counter<u64>,
+}
+
+/// Reports the crate's `ResilienceEvent`s to OpenTelemetry.
+///
+/// Every event updates one of the following instruments of `meter`, and is added as a span event
+/// to the span active where it was emitted, if any:
+/// - `resilient.attempts`: attempts started by the retry loops.
+/// - `resilient.retries` and `resilient.retry.delay` (seconds): retries scheduled and their
+///   delays.
+/// - `resilient.give_ups`: retry loops that gave up.
+/// - `resilient.circuit_breaker.transitions`: breaker transitions, by `state` (`open`,
+///   `half_open`, `closed`, `forced_open`, `disabled`).
+/// - `resilient.rejections`: rejected calls, by `reason` (`circuit_open`, `bulkhead_full`,
+///   `rate_limited`, `load_shed`).
+/// - `resilient.timeouts` and `resilient.fallbacks`: timeouts hit and fallbacks used.
+///
+/// Like every event subscriber, the instrumentation observes every pattern of the process.
+///
+/// # Arguments
+/// * `meter` - The meter creating the instruments, e.g. `opentelemetry::global::meter("resilient-rs")`.
+///
+/// # Returns
+/// The `SubscriptionId` of the instrumentation, which `events::unsubscribe` removes.
+///
+/// # Example
+/// ```
+/// use resilient_rs::events::unsubscribe;
+/// use resilient_rs::otel;
+///
+/// let instrumentation = otel::install(&opentelemetry::global::meter("resilient-rs"));
+/// unsubscribe(instrumentation);
+/// ```
+pub fn install(meter: &Meter) -> SubscriptionId {
+    let instruments = Instruments {
+        attempts: meter
+            .u64_counter("resilient.attempts")
+            .with_description("Attempts started by the retry loops.")
+            .build(),
+        retries: meter
+            .u64_counter("resilient.retries")
+            .with_description("Retries scheduled after a failed attempt.")
+            .build(),
+        retry_delay: meter
+            .f64_histogram("resilient.retry.delay")
+            .with_description("Delays waited before a retry.")
+            .with_unit("s")
+            .build(),
+        give_ups: meter
+            .u64_counter("resilient.give_ups")
+            .with_description("Operations abandoned after exhausting or failing retries.")
+            .build(),
+        breaker_transitions: meter
+            .u64_counter("resilient.circuit_breaker.transitions")
+            .with_description("Circuit breaker state transitions.")
+            .build(),
+        rejections: meter
+            .u64_counter("resilient.rejections")
+            .with_description("Calls rejected without running the operation.")
+            .build(),
+        timeouts: meter
+            .u64_counter("resilient.timeouts")
+            .with_description("Operations that exceeded their timeout.")
+            .build(),
+        fallbacks: meter
+            .u64_counter("resilient.fallbacks")
+            .with_description("Fallback invocations.")
+            .build(),
+    };
+    events::on_event(move |event| record(&instruments, event))
+}
+
+fn record(instruments: &Instruments, event: &ResilienceEvent) {
+    let (name, attributes) = match event {
+        ResilienceEvent::AttemptStarted { attempt } => {
+            instruments.attempts.add(1, &[]);
+            ("attempt_started", vec![attempt_attribute(*attempt)])
+        }
+        ResilienceEvent::RetryScheduled { attempt, delay } => {
+            instruments.retries.add(1, &[]);
+            instruments.retry_delay.record(delay.as_secs_f64(), &[]);
+            (
+                "retry_scheduled",
+                vec![
+                    attempt_attribute(*attempt),
+                    KeyValue::new("resilience.delay_ms", delay.as_millis() as i64),
+                ],
+            )
+        }
+        ResilienceEvent::GaveUp { attempts } => {
+            instruments.give_ups.add(1, &[]);
+            ("gave_up", vec![attempt_attribute(*attempts)])
+        }
+        ResilienceEvent::BreakerOpened => transition(instruments, "open"),
+        ResilienceEvent::BreakerHalfOpened => transition(instruments, "half_open"),
+        ResilienceEvent::BreakerClosed => transition(instruments, "closed"),
+        ResilienceEvent::BreakerForcedOpen => transition(instruments, "forced_open"),
+        ResilienceEvent::BreakerDisabled => transition(instruments, "disabled"),
+        ResilienceEvent::CallRejected => rejection(instruments, "circuit_open"),
+        ResilienceEvent::BulkheadRejected => rejection(instruments, "bulkhead_full"),
+        ResilienceEvent::RateLimited { .. } => rejection(instruments, "rate_limited"),
+        ResilienceEvent::LoadShed => rejection(instruments, "load_shed"),
+        ResilienceEvent::TimeoutHit { timeout } => {
+            instruments.timeouts.add(1, &[]);
+            (
+                "timeout",
+                vec![KeyValue::new(
+                    "resilience.timeout_ms",
+                    timeout.as_millis() as i64,
+                )],
+            )
+        }
+        ResilienceEvent::FallbackUsed => {
+            instruments.fallbacks.add(1, &[]);
+            ("fallback_used", Vec::new())
+        }
+        ResilienceEvent::StaleServed { .. } => ("stale_served", Vec::new()),
+        ResilienceEvent::AttemptCancelled { .. } => ("attempt_cancelled", Vec::new()),
+    };
+    let cx = Context::current();
+    if cx.has_active_span() {
+        cx.span().add_event(name, attributes);
+    }
+}
+
+fn transition(instruments: &Instruments, state: &'static str) -> (&'static str, Vec<KeyValue>) {
+    let attributes = vec![KeyValue::new("state", state)];
+    instruments.breaker_transitions.add(1, &attributes);
+    ("circuit_breaker_transition", attributes)
+}
+
+fn rejection(instruments: &Instruments, reason: &'static str) -> (&'static str, Vec<KeyValue>) {
+    let attributes = vec![KeyValue::new("reason", reason)];
+    instruments.rejections.add(1, &attributes);
+    ("call_rejected", attributes)
+}
+
+fn attempt_attribute(attempt: usize) -> KeyValue {
+    KeyValue::new("resilience.attempt", attempt as i64)
+}
+
+/// Records the outcome of an attempt on its span and ends the span.
+fn end_attempt<T, E: Display>(cx: &Context, result: &Result<T, E>) {
+    let span = cx.span();
+    match result {
+        Ok(_) => {
+            span.set_attribute(KeyValue::new("resilience.outcome", "success"));
+            span.set_status(Status::Ok);
+        }
+        Err(err) => {
+            span.set_attribute(KeyValue::new("resilience.outcome", "failure"));
+            span.set_status(Status::error(err.to_string()));
+        }
+    }
+    span.end();
+}
+
+/// Starts the span of an attempt as a child of `parent`.
+fn start_attempt<Tr>(tracer: &Tr, name: &str, parent: &Context, attempt: usize) -> Context
+where
+    Tr: Tracer,
+    Tr::Span: Send + Sync + 'static,
+{
+    let cx = parent.with_span(tracer.start_with_context(name.to_string(), parent));
+    cx.span().set_attribute(attempt_attribute(attempt));
+    cx
+}
+
+/// Retries an asynchronous operation like `asynchronous::retry`, tracing every attempt as a
+/// child span of the current OpenTelemetry context.
+///
+/// The context current when the returned future is first polled (typically attached with
+/// `opentelemetry::context::FutureExt::with_context`) is the parent of one span per attempt,
+/// named `name`, with a `resilience.attempt` attribute and a `resilience.outcome` attribute
+/// (`success` or `failure`); failed attempts also get an error status with the error message.
+/// Each attempt runs with its span as the current context, so the spans of the calls it makes,
+/// and the trace headers it propagates, descend from the attempt.
+///
+/// # Arguments
+/// * `tracer` - The tracer creating the attempt spans.
+/// * `name` - The name of the attempt spans.
+/// * `operation` - The fallible asynchronous operation to retry.
+/// * `retry_config` - The retry configuration.
+///
+/// # Returns
+/// The result of the last attempt, as `asynchronous::retry` returns it.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use async_std::task::block_on;
+/// use opentelemetry::context::FutureExt;
+/// use opentelemetry::trace::{TraceContextExt, Tracer};
+/// use opentelemetry::{Context, global};
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::otel;
+/// use resilient_rs::strategies::RetryStrategy;
+///
+/// let tracer = global::tracer("inventory");
+/// let request = Context::current_with_span(tracer.start("GET /stock"));
+/// let config = RetryConfig::new(3, Duration::from_millis(1), RetryStrategy::Linear);
+/// let result: Result<u32, String> = block_on(
+///     otel::retry(&tracer, "fetch stock", || async { Ok(42) }, &config).with_context(request),
+/// );
+/// assert_eq!(result, Ok(42));
+/// ```
+pub async fn retry<Tr, F, Fut, T, E>(
+    tracer: &Tr,
+    name: &str,
+    mut operation: F,
+    retry_config: &RetryConfig<E>,
+) -> Result<T, E>
+where
+    Tr: Tracer,
+    Tr::Span: Send + Sync + 'static,
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: Display,
+{
+    let parent = Context::current();
+    let mut attempt = 0;
+    asynchronous::retry(
+        || {
+            attempt += 1;
+            let cx = start_attempt(tracer, name, &parent, attempt);
+            let future = {
+                let _guard = cx.clone().attach();
+                operation()
+            };
+            async move {
+                let result = future.with_context(cx.clone()).await;
+                end_attempt(&cx, &result);
+                result
+            }
+        },
+        retry_config,
+    )
+    .await
+}
+
+/// Retries a blocking operation like `synchronous::retry`, tracing every attempt as a child span
+/// of the current OpenTelemetry context.
+///
+/// The spans are the same as those of `otel::retry`; each attempt runs with its span attached
+/// as the current context.
+///
+/// # Arguments
+/// * `tracer` - The tracer creating the attempt spans.
+/// * `name` - The name of the attempt spans.
+/// * `operation` - The fallible operation to retry.
+/// * `retry_config` - The retry configuration.
+///
+/// # Returns
+/// The result of the last attempt, as `synchronous::retry` returns it.
+pub fn retry_blocking<Tr, F, T, E>(
+    tracer: &Tr,
+    name: &str,
+    mut operation: F,
+    retry_config: &RetryConfig<E>,
+) -> Result<T, E>
+where
+    Tr: Tracer,
+    Tr::Span: Send + Sync + 'static,
+    F: FnMut() -> Result<T, E>,
+    E: Display,
+{
+    let parent = Context::current();
+    let mut attempt = 0;
+    synchronous::retry(
+        || {
+            attempt += 1;
+            let cx = start_attempt(tracer, name, &parent, attempt);
+            let result = {
+                let _guard = cx.clone().attach();
+                operation()
+            };
+            end_attempt(&cx, &result);
+            result
+        },
+        retry_config,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::RetryStrategy;
+    use opentelemetry::Value;
+    use opentelemetry::trace::TracerProvider;
+    use opentelemetry_sdk::trace::{InMemorySpanExporter, SdkTracerProvider};
+    use std::time::Duration;
+
+    #[test]
+    fn test_attempts_are_child_spans_with_their_outcome() {
+        let exporter = InMemorySpanExporter::default();
+        let provider = SdkTracerProvider::builder()
+            .with_simple_exporter(exporter.clone())
+            .build();
+        let tracer = provider.tracer("test");
+        let instrumentation = install(&opentelemetry::global::meter("test"));
+
+        let request = Context::current_with_span(tracer.start("request"));
+        let config = RetryConfig::new(3, Duration::from_millis(1), RetryStrategy::Linear);
+        let mut calls = 0;
+        let result = async_std::task::block_on(
+            retry(
+                &tracer,
+                "fetch",
+                || {
+                    calls += 1;
+                    let result = if calls == 1 {
+                        Err("refused")
+                    } else {
+                        Ok(calls)
+                    };
+                    async move { result }
+                },
+                &config,
+            )
+            .with_context(request.clone()),
+        );
+        request.span().end();
+        events::unsubscribe(instrumentation);
+        assert_eq!(result, Ok(2));
+
+        let spans = exporter.get_finished_spans().unwrap();
+        let parent = spans.iter().find(|span| span.name == "request").unwrap();
+        let attempts: Vec<_> = spans.iter().filter(|span| span.name == "fetch").collect();
+        assert_eq!(attempts.len(), 2);
+        let attribute = |span: &opentelemetry_sdk::trace::SpanData, key: &str| {
+            span.attributes
+                .iter()
+                .find(|kv| kv.key.as_str() == key)
+                .map(|kv| kv.value.clone())
+        };
+        for (attempt, outcome) in attempts.iter().zip(["failure", "success"]) {
+            assert_eq!(
+                attempt.parent_span_id,
+                parent.span_context.span_id(),
+                "attempts descend from the request"
+            );
+            assert_eq!(
+                attribute(attempt, "resilience.outcome"),
+                Some(Value::from(outcome))
+            );
+        }
+        assert_eq!(attempts[0].status, Status::error("refused"));
+        assert!(
+            parent
+                .events
+                .iter()
+                .any(|event| event.name == "retry_scheduled")
+        );
+    }
+}
@@ -0,0 +1,450 @@
+use crate::asynchronous::{Bulkhead, back_off, give_up_on_open, schedule_class_retry};
+use crate::breaker::{self, BreakerCore, CircuitBreakerError, CircuitBreakerState, lock};
+use crate::bulkhead::BulkheadError;
+use crate::classifier::ErrorClass;
+use crate::config::{BulkheadConfig, CircuitBreakerConfig, LogConfig, RetryConfig};
+use crate::events::{self, ResilienceEvent};
+use crate::logging::log_with;
+use crate::metrics;
+use crate::ratelimit::{RateLimited, RateLimiter};
+use crate::time::{self, timeout};
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The error returned by `Pipeline::execute`.
+///
+/// It names the stage of the pipeline that failed the call, so callers can branch on rejections
+/// without inspecting the operation's error.
+#[derive(Debug, PartialEq)]
+pub enum PipelineError<E> {
+    /// The last attempt exceeded the pipeline's timeout.
+    Timeout { timeout: Duration },
+    /// The call was rejected because the circuit breaker is open; `retry_after` is the remaining
+    /// cooldown.
+    CircuitOpen { retry_after: Duration },
+    /// The call was rejected because `max_concurrent_calls` calls were running through the
+    /// circuit breaker.
+    CircuitSaturated { max_concurrent_calls: usize },
+    /// The call was rejected because the bulkhead was full.
+    BulkheadFull,
+    /// The call was rejected by the rate limiter; `retry_after` is the time until it admits a
+    /// call again.
+    RateLimited { retry_after: Duration },
+    /// The operation, or the fallback, failed with this error.
+    Inner(E),
+}
+
+impl<E> PipelineError<E> {
+    /// Returns `true` if the call was rejected by the circuit breaker, the bulkhead or the rate
+    /// limiter without running the operation.
+    pub fn is_rejected(&self) -> bool {
+        matches!(
+            self,
+            PipelineError::CircuitOpen { .. }
+                | PipelineError::CircuitSaturated { .. }
+                | PipelineError::BulkheadFull
+                | PipelineError::RateLimited { .. }
+        )
+    }
+
+    /// Returns `true` if the last attempt timed out.
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, PipelineError::Timeout { .. })
+    }
+
+    /// Returns the error of the operation, or `None` if the call was rejected or timed out.
+    pub fn into_inner(self) -> Option<E> {
+        match self {
+            PipelineError::Inner(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for PipelineError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PipelineError::Timeout { timeout } => {
+                write!(f, "Operation timed out after {:?}", timeout)
+            }
+            PipelineError::CircuitOpen { .. } => {
+                write!(f, "Circuit Breaker is open. Please try later..!")
+            }
+            PipelineError::CircuitSaturated {
+                max_concurrent_calls,
+            } => write!(
+                f,
+                "Circuit Breaker is at its limit of {} concurrent calls. Please try later..!",
+                max_concurrent_calls
+            ),
+            PipelineError::BulkheadFull => write!(f, "Bulkhead is full. Please try later..!"),
+            PipelineError::RateLimited { retry_after } => {
+                write!(
+                    f,
+                    "{}",
+                    RateLimited {
+                        retry_after: *retry_after
+                    }
+                )
+            }
+            PipelineError::Inner(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> Error for PipelineError<E> {}
+
+impl<E> From<CircuitBreakerError<PipelineError<E>>> for PipelineError<E> {
+    fn from(err: CircuitBreakerError<PipelineError<E>>) -> Self {
+        match err {
+            CircuitBreakerError::Open { retry_after } => PipelineError::CircuitOpen { retry_after },
+            CircuitBreakerError::Saturated {
+                max_concurrent_calls,
+            } => PipelineError::CircuitSaturated {
+                max_concurrent_calls,
+            },
+            CircuitBreakerError::Inner(err) => err,
+        }
+    }
+}
+
+/// A function producing the result of a call once every stage of a `Pipeline` failed.
+///
+/// It receives the final error, so it can e.g. serve a default only when the circuit is open.
+pub type PipelineFallback<T, E> = fn(&PipelineError<E>) -> Result<T, E>;
+
+/// A single executor combining a timeout, retries, a rate limiter, a circuit breaker, a bulkhead
+/// and a fallback.
+///
+/// The stages always run in the same order, whatever the order of the builder calls, from the
+/// outermost to the innermost:
+/// 1. **Fallback**: runs once every other stage gave up.
+/// 2. **Retry**: retries the attempts that failed with a retryable error or timed out. Calls
+///    rejected by the rate limiter, the breaker or the bulkhead are not retried, and the loop
+///    gives up as soon as an attempt opens the circuit. Backoff delays do not hold a bulkhead
+///    slot.
+/// 3. **Rate limiter**: admits every attempt, retries included, against the dependency's quota.
+/// 4. **Bulkhead**: caps the number of concurrent attempts; waiting for a slot does not count
+///    against the timeout, and rejections do not count as breaker failures.
+/// 5. **Circuit breaker**: rejects attempts while open, and records timeouts and errors of the
+///    operation as failures.
+/// 6. **Timeout**: bounds every attempt of the operation.
+///
+/// Each stage is optional. The pipeline owns the state of its circuit breaker and bulkhead, so
+/// share one pipeline (e.g. through an `Arc`) between all the calls to a dependency.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use async_std::task::block_on;
+/// use resilient_rs::config::{BulkheadConfig, CircuitBreakerConfig, RetryConfig};
+/// use resilient_rs::pipeline::{Pipeline, PipelineError};
+///
+/// let pipeline = Pipeline::new()
+///     .with_timeout(Duration::from_millis(200))
+///     .with_retry(RetryConfig::default().with_max_attempts(2).with_delay(Duration::from_millis(10)))
+///     .with_circuit_breaker(CircuitBreakerConfig::new(1, 2, Duration::from_secs(30)))
+///     .with_bulkhead(BulkheadConfig::new(10))
+///     .with_fallback(|err| match err {
+///         PipelineError::CircuitOpen { .. } => Ok("cached profile"),
+///         _ => Err("profile service unavailable"),
+///     });
+///
+/// // Both attempts fail and open the circuit; later calls are served by the fallback.
+/// let first = block_on(pipeline.execute(|| async { Err::<&str, _>("connection refused") }));
+/// assert_eq!(first, Err(PipelineError::Inner("profile service unavailable")));
+/// let second = block_on(pipeline.execute(|| async { Ok("fresh profile") }));
+/// assert_eq!(second, Ok("cached profile"));
+/// ```
+pub struct Pipeline<T, E> {
+    timeout: Option<Duration>,
+    retry: Option<RetryConfig<E>>,
+    limiter: Option<Arc<dyn RateLimiter>>,
+    breaker: Option<Mutex<BreakerCore<PipelineError<E>>>>,
+    bulkhead: Option<Bulkhead>,
+    fallback: Option<PipelineFallback<T, E>>,
+    log: LogConfig,
+}
+
+impl<T, E> Default for Pipeline<T, E> {
+    fn default() -> Self {
+        Pipeline {
+            timeout: None,
+            retry: None,
+            limiter: None,
+            breaker: None,
+            bulkhead: None,
+            fallback: None,
+            log: LogConfig::default(),
+        }
+    }
+}
+
+impl<T, E> Pipeline<T, E> {
+    /// Creates a pipeline without any stage, executing the operation once.
+    pub fn new() -> Self {
+        Pipeline::default()
+    }
+
+    /// Bounds every attempt by `timeout`, and returns the modified pipeline.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Retries failed attempts according to `retry_config`, and returns the modified pipeline.
+    ///
+    /// The errors of the operation are classified by the `retry_config`; timed out attempts are
+    /// retried as `Transient` failures.
+    pub fn with_retry(mut self, retry_config: RetryConfig<E>) -> Self {
+        self.retry = Some(retry_config);
+        self
+    }
+
+    /// Admits the attempts through `limiter`, and returns the modified pipeline.
+    ///
+    /// The limiter can be shared with other pipelines calling the same dependency.
+    pub fn with_rate_limiter(mut self, limiter: Arc<dyn RateLimiter>) -> Self {
+        self.limiter = Some(limiter);
+        self
+    }
+
+    /// Supervises the attempts with a circuit breaker created from `config`, and returns the
+    /// modified pipeline.
+    pub fn with_circuit_breaker(mut self, config: CircuitBreakerConfig) -> Self {
+        self.breaker = Some(Mutex::new(BreakerCore::new(config)));
+        self
+    }
+
+    /// Caps the concurrent attempts with a bulkhead created from `config`, and returns the
+    /// modified pipeline.
+    pub fn with_bulkhead(mut self, config: BulkheadConfig) -> Self {
+        self.bulkhead = Some(Bulkhead::new(config));
+        self
+    }
+
+    /// Produces the result with `fallback` when the other stages fail, and returns the modified
+    /// pipeline.
+    pub fn with_fallback(mut self, fallback: PipelineFallback<T, E>) -> Self {
+        self.fallback = Some(fallback);
+        self
+    }
+
+    /// Sets the logging behavior for timeouts and fallbacks, and returns the modified pipeline.
+    ///
+    /// The retry, circuit breaker and bulkhead stages log through their own configurations.
+    pub fn with_log(mut self, log: LogConfig) -> Self {
+        self.log = log;
+        self
+    }
+
+    /// Returns the state of the pipeline's circuit breaker, or `None` if it has none.
+    pub fn circuit_state(&self) -> Option<CircuitBreakerState> {
+        self.breaker.as_ref().map(|breaker| lock(breaker).state())
+    }
+
+    /// Executes an asynchronous operation through every stage of the pipeline.
+    ///
+    /// # Arguments
+    /// * `operation` - A closure returning a `Future` resolving to a `Result<T, E>`, called once
+    ///   per attempt.
+    ///
+    /// # Returns
+    /// - `Ok(T)` if an attempt, or the fallback, succeeds.
+    /// - `Err(PipelineError)` naming the stage that failed the call otherwise, or
+    ///   `PipelineError::Inner` with the error of the fallback.
+    pub async fn execute<F, Fut>(&self, mut operation: F) -> Result<T, PipelineError<E>>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: fmt::Display,
+    {
+        let err = match self.retry_attempts(&mut operation).await {
+            Ok(output) => return Ok(output),
+            Err(err) => err,
+        };
+        let Some(fallback) = self.fallback else {
+            return Err(err);
+        };
+        log_with!(
+            self.log,
+            self.log.level,
+            "Pipeline failed with {}; executing fallback.",
+            err
+        );
+        metrics::increment(&metrics::FALLBACKS);
+        events::emit(ResilienceEvent::FallbackUsed);
+        fallback(&err).map_err(PipelineError::Inner)
+    }
+
+    async fn retry_attempts<F, Fut>(&self, operation: &mut F) -> Result<T, PipelineError<E>>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: fmt::Display,
+    {
+        let Some(retry_config) = &self.retry else {
+            return self.attempt(operation).await;
+        };
+        let mut attempts = 0;
+        let mut delay = retry_config.delay;
+
+        loop {
+            events::emit(ResilienceEvent::AttemptStarted {
+                attempt: attempts + 1,
+            });
+            let start = time::now();
+            let result = self.attempt(operation).await;
+            retry_config.record(|stats| stats.record_attempt(time::elapsed(start)));
+            let class = match &result {
+                Ok(_) => {
+                    retry_config.record(|stats| stats.record_success(attempts > 0));
+                    return result;
+                }
+                Err(PipelineError::Inner(err)) => retry_config.classify(err),
+                Err(PipelineError::Timeout { .. }) => ErrorClass::Transient,
+                Err(_) => return result,
+            };
+            if self.circuit_state() == Some(CircuitBreakerState::Open) {
+                give_up_on_open(retry_config, attempts + 1);
+                return result;
+            }
+            let Some(wait) = schedule_class_retry(retry_config, class, attempts + 1, delay) else {
+                return result;
+            };
+            back_off(None, retry_config.timer_wheel.as_ref(), wait).await;
+            delay = retry_config.next_delay(delay, attempts + 1);
+            attempts += 1;
+        }
+    }
+
+    /// Runs one attempt through the rate limiter, the bulkhead, the circuit breaker and the
+    /// timeout.
+    async fn attempt<F, Fut>(&self, operation: &mut F) -> Result<T, PipelineError<E>>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: fmt::Display,
+    {
+        if let Some(limiter) = &self.limiter
+            && let Err(RateLimited { retry_after }) = limiter.try_acquire()
+        {
+            return Err(PipelineError::RateLimited { retry_after });
+        }
+        let Some(bulkhead) = &self.bulkhead else {
+            return self.supervised(operation).await;
+        };
+        match bulkhead.call(|| self.supervised(operation)).await {
+            Ok(output) => Ok(output),
+            Err(BulkheadError::Full) => Err(PipelineError::BulkheadFull),
+            Err(BulkheadError::Inner(err)) => Err(err),
+        }
+    }
+
+    async fn supervised<F, Fut>(&self, operation: &mut F) -> Result<T, PipelineError<E>>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: fmt::Display,
+    {
+        let Some(breaker) = &self.breaker else {
+            return self.timed(operation).await;
+        };
+        let permit = breaker::acquire(breaker)?;
+        let start = time::now();
+        let result = self.timed(operation).await;
+        let generation = permit.finish();
+        Ok(breaker::complete(
+            breaker,
+            generation,
+            result,
+            time::elapsed(start),
+        )?)
+    }
+
+    async fn timed<F, Fut>(&self, operation: &mut F) -> Result<T, PipelineError<E>>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let Some(duration) = self.timeout else {
+            return operation().await.map_err(PipelineError::Inner);
+        };
+        match timeout(duration, operation()).await {
+            Ok(result) => result.map_err(PipelineError::Inner),
+            Err(_) => {
+                log_with!(
+                    self.log,
+                    self.log.level,
+                    "Operation timed out after {:?}.",
+                    duration
+                );
+                metrics::increment(&metrics::TIMEOUTS);
+                events::emit(ResilienceEvent::TimeoutHit { timeout: duration });
+                Err(PipelineError::Timeout { timeout: duration })
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::time::sleep;
+    use async_std::task::block_on;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_timed_out_attempts_are_retried() {
+        let pipeline: Pipeline<&str, &str> = Pipeline::new()
+            .with_timeout(Duration::from_millis(20))
+            .with_retry(RetryConfig {
+                max_attempts: 3,
+                delay: Duration::from_millis(1),
+                ..RetryConfig::default()
+            });
+        let attempts = AtomicUsize::new(0);
+
+        let result = block_on(pipeline.execute(|| async {
+            if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                sleep(Duration::from_millis(100)).await;
+            }
+            Ok("done")
+        }));
+        assert_eq!(result, Ok("done"));
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_breaker_stops_retries_and_rejections_are_not_retried() {
+        let pipeline: Pipeline<(), &str> = Pipeline::new()
+            .with_timeout(Duration::from_millis(20))
+            .with_retry(RetryConfig {
+                max_attempts: 5,
+                delay: Duration::from_millis(1),
+                ..RetryConfig::default()
+            })
+            .with_circuit_breaker(CircuitBreakerConfig::new(1, 2, Duration::from_secs(30)));
+        let attempts = AtomicUsize::new(0);
+
+        // A timeout and an error open the circuit, which ends the retry loop.
+        let result = block_on(pipeline.execute(|| async {
+            if attempts.fetch_add(1, Ordering::SeqCst) == 0 {
+                sleep(Duration::from_millis(100)).await;
+            }
+            Err("down")
+        }));
+        assert_eq!(result, Err(PipelineError::Inner("down")));
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+        assert_eq!(pipeline.circuit_state(), Some(CircuitBreakerState::Open));
+
+        let rejected = block_on(pipeline.execute(|| async {
+            attempts.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }));
+        assert!(rejected.unwrap_err().is_rejected());
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+    }
+}
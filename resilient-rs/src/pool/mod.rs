@@ -0,0 +1,441 @@
+use crate::asynchronous::schedule_class_retry;
+use crate::breaker::CircuitBreakerError;
+use crate::config::{CircuitBreakerConfig, RetryConfig};
+use crate::lockfree::LockFreeCircuitBreaker;
+use crate::logging::Level;
+use crate::logging::log_with;
+use crate::time;
+use async_std::channel::{self, Receiver, Sender};
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+/// The future returned by the connection factory of a backend.
+type ConnectFuture<C, E> = Pin<Box<dyn Future<Output = Result<C, E>> + Send>>;
+
+/// The check an idle connection must pass to be handed out again.
+type HealthCheck<C> = Box<dyn Fn(&mut C) -> bool + Send + Sync>;
+
+/// The error returned by `ConnectionPool::get`.
+#[derive(Debug, PartialEq)]
+pub enum PoolError<E> {
+    /// Every connection was checked out and none was returned within `max_wait`.
+    Timeout,
+    /// No connection could be opened because the circuit of every backend is open, or the pool
+    /// has no backend.
+    NoBackendAvailable,
+    /// Opening a connection failed on every attempt, with this last error.
+    Connect(E),
+}
+
+impl<E> PoolError<E> {
+    /// Returns the error of the last connection attempt, or `None` if none was made.
+    pub fn into_inner(self) -> Option<E> {
+        match self {
+            PoolError::Connect(err) => Some(err),
+            PoolError::Timeout | PoolError::NoBackendAvailable => None,
+        }
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for PoolError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PoolError::Timeout => write!(f, "Timed out waiting for a pooled connection"),
+            PoolError::NoBackendAvailable => write!(f, "No backend is available"),
+            PoolError::Connect(err) => write!(f, "Failed to connect: {}", err),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> std::error::Error for PoolError<E> {}
+
+struct Backend<C, E> {
+    name: String,
+    connect: Box<dyn Fn() -> ConnectFuture<C, E> + Send + Sync>,
+    breaker: LockFreeCircuitBreaker<E>,
+}
+
+/// An idle connection and the index of the backend it belongs to.
+struct Idle<C> {
+    backend: usize,
+    connection: C,
+}
+
+/// A small connection pool whose checkout path retries failed connects, evicts broken
+/// connections, and guards every backend with a circuit breaker.
+///
+/// At most `max_size` connections are checked out at a time; `get` waits for one to be returned
+/// beyond that, up to `max_wait` if one is set. Idle connections are reused most recent first,
+/// after passing the health check set with `with_health_check`; the ones failing it are dropped.
+/// When no idle connection is left, a new one is opened on the next backend, round-robin, whose
+/// circuit is not open. Connect failures feed the backend's breaker and are retried on the next
+/// backend according to the `RetryConfig`.
+///
+/// # Example
+/// ```
+/// use async_std::task::block_on;
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::pool::ConnectionPool;
+/// use std::sync::Arc;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// let opened = Arc::new(AtomicUsize::new(0));
+/// let pool = ConnectionPool::new(4, RetryConfig::default()).with_backend("primary", {
+///     let opened = opened.clone();
+///     move || {
+///         let id = opened.fetch_add(1, Ordering::SeqCst);
+///         async move { Ok::<_, String>(format!("connection {}", id)) }
+///     }
+/// });
+///
+/// block_on(async {
+///     let connection = pool.get().await.unwrap();
+///     assert_eq!(*connection, "connection 0");
+///     drop(connection);
+///     // The connection went back to the pool and is reused.
+///     assert_eq!(*pool.get().await.unwrap(), "connection 0");
+/// });
+/// assert_eq!(opened.load(Ordering::SeqCst), 1);
+/// ```
+pub struct ConnectionPool<C, E> {
+    backends: Vec<Backend<C, E>>,
+    next_backend: AtomicUsize,
+    idle: Mutex<Vec<Idle<C>>>,
+    slots: (Sender<()>, Receiver<()>),
+    max_wait: Option<Duration>,
+    health_check: Option<HealthCheck<C>>,
+    breaker_config: CircuitBreakerConfig,
+    retry_config: RetryConfig<E>,
+}
+
+impl<C, E> ConnectionPool<C, E> {
+    /// Creates a pool without backends, of at most `max_size` checked out connections.
+    ///
+    /// # Arguments
+    /// * `max_size` - The maximum number of connections checked out at a time; `0` is treated
+    ///   as `1`.
+    /// * `retry_config` - The attempts, delays and strategy of the connects of a checkout.
+    pub fn new(max_size: usize, retry_config: RetryConfig<E>) -> Self {
+        let max_size = max_size.max(1);
+        let (release, acquire) = channel::bounded(max_size);
+        for _ in 0..max_size {
+            release
+                .try_send(())
+                .expect("the channel has room for every slot");
+        }
+        ConnectionPool {
+            backends: Vec::new(),
+            next_backend: AtomicUsize::new(0),
+            idle: Mutex::new(Vec::new()),
+            slots: (release, acquire),
+            max_wait: None,
+            health_check: None,
+            breaker_config: CircuitBreakerConfig::default(),
+            retry_config,
+        }
+    }
+
+    /// Builder-style setter for the configuration of the breakers of the backends added after it.
+    pub fn with_breaker_config(mut self, breaker_config: CircuitBreakerConfig) -> Self {
+        self.breaker_config = breaker_config;
+        self
+    }
+
+    /// Adds a backend and returns the modified pool.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the backend, reported in logs and by `PooledConnection::backend`.
+    /// * `connect` - A closure returning a `Future` resolving to a new connection to the backend.
+    pub fn with_backend<F, Fut>(mut self, name: impl Into<String>, connect: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<C, E>> + Send + 'static,
+    {
+        self.backends.push(Backend {
+            name: name.into(),
+            connect: Box::new(move || Box::pin(connect())),
+            breaker: LockFreeCircuitBreaker::with_config(self.breaker_config),
+        });
+        self
+    }
+
+    /// Builder-style setter for the check an idle connection must pass to be reused.
+    ///
+    /// # Arguments
+    /// * `health_check` - Returns `false` for a broken connection, such as one closed by the
+    ///   server, which is then dropped instead of being handed out.
+    pub fn with_health_check<F>(mut self, health_check: F) -> Self
+    where
+        F: Fn(&mut C) -> bool + Send + Sync + 'static,
+    {
+        self.health_check = Some(Box::new(health_check));
+        self
+    }
+
+    /// Builder-style setter for the longest time `get` waits for a connection to be returned
+    /// when `max_size` connections are checked out. By default, it waits indefinitely.
+    pub fn with_max_wait(mut self, max_wait: Duration) -> Self {
+        self.max_wait = Some(max_wait);
+        self
+    }
+
+    /// Returns the circuit breaker of the backend named `name`, e.g. to report its state or to
+    /// reset it once the backend is known to be back.
+    pub fn breaker(&self, name: &str) -> Option<&LockFreeCircuitBreaker<E>> {
+        self.backends
+            .iter()
+            .find(|backend| backend.name == name)
+            .map(|backend| &backend.breaker)
+    }
+
+    /// Returns the number of idle connections.
+    pub fn idle(&self) -> usize {
+        self.idle_connections().len()
+    }
+
+    /// Checks out a connection, reusing a healthy idle one or opening a new one.
+    ///
+    /// # Returns
+    /// - `Ok(PooledConnection)`, which goes back to the pool when dropped.
+    /// - `Err(PoolError::Timeout)` if no connection was returned within `max_wait`.
+    /// - `Err(PoolError::NoBackendAvailable)` if the circuit of every backend is open.
+    /// - `Err(PoolError::Connect)` if opening a connection failed on every attempt.
+    pub async fn get(&self) -> Result<PooledConnection<'_, C, E>, PoolError<E>> {
+        let acquire = self.slots.1.recv();
+        let acquired = match self.max_wait {
+            Some(max_wait) => time::timeout(max_wait, acquire).await.ok(),
+            None => Some(acquire.await),
+        };
+        if acquired.is_none() {
+            return Err(PoolError::Timeout);
+        }
+        let slot = Slot(&self.slots.0);
+
+        while let Some(mut idle) = self.idle_connections().pop() {
+            if self
+                .health_check
+                .as_ref()
+                .is_none_or(|health_check| health_check(&mut idle.connection))
+            {
+                return Ok(self.checked_out(idle, slot));
+            }
+            log_with!(
+                self.retry_config.log,
+                Level::Debug,
+                "Evicting a broken connection to {}",
+                self.backends[idle.backend].name
+            );
+        }
+
+        let mut attempt = 1;
+        let mut delay = self.retry_config.delay;
+        loop {
+            let err = match self.connect().await {
+                Some(Ok(idle)) => return Ok(self.checked_out(idle, slot)),
+                Some(Err(err)) => err,
+                None => {
+                    log_with!(
+                        self.retry_config.log,
+                        Level::Error,
+                        "No backend is available"
+                    );
+                    return Err(PoolError::NoBackendAvailable);
+                }
+            };
+            let class = self.retry_config.classify(&err);
+            let Some(wait) = schedule_class_retry(&self.retry_config, class, attempt, delay) else {
+                return Err(PoolError::Connect(err));
+            };
+            time::sleep_on(self.retry_config.timer_wheel.as_ref(), wait).await;
+            delay = self.retry_config.next_delay(delay, attempt);
+            attempt += 1;
+        }
+    }
+
+    /// Opens a connection on the next backend whose circuit admits it, or returns `None` if
+    /// every circuit is open.
+    async fn connect(&self) -> Option<Result<Idle<C>, E>> {
+        let start = self.next_backend.fetch_add(1, Ordering::Relaxed);
+        for offset in 0..self.backends.len() {
+            let index = (start + offset) % self.backends.len();
+            let backend = &self.backends[index];
+            match backend.breaker.run_async(|| (backend.connect)()).await {
+                Ok(connection) => {
+                    return Some(Ok(Idle {
+                        backend: index,
+                        connection,
+                    }));
+                }
+                Err(CircuitBreakerError::Inner(err)) => return Some(Err(err)),
+                Err(_) => continue,
+            }
+        }
+        None
+    }
+
+    fn checked_out<'p>(&'p self, idle: Idle<C>, slot: Slot<'p>) -> PooledConnection<'p, C, E> {
+        PooledConnection {
+            pool: self,
+            backend: idle.backend,
+            connection: Some(idle.connection),
+            _slot: slot,
+        }
+    }
+
+    fn idle_connections(&self) -> MutexGuard<'_, Vec<Idle<C>>> {
+        self.idle.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A checked out slot of a `ConnectionPool`, released when dropped.
+struct Slot<'p>(&'p Sender<()>);
+
+impl Drop for Slot<'_> {
+    fn drop(&mut self) {
+        let _ = self.0.try_send(());
+    }
+}
+
+/// A connection checked out of a `ConnectionPool`, returned to it when dropped.
+///
+/// It dereferences to the connection. A connection that turned out broken while in use should
+/// be given up with `discard`, so the pool opens a new one instead of handing it out again.
+pub struct PooledConnection<'p, C, E> {
+    pool: &'p ConnectionPool<C, E>,
+    backend: usize,
+    connection: Option<C>,
+    _slot: Slot<'p>,
+}
+
+impl<C, E> PooledConnection<'_, C, E> {
+    /// Returns the name of the backend the connection belongs to.
+    pub fn backend(&self) -> &str {
+        &self.pool.backends[self.backend].name
+    }
+
+    /// Drops the connection instead of returning it to the pool.
+    pub fn discard(mut self) {
+        self.connection = None;
+    }
+}
+
+impl<C, E> Deref for PooledConnection<'_, C, E> {
+    type Target = C;
+
+    fn deref(&self) -> &C {
+        self.connection
+            .as_ref()
+            .expect("a connection is held until dropped")
+    }
+}
+
+impl<C, E> DerefMut for PooledConnection<'_, C, E> {
+    fn deref_mut(&mut self) -> &mut C {
+        self.connection
+            .as_mut()
+            .expect("a connection is held until dropped")
+    }
+}
+
+impl<C, E> Drop for PooledConnection<'_, C, E> {
+    fn drop(&mut self) {
+        if let Some(connection) = self.connection.take() {
+            self.pool.idle_connections().push(Idle {
+                backend: self.backend,
+                connection,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asynchronous::CircuitBreakerState;
+    use crate::sim::VirtualClock;
+    use crate::strategies::RetryStrategy;
+    use std::sync::Arc;
+
+    /// A connection that knows whether the server closed it.
+    struct Connection {
+        server: &'static str,
+        closed: bool,
+    }
+
+    fn backend(
+        name: &'static str,
+        failures: usize,
+    ) -> impl Fn() -> std::future::Ready<Result<Connection, String>> + Send + Sync + 'static {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        move || {
+            std::future::ready(if attempts.fetch_add(1, Ordering::SeqCst) < failures {
+                Err(format!("{} refused the connection", name))
+            } else {
+                Ok(Connection {
+                    server: name,
+                    closed: false,
+                })
+            })
+        }
+    }
+
+    #[test]
+    fn test_connect_failures_open_the_backend_breaker_and_fail_over() {
+        let config = RetryConfig::new(3, Duration::from_secs(1), RetryStrategy::Linear);
+        let pool = ConnectionPool::new(2, config)
+            .with_max_wait(Duration::from_secs(5))
+            .with_breaker_config(CircuitBreakerConfig::new(1, 1, Duration::from_secs(60)))
+            .with_backend("primary", backend("primary", usize::MAX))
+            .with_backend("replica", backend("replica", 0));
+        let clock = VirtualClock::new();
+
+        let connection = clock.block_on(pool.get()).unwrap();
+        assert_eq!(connection.backend(), "replica");
+        assert_eq!(connection.server, "replica");
+        assert_eq!(clock.elapsed(), Duration::from_secs(1));
+        assert_eq!(
+            pool.breaker("primary").unwrap().state(),
+            CircuitBreakerState::Open
+        );
+
+        // Both slots are taken, so the next checkout times out.
+        let _second = clock.block_on(pool.get()).unwrap();
+        assert!(matches!(
+            clock.block_on(pool.get()),
+            Err(PoolError::Timeout)
+        ));
+    }
+
+    #[test]
+    fn test_broken_connections_are_evicted() {
+        let opened = Arc::new(AtomicUsize::new(0));
+        let pool = ConnectionPool::new(1, RetryConfig::<String>::default())
+            .with_backend("primary", {
+                let opened = opened.clone();
+                move || {
+                    opened.fetch_add(1, Ordering::SeqCst);
+                    std::future::ready(Ok(Connection {
+                        server: "primary",
+                        closed: false,
+                    }))
+                }
+            })
+            .with_health_check(|connection: &mut Connection| !connection.closed);
+        let clock = VirtualClock::new();
+
+        let mut connection = clock.block_on(pool.get()).unwrap();
+        connection.closed = true;
+        drop(connection);
+        assert_eq!(pool.idle(), 1);
+
+        let connection = clock.block_on(pool.get()).unwrap();
+        assert!(!connection.closed);
+        assert_eq!(opened.load(Ordering::SeqCst), 2);
+        connection.discard();
+        assert_eq!(pool.idle(), 0);
+    }
+}
@@ -0,0 +1,121 @@
+use crate::metrics::{
+    BREAKER_OPENS, BREAKER_REJECTIONS, BULKHEAD_REJECTIONS, CANCELLATIONS, FALLBACKS, GIVE_UPS,
+    LOAD_SHED, RATE_LIMIT_REJECTIONS, RETRIES, STALE_RESPONSES, TIMEOUTS,
+};
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// The counters exposed by `gather`, as `(name, help, counter)`.
+const COUNTERS: [(&str, &str, &AtomicU64); 11] = [
+    (
+        "resilient_retries_total",
+        "Total number of retry attempts scheduled after a failed attempt.",
+        &RETRIES,
+    ),
+    (
+        "resilient_give_ups_total",
+        "Total number of operations abandoned after exhausting or failing retries.",
+        &GIVE_UPS,
+    ),
+    (
+        "resilient_circuit_breaker_opens_total",
+        "Total number of circuit breaker transitions to the open state.",
+        &BREAKER_OPENS,
+    ),
+    (
+        "resilient_circuit_breaker_rejections_total",
+        "Total number of calls rejected by an open circuit breaker.",
+        &BREAKER_REJECTIONS,
+    ),
+    (
+        "resilient_timeouts_total",
+        "Total number of operations that exceeded their timeout.",
+        &TIMEOUTS,
+    ),
+    (
+        "resilient_fallbacks_total",
+        "Total number of fallback invocations.",
+        &FALLBACKS,
+    ),
+    (
+        "resilient_bulkhead_rejections_total",
+        "Total number of calls rejected by a full bulkhead.",
+        &BULKHEAD_REJECTIONS,
+    ),
+    (
+        "resilient_rate_limit_rejections_total",
+        "Total number of calls rejected by a rate limiter.",
+        &RATE_LIMIT_REJECTIONS,
+    ),
+    (
+        "resilient_load_shed_total",
+        "Total number of calls shed by an overloaded load shedder.",
+        &LOAD_SHED,
+    ),
+    (
+        "resilient_cache_stale_responses_total",
+        "Total number of stale cached results served after a failed operation.",
+        &STALE_RESPONSES,
+    ),
+    (
+        "resilient_cancelled_attempts_total",
+        "Total number of attempts and circuit breaker calls dropped before completing.",
+        &CANCELLATIONS,
+    ),
+];
+
+/// Renders the crate's internal counters in the Prometheus text exposition format.
+///
+/// The counters aggregate every retry loop, circuit breaker and timeout executor in the process,
+/// so a service can expose resilience data from a plain `/metrics` handler without a full
+/// metrics stack.
+///
+/// # Returns
+/// A `String` in the Prometheus text format (version 0.0.4), one `counter` per metric.
+///
+/// # Example
+/// ```
+/// use resilient_rs::prometheus::gather;
+///
+/// let body = gather();
+/// assert!(body.contains("# TYPE resilient_retries_total counter"));
+/// ```
+pub fn gather() -> String {
+    let mut output = String::new();
+    for (name, help, counter) in COUNTERS {
+        let _ = writeln!(output, "# HELP {} {}", name, help);
+        let _ = writeln!(output, "# TYPE {} counter", name);
+        let _ = writeln!(output, "{} {}", name, counter.load(Ordering::Relaxed));
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metrics::increment;
+
+    fn value_of(body: &str, name: &str) -> u64 {
+        body.lines()
+            .find_map(|line| line.strip_prefix(&format!("{} ", name)))
+            .and_then(|value| value.parse().ok())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_gather_renders_all_counters() {
+        let body = gather();
+        for (name, _, _) in COUNTERS {
+            assert!(body.contains(&format!("# HELP {} ", name)));
+            assert!(body.contains(&format!("# TYPE {} counter", name)));
+        }
+    }
+
+    #[test]
+    fn test_gather_reflects_increments() {
+        let before = value_of(&gather(), "resilient_fallbacks_total");
+        increment(&FALLBACKS);
+        let after = value_of(&gather(), "resilient_fallbacks_total");
+        assert!(after > before);
+    }
+}
@@ -0,0 +1,504 @@
+use crate::asynchronous::schedule_class_retry;
+use crate::config::RetryConfig;
+use crate::logging::Level;
+use crate::logging::log_with;
+use crate::shutdown::ShutdownHandle;
+use crate::store::StoreError;
+use crate::time;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::future::Future;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, SystemTime};
+
+#[cfg(feature = "queue-sqlite")]
+mod sqlite;
+
+#[cfg(feature = "queue-sqlite")]
+pub use self::sqlite::SqliteQueueStore;
+
+/// The hook called with a job removed from a `RetryQueue` after its last failed attempt.
+pub type GiveUpHook = Arc<dyn Fn(QueuedJob) + Send + Sync>;
+
+/// An operation waiting in a `QueueStore` for its next attempt.
+///
+/// Everything needed to resume the retries after a restart is persisted with the payload: the
+/// attempts already made, when the next one is due, and the running delay grown by the retry
+/// strategy.
+///
+/// The retry policy is not persisted: it is the `RetryConfig` of the `RetryQueue` processing the
+/// queue named `queue`, held in process. Every worker of a queue should therefore be created with
+/// the same `RetryConfig`, and a changed policy applies to the jobs already queued.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueuedJob {
+    /// The identifier assigned by the store.
+    pub id: u64,
+    /// The name of the queue holding the job.
+    pub queue: String,
+    /// The serialized operation, passed to the handler on every attempt.
+    pub payload: Vec<u8>,
+    /// The number of attempts made so far, including the one a claimed job is about to get.
+    pub attempts: usize,
+    /// When the next attempt is due.
+    pub next_attempt_at: SystemTime,
+    /// The running delay, from which the delay after the next failure is computed.
+    pub delay: Duration,
+    /// The error of the last failed attempt, if any.
+    pub last_error: Option<String>,
+}
+
+/// A backend persisting the jobs of `RetryQueue`s, so their retries survive process restarts.
+///
+/// Jobs are claimed before they are attempted: `claim` counts the attempt and pushes the next
+/// attempt of the returned jobs `lease` into the future, in one atomic step. A worker crashing
+/// mid-attempt leaves the jobs to be picked up again once the lease expires, with the crashed
+/// attempt counted, so a payload crashing every worker still exhausts its attempts; concurrent
+/// workers sharing a store do not run the same job twice. Jobs are therefore delivered at least
+/// once, and handlers should be idempotent.
+pub trait QueueStore: Send + Sync {
+    /// Stores a new job and returns its identifier.
+    ///
+    /// # Arguments
+    /// * `queue` - The name of the queue the job belongs to.
+    /// * `payload` - The serialized operation.
+    /// * `next_attempt_at` - When the first attempt is due.
+    /// * `delay` - The initial running delay, usually the `RetryConfig`'s `delay`.
+    fn push(
+        &self,
+        queue: &str,
+        payload: &[u8],
+        next_attempt_at: SystemTime,
+        delay: Duration,
+    ) -> impl Future<Output = Result<u64, StoreError>> + Send;
+
+    /// Returns up to `limit` jobs of `queue` due at `now`, after incrementing their `attempts`
+    /// and postponing their next attempt to `now + lease`, the `next_attempt_at` of the returned
+    /// jobs.
+    fn claim(
+        &self,
+        queue: &str,
+        now: SystemTime,
+        lease: Duration,
+        limit: usize,
+    ) -> impl Future<Output = Result<Vec<QueuedJob>, StoreError>> + Send;
+
+    /// Saves the `attempts`, `next_attempt_at`, `delay` and `last_error` of a claimed job.
+    fn reschedule(&self, job: &QueuedJob) -> impl Future<Output = Result<(), StoreError>> + Send;
+
+    /// Deletes a job, once it succeeded or was given up.
+    fn remove(&self, id: u64) -> impl Future<Output = Result<(), StoreError>> + Send;
+
+    /// Returns the number of jobs of `queue`, due or not.
+    fn len(&self, queue: &str) -> impl Future<Output = Result<usize, StoreError>> + Send;
+}
+
+/// A `QueueStore` keeping jobs in memory, for a single process and for tests.
+///
+/// Jobs are lost when the process exits; use a persistent store such as the `SqliteQueueStore`
+/// to retry them across restarts.
+#[derive(Debug, Default)]
+pub struct InMemoryQueueStore {
+    jobs: Mutex<BTreeMap<u64, QueuedJob>>,
+}
+
+impl InMemoryQueueStore {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        InMemoryQueueStore::default()
+    }
+
+    fn jobs(&self) -> MutexGuard<'_, BTreeMap<u64, QueuedJob>> {
+        self.jobs.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl QueueStore for InMemoryQueueStore {
+    async fn push(
+        &self,
+        queue: &str,
+        payload: &[u8],
+        next_attempt_at: SystemTime,
+        delay: Duration,
+    ) -> Result<u64, StoreError> {
+        let mut jobs = self.jobs();
+        let id = jobs.last_key_value().map_or(1, |(id, _)| id + 1);
+        jobs.insert(
+            id,
+            QueuedJob {
+                id,
+                queue: queue.to_string(),
+                payload: payload.to_vec(),
+                attempts: 0,
+                next_attempt_at,
+                delay,
+                last_error: None,
+            },
+        );
+        Ok(id)
+    }
+
+    async fn claim(
+        &self,
+        queue: &str,
+        now: SystemTime,
+        lease: Duration,
+        limit: usize,
+    ) -> Result<Vec<QueuedJob>, StoreError> {
+        let mut jobs = self.jobs();
+        let mut due: Vec<&mut QueuedJob> = jobs
+            .values_mut()
+            .filter(|job| job.queue == queue && job.next_attempt_at <= now)
+            .collect();
+        due.sort_by_key(|job| (job.next_attempt_at, job.id));
+        Ok(due
+            .into_iter()
+            .take(limit)
+            .map(|job| {
+                job.attempts += 1;
+                job.next_attempt_at = now + lease;
+                job.clone()
+            })
+            .collect())
+    }
+
+    async fn reschedule(&self, job: &QueuedJob) -> Result<(), StoreError> {
+        if let Some(stored) = self.jobs().get_mut(&job.id) {
+            stored.attempts = job.attempts;
+            stored.next_attempt_at = job.next_attempt_at;
+            stored.delay = job.delay;
+            stored.last_error = job.last_error.clone();
+        }
+        Ok(())
+    }
+
+    async fn remove(&self, id: u64) -> Result<(), StoreError> {
+        self.jobs().remove(&id);
+        Ok(())
+    }
+
+    async fn len(&self, queue: &str) -> Result<usize, StoreError> {
+        Ok(self
+            .jobs()
+            .values()
+            .filter(|job| job.queue == queue)
+            .count())
+    }
+}
+
+/// A named queue of failed operations, retried by a worker with backoff from a `QueueStore`.
+///
+/// An operation that failed, or should run in the background, is serialized by the caller and
+/// handed to `enqueue`. A worker calling `run` (or `run_due` from its own scheduler) claims the
+/// due jobs and passes their payload to a handler: a successful job is removed, and a failed one
+/// is rescheduled according to the `RetryConfig` — its classifier, attempts, strategy and
+/// `max_delay` — until its attempts are exhausted or its error is not retryable, when it is
+/// removed and handed to the hook set with `with_on_give_up`. A job whose attempts were all
+/// interrupted by a crashing worker is given up the same way when it is next claimed.
+///
+/// The attempts and the running delay are stored with the job, so a worker started after a
+/// restart resumes the backoff where the previous process left it.
+///
+/// # Example
+/// ```
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::queue::{InMemoryQueueStore, RetryQueue};
+///
+/// # async_std::task::block_on(async {
+/// let config: RetryConfig<String> = RetryConfig::default().with_delay(Duration::ZERO);
+/// let queue = RetryQueue::new("webhooks", Arc::new(InMemoryQueueStore::new()), config);
+/// queue.enqueue(b"order-42".to_vec()).await.unwrap();
+///
+/// let processed = queue
+///     .run_due(|payload| async move {
+///         assert_eq!(payload, b"order-42");
+///         Ok::<(), String>(())
+///     })
+///     .await
+///     .unwrap();
+/// assert_eq!(processed, 1);
+/// assert_eq!(queue.len().await.unwrap(), 0);
+/// # });
+/// ```
+pub struct RetryQueue<S, E> {
+    name: String,
+    store: Arc<S>,
+    retry: RetryConfig<E>,
+    lease: Duration,
+    batch_size: usize,
+    poll_interval: Duration,
+    on_give_up: Option<GiveUpHook>,
+}
+
+impl<S: QueueStore, E: fmt::Display> RetryQueue<S, E> {
+    /// Creates a queue storing its jobs in `store` and retrying them according to `retry`.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the queue, separating its jobs from those of other queues sharing
+    ///   the store.
+    /// * `store` - The backend persisting the jobs.
+    /// * `retry` - The attempts, delays and strategy of the retries of every job.
+    pub fn new(name: impl Into<String>, store: Arc<S>, retry: RetryConfig<E>) -> Self {
+        RetryQueue {
+            name: name.into(),
+            store,
+            retry,
+            lease: Duration::from_secs(30),
+            batch_size: 16,
+            poll_interval: Duration::from_secs(1),
+            on_give_up: None,
+        }
+    }
+
+    /// Sets how long a claimed job is hidden from other workers and returns the modified queue.
+    ///
+    /// # Arguments
+    /// * `lease` - Longer than the slowest attempt, 30 seconds by default.
+    pub fn with_lease(mut self, lease: Duration) -> Self {
+        self.lease = lease;
+        self
+    }
+
+    /// Sets the maximum number of jobs claimed at once and returns the modified queue.
+    ///
+    /// # Arguments
+    /// * `batch_size` - The number of jobs claimed by `run_due`, 16 by default.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Sets how long `run` waits when no job is due and returns the modified queue.
+    ///
+    /// # Arguments
+    /// * `poll_interval` - The wait between polls of an idle queue, 1 second by default.
+    pub fn with_poll_interval(mut self, poll_interval: Duration) -> Self {
+        self.poll_interval = poll_interval;
+        self
+    }
+
+    /// Sets the hook called with the jobs given up, e.g. to move them to a dead-letter table, and
+    /// returns the modified queue.
+    ///
+    /// # Arguments
+    /// * `on_give_up` - Called with the removed job, whose `last_error` holds the error of its
+    ///   last attempt.
+    pub fn with_on_give_up(
+        mut self,
+        on_give_up: impl Fn(QueuedJob) + Send + Sync + 'static,
+    ) -> Self {
+        self.on_give_up = Some(Arc::new(on_give_up));
+        self
+    }
+
+    /// Stores an operation, due immediately.
+    ///
+    /// # Returns
+    /// The identifier of the job, or the error of the store.
+    pub async fn enqueue(&self, payload: impl Into<Vec<u8>>) -> Result<u64, StoreError> {
+        let payload = payload.into();
+        self.store
+            .push(&self.name, &payload, time::system_now(), self.retry.delay)
+            .await
+    }
+
+    /// Returns the number of jobs waiting in the queue.
+    pub async fn len(&self) -> Result<usize, StoreError> {
+        self.store.len(&self.name).await
+    }
+
+    /// Returns `true` if no job is waiting in the queue.
+    pub async fn is_empty(&self) -> Result<bool, StoreError> {
+        Ok(self.len().await? == 0)
+    }
+
+    /// Claims the jobs due now, up to the batch size, and attempts each of them once.
+    ///
+    /// # Arguments
+    /// * `handler` - Runs the operation serialized in a payload.
+    ///
+    /// # Returns
+    /// The number of jobs attempted, or the first error of the store.
+    pub async fn run_due<F, Fut>(&self, mut handler: F) -> Result<usize, StoreError>
+    where
+        F: FnMut(Vec<u8>) -> Fut,
+        Fut: Future<Output = Result<(), E>>,
+    {
+        let jobs = self
+            .store
+            .claim(&self.name, time::system_now(), self.lease, self.batch_size)
+            .await?;
+        let claimed = jobs.len();
+        for mut job in jobs {
+            if job.attempts > self.retry.attempt_limit() {
+                // Every attempt was interrupted before its outcome could be recorded.
+                self.give_up(job).await?;
+                continue;
+            }
+            let started = time::now();
+            let result = handler(job.payload.clone()).await;
+            let elapsed = time::elapsed(started);
+            self.retry.record(|stats| stats.record_attempt(elapsed));
+            let err = match result {
+                Ok(()) => {
+                    let retried = job.attempts > 1;
+                    self.retry.record(|stats| stats.record_success(retried));
+                    self.store.remove(job.id).await?;
+                    continue;
+                }
+                Err(err) => err,
+            };
+            let class = self.retry.classify(&err);
+            job.last_error = Some(err.to_string());
+            match schedule_class_retry(&self.retry, class, job.attempts, job.delay) {
+                Some(wait) => {
+                    job.delay = self.retry.next_delay(job.delay, job.attempts);
+                    job.next_attempt_at = time::system_now() + wait;
+                    self.store.reschedule(&job).await?;
+                }
+                None => self.give_up(job).await?,
+            }
+        }
+        Ok(claimed)
+    }
+
+    async fn give_up(&self, job: QueuedJob) -> Result<(), StoreError> {
+        self.store.remove(job.id).await?;
+        log_with!(
+            self.retry.log,
+            Level::Warn,
+            "Job {} of queue {} failed after {} attempts, removing it: {}",
+            job.id,
+            self.name,
+            job.attempts,
+            job.last_error.as_deref().unwrap_or("interrupted")
+        );
+        if let Some(on_give_up) = &self.on_give_up {
+            on_give_up(job);
+        }
+        Ok(())
+    }
+
+    /// Processes the queue until `shutdown` is requested, waiting the poll interval whenever no
+    /// job is due.
+    ///
+    /// Errors of the store are logged and retried after the poll interval, so an unavailable
+    /// database pauses the worker instead of stopping it.
+    pub async fn run<F, Fut>(&self, mut handler: F, shutdown: &ShutdownHandle)
+    where
+        F: FnMut(Vec<u8>) -> Fut,
+        Fut: Future<Output = Result<(), E>>,
+    {
+        while !shutdown.is_shutdown() {
+            let processed = match self.run_due(&mut handler).await {
+                Ok(processed) => processed,
+                Err(err) => {
+                    log_with!(
+                        self.retry.log,
+                        Level::Error,
+                        "Failed to process queue {}: {}",
+                        self.name,
+                        err
+                    );
+                    0
+                }
+            };
+            if processed < self.batch_size && !shutdown.sleep(None, self.poll_interval).await {
+                return;
+            }
+        }
+    }
+}
+
+impl<S, E> fmt::Debug for RetryQueue<S, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryQueue")
+            .field("name", &self.name)
+            .field("lease", &self.lease)
+            .field("batch_size", &self.batch_size)
+            .field("poll_interval", &self.poll_interval)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::RetryStrategy;
+    use async_std::task::block_on;
+
+    fn retry_config() -> RetryConfig<String> {
+        RetryConfig::new(2, Duration::ZERO, RetryStrategy::Linear)
+    }
+
+    fn given_up_queue(
+        store: &Arc<InMemoryQueueStore>,
+    ) -> (
+        RetryQueue<InMemoryQueueStore, String>,
+        Arc<Mutex<Vec<QueuedJob>>>,
+    ) {
+        let given_up = Arc::new(Mutex::new(Vec::new()));
+        let sink = given_up.clone();
+        let queue = RetryQueue::new("jobs", store.clone(), retry_config())
+            .with_on_give_up(move |job| sink.lock().unwrap().push(job));
+        (queue, given_up)
+    }
+
+    #[test]
+    fn test_failed_jobs_resume_their_attempts_in_a_new_worker() {
+        let store = Arc::new(InMemoryQueueStore::new());
+        let failing = |_payload: Vec<u8>| async { Err("unavailable".to_string()) };
+        block_on(async {
+            let queue = RetryQueue::new("jobs", store.clone(), retry_config());
+            queue.enqueue(b"a".to_vec()).await.unwrap();
+            assert_eq!(queue.run_due(failing).await.unwrap(), 1);
+            assert_eq!(queue.len().await.unwrap(), 1);
+        });
+
+        // A queue created after a restart continues from the stored attempt.
+        let (queue, given_up) = given_up_queue(&store);
+        block_on(async {
+            assert_eq!(queue.run_due(failing).await.unwrap(), 1);
+            assert!(queue.is_empty().await.unwrap());
+        });
+        let given_up = given_up.lock().unwrap();
+        assert_eq!(given_up.len(), 1);
+        assert_eq!(given_up[0].attempts, 2);
+        assert_eq!(given_up[0].last_error.as_deref(), Some("unavailable"));
+    }
+
+    #[test]
+    fn test_attempts_interrupted_by_crashes_are_counted() {
+        let store = Arc::new(InMemoryQueueStore::new());
+        let lease = Duration::from_secs(60);
+        let now = SystemTime::now() - 2 * lease;
+        block_on(async {
+            store.push("jobs", b"a", now, Duration::ZERO).await.unwrap();
+            store
+                .push("other", b"b", now, Duration::ZERO)
+                .await
+                .unwrap();
+
+            let claimed = store.claim("jobs", now, lease, 8).await.unwrap();
+            assert_eq!(claimed[0].attempts, 1);
+            assert!(store.claim("jobs", now, lease, 8).await.unwrap().is_empty());
+            // The worker holding the job crashes twice; each lease expiry counts an attempt.
+            let expired = store
+                .claim("jobs", now + lease, Duration::ZERO, 8)
+                .await
+                .unwrap();
+            assert_eq!(expired[0].attempts, 2);
+        });
+
+        let (queue, given_up) = given_up_queue(&store);
+        let processed = block_on(
+            queue.run_due(|_| async { panic!("an exhausted job must not be attempted again") }),
+        );
+        assert_eq!(processed.unwrap(), 1);
+        let given_up = given_up.lock().unwrap();
+        assert_eq!(given_up[0].attempts, 3);
+        assert_eq!(given_up[0].last_error, None);
+        assert_eq!(block_on(store.len("other")).unwrap(), 1);
+    }
+}
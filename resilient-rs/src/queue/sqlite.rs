@@ -0,0 +1,215 @@
+use super::{QueueStore, QueuedJob};
+use crate::store::StoreError;
+use sqlx::Row;
+use sqlx::sqlite::{SqlitePool, SqliteRow};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A `QueueStore` persisting jobs in a SQLite database, so they are retried after the process
+/// restarts.
+///
+/// Jobs are kept in the `resilient_queue` table, created by `new` and `connect` if it does not
+/// exist. Times are stored in milliseconds since the Unix epoch. A claim is a single `UPDATE`
+/// statement counting the attempt and taking the lease, so workers of several processes can share
+/// a database file and an attempt interrupted by a crash is still counted.
+///
+/// This type is available with the `queue-sqlite` feature.
+///
+/// # Example
+/// ```no_run
+/// use std::sync::Arc;
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::queue::{RetryQueue, SqliteQueueStore};
+///
+/// # async_std::task::block_on(async {
+/// let store = SqliteQueueStore::connect("sqlite://jobs.db?mode=rwc").await.unwrap();
+/// let queue = RetryQueue::new("emails", Arc::new(store), RetryConfig::<String>::default());
+/// queue.enqueue(b"welcome:42".to_vec()).await.unwrap();
+/// # });
+/// ```
+#[derive(Debug, Clone)]
+pub struct SqliteQueueStore {
+    pool: SqlitePool,
+}
+
+impl SqliteQueueStore {
+    /// Creates a store using `pool`, creating the `resilient_queue` table if needed.
+    pub async fn new(pool: SqlitePool) -> Result<Self, StoreError> {
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS resilient_queue (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                queue TEXT NOT NULL,
+                payload BLOB NOT NULL,
+                attempts INTEGER NOT NULL,
+                next_attempt_at INTEGER NOT NULL,
+                delay_ms INTEGER NOT NULL,
+                last_error TEXT
+            )",
+        )
+        .execute(&pool)
+        .await?;
+        sqlx::query(
+            "CREATE INDEX IF NOT EXISTS resilient_queue_due
+                ON resilient_queue (queue, next_attempt_at)",
+        )
+        .execute(&pool)
+        .await?;
+        Ok(SqliteQueueStore { pool })
+    }
+
+    /// Opens the database at `url`, e.g. `"sqlite://jobs.db?mode=rwc"`, and creates the store.
+    pub async fn connect(url: &str) -> Result<Self, StoreError> {
+        SqliteQueueStore::new(SqlitePool::connect(url).await?).await
+    }
+}
+
+impl QueueStore for SqliteQueueStore {
+    async fn push(
+        &self,
+        queue: &str,
+        payload: &[u8],
+        next_attempt_at: SystemTime,
+        delay: Duration,
+    ) -> Result<u64, StoreError> {
+        let id = sqlx::query(
+            "INSERT INTO resilient_queue (queue, payload, attempts, next_attempt_at, delay_ms)
+                VALUES (?, ?, 0, ?, ?)",
+        )
+        .bind(queue)
+        .bind(payload)
+        .bind(millis(next_attempt_at))
+        .bind(delay.as_millis() as i64)
+        .execute(&self.pool)
+        .await?
+        .last_insert_rowid();
+        Ok(id as u64)
+    }
+
+    async fn claim(
+        &self,
+        queue: &str,
+        now: SystemTime,
+        lease: Duration,
+        limit: usize,
+    ) -> Result<Vec<QueuedJob>, StoreError> {
+        let rows = sqlx::query(
+            "UPDATE resilient_queue SET attempts = attempts + 1, next_attempt_at = ?
+                WHERE id IN (
+                    SELECT id FROM resilient_queue
+                    WHERE queue = ? AND next_attempt_at <= ?
+                    ORDER BY next_attempt_at, id
+                    LIMIT ?
+                )
+                RETURNING id, queue, payload, attempts, next_attempt_at, delay_ms, last_error",
+        )
+        .bind(millis(now + lease))
+        .bind(queue)
+        .bind(millis(now))
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+        let mut jobs = rows.iter().map(job).collect::<Result<Vec<_>, _>>()?;
+        jobs.sort_by_key(|job| job.id);
+        Ok(jobs)
+    }
+
+    async fn reschedule(&self, job: &QueuedJob) -> Result<(), StoreError> {
+        sqlx::query(
+            "UPDATE resilient_queue
+                SET attempts = ?, next_attempt_at = ?, delay_ms = ?, last_error = ?
+                WHERE id = ?",
+        )
+        .bind(job.attempts as i64)
+        .bind(millis(job.next_attempt_at))
+        .bind(job.delay.as_millis() as i64)
+        .bind(job.last_error.as_deref())
+        .bind(job.id as i64)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn remove(&self, id: u64) -> Result<(), StoreError> {
+        sqlx::query("DELETE FROM resilient_queue WHERE id = ?")
+            .bind(id as i64)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn len(&self, queue: &str) -> Result<usize, StoreError> {
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM resilient_queue WHERE queue = ?")
+            .bind(queue)
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(count as usize)
+    }
+}
+
+fn millis(time: SystemTime) -> i64 {
+    time.duration_since(UNIX_EPOCH)
+        .map_or(0, |since| since.as_millis() as i64)
+}
+
+fn job(row: &SqliteRow) -> Result<QueuedJob, sqlx::Error> {
+    Ok(QueuedJob {
+        id: row.try_get::<i64, _>("id")? as u64,
+        queue: row.try_get("queue")?,
+        payload: row.try_get("payload")?,
+        attempts: row.try_get::<i64, _>("attempts")? as usize,
+        next_attempt_at: UNIX_EPOCH
+            + Duration::from_millis(row.try_get::<i64, _>("next_attempt_at")?.max(0) as u64),
+        delay: Duration::from_millis(row.try_get::<i64, _>("delay_ms")?.max(0) as u64),
+        last_error: row.try_get("last_error")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RetryConfig;
+    use crate::queue::RetryQueue;
+    use async_std::task::block_on;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_jobs_survive_reopening_the_database() {
+        let path = std::env::temp_dir().join(format!("resilient-queue-{}.db", std::process::id()));
+        let url = format!("sqlite://{}?mode=rwc", path.display());
+        let config = || RetryConfig {
+            max_attempts: 3,
+            delay: Duration::ZERO,
+            ..RetryConfig::default()
+        };
+        block_on(async {
+            let store = Arc::new(SqliteQueueStore::connect(&url).await.unwrap());
+            let queue = RetryQueue::new("jobs", store.clone(), config());
+            queue.enqueue(b"a".to_vec()).await.unwrap();
+            let failed = queue
+                .run_due(|_| async { Err("unavailable".to_string()) })
+                .await
+                .unwrap();
+            assert_eq!(failed, 1);
+            store.pool.close().await;
+
+            let store = Arc::new(SqliteQueueStore::connect(&url).await.unwrap());
+            let [job] = &store
+                .claim("jobs", SystemTime::now(), Duration::ZERO, 8)
+                .await
+                .unwrap()[..]
+            else {
+                panic!("the failed job was not persisted");
+            };
+            assert_eq!(job.payload, b"a");
+            // The failed attempt, plus the one taken by this claim.
+            assert_eq!(job.attempts, 2);
+            assert_eq!(job.last_error.as_deref(), Some("unavailable"));
+
+            // The lease of the claim above expired without an outcome, as after a crash.
+            let queue = RetryQueue::new("jobs", store.clone(), config());
+            assert_eq!(queue.run_due(|_| async { Ok(()) }).await.unwrap(), 1);
+            assert_eq!(queue.len().await.unwrap(), 0);
+            store.pool.close().await;
+        });
+        let _ = std::fs::remove_file(path);
+    }
+}
@@ -0,0 +1,385 @@
+use crate::config::{ConfigError, RateLimitConfig};
+use crate::events::{self, ResilienceEvent};
+use crate::logging::log_with;
+use crate::metrics;
+use crate::time;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+/// A source of the current time for the rate limiters.
+///
+/// Limiters read the time through this trait so that tests can drive them with a manual clock
+/// instead of sleeping across window boundaries.
+pub trait Clock: Send + Sync {
+    /// Returns the current instant.
+    fn now(&self) -> Instant;
+}
+
+/// The `Clock` reading `Instant::now`, used by default.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        time::now()
+    }
+}
+
+/// The error returned when a rate limiter rejects a call.
+///
+/// `retry_after` is how long the caller should wait before the limiter admits a call again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RateLimited {
+    pub retry_after: Duration,
+}
+
+impl fmt::Display for RateLimited {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Rate limit reached. Please retry after {:?}..!",
+            self.retry_after
+        )
+    }
+}
+
+impl Error for RateLimited {}
+
+/// A rate limiter deciding whether a call may run now.
+pub trait RateLimiter: Send + Sync {
+    /// Admits a call if the limit allows it, counting it against the limit.
+    ///
+    /// # Returns
+    /// - `Ok(())` if the call may run.
+    /// - `Err(RateLimited)` with the time until a call would be admitted otherwise.
+    fn try_acquire(&self) -> Result<(), RateLimited>;
+}
+
+/// A rate limiter admitting at most `limit` calls per fixed `window`.
+///
+/// Time is cut into consecutive windows of `window`, the first one starting when the limiter is
+/// created; each window admits `limit` calls, and the count resets at the next boundary. Windows
+/// with no calls are skipped, so the boundaries stay aligned to the creation time.
+///
+/// This matches upstream quotas expressed as "at most N calls per minute" and takes constant
+/// memory, but a burst of up to `2 * limit` calls can get through around a boundary: `limit`
+/// calls at the end of one window and `limit` at the start of the next. Use a
+/// `SlidingLogLimiter` when that burst is a problem.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::config::RateLimitConfig;
+/// use resilient_rs::ratelimit::{FixedWindowLimiter, RateLimiter};
+///
+/// let limiter = FixedWindowLimiter::new(RateLimitConfig::new(2, Duration::from_secs(60)));
+/// assert!(limiter.try_acquire().is_ok());
+/// assert!(limiter.try_acquire().is_ok());
+///
+/// let rejected = limiter.try_acquire().unwrap_err();
+/// assert!(rejected.retry_after <= Duration::from_secs(60));
+/// ```
+pub struct FixedWindowLimiter {
+    config: RateLimitConfig,
+    clock: Arc<dyn Clock>,
+    window: Mutex<FixedWindow>,
+}
+
+/// The window a `FixedWindowLimiter` is currently counting calls in.
+struct FixedWindow {
+    start: Instant,
+    count: usize,
+}
+
+impl FixedWindowLimiter {
+    /// Creates a limiter whose first window starts now.
+    ///
+    /// # Arguments
+    /// * `config` - The limit and window length.
+    ///
+    /// # Panics
+    /// Panics if `config` is invalid, e.g. a literal with a zero `window`. Use `try_new` to get a
+    /// `ConfigError` instead.
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self::try_new(config).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates a limiter, returning an error instead of panicking on an invalid `config`.
+    ///
+    /// # Returns
+    /// The limiter, or the `ConfigError` reported by `RateLimitConfig::validate`.
+    pub fn try_new(config: RateLimitConfig) -> Result<Self, ConfigError> {
+        config.validate()?;
+        Ok(FixedWindowLimiter {
+            config,
+            clock: Arc::new(SystemClock),
+            window: Mutex::new(FixedWindow {
+                start: time::now(),
+                count: 0,
+            }),
+        })
+    }
+
+    /// Reads the time from `clock` instead of the system clock, and returns the modified limiter.
+    ///
+    /// The first window restarts at the clock's current time.
+    ///
+    /// # Arguments
+    /// * `clock` - The clock driving the windows, typically a manual clock in tests.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.window
+            .get_mut()
+            .unwrap_or_else(PoisonError::into_inner)
+            .start = clock.now();
+        self.clock = clock;
+        self
+    }
+
+    /// Returns the number of calls the current window still admits.
+    pub fn remaining(&self) -> usize {
+        let now = self.clock.now();
+        let mut window = self.window();
+        self.advance(&mut window, now);
+        self.config.limit.saturating_sub(window.count)
+    }
+
+    /// Moves `window` to the window containing `now`, resetting its count if it changed.
+    fn advance(&self, window: &mut FixedWindow, now: Instant) {
+        let elapsed = now.saturating_duration_since(window.start);
+        if elapsed < self.config.window {
+            return;
+        }
+        let skipped = elapsed.as_nanos() / self.config.window.as_nanos();
+        let skipped = u32::try_from(skipped).unwrap_or(u32::MAX);
+        window.start = self
+            .config
+            .window
+            .checked_mul(skipped)
+            .and_then(|offset| window.start.checked_add(offset))
+            .unwrap_or(now);
+        window.count = 0;
+    }
+
+    fn window(&self) -> MutexGuard<'_, FixedWindow> {
+        self.window.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl RateLimiter for FixedWindowLimiter {
+    fn try_acquire(&self) -> Result<(), RateLimited> {
+        let now = self.clock.now();
+        let mut window = self.window();
+        self.advance(&mut window, now);
+        if window.count >= self.config.limit {
+            let retry_after = (window.start + self.config.window).saturating_duration_since(now);
+            return Err(rejected(&self.config, retry_after));
+        }
+        window.count += 1;
+        Ok(())
+    }
+}
+
+/// A rate limiter admitting at most `limit` calls in any interval of length `window`.
+///
+/// The limiter keeps the time of every admitted call still inside the window, so unlike the
+/// `FixedWindowLimiter`, no burst gets through around window boundaries: a call is admitted only
+/// if fewer than `limit` calls were admitted during the `window` that precedes it. Calls older
+/// than the window are pruned on every check, and rejected calls are not recorded, so the log
+/// never holds more than `limit` entries.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::config::RateLimitConfig;
+/// use resilient_rs::ratelimit::{RateLimiter, SlidingLogLimiter};
+///
+/// let limiter = SlidingLogLimiter::new(RateLimitConfig::new(1, Duration::from_secs(1)));
+/// assert!(limiter.try_acquire().is_ok());
+/// assert!(limiter.try_acquire().is_err());
+/// ```
+pub struct SlidingLogLimiter {
+    config: RateLimitConfig,
+    clock: Arc<dyn Clock>,
+    admitted: Mutex<VecDeque<Instant>>,
+}
+
+impl SlidingLogLimiter {
+    /// Creates a limiter with an empty log.
+    ///
+    /// # Arguments
+    /// * `config` - The limit and window length.
+    ///
+    /// # Panics
+    /// Panics if `config` is invalid, e.g. a literal with a `limit` of 0. Use `try_new` to get a
+    /// `ConfigError` instead.
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self::try_new(config).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates a limiter, returning an error instead of panicking on an invalid `config`.
+    ///
+    /// # Returns
+    /// The limiter, or the `ConfigError` reported by `RateLimitConfig::validate`.
+    pub fn try_new(config: RateLimitConfig) -> Result<Self, ConfigError> {
+        config.validate()?;
+        Ok(SlidingLogLimiter {
+            config,
+            clock: Arc::new(SystemClock),
+            admitted: Mutex::new(VecDeque::with_capacity(config.limit)),
+        })
+    }
+
+    /// Reads the time from `clock` instead of the system clock, and returns the modified limiter.
+    ///
+    /// # Arguments
+    /// * `clock` - The clock timestamping the calls, typically a manual clock in tests.
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// Returns the number of calls that would be admitted right now.
+    pub fn remaining(&self) -> usize {
+        let now = self.clock.now();
+        let mut admitted = self.admitted();
+        self.prune(&mut admitted, now);
+        self.config.limit.saturating_sub(admitted.len())
+    }
+
+    /// Forgets the calls admitted more than `window` before `now`.
+    fn prune(&self, admitted: &mut VecDeque<Instant>, now: Instant) {
+        while let Some(oldest) = admitted.front()
+            && now.saturating_duration_since(*oldest) >= self.config.window
+        {
+            admitted.pop_front();
+        }
+    }
+
+    fn admitted(&self) -> MutexGuard<'_, VecDeque<Instant>> {
+        self.admitted.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl RateLimiter for SlidingLogLimiter {
+    fn try_acquire(&self) -> Result<(), RateLimited> {
+        let now = self.clock.now();
+        let mut admitted = self.admitted();
+        self.prune(&mut admitted, now);
+        if admitted.len() >= self.config.limit {
+            // The oldest call leaving the window frees the next slot.
+            let oldest = admitted[0];
+            let retry_after = (oldest + self.config.window).saturating_duration_since(now);
+            return Err(rejected(&self.config, retry_after));
+        }
+        admitted.push_back(now);
+        Ok(())
+    }
+}
+
+/// Logs and counts a call rejected by a rate limiter.
+fn rejected(config: &RateLimitConfig, retry_after: Duration) -> RateLimited {
+    log_with!(
+        config.log,
+        config.log.level,
+        "Rate limit of {} calls per {:?} reached.. Request is blocked for {:?}",
+        config.limit,
+        config.window,
+        retry_after
+    );
+    metrics::increment(&metrics::RATE_LIMIT_REJECTIONS);
+    events::emit(ResilienceEvent::RateLimited { retry_after });
+    RateLimited { retry_after }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::VirtualClock;
+
+    #[test]
+    fn test_fixed_window_resets_at_boundaries() {
+        let clock = Arc::new(VirtualClock::new());
+        let limiter = FixedWindowLimiter::new(RateLimitConfig::new(2, Duration::from_secs(60)))
+            .with_clock(clock.clone());
+
+        clock.advance(Duration::from_secs(50));
+        assert!(limiter.try_acquire().is_ok());
+        assert!(limiter.try_acquire().is_ok());
+        assert_eq!(
+            limiter.try_acquire(),
+            Err(RateLimited {
+                retry_after: Duration::from_secs(10)
+            })
+        );
+
+        // A new window starts at the boundary, admitting a burst right after the previous one.
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(limiter.remaining(), 2);
+        assert!(limiter.try_acquire().is_ok());
+        assert!(limiter.try_acquire().is_ok());
+        assert!(limiter.try_acquire().is_err());
+
+        // Idle windows are skipped without shifting the boundaries.
+        clock.advance(Duration::from_secs(150));
+        assert!(limiter.try_acquire().is_ok());
+        assert!(limiter.try_acquire().is_ok());
+        assert_eq!(
+            limiter.try_acquire().unwrap_err().retry_after,
+            Duration::from_secs(30)
+        );
+    }
+
+    #[test]
+    fn test_fixed_window_rejects_a_zero_window() {
+        let config = RateLimitConfig {
+            window: Duration::ZERO,
+            ..RateLimitConfig::default()
+        };
+        let error = FixedWindowLimiter::try_new(config).err().unwrap();
+        assert!(error.to_string().contains("window"));
+    }
+
+    #[test]
+    fn test_sliding_log_has_no_boundary_burst() {
+        let clock = Arc::new(VirtualClock::new());
+        let limiter = SlidingLogLimiter::new(RateLimitConfig::new(2, Duration::from_secs(60)))
+            .with_clock(clock.clone());
+
+        assert!(limiter.try_acquire().is_ok());
+        clock.advance(Duration::from_secs(50));
+        assert!(limiter.try_acquire().is_ok());
+        assert_eq!(
+            limiter.try_acquire(),
+            Err(RateLimited {
+                retry_after: Duration::from_secs(10)
+            })
+        );
+
+        // Where a fixed window would reset, only the call made 60 seconds ago has expired.
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(limiter.remaining(), 1);
+        assert!(limiter.try_acquire().is_ok());
+        assert_eq!(
+            limiter.try_acquire().unwrap_err().retry_after,
+            Duration::from_secs(50)
+        );
+        assert_eq!(limiter.admitted().len(), 2);
+
+        clock.advance(Duration::from_secs(120));
+        assert_eq!(limiter.remaining(), 2);
+        assert!(limiter.admitted().is_empty());
+    }
+
+    #[test]
+    fn test_sliding_log_rejects_a_zero_limit() {
+        let config = RateLimitConfig {
+            limit: 0,
+            ..RateLimitConfig::default()
+        };
+        let error = SlidingLogLimiter::try_new(config).err().unwrap();
+        assert!(error.to_string().contains("limit"));
+    }
+}
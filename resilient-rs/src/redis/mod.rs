@@ -0,0 +1,292 @@
+use crate::asynchronous::{GuardedRetry, Step};
+use crate::classifier::{ErrorClass, ErrorClassifier};
+use crate::config::{CircuitBreakerConfig, RetryConfig};
+use crate::synchronous::{CircuitBreaker, back_off};
+use ::redis::{Client, Connection, ErrorKind, RedisError, RedisResult, RetryMethod};
+use std::fmt;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+/// An `ErrorClassifier` for `redis::RedisError`.
+///
+/// - Connection errors (refused, reset or dropped connections, timeouts) and parse errors, after
+///   which the connection is reopened, are `Transient`.
+/// - `LOADING`, `TRYAGAIN`, `CLUSTERDOWN` and `MASTERDOWN` replies, sent while a node is starting
+///   or failing over, are `Transient`.
+/// - `MOVED` and `ASK` redirections are `Transient`, since a cluster's slots are only briefly in
+///   flux while it is resharded.
+/// - Authentication failures are `Fatal`: the node will reject every attempt until the
+///   configuration is fixed.
+/// - Every other error, such as `WRONGTYPE` or a `NOSCRIPT` reply, is `Permanent`.
+///
+/// # Example
+/// ```
+/// use resilient_rs::classifier::{ErrorClass, ErrorClassifier};
+/// use resilient_rs::redis::RedisClassifier;
+///
+/// let loading = redis::RedisError::from((redis::ErrorKind::BusyLoadingError, "LOADING"));
+/// assert_eq!(RedisClassifier.classify(&loading), ErrorClass::Transient);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RedisClassifier;
+
+impl ErrorClassifier<RedisError> for RedisClassifier {
+    fn classify(&self, error: &RedisError) -> ErrorClass {
+        if error.kind() == ErrorKind::AuthenticationFailed {
+            return ErrorClass::Fatal;
+        }
+        match error.retry_method() {
+            RetryMethod::NoRetry => ErrorClass::Permanent,
+            _ => ErrorClass::Transient,
+        }
+    }
+}
+
+/// The error returned by `ResilientRedis::run`.
+#[derive(Debug)]
+pub enum RedisCallError {
+    /// The circuit breaker of the node rejected the command, which was not sent.
+    CircuitOpen {
+        node: String,
+        retry_after: Option<Duration>,
+    },
+    /// The command failed after the retries allowed for it.
+    Redis(RedisError),
+}
+
+impl RedisCallError {
+    /// Returns `true` if the command was rejected by the circuit breaker without being sent.
+    pub fn is_circuit_open(&self) -> bool {
+        matches!(self, RedisCallError::CircuitOpen { .. })
+    }
+
+    /// Returns the error of the last attempt, or `None` if the command was rejected.
+    pub fn into_inner(self) -> Option<RedisError> {
+        match self {
+            RedisCallError::Redis(err) => Some(err),
+            RedisCallError::CircuitOpen { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for RedisCallError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RedisCallError::CircuitOpen { node, .. } => write!(
+                f,
+                "Circuit Breaker for {} rejected the command. Please try later..!",
+                node
+            ),
+            RedisCallError::Redis(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl std::error::Error for RedisCallError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RedisCallError::Redis(err) => Some(err),
+            RedisCallError::CircuitOpen { .. } => None,
+        }
+    }
+}
+
+/// A Redis node whose commands are retried with backoff, reconnecting between attempts when the
+/// connection broke, and optionally guarded by a circuit breaker.
+///
+/// A single connection is opened lazily and reused. After a connection error, it is dropped and a
+/// new one is opened by the next attempt; errors replied by the node (e.g. `LOADING`) keep the
+/// connection open.
+///
+/// Errors are classified by the `error_classifier` or `retry_condition` of the `RetryConfig` when
+/// one is set, and by `RedisClassifier` otherwise. With `with_circuit_breaker`, only the errors
+/// `RedisClassifier` does not consider `Permanent` count as failures of the node, so a
+/// `WRONGTYPE` reply never opens the circuit.
+///
+/// This type is available with the `redis` feature.
+///
+/// # Example
+/// ```no_run
+/// use std::time::Duration;
+/// use redis::Commands;
+/// use resilient_rs::config::{CircuitBreakerConfig, RetryConfig};
+/// use resilient_rs::redis::ResilientRedis;
+///
+/// let redis = ResilientRedis::open("redis://127.0.0.1/", RetryConfig::default())
+///     .unwrap()
+///     .with_circuit_breaker(CircuitBreakerConfig::new(1, 5, Duration::from_secs(30)));
+///
+/// let visits: u64 = redis.run(|connection| connection.incr("visits", 1)).unwrap();
+/// ```
+pub struct ResilientRedis {
+    client: Client,
+    node: String,
+    retry: RetryConfig<RedisError>,
+    breaker: Option<Arc<CircuitBreaker<RedisError>>>,
+    connection: Mutex<Option<Connection>>,
+}
+
+impl ResilientRedis {
+    /// Creates a wrapper for the Redis node at `info`, e.g. `"redis://127.0.0.1/"`.
+    ///
+    /// No connection is made until the first command.
+    ///
+    /// # Arguments
+    /// * `info` - The address of the node.
+    /// * `retry` - The attempts, delays and strategy of the retries.
+    pub fn open(
+        info: impl ::redis::IntoConnectionInfo,
+        retry: RetryConfig<RedisError>,
+    ) -> RedisResult<Self> {
+        let client = Client::open(info)?;
+        Ok(ResilientRedis {
+            node: client.get_connection_info().addr.to_string(),
+            client,
+            retry,
+            breaker: None,
+            connection: Mutex::new(None),
+        })
+    }
+
+    /// Guards the node with a circuit breaker configured with `config`, and returns the modified
+    /// wrapper.
+    pub fn with_circuit_breaker(self, config: CircuitBreakerConfig) -> Self {
+        let breaker = CircuitBreaker::with_config(config)
+            .with_classifier(RedisClassifier)
+            .with_record_failure_if(|err| RedisClassifier.classify(err) != ErrorClass::Permanent);
+        self.with_breaker(Arc::new(breaker))
+    }
+
+    /// Guards the node with `breaker`, shared with other wrappers of the same node, and returns
+    /// the modified wrapper.
+    pub fn with_breaker(mut self, breaker: Arc<CircuitBreaker<RedisError>>) -> Self {
+        self.breaker = Some(breaker);
+        self
+    }
+
+    /// Returns the address of the node, e.g. `"127.0.0.1:6379"`.
+    pub fn node(&self) -> &str {
+        &self.node
+    }
+
+    /// Runs a command, or any function of a connection such as a pipeline, retrying it on
+    /// retryable errors.
+    ///
+    /// `command` may be called once per attempt, so it must be safe to repeat; use `MULTI`/`EXEC`
+    /// or idempotent commands for writes.
+    ///
+    /// # Returns
+    /// - `Ok(T)` with the result of the first successful attempt.
+    /// - `Err(RedisCallError::CircuitOpen)` if the node's breaker rejected the command.
+    /// - `Err(RedisCallError::Redis)` with the last error otherwise.
+    pub fn run<F, T>(&self, mut command: F) -> Result<T, RedisCallError>
+    where
+        F: FnMut(&mut Connection) -> RedisResult<T>,
+    {
+        let mut retry = GuardedRetry::new(
+            &self.retry,
+            self.breaker.as_deref().map(CircuitBreaker::state_machine),
+        );
+
+        loop {
+            let attempt = match retry.start() {
+                Ok(attempt) => attempt,
+                Err(retry_after) => {
+                    return Err(RedisCallError::CircuitOpen {
+                        node: self.node.clone(),
+                        retry_after,
+                    });
+                }
+            };
+            let result = self.attempt(&mut command);
+            let step = retry.end_reported(attempt, result, |err| {
+                if self.retry.error_classifier.is_some() || self.retry.retry_condition.is_some() {
+                    self.retry.classify(err)
+                } else {
+                    RedisClassifier.classify(err)
+                }
+            });
+            let wait = match step {
+                Step::Done(result) => return result.map_err(RedisCallError::Redis),
+                Step::Retry(wait) => wait,
+            };
+            back_off(None, wait);
+        }
+    }
+
+    /// Runs one attempt on the shared connection, opening it if needed and dropping it after a
+    /// connection error.
+    fn attempt<F, T>(&self, command: &mut F) -> RedisResult<T>
+    where
+        F: FnMut(&mut Connection) -> RedisResult<T>,
+    {
+        let mut connection = self
+            .connection
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        if connection.is_none() {
+            *connection = Some(self.client.get_connection()?);
+        }
+        let result = command(connection.as_mut().expect("connection was just opened"));
+        if let Err(err) = &result
+            && (err.is_unrecoverable_error() || err.is_io_error())
+        {
+            *connection = None;
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::RetryStrategy;
+
+    fn retry_config() -> RetryConfig<RedisError> {
+        RetryConfig::new(3, Duration::from_millis(1), RetryStrategy::Linear)
+    }
+
+    #[test]
+    fn test_classifies_redis_errors() {
+        let error = |kind| RedisError::from((kind, "reply"));
+        assert_eq!(
+            RedisClassifier.classify(&error(ErrorKind::BusyLoadingError)),
+            ErrorClass::Transient
+        );
+        assert_eq!(
+            RedisClassifier.classify(&error(ErrorKind::Moved)),
+            ErrorClass::Transient
+        );
+        assert_eq!(
+            RedisClassifier.classify(&error(ErrorKind::TypeError)),
+            ErrorClass::Permanent
+        );
+        assert_eq!(
+            RedisClassifier.classify(&error(ErrorKind::AuthenticationFailed)),
+            ErrorClass::Fatal
+        );
+        let refused = RedisError::from(std::io::Error::from(std::io::ErrorKind::ConnectionRefused));
+        assert_eq!(RedisClassifier.classify(&refused), ErrorClass::Transient);
+    }
+
+    #[test]
+    fn test_refused_connections_are_retried_until_the_circuit_opens() {
+        // Nothing listens on port 1, so every attempt fails to connect.
+        let redis = ResilientRedis::open("redis://127.0.0.1:1/", retry_config())
+            .unwrap()
+            .with_circuit_breaker(CircuitBreakerConfig::new(1, 2, Duration::from_secs(30)));
+        let mut calls = 0;
+
+        let err = redis
+            .run(|connection| {
+                calls += 1;
+                ::redis::cmd("PING").query::<String>(connection)
+            })
+            .unwrap_err();
+        assert!(!err.is_circuit_open());
+        assert_eq!(calls, 0);
+
+        let err = redis.run(|connection| ::redis::cmd("PING").query::<String>(connection));
+        assert!(err.unwrap_err().is_circuit_open());
+    }
+}
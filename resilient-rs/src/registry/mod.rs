@@ -0,0 +1,560 @@
+use crate::asynchronous::{self, CircuitBreaker, CircuitBreakerError};
+use crate::config::{
+    BoxError, CircuitBreakerConfig, ExecConfig, LogConfig, RetryConfig, RetryPolicy,
+};
+use crate::ratelimit::RateLimiter;
+use std::any::Any;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::Duration;
+
+#[cfg(feature = "config-file")]
+mod profile;
+
+/// The error returned by `PolicyRegistry::retry`, `PolicyRegistry::call` and
+/// `PolicyRegistry::timeout`.
+///
+/// It tells apart a lookup of a policy that was never registered, usually a typo or a missing
+/// profile entry, from a failure of the operation itself.
+///
+/// # Example
+/// ```
+/// use async_std::task::block_on;
+/// use resilient_rs::registry::{PolicyRegistry, RegistryError};
+///
+/// let registry = PolicyRegistry::new();
+/// let result = block_on(registry.retry("missing", || async { Ok::<_, String>(()) }));
+/// assert!(matches!(result, Err(RegistryError::UnknownPolicy { .. })));
+/// ```
+#[derive(Debug, PartialEq)]
+pub enum RegistryError<E> {
+    /// No policy of this `kind` is registered under `name`, so the operation was not run.
+    UnknownPolicy { kind: &'static str, name: String },
+    /// The operation ran and failed with this error.
+    Inner(E),
+}
+
+impl<E> RegistryError<E> {
+    /// Returns `true` if no policy was registered under the requested name.
+    pub fn is_unknown_policy(&self) -> bool {
+        matches!(self, RegistryError::UnknownPolicy { .. })
+    }
+
+    /// Returns the error of the operation, or `None` if the policy was unknown.
+    pub fn into_inner(self) -> Option<E> {
+        match self {
+            RegistryError::Inner(err) => Some(err),
+            RegistryError::UnknownPolicy { .. } => None,
+        }
+    }
+
+    fn unknown(kind: &'static str, name: &str) -> Self {
+        RegistryError::UnknownPolicy {
+            kind,
+            name: name.to_string(),
+        }
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for RegistryError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RegistryError::UnknownPolicy { kind, name } => {
+                write!(f, "No {} named `{}` is registered", kind, name)
+            }
+            RegistryError::Inner(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> Error for RegistryError<E> {}
+
+/// A registry of named retry, circuit breaker, timeout and rate limit policies.
+///
+/// Policies are registered once, typically at startup, under names such as `"payments-api"` or
+/// `"s3-upload"`, and can then be used from anywhere in the application without passing
+/// configuration references through every layer. Use `PolicyRegistry::global()` for a
+/// process-wide registry, or create your own and share it behind an `Arc`.
+///
+/// Each name designates one circuit breaker instance, so every caller using the same breaker
+/// name shares its state; the same goes for rate limiters.
+///
+/// With the `config-file` feature, the policies can be loaded from a `resilience.toml` profile
+/// with `load_profile`.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use async_std::task::block_on;
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::registry::PolicyRegistry;
+/// use resilient_rs::strategies::RetryStrategy;
+///
+/// let registry = PolicyRegistry::new();
+/// registry.register_retry(
+///     "payments-api",
+///     RetryConfig::<String>::new(3, Duration::from_millis(10), RetryStrategy::ExponentialBackoff),
+/// );
+///
+/// let result = block_on(registry.retry("payments-api", || async { Ok::<_, String>("charged") }));
+/// assert_eq!(result, Ok("charged"));
+/// ```
+#[derive(Default)]
+pub struct PolicyRegistry {
+    retries: RwLock<HashMap<String, Arc<dyn Any + Send + Sync>>>,
+    breakers: RwLock<HashMap<String, Arc<CircuitBreaker>>>,
+    timeouts: RwLock<HashMap<String, Duration>>,
+    rate_limiters: RwLock<HashMap<String, Arc<dyn RateLimiter>>>,
+}
+
+impl PolicyRegistry {
+    /// Creates an empty registry.
+    pub fn new() -> Self {
+        PolicyRegistry::default()
+    }
+
+    /// Returns the process-wide registry.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use resilient_rs::registry::PolicyRegistry;
+    ///
+    /// PolicyRegistry::global().register_timeout("search", Duration::from_millis(300));
+    /// assert_eq!(PolicyRegistry::global().timeout_for("search"), Some(Duration::from_millis(300)));
+    /// ```
+    pub fn global() -> &'static PolicyRegistry {
+        static GLOBAL: OnceLock<PolicyRegistry> = OnceLock::new();
+        GLOBAL.get_or_init(PolicyRegistry::new)
+    }
+
+    /// Registers a retry policy under `name`, replacing any previous policy with that name.
+    ///
+    /// # Arguments
+    /// * `name` - The name used to look the policy up.
+    /// * `config` - The retry configuration for operations failing with errors of type `E`.
+    pub fn register_retry<E: 'static>(&self, name: impl Into<String>, config: RetryConfig<E>) {
+        self.retries
+            .write()
+            .unwrap()
+            .insert(name.into(), Arc::new(config));
+    }
+
+    /// Registers a type-erased retry policy under `name`, replacing any previous retry policy
+    /// with that name.
+    ///
+    /// Unlike `register_retry`, the policy is not tied to an error type, so it can be looked up
+    /// with `retry_policy` and applied to operations failing with any error type.
+    ///
+    /// # Arguments
+    /// * `name` - The name used to look the policy up.
+    /// * `policy` - The retry policy, e.g. converted from a `RetryConfig` with `into()`.
+    pub fn register_retry_policy(&self, name: impl Into<String>, policy: RetryPolicy) {
+        self.retries
+            .write()
+            .unwrap()
+            .insert(name.into(), Arc::new(policy));
+    }
+
+    /// Registers a circuit breaker under `name`, replacing any previous breaker with that name.
+    ///
+    /// # Arguments
+    /// * `name` - The name used to look the breaker up.
+    /// * `breaker` - The circuit breaker, e.g. `CircuitBreaker::new(config).with_stats(stats)`.
+    pub fn register_breaker(&self, name: impl Into<String>, breaker: CircuitBreaker) {
+        self.breakers
+            .write()
+            .unwrap()
+            .insert(name.into(), Arc::new(breaker));
+    }
+
+    /// Registers a circuit breaker with the given configuration under `name`.
+    ///
+    /// # Arguments
+    /// * `name` - The name used to look the breaker up.
+    /// * `config` - The configuration of the new circuit breaker.
+    pub fn register_breaker_config(&self, name: impl Into<String>, config: CircuitBreakerConfig) {
+        self.register_breaker(name, CircuitBreaker::new(config));
+    }
+
+    /// Registers a timeout under `name`, replacing any previous timeout with that name.
+    ///
+    /// # Arguments
+    /// * `name` - The name used to look the timeout up.
+    /// * `timeout` - The maximum duration of operations run with `timeout`.
+    pub fn register_timeout(&self, name: impl Into<String>, timeout: Duration) {
+        self.timeouts.write().unwrap().insert(name.into(), timeout);
+    }
+
+    /// Registers a rate limiter under `name`, replacing any previous rate limiter with that name.
+    ///
+    /// # Arguments
+    /// * `name` - The name used to look the rate limiter up.
+    /// * `rate_limiter` - The rate limiter, shared by every caller using `name`.
+    ///
+    /// # Example
+    /// ```
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    /// use resilient_rs::config::RateLimitConfig;
+    /// use resilient_rs::ratelimit::FixedWindowLimiter;
+    /// use resilient_rs::registry::PolicyRegistry;
+    ///
+    /// let registry = PolicyRegistry::new();
+    /// let config = RateLimitConfig::new(1, Duration::from_secs(60));
+    /// registry.register_rate_limiter("geocoding", Arc::new(FixedWindowLimiter::new(config)));
+    ///
+    /// let limiter = registry.rate_limiter("geocoding").unwrap();
+    /// assert!(limiter.try_acquire().is_ok());
+    /// assert!(limiter.try_acquire().is_err());
+    /// ```
+    pub fn register_rate_limiter(
+        &self,
+        name: impl Into<String>,
+        rate_limiter: Arc<dyn RateLimiter>,
+    ) {
+        self.rate_limiters
+            .write()
+            .unwrap()
+            .insert(name.into(), rate_limiter);
+    }
+
+    /// Returns the retry policy registered under `name` for errors of type `E`.
+    ///
+    /// # Returns
+    /// `None` if no policy is registered under `name`, or if it was registered for another error type.
+    pub fn retry_config<E: 'static>(&self, name: &str) -> Option<Arc<RetryConfig<E>>> {
+        let config = self.retries.read().unwrap().get(name)?.clone();
+        config.downcast::<RetryConfig<E>>().ok()
+    }
+
+    /// Returns the type-erased retry policy registered under `name`.
+    ///
+    /// # Returns
+    /// `None` if no policy is registered under `name`, or if it was registered with
+    /// `register_retry` for a specific error type.
+    ///
+    /// # Example
+    /// ```
+    /// use std::io;
+    /// use std::time::Duration;
+    /// use resilient_rs::config::RetryConfig;
+    /// use resilient_rs::registry::PolicyRegistry;
+    /// use resilient_rs::strategies::RetryStrategy;
+    ///
+    /// let registry = PolicyRegistry::new();
+    /// let config = RetryConfig::<io::Error>::new(4, Duration::from_millis(5), RetryStrategy::Linear);
+    /// registry.register_retry_policy("disk", config.into());
+    ///
+    /// let policy = registry.retry_policy("disk").unwrap();
+    /// let config = policy.to_config::<std::fmt::Error>();
+    /// assert_eq!(config.max_attempts(), 4);
+    /// ```
+    pub fn retry_policy(&self, name: &str) -> Option<Arc<RetryPolicy>> {
+        let policy = self.retries.read().unwrap().get(name)?.clone();
+        policy.downcast::<RetryPolicy>().ok()
+    }
+
+    /// Returns the circuit breaker registered under `name`.
+    pub fn breaker(&self, name: &str) -> Option<Arc<CircuitBreaker>> {
+        self.breakers.read().unwrap().get(name).cloned()
+    }
+
+    /// Returns the timeout registered under `name`.
+    pub fn timeout_for(&self, name: &str) -> Option<Duration> {
+        self.timeouts.read().unwrap().get(name).copied()
+    }
+
+    /// Returns the rate limiter registered under `name`.
+    pub fn rate_limiter(&self, name: &str) -> Option<Arc<dyn RateLimiter>> {
+        self.rate_limiters.read().unwrap().get(name).cloned()
+    }
+
+    /// Retries an asynchronous operation with the retry policy registered under `name`.
+    ///
+    /// # Returns
+    /// - `Ok(T)` if an attempt succeeds.
+    /// - `Err(RegistryError::Inner(E))` with the error of the last attempt if the retries fail.
+    /// - `Err(RegistryError::UnknownPolicy)` if no retry policy for errors of type `E` is
+    ///   registered under `name`.
+    pub async fn retry<F, Fut, T, E>(&self, name: &str, operation: F) -> Result<T, RegistryError<E>>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: 'static,
+    {
+        let config = self
+            .retry_config::<E>(name)
+            .ok_or_else(|| RegistryError::unknown("retry policy", name))?;
+        asynchronous::retry(operation, &config)
+            .await
+            .map_err(RegistryError::Inner)
+    }
+
+    /// Runs an asynchronous operation through the circuit breaker registered under `name`.
+    ///
+    /// Concurrent calls through the same breaker run in parallel, within the breaker's
+    /// `max_concurrent_calls` limit.
+    ///
+    /// # Returns
+    /// - `Ok(T)` if the operation succeeds.
+    /// - `Err(RegistryError::Inner(CircuitBreakerError))` if the operation fails or the breaker
+    ///   rejects the call.
+    /// - `Err(RegistryError::UnknownPolicy)` if no circuit breaker is registered under `name`.
+    pub async fn call<F, Fut, T>(
+        &self,
+        name: &str,
+        operation: F,
+    ) -> Result<T, RegistryError<CircuitBreakerError<BoxError>>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, BoxError>>,
+    {
+        let breaker = self
+            .breaker(name)
+            .ok_or_else(|| RegistryError::unknown("circuit breaker", name))?;
+        breaker.run(operation).await.map_err(RegistryError::Inner)
+    }
+
+    /// Runs an asynchronous operation with the timeout registered under `name`.
+    ///
+    /// # Returns
+    /// - `Ok(T)` if the operation completes in time and succeeds.
+    /// - `Err(RegistryError::Inner(BoxError))` if the operation fails or times out.
+    /// - `Err(RegistryError::UnknownPolicy)` if no timeout is registered under `name`.
+    pub async fn timeout<T>(
+        &self,
+        name: &str,
+        operation: impl Future<Output = Result<T, BoxError>>,
+    ) -> Result<T, RegistryError<BoxError>> {
+        let timeout_duration = self
+            .timeout_for(name)
+            .ok_or_else(|| RegistryError::unknown("timeout", name))?;
+        let exec_config = ExecConfig {
+            timeout_duration,
+            fallback: None,
+            log: LogConfig::default(),
+        };
+        asynchronous::execute_with_fallback(operation, &exec_config)
+            .await
+            .map_err(RegistryError::Inner)
+    }
+}
+
+/// A registry handing out shared circuit breakers by name.
+///
+/// Services calling many dependencies typically want one breaker per dependency, all with sensible
+/// defaults and a few tuned exceptions. The registry creates each breaker on first use, keeps it
+/// for the lifetime of the registry and returns the same instance to every caller asking for that
+/// name, so breaker lifetimes do not have to be managed by hand.
+///
+/// Breakers are created with the registry's default configuration, unless an override was
+/// registered for their name with `with_override`, or a configuration is passed to `get_or_create`.
+///
+/// The breakers are handed out as `Arc<CircuitBreaker>`, whose methods take `&self`, so callers
+/// sharing a breaker run their calls concurrently instead of waiting for each other.
+///
+/// # Example
+/// ```
+/// use std::error::Error;
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use async_std::task::block_on;
+/// use resilient_rs::config::CircuitBreakerConfig;
+/// use resilient_rs::registry::CircuitBreakerRegistry;
+///
+/// let registry = CircuitBreakerRegistry::new(CircuitBreakerConfig::default())
+///     .with_override("payments-api", CircuitBreakerConfig::new(1, 2, Duration::from_secs(30)));
+///
+/// // Every caller asking for "inventory-service" gets the same breaker.
+/// let inventory = registry.get("inventory-service");
+/// let result = block_on(inventory.run(|| async { Ok::<_, Box<dyn Error + Send + Sync>>(42) }));
+/// assert_eq!(result.unwrap(), 42);
+/// assert!(Arc::ptr_eq(&inventory, &registry.get("inventory-service")));
+/// ```
+pub struct CircuitBreakerRegistry {
+    default_config: CircuitBreakerConfig,
+    overrides: HashMap<String, CircuitBreakerConfig>,
+    breakers: RwLock<HashMap<String, Arc<CircuitBreaker>>>,
+}
+
+impl CircuitBreakerRegistry {
+    /// Creates an empty registry creating breakers with `default_config`.
+    pub fn new(default_config: CircuitBreakerConfig) -> Self {
+        CircuitBreakerRegistry {
+            default_config,
+            overrides: HashMap::new(),
+            breakers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Registers the configuration used for the breaker named `name` and returns the modified registry.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the breaker, e.g. the dependency it protects.
+    /// * `config` - The configuration used instead of the default when the breaker is created.
+    pub fn with_override(mut self, name: impl Into<String>, config: CircuitBreakerConfig) -> Self {
+        self.overrides.insert(name.into(), config);
+        self
+    }
+
+    /// Returns the breaker named `name`, creating it with the default or overridden configuration.
+    pub fn get(&self, name: &str) -> Arc<CircuitBreaker> {
+        let config = self
+            .overrides
+            .get(name)
+            .copied()
+            .unwrap_or(self.default_config);
+        self.get_or_create(name, config)
+    }
+
+    /// Returns the breaker named `name`, creating it with `config` if it does not exist yet.
+    ///
+    /// `config` is only used when the breaker is created; an existing breaker keeps its
+    /// configuration and state.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the breaker, e.g. the dependency it protects.
+    /// * `config` - The configuration of the breaker if it has to be created.
+    pub fn get_or_create(&self, name: &str, config: CircuitBreakerConfig) -> Arc<CircuitBreaker> {
+        if let Some(breaker) = self.breakers.read().unwrap().get(name) {
+            return breaker.clone();
+        }
+        self.breakers
+            .write()
+            .unwrap()
+            .entry(name.to_string())
+            .or_insert_with(|| Arc::new(CircuitBreaker::new(config)))
+            .clone()
+    }
+
+    /// Returns the names of the breakers created so far.
+    pub fn names(&self) -> Vec<String> {
+        self.breakers.read().unwrap().keys().cloned().collect()
+    }
+
+    /// Returns the breakers created so far.
+    pub(crate) fn breakers(&self) -> Vec<Arc<CircuitBreaker>> {
+        self.breakers.read().unwrap().values().cloned().collect()
+    }
+}
+
+impl Default for CircuitBreakerRegistry {
+    fn default() -> Self {
+        CircuitBreakerRegistry::new(CircuitBreakerConfig::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asynchronous::CircuitBreakerState;
+    use crate::strategies::RetryStrategy;
+    use async_std::task::{self, block_on, sleep};
+
+    #[test]
+    fn test_retry_config_lookup_checks_error_type() {
+        let registry = PolicyRegistry::new();
+        registry.register_retry(
+            "s3-upload",
+            RetryConfig::<String>::new(4, Duration::from_millis(1), RetryStrategy::Linear),
+        );
+        assert_eq!(
+            registry
+                .retry_config::<String>("s3-upload")
+                .unwrap()
+                .max_attempts,
+            4
+        );
+        assert!(registry.retry_config::<i32>("s3-upload").is_none());
+        assert!(registry.retry_config::<String>("unknown").is_none());
+    }
+
+    #[test]
+    fn test_named_breaker_is_shared() {
+        let registry = PolicyRegistry::new();
+        registry.register_breaker_config(
+            "inventory",
+            CircuitBreakerConfig::new(1, 1, Duration::from_secs(60)),
+        );
+        let _ =
+            block_on(registry.call("inventory", || async { Err::<(), BoxError>("down".into()) }));
+        let result = block_on(registry.call("inventory", || async { Ok(()) }));
+        assert!(result.unwrap_err().into_inner().unwrap().is_open());
+    }
+
+    #[test]
+    fn test_named_timeout() {
+        let registry = PolicyRegistry::new();
+        registry.register_timeout("search", Duration::from_millis(10));
+        let result = block_on(registry.timeout("search", async {
+            sleep(Duration::from_millis(100)).await;
+            Ok(())
+        }));
+        assert!(matches!(result, Err(RegistryError::Inner(_))));
+    }
+
+    #[test]
+    fn test_unknown_names_are_reported_without_running_the_operation() {
+        let registry = PolicyRegistry::new();
+        let result = block_on(registry.retry("missing", || async { Ok::<(), String>(()) }));
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "No retry policy named `missing` is registered"
+        );
+        let result = block_on(registry.call("missing", || async { Ok(()) }));
+        assert!(result.unwrap_err().is_unknown_policy());
+        let result = block_on(registry.timeout("missing", async { Ok(()) }));
+        assert!(result.unwrap_err().is_unknown_policy());
+    }
+
+    #[test]
+    fn test_calls_through_a_named_breaker_are_not_serialized() {
+        let registry = Arc::new(PolicyRegistry::new());
+        registry.register_breaker_config("inventory", CircuitBreakerConfig::default());
+        let (started_tx, started_rx) = async_std::channel::bounded::<()>(1);
+        let (release_tx, release_rx) = async_std::channel::bounded::<()>(1);
+
+        block_on(async {
+            let slow = task::spawn({
+                let registry = registry.clone();
+                async move {
+                    registry
+                        .call("inventory", || async move {
+                            started_tx.send(()).await.unwrap();
+                            release_rx.recv().await.unwrap();
+                            Ok("slow")
+                        })
+                        .await
+                }
+            });
+            started_rx.recv().await.unwrap();
+            // The slow call is in flight, and does not keep the breaker locked.
+            let fast = registry.call("inventory", || async { Ok("fast") }).await;
+            assert_eq!(fast.unwrap(), "fast");
+            release_tx.send(()).await.unwrap();
+            assert_eq!(slow.await.unwrap(), "slow");
+        });
+    }
+
+    #[test]
+    fn test_breaker_registry_shares_breakers_by_name() {
+        let registry = CircuitBreakerRegistry::default().with_override(
+            "inventory-service",
+            CircuitBreakerConfig::new(1, 1, Duration::from_secs(60)),
+        );
+        let breaker = registry.get("inventory-service");
+        let _ = block_on(breaker.run(|| async { Err::<(), BoxError>("down".into()) }));
+        let again = registry.get_or_create("inventory-service", CircuitBreakerConfig::default());
+        assert!(Arc::ptr_eq(&breaker, &again));
+        assert_eq!(again.state(), CircuitBreakerState::Open);
+
+        let other = registry.get("search");
+        assert!(!Arc::ptr_eq(&breaker, &other));
+        let mut names = registry.names();
+        names.sort();
+        assert_eq!(names, ["inventory-service", "search"]);
+    }
+}
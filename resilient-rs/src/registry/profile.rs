@@ -0,0 +1,466 @@
+use super::PolicyRegistry;
+use crate::config::{CircuitBreakerConfig, ConfigError, RateLimitConfig, RetryConfig, RetryPolicy};
+use crate::ratelimit::{FixedWindowLimiter, RateLimiter, SlidingLogLimiter};
+use crate::strategies::RetryStrategy;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use toml::Spanned;
+
+/// A `resilience.toml` profile, as deserialized before it is checked.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Profile {
+    #[serde(default)]
+    policies: BTreeMap<String, Spanned<PolicySpec>>,
+}
+
+/// The `[policies.<name>]` table of one logical operation.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PolicySpec {
+    #[serde(default, with = "humantime_serde")]
+    timeout: Option<Duration>,
+    retry: Option<Spanned<RetrySpec>>,
+    breaker: Option<Spanned<BreakerSpec>>,
+    rate_limit: Option<Spanned<RateLimitSpec>>,
+}
+
+/// The `[policies.<name>.retry]` table.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RetrySpec {
+    preset: Option<RetryPreset>,
+    max_attempts: Option<usize>,
+    #[serde(default, with = "humantime_serde")]
+    delay: Option<Duration>,
+    #[serde(default, with = "humantime_serde")]
+    max_delay: Option<Duration>,
+    strategy: Option<StrategyName>,
+    jitter_factor: Option<f64>,
+    coefficient: Option<usize>,
+    exponent: Option<u32>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum RetryPreset {
+    NetworkDefault,
+    Aggressive,
+    Conservative,
+}
+
+/// The name of a `RetryStrategy`, whose parameters are given as separate keys.
+#[derive(Deserialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum StrategyName {
+    Linear,
+    ExponentialBackoff,
+    ExponentialBackoffWithJitter,
+    FibonacciBackoff,
+    ArithmeticProgression,
+    Polynomial,
+    Harmonic,
+}
+
+impl fmt::Display for StrategyName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            StrategyName::Linear => "linear",
+            StrategyName::ExponentialBackoff => "exponential_backoff",
+            StrategyName::ExponentialBackoffWithJitter => "exponential_backoff_with_jitter",
+            StrategyName::FibonacciBackoff => "fibonacci_backoff",
+            StrategyName::ArithmeticProgression => "arithmetic_progression",
+            StrategyName::Polynomial => "polynomial",
+            StrategyName::Harmonic => "harmonic",
+        })
+    }
+}
+
+/// The `[policies.<name>.breaker]` table.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct BreakerSpec {
+    preset: Option<BreakerPreset>,
+    failure_threshold: Option<usize>,
+    success_threshold: Option<usize>,
+    #[serde(default, with = "humantime_serde")]
+    cooldown_period: Option<Duration>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum BreakerPreset {
+    Sensitive,
+    Tolerant,
+}
+
+/// The `[policies.<name>.rate_limit]` table.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RateLimitSpec {
+    limit: Option<usize>,
+    #[serde(default, with = "humantime_serde")]
+    window: Option<Duration>,
+    #[serde(default)]
+    algorithm: RateLimitAlgorithm,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+enum RateLimitAlgorithm {
+    #[default]
+    FixedWindow,
+    SlidingLog,
+}
+
+/// The settings of one named policy, before they are registered.
+#[derive(Default)]
+struct Policy {
+    retry: Option<RetryPolicy>,
+    breaker: Option<CircuitBreakerConfig>,
+    timeout: Option<Duration>,
+    rate_limiter: Option<Arc<dyn RateLimiter>>,
+}
+
+impl PolicyRegistry {
+    /// Loads the named policies of a `resilience.toml` profile into the registry.
+    ///
+    /// See `load_profile_str` for the format of the file.
+    ///
+    /// # Returns
+    /// The names of the loaded policies, or a `ConfigError::InvalidProfile` if the file cannot be
+    /// read or is invalid, in which case nothing is registered.
+    pub fn load_profile(&self, path: impl AsRef<Path>) -> Result<Vec<String>, ConfigError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|err| ConfigError::InvalidProfile {
+            line: None,
+            reason: format!("cannot read `{}`: {}", path.display(), err),
+        })?;
+        self.load_profile_str(&text)
+    }
+
+    /// Loads the named policies of a profile held in a string into the registry.
+    ///
+    /// A profile defines, for every logical operation, the retry, circuit breaker, timeout and
+    /// rate limit protecting it, all registered under the operation's name, so they can be tuned
+    /// by editing the file instead of the code:
+    /// - `[policies.<name>]` may set the `timeout`.
+    /// - `[policies.<name>.retry]` sets a type-erased retry policy, looked up with
+    ///   `retry_policy`: `preset` (`network_default`, `aggressive` or `conservative`),
+    ///   `max_attempts`, `delay`, `max_delay` and `strategy`, with its `jitter_factor`,
+    ///   `coefficient` or `exponent` when the strategy needs one.
+    /// - `[policies.<name>.breaker]` sets a circuit breaker: `preset` (`sensitive` or
+    ///   `tolerant`), `failure_threshold`, `success_threshold` and `cooldown_period`.
+    /// - `[policies.<name>.rate_limit]` sets a rate limiter, looked up with `rate_limiter`:
+    ///   `limit`, `window` and `algorithm` (`fixed_window`, the default, or `sliding_log`).
+    ///
+    /// Settings that are not given keep the preset's values, or the `Default` of the
+    /// configuration. Durations are strings such as `"250ms"`, `"2s"` or `"1m 30s"`.
+    ///
+    /// The profile is parsed with the `toml` crate and deserialized into typed tables. Unknown
+    /// tables and keys are rejected, so a typo does not silently leave a default in place. The
+    /// whole profile is checked before anything is registered; policies already registered under
+    /// the same names are replaced.
+    ///
+    /// # Returns
+    /// The names of the loaded policies, in alphabetical order, or a
+    /// `ConfigError::InvalidProfile` with the line of the first problem.
+    ///
+    /// # Example
+    /// ```
+    /// use std::time::Duration;
+    /// use resilient_rs::registry::PolicyRegistry;
+    ///
+    /// let profile = r#"
+    ///     [policies.checkout]
+    ///     timeout = "2s"
+    ///
+    ///     [policies.checkout.retry]
+    ///     preset = "network_default"
+    ///     max_attempts = 4
+    ///
+    ///     [policies.checkout.breaker]
+    ///     failure_threshold = 5
+    ///     cooldown_period = "30s"
+    ///
+    ///     [policies.checkout.rate_limit]
+    ///     limit = 100
+    ///     window = "1s"
+    /// "#;
+    ///
+    /// let registry = PolicyRegistry::new();
+    /// assert_eq!(registry.load_profile_str(profile).unwrap(), ["checkout"]);
+    /// assert_eq!(registry.timeout_for("checkout"), Some(Duration::from_secs(2)));
+    /// assert_eq!(registry.retry_policy("checkout").unwrap().max_attempts, 4);
+    /// assert!(registry.breaker("checkout").is_some());
+    /// assert!(registry.rate_limiter("checkout").is_some());
+    /// ```
+    pub fn load_profile_str(&self, profile: &str) -> Result<Vec<String>, ConfigError> {
+        let parsed: Profile =
+            toml::from_str(profile).map_err(|err| ConfigError::InvalidProfile {
+                line: err.span().map(|span| line_at(profile, span.start)),
+                reason: err.message().trim_end().to_string(),
+            })?;
+        let mut policies: BTreeMap<String, Policy> = BTreeMap::new();
+        for (name, spec) in parsed.policies {
+            let (span, spec) = (spec.span(), spec.into_inner());
+            let table = |kind: Option<&str>, span: Range<usize>| Table {
+                name: match kind {
+                    Some(kind) => format!("policies.{}.{}", name, kind),
+                    None => format!("policies.{}", name),
+                },
+                line: line_at(profile, span.start),
+            };
+            if spec.timeout == Some(Duration::ZERO) {
+                return Err(table(None, span).invalid("`timeout` must be non-zero"));
+            }
+            let retry = spec
+                .retry
+                .map(|retry| retry_policy(table(Some("retry"), retry.span()), retry.into_inner()))
+                .transpose()?;
+            let breaker = spec
+                .breaker
+                .map(|breaker| {
+                    breaker_config(table(Some("breaker"), breaker.span()), breaker.into_inner())
+                })
+                .transpose()?;
+            let rate_limiter = spec
+                .rate_limit
+                .map(|limit| {
+                    rate_limiter(table(Some("rate_limit"), limit.span()), limit.into_inner())
+                })
+                .transpose()?;
+            let policy = Policy {
+                retry,
+                breaker,
+                timeout: spec.timeout,
+                rate_limiter,
+            };
+            policies.insert(name, policy);
+        }
+
+        for (name, policy) in &policies {
+            if let Some(retry) = &policy.retry {
+                self.register_retry_policy(name.clone(), retry.clone());
+            }
+            if let Some(breaker) = policy.breaker {
+                self.register_breaker_config(name.clone(), breaker);
+            }
+            if let Some(timeout) = policy.timeout {
+                self.register_timeout(name.clone(), timeout);
+            }
+            if let Some(rate_limiter) = &policy.rate_limiter {
+                self.register_rate_limiter(name.clone(), rate_limiter.clone());
+            }
+        }
+        Ok(policies.into_keys().collect())
+    }
+}
+
+/// The name and line of a table, used to report the settings it holds that are rejected.
+struct Table {
+    name: String,
+    line: usize,
+}
+
+impl Table {
+    fn invalid(&self, reason: impl fmt::Display) -> ConfigError {
+        ConfigError::InvalidProfile {
+            line: Some(self.line),
+            reason: format!("[{}]: {}", self.name, reason),
+        }
+    }
+
+    fn require<T>(&self, value: Option<T>, key: &str, why: &str) -> Result<T, ConfigError> {
+        value.ok_or_else(|| self.invalid(format!("`{}` is required {}", key, why)))
+    }
+
+    /// Rejects a strategy parameter set for a strategy that does not use it.
+    fn unused<T>(&self, value: Option<T>, key: &str, strategy: &str) -> Result<(), ConfigError> {
+        match value {
+            None => Ok(()),
+            Some(_) => Err(self.invalid(format!(
+                "`{}` is only used with strategy `{}`",
+                key, strategy
+            ))),
+        }
+    }
+}
+
+fn retry_policy(table: Table, spec: RetrySpec) -> Result<RetryPolicy, ConfigError> {
+    // The error type is erased right away; without a classifier it plays no part.
+    let mut config: RetryConfig<fmt::Error> = match spec.preset {
+        Some(RetryPreset::NetworkDefault) => RetryConfig::network_default(),
+        Some(RetryPreset::Aggressive) => RetryConfig::aggressive(),
+        Some(RetryPreset::Conservative) => RetryConfig::conservative(),
+        None => RetryConfig::default(),
+    };
+    if let Some(max_attempts) = spec.max_attempts {
+        config.max_attempts = max_attempts;
+    }
+    if let Some(delay) = spec.delay {
+        config.delay = delay;
+    }
+    if spec.max_delay.is_some() {
+        config.max_delay = spec.max_delay;
+    }
+    let strategy = spec.strategy;
+    let why = strategy.map(|strategy| format!("with strategy `{}`", strategy));
+    if !matches!(strategy, Some(StrategyName::ExponentialBackoffWithJitter)) {
+        table.unused(
+            spec.jitter_factor,
+            "jitter_factor",
+            "exponential_backoff_with_jitter",
+        )?;
+    }
+    if !matches!(strategy, Some(StrategyName::ArithmeticProgression)) {
+        table.unused(spec.coefficient, "coefficient", "arithmetic_progression")?;
+    }
+    if !matches!(strategy, Some(StrategyName::Polynomial)) {
+        table.unused(spec.exponent, "exponent", "polynomial")?;
+    }
+    if let (Some(strategy), Some(why)) = (strategy, why) {
+        config.strategy = match strategy {
+            StrategyName::Linear => RetryStrategy::Linear,
+            StrategyName::ExponentialBackoff => RetryStrategy::ExponentialBackoff,
+            StrategyName::ExponentialBackoffWithJitter => {
+                RetryStrategy::ExponentialBackoffWithJitter {
+                    jitter_factor: table.require(spec.jitter_factor, "jitter_factor", &why)?,
+                }
+            }
+            StrategyName::FibonacciBackoff => RetryStrategy::FibonacciBackoff,
+            StrategyName::ArithmeticProgression => RetryStrategy::ArithmeticProgression {
+                coefficient: table.require(spec.coefficient, "coefficient", &why)?,
+            },
+            StrategyName::Polynomial => RetryStrategy::Polynomial {
+                exponent: table.require(spec.exponent, "exponent", &why)?,
+            },
+            StrategyName::Harmonic => RetryStrategy::Harmonic,
+        };
+    }
+    config.validate().map_err(|err| table.invalid(err))?;
+    Ok(config.into())
+}
+
+fn breaker_config(table: Table, spec: BreakerSpec) -> Result<CircuitBreakerConfig, ConfigError> {
+    let mut config = match spec.preset {
+        Some(BreakerPreset::Sensitive) => CircuitBreakerConfig::sensitive(),
+        Some(BreakerPreset::Tolerant) => CircuitBreakerConfig::tolerant(),
+        None => CircuitBreakerConfig::default(),
+    };
+    if let Some(threshold) = spec.failure_threshold {
+        config.failure_threshold = threshold;
+    }
+    if let Some(threshold) = spec.success_threshold {
+        config.success_threshold = threshold;
+    }
+    if let Some(cooldown) = spec.cooldown_period {
+        config.cooldown_period = cooldown;
+    }
+    config.validate().map_err(|err| table.invalid(err))?;
+    Ok(config)
+}
+
+fn rate_limiter(table: Table, spec: RateLimitSpec) -> Result<Arc<dyn RateLimiter>, ConfigError> {
+    let mut config = RateLimitConfig::default();
+    if let Some(limit) = spec.limit {
+        config.limit = limit;
+    }
+    if let Some(window) = spec.window {
+        config.window = window;
+    }
+    config.validate().map_err(|err| table.invalid(err))?;
+    Ok(match spec.algorithm {
+        RateLimitAlgorithm::SlidingLog => Arc::new(SlidingLogLimiter::new(config)),
+        RateLimitAlgorithm::FixedWindow => Arc::new(FixedWindowLimiter::new(config)),
+    })
+}
+
+/// Returns the line of the byte at `offset` in `text`.
+fn line_at(text: &str, offset: usize) -> usize {
+    text[..offset.min(text.len())].matches('\n').count() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profile_materializes_named_policies() {
+        let profile = r#"
+            # Tuned by the on-call team.
+            [policies.checkout]
+            timeout = "1m 30s"
+
+            [policies.checkout.retry]
+            max_attempts = 6
+            delay = "50ms"
+            strategy = "polynomial"
+            exponent = 2
+            max_delay = "2s"
+
+            [policies."search-v2".breaker]
+            preset = "sensitive"
+            cooldown_period = "45s" # longer than the preset's
+
+            [policies."search-v2".rate_limit]
+            limit = 2
+            window = "1h"
+            algorithm = "sliding_log"
+        "#;
+        let registry = PolicyRegistry::new();
+        assert_eq!(
+            registry.load_profile_str(profile).unwrap(),
+            ["checkout", "search-v2"]
+        );
+
+        assert_eq!(
+            registry.timeout_for("checkout"),
+            Some(Duration::from_secs(90))
+        );
+        let retry = registry.retry_policy("checkout").unwrap();
+        assert_eq!(retry.max_attempts, 6);
+        assert_eq!(retry.delay, Duration::from_millis(50));
+        assert_eq!(retry.strategy, RetryStrategy::Polynomial { exponent: 2 });
+        assert_eq!(retry.max_delay, Some(Duration::from_secs(2)));
+        assert!(registry.breaker("checkout").is_none());
+
+        assert!(registry.breaker("search-v2").is_some());
+        let limiter = registry.rate_limiter("search-v2").unwrap();
+        assert!(limiter.try_acquire().is_ok());
+        assert!(limiter.try_acquire().is_ok());
+        assert!(limiter.try_acquire().is_err());
+    }
+
+    #[test]
+    fn test_invalid_profiles_are_rejected_with_their_line() {
+        let registry = PolicyRegistry::new();
+        let line_of = |profile: &str| match registry.load_profile_str(profile) {
+            Err(ConfigError::InvalidProfile { line, .. }) => line,
+            other => panic!("unexpected {:?}", other),
+        };
+
+        assert_eq!(line_of("[policies.a.retry]\nmax_attemps = 3"), Some(2));
+        assert_eq!(line_of("[policies.a]\ntimeout = \"soon\""), Some(2));
+        assert_eq!(line_of("[policies.a.retry]\nmax_attempts = 0"), Some(1));
+        assert_eq!(
+            line_of("[policies.a.retry]\nstrategy = \"exponential_backoff_with_jitter\""),
+            Some(1)
+        );
+        assert_eq!(line_of("[policies.a.retry]\nexponent = 2"), Some(1));
+        assert_eq!(line_of("\n\n[policies.a.bulkhead]"), Some(3));
+        assert_eq!(line_of("[policies.a]\n[policies.a]"), Some(2));
+        assert!(registry.timeout_for("a").is_none());
+
+        let err = registry.load_profile("does-not-exist.toml").unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::InvalidProfile { line: None, .. }
+        ));
+    }
+}
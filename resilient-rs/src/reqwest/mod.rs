@@ -0,0 +1,294 @@
+use crate::asynchronous::{CircuitBreaker, GuardedRetry, Step, back_off};
+use crate::classifier::ErrorClass;
+use crate::config::{CircuitBreakerConfig, RetryConfig};
+use crate::registry::CircuitBreakerRegistry;
+use http::Extensions;
+use reqwest::{Request, Response};
+use reqwest_middleware::{Error, Middleware, Next, Result};
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// The error returned, wrapped in `reqwest_middleware::Error::Middleware`, when the circuit
+/// breaker of the request's host rejects it.
+///
+/// `retry_after` is the remaining cooldown if the circuit is open, or `None` if the breaker's
+/// concurrency cap was reached.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CircuitRejected {
+    pub host: String,
+    pub retry_after: Option<Duration>,
+}
+
+impl fmt::Display for CircuitRejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Circuit Breaker for {} rejected the request. Please try later..!",
+            self.host
+        )
+    }
+}
+
+impl std::error::Error for CircuitRejected {}
+
+/// Classifies an HTTP response for retrying.
+///
+/// Server errors are classified by `http::classify`; client errors other than the ones it
+/// retries are not failures of the call, and are returned to the caller as is.
+///
+/// # Returns
+/// - `Some(ErrorClass::Throttled)` for `429 Too Many Requests`, and for `503 Service Unavailable`
+///   with a delay hint, carrying the delay returned by `http::suggested_delay` (`Retry-After` in
+///   seconds or as an HTTP-date, or a rate limit reset).
+/// - `Some(ErrorClass::Transient)` for `408 Request Timeout` and the other statuses accepted by
+///   `http::is_retryable`.
+/// - `Some(ErrorClass::Permanent)` for the other `5xx` statuses, such as `501 Not Implemented`.
+/// - `None` for every other status.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::classifier::ErrorClass;
+/// use resilient_rs::reqwest::classify_response;
+///
+/// let response = http::Response::builder().status(429).header("Retry-After", "2").body("").unwrap();
+/// assert_eq!(
+///     classify_response(&response.into()),
+///     Some(ErrorClass::Throttled { retry_after: Some(Duration::from_secs(2)) })
+/// );
+/// ```
+pub fn classify_response(response: &Response) -> Option<ErrorClass> {
+    match crate::http::classify(response.status(), response.headers()) {
+        Some(ErrorClass::Permanent) if !response.status().is_server_error() => None,
+        class => class,
+    }
+}
+
+/// A `reqwest_middleware::Middleware` retrying failed requests and, optionally, supervising every
+/// host with its own circuit breaker.
+///
+/// Responses are classified by `classify_response`: `429` and the retryable `5xx` responses are
+/// retried, honoring `Retry-After`, and the last response is returned once the attempts are
+/// exhausted.
+/// Connection errors and timeouts are retried; other errors are not, unless the `RetryConfig`
+/// has an `error_classifier` or a `retry_condition`, which then decides for every error.
+///
+/// With `with_circuit_breaker`, `5xx` responses and errors count as failures of the request's
+/// host, and requests to a host whose circuit is open fail with a `CircuitRejected` error
+/// without being sent. Retries stop as soon as an attempt opens the circuit.
+///
+/// Requests with a streaming body cannot be cloned and are sent once.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::config::{CircuitBreakerConfig, RetryConfig};
+/// use resilient_rs::reqwest::ResilienceMiddleware;
+/// use reqwest_middleware::ClientBuilder;
+///
+/// let middleware = ResilienceMiddleware::new(
+///     RetryConfig::default()
+///         .with_max_attempts(3)
+///         .with_delay(Duration::from_millis(200)),
+/// )
+/// .with_circuit_breaker(CircuitBreakerConfig::new(1, 5, Duration::from_secs(30)));
+/// let client = ClientBuilder::new(reqwest::Client::new()).with(middleware).build();
+/// ```
+pub struct ResilienceMiddleware {
+    retry: RetryConfig<Error>,
+    breakers: Option<Arc<CircuitBreakerRegistry>>,
+}
+
+impl ResilienceMiddleware {
+    /// Creates a middleware retrying according to `retry`, without circuit breaking.
+    ///
+    /// # Arguments
+    /// * `retry` - The attempts, delays and strategy of the retries.
+    pub fn new(retry: RetryConfig<Error>) -> Self {
+        ResilienceMiddleware {
+            retry,
+            breakers: None,
+        }
+    }
+
+    /// Supervises every host with its own breaker configured with `config`, and returns the
+    /// modified middleware.
+    pub fn with_circuit_breaker(self, config: CircuitBreakerConfig) -> Self {
+        self.with_breaker_registry(Arc::new(CircuitBreakerRegistry::new(config)))
+    }
+
+    /// Supervises every host with the breaker of `registry` named after the host, and returns
+    /// the modified middleware.
+    ///
+    /// Sharing the registry lets other parts of the application inspect the breakers, or tune
+    /// some hosts with `CircuitBreakerRegistry::with_override`.
+    pub fn with_breaker_registry(mut self, registry: Arc<CircuitBreakerRegistry>) -> Self {
+        self.breakers = Some(registry);
+        self
+    }
+
+    /// Classifies the outcome of an attempt, or returns `None` if it succeeded.
+    fn classify(&self, result: &Result<Response>) -> Option<ErrorClass> {
+        let err = match result {
+            Ok(response) => return classify_response(response),
+            Err(err) => err,
+        };
+        if self.retry.error_classifier.is_some() || self.retry.retry_condition.is_some() {
+            return Some(self.retry.classify(err));
+        }
+        match err {
+            Error::Reqwest(err) if err.is_connect() || err.is_timeout() => {
+                Some(ErrorClass::Transient)
+            }
+            _ => Some(ErrorClass::Permanent),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl Middleware for ResilienceMiddleware {
+    async fn handle(
+        &self,
+        mut request: Request,
+        extensions: &mut Extensions,
+        next: Next<'_>,
+    ) -> Result<Response> {
+        let host = request.url().host_str().unwrap_or_default().to_string();
+        let breaker = self.breakers.as_ref().map(|registry| registry.get(&host));
+        let mut retry = GuardedRetry::new(
+            &self.retry,
+            breaker.as_deref().map(CircuitBreaker::state_machine),
+        );
+
+        loop {
+            let retry_request = request.try_clone();
+            let attempt = match retry.start() {
+                Ok(attempt) => attempt,
+                Err(retry_after) => {
+                    return Err(Error::middleware(CircuitRejected { host, retry_after }));
+                }
+            };
+            let result = next.clone().run(request, extensions).await;
+            let outcome = match &result {
+                Ok(response) if response.status().is_server_error() => {
+                    Err(format!("HTTP {}", response.status()).into())
+                }
+                Ok(_) => Ok(()),
+                Err(err) => Err(err.to_string().into()),
+            };
+            // A request with a streaming body is sent once.
+            let class = self.classify(&result).map(|class| {
+                if retry_request.is_some() {
+                    class
+                } else {
+                    ErrorClass::Permanent
+                }
+            });
+            let wait = match retry.end(attempt, result, outcome, class) {
+                Step::Done(result) => return result,
+                Step::Retry(wait) => wait,
+            };
+            back_off(None, self.retry.timer_wheel.as_ref(), wait).await;
+            request = retry_request.expect("only clonable requests are retried");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::RetryStrategy;
+    use async_std::task::block_on;
+    use reqwest::StatusCode;
+    use reqwest_middleware::ClientBuilder;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Answers every request with the next status of `statuses`, without any network access.
+    struct Canned {
+        statuses: Vec<u16>,
+        calls: Arc<AtomicUsize>,
+    }
+
+    #[async_trait::async_trait]
+    impl Middleware for Canned {
+        async fn handle(&self, _: Request, _: &mut Extensions, _: Next<'_>) -> Result<Response> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            let status = self.statuses[call.min(self.statuses.len() - 1)];
+            let response = http::Response::builder().status(status).body("").unwrap();
+            Ok(response.into())
+        }
+    }
+
+    fn client(
+        middleware: ResilienceMiddleware,
+        statuses: Vec<u16>,
+    ) -> (ClientBuilder, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let canned = Canned {
+            statuses,
+            calls: calls.clone(),
+        };
+        let builder = ClientBuilder::new(reqwest::Client::new())
+            .with(middleware)
+            .with(canned);
+        (builder, calls)
+    }
+
+    fn retry_config() -> RetryConfig<Error> {
+        RetryConfig::new(3, Duration::from_millis(1), RetryStrategy::Linear)
+    }
+
+    #[test]
+    fn test_retries_server_errors_until_success() {
+        let (builder, calls) = client(
+            ResilienceMiddleware::new(retry_config()),
+            vec![503, 429, 200, 500],
+        );
+        let client = builder.build();
+
+        let response = block_on(client.get("http://inventory.test/items").send()).unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        // Once the attempts are exhausted, the last response is returned.
+        let response = block_on(client.get("http://inventory.test/items").send()).unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(calls.load(Ordering::SeqCst), 6);
+    }
+
+    #[test]
+    fn test_only_retryable_statuses_are_retried() {
+        for (status, attempts) in [(404, 1), (501, 1), (408, 3)] {
+            let (builder, calls) = client(ResilienceMiddleware::new(retry_config()), vec![status]);
+            let response = block_on(builder.build().get("http://inventory.test/items").send());
+            assert_eq!(response.unwrap().status().as_u16(), status);
+            assert_eq!(calls.load(Ordering::SeqCst), attempts, "status {}", status);
+        }
+    }
+
+    #[test]
+    fn test_circuit_breaker_per_host() {
+        let middleware = ResilienceMiddleware::new(retry_config())
+            .with_circuit_breaker(CircuitBreakerConfig::new(1, 2, Duration::from_secs(30)));
+        let (builder, calls) = client(middleware, vec![500]);
+        let client = builder.build();
+
+        // Two failed attempts open the circuit of the host, which ends the retries.
+        let response = block_on(client.get("http://payments.test/charge").send()).unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        let rejected = block_on(client.get("http://payments.test/charge").send()).unwrap_err();
+        let Error::Middleware(err) = rejected else {
+            panic!("expected a middleware error");
+        };
+        let rejected = err.downcast_ref::<CircuitRejected>().unwrap();
+        assert_eq!(rejected.host, "payments.test");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+
+        // Other hosts have their own breaker.
+        let response = block_on(client.get("http://inventory.test/items").send()).unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+}
@@ -0,0 +1,353 @@
+use crate::asynchronous::retry;
+use crate::config::RetryConfig;
+use crate::logging::Level;
+use crate::logging::log_with;
+use std::fmt;
+use std::pin::Pin;
+
+/// The future returned by the actions and compensations of a `SagaStep`.
+pub type StepFuture<E> = Pin<Box<dyn Future<Output = Result<(), E>> + Send>>;
+
+/// An action or compensation of a `SagaStep`, called with a clone of the saga's context.
+type StepFn<C, E> = Box<dyn Fn(C) -> StepFuture<E> + Send + Sync>;
+
+/// A step of a `Saga`: an action, the compensation undoing it, and the retry policies of both.
+///
+/// By default the action and the compensation are attempted once; use `with_retry` and
+/// `with_compensation_retry` to retry them. Both may be called several times, so they must be
+/// idempotent, e.g. by keying the remote writes on an identifier stored in the context.
+pub struct SagaStep<C, E> {
+    name: String,
+    action: StepFn<C, E>,
+    compensation: Option<StepFn<C, E>>,
+    retry: RetryConfig<E>,
+    compensation_retry: RetryConfig<E>,
+}
+
+impl<C, E> SagaStep<C, E> {
+    /// Creates a step without compensation, e.g. for a validation with no side effect.
+    ///
+    /// # Arguments
+    /// * `name` - The name of the step, reported by `SagaError`.
+    /// * `action` - The operation of the step.
+    pub fn new<F, Fut>(name: impl Into<String>, action: F) -> Self
+    where
+        F: Fn(C) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+    {
+        SagaStep {
+            name: name.into(),
+            action: Box::new(move |context| Box::pin(action(context))),
+            compensation: None,
+            retry: single_attempt(),
+            compensation_retry: single_attempt(),
+        }
+    }
+
+    /// Sets the operation undoing the action when a later step fails, and returns the modified
+    /// step.
+    pub fn with_compensation<F, Fut>(mut self, compensation: F) -> Self
+    where
+        F: Fn(C) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+    {
+        self.compensation = Some(Box::new(move |context| Box::pin(compensation(context))));
+        self
+    }
+
+    /// Sets the retry policy of the action and returns the modified step.
+    pub fn with_retry(mut self, retry: RetryConfig<E>) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Sets the retry policy of the compensation and returns the modified step.
+    pub fn with_compensation_retry(mut self, retry: RetryConfig<E>) -> Self {
+        self.compensation_retry = retry;
+        self
+    }
+
+    /// Returns the name of the step.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// A compensation that still failed after its retries.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompensationFailure<E> {
+    /// The name of the step whose compensation failed.
+    pub step: String,
+    /// The error of the last attempt of the compensation.
+    pub error: E,
+}
+
+/// The error returned by `Saga::run` when a step failed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SagaError<E> {
+    /// The name of the step that failed.
+    pub step: String,
+    /// The error of the last attempt of the failed step.
+    pub error: E,
+    /// The names of the steps that were compensated, in the order of their compensation.
+    pub compensated: Vec<String>,
+    /// The compensations that failed, in the order they were attempted.
+    pub compensation_failures: Vec<CompensationFailure<E>>,
+}
+
+impl<E> SagaError<E> {
+    /// Returns `true` if every completed step was compensated, leaving no partial changes.
+    pub fn is_fully_compensated(&self) -> bool {
+        self.compensation_failures.is_empty()
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for SagaError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Saga step {} failed: {}", self.step, self.error)?;
+        for failure in &self.compensation_failures {
+            write!(
+                f,
+                "; compensation of {} failed: {}",
+                failure.step, failure.error
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl<E: std::error::Error + 'static> std::error::Error for SagaError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// A sequence of steps that either all complete, or are undone by running the compensations of
+/// the completed steps.
+///
+/// The steps run in order, each retried according to its own policy. When a step still fails,
+/// the steps completed before it are compensated in reverse order, each compensation retried
+/// according to its own policy. A compensation that fails does not stop the others; it is
+/// reported in the `SagaError` so that the remaining changes can be repaired by hand.
+///
+/// Every action and compensation receives a clone of the context passed to `run`, usually an
+/// `Arc` holding the identifiers shared by the steps.
+///
+/// # Example
+/// ```
+/// use std::sync::{Arc, Mutex};
+/// use resilient_rs::saga::{Saga, SagaStep};
+///
+/// type Ledger = Arc<Mutex<Vec<&'static str>>>;
+///
+/// let saga: Saga<Ledger, String> = Saga::new()
+///     .with_step(
+///         SagaStep::new("reserve", |ledger: Ledger| async move {
+///             ledger.lock().unwrap().push("reserved");
+///             Ok(())
+///         })
+///         .with_compensation(|ledger: Ledger| async move {
+///             ledger.lock().unwrap().push("released");
+///             Ok(())
+///         }),
+///     )
+///     .with_step(SagaStep::new("charge", |_| async { Err("card declined".to_string()) }));
+///
+/// let ledger = Ledger::default();
+/// let err = async_std::task::block_on(saga.run(ledger.clone())).unwrap_err();
+/// assert_eq!(err.step, "charge");
+/// assert!(err.is_fully_compensated());
+/// assert_eq!(*ledger.lock().unwrap(), ["reserved", "released"]);
+/// ```
+pub struct Saga<C, E> {
+    steps: Vec<SagaStep<C, E>>,
+}
+
+impl<C, E> Saga<C, E> {
+    /// Creates a saga without steps.
+    pub fn new() -> Self {
+        Saga { steps: Vec::new() }
+    }
+
+    /// Appends a step and returns the modified saga.
+    pub fn with_step(mut self, step: SagaStep<C, E>) -> Self {
+        self.steps.push(step);
+        self
+    }
+
+    /// Returns the number of steps.
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Returns `true` if the saga has no step.
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+}
+
+impl<C: Clone, E> Saga<C, E> {
+    /// Runs the steps in order, compensating the completed ones if a step fails.
+    ///
+    /// # Arguments
+    /// * `context` - The value cloned into every action and compensation.
+    ///
+    /// # Returns
+    /// - `Ok(())` if every step completed.
+    /// - `Err(SagaError)` with the failed step, its error and the outcome of the compensations.
+    pub async fn run(&self, context: C) -> Result<(), SagaError<E>> {
+        for (index, step) in self.steps.iter().enumerate() {
+            let result = retry(|| (step.action)(context.clone()), &step.retry).await;
+            if let Err(error) = result {
+                log_with!(
+                    step.retry.log,
+                    Level::Warn,
+                    "Saga step {} failed, compensating {} completed steps",
+                    step.name,
+                    index
+                );
+                let mut err = SagaError {
+                    step: step.name.clone(),
+                    error,
+                    compensated: Vec::new(),
+                    compensation_failures: Vec::new(),
+                };
+                self.compensate(&self.steps[..index], &context, &mut err)
+                    .await;
+                return Err(err);
+            }
+        }
+        Ok(())
+    }
+
+    /// Runs the compensations of `completed` in reverse order.
+    async fn compensate(&self, completed: &[SagaStep<C, E>], context: &C, err: &mut SagaError<E>) {
+        for step in completed.iter().rev() {
+            let Some(compensation) = &step.compensation else {
+                continue;
+            };
+            match retry(|| compensation(context.clone()), &step.compensation_retry).await {
+                Ok(()) => err.compensated.push(step.name.clone()),
+                Err(error) => {
+                    log_with!(
+                        step.compensation_retry.log,
+                        Level::Error,
+                        "Compensation of saga step {} failed",
+                        step.name
+                    );
+                    err.compensation_failures.push(CompensationFailure {
+                        step: step.name.clone(),
+                        error,
+                    });
+                }
+            }
+        }
+    }
+}
+
+impl<C, E> Default for Saga<C, E> {
+    fn default() -> Self {
+        Saga::new()
+    }
+}
+
+/// The policy of actions and compensations without a retry policy of their own.
+fn single_attempt<E>() -> RetryConfig<E> {
+    RetryConfig {
+        max_attempts: 1,
+        ..RetryConfig::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::strategies::RetryStrategy;
+    use async_std::task::block_on;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    type Journal = Arc<Mutex<Vec<String>>>;
+
+    fn retry_config() -> RetryConfig<String> {
+        RetryConfig::new(3, Duration::from_millis(1), RetryStrategy::Linear)
+    }
+
+    fn step(name: &'static str) -> SagaStep<Journal, String> {
+        SagaStep::new(name, move |journal: Journal| async move {
+            journal.lock().unwrap().push(format!("do {}", name));
+            Ok(())
+        })
+        .with_compensation(move |journal: Journal| async move {
+            journal.lock().unwrap().push(format!("undo {}", name));
+            Ok(())
+        })
+    }
+
+    #[test]
+    fn test_failed_step_compensates_completed_steps_in_reverse() {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let saga = Saga::new()
+            .with_step(step("reserve"))
+            .with_step(step("charge"))
+            .with_step(
+                SagaStep::new("ship", move |_| {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    async { Err("carrier unavailable".to_string()) }
+                })
+                .with_retry(retry_config()),
+            );
+
+        let journal = Journal::default();
+        let err = block_on(saga.run(journal.clone())).unwrap_err();
+        assert_eq!(err.step, "ship");
+        assert_eq!(err.error, "carrier unavailable");
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+        assert_eq!(err.compensated, ["charge", "reserve"]);
+        assert!(err.is_fully_compensated());
+        assert_eq!(
+            *journal.lock().unwrap(),
+            ["do reserve", "do charge", "undo charge", "undo reserve"]
+        );
+
+        let journal = Journal::default();
+        let saga = Saga::new().with_step(step("reserve"));
+        assert!(block_on(saga.run(journal.clone())).is_ok());
+        assert_eq!(*journal.lock().unwrap(), ["do reserve"]);
+    }
+
+    #[test]
+    fn test_compensations_are_retried_and_failures_reported() {
+        let attempts = Arc::new(AtomicUsize::new(0));
+        let counter = attempts.clone();
+        let saga = Saga::new()
+            .with_step(step("reserve"))
+            .with_step(
+                SagaStep::new("charge", |_| async { Ok(()) })
+                    .with_compensation(move |_| {
+                        let attempt = counter.fetch_add(1, Ordering::SeqCst) + 1;
+                        async move { Err(format!("refund attempt {} failed", attempt)) }
+                    })
+                    .with_compensation_retry(retry_config()),
+            )
+            .with_step(SagaStep::new("ship", |_| async {
+                Err("carrier unavailable".to_string())
+            }));
+
+        let journal = Journal::default();
+        let err = block_on(saga.run(journal.clone())).unwrap_err();
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        assert!(!err.is_fully_compensated());
+        assert_eq!(
+            err.compensation_failures,
+            [CompensationFailure {
+                step: "charge".to_string(),
+                error: "refund attempt 3 failed".to_string(),
+            }]
+        );
+        assert_eq!(err.compensated, ["reserve"]);
+        assert_eq!(*journal.lock().unwrap(), ["do reserve", "undo reserve"]);
+    }
+}
@@ -0,0 +1,286 @@
+use crate::asynchronous::retry;
+use crate::config::RetryConfig;
+use std::error::Error;
+use std::fmt;
+use std::future::poll_fn;
+use std::task::Poll;
+
+/// Decides whether a `scatter_gather` succeeded when some of its items failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GatherPolicy {
+    /// Fails as soon as one item failed on every attempt; the items still in flight are
+    /// cancelled and the remaining ones are not started.
+    FailFast,
+    /// Runs every item and always succeeds, reporting the failed items along with the results.
+    BestEffort,
+    /// Succeeds if at least this many items succeeded, e.g. for quorum reads. Fails as soon as
+    /// too many items failed for the count to be reached.
+    MinSuccesses(usize),
+}
+
+/// The outcome of the items of a `scatter_gather`, identified by their position in the inputs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gathered<T, E> {
+    /// The results of the items that succeeded, in input order.
+    pub successes: Vec<(usize, T)>,
+    /// The last error of the items that failed on every attempt, in input order.
+    pub failures: Vec<(usize, E)>,
+    /// The items that were cancelled or not started because the policy had already failed, in
+    /// input order.
+    pub skipped: Vec<usize>,
+}
+
+impl<T, E> Gathered<T, E> {
+    /// Returns the results of the items that succeeded, without their positions.
+    pub fn into_values(self) -> Vec<T> {
+        self.successes.into_iter().map(|(_, value)| value).collect()
+    }
+
+    /// Returns `true` if every item succeeded.
+    pub fn is_complete(&self) -> bool {
+        self.failures.is_empty() && self.skipped.is_empty()
+    }
+}
+
+/// The error of a `scatter_gather` whose `GatherPolicy` was not satisfied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct GatherError<T, E> {
+    /// The number of successes required by the policy.
+    pub required: usize,
+    /// Everything gathered before the policy failed.
+    pub gathered: Gathered<T, E>,
+}
+
+impl<T, E: fmt::Display> fmt::Display for GatherError<T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Scatter-gather failed: {} of the {} required items succeeded",
+            self.gathered.successes.len(),
+            self.required
+        )?;
+        if let Some((index, error)) = self.gathered.failures.first() {
+            write!(f, " (item {} failed: {})", index, error)?;
+        }
+        Ok(())
+    }
+}
+
+impl<T: fmt::Debug, E: fmt::Debug + fmt::Display> Error for GatherError<T, E> {}
+
+/// Runs an asynchronous operation for every input, at most `concurrency` at a time, retrying
+/// each item with `retry_config`, and gathers the results according to `policy`.
+///
+/// Items are started in input order as slots free up, and every item gets its own attempts, so
+/// one flaky item does not use up the retries of the others. Items still running when the policy
+/// can no longer be satisfied are cancelled by dropping them.
+///
+/// # Arguments
+/// * `inputs` - The inputs to fan the operation out over.
+/// * `operation` - A closure called with a clone of an input for each attempt, returning a
+///   `Future` resolving to a `Result<T, E>`.
+/// * `concurrency` - The maximum number of items in flight; `0` is treated as `1`.
+/// * `retry_config` - The retry policy applied to each item.
+/// * `policy` - Decides whether the call succeeds when some items failed.
+///
+/// # Returns
+/// * `Ok(Gathered<T, E>)` - If the policy is satisfied; the failed items are reported along with
+///   the results.
+/// * `Err(GatherError<T, E>)` - If the policy is not satisfied, with what was gathered so far.
+///
+/// # Example
+/// ```
+/// use async_std::task::block_on;
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::scatter::{scatter_gather, GatherPolicy};
+/// use resilient_rs::strategies::RetryStrategy;
+/// use std::time::Duration;
+///
+/// let retry_config = RetryConfig::new(2, Duration::from_millis(1), RetryStrategy::Linear);
+/// let shards = vec![1, 2, 3, 4];
+/// let gathered = block_on(scatter_gather(
+///     shards,
+///     |shard| async move {
+///         if shard == 3 {
+///             Err(format!("shard {} unavailable", shard))
+///         } else {
+///             Ok(shard * 10)
+///         }
+///     },
+///     2,
+///     &retry_config,
+///     GatherPolicy::MinSuccesses(3),
+/// ))
+/// .unwrap();
+///
+/// assert_eq!(gathered.successes, vec![(0, 10), (1, 20), (3, 40)]);
+/// assert_eq!(gathered.failures[0].0, 2);
+/// ```
+pub async fn scatter_gather<I, F, Fut, T, E>(
+    inputs: impl IntoIterator<Item = I>,
+    operation: F,
+    concurrency: usize,
+    retry_config: &RetryConfig<E>,
+    policy: GatherPolicy,
+) -> Result<Gathered<T, E>, GatherError<T, E>>
+where
+    I: Clone,
+    F: Fn(I) -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+{
+    let inputs: Vec<I> = inputs.into_iter().collect();
+    let total = inputs.len();
+    let required = match policy {
+        GatherPolicy::FailFast => total,
+        GatherPolicy::BestEffort => 0,
+        GatherPolicy::MinSuccesses(count) => count,
+    };
+    let concurrency = concurrency.max(1);
+    let operation = &operation;
+
+    let mut gathered = Gathered {
+        successes: Vec::new(),
+        failures: Vec::new(),
+        skipped: Vec::new(),
+    };
+    let mut pending = inputs.into_iter().enumerate();
+    let mut in_flight = Vec::with_capacity(concurrency.min(total));
+
+    loop {
+        while in_flight.len() < concurrency {
+            let Some((index, input)) = pending.next() else {
+                break;
+            };
+            in_flight.push((
+                index,
+                Box::pin(async move { retry(|| operation(input.clone()), retry_config).await }),
+            ));
+        }
+        if in_flight.is_empty() {
+            break;
+        }
+
+        let (index, result) = poll_fn(|cx| {
+            for (position, (index, item)) in in_flight.iter_mut().enumerate() {
+                if let Poll::Ready(result) = item.as_mut().poll(cx) {
+                    let index = *index;
+                    drop(in_flight.swap_remove(position));
+                    return Poll::Ready((index, result));
+                }
+            }
+            Poll::Pending
+        })
+        .await;
+        match result {
+            Ok(value) => gathered.successes.push((index, value)),
+            Err(err) => {
+                gathered.failures.push((index, err));
+                // Stop as soon as the remaining items can no longer reach the required count.
+                if total - gathered.failures.len() < required {
+                    break;
+                }
+            }
+        }
+    }
+
+    gathered
+        .skipped
+        .extend(in_flight.iter().map(|(index, _)| *index));
+    gathered.skipped.extend(pending.map(|(index, _)| index));
+    gathered.successes.sort_by_key(|(index, _)| *index);
+    gathered.failures.sort_by_key(|(index, _)| *index);
+    gathered.skipped.sort_unstable();
+
+    if gathered.successes.len() < required {
+        Err(GatherError { required, gathered })
+    } else {
+        Ok(gathered)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::VirtualClock;
+    use crate::strategies::RetryStrategy;
+    use crate::time;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    #[test]
+    fn test_limits_concurrency_and_retries_each_item() {
+        let retry_config = RetryConfig::new(2, Duration::from_secs(1), RetryStrategy::Linear);
+        let attempts = Mutex::new(Vec::new());
+        let running = Mutex::new((0, 0));
+        let clock = VirtualClock::new();
+
+        let gathered = clock
+            .block_on(scatter_gather(
+                0..5,
+                |item| {
+                    let attempts = &attempts;
+                    let running = &running;
+                    async move {
+                        let attempt = {
+                            let mut attempts = attempts.lock().unwrap();
+                            attempts.push(item);
+                            attempts.iter().filter(|&&seen| seen == item).count()
+                        };
+                        {
+                            let mut running = running.lock().unwrap();
+                            running.0 += 1;
+                            running.1 = running.1.max(running.0);
+                        }
+                        time::sleep(Duration::from_secs(10)).await;
+                        running.lock().unwrap().0 -= 1;
+                        match item {
+                            // Fails once, then succeeds on its retry.
+                            1 if attempt == 1 => Err("flaky"),
+                            4 => Err("down"),
+                            _ => Ok(item * 2),
+                        }
+                    }
+                },
+                2,
+                &retry_config,
+                GatherPolicy::BestEffort,
+            ))
+            .unwrap();
+
+        assert_eq!(gathered.successes, vec![(0, 0), (1, 2), (2, 4), (3, 6)]);
+        assert_eq!(gathered.failures, vec![(4, "down")]);
+        assert!(gathered.skipped.is_empty());
+        assert_eq!(running.lock().unwrap().1, 2);
+        assert_eq!(attempts.lock().unwrap().len(), 7);
+    }
+
+    #[test]
+    fn test_policies_stop_once_unsatisfiable() {
+        let retry_config = RetryConfig::new(1, Duration::from_secs(1), RetryStrategy::Linear);
+        let run = |policy| {
+            VirtualClock::new().block_on(scatter_gather(
+                0..6,
+                |item| async move {
+                    time::sleep(Duration::from_secs(item)).await;
+                    if item % 2 == 0 { Err(item) } else { Ok(item) }
+                },
+                3,
+                &retry_config,
+                policy,
+            ))
+        };
+
+        let err = run(GatherPolicy::FailFast).unwrap_err();
+        assert_eq!(err.required, 6);
+        assert_eq!(err.gathered.failures, vec![(0, 0)]);
+        assert_eq!(err.gathered.skipped, vec![1, 2, 3, 4, 5]);
+
+        let err = run(GatherPolicy::MinSuccesses(4)).unwrap_err();
+        assert_eq!(err.gathered.successes, vec![(1, 1), (3, 3)]);
+        assert_eq!(err.gathered.failures, vec![(0, 0), (2, 2), (4, 4)]);
+        assert_eq!(err.gathered.skipped, vec![5]);
+
+        let gathered = run(GatherPolicy::MinSuccesses(3)).unwrap();
+        assert_eq!(gathered.into_values(), vec![1, 3, 5]);
+    }
+}
@@ -0,0 +1,331 @@
+use crate::classifier::{ErrorClass, ErrorClassifier};
+use crate::config::LoadShedConfig;
+use crate::events::{self, ResilienceEvent};
+use crate::logging::log_with;
+use crate::metrics;
+use crate::time;
+use std::error::Error;
+use std::fmt;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Why a `LoadShedder` considered the service overloaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverloadReason {
+    /// `in_flight` calls were already running, reaching `max_in_flight`.
+    InFlight { in_flight: usize },
+    /// The average latency of recent calls exceeded `max_latency`, and the halved in-flight
+    /// limit was reached.
+    Latency { average: Duration },
+}
+
+/// The error returned by `LoadShedder::call`.
+///
+/// It tells a call shed by an overloaded shedder apart from a failure of the operation itself.
+/// Shed calls should not be retried locally, since retrying adds to the very load being shed;
+/// wrap the retry classifier in `ShedAware` to give up on them immediately.
+#[derive(Debug, PartialEq)]
+pub enum LoadShedError<E> {
+    /// The call was rejected without running the operation.
+    Overloaded { reason: OverloadReason },
+    /// The operation ran and failed with this error.
+    Inner(E),
+}
+
+impl<E> LoadShedError<E> {
+    /// Returns `true` if the call was shed.
+    pub fn is_overloaded(&self) -> bool {
+        matches!(self, LoadShedError::Overloaded { .. })
+    }
+
+    /// Returns the error of the operation, or `None` if the call was shed.
+    pub fn into_inner(self) -> Option<E> {
+        match self {
+            LoadShedError::Inner(err) => Some(err),
+            LoadShedError::Overloaded { .. } => None,
+        }
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for LoadShedError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LoadShedError::Overloaded {
+                reason: OverloadReason::InFlight { in_flight },
+            } => write!(
+                f,
+                "Service is overloaded ({} calls in flight). Please try later..!",
+                in_flight
+            ),
+            LoadShedError::Overloaded {
+                reason: OverloadReason::Latency { average },
+            } => write!(
+                f,
+                "Service is overloaded (average latency {:?}). Please try later..!",
+                average
+            ),
+            LoadShedError::Inner(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> Error for LoadShedError<E> {}
+
+/// An `ErrorClassifier` for `LoadShedError`s, classifying shed calls as `Permanent` and
+/// delegating the operation's errors to the wrapped classifier.
+///
+/// # Example
+/// ```
+/// use resilient_rs::classifier::ErrorClass;
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::shedding::{LoadShedError, ShedAware};
+///
+/// let config: RetryConfig<LoadShedError<String>> =
+///     RetryConfig::default().with_error_classifier(ShedAware(|_: &String| ErrorClass::Transient));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct ShedAware<C>(pub C);
+
+impl<E, C> ErrorClassifier<LoadShedError<E>> for ShedAware<C>
+where
+    C: ErrorClassifier<E>,
+{
+    fn classify(&self, error: &LoadShedError<E>) -> ErrorClass {
+        match error {
+            LoadShedError::Overloaded { .. } => ErrorClass::Permanent,
+            LoadShedError::Inner(err) => self.0.classify(err),
+        }
+    }
+}
+
+/// A guard rejecting excess work once the service it protects is overloaded.
+///
+/// The shedder counts the calls in flight and keeps an exponentially weighted moving average of
+/// their latency. A call is shed with `LoadShedError::Overloaded` when:
+/// - `max_in_flight` calls are already running, or
+/// - the average latency exceeds `max_latency` and half of `max_in_flight` calls are running.
+///
+/// Halving the limit instead of shedding everything while latency is high keeps some calls
+/// running, so the average keeps being updated and recovers once the service does.
+///
+/// The shedder takes `&self` and can be shared through an `Arc` or a static. In a pipeline,
+/// place it inside the retry loop and use the `ShedAware` classifier, so that shed calls fail
+/// fast instead of being retried.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::config::LoadShedConfig;
+/// use resilient_rs::shedding::{LoadShedder, LoadShedError, OverloadReason};
+///
+/// let shedder = LoadShedder::new(LoadShedConfig::new(1, Duration::from_secs(1)));
+/// let nested = shedder.call(|| Ok::<_, &str>(shedder.call(|| Ok::<_, &str>(()))));
+/// assert_eq!(
+///     nested,
+///     Ok(Err(LoadShedError::Overloaded { reason: OverloadReason::InFlight { in_flight: 1 } }))
+/// );
+/// ```
+pub struct LoadShedder {
+    config: LoadShedConfig,
+    state: Mutex<ShedState>,
+}
+
+/// The calls in flight and the average latency observed by a `LoadShedder`.
+struct ShedState {
+    in_flight: usize,
+    average: Option<Duration>,
+    shed_rate: f64,
+}
+
+/// A call admitted by a `LoadShedder`, freeing its slot when dropped.
+struct ShedPermit<'a> {
+    shedder: &'a LoadShedder,
+    started: Instant,
+}
+
+impl ShedPermit<'_> {
+    /// Records the latency of the completed call and frees its slot.
+    fn complete(self) {
+        let latency = time::elapsed(self.started);
+        let smoothing = self.shedder.config.smoothing;
+        let mut state = self.shedder.state();
+        state.average = Some(match state.average {
+            Some(average) => average.mul_f64(1.0 - smoothing) + latency.mul_f64(smoothing),
+            None => latency,
+        });
+        // The slot is freed by `drop`, which needs the lock.
+        drop(state);
+    }
+}
+
+impl Drop for ShedPermit<'_> {
+    fn drop(&mut self) {
+        self.shedder.state().in_flight -= 1;
+    }
+}
+
+impl LoadShedder {
+    /// Creates a shedder with no call in flight and no latency observed.
+    ///
+    /// # Arguments
+    /// * `config` - The in-flight and latency thresholds.
+    pub fn new(config: LoadShedConfig) -> Self {
+        LoadShedder {
+            config,
+            state: Mutex::new(ShedState {
+                in_flight: 0,
+                average: None,
+                shed_rate: 0.0,
+            }),
+        }
+    }
+
+    /// Executes a blocking operation unless the service is overloaded.
+    ///
+    /// # Returns
+    /// - `Ok(T)` if the operation succeeds.
+    /// - `Err(LoadShedError::Inner(E))` if the operation fails.
+    /// - `Err(LoadShedError::Overloaded)` if the call was shed.
+    pub fn call<F, T, E>(&self, operation: F) -> Result<T, LoadShedError<E>>
+    where
+        F: FnOnce() -> Result<T, E>,
+    {
+        let permit = self.try_acquire()?;
+        let result = operation();
+        permit.complete();
+        result.map_err(LoadShedError::Inner)
+    }
+
+    /// Executes an asynchronous operation unless the service is overloaded.
+    ///
+    /// # Returns
+    /// The same as `call`. If the returned future is dropped before the operation completes, the
+    /// slot is freed without recording a latency.
+    pub async fn call_async<F, Fut, T, E>(&self, operation: F) -> Result<T, LoadShedError<E>>
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let permit = self.try_acquire()?;
+        let result = operation().await;
+        permit.complete();
+        result.map_err(LoadShedError::Inner)
+    }
+
+    /// Returns the number of calls currently running.
+    pub fn in_flight(&self) -> usize {
+        self.state().in_flight
+    }
+
+    /// Returns the moving average of the latency of completed calls, or `None` if no call
+    /// completed yet.
+    pub fn average_latency(&self) -> Option<Duration> {
+        self.state().average
+    }
+
+    /// Returns the moving average of the fraction of calls shed, between 0 and 1, weighted by
+    /// `smoothing` like the latency.
+    pub fn shed_rate(&self) -> f64 {
+        self.state().shed_rate
+    }
+
+    fn try_acquire<E>(&self) -> Result<ShedPermit<'_>, LoadShedError<E>> {
+        let mut state = self.state();
+        let max_in_flight = self.config.max_in_flight;
+        let reason = match state.average {
+            Some(average) if average > self.config.max_latency => (state.in_flight
+                >= (max_in_flight / 2).max(1))
+            .then_some(OverloadReason::Latency { average }),
+            _ => (state.in_flight >= max_in_flight).then_some(OverloadReason::InFlight {
+                in_flight: state.in_flight,
+            }),
+        };
+        let smoothing = self.config.smoothing;
+        let shed = if reason.is_some() { 1.0 } else { 0.0 };
+        state.shed_rate = state.shed_rate * (1.0 - smoothing) + shed * smoothing;
+        if let Some(reason) = reason {
+            drop(state);
+            log_with!(
+                self.config.log,
+                self.config.log.level,
+                "Load shedder is overloaded ({:?}).. Request is shed",
+                reason
+            );
+            metrics::increment(&metrics::LOAD_SHED);
+            events::emit(ResilienceEvent::LoadShed);
+            return Err(LoadShedError::Overloaded { reason });
+        }
+        state.in_flight += 1;
+        Ok(ShedPermit {
+            shedder: self,
+            started: time::now(),
+        })
+    }
+
+    fn state(&self) -> MutexGuard<'_, ShedState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RetryConfig;
+    use crate::synchronous::retry;
+    use std::thread::sleep;
+
+    #[test]
+    fn test_sheds_on_high_latency_until_it_recovers() {
+        let config = LoadShedConfig::new(4, Duration::from_millis(20)).with_smoothing(1.0);
+        let shedder = LoadShedder::new(config);
+
+        let slow = shedder.call(|| {
+            sleep(Duration::from_millis(30));
+            Ok::<_, &str>(())
+        });
+        assert!(slow.is_ok());
+
+        // With latency over the threshold, only half of `max_in_flight` calls are admitted.
+        let nested = shedder.call(|| {
+            Ok::<_, &str>(shedder.call(|| Ok::<_, &str>(shedder.call(|| Ok::<_, &str>(())))))
+        });
+        let shed = nested.unwrap().unwrap().unwrap_err();
+        assert!(matches!(
+            shed,
+            LoadShedError::Overloaded {
+                reason: OverloadReason::Latency { .. }
+            }
+        ));
+
+        // The fast calls completed meanwhile bring the average back under the threshold.
+        assert!(shedder.average_latency().unwrap() < Duration::from_millis(20));
+        assert_eq!(shedder.in_flight(), 0);
+    }
+
+    #[test]
+    fn test_shed_calls_are_not_retried() {
+        let shedder = LoadShedder::new(LoadShedConfig::new(1, Duration::from_secs(1)));
+        let retry_config = RetryConfig {
+            delay: Duration::from_millis(1),
+            ..RetryConfig::default()
+        }
+        .with_error_classifier(ShedAware(|_: &&str| ErrorClass::Transient));
+
+        let result = shedder.call(|| {
+            let mut attempts = 0;
+            let nested = retry(
+                || {
+                    attempts += 1;
+                    shedder.call(|| Ok::<_, &str>(()))
+                },
+                &retry_config,
+            );
+            Ok::<_, &str>((nested, attempts))
+        });
+        let (nested, attempts) = result.unwrap();
+        assert!(nested.unwrap_err().is_overloaded());
+        assert_eq!(attempts, 1);
+        // The nested call was shed after an admitted one, weighted by the default smoothing.
+        assert!((shedder.shed_rate() - 0.2).abs() < 1e-9);
+    }
+}
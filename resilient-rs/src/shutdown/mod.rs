@@ -0,0 +1,244 @@
+use crate::time;
+use crate::wheel::TimerWheel;
+use async_std::channel::{self, Receiver, Sender};
+use std::error::Error;
+use std::fmt;
+use std::future::poll_fn;
+use std::pin::pin;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::task::Poll;
+use std::time::Duration;
+
+/// A signal telling retry loops that the process is shutting down.
+///
+/// Once `shutdown` is called, the `retry_until_shutdown` loops of the `asynchronous` and
+/// `synchronous` modules stop scheduling new attempts: an attempt in flight is allowed to
+/// finish, but a pending backoff is cut short and the loop returns `ShutdownError::Cancelled`
+/// instead of sleeping out its delay. Handles are cheap to clone and share the same signal, so
+/// one can be given to a signal handler (e.g. a `ctrl-c` handler) and the others to the loops.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::shutdown::{ShutdownError, ShutdownHandle};
+/// use resilient_rs::strategies::RetryStrategy;
+/// use resilient_rs::synchronous::retry_until_shutdown;
+///
+/// let shutdown = ShutdownHandle::new();
+/// let retry_config = RetryConfig::new(5, Duration::from_secs(60), RetryStrategy::Linear);
+/// let signal = shutdown.clone();
+/// let result: Result<(), _> = retry_until_shutdown(
+///     || {
+///         // The process receives SIGTERM while the dependency is down.
+///         signal.shutdown();
+///         Err("connection refused")
+///     },
+///     &retry_config,
+///     &shutdown,
+/// );
+///
+/// assert_eq!(
+///     result,
+///     Err(ShutdownError::Cancelled { attempts: 1, last_error: Some("connection refused") })
+/// );
+/// ```
+#[derive(Clone)]
+pub struct ShutdownHandle {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    down: Mutex<bool>,
+    condvar: Condvar,
+    // Dropped on shutdown, which closes the channel and wakes up every async waiter.
+    sender: Mutex<Option<Sender<()>>>,
+    receiver: Receiver<()>,
+}
+
+impl ShutdownHandle {
+    /// Creates a handle whose shutdown was not requested yet.
+    pub fn new() -> Self {
+        let (sender, receiver) = channel::bounded(1);
+        ShutdownHandle {
+            inner: Arc::new(Inner {
+                down: Mutex::new(false),
+                condvar: Condvar::new(),
+                sender: Mutex::new(Some(sender)),
+                receiver,
+            }),
+        }
+    }
+
+    /// Requests the shutdown, waking up every loop waiting on this handle.
+    pub fn shutdown(&self) {
+        *self.down() = true;
+        self.inner.condvar.notify_all();
+        self.inner
+            .sender
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .take();
+    }
+
+    /// Returns `true` once the shutdown was requested.
+    pub fn is_shutdown(&self) -> bool {
+        *self.down()
+    }
+
+    /// Waits until the shutdown is requested.
+    pub async fn wait(&self) {
+        // `recv` only fails once the channel is closed by `shutdown`.
+        while self.inner.receiver.recv().await.is_ok() {}
+    }
+
+    /// Waits for `duration`, on `wheel` if one is given, or less if the shutdown is requested in
+    /// the meantime.
+    ///
+    /// # Returns
+    /// `true` if the whole duration elapsed without a shutdown.
+    pub(crate) async fn sleep(&self, wheel: Option<&TimerWheel>, duration: Duration) -> bool {
+        let mut sleep = pin!(time::sleep_on(wheel, duration));
+        let mut shutdown = pin!(self.wait());
+        poll_fn(|cx| {
+            if shutdown.as_mut().poll(cx).is_ready() {
+                return Poll::Ready(false);
+            }
+            sleep.as_mut().poll(cx).map(|()| true)
+        })
+        .await
+    }
+
+    /// Blocks the thread for `duration`, or less if the shutdown is requested in the meantime.
+    ///
+    /// # Returns
+    /// `true` if the whole duration elapsed without a shutdown.
+    pub(crate) fn sleep_blocking(&self, duration: Duration) -> bool {
+        let down =
+            time::wait_timeout_while(&self.inner.condvar, self.down(), duration, |down| !*down);
+        !*down
+    }
+
+    fn down(&self) -> MutexGuard<'_, bool> {
+        self.inner
+            .down
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Default for ShutdownHandle {
+    fn default() -> Self {
+        ShutdownHandle::new()
+    }
+}
+
+impl fmt::Debug for ShutdownHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShutdownHandle")
+            .field("is_shutdown", &self.is_shutdown())
+            .finish()
+    }
+}
+
+/// The error returned by a retry loop watching a `ShutdownHandle`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShutdownError<E> {
+    /// The shutdown was requested before the operation succeeded, so no further attempt was
+    /// made.
+    Cancelled {
+        /// The number of attempts made before the shutdown.
+        attempts: usize,
+        /// The error of the last attempt, if one was made.
+        last_error: Option<E>,
+    },
+    /// The operation failed after all retry attempts, or with an error that is not retryable.
+    Failed(E),
+}
+
+impl<E> ShutdownError<E> {
+    /// Returns `true` if the loop stopped because of the shutdown.
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self, ShutdownError::Cancelled { .. })
+    }
+
+    /// Returns the error of a loop that was not watching a `ShutdownHandle`, and therefore
+    /// cannot have been cancelled.
+    pub(crate) fn into_failed(self) -> E {
+        match self {
+            ShutdownError::Failed(err) => err,
+            ShutdownError::Cancelled { .. } => unreachable!("no shutdown handle was given"),
+        }
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for ShutdownError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShutdownError::Cancelled {
+                attempts,
+                last_error: Some(err),
+            } => write!(
+                f,
+                "Retries cancelled by shutdown after {} attempts: {}",
+                attempts, err
+            ),
+            ShutdownError::Cancelled { attempts, .. } => {
+                write!(
+                    f,
+                    "Retries cancelled by shutdown after {} attempts",
+                    attempts
+                )
+            }
+            ShutdownError::Failed(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+impl<E: fmt::Debug + fmt::Display> Error for ShutdownError<E> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::VirtualClock;
+
+    #[test]
+    fn test_sleep_is_cut_short_by_shutdown() {
+        let shutdown = ShutdownHandle::new();
+        let clock = VirtualClock::new();
+        assert!(clock.block_on(shutdown.sleep(None, Duration::from_secs(5))));
+        assert_eq!(clock.elapsed(), Duration::from_secs(5));
+
+        let signal = shutdown.clone();
+        let completed = clock.block_on(async {
+            let mut sleep = pin!(shutdown.sleep(None, Duration::from_secs(60)));
+            let mut trigger = pin!(async {
+                time::sleep(Duration::from_secs(1)).await;
+                signal.shutdown();
+            });
+            poll_fn(|cx| {
+                let _ = trigger.as_mut().poll(cx);
+                sleep.as_mut().poll(cx)
+            })
+            .await
+        });
+        assert!(!completed);
+        assert_eq!(clock.elapsed(), Duration::from_secs(6));
+        assert!(shutdown.is_shutdown());
+        assert!(!clock.block_on(shutdown.sleep(None, Duration::from_secs(60))));
+    }
+
+    #[test]
+    fn test_blocking_sleep_wakes_up_on_shutdown() {
+        let shutdown = ShutdownHandle::new();
+        let signal = shutdown.clone();
+        let trigger = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(20));
+            signal.shutdown();
+        });
+        let start = std::time::Instant::now();
+        assert!(!shutdown.sleep_blocking(Duration::from_secs(10)));
+        assert!(start.elapsed() < Duration::from_secs(5));
+        trigger.join().unwrap();
+    }
+}
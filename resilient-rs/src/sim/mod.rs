@@ -0,0 +1,401 @@
+use crate::ratelimit::Clock;
+use rand::RngCore;
+use std::cell::{Cell, RefCell};
+use std::marker::PhantomData;
+use std::pin::{Pin, pin};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Poll, Wake, Waker};
+use std::thread::{self, Thread};
+use std::time::{Duration, Instant, SystemTime};
+
+thread_local! {
+    /// The virtual clock installed on this thread, if any.
+    static CURRENT: RefCell<Option<VirtualClock>> = const { RefCell::new(None) };
+    /// Whether this thread is running `VirtualClock::block_on`.
+    static DRIVEN: Cell<bool> = const { Cell::new(false) };
+}
+
+/// A virtual clock that the crate's sleeps, timeouts, circuit breaker cooldowns, rate limiter
+/// windows and backoff jitter run against instead of real time, for fast and deterministic tests.
+///
+/// A clock is installed on the current thread, either:
+/// - with `block_on`, which runs a future to completion and, whenever the future waits, jumps
+///   the clock straight to the next sleep or timeout to expire. Concurrent sleeps and timeouts
+///   expire in the order of their deadlines, as they would in real time.
+/// - with `enter`, for blocking code such as the `synchronous` retries: every sleep advances the
+///   clock by its duration and returns at once.
+///
+/// Time only moves when the code under test sleeps or the test calls `advance`. Backoff jitter
+/// is drawn from a generator seeded with `with_seed`, so a run can be replayed exactly.
+///
+/// The clock is only seen by the thread it is installed on: tasks spawned onto a runtime keep
+/// running in real time.
+///
+/// This module is available with the `sim` feature.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::asynchronous::retry;
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::sim::VirtualClock;
+///
+/// let clock = VirtualClock::new();
+/// let config = RetryConfig::default().with_max_attempts(3).with_delay(Duration::from_secs(60));
+///
+/// // Two minutes of backoff, completed instantly.
+/// let result = clock.block_on(retry(|| async { Err::<(), _>("unavailable") }, &config));
+/// assert!(result.is_err());
+/// assert!(clock.elapsed() >= Duration::from_secs(120));
+/// ```
+#[derive(Debug, Clone)]
+pub struct VirtualClock {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    start: Instant,
+    start_system: SystemTime,
+    state: Mutex<State>,
+}
+
+#[derive(Debug)]
+struct State {
+    elapsed: Duration,
+    timers: Vec<Timer>,
+    next_id: u64,
+    rng: SplitMix64,
+}
+
+/// A pending sleep, expiring once the clock reaches `deadline`.
+#[derive(Debug)]
+struct Timer {
+    id: u64,
+    deadline: Duration,
+    waker: Waker,
+}
+
+impl VirtualClock {
+    /// Creates a clock starting at the current real time, with jitter seeded with `0`.
+    pub fn new() -> Self {
+        VirtualClock {
+            inner: Arc::new(Inner {
+                start: Instant::now(),
+                start_system: SystemTime::now(),
+                state: Mutex::new(State {
+                    elapsed: Duration::ZERO,
+                    timers: Vec::new(),
+                    next_id: 0,
+                    rng: SplitMix64(0),
+                }),
+            }),
+        }
+    }
+
+    /// Seeds the generator of the backoff jitter with `seed`, and returns the modified clock.
+    pub fn with_seed(self, seed: u64) -> Self {
+        self.state().rng = SplitMix64(seed);
+        self
+    }
+
+    /// Returns the current virtual instant.
+    pub fn now(&self) -> Instant {
+        self.inner.start + self.elapsed()
+    }
+
+    /// Returns the current virtual system time.
+    pub fn system_now(&self) -> SystemTime {
+        self.inner.start_system + self.elapsed()
+    }
+
+    /// Returns the virtual time elapsed since the clock was created.
+    pub fn elapsed(&self) -> Duration {
+        self.state().elapsed
+    }
+
+    /// Moves the clock forward by `duration`, expiring the sleeps and timeouts due by then.
+    pub fn advance(&self, duration: Duration) {
+        let target = self.elapsed() + duration;
+        self.advance_to(target);
+    }
+
+    /// Installs the clock on the current thread until the returned guard is dropped.
+    ///
+    /// While installed, sleeps complete at once after advancing the clock by their duration.
+    /// Asynchronous code racing sleeps or timeouts should use `block_on` instead.
+    pub fn enter(&self) -> ClockGuard {
+        self.install(false)
+    }
+
+    /// Runs `future` to completion on the current thread with the clock installed, advancing
+    /// the clock to the next deadline whenever the future waits only on sleeps or timeouts.
+    ///
+    /// Waits on anything else, such as I/O or another thread, block in real time.
+    pub fn block_on<F: Future>(&self, future: F) -> F::Output {
+        let _guard = self.install(true);
+        let signal = Arc::new(Signal {
+            woken: AtomicBool::new(false),
+            thread: thread::current(),
+        });
+        let waker = Waker::from(signal.clone());
+        let mut cx = Context::from_waker(&waker);
+        let mut future = pin!(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+            if signal.woken.swap(false, Ordering::SeqCst) {
+                continue;
+            }
+            if !self.advance_to_next_timer() {
+                thread::park();
+            }
+        }
+    }
+
+    /// Returns a future completing once the clock advanced by `duration`.
+    pub(crate) fn sleep(&self, duration: Duration) -> Sleep {
+        Sleep {
+            clock: self.clone(),
+            deadline: self.elapsed() + duration,
+            id: None,
+        }
+    }
+
+    fn install(&self, driven: bool) -> ClockGuard {
+        ClockGuard {
+            previous: CURRENT.with(|current| current.replace(Some(self.clone()))),
+            driven: DRIVEN.replace(driven),
+            _thread: PhantomData,
+        }
+    }
+
+    /// Moves the clock to `target`, if it is later, and wakes the sleeps due by then.
+    fn advance_to(&self, target: Duration) {
+        let mut state = self.state();
+        state.elapsed = state.elapsed.max(target);
+        let elapsed = state.elapsed;
+        let (due, pending) = std::mem::take(&mut state.timers)
+            .into_iter()
+            .partition::<Vec<_>, _>(|timer| timer.deadline <= elapsed);
+        state.timers = pending;
+        drop(state);
+        due.into_iter().for_each(|timer| timer.waker.wake());
+    }
+
+    /// Moves the clock to the earliest pending deadline, returning `false` if there is none.
+    fn advance_to_next_timer(&self) -> bool {
+        let next = self.state().timers.iter().map(|timer| timer.deadline).min();
+        match next {
+            Some(deadline) => {
+                self.advance_to(deadline);
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.inner
+            .state
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Default for VirtualClock {
+    fn default() -> Self {
+        VirtualClock::new()
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> Instant {
+        VirtualClock::now(self)
+    }
+}
+
+/// Keeps a `VirtualClock` installed on the current thread, restoring the previous clock, if
+/// any, when dropped.
+#[must_use = "the clock is uninstalled when the guard is dropped"]
+#[derive(Debug)]
+pub struct ClockGuard {
+    previous: Option<VirtualClock>,
+    driven: bool,
+    // The guard restores thread-local state, so it must be dropped on its thread.
+    _thread: PhantomData<*const ()>,
+}
+
+impl Drop for ClockGuard {
+    fn drop(&mut self) {
+        CURRENT.with(|current| *current.borrow_mut() = self.previous.take());
+        DRIVEN.set(self.driven);
+    }
+}
+
+/// Calls `f` with the clock installed on the current thread, if any.
+pub(crate) fn with_current<T>(f: impl FnOnce(&VirtualClock) -> T) -> Option<T> {
+    CURRENT.with(|current| current.borrow().as_ref().map(f))
+}
+
+/// Calls `f` with the seeded generator of the clock installed on the current thread, or hands
+/// `f` back if there is none.
+pub(crate) fn with_current_rng<T, F>(f: F) -> Result<T, F>
+where
+    F: FnOnce(&mut dyn RngCore) -> T,
+{
+    CURRENT.with(|current| match current.borrow().as_ref() {
+        Some(clock) => Ok(f(&mut clock.state().rng)),
+        None => Err(f),
+    })
+}
+
+/// A sleep on a `VirtualClock`.
+#[derive(Debug)]
+pub(crate) struct Sleep {
+    clock: VirtualClock,
+    deadline: Duration,
+    id: Option<u64>,
+}
+
+impl Future for Sleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        let this = &mut *self;
+        let mut state = this.clock.state();
+        if state.elapsed >= this.deadline {
+            return Poll::Ready(());
+        }
+        if !DRIVEN.get() {
+            drop(state);
+            this.clock.advance_to(this.deadline);
+            return Poll::Ready(());
+        }
+        match this.id {
+            Some(id) => {
+                if let Some(timer) = state.timers.iter_mut().find(|timer| timer.id == id) {
+                    timer.waker.clone_from(cx.waker());
+                    return Poll::Pending;
+                }
+            }
+            None => {
+                this.id = Some(state.next_id);
+                state.next_id += 1;
+            }
+        }
+        let timer = Timer {
+            id: this.id.expect("the timer id was just set"),
+            deadline: this.deadline,
+            waker: cx.waker().clone(),
+        };
+        state.timers.push(timer);
+        Poll::Pending
+    }
+}
+
+impl Drop for Sleep {
+    fn drop(&mut self) {
+        if let Some(id) = self.id {
+            self.clock.state().timers.retain(|timer| timer.id != id);
+        }
+    }
+}
+
+/// Wakes the thread running `VirtualClock::block_on`.
+struct Signal {
+    woken: AtomicBool,
+    thread: Thread,
+}
+
+impl Wake for Signal {
+    fn wake(self: Arc<Self>) {
+        self.wake_by_ref();
+    }
+
+    fn wake_by_ref(self: &Arc<Self>) {
+        self.woken.store(true, Ordering::SeqCst);
+        self.thread.unpark();
+    }
+}
+
+/// The SplitMix64 generator, a small seedable generator good enough for jitter.
+#[derive(Debug, Clone)]
+struct SplitMix64(u64);
+
+impl RngCore for SplitMix64 {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        rand::rand_core::impls::fill_bytes_via_next(self, dest)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{CircuitBreakerConfig, RetryConfig};
+    use crate::strategies::RetryStrategy;
+    use crate::synchronous::{CircuitBreaker, CircuitBreakerError, retry};
+
+    #[test]
+    fn test_block_on_expires_timers_in_deadline_order() {
+        let clock = VirtualClock::new();
+        let (fast, slow) = clock.block_on(async {
+            let fast = crate::time::timeout(Duration::from_secs(10), async {
+                crate::time::sleep(Duration::from_secs(5)).await;
+                crate::time::now()
+            });
+            let slow = crate::time::timeout(
+                Duration::from_secs(30),
+                crate::time::sleep(Duration::from_secs(3600)),
+            );
+            (fast.await, slow.await)
+        });
+        assert_eq!(fast.unwrap(), clock.inner.start + Duration::from_secs(5));
+        assert!(slow.is_err());
+        assert_eq!(clock.elapsed(), Duration::from_secs(35));
+        assert!(clock.state().timers.is_empty());
+    }
+
+    #[test]
+    fn test_blocking_retries_and_breaker_cooldowns_run_on_virtual_time() {
+        let jittered = || {
+            let clock = VirtualClock::new().with_seed(7);
+            let _guard = clock.enter();
+            let config = RetryConfig {
+                max_attempts: 4,
+                delay: Duration::from_secs(10),
+                strategy: RetryStrategy::ExponentialBackoffWithJitter { jitter_factor: 0.5 },
+                ..RetryConfig::default()
+            };
+            let _ = retry(|| Err::<(), _>("unavailable"), &config);
+            clock.elapsed()
+        };
+        let elapsed = jittered();
+        assert!(elapsed >= Duration::from_secs(30));
+        assert_eq!(jittered(), elapsed);
+
+        let clock = VirtualClock::new();
+        let _guard = clock.enter();
+        let breaker =
+            CircuitBreaker::with_config(CircuitBreakerConfig::new(1, 1, Duration::from_secs(60)));
+        let _ = breaker.run(|| Err::<(), _>("unavailable"));
+        let rejected = breaker.run(|| Ok::<_, &str>(()));
+        assert!(matches!(rejected, Err(CircuitBreakerError::Open { .. })));
+        clock.advance(Duration::from_secs(60));
+        assert_eq!(breaker.run(|| Ok::<_, &str>(1)).ok(), Some(1));
+    }
+}
@@ -0,0 +1,292 @@
+use crate::asynchronous::schedule_class_retry;
+use crate::config::RetryConfig;
+use crate::logging::Level;
+use crate::logging::log_with;
+use crate::time;
+use futures_sink::Sink;
+use std::future::poll_fn;
+use std::pin::Pin;
+
+/// The future returned by the factory of a `RetrySink`, resolving to a new sink.
+type SinkFuture<S, E> = Pin<Box<dyn Future<Output = Result<S, E>> + Send>>;
+
+/// Wraps a `futures::Sink` so that failed sends and flushes are retried according to a
+/// `RetryConfig`, for producers writing to sockets, channels and message brokers.
+///
+/// A failed `send` is attempted again with a clone of the item, after the delay of the config's
+/// strategy, until it succeeds or the attempts run out; errors the config does not classify as
+/// retryable are returned right away. Many sinks are unusable once they returned an error, such
+/// as a sink over a closed connection: with a factory, set with `with_factory`, the failed sink
+/// is dropped and a new one is created before the next attempt.
+///
+/// # Example
+/// ```
+/// use async_std::task::block_on;
+/// use futures_sink::Sink;
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::sink::RetrySink;
+/// use resilient_rs::strategies::RetryStrategy;
+/// use std::pin::Pin;
+/// use std::task::{Context, Poll};
+/// use std::time::Duration;
+///
+/// /// A connection that drops the first message it is given.
+/// struct Connection {
+///     sent: Vec<String>,
+///     dropped: bool,
+/// }
+///
+/// impl Sink<String> for Connection {
+///     type Error = String;
+///
+///     fn poll_ready(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), String>> {
+///         Poll::Ready(Ok(()))
+///     }
+///
+///     fn start_send(mut self: Pin<&mut Self>, item: String) -> Result<(), String> {
+///         if !self.dropped {
+///             self.dropped = true;
+///             return Err("connection reset".to_string());
+///         }
+///         self.sent.push(item);
+///         Ok(())
+///     }
+///
+///     fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), String>> {
+///         Poll::Ready(Ok(()))
+///     }
+///
+///     fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), String>> {
+///         Poll::Ready(Ok(()))
+///     }
+/// }
+///
+/// let config = RetryConfig::new(3, Duration::from_millis(10), RetryStrategy::Linear);
+/// let connection = Connection { sent: Vec::new(), dropped: false };
+/// let mut sink = RetrySink::new(connection, config);
+/// block_on(sink.send("order created".to_string())).unwrap();
+/// assert_eq!(sink.get_ref().unwrap().sent, ["order created"]);
+/// ```
+pub struct RetrySink<S, E> {
+    sink: Option<S>,
+    factory: Option<Box<dyn FnMut() -> SinkFuture<S, E> + Send>>,
+    retry_config: RetryConfig<E>,
+}
+
+impl<S, E> RetrySink<S, E> {
+    /// Wraps `sink`, retrying its sends and flushes according to `retry_config`.
+    ///
+    /// # Arguments
+    /// * `sink` - The sink to write to.
+    /// * `retry_config` - The attempts, delays and strategy of the retries of every operation.
+    pub fn new(sink: S, retry_config: RetryConfig<E>) -> Self {
+        RetrySink {
+            sink: Some(sink),
+            factory: None,
+            retry_config,
+        }
+    }
+
+    /// Builder-style setter for a factory re-creating the sink after it failed.
+    ///
+    /// When set, the sink is dropped after every failed operation and the factory is called
+    /// before the next attempt, e.g. to reconnect a socket. A factory error counts as a failed
+    /// attempt.
+    ///
+    /// # Arguments
+    /// * `factory` - A closure returning a `Future` resolving to a new sink.
+    ///
+    /// # Returns
+    /// The updated `RetrySink` with the specified factory.
+    pub fn with_factory<F, Fut>(mut self, mut factory: F) -> Self
+    where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<S, E>> + Send + 'static,
+    {
+        self.factory = Some(Box::new(move || Box::pin(factory())));
+        self
+    }
+
+    /// Returns the current sink, or `None` if it failed and was not re-created yet.
+    pub fn get_ref(&self) -> Option<&S> {
+        self.sink.as_ref()
+    }
+
+    /// Consumes the `RetrySink`, returning the current sink, or `None` if it failed and was not
+    /// re-created yet.
+    pub fn into_inner(self) -> Option<S> {
+        self.sink
+    }
+
+    /// Sends `item` into the sink and flushes it, retrying on failure.
+    ///
+    /// # Returns
+    /// - `Ok(())` once the item was sent and flushed.
+    /// - `Err(E)` with the error of the last attempt, once the attempts are exhausted or an
+    ///   error is not retryable.
+    pub async fn send<Item>(&mut self, item: Item) -> Result<(), E>
+    where
+        Item: Clone,
+        S: Sink<Item, Error = E> + Unpin,
+    {
+        self.retry(Some(item)).await
+    }
+
+    /// Flushes the items buffered by the sink, retrying on failure.
+    ///
+    /// # Returns
+    /// The same as `send`.
+    pub async fn flush<Item>(&mut self) -> Result<(), E>
+    where
+        Item: Clone,
+        S: Sink<Item, Error = E> + Unpin,
+    {
+        self.retry(None).await
+    }
+
+    async fn retry<Item>(&mut self, item: Option<Item>) -> Result<(), E>
+    where
+        Item: Clone,
+        S: Sink<Item, Error = E> + Unpin,
+    {
+        let mut attempt = 1;
+        let mut delay = self.retry_config.delay;
+        loop {
+            let result = match (self.sink.as_mut(), self.factory.as_mut()) {
+                (Some(sink), _) => send_and_flush(sink, item.clone()).await,
+                (None, Some(factory)) => match factory().await {
+                    Ok(sink) => send_and_flush(self.sink.insert(sink), item.clone()).await,
+                    Err(err) => Err(err),
+                },
+                (None, None) => unreachable!("a sink is only dropped when it can be re-created"),
+            };
+            let err = match result {
+                Ok(()) => return Ok(()),
+                Err(err) => err,
+            };
+            let class = self.retry_config.classify(&err);
+            let Some(wait) = schedule_class_retry(&self.retry_config, class, attempt, delay) else {
+                return Err(err);
+            };
+            if self.factory.is_some() {
+                log_with!(
+                    self.retry_config.log,
+                    Level::Debug,
+                    "Sink failed; re-creating it before the next attempt"
+                );
+                self.sink = None;
+            }
+            time::sleep_on(self.retry_config.timer_wheel.as_ref(), wait).await;
+            delay = self.retry_config.next_delay(delay, attempt);
+            attempt += 1;
+        }
+    }
+}
+
+/// Sends `item`, if any, into `sink` and flushes it.
+pub(crate) async fn send_and_flush<S, Item>(
+    sink: &mut S,
+    item: Option<Item>,
+) -> Result<(), S::Error>
+where
+    S: Sink<Item> + Unpin,
+{
+    if let Some(item) = item {
+        poll_fn(|cx| Pin::new(&mut *sink).poll_ready(cx)).await?;
+        Pin::new(&mut *sink).start_send(item)?;
+    }
+    poll_fn(|cx| Pin::new(&mut *sink).poll_flush(cx)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classifier::ErrorClass;
+    use crate::sim::VirtualClock;
+    use crate::strategies::RetryStrategy;
+    use std::sync::{Arc, Mutex};
+    use std::task::{Context, Poll};
+    use std::time::Duration;
+
+    /// A sink writing into a shared log, refusing the first `failures` items it is given.
+    struct FlakySink {
+        written: Arc<Mutex<Vec<u32>>>,
+        buffered: Vec<u32>,
+        failures: usize,
+    }
+
+    impl Sink<u32> for FlakySink {
+        type Error = &'static str;
+
+        fn poll_ready(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn start_send(mut self: Pin<&mut Self>, item: u32) -> Result<(), Self::Error> {
+            if self.failures > 0 {
+                self.failures -= 1;
+                return Err("broken pipe");
+            }
+            self.buffered.push(item);
+            Ok(())
+        }
+
+        fn poll_flush(
+            mut self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+        ) -> Poll<Result<(), Self::Error>> {
+            let buffered = std::mem::take(&mut self.buffered);
+            self.written.lock().unwrap().extend(buffered);
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn flaky(written: &Arc<Mutex<Vec<u32>>>, failures: usize) -> FlakySink {
+        FlakySink {
+            written: written.clone(),
+            buffered: Vec::new(),
+            failures,
+        }
+    }
+
+    #[test]
+    fn test_send_is_retried_on_the_same_sink() {
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let config = RetryConfig::new(3, Duration::from_secs(1), RetryStrategy::Linear);
+        let mut sink = RetrySink::new(flaky(&written, 2), config);
+        let clock = VirtualClock::new();
+
+        assert_eq!(clock.block_on(sink.send(7)), Ok(()));
+        assert_eq!(clock.elapsed(), Duration::from_secs(2));
+        assert_eq!(*written.lock().unwrap(), [7]);
+
+        let config = RetryConfig::new(3, Duration::from_secs(1), RetryStrategy::Linear)
+            .with_error_classifier(|_: &&str| ErrorClass::Permanent);
+        let mut sink = RetrySink::new(flaky(&written, 1), config);
+        assert_eq!(clock.block_on(sink.send(8)), Err("broken pipe"));
+    }
+
+    #[test]
+    fn test_factory_re_creates_the_failed_sink() {
+        let written = Arc::new(Mutex::new(Vec::new()));
+        let connections = Arc::new(Mutex::new(0));
+        let config = RetryConfig::new(3, Duration::from_secs(1), RetryStrategy::Linear);
+        let mut sink = RetrySink::new(flaky(&written, 1), config).with_factory({
+            let written = written.clone();
+            let connections = connections.clone();
+            move || {
+                *connections.lock().unwrap() += 1;
+                let sink = flaky(&written, 0);
+                async move { Ok(sink) }
+            }
+        });
+
+        assert_eq!(VirtualClock::new().block_on(sink.send(1)), Ok(()));
+        assert_eq!(*connections.lock().unwrap(), 1);
+        assert_eq!(*written.lock().unwrap(), [1]);
+    }
+}
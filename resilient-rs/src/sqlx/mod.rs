@@ -0,0 +1,288 @@
+use crate::asynchronous::{back_off, schedule_class_retry};
+use crate::classifier::{ErrorClass, ErrorClassifier};
+use crate::config::RetryConfig;
+use crate::events::{self, ResilienceEvent};
+use crate::logging::Level;
+use crate::logging::log_with;
+use crate::time;
+use sqlx::{Database, Error, Pool, Transaction};
+use std::future::Future;
+use std::pin::Pin;
+
+/// The future returned by the transaction closures of `retry_tx`, borrowing the transaction.
+pub type TxFuture<'t, T> = Pin<Box<dyn Future<Output = Result<T, Error>> + Send + 't>>;
+
+/// SQLSTATE codes of failures caused by concurrent transactions or a database going away, which
+/// succeed when the transaction is run again.
+const RETRYABLE_SQLSTATES: [&str; 8] = [
+    "40001", // serialization_failure, also reported by MySQL for deadlocks
+    "40P01", // deadlock_detected
+    "40003", // statement_completion_unknown
+    "55P03", // lock_not_available
+    "53300", // too_many_connections
+    "57P01", // admin_shutdown
+    "57P02", // crash_shutdown
+    "57P03", // cannot_connect_now
+];
+
+/// An `ErrorClassifier` for `sqlx::Error`, separating failures worth running the transaction
+/// again from permanent ones.
+///
+/// - Serialization failures (`40001`), deadlocks (`40P01`), lock timeouts, database shutdowns,
+///   connection exceptions (SQLSTATE class `08`) and too many connections are `Transient`.
+/// - Dropped connections (`Io`), pool timeouts and crashed background workers are `Transient`.
+/// - Every other error, such as constraint violations, missing rows, decoding errors or a closed
+///   pool, is `Permanent`.
+///
+/// The classification relies on SQLSTATE codes, reported by PostgreSQL and MySQL. SQLite busy
+/// errors have no SQLSTATE; wrap the classifier in a closure to retry them.
+///
+/// # Example
+/// ```
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::sqlx::SqlxClassifier;
+///
+/// let config: RetryConfig<sqlx::Error> = RetryConfig::default().with_error_classifier(SqlxClassifier);
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SqlxClassifier;
+
+impl ErrorClassifier<Error> for SqlxClassifier {
+    fn classify(&self, error: &Error) -> ErrorClass {
+        match error {
+            Error::Database(err) => match err.code() {
+                Some(code) if code.starts_with("08") || RETRYABLE_SQLSTATES.contains(&&*code) => {
+                    ErrorClass::Transient
+                }
+                _ if err.is_transient_in_connect_phase() => ErrorClass::Transient,
+                _ => ErrorClass::Permanent,
+            },
+            Error::Io(_) | Error::PoolTimedOut | Error::WorkerCrashed => ErrorClass::Transient,
+            _ => ErrorClass::Permanent,
+        }
+    }
+}
+
+/// Runs a database transaction, running it again from the start on retryable failures.
+///
+/// Every attempt begins a new transaction on `pool` and passes it to `transaction`. The
+/// transaction is committed if the closure succeeds and rolled back if it fails. A failure to
+/// begin, of the closure or of the commit (where PostgreSQL reports serialization failures of
+/// `SERIALIZABLE` transactions) is retried according to `retry_config`, so the closure must not
+/// have side effects outside of the transaction.
+///
+/// Failures are classified by the `error_classifier` or `retry_condition` of `retry_config` when
+/// one is set, and by `SqlxClassifier` otherwise.
+///
+/// # Arguments
+/// * `pool` - The pool the transactions are begun on.
+/// * `retry_config` - The attempts, delays and strategy of the retries.
+/// * `transaction` - The body of the transaction, returning a boxed future borrowing it.
+///
+/// # Returns
+/// - `Ok(T)` with the result of the closure once a transaction was committed.
+/// - `Err(sqlx::Error)` with the last failure otherwise.
+///
+/// # Example
+/// ```
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::sqlx::retry_tx;
+/// use sqlx::SqlitePool;
+///
+/// async fn transfer(pool: &SqlitePool) -> Result<u64, sqlx::Error> {
+///     retry_tx(pool, &RetryConfig::default(), |tx| {
+///         Box::pin(async move {
+///             sqlx::query("UPDATE accounts SET balance = balance - 10 WHERE id = 1")
+///                 .execute(&mut **tx)
+///                 .await?;
+///             let credited = sqlx::query("UPDATE accounts SET balance = balance + 10 WHERE id = 2")
+///                 .execute(&mut **tx)
+///                 .await?;
+///             Ok(credited.rows_affected())
+///         })
+///     })
+///     .await
+/// }
+/// ```
+pub async fn retry_tx<DB, F, T>(
+    pool: &Pool<DB>,
+    retry_config: &RetryConfig<Error>,
+    mut transaction: F,
+) -> Result<T, Error>
+where
+    DB: Database,
+    F: for<'t> FnMut(&'t mut Transaction<'static, DB>) -> TxFuture<'t, T>,
+{
+    let mut attempts = 0;
+    let mut delay = retry_config.delay;
+
+    loop {
+        events::emit(ResilienceEvent::AttemptStarted {
+            attempt: attempts + 1,
+        });
+        let start = time::now();
+        let result = run_tx(pool, &mut transaction).await;
+        let elapsed = time::elapsed(start);
+        retry_config.record(|stats| stats.record_attempt(elapsed));
+
+        let err = match result {
+            Ok(value) => {
+                retry_config.record(|stats| stats.record_success(attempts > 0));
+                return Ok(value);
+            }
+            Err(err) => err,
+        };
+        let class =
+            if retry_config.error_classifier.is_some() || retry_config.retry_condition.is_some() {
+                retry_config.classify(&err)
+            } else {
+                SqlxClassifier.classify(&err)
+            };
+        let Some(wait) = schedule_class_retry(retry_config, class, attempts + 1, delay) else {
+            return Err(err);
+        };
+        log_with!(
+            retry_config.log,
+            Level::Info,
+            "Transaction failed: {}, running it again",
+            err
+        );
+        back_off(None, retry_config.timer_wheel.as_ref(), wait).await;
+        delay = retry_config.next_delay(delay, attempts + 1);
+        attempts += 1;
+    }
+}
+
+/// Runs one attempt of `retry_tx`: begins a transaction, runs the closure, then commits or rolls
+/// back.
+async fn run_tx<DB, F, T>(pool: &Pool<DB>, transaction: &mut F) -> Result<T, Error>
+where
+    DB: Database,
+    F: for<'t> FnMut(&'t mut Transaction<'static, DB>) -> TxFuture<'t, T>,
+{
+    let mut tx = pool.begin().await?;
+    match transaction(&mut tx).await {
+        Ok(value) => {
+            tx.commit().await?;
+            Ok(value)
+        }
+        Err(err) => {
+            // A failed rollback leaves nothing to undo: the connection is closed by the pool.
+            let _ = tx.rollback().await;
+            Err(err)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::error::{DatabaseError, ErrorKind};
+    use sqlx::sqlite::SqlitePool;
+    use std::borrow::Cow;
+    use std::fmt;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    #[derive(Debug)]
+    struct Sqlstate(&'static str);
+
+    impl fmt::Display for Sqlstate {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "SQLSTATE {}", self.0)
+        }
+    }
+
+    impl std::error::Error for Sqlstate {}
+
+    impl DatabaseError for Sqlstate {
+        fn message(&self) -> &str {
+            self.0
+        }
+
+        fn code(&self) -> Option<Cow<'_, str>> {
+            Some(Cow::Borrowed(self.0))
+        }
+
+        fn as_error(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn as_error_mut(&mut self) -> &mut (dyn std::error::Error + Send + Sync + 'static) {
+            self
+        }
+
+        fn into_error(self: Box<Self>) -> Box<dyn std::error::Error + Send + Sync + 'static> {
+            self
+        }
+
+        fn kind(&self) -> ErrorKind {
+            ErrorKind::Other
+        }
+    }
+
+    fn database_error(code: &'static str) -> Error {
+        Error::Database(Box::new(Sqlstate(code)))
+    }
+
+    #[test]
+    fn test_classifies_sqlstates() {
+        assert_eq!(
+            SqlxClassifier.classify(&database_error("40001")),
+            ErrorClass::Transient
+        );
+        assert_eq!(
+            SqlxClassifier.classify(&database_error("40P01")),
+            ErrorClass::Transient
+        );
+        assert_eq!(
+            SqlxClassifier.classify(&database_error("08006")),
+            ErrorClass::Transient
+        );
+        assert_eq!(
+            SqlxClassifier.classify(&database_error("23505")),
+            ErrorClass::Permanent
+        );
+        assert_eq!(
+            SqlxClassifier.classify(&Error::PoolTimedOut),
+            ErrorClass::Transient
+        );
+        assert_eq!(
+            SqlxClassifier.classify(&Error::RowNotFound),
+            ErrorClass::Permanent
+        );
+    }
+
+    #[test]
+    fn test_retry_tx_runs_serialization_failures_again() {
+        async_std::task::block_on(async {
+            let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+            let config = RetryConfig {
+                max_attempts: 3,
+                delay: Duration::from_millis(1),
+                ..RetryConfig::default()
+            };
+            let attempts = AtomicUsize::new(0);
+
+            let result = retry_tx(&pool, &config, |tx| {
+                let attempt = attempts.fetch_add(1, Ordering::SeqCst);
+                Box::pin(async move {
+                    let row: (i64,) = sqlx::query_as("SELECT 42").fetch_one(&mut **tx).await?;
+                    match attempt {
+                        0 => Err(database_error("40001")),
+                        _ => Ok(row.0),
+                    }
+                })
+            })
+            .await;
+            assert_eq!(result.unwrap(), 42);
+            assert_eq!(attempts.load(Ordering::SeqCst), 2);
+
+            let result: Result<(), Error> = retry_tx(&pool, &config, |_| {
+                Box::pin(async { Err(database_error("23505")) })
+            })
+            .await;
+            assert!(result.is_err());
+        });
+    }
+}
@@ -0,0 +1,239 @@
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The default number of per-attempt latencies kept by a `Stats` handle.
+const DEFAULT_LATENCY_CAPACITY: usize = 1024;
+
+/// A shared, opt-in statistics collector for retries and circuit breakers.
+///
+/// `Stats` is a cheap, cloneable handle around shared counters. Attach the same handle to any
+/// number of `RetryConfig`s and `CircuitBreaker`s and read the aggregated values through the
+/// getters, for example to export them to your own monitoring system.
+///
+/// Per-attempt latencies are kept in a bounded buffer holding the most recent samples, so memory
+/// usage stays constant regardless of how many operations are executed.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::stats::Stats;
+/// use resilient_rs::strategies::RetryStrategy;
+/// use resilient_rs::synchronous::retry;
+///
+/// let stats = Stats::new();
+/// let config = RetryConfig::new(3, Duration::from_millis(1), RetryStrategy::Linear)
+///     .with_stats(stats.clone());
+///
+/// let mut calls = 0;
+/// let result: Result<i32, &str> = retry(|| {
+///     calls += 1;
+///     if calls < 2 { Err("transient") } else { Ok(42) }
+/// }, &config);
+///
+/// assert_eq!(result, Ok(42));
+/// assert_eq!(stats.attempts(), 2);
+/// assert_eq!(stats.successes_after_retry(), 1);
+/// assert_eq!(stats.give_ups(), 0);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Stats {
+    inner: Arc<StatsInner>,
+}
+
+#[derive(Debug)]
+struct StatsInner {
+    attempts: AtomicU64,
+    successes: AtomicU64,
+    successes_after_retry: AtomicU64,
+    give_ups: AtomicU64,
+    backoff_nanos: AtomicU64,
+    rejections: AtomicU64,
+    breaker_opens: AtomicU64,
+    latency_capacity: usize,
+    latencies: Mutex<VecDeque<Duration>>,
+}
+
+impl Default for Stats {
+    /// Creates a `Stats` handle keeping the 1024 most recent attempt latencies.
+    fn default() -> Self {
+        Stats::with_latency_capacity(DEFAULT_LATENCY_CAPACITY)
+    }
+}
+
+impl Stats {
+    /// Creates a new, empty `Stats` handle.
+    pub fn new() -> Self {
+        Stats::default()
+    }
+
+    /// Returns `true` if both handles share the same statistics.
+    pub(crate) fn ptr_eq(&self, other: &Stats) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+
+    /// Creates a new `Stats` handle that keeps at most `capacity` per-attempt latencies.
+    ///
+    /// # Arguments
+    /// * `capacity` - The maximum number of latency samples retained; older samples are dropped first.
+    pub fn with_latency_capacity(capacity: usize) -> Self {
+        Stats {
+            inner: Arc::new(StatsInner {
+                attempts: AtomicU64::new(0),
+                successes: AtomicU64::new(0),
+                successes_after_retry: AtomicU64::new(0),
+                give_ups: AtomicU64::new(0),
+                backoff_nanos: AtomicU64::new(0),
+                rejections: AtomicU64::new(0),
+                breaker_opens: AtomicU64::new(0),
+                latency_capacity: capacity,
+                latencies: Mutex::new(VecDeque::with_capacity(capacity)),
+            }),
+        }
+    }
+
+    /// The total number of times an operation was executed, including retries.
+    pub fn attempts(&self) -> u64 {
+        self.inner.attempts.load(Ordering::Relaxed)
+    }
+
+    /// The number of operations that eventually succeeded, on the first attempt or after retries.
+    pub fn successes(&self) -> u64 {
+        self.inner.successes.load(Ordering::Relaxed)
+    }
+
+    /// The number of operations that succeeded only after at least one retry.
+    pub fn successes_after_retry(&self) -> u64 {
+        self.inner.successes_after_retry.load(Ordering::Relaxed)
+    }
+
+    /// The number of operations that were abandoned, either because attempts were exhausted or
+    /// because the error was not retryable.
+    pub fn give_ups(&self) -> u64 {
+        self.inner.give_ups.load(Ordering::Relaxed)
+    }
+
+    /// The cumulative time spent sleeping between retry attempts.
+    pub fn total_backoff(&self) -> Duration {
+        Duration::from_nanos(self.inner.backoff_nanos.load(Ordering::Relaxed))
+    }
+
+    /// The number of calls rejected by a circuit breaker while it was open.
+    pub fn rejections(&self) -> u64 {
+        self.inner.rejections.load(Ordering::Relaxed)
+    }
+
+    /// The number of times a circuit breaker transitioned to the open state.
+    pub fn breaker_opens(&self) -> u64 {
+        self.inner.breaker_opens.load(Ordering::Relaxed)
+    }
+
+    /// The most recent per-attempt latencies, oldest first.
+    pub fn latencies(&self) -> Vec<Duration> {
+        self.inner
+            .latencies
+            .lock()
+            .unwrap()
+            .iter()
+            .copied()
+            .collect()
+    }
+
+    /// Resets every counter and clears the recorded latencies.
+    pub fn reset(&self) {
+        self.inner.attempts.store(0, Ordering::Relaxed);
+        self.inner.successes.store(0, Ordering::Relaxed);
+        self.inner.successes_after_retry.store(0, Ordering::Relaxed);
+        self.inner.give_ups.store(0, Ordering::Relaxed);
+        self.inner.backoff_nanos.store(0, Ordering::Relaxed);
+        self.inner.rejections.store(0, Ordering::Relaxed);
+        self.inner.breaker_opens.store(0, Ordering::Relaxed);
+        self.inner.latencies.lock().unwrap().clear();
+    }
+
+    pub(crate) fn record_attempt(&self, latency: Duration) {
+        self.inner.attempts.fetch_add(1, Ordering::Relaxed);
+        if self.inner.latency_capacity == 0 {
+            return;
+        }
+        let mut latencies = self.inner.latencies.lock().unwrap();
+        if latencies.len() == self.inner.latency_capacity {
+            latencies.pop_front();
+        }
+        latencies.push_back(latency);
+    }
+
+    pub(crate) fn record_success(&self, retried: bool) {
+        self.inner.successes.fetch_add(1, Ordering::Relaxed);
+        if retried {
+            self.inner
+                .successes_after_retry
+                .fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub(crate) fn record_give_up(&self) {
+        self.inner.give_ups.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_backoff(&self, delay: Duration) {
+        let nanos = u64::try_from(delay.as_nanos()).unwrap_or(u64::MAX);
+        self.inner.backoff_nanos.fetch_add(nanos, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_rejection(&self) {
+        self.inner.rejections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_breaker_open(&self) {
+        self.inner.breaker_opens.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clones_share_counters() {
+        let stats = Stats::new();
+        let clone = stats.clone();
+        clone.record_attempt(Duration::from_millis(5));
+        clone.record_success(true);
+        clone.record_backoff(Duration::from_millis(10));
+        assert_eq!(stats.attempts(), 1);
+        assert_eq!(stats.successes(), 1);
+        assert_eq!(stats.successes_after_retry(), 1);
+        assert_eq!(stats.total_backoff(), Duration::from_millis(10));
+    }
+
+    #[test]
+    fn test_latencies_are_bounded() {
+        let stats = Stats::with_latency_capacity(2);
+        for ms in 1..=3 {
+            stats.record_attempt(Duration::from_millis(ms));
+        }
+        assert_eq!(stats.attempts(), 3);
+        assert_eq!(
+            stats.latencies(),
+            vec![Duration::from_millis(2), Duration::from_millis(3)]
+        );
+    }
+
+    #[test]
+    fn test_reset() {
+        let stats = Stats::new();
+        stats.record_attempt(Duration::from_millis(1));
+        stats.record_give_up();
+        stats.record_rejection();
+        stats.record_breaker_open();
+        stats.reset();
+        assert_eq!(stats.attempts(), 0);
+        assert_eq!(stats.give_ups(), 0);
+        assert_eq!(stats.rejections(), 0);
+        assert_eq!(stats.breaker_opens(), 0);
+        assert!(stats.latencies().is_empty());
+    }
+}
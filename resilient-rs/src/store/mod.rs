@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::sync::Mutex;
+use std::time::SystemTime;
+
+#[cfg(feature = "redis")]
+mod redis;
+
+#[cfg(feature = "redis")]
+pub use self::redis::RedisStateStore;
+
+/// The error type returned by `StateStore` operations.
+pub type StoreError = Box<dyn Error + Send + Sync>;
+
+/// The circuit breaker state shared through a `StateStore`.
+///
+/// Only what replicas must agree on is shared: whether the circuit is open (and since when), and
+/// the number of consecutive failures. `HalfOpen` trials, success counts and sliding
+/// `FailureWindow`s stay local to each breaker.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SharedBreakerState {
+    /// When the circuit was opened, or `None` if it is closed.
+    pub open_since: Option<SystemTime>,
+    /// Consecutive failures recorded by every breaker sharing the state.
+    pub failure_count: usize,
+}
+
+/// A backend sharing circuit breaker state between breakers, typically across the replicas of a
+/// horizontally scaled service.
+///
+/// Without a shared store, every replica trips on its own, so a failing dependency keeps receiving
+/// traffic from the replicas that have not yet seen enough failures. A breaker configured with
+/// `with_state_store` counts its failures in the store and opens as soon as any breaker sharing
+/// the name has opened.
+///
+/// Every method is called on the caller's thread while the breaker decides on or records a call,
+/// but without holding the breaker's lock, so a slow store delays only the calls talking to it.
+/// Implementations should still be fast and bound their latency with timeouts. When an operation
+/// fails, the breaker logs the error and falls back to its local state for that call, so an
+/// unavailable store never blocks traffic.
+pub trait StateStore: Send + Sync {
+    /// Returns the shared state of the breaker named `name`.
+    fn load(&self, name: &str) -> Result<SharedBreakerState, StoreError>;
+
+    /// Atomically increments the consecutive failure count and returns the new count.
+    fn record_failure(&self, name: &str) -> Result<usize, StoreError>;
+
+    /// Resets the consecutive failure count after a successful call.
+    fn reset_failures(&self, name: &str) -> Result<(), StoreError>;
+
+    /// Marks the circuit as open since `at`.
+    fn open(&self, name: &str, at: SystemTime) -> Result<(), StoreError>;
+
+    /// Marks the circuit as closed and resets the consecutive failure count.
+    fn close(&self, name: &str) -> Result<(), StoreError>;
+}
+
+/// A `StateStore` keeping the shared state in memory.
+///
+/// It shares state between breakers of the same process, e.g. the asynchronous and synchronous
+/// breakers guarding one dependency, and is useful in tests of code written against `StateStore`.
+///
+/// # Example
+/// ```
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use resilient_rs::config::CircuitBreakerConfig;
+/// use resilient_rs::store::InMemoryStateStore;
+/// use resilient_rs::synchronous::CircuitBreaker;
+///
+/// let store = Arc::new(InMemoryStateStore::new());
+/// let config = CircuitBreakerConfig::new(1, 1, Duration::from_secs(30));
+/// let replica_a = CircuitBreaker::<&str>::with_config(config).with_state_store("inventory", store.clone());
+/// let replica_b = CircuitBreaker::<&str>::with_config(config).with_state_store("inventory", store);
+///
+/// let _ = replica_a.run(|| Err::<(), _>("down"));
+/// assert!(replica_b.run(|| Ok::<_, &str>(())).unwrap_err().is_open());
+/// ```
+#[derive(Debug, Default)]
+pub struct InMemoryStateStore {
+    states: Mutex<HashMap<String, SharedBreakerState>>,
+}
+
+impl InMemoryStateStore {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        InMemoryStateStore::default()
+    }
+
+    fn update<T>(&self, name: &str, f: impl FnOnce(&mut SharedBreakerState) -> T) -> T {
+        let mut states = self.states.lock().unwrap();
+        f(states.entry(name.to_string()).or_default())
+    }
+}
+
+impl StateStore for InMemoryStateStore {
+    fn load(&self, name: &str) -> Result<SharedBreakerState, StoreError> {
+        Ok(self
+            .states
+            .lock()
+            .unwrap()
+            .get(name)
+            .copied()
+            .unwrap_or_default())
+    }
+
+    fn record_failure(&self, name: &str) -> Result<usize, StoreError> {
+        Ok(self.update(name, |state| {
+            state.failure_count += 1;
+            state.failure_count
+        }))
+    }
+
+    fn reset_failures(&self, name: &str) -> Result<(), StoreError> {
+        self.update(name, |state| state.failure_count = 0);
+        Ok(())
+    }
+
+    fn open(&self, name: &str, at: SystemTime) -> Result<(), StoreError> {
+        self.update(name, |state| state.open_since = Some(at));
+        Ok(())
+    }
+
+    fn close(&self, name: &str) -> Result<(), StoreError> {
+        self.update(name, |state| *state = SharedBreakerState::default());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_in_memory_store_tracks_state_per_name() {
+        let store = InMemoryStateStore::new();
+        assert_eq!(store.record_failure("a").unwrap(), 1);
+        assert_eq!(store.record_failure("a").unwrap(), 2);
+        assert_eq!(store.load("b").unwrap(), SharedBreakerState::default());
+
+        let at = SystemTime::now();
+        store.open("a", at).unwrap();
+        assert_eq!(
+            store.load("a").unwrap(),
+            SharedBreakerState {
+                open_since: Some(at),
+                failure_count: 2
+            }
+        );
+        store.close("a").unwrap();
+        assert_eq!(store.load("a").unwrap(), SharedBreakerState::default());
+    }
+}
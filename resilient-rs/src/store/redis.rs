@@ -0,0 +1,173 @@
+use super::{SharedBreakerState, StateStore, StoreError};
+use crate::time;
+use ::redis::{Client, Commands, Connection, IntoConnectionInfo};
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// A `StateStore` sharing circuit breaker state through Redis.
+///
+/// The state of the breaker named `name` is kept under two keys, `{prefix}:{name}:failures` (the
+/// consecutive failure count, updated with `INCR`) and `{prefix}:{name}:open_since` (the opening
+/// time in milliseconds since the Unix epoch), so every replica pointing to the same Redis
+/// instance observes the same state.
+///
+/// A single connection is opened lazily and reused. Connecting, reading and writing are bounded by
+/// a timeout (100ms by default), since the store is called on every breaker call. After an error
+/// the connection is dropped, and calls fail immediately for a backoff period (1s by default)
+/// before a reconnection is attempted, so an unreachable Redis costs each call at most one
+/// timeout and most calls none; the breakers fall back to their local state meanwhile.
+///
+/// This type is available with the `redis` feature.
+///
+/// # Example
+/// ```no_run
+/// use std::sync::Arc;
+/// use resilient_rs::asynchronous::CircuitBreaker;
+/// use resilient_rs::config::CircuitBreakerConfig;
+/// use resilient_rs::store::RedisStateStore;
+///
+/// let store = Arc::new(RedisStateStore::open("redis://127.0.0.1/").unwrap());
+/// let cb = CircuitBreaker::new(CircuitBreakerConfig::default()).with_state_store("payments-api", store);
+/// ```
+pub struct RedisStateStore {
+    client: Client,
+    prefix: String,
+    timeout: Duration,
+    reconnect_backoff: Duration,
+    link: Mutex<Link>,
+}
+
+/// The shared connection, or when the next reconnection may be attempted.
+#[derive(Default)]
+struct Link {
+    connection: Option<Connection>,
+    retry_at: Option<Instant>,
+}
+
+impl RedisStateStore {
+    /// Creates a store for the Redis instance at `info`, e.g. `"redis://127.0.0.1/"`.
+    ///
+    /// No connection is made until the store is first used.
+    pub fn open(info: impl IntoConnectionInfo) -> Result<Self, StoreError> {
+        Ok(RedisStateStore {
+            client: Client::open(info)?,
+            prefix: "resilient-rs:breaker".to_string(),
+            timeout: Duration::from_millis(100),
+            reconnect_backoff: Duration::from_secs(1),
+            link: Mutex::new(Link::default()),
+        })
+    }
+
+    /// Sets the timeout of connecting to Redis and of every read and write, and returns the
+    /// modified store.
+    ///
+    /// # Arguments
+    /// * `timeout` - The timeout, 100ms by default. It must not be zero.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets how long calls fail without trying to reconnect after a connection error, and returns
+    /// the modified store.
+    ///
+    /// # Arguments
+    /// * `backoff` - The backoff, 1s by default.
+    pub fn with_reconnect_backoff(mut self, backoff: Duration) -> Self {
+        self.reconnect_backoff = backoff;
+        self
+    }
+
+    /// Sets the prefix of the keys used by this store and returns the modified store.
+    ///
+    /// # Arguments
+    /// * `prefix` - The key prefix, `"resilient-rs:breaker"` by default.
+    pub fn with_prefix(mut self, prefix: impl Into<String>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    fn key(&self, name: &str, field: &str) -> String {
+        format!("{}:{}:{}", self.prefix, name, field)
+    }
+
+    /// Opens a connection whose reads and writes are bounded by the timeout.
+    fn connect(&self) -> ::redis::RedisResult<Connection> {
+        let connection = self.client.get_connection_with_timeout(self.timeout)?;
+        connection.set_read_timeout(Some(self.timeout))?;
+        connection.set_write_timeout(Some(self.timeout))?;
+        Ok(connection)
+    }
+
+    /// Runs `f` on the shared connection, opening it if needed.
+    ///
+    /// On error the connection is dropped, and no reconnection is attempted before the backoff
+    /// has elapsed.
+    fn with_connection<T>(
+        &self,
+        f: impl FnOnce(&mut Connection) -> ::redis::RedisResult<T>,
+    ) -> Result<T, StoreError> {
+        let mut link = self.link.lock().unwrap();
+        if link.connection.is_none() {
+            if let Some(retry_at) = link.retry_at
+                && time::now() < retry_at
+            {
+                return Err(format!(
+                    "Redis is unavailable, reconnecting in {:?}",
+                    retry_at.saturating_duration_since(time::now())
+                )
+                .into());
+            }
+            match self.connect() {
+                Ok(connection) => link.connection = Some(connection),
+                Err(err) => {
+                    link.retry_at = Some(time::now() + self.reconnect_backoff);
+                    return Err(err.into());
+                }
+            }
+        }
+        let result = f(link
+            .connection
+            .as_mut()
+            .expect("connection was just opened"));
+        if result.is_err() {
+            link.connection = None;
+            link.retry_at = Some(time::now() + self.reconnect_backoff);
+        }
+        Ok(result?)
+    }
+}
+
+impl StateStore for RedisStateStore {
+    fn load(&self, name: &str) -> Result<SharedBreakerState, StoreError> {
+        let keys = [self.key(name, "open_since"), self.key(name, "failures")];
+        let (open_since, failures): (Option<u64>, Option<u64>) =
+            self.with_connection(|connection| connection.mget(&keys))?;
+        Ok(SharedBreakerState {
+            open_since: open_since.map(|millis| UNIX_EPOCH + Duration::from_millis(millis)),
+            failure_count: failures.unwrap_or(0) as usize,
+        })
+    }
+
+    fn record_failure(&self, name: &str) -> Result<usize, StoreError> {
+        let key = self.key(name, "failures");
+        let count: u64 = self.with_connection(|connection| connection.incr(&key, 1))?;
+        Ok(count as usize)
+    }
+
+    fn reset_failures(&self, name: &str) -> Result<(), StoreError> {
+        let key = self.key(name, "failures");
+        self.with_connection(|connection| connection.del(&key))
+    }
+
+    fn open(&self, name: &str, at: SystemTime) -> Result<(), StoreError> {
+        let key = self.key(name, "open_since");
+        let millis = at.duration_since(UNIX_EPOCH)?.as_millis() as u64;
+        self.with_connection(|connection| connection.set(&key, millis))
+    }
+
+    fn close(&self, name: &str) -> Result<(), StoreError> {
+        let keys = [self.key(name, "open_since"), self.key(name, "failures")];
+        self.with_connection(|connection| connection.del(&keys))
+    }
+}
@@ -1,10 +1,14 @@
+use crate::time;
 use rand::Rng;
+use std::sync::Arc;
 use std::time::Duration;
 
 /// Defines the retry strategy to use when scheduling retry attempts.
 ///
 /// This enum specifies how delays between retries are calculated.
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum RetryStrategy {
     /// A linear retry strategy where the delay between retries remains constant.
     ///
@@ -50,65 +54,402 @@ pub enum RetryStrategy {
     /// - Retry 3: 9s
     /// - And so on...
     ArithmeticProgression { coefficient: usize },
+    /// A polynomial strategy where the delay grows with a power of the retry number.
+    ///
+    /// This sits between `ArithmeticProgression` and `ExponentialBackoff`: it grows faster than
+    /// a constant step, but without doubling. For example, with an exponent of 2 and a base
+    /// delay of 1s:
+    /// - Retry 1: 1s
+    /// - Retry 2: 4s
+    /// - Retry 3: 9s
+    /// - And so on...
+    Polynomial { exponent: u32 },
+    /// A harmonic strategy where the delay grows with the harmonic numbers `1 + 1/2 + ... + 1/n`.
+    ///
+    /// The delay keeps growing, but ever more slowly, which suits workloads that should back off
+    /// a little on every retry without ever waiting much longer than the base delay. For
+    /// example, with a base delay of 1s:
+    /// - Retry 1: 1s
+    /// - Retry 2: 1.5s
+    /// - Retry 3: ~1.83s
+    /// - Retry 4: ~2.08s
+    /// - And so on...
+    Harmonic,
 }
-/// Configuration for retrying operations.
-///
-/// This struct defines the parameters for retrying an operation, including
-/// the maximum number of attempts, the delay between retries, and the retry strategy.
-
 impl RetryStrategy {
     /// Calculates the delay duration for a specific retry attempt based on the retry strategy.
     ///
+    /// `attempt` is the 1-based index of the retry: `1` is the delay before the first retry,
+    /// `2` the delay before the second one, and so on. The delay is computed from `base_delay`
+    /// alone:
+    /// - `Linear` returns `base_delay`.
+    /// - `ExponentialBackoff` returns `base_delay * 2^(attempt - 1)`.
+    /// - `ExponentialBackoffWithJitter` returns `base_delay * 2^(attempt - 1)`, plus or minus up
+    ///   to `base_delay * jitter_factor`, never below zero.
+    /// - `FibonacciBackoff` returns `base_delay` times `1, 2, 3, 5, 8, ...` for attempts `1, 2,
+    ///   3, 4, 5, ...`, i.e. the Fibonacci number following the `attempt`-th one.
+    /// - `ArithmeticProgression` returns `base_delay * coefficient * attempt`.
+    /// - `Polynomial` returns `base_delay * attempt^exponent`.
+    /// - `Harmonic` returns `base_delay * (1 + 1/2 + ... + 1/attempt)`.
+    ///
+    /// An `attempt` of `0` returns `base_delay`, except with `ArithmeticProgression`, which
+    /// returns zero. Delays too long to be represented saturate at `Duration::MAX`, whatever the
+    /// `attempt`. No `max_delay` is applied.
+    ///
+    /// The retry loops of this crate apply the strategy to the previous delay rather than to the
+    /// base delay, so their delays grow faster than the ones returned here; use
+    /// `RetryConfig::backoff` to get the exact delays of a retry loop.
+    ///
     /// # Arguments
     /// * `base_delay` - The base duration to use as the starting point for delay calculations.
     /// * `attempt` - The current attempt number (1-based index for retries).
     ///
     /// # Returns
     /// A `Duration` representing the time to wait before the next retry attempt.
-    pub(crate) fn calculate_delay(&self, base_delay: Duration, attempt: usize) -> Duration {
+    ///
+    /// # Example
+    /// ```
+    /// use resilient_rs::strategies::RetryStrategy;
+    /// use std::time::Duration;
+    ///
+    /// let base = Duration::from_millis(100);
+    /// let delays: Vec<Duration> = (1..=5)
+    ///     .map(|attempt| RetryStrategy::ExponentialBackoff.calculate_delay(base, attempt))
+    ///     .collect();
+    /// assert_eq!(delays, [100, 200, 400, 800, 1600].map(Duration::from_millis));
+    /// assert_eq!(
+    ///     RetryStrategy::FibonacciBackoff.calculate_delay(base, 5),
+    ///     Duration::from_millis(800)
+    /// );
+    /// ```
+    pub fn calculate_delay(&self, base_delay: Duration, attempt: usize) -> Duration {
         match self {
             RetryStrategy::Linear => base_delay,
-            RetryStrategy::ExponentialBackoff => {
-                if attempt == 0 {
-                    base_delay
-                } else {
-                    base_delay * 2u32.pow((attempt - 1) as u32)
-                }
-            }
+            RetryStrategy::ExponentialBackoff => scaled(
+                base_delay,
+                u32::try_from(attempt.saturating_sub(1))
+                    .ok()
+                    .and_then(|exponent| 2u128.checked_pow(exponent)),
+            ),
             RetryStrategy::FibonacciBackoff => {
-                if attempt < 2 {
-                    base_delay
-                } else {
-                    let mut prev = base_delay;
-                    let mut curr = base_delay;
-                    for _ in 2..=attempt {
-                        let next = prev + curr;
-                        prev = curr;
-                        curr = next;
+                let mut prev = base_delay;
+                let mut curr = base_delay;
+                // Once saturated, the delay stays at `Duration::MAX` for every later attempt.
+                for _ in 2..=attempt {
+                    if curr == Duration::MAX {
+                        break;
                     }
-                    curr
+                    let next = prev.saturating_add(curr);
+                    prev = curr;
+                    curr = next;
                 }
+                curr
+            }
+            RetryStrategy::ArithmeticProgression { coefficient } => scaled(
+                base_delay,
+                (*coefficient as u128).checked_mul(attempt as u128),
+            ),
+            RetryStrategy::Polynomial { exponent } => {
+                scaled(base_delay, (attempt.max(1) as u128).checked_pow(*exponent))
             }
-            RetryStrategy::ArithmeticProgression { coefficient } => {
-                base_delay * (*coefficient as u32 * attempt as u32)
+            RetryStrategy::Harmonic => {
+                let n = attempt.max(1);
+                // Past a few thousand terms, the series is summed with its asymptotic expansion.
+                let harmonic = if n <= 4096 {
+                    (1..=n).map(|n| 1.0 / n as f64).sum()
+                } else {
+                    const EULER_GAMMA: f64 = 0.577_215_664_901_532_9;
+                    (n as f64).ln() + EULER_GAMMA + 1.0 / (2.0 * n as f64)
+                };
+                from_secs_saturating(base_delay.as_secs_f64() * harmonic)
             }
             RetryStrategy::ExponentialBackoffWithJitter { jitter_factor } => {
                 let base_secs = base_delay.as_secs_f64();
-                let exp_delay = base_secs * 2f64.powi((attempt - 1) as i32);
+                let exponent = i32::try_from(attempt.saturating_sub(1)).unwrap_or(i32::MAX);
+                let exp_delay = base_secs * 2f64.powi(exponent);
                 let jitter_amount = base_secs * jitter_factor;
-                let jitter = rand::rng().random_range(-jitter_amount..=jitter_amount);
-                let final_delay = (exp_delay + jitter).max(0.0);
-                Duration::from_secs_f64(final_delay)
+                let jitter = time::with_rng(|rng| rng.random_range(-jitter_amount..=jitter_amount));
+                from_secs_saturating((exp_delay + jitter).max(0.0))
             }
         }
     }
 }
 
+/// Multiplies `base_delay` by `factor`, saturating at `Duration::MAX`; a `factor` of `None`
+/// stands for one too large to be represented.
+fn scaled(base_delay: Duration, factor: Option<u128>) -> Duration {
+    if base_delay.is_zero() {
+        return Duration::ZERO;
+    }
+    factor
+        .and_then(|factor| base_delay.as_nanos().checked_mul(factor))
+        .and_then(|nanos| {
+            let secs = u64::try_from(nanos / 1_000_000_000).ok()?;
+            Some(Duration::new(secs, (nanos % 1_000_000_000) as u32))
+        })
+        .unwrap_or(Duration::MAX)
+}
+
+/// Converts a non-negative number of seconds to a `Duration`, saturating at `Duration::MAX`.
+fn from_secs_saturating(secs: f64) -> Duration {
+    Duration::try_from_secs_f64(secs).unwrap_or(Duration::MAX)
+}
+
+/// The running delays of a retry loop, computed once for a given base delay, strategy and cap.
+///
+/// The retry loops grow their delay after every failed attempt by applying the strategy to the
+/// previous delay. A schedule performs these computations up front, so that each retry is an index
+/// lookup instead of floating-point math or a Fibonacci recomputation. It is created with
+/// `RetryConfig::with_precomputed_schedule`.
+///
+/// The delays of `ExponentialBackoffWithJitter` are drawn once, when the schedule is computed, so
+/// every retry loop sharing the schedule waits the same jittered delays.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DelaySchedule {
+    delay: Duration,
+    strategy: RetryStrategy,
+    max_delay: Option<Duration>,
+    delays: Arc<[Duration]>,
+}
+
+impl DelaySchedule {
+    /// Computes the delays of the first `len - 1` retries.
+    ///
+    /// # Arguments
+    /// * `delay` - The base delay of the retry loop.
+    /// * `strategy` - The strategy growing the delay after every retry.
+    /// * `max_delay` - An optional upper bound for the delays.
+    /// * `len` - The number of delays to compute, typically the maximum number of attempts.
+    pub(crate) fn compute(
+        delay: Duration,
+        strategy: RetryStrategy,
+        max_delay: Option<Duration>,
+        len: usize,
+    ) -> Self {
+        Self::grown(delay, strategy, max_delay, len, |previous, retry| {
+            strategy.calculate_delay(previous, retry)
+        })
+    }
+
+    /// Computes the delays of the first `len - 1` retries of an `ExponentialBackoff` schedule
+    /// doubling the delay after every retry, as the deprecated `retry_with_exponential_backoff`
+    /// functions always did.
+    pub(crate) fn doubling(delay: Duration, max_delay: Option<Duration>, len: usize) -> Self {
+        let strategy = RetryStrategy::ExponentialBackoff;
+        Self::grown(delay, strategy, max_delay, len, |previous, _| {
+            previous.saturating_mul(2)
+        })
+    }
+
+    fn grown(
+        delay: Duration,
+        strategy: RetryStrategy,
+        max_delay: Option<Duration>,
+        len: usize,
+        grow: impl Fn(Duration, usize) -> Duration,
+    ) -> Self {
+        let cap = |delay: Duration| max_delay.map_or(delay, |max_delay| delay.min(max_delay));
+        let mut delays = Vec::with_capacity(len.max(1));
+        delays.push(delay);
+        for retry in 1..len {
+            let next = cap(grow(delays[retry - 1], retry));
+            delays.push(next);
+        }
+        DelaySchedule {
+            delay,
+            strategy,
+            max_delay,
+            delays: delays.into(),
+        }
+    }
+
+    /// Returns the computed delays; the delay at index `n` is the one waited after the `n`th
+    /// retry, index `0` holding the base delay.
+    pub fn delays(&self) -> &[Duration] {
+        &self.delays
+    }
+
+    /// Returns the delay after the given 1-based `retry`, if it was computed for these settings.
+    pub(crate) fn get(
+        &self,
+        delay: Duration,
+        strategy: RetryStrategy,
+        max_delay: Option<Duration>,
+        retry: usize,
+    ) -> Option<Duration> {
+        if self.delay != delay || self.strategy != strategy || self.max_delay != max_delay {
+            return None;
+        }
+        self.delays.get(retry).copied()
+    }
+}
+
+/// A stateful backoff producing the delays of a retry loop one at a time, for code that drives
+/// its own loop, such as a custom event loop or an FFI callback, but wants the crate's delays.
+///
+/// Each call to `next_delay` returns the delay to wait after one more failed attempt, grown by
+/// the strategy and capped like in the crate's retry loops, until the attempts run out. `reset`
+/// starts over from the base delay, e.g. once a connection came back.
+///
+/// A `Backoff` is created with `new`, or from a `RetryConfig` with `RetryConfig::backoff`, which
+/// also carries over its `max_attempts`, `max_delay` and precomputed schedule.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::strategies::{Backoff, RetryStrategy};
+///
+/// let mut backoff = Backoff::new(Duration::from_millis(100), RetryStrategy::ExponentialBackoff)
+///     .with_max_delay(Duration::from_millis(300))
+///     .with_max_attempts(4);
+/// assert_eq!(backoff.next_delay(), Some(Duration::from_millis(100)));
+/// assert_eq!(backoff.next_delay(), Some(Duration::from_millis(100)));
+/// assert_eq!(backoff.next_delay(), Some(Duration::from_millis(200)));
+/// assert_eq!(backoff.next_delay(), None);
+/// assert_eq!(backoff.attempts(), 3);
+///
+/// backoff.reset();
+/// assert_eq!(backoff.next_delay(), Some(Duration::from_millis(100)));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backoff {
+    base: Duration,
+    strategy: RetryStrategy,
+    max_delay: Option<Duration>,
+    max_attempts: usize,
+    schedule: Option<DelaySchedule>,
+    delay: Duration,
+    attempts: usize,
+}
+
+impl Backoff {
+    /// Creates a backoff starting at `delay` and growing it with `strategy`, without a cap or a
+    /// limit on the number of attempts.
+    pub fn new(delay: Duration, strategy: RetryStrategy) -> Self {
+        Backoff {
+            base: delay,
+            strategy,
+            max_delay: None,
+            max_attempts: usize::MAX,
+            schedule: None,
+            delay,
+            attempts: 0,
+        }
+    }
+
+    /// Builder-style setter for the longest delay returned.
+    pub fn with_max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = Some(max_delay);
+        self
+    }
+
+    /// Builder-style setter for the number of attempts, the first one included, after which
+    /// `next_delay` returns `None`.
+    pub fn with_max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    /// Builder-style setter for a precomputed schedule of delays, used where it covers the
+    /// retries and matches the settings of the backoff.
+    pub(crate) fn with_schedule(mut self, schedule: Option<DelaySchedule>) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
+    /// Records a failed attempt and returns the delay to wait before the next one.
+    ///
+    /// # Returns
+    /// - `Some(delay)` if another attempt is allowed.
+    /// - `None` once `max_attempts` attempts failed, without recording the attempt.
+    pub fn next_delay(&mut self) -> Option<Duration> {
+        if self.attempts.saturating_add(1) >= self.max_attempts {
+            return None;
+        }
+        let wait = self.capped(self.delay);
+        self.attempts += 1;
+        self.delay = self
+            .schedule
+            .as_ref()
+            .and_then(|schedule| {
+                schedule.get(self.base, self.strategy, self.max_delay, self.attempts)
+            })
+            .unwrap_or_else(|| {
+                self.capped(self.strategy.calculate_delay(self.delay, self.attempts))
+            });
+        Some(wait)
+    }
+
+    /// Starts over from the base delay, forgetting the failed attempts.
+    pub fn reset(&mut self) {
+        self.delay = self.base;
+        self.attempts = 0;
+    }
+
+    /// Returns the number of failed attempts recorded since the backoff was created or reset.
+    pub fn attempts(&self) -> usize {
+        self.attempts
+    }
+
+    fn capped(&self, delay: Duration) -> Duration {
+        self.max_delay
+            .map_or(delay, |max_delay| delay.min(max_delay))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::time::Duration;
 
+    #[test]
+    fn test_large_attempts_saturate_instead_of_panicking() {
+        let base_delay = Duration::from_secs(1);
+        let geometric = [
+            RetryStrategy::ExponentialBackoff,
+            RetryStrategy::ExponentialBackoffWithJitter { jitter_factor: 0.1 },
+            RetryStrategy::FibonacciBackoff,
+        ];
+        for strategy in geometric {
+            for attempt in [100, 1 << 20] {
+                assert_eq!(
+                    strategy.calculate_delay(base_delay, attempt),
+                    Duration::MAX,
+                    "{:?} at attempt {}",
+                    strategy,
+                    attempt
+                );
+            }
+        }
+        let strategies = geometric.into_iter().chain([
+            RetryStrategy::ArithmeticProgression { coefficient: 3 },
+            RetryStrategy::Polynomial { exponent: 3 },
+        ]);
+        for strategy in strategies {
+            assert_eq!(
+                strategy.calculate_delay(base_delay, usize::MAX),
+                Duration::MAX,
+                "{:?}",
+                strategy
+            );
+            assert_eq!(strategy.calculate_delay(Duration::MAX, 2), Duration::MAX);
+        }
+        assert_eq!(
+            RetryStrategy::ExponentialBackoff.calculate_delay(base_delay, 33),
+            Duration::from_secs(1 << 32)
+        );
+        assert_eq!(
+            RetryStrategy::ExponentialBackoff.calculate_delay(base_delay, 65),
+            Duration::MAX
+        );
+        let harmonic = RetryStrategy::Harmonic.calculate_delay(base_delay, usize::MAX);
+        assert!(harmonic > Duration::from_secs(44) && harmonic < Duration::from_secs(45));
+        assert_eq!(
+            RetryStrategy::ExponentialBackoff.calculate_delay(Duration::ZERO, 100),
+            Duration::ZERO
+        );
+    }
+
     #[test]
     fn test_linear_strategy() {
         let base_delay = Duration::from_secs(2);
@@ -228,6 +569,44 @@ mod tests {
         assert_eq!(ap.calculate_delay(base_delay, 3), Duration::from_secs(18));
     }
 
+    #[test]
+    fn test_polynomial_strategy() {
+        let base_delay = Duration::from_millis(100);
+        let quadratic = RetryStrategy::Polynomial { exponent: 2 };
+        let delays: Vec<Duration> = (1..=5)
+            .map(|attempt| quadratic.calculate_delay(base_delay, attempt))
+            .collect();
+        assert_eq!(
+            delays,
+            [100, 400, 900, 1600, 2500].map(Duration::from_millis)
+        );
+
+        let cubic = RetryStrategy::Polynomial { exponent: 3 };
+        assert_eq!(cubic.calculate_delay(base_delay, 0), base_delay);
+        assert_eq!(
+            cubic.calculate_delay(base_delay, 3),
+            Duration::from_millis(2700)
+        );
+        assert_eq!(
+            cubic.calculate_delay(base_delay, 100_000),
+            Duration::from_secs(100_000_000_000_000)
+        );
+        assert_eq!(cubic.calculate_delay(base_delay, 10_000_000), Duration::MAX);
+    }
+
+    #[test]
+    fn test_harmonic_strategy() {
+        let base_delay = Duration::from_secs(12);
+        let harmonic = RetryStrategy::Harmonic;
+        let delays: Vec<f64> = (0..=4)
+            .map(|attempt| harmonic.calculate_delay(base_delay, attempt).as_secs_f64())
+            .collect();
+        // 12 * (1, 1, 1.5, 1.8333, 2.0833)
+        for (delay, expected) in delays.iter().zip([12.0, 12.0, 18.0, 22.0, 25.0]) {
+            assert!((delay - expected).abs() < 1e-6, "{} != {}", delay, expected);
+        }
+    }
+
     #[test]
     fn test_exponential_backoff_with_jitter_strategy() {
         let base_delay = Duration::from_secs(2);
@@ -250,6 +629,13 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_jitter_treats_attempt_zero_as_the_first_retry() {
+        let base_delay = Duration::from_secs(2);
+        let jitter = RetryStrategy::ExponentialBackoffWithJitter { jitter_factor: 0.0 };
+        assert_eq!(jitter.calculate_delay(base_delay, 0), base_delay);
+    }
+
     #[test]
     fn test_exponential_backoff_with_small_jitter() {
         let base_delay = Duration::from_secs(2);
@@ -269,4 +655,56 @@ mod tests {
             attempt_3 >= Duration::from_secs_f64(7.8) && attempt_3 <= Duration::from_secs_f64(8.2)
         );
     }
+
+    #[test]
+    fn test_backoff_follows_the_retry_loop_delays() {
+        use crate::config::RetryConfig;
+
+        let config: RetryConfig<()> =
+            RetryConfig::new(5, Duration::from_secs(1), RetryStrategy::FibonacciBackoff)
+                .with_max_delay(Duration::from_secs(4));
+        let drain = |mut backoff: Backoff| {
+            std::iter::from_fn(move || backoff.next_delay()).collect::<Vec<_>>()
+        };
+        let expected = [1, 1, 2, 4].map(Duration::from_secs);
+        assert_eq!(drain(config.backoff()), expected);
+        assert_eq!(
+            drain(config.clone().with_precomputed_schedule().backoff()),
+            expected
+        );
+
+        let mut backoff = config.backoff();
+        backoff.next_delay();
+        backoff.next_delay();
+        assert_eq!(backoff.attempts(), 2);
+        backoff.reset();
+        assert_eq!(backoff.attempts(), 0);
+        assert_eq!(drain(backoff), expected);
+    }
+
+    #[test]
+    fn test_long_running_backoff_saturates_at_the_cap() {
+        let strategies = [
+            RetryStrategy::Linear,
+            RetryStrategy::ExponentialBackoff,
+            RetryStrategy::ExponentialBackoffWithJitter { jitter_factor: 0.5 },
+            RetryStrategy::FibonacciBackoff,
+            RetryStrategy::ArithmeticProgression { coefficient: 3 },
+            RetryStrategy::Polynomial { exponent: 4 },
+        ];
+        for strategy in strategies {
+            let mut capped = Backoff::new(Duration::from_secs(1), strategy)
+                .with_max_delay(Duration::from_secs(30));
+            let mut unbounded = Backoff::new(Duration::from_secs(1), strategy);
+            for _ in 0..200 {
+                assert!(capped.next_delay().unwrap() <= Duration::from_secs(30));
+                assert!(unbounded.next_delay().is_some());
+            }
+            assert_eq!(capped.attempts(), 200);
+            if strategy != RetryStrategy::Linear {
+                assert_eq!(capped.next_delay(), Some(Duration::from_secs(30)));
+                assert_eq!(unbounded.next_delay(), Some(Duration::MAX));
+            }
+        }
+    }
 }
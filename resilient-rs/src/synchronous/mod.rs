@@ -1,6 +1,36 @@
-use crate::config::RetryConfig;
-use log::{info, warn};
-use std::thread::sleep;
+use crate::asynchronous::schedule_class_retry;
+use crate::breaker::{self, BreakerCore};
+use crate::bulkhead;
+use crate::classifier::{ErrorClass, ErrorClassifier};
+use crate::config::{
+    BoxError, BulkheadConfig, CircuitBreakerConfig, ExecConfig, FallbackCause, RetryConfig,
+    SharedCircuitBreakerConfig, SharedRetryConfig,
+};
+use crate::defaults;
+use crate::error;
+use crate::events::{self, ResilienceEvent};
+use crate::idempotency::IdempotencyKey;
+use crate::logging::Level;
+use crate::logging::{log_sampled, log_with};
+use crate::metrics;
+use crate::shutdown::{ShutdownError, ShutdownHandle};
+use crate::stats::Stats;
+use crate::store::StateStore;
+use crate::time;
+use crate::timeline::{AttemptOutcome, Timeline};
+use async_std::stream::Stream;
+use std::error::Error;
+use std::fmt;
+use std::ops::Deref;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+
+pub use crate::breaker::{
+    CircuitBreakerError, CircuitBreakerMetrics, CircuitBreakerState, TransitionEvent,
+};
+pub use crate::bulkhead::BulkheadError;
 
 /// Retries a given operation based on the specified retry configuration.
 ///
@@ -10,7 +40,8 @@ use std::thread::sleep;
 ///
 /// # Returns
 /// * `Ok(T)` if the operation succeeds within the allowed attempts.
-/// * `Err(E)` if the operation fails after all retry attempts.
+/// * `Err(E)` if the operation fails after all retry attempts, or with an error classified as
+///   `Permanent` or `Fatal`.
 ///
 /// # Example
 /// ```
@@ -19,7 +50,7 @@ use std::thread::sleep;
 /// use resilient_rs::strategies::RetryStrategy::Linear;
 /// use resilient_rs::synchronous::retry;
 ///
-/// let retry_config = RetryConfig { max_attempts: 3, delay: Duration::from_millis(500), retry_condition: None, strategy: Linear };
+/// let retry_config = RetryConfig::new(3, Duration::from_millis(500), Linear);
 /// let result: Result<i32, &str> = retry(|| {
 ///     Err("Temporary failure") // Always fails in this example
 /// }, &retry_config);
@@ -27,45 +58,392 @@ use std::thread::sleep;
 /// ```
 /// # Notes
 /// - The function logs warnings for failed attempts and final failure.
-pub fn retry<F, T, E>(mut operation: F, retry_config: &RetryConfig<E>) -> Result<T, E>
+pub fn retry<F, T, E>(operation: F, retry_config: &RetryConfig<E>) -> Result<T, E>
+where
+    F: FnMut() -> Result<T, E>,
+{
+    retry_loop(operation, || retry_config, None, None).map_err(ShutdownError::into_failed)
+}
+
+/// Retries an operation like `retry`, and returns the timeline of its attempts along with the
+/// result.
+///
+/// The timeline records the start, duration and outcome of every attempt, and the delay chosen
+/// before the next one, whether the call succeeds or fails. See `timeline::Timeline`.
+///
+/// # Arguments
+/// * `operation` - A closure that returns a `Result<T, E>`.
+/// * `retry_config` - The retry configuration.
+///
+/// # Returns
+/// The result `retry` would return, and the `Timeline` of the call.
+pub fn retry_with_timeline<F, T, E>(
+    operation: F,
+    retry_config: &RetryConfig<E>,
+) -> (Result<T, E>, Timeline)
+where
+    F: FnMut() -> Result<T, E>,
+{
+    let mut timeline = Timeline::start();
+    let result = retry_loop(operation, || retry_config, None, Some(&mut timeline))
+        .map_err(ShutdownError::into_failed);
+    timeline.finish();
+    (result, timeline)
+}
+
+/// Retries an operation with the process-wide retry policy.
+///
+/// The policy is the one set with `set_global_retry_config`, or `RetryConfig::default()` if
+/// none was set, so that small applications can configure retries once at startup.
+///
+/// # Arguments
+/// * `operation` - A closure that returns a `Result<T, E>`.
+///
+/// # Returns
+/// The same as `retry`.
+///
+/// # Example
+/// ```
+/// use resilient_rs::synchronous::retry_with_defaults;
+///
+/// let result = retry_with_defaults(|| Ok::<_, String>("connected"));
+/// assert_eq!(result, Ok("connected"));
+/// ```
+pub fn retry_with_defaults<F, T, E>(operation: F) -> Result<T, E>
 where
     F: FnMut() -> Result<T, E>,
+{
+    retry(operation, &defaults::global_retry_config())
+}
+
+/// Retries a non-idempotent operation, passing the same idempotency key to every attempt.
+///
+/// A key is generated once, before the first attempt, and handed to the operation each time it
+/// runs, so that it can be sent along with the request (typically in an `Idempotency-Key`
+/// header). A server deduplicating requests by key then executes the operation at most once,
+/// even when an attempt timed out after the server had processed it.
+///
+/// # Arguments
+/// * `operation` - A closure receiving the idempotency key and returning a `Result<T, E>`.
+/// * `retry_config` - A reference to `RetryConfig` specifying the maximum attempts and delay between retries.
+///
+/// # Returns
+/// The same as `retry`.
+///
+/// # Example
+/// ```
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::synchronous::retry_idempotent;
+///
+/// let mut keys = Vec::new();
+/// let result = retry_idempotent(|key| {
+///     keys.push(key.clone());
+///     if keys.len() < 2 { Err("connection reset") } else { Ok("created") }
+/// }, &RetryConfig::default());
+/// assert_eq!(result, Ok("created"));
+/// assert_eq!(keys[0], keys[1]);
+/// ```
+pub fn retry_idempotent<F, T, E>(mut operation: F, retry_config: &RetryConfig<E>) -> Result<T, E>
+where
+    F: FnMut(&IdempotencyKey) -> Result<T, E>,
+{
+    let key = IdempotencyKey::generate();
+    retry(|| operation(&key), retry_config)
+}
+
+/// Retries an operation consuming an owned input, such as a request body, cloning the input for
+/// every attempt.
+///
+/// The operation receives its input by value, so it does not have to clone it itself. The input
+/// is cloned for every attempt except the last one the configuration allows, which receives the
+/// original.
+///
+/// # Arguments
+/// * `input` - The input handed to every attempt.
+/// * `operation` - A closure receiving the input and returning a `Result<T, E>`.
+/// * `retry_config` - A reference to `RetryConfig` specifying the maximum attempts and delay between retries.
+///
+/// # Returns
+/// The same as `retry`.
+///
+/// # Example
+/// ```
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::synchronous::retry_with_input;
+///
+/// let command = String::from("SET greeting hello");
+/// let result = retry_with_input(command, |command: String| Ok::<_, String>(command.len()), &RetryConfig::default());
+/// assert_eq!(result, Ok(18));
+/// ```
+pub fn retry_with_input<I, F, T, E>(
+    input: I,
+    mut operation: F,
+    retry_config: &RetryConfig<E>,
+) -> Result<T, E>
+where
+    I: Clone,
+    F: FnMut(I) -> Result<T, E>,
+{
+    let last_attempt = retry_config.attempt_limit();
+    let mut input = Some(input);
+    let mut attempt = 0;
+    retry(
+        || {
+            attempt += 1;
+            let input = if attempt >= last_attempt {
+                input.take()
+            } else {
+                input.clone()
+            };
+            operation(input.expect("no attempt is made after the last one"))
+        },
+        retry_config,
+    )
+}
+
+/// Retries an operation that leaves partial work behind when it fails, undoing that work after
+/// every failed attempt.
+///
+/// `rollback` is called with the error of every failed attempt, right after it fails and before
+/// the backoff delay, including the last attempt and errors that are not retried, so a retry
+/// never builds on the half-done work of the previous attempt.
+///
+/// # Arguments
+/// * `operation` - A closure that returns a `Result<T, E>`.
+/// * `rollback` - A closure receiving the error of a failed attempt and undoing its partial work,
+///   e.g. deleting a temporary file or releasing a lock.
+/// * `retry_config` - A reference to `RetryConfig` specifying the maximum attempts and delay between retries.
+///
+/// # Returns
+/// The same as `retry`.
+///
+/// # Example
+/// ```
+/// use std::cell::{Cell, RefCell};
+/// use std::time::Duration;
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::strategies::RetryStrategy;
+/// use resilient_rs::synchronous::retry_transactional;
+///
+/// let locks = RefCell::new(Vec::new());
+/// let attempts = Cell::new(0);
+/// let config = RetryConfig::new(3, Duration::from_millis(1), RetryStrategy::Linear);
+/// let result = retry_transactional(
+///     || {
+///         let mut locks = locks.borrow_mut();
+///         if !locks.is_empty() {
+///             return Err("lock already held");
+///         }
+///         locks.push("inventory:42");
+///         attempts.set(attempts.get() + 1);
+///         if attempts.get() == 1 { Err("deadlock detected") } else { Ok("reserved") }
+///     },
+///     |_err| {
+///         // Release the lock taken by the failed attempt.
+///         locks.borrow_mut().clear();
+///     },
+///     &config,
+/// );
+/// assert_eq!(result, Ok("reserved"));
+/// assert_eq!(*locks.borrow(), ["inventory:42"]);
+/// ```
+pub fn retry_transactional<F, R, T, E>(
+    mut operation: F,
+    mut rollback: R,
+    retry_config: &RetryConfig<E>,
+) -> Result<T, E>
+where
+    F: FnMut() -> Result<T, E>,
+    R: FnMut(&E),
+{
+    retry(
+        || {
+            operation().inspect_err(|err| {
+                log_with!(
+                    retry_config.log,
+                    Level::Debug,
+                    "Attempt failed; rolling back its partial work"
+                );
+                rollback(err);
+            })
+        },
+        retry_config,
+    )
+}
+
+/// Retries a given operation using a hot-reloadable configuration.
+///
+/// This behaves exactly like `retry`, but the configuration is re-read from the
+/// `SharedRetryConfig` at the start of every attempt, so updates made while the operation is
+/// being retried (for example raising `max_attempts` during an incident) take effect immediately.
+///
+/// # Arguments
+/// * `operation` - A closure that returns a `Result<T, E>`. The function will retry this operation if it fails.
+/// * `retry_config` - The shared configuration handle to read before each attempt.
+///
+/// # Returns
+/// * `Ok(T)` if the operation succeeds within the allowed attempts.
+/// * `Err(E)` if the operation fails after all retry attempts, or with an error classified as
+///   `Permanent` or `Fatal`.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::config::{RetryConfig, SharedRetryConfig};
+/// use resilient_rs::strategies::RetryStrategy;
+/// use resilient_rs::synchronous::retry_shared;
+///
+/// let shared = SharedRetryConfig::new(RetryConfig::new(2, Duration::from_millis(1), RetryStrategy::Linear));
+/// let result: Result<i32, &str> = retry_shared(|| Ok(1), &shared);
+/// assert_eq!(result, Ok(1));
+/// ```
+pub fn retry_shared<F, T, E>(operation: F, retry_config: &SharedRetryConfig<E>) -> Result<T, E>
+where
+    F: FnMut() -> Result<T, E>,
+{
+    retry_loop(operation, || retry_config.load(), None, None).map_err(ShutdownError::into_failed)
+}
+
+/// Retries a given operation like `retry`, but stops scheduling new attempts once `shutdown` is
+/// requested.
+///
+/// An attempt in flight when the shutdown is requested is allowed to finish; if it fails, or if
+/// the thread is waiting out a backoff, the loop returns `ShutdownError::Cancelled` at once
+/// instead of retrying, so in-flight work can drain quickly when the process is stopping.
+///
+/// # Arguments
+/// * `operation` - A closure that returns a `Result<T, E>`.
+/// * `retry_config` - A reference to `RetryConfig` specifying the maximum attempts and delay between retries.
+/// * `shutdown` - The handle signaling the shutdown.
+///
+/// # Returns
+/// The same as `asynchronous::retry_until_shutdown`.
+///
+/// # Example
+/// ```
+/// use std::thread;
+/// use std::time::Duration;
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::shutdown::ShutdownHandle;
+/// use resilient_rs::strategies::RetryStrategy;
+/// use resilient_rs::synchronous::retry_until_shutdown;
+///
+/// let shutdown = ShutdownHandle::new();
+/// let signal = shutdown.clone();
+/// thread::spawn(move || {
+///     thread::sleep(Duration::from_millis(20));
+///     signal.shutdown();
+/// });
+///
+/// // Backs off for an hour, but returns as soon as the shutdown is requested.
+/// let retry_config = RetryConfig::new(3, Duration::from_secs(3600), RetryStrategy::Linear);
+/// let result: Result<(), _> = retry_until_shutdown(|| Err("broker unavailable"), &retry_config, &shutdown);
+/// assert!(result.unwrap_err().is_cancelled());
+/// ```
+pub fn retry_until_shutdown<F, T, E>(
+    operation: F,
+    retry_config: &RetryConfig<E>,
+    shutdown: &ShutdownHandle,
+) -> Result<T, ShutdownError<E>>
+where
+    F: FnMut() -> Result<T, E>,
+{
+    retry_loop(operation, || retry_config, Some(shutdown), None)
+}
+
+fn retry_loop<F, T, E, C>(
+    mut operation: F,
+    load: impl Fn() -> C,
+    shutdown: Option<&ShutdownHandle>,
+    mut timeline: Option<&mut Timeline>,
+) -> Result<T, ShutdownError<E>>
+where
+    F: FnMut() -> Result<T, E>,
+    C: Deref<Target = RetryConfig<E>>,
 {
     let mut attempts = 0;
-    let mut delay = retry_config.delay;
+    let mut delay = load().delay;
+    let mut last_error = None;
 
     loop {
-        match operation() {
-            Ok(output) => {
-                info!("Operation succeeded after {} attempts", attempts + 1);
+        let retry_config = load();
+        if shutdown.is_some_and(ShutdownHandle::is_shutdown) {
+            log_with!(
+                retry_config.log,
+                Level::Info,
+                { attempt = attempts };
+                "Shutdown requested; not retrying after {} attempts",
+                attempts
+            );
+            return Err(ShutdownError::Cancelled {
+                attempts,
+                last_error,
+            });
+        }
+        events::emit(ResilienceEvent::AttemptStarted {
+            attempt: attempts + 1,
+        });
+        let start = time::now();
+        let result = if retry_config.catch_panics {
+            panic::catch_unwind(AssertUnwindSafe(&mut operation))
+        } else {
+            Ok(operation())
+        };
+        retry_config.record(|stats| stats.record_attempt(time::elapsed(start)));
+        if let Some(timeline) = timeline.as_deref_mut() {
+            let outcome = match &result {
+                Ok(Ok(_)) => AttemptOutcome::Succeeded,
+                Ok(Err(err)) => AttemptOutcome::Failed(retry_config.classify(err)),
+                Err(_) => AttemptOutcome::Panicked,
+            };
+            timeline.record_attempt(start, outcome);
+        }
+        match result {
+            Ok(Ok(output)) => {
+                log_with!(
+                    retry_config.log,
+                    Level::Info,
+                    { attempt = attempts + 1 };
+                    "Operation succeeded after {} attempts",
+                    attempts + 1
+                );
+                retry_config.record(|stats| stats.record_success(attempts > 0));
                 return Ok(output);
             }
-            Err(err) if attempts + 1 < retry_config.max_attempts => {
-                let should_retry = retry_config.retry_condition.map_or(true, |f| f(&err));
-                if should_retry {
-                    warn!(
-                        "Operation failed (attempt {}/{}), retrying after {:?}...",
-                        attempts + 1,
-                        retry_config.max_attempts,
-                        delay
-                    );
-                    sleep(delay);
-                    delay = retry_config.strategy.calculate_delay(delay, attempts + 1);
-                } else {
-                    warn!(
-                        "Operation failed (attempt {}/{}), not retryable, giving up.",
-                        attempts + 1,
-                        retry_config.max_attempts
-                    );
-                    return Err(err);
+            Ok(Err(err)) => {
+                let class = retry_config.classify(&err);
+                let Some(wait) = schedule_class_retry(&retry_config, class, attempts + 1, delay)
+                else {
+                    return Err(ShutdownError::Failed(err));
+                };
+                if let Some(timeline) = timeline.as_deref_mut() {
+                    timeline.record_delay(wait);
+                }
+                if back_off(shutdown, wait) {
+                    delay = retry_config.next_delay(delay, attempts + 1);
                 }
+                last_error = Some(err);
             }
-            Err(err) => {
-                warn!(
-                    "Operation failed after {} attempts, giving up.",
+            Err(payload) => {
+                log_sampled!(
+                    retry_config.log,
+                    attempts + 1,
+                    retry_config.log.level,
+                    { attempt = attempts + 1 };
+                    "Operation panicked (attempt {}), handling it as a transient failure.",
                     attempts + 1
                 );
-                return Err(err);
+                let Some(wait) =
+                    schedule_class_retry(&retry_config, ErrorClass::Transient, attempts + 1, delay)
+                else {
+                    panic::resume_unwind(payload);
+                };
+                if let Some(timeline) = timeline.as_deref_mut() {
+                    timeline.record_delay(wait);
+                }
+                if back_off(shutdown, wait) {
+                    delay = retry_config.next_delay(delay, attempts + 1);
+                }
             }
         }
 
@@ -73,69 +451,583 @@ where
     }
 }
 
+/// Blocks the thread for the backoff delay of a retry loop.
+///
+/// Delays shorter than a millisecond, including zero, only yield the thread instead of going
+/// through a sleep system call.
+///
+/// # Returns
+/// `true` if the whole delay elapsed, or `false` if it was cut short by `shutdown`.
+pub(crate) fn back_off(shutdown: Option<&ShutdownHandle>, wait: Duration) -> bool {
+    if wait < time::MIN_SLEEP {
+        time::yield_now_blocking();
+        return !shutdown.is_some_and(ShutdownHandle::is_shutdown);
+    }
+    match shutdown {
+        Some(shutdown) => shutdown.sleep_blocking(wait),
+        None => {
+            time::sleep_blocking(wait);
+            true
+        }
+    }
+}
+
+/// Retries an operation with exponential backoff.
+///
+/// This is a shim kept for compatibility: it runs the loop of `retry` with the delay doubling
+/// after every retry (`delay`, `2 * delay`, `4 * delay`, ...) as it always did, so both honor the
+/// same retry conditions, classifiers, per-class policies and statistics.
 #[deprecated(
     since = "0.4.7",
     note = "use `retry` with `ExponentialBackoff` this will be removed in upcoming versions"
 )]
 pub fn retry_with_exponential_backoff<F, T, E>(
-    mut operation: F,
+    operation: F,
     retry_config: &RetryConfig<E>,
 ) -> Result<T, E>
 where
     F: FnMut() -> Result<T, E>,
 {
-    let mut attempts = 0;
-    let mut delay = retry_config.delay;
+    retry(operation, &retry_config.doubling())
+}
 
-    loop {
-        match operation() {
-            Ok(output) => {
-                info!("Operation succeeded after {} attempts", attempts + 1);
-                return Ok(output);
-            }
-            Err(err) if attempts + 1 < retry_config.max_attempts => {
-                let should_retry = retry_config.retry_condition.map_or(true, |f| f(&err));
-
-                if should_retry {
-                    warn!(
-                        "Operation failed (attempt {}/{}), retrying after {:?}...",
-                        attempts + 1,
-                        retry_config.max_attempts,
-                        delay
-                    );
-                    sleep(delay);
-                    delay *= 2;
-                } else {
-                    warn!(
-                        "Operation failed (attempt {}/{}), not retryable, giving up.",
-                        attempts + 1,
-                        retry_config.max_attempts
-                    );
-                    return Err(err);
-                }
-            }
-            Err(err) => {
-                warn!(
-                    "Operation failed after {} attempts, giving up.",
-                    attempts + 1
+/// Executes a blocking operation with a timeout and an optional fallback.
+///
+/// This is the synchronous counterpart of `asynchronous::execute_with_fallback`, for blocking
+/// call sites that cannot adopt async. The operation runs on a helper thread while the calling
+/// thread waits up to `exec_config.timeout_duration` for its result. If it times out, the
+/// fallback (if provided) produces the result instead, receiving a `FallbackCause::Timeout`.
+///
+/// A blocking operation cannot be interrupted: after a timeout, the helper thread keeps running
+/// it in the background and its result is discarded. Pair this with timeouts of the underlying
+/// client where possible, so abandoned threads do not pile up. A panic of the operation is
+/// propagated to the caller. The timeout is always measured in real time, even on a `sim`
+/// virtual clock.
+///
+/// # Arguments
+/// * `operation` - A blocking operation returning a `Result<T, Box<dyn Error + Send + Sync>>`;
+///   it is moved to the helper thread, so it must be `Send` and `'static`.
+/// * `exec_config` - A reference to an `ExecConfig<T>` containing the timeout duration and an
+///   optional fallback function.
+///
+/// # Returns
+/// * `Ok(T)` - If the operation succeeds within the timeout, or if the fallback succeeds after a
+///   timeout.
+/// * `Err(Box<dyn Error + Send + Sync>)` - The error of the operation, a
+///   `resilient_rs::Error::Timeout` if it timed out without a fallback, or the error of the
+///   fallback.
+///
+/// # Example
+/// ```rust
+/// use std::thread::sleep;
+/// use std::time::Duration;
+/// use resilient_rs::config::ExecConfig;
+/// use resilient_rs::synchronous::execute_with_timeout;
+///
+/// let config = ExecConfig::new(Duration::from_millis(50)).with_fallback(|cause| {
+///     assert!(cause.is_timeout());
+///     Ok("cached report".to_string())
+/// });
+///
+/// let result = execute_with_timeout(
+///     || {
+///         sleep(Duration::from_millis(200));
+///         Ok("fresh report".to_string())
+///     },
+///     &config,
+/// );
+/// assert_eq!(result.unwrap(), "cached report");
+/// ```
+pub fn execute_with_timeout<F, T>(operation: F, exec_config: &ExecConfig<T>) -> Result<T, BoxError>
+where
+    F: FnOnce() -> Result<T, Box<dyn Error + Send + Sync>> + Send + 'static,
+    T: Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel();
+    let worker = std::thread::spawn(move || {
+        // The receiver is gone if the caller already timed out.
+        let _ = sender.send(operation());
+    });
+    match receiver.recv_timeout(exec_config.timeout_duration) {
+        Ok(result) => {
+            log_with!(
+                exec_config.log,
+                Level::Info,
+                "Operation completed before timeout; returning result."
+            );
+            result
+        }
+        Err(RecvTimeoutError::Disconnected) => match worker.join() {
+            Err(panic) => std::panic::resume_unwind(panic),
+            Ok(()) => unreachable!("the worker sends a result before exiting"),
+        },
+        Err(RecvTimeoutError::Timeout) => {
+            metrics::increment(&metrics::TIMEOUTS);
+            events::emit(ResilienceEvent::TimeoutHit {
+                timeout: exec_config.timeout_duration,
+            });
+            if let Some(fallback) = &exec_config.fallback {
+                log_with!(
+                    exec_config.log,
+                    exec_config.log.level,
+                    "Operation timed out; executing fallback."
+                );
+                metrics::increment(&metrics::FALLBACKS);
+                events::emit(ResilienceEvent::FallbackUsed);
+                fallback(FallbackCause::Timeout {
+                    timeout: exec_config.timeout_duration,
+                })
+            } else {
+                log_with!(
+                    exec_config.log,
+                    Level::Error,
+                    "Operation timed out; no fallback provided, returning error."
                 );
-                return Err(err);
+                Err(Box::new(error::Error::Timeout {
+                    timeout: exec_config.timeout_duration,
+                }))
             }
         }
+    }
+}
 
-        attempts += 1;
+/// A thread-safe circuit breaker for blocking operations.
+///
+/// This is the synchronous counterpart of `asynchronous::CircuitBreaker`: it takes the same
+/// `CircuitBreakerConfig`, supports the same classifier, failure predicate, statistics and
+/// hot-reloadable configuration, and makes exactly the same state transitions. Its state lives
+/// behind a `Mutex`, so `run` takes `&self` and one breaker can be shared between threads, e.g.
+/// in an `Arc` or a `static`.
+///
+/// The lock is only held while the breaker decides whether to admit a call and while it records
+/// the outcome, never while the operation runs, so slow calls do not serialize each other. As a
+/// consequence, several threads may run trial calls concurrently while the breaker is `HalfOpen`.
+/// On hot paths where many threads contend for the lock, `lockfree::LockFreeCircuitBreaker` makes
+/// the same transitions with atomics, at the cost of the less common features.
+///
+/// # Type Parameters
+/// * `E` - The error type of the supervised operations, `Box<dyn Error + Send + Sync>` by default. Use
+///   `CircuitBreaker::with_config` to supervise operations failing with another error type.
+///
+/// # Example
+/// ```rust
+/// use std::sync::Arc;
+/// use std::thread;
+/// use std::time::Duration;
+/// use resilient_rs::config::CircuitBreakerConfig;
+/// use resilient_rs::synchronous::{CircuitBreaker, CircuitBreakerState};
+///
+/// let cb = Arc::new(CircuitBreaker::<String>::with_config(CircuitBreakerConfig::new(
+///     1,
+///     2,
+///     Duration::from_secs(30),
+/// )));
+/// let workers: Vec<_> = (0..2)
+///     .map(|_| {
+///         let cb = Arc::clone(&cb);
+///         thread::spawn(move || cb.run(|| Err::<(), _>("connection refused".to_string())))
+///     })
+///     .collect();
+/// for worker in workers {
+///     let _ = worker.join();
+/// }
+/// assert_eq!(cb.state(), CircuitBreakerState::Open);
+/// ```
+pub struct CircuitBreaker<E = BoxError> {
+    core: Mutex<BreakerCore<E>>,
+}
+
+impl CircuitBreaker {
+    /// Creates a new `CircuitBreaker` instance with the given configuration.
+    ///
+    /// # Parameters
+    /// - `config`: A `CircuitBreakerConfig` defining the failure threshold, success threshold, and
+    ///   cooldown period.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::time::Duration;
+    /// use resilient_rs::config::CircuitBreakerConfig;
+    /// use resilient_rs::synchronous::CircuitBreaker;
+    ///
+    /// let cb = CircuitBreaker::new(CircuitBreakerConfig::new(2, 3, Duration::from_secs(5)));
+    /// ```
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        CircuitBreaker::with_config(config)
+    }
+}
+
+impl<E> CircuitBreaker<E> {
+    /// Creates a new `CircuitBreaker` supervising operations that fail with errors of type `E`.
+    ///
+    /// `CircuitBreaker::new` is the shorthand for operations returning `Box<dyn Error + Send + Sync>`.
+    pub fn with_config(config: CircuitBreakerConfig) -> Self {
+        CircuitBreaker {
+            core: Mutex::new(BreakerCore::new(config)),
+        }
+    }
+
+    /// Sets an error classifier and returns the modified `CircuitBreaker`.
+    ///
+    /// Failures classified as `Fatal` open the circuit immediately, regardless of the
+    /// configured `failure_threshold`.
+    pub fn with_classifier(
+        mut self,
+        classifier: impl ErrorClassifier<E> + Send + Sync + 'static,
+    ) -> Self {
+        self.core_mut().classifier = Some(Arc::new(classifier));
+        self
+    }
+
+    /// Sets a predicate deciding which errors count as failures and returns the modified `CircuitBreaker`.
+    ///
+    /// Errors for which the predicate returns `false` are returned to the caller without
+    /// affecting the breaker's state. By default, every error counts.
+    pub fn with_record_failure_if(
+        mut self,
+        predicate: impl Fn(&E) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.core_mut().record_failure_if = Some(Arc::new(predicate));
+        self
+    }
+
+    /// Sets a statistics handle and returns the modified `CircuitBreaker`.
+    pub fn with_stats(mut self, stats: Stats) -> Self {
+        self.core_mut().stats = Some(stats);
+        self
+    }
+
+    /// Makes the circuit breaker follow a hot-reloadable configuration, re-read on every call to `run`.
+    pub fn with_shared_config(mut self, config: SharedCircuitBreakerConfig) -> Self {
+        self.core_mut().set_shared_config(config);
+        self
+    }
+
+    /// Shares the breaker's state with other breakers through a `StateStore`; see
+    /// `asynchronous::CircuitBreaker::with_state_store`.
+    pub fn with_state_store(mut self, name: impl Into<String>, store: Arc<dyn StateStore>) -> Self {
+        self.core_mut().set_state_store(name.into(), store);
+        self
+    }
+
+    /// Executes an operation under circuit breaker supervision.
+    ///
+    /// # Parameters
+    /// - `operation`: A closure returning a `Result`. It is not called if the breaker rejects the call.
+    ///
+    /// # Returns
+    /// - `Ok(T)` if the operation succeeds.
+    /// - `Err(CircuitBreakerError::Inner(E))` if the operation fails.
+    /// - `Err(CircuitBreakerError::Open { retry_after })` if the breaker is `Open` and the call was
+    ///   not executed.
+    /// - `Err(CircuitBreakerError::Saturated { .. })` if `max_concurrent_calls` calls were already
+    ///   running and the call was not executed.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::time::Duration;
+    /// use resilient_rs::config::CircuitBreakerConfig;
+    /// use resilient_rs::synchronous::{CircuitBreaker, CircuitBreakerError};
+    ///
+    /// let cb = CircuitBreaker::<&str>::with_config(CircuitBreakerConfig::new(1, 1, Duration::from_secs(30)));
+    /// assert_eq!(cb.run(|| Err::<(), _>("timeout")), Err(CircuitBreakerError::Inner("timeout")));
+    /// assert!(cb.run(|| Ok::<_, &str>(())).unwrap_err().is_open());
+    /// ```
+    pub fn run<F, T>(&self, operation: F) -> Result<T, CircuitBreakerError<E>>
+    where
+        F: FnOnce() -> Result<T, E>,
+        E: fmt::Display,
+    {
+        let permit = breaker::acquire(&self.core)?;
+        let start = time::now();
+        let result = operation();
+        let generation = permit.finish();
+        breaker::complete(&self.core, generation, result, time::elapsed(start))
+    }
+
+    /// Executes an operation under circuit breaker supervision, falling back when the call is
+    /// rejected, because the circuit is open or the concurrency cap is reached.
+    ///
+    /// Failures of the operation itself are returned as is and are not handled by the fallback.
+    ///
+    /// # Returns
+    /// - `Ok(T)` if the operation succeeds, or if the call is rejected and the fallback succeeds.
+    /// - `Err(E)` if the operation fails, or if the call is rejected and the fallback fails.
+    pub fn run_with_fallback<F, FB, T>(&self, operation: F, fallback: FB) -> Result<T, E>
+    where
+        F: FnOnce() -> Result<T, E>,
+        FB: FnOnce() -> Result<T, E>,
+        E: fmt::Display,
+    {
+        match self.run(operation) {
+            Ok(output) => Ok(output),
+            Err(CircuitBreakerError::Inner(err)) => Err(err),
+            Err(_) => {
+                breaker::update(&self.core, BreakerCore::fallback_used);
+                fallback()
+            }
+        }
+    }
+
+    /// Wraps `f` into a function whose calls are supervised by the shared `breaker`; see
+    /// `asynchronous::CircuitBreaker::decorate`.
+    ///
+    /// # Examples
+    /// ```rust
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    /// use resilient_rs::config::CircuitBreakerConfig;
+    /// use resilient_rs::synchronous::CircuitBreaker;
+    ///
+    /// let config = CircuitBreakerConfig::new(1, 1, Duration::from_secs(30));
+    /// let breaker = Arc::new(CircuitBreaker::<&str>::with_config(config));
+    /// let parse = CircuitBreaker::decorate(&breaker, |input: &str| input.parse::<u8>().map_err(|_| "invalid"));
+    ///
+    /// assert_eq!(parse("42"), Ok(42));
+    /// let _ = parse("forty-two");
+    /// assert!(parse("42").unwrap_err().is_open());
+    /// ```
+    pub fn decorate<F, A, T>(
+        breaker: &Arc<CircuitBreaker<E>>,
+        f: F,
+    ) -> impl Fn(A) -> Result<T, CircuitBreakerError<E>> + Clone + Send + Sync + 'static
+    where
+        F: Fn(A) -> Result<T, E> + Send + Sync + 'static,
+        E: fmt::Display + Send + 'static,
+    {
+        let breaker = Arc::clone(breaker);
+        let f = Arc::new(f);
+        move |arg| breaker.run(|| f(arg))
+    }
+
+    /// Forces the breaker open, rejecting every call until `reset` is called.
+    pub fn force_open(&self) {
+        breaker::update(&self.core, BreakerCore::force_open);
+    }
+
+    /// Disables the breaker, letting every call through without recording its outcome until
+    /// `reset` is called.
+    pub fn disable(&self) {
+        breaker::update(&self.core, BreakerCore::disable);
+    }
+
+    /// Closes the breaker and forgets every recorded failure, leaving `ForcedOpen` or `Disabled`
+    /// as well as a tripped `Open` state.
+    pub fn reset(&self) {
+        breaker::update(&self.core, BreakerCore::reset);
+    }
+
+    /// Returns a stream of the breaker's state transitions; see
+    /// `asynchronous::CircuitBreaker::events`.
+    pub fn events(&self) -> impl Stream<Item = TransitionEvent> + Send + Unpin + 'static {
+        self.core().subscribe()
+    }
+
+    /// Returns the current state of the breaker.
+    pub fn state(&self) -> CircuitBreakerState {
+        self.core().state()
+    }
+
+    /// Returns the number of consecutive failures counted towards `failure_threshold`.
+    pub fn failure_count(&self) -> usize {
+        self.core().failure_count()
+    }
+
+    /// Returns the number of successful trial calls counted towards `success_threshold`.
+    pub fn success_count(&self) -> usize {
+        self.core().success_count()
+    }
+
+    /// Returns how long the breaker stays `Open` before letting a trial call through, or `None`
+    /// if it is not `Open`.
+    pub fn time_until_half_open(&self) -> Option<Duration> {
+        self.core().time_until_half_open()
+    }
+
+    /// Returns the percentage of successful calls among the last `metrics_window_size` calls.
+    pub fn success_rate(&self) -> Option<f64> {
+        self.core().metrics().success_rate
+    }
+
+    /// Returns the mean latency of the last `metrics_window_size` calls.
+    pub fn mean_latency(&self) -> Option<Duration> {
+        self.core().metrics().mean_latency
+    }
+
+    /// Returns the latency below which `percentile` percent of the last `metrics_window_size`
+    /// calls completed, e.g. `99.0` for the p99 latency.
+    pub fn latency_percentile(&self, percentile: f64) -> Option<Duration> {
+        self.core().latency_percentile(percentile)
+    }
+
+    /// Returns a snapshot of the breaker's state and counters.
+    pub fn metrics(&self) -> CircuitBreakerMetrics {
+        self.core().metrics()
+    }
+
+    /// Locks the state machine; see `breaker::lock`.
+    fn core(&self) -> MutexGuard<'_, BreakerCore<E>> {
+        breaker::lock(&self.core)
+    }
+
+    /// Returns the state machine of the breaker, e.g. to supervise a `GuardedRetry`.
+    #[cfg(feature = "redis")]
+    pub(crate) fn state_machine(&self) -> &Mutex<BreakerCore<E>> {
+        &self.core
+    }
+
+    fn core_mut(&mut self) -> &mut BreakerCore<E> {
+        self.core.get_mut().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A bulkhead limiting the number of concurrent executions of the operations it guards.
+///
+/// This is the blocking counterpart of `asynchronous::Bulkhead`: once `max_concurrent_calls`
+/// operations are running, further calls block their thread for up to `max_wait` waiting for a
+/// slot, and are rejected with `BulkheadError::Full` otherwise. A slot is released when the
+/// operation returns or panics.
+///
+/// # Examples
+/// ```rust
+/// use std::sync::Arc;
+/// use resilient_rs::config::BulkheadConfig;
+/// use resilient_rs::synchronous::Bulkhead;
+///
+/// let bulkhead = Arc::new(Bulkhead::new(BulkheadConfig::new(4)));
+/// let workers: Vec<_> = (0..4)
+///     .map(|i| {
+///         let bulkhead = Arc::clone(&bulkhead);
+///         std::thread::spawn(move || bulkhead.call(|| Ok::<_, &str>(i * 2)))
+///     })
+///     .collect();
+/// for worker in workers {
+///     assert!(worker.join().unwrap().is_ok());
+/// }
+/// ```
+pub struct Bulkhead {
+    config: BulkheadConfig,
+    running: Mutex<usize>,
+    released: Condvar,
+}
+
+/// A slot of a `Bulkhead`, handed back when dropped.
+struct BulkheadSlot<'a> {
+    bulkhead: &'a Bulkhead,
+}
+
+impl Drop for BulkheadSlot<'_> {
+    fn drop(&mut self) {
+        *self.bulkhead.running() -= 1;
+        self.bulkhead.released.notify_one();
+    }
+}
+
+impl Bulkhead {
+    /// Creates a bulkhead with every slot free.
+    ///
+    /// # Parameters
+    /// - `config`: The bulkhead configuration.
+    pub fn new(config: BulkheadConfig) -> Self {
+        Bulkhead {
+            config,
+            running: Mutex::new(0),
+            released: Condvar::new(),
+        }
+    }
+
+    /// Executes an operation once a slot of the bulkhead is free.
+    ///
+    /// # Parameters
+    /// - `operation`: A closure returning a `Result`. It is not called if the call is rejected.
+    ///
+    /// # Returns
+    /// - `Ok(T)` if the operation succeeds.
+    /// - `Err(BulkheadError::Inner(E))` if the operation fails.
+    /// - `Err(BulkheadError::Full)` if no slot freed up within `max_wait`.
+    pub fn call<F, T, E>(&self, operation: F) -> Result<T, BulkheadError<E>>
+    where
+        F: FnOnce() -> Result<T, E>,
+    {
+        let slot = self.acquire()?;
+        let result = operation();
+        drop(slot);
+        result.map_err(BulkheadError::Inner)
+    }
+
+    /// Returns the number of free slots.
+    pub fn available(&self) -> usize {
+        self.config
+            .max_concurrent_calls
+            .saturating_sub(*self.running())
+    }
+
+    fn acquire<E>(&self) -> Result<BulkheadSlot<'_>, BulkheadError<E>> {
+        let max = self.config.max_concurrent_calls;
+        let (mut running, _) = self
+            .released
+            .wait_timeout_while(self.running(), self.config.max_wait, |running| {
+                *running >= max
+            })
+            .unwrap_or_else(PoisonError::into_inner);
+        if *running >= max {
+            return Err(bulkhead::rejected(
+                &self.config.log,
+                self.config.max_concurrent_calls,
+            ));
+        }
+        *running += 1;
+        Ok(BulkheadSlot { bulkhead: self })
+    }
+
+    /// Locks the running count, recovering it if a thread panicked while holding the lock.
+    fn running(&self) -> MutexGuard<'_, usize> {
+        self.running.lock().unwrap_or_else(PoisonError::into_inner)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::classifier::ErrorClass;
+    use crate::config::{ClassPolicy, CooldownEscalation, PolicyTable};
+    use crate::sim::VirtualClock;
+    use crate::store::{InMemoryStateStore, SharedBreakerState, StoreError};
     use crate::strategies::RetryStrategy::{ExponentialBackoff, Linear};
     use std::cell::RefCell;
     use std::fmt::Error;
+    use std::sync::Barrier;
     use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::thread::sleep;
     use std::time::Duration;
 
+    #[test]
+    fn test_execute_with_timeout_returns_result_in_time() {
+        let config = ExecConfig::new(Duration::from_secs(5));
+        let result = execute_with_timeout(|| Ok(42), &config);
+        assert_eq!(result.unwrap(), 42);
+
+        let result = execute_with_timeout::<_, i32>(|| Err("disk full".into()), &config);
+        assert_eq!(result.unwrap_err().to_string(), "disk full");
+    }
+
+    #[test]
+    fn test_execute_with_timeout_falls_back_after_timeout() {
+        let slow = || {
+            sleep(Duration::from_millis(300));
+            Ok("fresh")
+        };
+        let config = ExecConfig::new(Duration::from_millis(20));
+        let err = execute_with_timeout(slow, &config).unwrap_err();
+        assert!(err.downcast_ref::<crate::Error>().unwrap().is_timeout());
+
+        let config = config.with_fallback(|cause| {
+            assert!(cause.is_timeout());
+            Ok("stale")
+        });
+        assert_eq!(execute_with_timeout(slow, &config).unwrap(), "stale");
+    }
+
     #[test]
     fn test_retry_success() {
         let retry_config = RetryConfig {
@@ -143,6 +1035,7 @@ mod tests {
             delay: Duration::from_millis(10),
             retry_condition: None,
             strategy: Linear,
+            ..RetryConfig::default()
         };
 
         let mut attempts = 0;
@@ -169,6 +1062,7 @@ mod tests {
             delay: Duration::from_millis(10),
             retry_condition: None,
             strategy: Linear,
+            ..RetryConfig::default()
         };
 
         let attempts = AtomicUsize::new(0);
@@ -206,6 +1100,7 @@ mod tests {
             delay: Duration::from_millis(10),
             retry_condition: None,
             strategy: Linear,
+            ..RetryConfig::default()
         };
 
         let result = retry(succeed_on_third_attempt, &retry_config);
@@ -222,6 +1117,7 @@ mod tests {
             delay: Duration::from_millis(100),
             retry_condition: None,
             strategy: ExponentialBackoff,
+            ..RetryConfig::default()
         };
 
         let result: Result<i32, Error> = retry(|| Ok(60), &retry_config);
@@ -235,6 +1131,7 @@ mod tests {
             delay: Duration::from_millis(100),
             retry_condition: None,
             strategy: ExponentialBackoff,
+            ..RetryConfig::default()
         };
 
         static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
@@ -261,6 +1158,7 @@ mod tests {
             delay: Duration::from_millis(100),
             retry_condition: None,
             strategy: ExponentialBackoff,
+            ..RetryConfig::default()
         };
 
         static ATTEMPTS: AtomicUsize = AtomicUsize::new(0);
@@ -345,4 +1243,584 @@ mod tests {
         assert_eq!(result, Err("401".to_string()));
         assert_eq!(*attempts.borrow(), 1);
     }
+
+    #[test]
+    fn test_retry_with_classifier_stops_on_permanent() {
+        let attempts = RefCell::new(0);
+        let config = RetryConfig::new(5, Duration::from_millis(1), Linear).with_error_classifier(
+            |e: &String| {
+                if e.contains("503") {
+                    ErrorClass::Transient
+                } else {
+                    ErrorClass::Permanent
+                }
+            },
+        );
+
+        let result: Result<(), String> = retry(
+            || {
+                let mut attempts = attempts.borrow_mut();
+                *attempts += 1;
+                if *attempts < 3 {
+                    Err("503".to_string())
+                } else {
+                    Err("400".to_string())
+                }
+            },
+            &config,
+        );
+
+        assert_eq!(result, Err("400".to_string()));
+        assert_eq!(*attempts.borrow(), 3);
+    }
+
+    #[test]
+    fn test_retry_with_classifier_uses_throttle_delay() {
+        let attempts = RefCell::new(0);
+        let config = RetryConfig::new(2, Duration::from_secs(10), Linear).with_error_classifier(
+            |_: &&str| ErrorClass::Throttled {
+                retry_after: Some(Duration::from_millis(5)),
+            },
+        );
+
+        let start = std::time::Instant::now();
+        let result = retry(
+            || {
+                let mut attempts = attempts.borrow_mut();
+                *attempts += 1;
+                if *attempts < 2 { Err("429") } else { Ok(()) }
+            },
+            &config,
+        );
+
+        assert_eq!(result, Ok(()));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_retry_with_per_class_policies() {
+        let attempts = RefCell::new(0);
+        let config = RetryConfig::new(2, Duration::from_millis(1), Linear)
+            .with_error_classifier(|e: &String| {
+                if e.contains("429") {
+                    ErrorClass::Throttled { retry_after: None }
+                } else {
+                    ErrorClass::Transient
+                }
+            })
+            .with_policies(PolicyTable::new().on_throttled(ClassPolicy::new(
+                5,
+                Duration::from_millis(1),
+                ExponentialBackoff,
+            )));
+
+        let result: Result<(), String> = retry(
+            || {
+                let mut attempts = attempts.borrow_mut();
+                *attempts += 1;
+                Err("429".to_string())
+            },
+            &config,
+        );
+
+        assert_eq!(result, Err("429".to_string()));
+        assert_eq!(*attempts.borrow(), 5);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_deprecated_backoff_shim_honors_classifier_and_policies() {
+        let attempts = RefCell::new(0);
+        let config = RetryConfig::new(2, Duration::from_millis(1), Linear)
+            .with_error_classifier(|e: &String| match e.as_str() {
+                "429" => ErrorClass::Throttled { retry_after: None },
+                "400" => ErrorClass::Permanent,
+                _ => ErrorClass::Transient,
+            })
+            .with_policies(PolicyTable::new().on_throttled(ClassPolicy::new(
+                4,
+                Duration::from_millis(1),
+                Linear,
+            )));
+        let failing = |err: &'static str| {
+            *attempts.borrow_mut() = 0;
+            retry_with_exponential_backoff(
+                || {
+                    *attempts.borrow_mut() += 1;
+                    Err::<(), _>(err.to_string())
+                },
+                &config,
+            )
+        };
+
+        assert!(failing("429").is_err());
+        assert_eq!(*attempts.borrow(), 4);
+        assert!(failing("400").is_err());
+        assert_eq!(*attempts.borrow(), 1);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn test_deprecated_backoff_shim_doubles_the_delay() {
+        let clock = VirtualClock::new();
+        let _guard = clock.enter();
+        let config = RetryConfig::new(5, Duration::from_secs(1), Linear)
+            .with_max_delay(Duration::from_secs(6));
+
+        let result = retry_with_exponential_backoff(|| Err::<(), _>("unavailable"), &config);
+        assert_eq!(result, Err("unavailable"));
+        // 1s, 2s and 4s as before the shim ran the `retry` loop, then capped at `max_delay`.
+        assert_eq!(clock.elapsed(), Duration::from_secs(13));
+    }
+
+    #[test]
+    fn test_retry_shared_picks_up_updates_between_attempts() {
+        let shared: SharedRetryConfig<&str> =
+            SharedRetryConfig::new(RetryConfig::new(2, Duration::from_millis(1), Linear));
+        let attempts = RefCell::new(0);
+
+        let result: Result<(), &str> = retry_shared(
+            || {
+                let mut attempts = attempts.borrow_mut();
+                *attempts += 1;
+                if *attempts == 1 {
+                    shared.update(RetryConfig::new(4, Duration::from_millis(1), Linear));
+                }
+                Err("unavailable")
+            },
+            &shared,
+        );
+
+        assert!(result.is_err());
+        assert_eq!(*attempts.borrow(), 4);
+    }
+
+    #[test]
+    fn test_circuit_breaker_opens_and_recovers() {
+        let clock = VirtualClock::new();
+        let _guard = clock.enter();
+        let cb = CircuitBreaker::<&str>::with_config(CircuitBreakerConfig::new(
+            1,
+            2,
+            Duration::from_millis(20),
+        ));
+        for _ in 0..2 {
+            assert_eq!(
+                cb.run(|| Err::<(), _>("down")),
+                Err(CircuitBreakerError::Inner("down"))
+            );
+        }
+        assert_eq!(cb.state(), CircuitBreakerState::Open);
+        assert!(cb.run(|| Ok::<_, &str>(())).unwrap_err().is_open());
+        assert_eq!(cb.run_with_fallback(|| Ok(1), || Ok(0)), Ok(0));
+
+        clock.advance(Duration::from_millis(30));
+        assert_eq!(cb.run(|| Ok::<_, &str>(1)), Ok(1));
+        assert_eq!(cb.state(), CircuitBreakerState::Close);
+        assert_eq!(cb.failure_count(), 0);
+    }
+
+    #[test]
+    fn test_circuit_breaker_is_shared_between_threads() {
+        let cb = Arc::new(
+            CircuitBreaker::<String>::with_config(CircuitBreakerConfig::new(
+                1,
+                4,
+                Duration::from_secs(60),
+            ))
+            .with_record_failure_if(|err: &String| err != "not found"),
+        );
+        // Every worker waits inside the operation for the others, which only completes if the
+        // breaker does not hold its lock while the operation runs.
+        let barrier = Arc::new(Barrier::new(8));
+        let workers: Vec<_> = (0..8)
+            .map(|i| {
+                let cb = Arc::clone(&cb);
+                let barrier = Arc::clone(&barrier);
+                std::thread::spawn(move || {
+                    cb.run(|| {
+                        barrier.wait();
+                        Err::<(), _>(if i % 2 == 0 { "down" } else { "not found" }.to_string())
+                    })
+                })
+            })
+            .collect();
+        for worker in workers {
+            assert!(!worker.join().unwrap().unwrap_err().is_open());
+        }
+        assert_eq!(cb.state(), CircuitBreakerState::Open);
+        assert!(cb.run(|| Ok::<_, String>(())).unwrap_err().is_open());
+    }
+
+    #[test]
+    fn test_circuit_breakers_share_state_through_store() {
+        let store = Arc::new(InMemoryStateStore::new());
+        let config = CircuitBreakerConfig::new(1, 2, Duration::from_millis(20));
+        let replica_a =
+            CircuitBreaker::<&str>::with_config(config).with_state_store("orders", store.clone());
+        let replica_b =
+            CircuitBreaker::<&str>::with_config(config).with_state_store("orders", store.clone());
+
+        // One failure on each replica reaches the shared threshold.
+        let _ = replica_a.run(|| Err::<(), _>("down"));
+        assert_eq!(replica_a.state(), CircuitBreakerState::Close);
+        let _ = replica_b.run(|| Err::<(), _>("down"));
+        assert_eq!(replica_b.state(), CircuitBreakerState::Open);
+        assert!(replica_a.run(|| Ok::<_, &str>(())).unwrap_err().is_open());
+
+        // A successful trial on one replica closes the circuit for both.
+        sleep(Duration::from_millis(30));
+        assert_eq!(replica_b.run(|| Ok::<_, &str>(1)), Ok(1));
+        assert_eq!(replica_b.state(), CircuitBreakerState::Close);
+        assert_eq!(replica_a.run(|| Ok::<_, &str>(2)), Ok(2));
+        assert_eq!(replica_a.state(), CircuitBreakerState::Close);
+        assert_eq!(store.load("orders").unwrap().failure_count, 0);
+    }
+
+    #[test]
+    fn test_circuit_breaker_falls_back_to_local_state_when_store_fails() {
+        struct Unavailable;
+
+        impl StateStore for Unavailable {
+            fn load(&self, _: &str) -> Result<SharedBreakerState, StoreError> {
+                Err("connection refused".into())
+            }
+            fn record_failure(&self, _: &str) -> Result<usize, StoreError> {
+                Err("connection refused".into())
+            }
+            fn reset_failures(&self, _: &str) -> Result<(), StoreError> {
+                Err("connection refused".into())
+            }
+            fn open(&self, _: &str, _: std::time::SystemTime) -> Result<(), StoreError> {
+                Err("connection refused".into())
+            }
+            fn close(&self, _: &str) -> Result<(), StoreError> {
+                Err("connection refused".into())
+            }
+        }
+
+        let cb = CircuitBreaker::<&str>::with_config(CircuitBreakerConfig::new(
+            1,
+            2,
+            Duration::from_secs(60),
+        ))
+        .with_state_store("orders", Arc::new(Unavailable));
+        let _ = cb.run(|| Err::<(), _>("down"));
+        assert_eq!(cb.failure_count(), 1);
+        let _ = cb.run(|| Err::<(), _>("down"));
+        assert_eq!(cb.state(), CircuitBreakerState::Open);
+    }
+
+    #[test]
+    fn test_event_listeners_can_call_back_into_the_breaker() {
+        let cb = Arc::new(CircuitBreaker::<&str>::with_config(
+            CircuitBreakerConfig::new(1, 1, Duration::from_secs(60)),
+        ));
+        let (seen_tx, seen) = std::sync::mpsc::channel();
+        let id = {
+            let cb = cb.clone();
+            let seen_tx = Mutex::new(seen_tx);
+            crate::events::on_event(move |event| {
+                // Other tests open breakers too; only this breaker's opening is reported.
+                if let crate::events::ResilienceEvent::BreakerOpened = event
+                    && cb.state() == CircuitBreakerState::Open
+                {
+                    let _ = seen_tx.lock().unwrap().send(cb.state());
+                }
+            })
+        };
+        let caller = {
+            let cb = cb.clone();
+            std::thread::spawn(move || cb.run(|| Err::<(), _>("down")))
+        };
+        let state = seen.recv_timeout(Duration::from_secs(5));
+        crate::events::unsubscribe(id);
+        assert_eq!(state, Ok(CircuitBreakerState::Open));
+        assert_eq!(
+            caller.join().unwrap(),
+            Err(CircuitBreakerError::Inner("down"))
+        );
+    }
+
+    #[test]
+    fn test_slow_store_does_not_hold_the_breaker_lock() {
+        struct Slow {
+            entered: std::sync::mpsc::SyncSender<()>,
+            release: Mutex<std::sync::mpsc::Receiver<()>>,
+        }
+
+        impl StateStore for Slow {
+            fn load(&self, _: &str) -> Result<SharedBreakerState, StoreError> {
+                self.entered.send(()).unwrap();
+                let _ = self
+                    .release
+                    .lock()
+                    .unwrap()
+                    .recv_timeout(Duration::from_secs(5));
+                Ok(SharedBreakerState::default())
+            }
+            fn record_failure(&self, _: &str) -> Result<usize, StoreError> {
+                Ok(1)
+            }
+            fn reset_failures(&self, _: &str) -> Result<(), StoreError> {
+                Ok(())
+            }
+            fn open(&self, _: &str, _: std::time::SystemTime) -> Result<(), StoreError> {
+                Ok(())
+            }
+            fn close(&self, _: &str) -> Result<(), StoreError> {
+                Ok(())
+            }
+        }
+
+        let (entered, entered_rx) = std::sync::mpsc::sync_channel(1);
+        let (release_tx, release) = std::sync::mpsc::sync_channel(1);
+        let store = Slow {
+            entered,
+            release: Mutex::new(release),
+        };
+        let cb = Arc::new(
+            CircuitBreaker::<&str>::with_config(CircuitBreakerConfig::default())
+                .with_state_store("orders", Arc::new(store)),
+        );
+        let caller = {
+            let cb = cb.clone();
+            std::thread::spawn(move || cb.run(|| Ok::<_, &str>(())))
+        };
+        entered_rx.recv().unwrap();
+        // The caller is stuck in the store; the breaker itself stays available.
+        let start = std::time::Instant::now();
+        assert_eq!(cb.state(), CircuitBreakerState::Close);
+        assert!(start.elapsed() < Duration::from_secs(1));
+        release_tx.send(()).unwrap();
+        assert_eq!(caller.join().unwrap(), Ok(()));
+    }
+
+    #[test]
+    fn test_circuit_breaker_administrative_states() {
+        let cb = CircuitBreaker::<&str>::with_config(CircuitBreakerConfig::new(
+            1,
+            1,
+            Duration::from_secs(60),
+        ));
+        cb.disable();
+        for _ in 0..3 {
+            assert_eq!(
+                cb.run(|| Err::<(), _>("down")),
+                Err(CircuitBreakerError::Inner("down"))
+            );
+        }
+        assert_eq!(cb.state(), CircuitBreakerState::Disabled);
+        assert_eq!(cb.failure_count(), 0);
+
+        cb.force_open();
+        let calls = AtomicUsize::new(0);
+        let result = cb.run(|| {
+            calls.fetch_add(1, Ordering::SeqCst);
+            Ok::<_, &str>(())
+        });
+        assert!(result.unwrap_err().is_open());
+        assert_eq!(calls.load(Ordering::SeqCst), 0);
+        assert_eq!(cb.time_until_half_open(), None);
+
+        cb.reset();
+        assert_eq!(cb.state(), CircuitBreakerState::Close);
+        assert_eq!(cb.run(|| Ok::<_, &str>(1)), Ok(1));
+    }
+
+    #[test]
+    fn test_circuit_breaker_escalates_cooldown_on_reopen() {
+        let cb = CircuitBreaker::<&str>::with_config(
+            CircuitBreakerConfig::new(1, 1, Duration::from_millis(20)).with_cooldown_escalation(
+                CooldownEscalation::new(3.0, Duration::from_secs(1), Duration::from_secs(10)),
+            ),
+        );
+        let _ = cb.run(|| Err::<(), _>("down"));
+        assert!(cb.time_until_half_open().unwrap() <= Duration::from_millis(20));
+
+        // The trial call fails: the circuit re-opens with a three times longer cooldown.
+        sleep(Duration::from_millis(25));
+        let _ = cb.run(|| Err::<(), _>("down"));
+        assert_eq!(cb.state(), CircuitBreakerState::Open);
+        assert!(cb.time_until_half_open().unwrap() > Duration::from_millis(40));
+
+        cb.reset();
+        let _ = cb.run(|| Err::<(), _>("down"));
+        assert!(cb.time_until_half_open().unwrap() <= Duration::from_millis(20));
+    }
+
+    #[test]
+    fn test_circuit_breaker_events_stream_transitions() {
+        use async_std::prelude::StreamExt;
+
+        let cb = CircuitBreaker::<&str>::with_config(CircuitBreakerConfig::new(
+            1,
+            1,
+            Duration::from_millis(10),
+        ));
+        let mut events = cb.events();
+        drop(cb.events());
+        let _ = cb.run(|| Err::<(), _>("down"));
+        sleep(Duration::from_millis(15));
+        let _ = cb.run(|| Ok::<_, &str>(()));
+        cb.force_open();
+
+        let transitions: Vec<_> = async_std::task::block_on(async {
+            let mut transitions = Vec::new();
+            for _ in 0..4 {
+                let event = events.next().await.unwrap();
+                transitions.push((event.from, event.to));
+            }
+            transitions
+        });
+        assert_eq!(
+            transitions,
+            [
+                (CircuitBreakerState::Close, CircuitBreakerState::Open),
+                (CircuitBreakerState::Open, CircuitBreakerState::HalfOpen),
+                (CircuitBreakerState::HalfOpen, CircuitBreakerState::Close),
+                (CircuitBreakerState::Close, CircuitBreakerState::ForcedOpen),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_circuit_breaker_rolling_success_rate_and_latency() {
+        let cb = CircuitBreaker::<&str>::with_config(
+            CircuitBreakerConfig::default().with_metrics_window_size(4),
+        );
+        assert_eq!(cb.success_rate(), None);
+        for fail in [true, false, false, false, true] {
+            let _ = cb.run(|| {
+                sleep(Duration::from_millis(2));
+                if fail { Err("down") } else { Ok(()) }
+            });
+        }
+        assert_eq!(cb.success_rate(), Some(75.0));
+        assert!(cb.mean_latency().unwrap() >= Duration::from_millis(2));
+        assert!(cb.latency_percentile(50.0) <= cb.latency_percentile(100.0));
+        assert_eq!(cb.metrics().success_rate, Some(75.0));
+    }
+
+    #[test]
+    fn test_circuit_breaker_rejects_calls_beyond_concurrency_cap() {
+        let cb = Arc::new(CircuitBreaker::<&str>::with_config(
+            CircuitBreakerConfig::default().with_max_concurrent_calls(1),
+        ));
+        let started = Arc::new(Barrier::new(2));
+        let release = Arc::new(Barrier::new(2));
+        let worker = {
+            let (cb, started, release) = (cb.clone(), started.clone(), release.clone());
+            std::thread::spawn(move || {
+                cb.run(|| {
+                    started.wait();
+                    release.wait();
+                    Ok::<_, &str>("slow")
+                })
+            })
+        };
+
+        started.wait();
+        let rejected = cb.run(|| Ok::<_, &str>("fast")).unwrap_err();
+        assert_eq!(
+            rejected,
+            CircuitBreakerError::Saturated {
+                max_concurrent_calls: 1
+            }
+        );
+        assert!(rejected.is_saturated());
+        assert_eq!(
+            cb.run_with_fallback(|| Ok("fast"), || Ok("fallback")),
+            Ok("fallback")
+        );
+        assert_eq!(cb.state(), CircuitBreakerState::Close);
+        assert_eq!(cb.failure_count(), 0);
+
+        release.wait();
+        assert_eq!(worker.join().unwrap(), Ok("slow"));
+        assert_eq!(cb.run(|| Ok::<_, &str>("fast")), Ok("fast"));
+    }
+
+    #[test]
+    fn test_bulkhead_rejects_or_waits_when_full() {
+        let bulkhead = Arc::new(Bulkhead::new(
+            BulkheadConfig::new(1).with_max_wait(Duration::from_millis(500)),
+        ));
+        let started = Arc::new(Barrier::new(2));
+        let worker = {
+            let (bulkhead, started) = (bulkhead.clone(), started.clone());
+            std::thread::spawn(move || {
+                bulkhead.call(|| {
+                    started.wait();
+                    sleep(Duration::from_millis(20));
+                    Ok::<_, &str>("slow")
+                })
+            })
+        };
+
+        started.wait();
+        assert_eq!(bulkhead.available(), 0);
+        // The slot frees up well within `max_wait`.
+        assert_eq!(bulkhead.call(|| Ok::<_, &str>("queued")), Ok("queued"));
+        assert_eq!(worker.join().unwrap(), Ok("slow"));
+
+        let impatient = Bulkhead::new(BulkheadConfig::new(1));
+        let nested = impatient.call(|| Ok::<_, &str>(impatient.call(|| Ok::<_, &str>(()))));
+        assert_eq!(nested, Ok(Err(BulkheadError::Full)));
+        assert_eq!(
+            impatient.call(|| Err::<(), _>("down")),
+            Err(BulkheadError::Inner("down"))
+        );
+        assert_eq!(impatient.available(), 1);
+    }
+
+    #[test]
+    fn test_panics_are_retried_only_when_caught() {
+        let clock = VirtualClock::new();
+        let _guard = clock.enter();
+        let config = RetryConfig::new(3, Duration::from_secs(1), Linear).with_catch_panics();
+        let mut calls = 0;
+        let result = retry(
+            || {
+                calls += 1;
+                if calls < 3 {
+                    panic!("index out of bounds");
+                }
+                Ok::<_, Error>(calls)
+            },
+            &config,
+        );
+        assert_eq!(result, Ok(3));
+        assert_eq!(clock.elapsed(), Duration::from_secs(2));
+
+        let calls = AtomicUsize::new(0);
+        let exhausted = panic::catch_unwind(AssertUnwindSafe(|| {
+            retry(
+                || -> Result<(), Error> {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    panic!("always");
+                },
+                &config,
+            )
+        }));
+        assert_eq!(
+            exhausted.unwrap_err().downcast_ref::<&str>(),
+            Some(&"always")
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let uncaught = RetryConfig::new(3, Duration::from_secs(1), Linear);
+        calls.store(0, Ordering::SeqCst);
+        let propagated = panic::catch_unwind(AssertUnwindSafe(|| {
+            retry(
+                || -> Result<(), Error> {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    panic!("always");
+                },
+                &uncaught,
+            )
+        }));
+        assert!(propagated.is_err());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
 }
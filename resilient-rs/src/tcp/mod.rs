@@ -0,0 +1,268 @@
+use crate::asynchronous::schedule_class_retry;
+use crate::config::RetryConfig;
+use crate::logging::log_with;
+use crate::time;
+use async_std::io::{ReadExt, WriteExt};
+use async_std::net::TcpStream;
+use std::io;
+use std::pin::Pin;
+
+/// The future returned by the handshake hook of a `ResilientTcpStream`.
+pub type HandshakeFuture<'s> = Pin<Box<dyn Future<Output = io::Result<()>> + Send + 's>>;
+
+/// A handshake run on every new connection.
+type Handshake = Box<dyn for<'s> FnMut(&'s mut TcpStream) -> HandshakeFuture<'s> + Send>;
+
+/// An I/O operation of a `ResilientTcpStream`, replayed on a new connection after a failure.
+enum Op<'b> {
+    Read(&'b mut [u8]),
+    WriteAll(&'b [u8]),
+    Flush,
+}
+
+/// A TCP connection that dials with retry and backoff, and re-establishes itself when a read or
+/// write fails.
+///
+/// The stream connects on first use, or with `connect`. When dialing, the handshake or an I/O
+/// operation fails with an error the `RetryConfig` classifies as retryable, the connection is
+/// dropped, and the operation is attempted again on a new connection after the config's delay,
+/// until it succeeds or the attempts run out. A handshake set with `with_handshake`, such as
+/// authenticating or selecting a database, runs on every new connection before anything else.
+///
+/// A write that failed is sent again in full on the new connection, so a protocol must tolerate
+/// the peer seeing a partial message followed by the complete one, e.g. because it is
+/// request/response over a fresh session.
+///
+/// # Example
+/// ```
+/// use async_std::io::{ReadExt, WriteExt};
+/// use async_std::net::TcpListener;
+/// use async_std::task::{block_on, spawn};
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::tcp::{HandshakeFuture, ResilientTcpStream};
+/// use async_std::net::TcpStream;
+///
+/// block_on(async {
+///     let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+///     let addr = listener.local_addr().unwrap();
+///     spawn(async move {
+///         let (mut socket, _) = listener.accept().await.unwrap();
+///         let mut hello = [0; 5];
+///         socket.read_exact(&mut hello).await.unwrap();
+///         socket.write_all(b"+OK").await.unwrap();
+///     });
+///
+///     let mut stream = ResilientTcpStream::new(addr.to_string(), RetryConfig::default())
+///         .with_handshake(|stream: &mut TcpStream| -> HandshakeFuture<'_> {
+///             Box::pin(async move { stream.write_all(b"HELLO").await })
+///         });
+///     let mut reply = [0; 3];
+///     stream.read_exact(&mut reply).await.unwrap();
+///     assert_eq!(&reply, b"+OK");
+/// });
+/// ```
+pub struct ResilientTcpStream {
+    addr: String,
+    stream: Option<TcpStream>,
+    handshake: Option<Handshake>,
+    retry_config: RetryConfig<io::Error>,
+    reconnects: usize,
+}
+
+impl ResilientTcpStream {
+    /// Creates a stream connecting to `addr`, with the retries of `retry_config`.
+    ///
+    /// # Arguments
+    /// * `addr` - The address to dial, such as `"cache.internal:6379"`.
+    /// * `retry_config` - The attempts, delays and strategy of every operation, dialing included.
+    pub fn new(addr: impl Into<String>, retry_config: RetryConfig<io::Error>) -> Self {
+        ResilientTcpStream {
+            addr: addr.into(),
+            stream: None,
+            handshake: None,
+            retry_config,
+            reconnects: 0,
+        }
+    }
+
+    /// Builder-style setter for a handshake run on every new connection.
+    ///
+    /// # Arguments
+    /// * `handshake` - A closure returning a `HandshakeFuture` that prepares the connection.
+    ///   A failed handshake counts as a failed attempt.
+    ///
+    /// # Returns
+    /// The updated `ResilientTcpStream` with the specified handshake.
+    pub fn with_handshake<F>(mut self, handshake: F) -> Self
+    where
+        F: for<'s> FnMut(&'s mut TcpStream) -> HandshakeFuture<'s> + Send + 'static,
+    {
+        self.handshake = Some(Box::new(handshake));
+        self
+    }
+
+    /// Returns the current connection, or `None` if it is not established.
+    pub fn get_ref(&self) -> Option<&TcpStream> {
+        self.stream.as_ref()
+    }
+
+    /// Returns the number of established connections that failed and were dropped.
+    pub fn reconnects(&self) -> usize {
+        self.reconnects
+    }
+
+    /// Dials the address and runs the handshake, if not connected yet.
+    pub async fn connect(&mut self) -> io::Result<()> {
+        self.run(Op::Flush).await.map(|_| ())
+    }
+
+    /// Reads some bytes into `buf`, reconnecting if the connection failed.
+    ///
+    /// # Returns
+    /// The number of bytes read, `0` once the peer closed the connection.
+    pub async fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.run(Op::Read(buf)).await
+    }
+
+    /// Reads exactly enough bytes to fill `buf`.
+    ///
+    /// # Returns
+    /// `Err` with `io::ErrorKind::UnexpectedEof` if the peer closed the connection first.
+    pub async fn read_exact(&mut self, mut buf: &mut [u8]) -> io::Result<()> {
+        while !buf.is_empty() {
+            match self.read(buf).await? {
+                0 => return Err(io::ErrorKind::UnexpectedEof.into()),
+                read => buf = &mut buf[read..],
+            }
+        }
+        Ok(())
+    }
+
+    /// Writes all of `buf`, reconnecting and writing it again if the connection failed.
+    pub async fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.run(Op::WriteAll(buf)).await.map(|_| ())
+    }
+
+    /// Flushes the connection, reconnecting if it failed.
+    pub async fn flush(&mut self) -> io::Result<()> {
+        self.run(Op::Flush).await.map(|_| ())
+    }
+
+    /// Runs `op`, on a new connection if needed, retrying it according to the `RetryConfig`.
+    async fn run(&mut self, mut op: Op<'_>) -> io::Result<usize> {
+        let mut attempt = 1;
+        let mut delay = self.retry_config.delay;
+        loop {
+            let err = match self.attempt(&mut op).await {
+                Ok(output) => return Ok(output),
+                Err(err) => err,
+            };
+            let class = self.retry_config.classify(&err);
+            let Some(wait) = schedule_class_retry(&self.retry_config, class, attempt, delay) else {
+                return Err(err);
+            };
+            if self.stream.take().is_some() {
+                log_with!(
+                    self.retry_config.log,
+                    self.retry_config.log.level,
+                    "Connection to {} failed ({}), reconnecting...",
+                    self.addr,
+                    err
+                );
+                self.reconnects += 1;
+            }
+            time::sleep_on(self.retry_config.timer_wheel.as_ref(), wait).await;
+            delay = self.retry_config.next_delay(delay, attempt);
+            attempt += 1;
+        }
+    }
+
+    async fn attempt(&mut self, op: &mut Op<'_>) -> io::Result<usize> {
+        if self.stream.is_none() {
+            let mut stream = TcpStream::connect(&self.addr).await?;
+            if let Some(handshake) = self.handshake.as_mut() {
+                handshake(&mut stream).await?;
+            }
+            self.stream = Some(stream);
+        }
+        let stream = self.stream.as_mut().expect("the stream was just connected");
+        match op {
+            Op::Read(buf) => stream.read(buf).await,
+            Op::WriteAll(buf) => stream.write_all(buf).await.map(|_| buf.len()),
+            Op::Flush => stream.flush().await.map(|_| 0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classifier::ErrorClass;
+    use crate::strategies::RetryStrategy;
+    use async_std::net::TcpListener;
+    use async_std::task::{block_on, spawn};
+    use std::sync::Arc;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    fn retry_config() -> RetryConfig<io::Error> {
+        RetryConfig::new(3, Duration::from_millis(1), RetryStrategy::Linear)
+    }
+
+    #[test]
+    fn test_failed_handshake_reconnects() {
+        block_on(async {
+            let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let addr = listener.local_addr().unwrap();
+            let server = spawn(async move {
+                let mut received = Vec::new();
+                for _ in 0..2 {
+                    let (mut socket, _) = listener.accept().await.unwrap();
+                    let mut message = Vec::new();
+                    socket.read_to_end(&mut message).await.unwrap();
+                    received.push(String::from_utf8(message).unwrap());
+                }
+                received
+            });
+
+            let handshakes = Arc::new(AtomicUsize::new(0));
+            let mut stream = ResilientTcpStream::new(addr.to_string(), retry_config())
+                .with_handshake({
+                    let handshakes = handshakes.clone();
+                    move |stream: &mut TcpStream| -> HandshakeFuture<'_> {
+                        let handshake = handshakes.fetch_add(1, Ordering::SeqCst);
+                        Box::pin(async move {
+                            stream.write_all(b"AUTH;").await?;
+                            match handshake {
+                                0 => Err(io::ErrorKind::ConnectionReset.into()),
+                                _ => Ok(()),
+                            }
+                        })
+                    }
+                });
+            stream.write_all(b"SET key").await.unwrap();
+            assert_eq!(handshakes.load(Ordering::SeqCst), 2);
+            drop(stream);
+
+            assert_eq!(server.await, ["AUTH;", "AUTH;SET key"]);
+        });
+    }
+
+    #[test]
+    fn test_dial_gives_up_on_permanent_errors() {
+        let config = retry_config().with_error_classifier(|err: &io::Error| match err.kind() {
+            io::ErrorKind::ConnectionRefused => ErrorClass::Permanent,
+            _ => ErrorClass::Transient,
+        });
+        // Nothing listens on a port freed right after binding it.
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+        let mut stream = ResilientTcpStream::new(addr.to_string(), config);
+
+        let err = block_on(stream.connect()).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::ConnectionRefused);
+        assert!(stream.get_ref().is_none());
+    }
+}
@@ -0,0 +1,278 @@
+use crate::ratelimit::{RateLimited, RateLimiter};
+use crate::time;
+use async_std::channel::{self, Receiver, Sender};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+/// Coalesces bursts of triggers, such as file watcher notifications, UI events or webhook
+/// floods, into a single batch handed over once the triggers quiet down.
+///
+/// Producers call `trigger` with each event; a consumer loop awaits `settled`, which returns
+/// every event gathered so far once no new one arrived for `wait`, and then calls the backend
+/// once per batch instead of once per event. With `with_max_wait`, a batch is also handed over
+/// after that long since its first event, so a steady stream of triggers cannot postpone it
+/// forever.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use async_std::task::block_on;
+/// use resilient_rs::throttle::Debouncer;
+///
+/// let debouncer = Debouncer::new(Duration::from_millis(20));
+/// debouncer.trigger("config.toml");
+/// debouncer.trigger("routes.toml");
+///
+/// let changed = block_on(debouncer.settled());
+/// assert_eq!(changed, ["config.toml", "routes.toml"]);
+/// ```
+pub struct Debouncer<T> {
+    wait: Duration,
+    max_wait: Option<Duration>,
+    batch: Mutex<Batch<T>>,
+    notify: Sender<()>,
+    notified: Receiver<()>,
+}
+
+/// The events gathered by a `Debouncer` since its last batch was handed over.
+struct Batch<T> {
+    events: Vec<T>,
+    first: Option<Instant>,
+    last: Option<Instant>,
+}
+
+impl<T> Debouncer<T> {
+    /// Creates a debouncer handing over a batch once no trigger arrived for `wait`.
+    pub fn new(wait: Duration) -> Self {
+        let (notify, notified) = channel::bounded(1);
+        Debouncer {
+            wait,
+            max_wait: None,
+            batch: Mutex::new(Batch {
+                events: Vec::new(),
+                first: None,
+                last: None,
+            }),
+            notify,
+            notified,
+        }
+    }
+
+    /// Sets the longest time a batch may wait after its first event, and returns the modified
+    /// debouncer.
+    pub fn with_max_wait(mut self, max_wait: Duration) -> Self {
+        self.max_wait = Some(max_wait);
+        self
+    }
+
+    /// Adds an event to the current batch and restarts its quiet period.
+    pub fn trigger(&self, event: T) {
+        let now = time::now();
+        {
+            let mut batch = self.batch();
+            batch.events.push(event);
+            batch.first.get_or_insert(now);
+            batch.last = Some(now);
+        }
+        // A full channel already wakes the consumer up.
+        let _ = self.notify.try_send(());
+    }
+
+    /// Returns the number of events waiting in the current batch.
+    pub fn pending(&self) -> usize {
+        self.batch().events.len()
+    }
+
+    /// Waits until the current batch settled, then returns its events in trigger order.
+    ///
+    /// Waits for a first trigger if the batch is empty.
+    pub async fn settled(&self) -> Vec<T> {
+        loop {
+            let remaining = {
+                let mut batch = self.batch();
+                match (batch.first, batch.last) {
+                    (Some(first), Some(last)) => {
+                        let mut deadline = last + self.wait;
+                        if let Some(max_wait) = self.max_wait {
+                            deadline = deadline.min(first + max_wait);
+                        }
+                        let now = time::now();
+                        if now >= deadline {
+                            batch.first = None;
+                            batch.last = None;
+                            return std::mem::take(&mut batch.events);
+                        }
+                        Some(deadline - now)
+                    }
+                    _ => None,
+                }
+            };
+            match remaining {
+                Some(remaining) => time::sleep(remaining).await,
+                // The debouncer owns a sender, so the channel never closes.
+                None => {
+                    let _ = self.notified.recv().await;
+                }
+            }
+        }
+    }
+
+    fn batch(&self) -> MutexGuard<'_, Batch<T>> {
+        self.batch.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Spaces calls at least `interval` apart, delaying the ones arriving too early instead of
+/// rejecting them, so a bursty caller hits a retry- or breaker-protected backend at a steady
+/// pace.
+///
+/// Each call reserves the next free slot, so concurrent callers are served one `interval` apart
+/// in the order they arrived. `call` waits asynchronously and `call_blocking` puts the thread to
+/// sleep; as a `RateLimiter`, `try_acquire` rejects a call arriving before its slot instead.
+///
+/// # Example
+/// ```
+/// use std::time::{Duration, Instant};
+/// use resilient_rs::throttle::Throttle;
+///
+/// let throttle = Throttle::new(Duration::from_millis(10));
+/// let start = Instant::now();
+/// for _ in 0..3 {
+///     throttle.call_blocking(|| println!("refreshing the cache"));
+/// }
+/// assert!(start.elapsed() >= Duration::from_millis(20));
+/// ```
+pub struct Throttle {
+    interval: Duration,
+    next: Mutex<Option<Instant>>,
+}
+
+impl Throttle {
+    /// Creates a throttle admitting its first call immediately.
+    pub fn new(interval: Duration) -> Self {
+        Throttle {
+            interval,
+            next: Mutex::new(None),
+        }
+    }
+
+    /// Waits for the next free slot, then runs the asynchronous operation.
+    pub async fn call<F, Fut, T>(&self, operation: F) -> T
+    where
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = T>,
+    {
+        let wait = self.reserve();
+        if !wait.is_zero() {
+            time::sleep(wait).await;
+        }
+        operation().await
+    }
+
+    /// Sleeps until the next free slot, then runs the operation.
+    pub fn call_blocking<F, T>(&self, operation: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        let wait = self.reserve();
+        if !wait.is_zero() {
+            time::sleep_blocking(wait);
+        }
+        operation()
+    }
+
+    /// Reserves the next free slot and returns how long to wait for it.
+    fn reserve(&self) -> Duration {
+        let now = time::now();
+        let mut next = self.next();
+        let slot = next.map_or(now, |next| next.max(now));
+        *next = Some(slot + self.interval);
+        slot - now
+    }
+
+    fn next(&self) -> MutexGuard<'_, Option<Instant>> {
+        self.next.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl RateLimiter for Throttle {
+    fn try_acquire(&self) -> Result<(), RateLimited> {
+        let now = time::now();
+        let mut next = self.next();
+        match *next {
+            Some(slot) if slot > now => Err(RateLimited {
+                retry_after: slot - now,
+            }),
+            _ => {
+                *next = Some(now + self.interval);
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::VirtualClock;
+
+    #[test]
+    fn test_debouncer_waits_for_quiet_or_max_wait() {
+        let clock = VirtualClock::new();
+        let debouncer = Debouncer::new(Duration::from_millis(100));
+        let batch = clock.block_on(async {
+            debouncer.trigger(1);
+            time::sleep(Duration::from_millis(60)).await;
+            debouncer.trigger(2);
+            debouncer.settled().await
+        });
+        assert_eq!(batch, [1, 2]);
+        assert_eq!(clock.elapsed(), Duration::from_millis(160));
+        assert_eq!(debouncer.pending(), 0);
+
+        let clock = VirtualClock::new();
+        let debouncer =
+            Debouncer::new(Duration::from_millis(100)).with_max_wait(Duration::from_millis(120));
+        let batch = clock.block_on(async {
+            debouncer.trigger("a");
+            time::sleep(Duration::from_millis(80)).await;
+            debouncer.trigger("b");
+            debouncer.settled().await
+        });
+        assert_eq!(batch, ["a", "b"]);
+        assert_eq!(clock.elapsed(), Duration::from_millis(120));
+    }
+
+    #[test]
+    fn test_throttle_spaces_calls() {
+        let clock = VirtualClock::new();
+        let throttle = Throttle::new(Duration::from_secs(1));
+        let calls = clock.block_on(async {
+            let mut calls = Vec::new();
+            for call in 0..3 {
+                calls.push(throttle.call(|| async move { (call, time::now()) }).await);
+            }
+            calls
+        });
+        assert_eq!(calls[2].0, 2);
+        assert_eq!(calls[2].1 - calls[0].1, Duration::from_secs(2));
+
+        let clock = VirtualClock::new();
+        let _guard = clock.enter();
+        let throttle = Throttle::new(Duration::from_secs(1));
+
+        for call in 0..3 {
+            assert_eq!(throttle.call_blocking(|| call), call);
+        }
+        assert_eq!(clock.elapsed(), Duration::from_secs(2));
+        assert_eq!(
+            throttle.try_acquire(),
+            Err(RateLimited {
+                retry_after: Duration::from_secs(1)
+            })
+        );
+
+        clock.advance(Duration::from_secs(1));
+        assert!(throttle.try_acquire().is_ok());
+    }
+}
@@ -0,0 +1,148 @@
+use crate::wheel::TimerWheel;
+use async_std::future::TimeoutError;
+use rand::RngCore;
+use std::sync::{Condvar, MutexGuard};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Returns the current instant, read from the virtual clock of the current thread if one is
+/// installed.
+pub(crate) fn now() -> Instant {
+    #[cfg(any(test, feature = "sim"))]
+    if let Some(now) = crate::sim::with_current(|clock| clock.now()) {
+        return now;
+    }
+    Instant::now()
+}
+
+/// Returns the current system time, read from the virtual clock of the current thread if one is
+/// installed.
+pub(crate) fn system_now() -> SystemTime {
+    #[cfg(any(test, feature = "sim"))]
+    if let Some(now) = crate::sim::with_current(|clock| clock.system_now()) {
+        return now;
+    }
+    SystemTime::now()
+}
+
+/// Returns the time elapsed since `since`, as `Instant::elapsed` does.
+pub(crate) fn elapsed(since: Instant) -> Duration {
+    now().saturating_duration_since(since)
+}
+
+/// Waits for `duration` without blocking the thread.
+pub(crate) async fn sleep(duration: Duration) {
+    #[cfg(any(test, feature = "sim"))]
+    if let Some(sleep) = crate::sim::with_current(|clock| clock.sleep(duration)) {
+        return sleep.await;
+    }
+    async_std::task::sleep(duration).await
+}
+
+/// Waits for `duration` on `wheel` if one is given, or on a timer of its own otherwise; on a
+/// virtual clock, the wheel is ignored.
+pub(crate) async fn sleep_on(wheel: Option<&TimerWheel>, duration: Duration) {
+    #[cfg(any(test, feature = "sim"))]
+    let wheel = wheel.filter(|_| crate::sim::with_current(|_| ()).is_none());
+    match wheel {
+        Some(wheel) => wheel.sleep(duration).await,
+        None => sleep(duration).await,
+    }
+}
+
+/// Blocks the thread for `duration`; on a virtual clock, advances the clock instead.
+pub(crate) fn sleep_blocking(duration: Duration) {
+    #[cfg(any(test, feature = "sim"))]
+    if crate::sim::with_current(|clock| clock.advance(duration)).is_some() {
+        return;
+    }
+    std::thread::sleep(duration)
+}
+
+/// The shortest delay worth a timer; shorter backoff delays of the retry loops only yield.
+pub(crate) const MIN_SLEEP: Duration = Duration::from_millis(1);
+
+/// Yields to the executor in place of a sleep for a delay shorter than `MIN_SLEEP`.
+///
+/// Timers are not precise below a millisecond, so a retry loop with such a delay would just go
+/// through the timer machinery for nothing, while it still has to let other tasks run between
+/// its attempts. Virtual clocks are not advanced for such delays.
+pub(crate) async fn yield_now() {
+    async_std::task::yield_now().await
+}
+
+/// Yields the thread in place of a sleep for a delay shorter than `MIN_SLEEP`, avoiding the
+/// system call of a zero-length sleep.
+pub(crate) fn yield_now_blocking() {
+    std::thread::yield_now()
+}
+
+/// Blocks on `condvar` while `condition` holds, for at most `duration`, as
+/// `Condvar::wait_timeout_while` does; on a virtual clock, advances the clock by `duration`
+/// unless `condition` already stopped holding.
+pub(crate) fn wait_timeout_while<'a, T>(
+    condvar: &Condvar,
+    guard: MutexGuard<'a, T>,
+    duration: Duration,
+    condition: impl FnMut(&mut T) -> bool,
+) -> MutexGuard<'a, T> {
+    #[cfg(any(test, feature = "sim"))]
+    if crate::sim::with_current(|_| ()).is_some() {
+        let (mut guard, mut condition) = (guard, condition);
+        if condition(&mut guard) {
+            sleep_blocking(duration);
+        }
+        return guard;
+    }
+    condvar
+        .wait_timeout_while(guard, duration, condition)
+        .map_or_else(|poisoned| poisoned.into_inner().0, |(guard, _)| guard)
+}
+
+/// Runs `future` until it completes or `duration` elapses, as `async_std::future::timeout` does.
+pub(crate) async fn timeout<F: Future>(
+    duration: Duration,
+    future: F,
+) -> Result<F::Output, TimeoutError> {
+    #[cfg(any(test, feature = "sim"))]
+    if let Some(sleep) = crate::sim::with_current(|clock| clock.sleep(duration)) {
+        let mut future = std::pin::pin!(future);
+        let mut sleep = std::pin::pin!(sleep);
+        let expired = std::future::poll_fn(|cx| {
+            if let std::task::Poll::Ready(output) = future.as_mut().poll(cx) {
+                return std::task::Poll::Ready(Some(output));
+            }
+            sleep.as_mut().poll(cx).map(|()| None)
+        })
+        .await;
+        return match expired {
+            Some(output) => Ok(output),
+            None => Err(timeout_error()),
+        };
+    }
+    async_std::future::timeout(duration, future).await
+}
+
+/// Returns a `TimeoutError`, which cannot be built outside async-std, from a timeout expiring at
+/// once.
+#[cfg(any(test, feature = "sim"))]
+fn timeout_error() -> TimeoutError {
+    let expired = std::pin::pin!(async_std::future::timeout(
+        Duration::ZERO,
+        std::future::pending::<()>()
+    ));
+    match expired.poll(&mut std::task::Context::from_waker(std::task::Waker::noop())) {
+        std::task::Poll::Ready(Err(err)) => err,
+        _ => unreachable!("a zero timeout expires on the first poll"),
+    }
+}
+
+/// Calls `f` with the random number generator of the current thread, which is the seeded
+/// generator of the virtual clock if one is installed.
+pub(crate) fn with_rng<T>(f: impl FnOnce(&mut dyn RngCore) -> T) -> T {
+    #[cfg(any(test, feature = "sim"))]
+    let f = match crate::sim::with_current_rng(f) {
+        Ok(output) => return output,
+        Err(f) => f,
+    };
+    f(&mut rand::rng())
+}
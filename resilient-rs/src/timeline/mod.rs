@@ -0,0 +1,207 @@
+use crate::classifier::ErrorClass;
+use crate::time;
+use std::fmt;
+use std::time::{Duration, Instant, SystemTime};
+
+/// The outcome of one attempt of a retried operation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AttemptOutcome {
+    /// The attempt succeeded.
+    Succeeded,
+    /// The attempt failed with an error of the given class.
+    Failed(ErrorClass),
+    /// The attempt panicked, with `RetryConfig::catch_panics` set.
+    Panicked,
+}
+
+/// What happened during one attempt of a retried operation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttemptRecord {
+    /// The 1-based number of the attempt.
+    pub attempt: usize,
+    /// The wall-clock time the attempt started at.
+    pub started_at: SystemTime,
+    /// The time elapsed between the start of the call and the start of the attempt.
+    pub offset: Duration,
+    /// How long the attempt ran.
+    pub duration: Duration,
+    /// How the attempt ended.
+    pub outcome: AttemptOutcome,
+    /// The delay chosen before the next attempt, or `None` if no attempt followed.
+    pub delay: Option<Duration>,
+}
+
+/// The attempts of one retried call, as recorded by `asynchronous::retry_with_timeline` and
+/// `synchronous::retry_with_timeline`.
+///
+/// A timeline explains where the time of a slow call went: how long each attempt ran, how it
+/// ended, and how long the retry loop waited before the next one. Its `Display` output is a
+/// one-line-per-attempt report suited to logs and postmortems.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::strategies::RetryStrategy;
+/// use resilient_rs::synchronous::retry_with_timeline;
+/// use resilient_rs::timeline::AttemptOutcome;
+///
+/// let config = RetryConfig::new(3, Duration::from_millis(10), RetryStrategy::Linear);
+/// let mut calls = 0;
+/// let (result, timeline) = retry_with_timeline(|| {
+///     calls += 1;
+///     if calls < 3 { Err("connection reset") } else { Ok(calls) }
+/// }, &config);
+/// assert_eq!(result, Ok(3));
+/// assert_eq!(timeline.attempts().len(), 3);
+/// assert_eq!(timeline.attempts()[0].delay, Some(Duration::from_millis(10)));
+/// assert_eq!(timeline.attempts()[2].outcome, AttemptOutcome::Succeeded);
+/// assert!(timeline.total() >= timeline.total_backoff());
+/// println!("{}", timeline);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timeline {
+    started: Instant,
+    attempts: Vec<AttemptRecord>,
+    total: Duration,
+}
+
+impl Timeline {
+    /// Starts recording a call.
+    pub(crate) fn start() -> Self {
+        Timeline {
+            started: time::now(),
+            attempts: Vec::new(),
+            total: Duration::ZERO,
+        }
+    }
+
+    /// Records an attempt that started at `start` and just ended with `outcome`.
+    pub(crate) fn record_attempt(&mut self, start: Instant, outcome: AttemptOutcome) {
+        let duration = time::elapsed(start);
+        let offset = start.saturating_duration_since(self.started);
+        self.attempts.push(AttemptRecord {
+            attempt: self.attempts.len() + 1,
+            started_at: time::system_now() - duration,
+            offset,
+            duration,
+            outcome,
+            delay: None,
+        });
+    }
+
+    /// Records the delay chosen after the last recorded attempt.
+    pub(crate) fn record_delay(&mut self, delay: Duration) {
+        if let Some(last) = self.attempts.last_mut() {
+            last.delay = Some(delay);
+        }
+    }
+
+    /// Records the end of the call.
+    pub(crate) fn finish(&mut self) {
+        self.total = time::elapsed(self.started);
+    }
+
+    /// Returns the recorded attempts, in order.
+    pub fn attempts(&self) -> &[AttemptRecord] {
+        &self.attempts
+    }
+
+    /// Returns the time the whole call took, attempts and delays included.
+    pub fn total(&self) -> Duration {
+        self.total
+    }
+
+    /// Returns the sum of the delays chosen between the attempts.
+    pub fn total_backoff(&self) -> Duration {
+        self.attempts
+            .iter()
+            .filter_map(|attempt| attempt.delay)
+            .sum()
+    }
+}
+
+impl fmt::Display for Timeline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(
+            f,
+            "{} attempts in {:?} ({:?} of backoff)",
+            self.attempts.len(),
+            self.total,
+            self.total_backoff()
+        )?;
+        for attempt in &self.attempts {
+            write!(
+                f,
+                "  attempt {} at +{:?}: ran {:?}, ",
+                attempt.attempt, attempt.offset, attempt.duration
+            )?;
+            match attempt.outcome {
+                AttemptOutcome::Succeeded => write!(f, "succeeded")?,
+                AttemptOutcome::Failed(class) => write!(f, "failed ({:?})", class)?,
+                AttemptOutcome::Panicked => write!(f, "panicked")?,
+            }
+            match attempt.delay {
+                Some(delay) => writeln!(f, ", retried after {:?}", delay)?,
+                None => writeln!(f)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RetryConfig;
+    use crate::sim::VirtualClock;
+    use crate::strategies::RetryStrategy;
+
+    #[test]
+    fn test_timeline_explains_where_the_time_went() {
+        let clock = VirtualClock::new();
+        let config = RetryConfig::new(3, Duration::from_secs(2), RetryStrategy::ExponentialBackoff)
+            .with_error_classifier(|err: &&str| match *err {
+                "throttled" => ErrorClass::Throttled {
+                    retry_after: Some(Duration::from_secs(5)),
+                },
+                _ => ErrorClass::Transient,
+            });
+        let mut calls = 0;
+        let (result, timeline) = clock.block_on(crate::asynchronous::retry_with_timeline(
+            || {
+                calls += 1;
+                let calls = calls;
+                async move {
+                    crate::time::sleep(Duration::from_millis(500)).await;
+                    match calls {
+                        1 => Err("reset"),
+                        2 => Err("throttled"),
+                        _ => Ok(calls),
+                    }
+                }
+            },
+            &config,
+        ));
+        assert_eq!(result, Ok(3));
+        assert_eq!(timeline.total(), Duration::from_millis(8_500));
+        assert_eq!(timeline.total_backoff(), Duration::from_secs(7));
+        let offsets: Vec<_> = timeline.attempts().iter().map(|a| a.offset).collect();
+        assert_eq!(
+            offsets,
+            [0, 2_500, 8_000].map(Duration::from_millis).to_vec()
+        );
+        assert_eq!(
+            timeline.attempts()[1].outcome,
+            AttemptOutcome::Failed(ErrorClass::Throttled {
+                retry_after: Some(Duration::from_secs(5))
+            })
+        );
+        assert_eq!(timeline.attempts()[2].delay, None);
+        assert!(
+            timeline
+                .to_string()
+                .starts_with("3 attempts in 8.5s (7s of backoff)\n  attempt 1 at +0ns: ran 500ms, failed (Transient), retried after 2s\n")
+        );
+    }
+}
@@ -0,0 +1,500 @@
+use crate::asynchronous::{CircuitBreaker, CircuitBreakerError, retry};
+use crate::config::{CircuitBreakerConfig, ExecConfig, FallbackCause, RetryConfig};
+use crate::error;
+use crate::events::{self, ResilienceEvent};
+use crate::logging::Level;
+use crate::logging::log_with;
+use crate::metrics;
+use crate::ratelimit::RateLimiter;
+use crate::time::timeout;
+use std::fmt;
+use std::future::{Future, poll_fn};
+use std::mem;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use tower_layer::Layer;
+use tower_service::Service;
+
+/// The type-erased error returned by the services that can reject a call on their own, as used
+/// throughout the tower ecosystem.
+pub use crate::config::BoxError;
+
+/// The future returned by the services of this module.
+pub type ResponseFuture<T, E> = Pin<Box<dyn Future<Output = Result<T, E>> + Send + 'static>>;
+
+/// A `Layer` retrying the requests of the wrapped service according to a `RetryConfig`.
+///
+/// Every attempt clones the service and the request, so both must be `Clone`; the service is
+/// driven to readiness before each attempt.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::tower::RetryLayer;
+///
+/// let layer = RetryLayer::new(
+///     RetryConfig::<std::io::Error>::default()
+///         .with_max_attempts(3)
+///         .with_delay(Duration::from_millis(100)),
+/// );
+/// ```
+pub struct RetryLayer<E> {
+    config: Arc<RetryConfig<E>>,
+}
+
+impl<E> RetryLayer<E> {
+    /// Creates a layer retrying with `config`.
+    pub fn new(config: RetryConfig<E>) -> Self {
+        RetryLayer {
+            config: Arc::new(config),
+        }
+    }
+}
+
+impl<E> Clone for RetryLayer<E> {
+    fn clone(&self) -> Self {
+        RetryLayer {
+            config: self.config.clone(),
+        }
+    }
+}
+
+impl<S, E> Layer<S> for RetryLayer<E> {
+    type Service = Retry<S, E>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        Retry {
+            inner,
+            config: self.config.clone(),
+        }
+    }
+}
+
+/// The service created by `RetryLayer`.
+pub struct Retry<S, E> {
+    inner: S,
+    config: Arc<RetryConfig<E>>,
+}
+
+impl<S: Clone, E> Clone for Retry<S, E> {
+    fn clone(&self) -> Self {
+        Retry {
+            inner: self.inner.clone(),
+            config: self.config.clone(),
+        }
+    }
+}
+
+impl<S, Req> Service<Req> for Retry<S, S::Error>
+where
+    S: Service<Req> + Clone + Send + 'static,
+    S::Future: Send,
+    S::Response: Send + 'static,
+    S::Error: Send + Sync + 'static,
+    Req: Clone + Send + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = ResponseFuture<S::Response, S::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Req) -> Self::Future {
+        let inner = self.inner.clone();
+        let config = self.config.clone();
+        Box::pin(async move {
+            let attempt = move || {
+                let mut service = inner.clone();
+                let request = request.clone();
+                async move {
+                    poll_fn(|cx| service.poll_ready(cx)).await?;
+                    service.call(request).await
+                }
+            };
+            retry(attempt, &config).await
+        })
+    }
+}
+
+/// A `Layer` supervising the requests of the wrapped service with a shared `CircuitBreaker`.
+///
+/// Every service created by the layer, and every clone of them, reports to the same breaker, so
+/// the breaker sees all the traffic to the dependency. Rejected calls fail with
+/// `CircuitBreakerError::Open` or `CircuitBreakerError::Saturated` without reaching the service.
+/// The service must be `Clone`, since it is only called once the breaker admitted the call.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::config::CircuitBreakerConfig;
+/// use resilient_rs::tower::CircuitBreakerLayer;
+///
+/// let layer = CircuitBreakerLayer::<std::io::Error>::with_config(
+///     CircuitBreakerConfig::new(2, 5, Duration::from_secs(30)),
+/// );
+/// ```
+pub struct CircuitBreakerLayer<E> {
+    breaker: Arc<CircuitBreaker<E>>,
+}
+
+impl<E> CircuitBreakerLayer<E> {
+    /// Creates a layer reporting to `breaker`, which the caller can keep to inspect its state.
+    pub fn new(breaker: Arc<CircuitBreaker<E>>) -> Self {
+        CircuitBreakerLayer { breaker }
+    }
+
+    /// Creates a layer reporting to a new breaker configured with `config`.
+    pub fn with_config(config: CircuitBreakerConfig) -> Self {
+        CircuitBreakerLayer::new(Arc::new(CircuitBreaker::with_config(config)))
+    }
+}
+
+impl<E> Clone for CircuitBreakerLayer<E> {
+    fn clone(&self) -> Self {
+        CircuitBreakerLayer {
+            breaker: self.breaker.clone(),
+        }
+    }
+}
+
+impl<S, E> Layer<S> for CircuitBreakerLayer<E> {
+    type Service = CircuitBreakerService<S, E>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        CircuitBreakerService {
+            inner,
+            breaker: self.breaker.clone(),
+        }
+    }
+}
+
+/// The service created by `CircuitBreakerLayer`.
+pub struct CircuitBreakerService<S, E> {
+    inner: S,
+    breaker: Arc<CircuitBreaker<E>>,
+}
+
+impl<S: Clone, E> Clone for CircuitBreakerService<S, E> {
+    fn clone(&self) -> Self {
+        CircuitBreakerService {
+            inner: self.inner.clone(),
+            breaker: self.breaker.clone(),
+        }
+    }
+}
+
+impl<S, Req> Service<Req> for CircuitBreakerService<S, S::Error>
+where
+    S: Service<Req> + Clone + Send + 'static,
+    S::Future: Send + 'static,
+    S::Response: Send + 'static,
+    S::Error: fmt::Display + Send + 'static,
+    Req: Send + 'static,
+{
+    type Response = S::Response;
+    type Error = CircuitBreakerError<S::Error>;
+    type Future = ResponseFuture<S::Response, Self::Error>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner
+            .poll_ready(cx)
+            .map_err(CircuitBreakerError::Inner)
+    }
+
+    fn call(&mut self, request: Req) -> Self::Future {
+        // Call the service that was driven to readiness, only once the breaker admits the call.
+        let clone = self.inner.clone();
+        let mut inner = mem::replace(&mut self.inner, clone);
+        let breaker = self.breaker.clone();
+        Box::pin(async move { breaker.run(move || inner.call(request)).await })
+    }
+}
+
+/// A `Layer` bounding the requests of the wrapped service by the timeout of an `ExecConfig`,
+/// answering timed out requests with its fallback.
+///
+/// Like `execute_with_fallback`, only timeouts trigger the fallback; errors of the service are
+/// returned as is. A timed out request without fallback fails with
+/// `resilient_rs::Error::Timeout`, and the error of a failed fallback is returned as is.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use resilient_rs::config::ExecConfig;
+/// use resilient_rs::tower::TimeoutFallbackLayer;
+///
+/// let config = ExecConfig::new(Duration::from_secs(2)).with_fallback(|_| Ok("service busy".to_string()));
+/// let layer = TimeoutFallbackLayer::new(config);
+/// ```
+pub struct TimeoutFallbackLayer<T> {
+    config: Arc<ExecConfig<T>>,
+}
+
+impl<T> TimeoutFallbackLayer<T> {
+    /// Creates a layer applying the timeout and fallback of `config`.
+    pub fn new(config: ExecConfig<T>) -> Self {
+        TimeoutFallbackLayer {
+            config: Arc::new(config),
+        }
+    }
+}
+
+impl<T> Clone for TimeoutFallbackLayer<T> {
+    fn clone(&self) -> Self {
+        TimeoutFallbackLayer {
+            config: self.config.clone(),
+        }
+    }
+}
+
+impl<S, T> Layer<S> for TimeoutFallbackLayer<T> {
+    type Service = TimeoutFallback<S, T>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        TimeoutFallback {
+            inner,
+            config: self.config.clone(),
+        }
+    }
+}
+
+/// The service created by `TimeoutFallbackLayer`.
+pub struct TimeoutFallback<S, T> {
+    inner: S,
+    config: Arc<ExecConfig<T>>,
+}
+
+impl<S: Clone, T> Clone for TimeoutFallback<S, T> {
+    fn clone(&self) -> Self {
+        TimeoutFallback {
+            inner: self.inner.clone(),
+            config: self.config.clone(),
+        }
+    }
+}
+
+impl<S, Req> Service<Req> for TimeoutFallback<S, S::Response>
+where
+    S: Service<Req>,
+    S::Future: Send + 'static,
+    S::Response: Send + Sync + 'static,
+    S::Error: Into<BoxError>,
+{
+    type Response = S::Response;
+    type Error = BoxError;
+    type Future = ResponseFuture<S::Response, BoxError>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, request: Req) -> Self::Future {
+        let response = self.inner.call(request);
+        let config = self.config.clone();
+        Box::pin(async move {
+            if let Ok(result) = timeout(config.timeout_duration, response).await {
+                return result.map_err(Into::into);
+            }
+            metrics::increment(&metrics::TIMEOUTS);
+            events::emit(ResilienceEvent::TimeoutHit {
+                timeout: config.timeout_duration,
+            });
+            let Some(fallback) = &config.fallback else {
+                log_with!(
+                    config.log,
+                    Level::Error,
+                    "Request timed out; no fallback provided, returning error."
+                );
+                return Err(Box::new(error::Error::Timeout {
+                    timeout: config.timeout_duration,
+                }));
+            };
+            log_with!(
+                config.log,
+                config.log.level,
+                "Request timed out; executing fallback."
+            );
+            metrics::increment(&metrics::FALLBACKS);
+            events::emit(ResilienceEvent::FallbackUsed);
+            fallback(FallbackCause::Timeout {
+                timeout: config.timeout_duration,
+            })
+        })
+    }
+}
+
+/// A `Layer` rejecting the requests of the wrapped service once a `RateLimiter` refuses them.
+///
+/// Rejected requests fail with a boxed `resilient_rs::Error::RateLimited`, which can be downcast
+/// to read its `retry_after`, e.g. to answer with a `Retry-After` header. Every service created
+/// by the layer shares the limiter.
+///
+/// # Example
+/// ```
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use resilient_rs::config::RateLimitConfig;
+/// use resilient_rs::ratelimit::SlidingLogLimiter;
+/// use resilient_rs::tower::RateLimitLayer;
+///
+/// let limiter = SlidingLogLimiter::new(RateLimitConfig::new(100, Duration::from_secs(1)));
+/// let layer = RateLimitLayer::new(Arc::new(limiter));
+/// ```
+#[derive(Clone)]
+pub struct RateLimitLayer {
+    limiter: Arc<dyn RateLimiter>,
+}
+
+impl RateLimitLayer {
+    /// Creates a layer admitting the requests allowed by `limiter`.
+    pub fn new(limiter: Arc<dyn RateLimiter>) -> Self {
+        RateLimitLayer { limiter }
+    }
+}
+
+impl<S> Layer<S> for RateLimitLayer {
+    type Service = RateLimit<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        RateLimit {
+            inner,
+            limiter: self.limiter.clone(),
+        }
+    }
+}
+
+/// The service created by `RateLimitLayer`.
+#[derive(Clone)]
+pub struct RateLimit<S> {
+    inner: S,
+    limiter: Arc<dyn RateLimiter>,
+}
+
+impl<S, Req> Service<Req> for RateLimit<S>
+where
+    S: Service<Req>,
+    S::Future: Send + 'static,
+    S::Error: Into<BoxError>,
+{
+    type Response = S::Response;
+    type Error = BoxError;
+    type Future = ResponseFuture<S::Response, BoxError>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx).map_err(Into::into)
+    }
+
+    fn call(&mut self, request: Req) -> Self::Future {
+        if let Err(rejected) = self.limiter.try_acquire() {
+            return Box::pin(
+                async move { Err(Box::new(error::Error::from(rejected)) as BoxError) },
+            );
+        }
+        let response = self.inner.call(request);
+        Box::pin(async move { response.await.map_err(Into::into) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RateLimitConfig;
+    use crate::ratelimit::FixedWindowLimiter;
+    use async_std::task::{block_on, sleep};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    /// A service failing its first `failures` calls, then echoing the request.
+    #[derive(Clone)]
+    struct Flaky {
+        calls: Arc<AtomicUsize>,
+        failures: usize,
+    }
+
+    impl Service<u32> for Flaky {
+        type Response = u32;
+        type Error = String;
+        type Future = ResponseFuture<u32, String>;
+
+        fn poll_ready(&mut self, _: &mut Context<'_>) -> Poll<Result<(), String>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn call(&mut self, request: u32) -> Self::Future {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            let failures = self.failures;
+            Box::pin(async move {
+                if request == 0 {
+                    sleep(Duration::from_millis(100)).await;
+                }
+                if call < failures {
+                    Err(format!("failure {}", call + 1))
+                } else {
+                    Ok(request)
+                }
+            })
+        }
+    }
+
+    fn flaky(failures: usize) -> (Flaky, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let service = Flaky {
+            calls: calls.clone(),
+            failures,
+        };
+        (service, calls)
+    }
+
+    #[test]
+    fn test_retry_and_circuit_breaker_layers() {
+        let retry_config = RetryConfig {
+            max_attempts: 3,
+            delay: Duration::from_millis(1),
+            ..RetryConfig::default()
+        };
+        let (service, calls) = flaky(2);
+        let mut service = RetryLayer::new(retry_config).layer(service);
+        assert_eq!(block_on(service.call(7)), Ok(7));
+        assert_eq!(calls.load(Ordering::SeqCst), 3);
+
+        let layer = CircuitBreakerLayer::with_config(CircuitBreakerConfig::new(
+            1,
+            1,
+            Duration::from_secs(30),
+        ));
+        let (service, calls) = flaky(1);
+        let mut service = layer.layer(service);
+        let mut clone = service.clone();
+        assert_eq!(
+            block_on(service.call(7)),
+            Err(CircuitBreakerError::Inner("failure 1".to_string()))
+        );
+        assert!(block_on(clone.call(7)).unwrap_err().is_open());
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_timeout_fallback_and_rate_limit_layers() {
+        let (service, _) = flaky(0);
+        let config = ExecConfig::new(Duration::from_millis(20))
+            .with_fallback(|cause| Ok(if cause.is_timeout() { 42 } else { 0 }));
+        let mut service = TimeoutFallbackLayer::new(config).layer(service);
+        assert_eq!(block_on(service.call(0)).unwrap(), 42);
+        assert_eq!(block_on(service.call(7)).unwrap(), 7);
+
+        let limiter = FixedWindowLimiter::new(RateLimitConfig::new(1, Duration::from_secs(60)));
+        let (service, calls) = flaky(0);
+        let mut service = RateLimitLayer::new(Arc::new(limiter)).layer(service);
+        assert_eq!(block_on(service.call(7)).unwrap(), 7);
+        let rejected = block_on(service.call(7)).unwrap_err();
+        assert!(matches!(
+            rejected.downcast_ref::<crate::Error>(),
+            Some(crate::Error::RateLimited { .. })
+        ));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,242 @@
+use crate::config::LogConfig;
+use crate::logging::Level;
+use crate::logging::log_with;
+use crate::time;
+use async_std::task::{self, JoinHandle};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, Weak};
+use std::time::{Duration, Instant};
+
+/// Detects stuck work: the monitored task must `pet` the watchdog at least once per `timeout`,
+/// otherwise the watchdog fires a callback, e.g. to restart the task, raise an alert or force a
+/// circuit breaker open.
+///
+/// Retries and timeouts react to operations that fail or are too slow; a watchdog catches the
+/// loops that stop making progress without failing, such as a consumer blocked on a lock or a
+/// worker waiting on a channel nobody writes to.
+///
+/// The callback fires once per stall, with the time elapsed since the last pet; the next pet
+/// re-arms it. The deadline is checked by a monitor started with `spawn` or `spawn_thread`, or
+/// by calling `check` from any existing loop. Handles are cheap to clone and share the same
+/// deadline, so the monitored task can own one while the monitor runs elsewhere; the monitor
+/// stops once every handle is dropped.
+///
+/// # Example
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use resilient_rs::watchdog::Watchdog;
+///
+/// let stalls = Arc::new(AtomicUsize::new(0));
+/// let counter = stalls.clone();
+/// let watchdog = Watchdog::new(Duration::from_millis(10), move |silence| {
+///     counter.fetch_add(1, Ordering::SeqCst);
+///     assert!(silence >= Duration::from_millis(10));
+/// });
+///
+/// watchdog.pet();
+/// assert!(!watchdog.check());
+/// std::thread::sleep(Duration::from_millis(20));
+/// assert!(watchdog.check());
+/// assert_eq!(stalls.load(Ordering::SeqCst), 1);
+/// ```
+#[derive(Clone)]
+pub struct Watchdog {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    timeout: Duration,
+    last_pet: Mutex<Instant>,
+    fired: AtomicBool,
+    on_stall: Box<dyn Fn(Duration) + Send + Sync>,
+    log: LogConfig,
+}
+
+impl Watchdog {
+    /// Creates a watchdog, considered petted now.
+    ///
+    /// # Arguments
+    /// * `timeout` - The longest time allowed between two pets.
+    /// * `on_stall` - Called with the time elapsed since the last pet when `timeout` is exceeded.
+    pub fn new<F>(timeout: Duration, on_stall: F) -> Self
+    where
+        F: Fn(Duration) + Send + Sync + 'static,
+    {
+        Watchdog {
+            inner: Arc::new(Inner {
+                timeout,
+                last_pet: Mutex::new(time::now()),
+                fired: AtomicBool::new(false),
+                on_stall: Box::new(on_stall),
+                log: LogConfig::default(),
+            }),
+        }
+    }
+
+    /// Sets the logging behavior of the stalls and recoveries, and returns the modified
+    /// watchdog.
+    ///
+    /// # Panics
+    /// Panics if the watchdog was already cloned.
+    pub fn with_log(mut self, log: LogConfig) -> Self {
+        Arc::get_mut(&mut self.inner)
+            .expect("with_log must be called before the watchdog is cloned")
+            .log = log;
+        self
+    }
+
+    /// Signals that the monitored task is making progress, re-arming the watchdog if it fired.
+    pub fn pet(&self) {
+        *self.last_pet() = time::now();
+        if self.inner.fired.swap(false, Ordering::AcqRel) {
+            log_with!(
+                self.inner.log,
+                Level::Info,
+                "Watchdog petted again; monitored task recovered"
+            );
+        }
+    }
+
+    /// Returns the time elapsed since the last pet.
+    pub fn since_last_pet(&self) -> Duration {
+        time::elapsed(*self.last_pet())
+    }
+
+    /// Returns `true` if the watchdog fired and was not petted since.
+    pub fn is_stalled(&self) -> bool {
+        self.inner.fired.load(Ordering::Acquire)
+    }
+
+    /// Fires the callback if the timeout elapsed since the last pet and it did not fire yet for
+    /// this stall.
+    ///
+    /// # Returns
+    /// `true` if the monitored task is stalled.
+    pub fn check(&self) -> bool {
+        let silence = self.since_last_pet();
+        if silence < self.inner.timeout {
+            return false;
+        }
+        if !self.inner.fired.swap(true, Ordering::AcqRel) {
+            log_with!(
+                self.inner.log,
+                Level::Error,
+                "Watchdog not petted for {:?}; monitored task is stalled",
+                silence
+            );
+            (self.inner.on_stall)(silence);
+        }
+        true
+    }
+
+    /// Spawns a task checking the deadline until every handle of the watchdog is dropped.
+    ///
+    /// # Returns
+    /// The handle of the spawned task.
+    pub fn spawn(&self) -> JoinHandle<()> {
+        let inner = Arc::downgrade(&self.inner);
+        task::spawn(async move {
+            while let Some(wait) = Watchdog::tick(&inner) {
+                time::sleep(wait).await;
+            }
+        })
+    }
+
+    /// Spawns a thread checking the deadline until every handle of the watchdog is dropped, for
+    /// applications without an async runtime.
+    ///
+    /// # Returns
+    /// The handle of the spawned thread.
+    pub fn spawn_thread(&self) -> std::thread::JoinHandle<()> {
+        let inner = Arc::downgrade(&self.inner);
+        std::thread::spawn(move || {
+            while let Some(wait) = Watchdog::tick(&inner) {
+                time::sleep_blocking(wait);
+            }
+        })
+    }
+
+    /// Checks the deadline of the watchdog, if it still exists, and returns how long to wait
+    /// before the next check.
+    fn tick(inner: &Weak<Inner>) -> Option<Duration> {
+        let watchdog = Watchdog {
+            inner: inner.upgrade()?,
+        };
+        watchdog.check();
+        // Once fired, keep polling at the timeout's pace to notice the next pet.
+        let remaining = watchdog
+            .inner
+            .timeout
+            .saturating_sub(watchdog.since_last_pet());
+        Some(if remaining.is_zero() {
+            watchdog.inner.timeout
+        } else {
+            remaining
+        })
+    }
+
+    fn last_pet(&self) -> MutexGuard<'_, Instant> {
+        self.inner
+            .last_pet
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim::VirtualClock;
+    use std::sync::atomic::AtomicUsize;
+
+    #[test]
+    fn test_fires_once_per_stall_and_rearms_on_pet() {
+        let clock = VirtualClock::new();
+        let _guard = clock.enter();
+        let stalls = Arc::new(Mutex::new(Vec::new()));
+        let log = stalls.clone();
+        let watchdog = Watchdog::new(Duration::from_secs(10), move |silence| {
+            log.lock().unwrap().push(silence)
+        });
+
+        clock.advance(Duration::from_secs(9));
+        assert!(!watchdog.check());
+        watchdog.pet();
+        clock.advance(Duration::from_secs(12));
+        assert!(watchdog.check());
+        assert!(watchdog.check());
+        assert!(watchdog.is_stalled());
+        assert_eq!(*stalls.lock().unwrap(), [Duration::from_secs(12)]);
+
+        watchdog.pet();
+        assert!(!watchdog.is_stalled());
+        clock.advance(Duration::from_secs(10));
+        assert!(watchdog.check());
+        assert_eq!(stalls.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_monitor_task_fires_and_stops_with_the_watchdog() {
+        let stalls = Arc::new(AtomicUsize::new(0));
+        let counter = stalls.clone();
+        let watchdog = Watchdog::new(Duration::from_millis(20), move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        let monitor = watchdog.spawn();
+
+        for _ in 0..5 {
+            std::thread::sleep(Duration::from_millis(5));
+            watchdog.pet();
+        }
+        assert_eq!(stalls.load(Ordering::SeqCst), 0);
+        std::thread::sleep(Duration::from_millis(60));
+        assert_eq!(stalls.load(Ordering::SeqCst), 1);
+
+        drop(watchdog);
+        let stopped =
+            async_std::task::block_on(async_std::future::timeout(Duration::from_secs(1), monitor));
+        assert!(stopped.is_ok());
+    }
+}
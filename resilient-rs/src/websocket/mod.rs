@@ -0,0 +1,294 @@
+use crate::asynchronous::retry;
+use crate::config::RetryConfig;
+use crate::logging::log_with;
+use crate::sink::send_and_flush;
+use futures_core::Stream;
+use futures_sink::Sink;
+use std::future::poll_fn;
+use std::pin::Pin;
+
+/// The future returned by the connection factory of a `ReconnectingWebSocket`.
+type ConnectFuture<C, E> = Pin<Box<dyn Future<Output = Result<C, E>> + Send>>;
+
+/// A WebSocket that transparently reconnects, with backoff, when its connection drops.
+///
+/// The socket owns a factory opening new connections, such as a closure calling
+/// `async_tungstenite::async_std::connect_async`. Any connection that is both a `Stream` of
+/// incoming messages and a `Sink` of outgoing ones can be used, which includes the
+/// `WebSocketStream` of `tungstenite`-based crates.
+///
+/// When receiving or sending fails with an error the `RetryConfig` classifies as retryable, or
+/// the server closes the stream, the connection is dropped and a new one is opened with the
+/// config's attempts, delays and strategy. Each reconnection starts over from the config's base
+/// delay, so a connection that stayed up for a while does not inherit the backoff of an earlier
+/// outage. After reconnecting, the messages returned by the `with_resubscribe` hook are sent
+/// first, e.g. to subscribe to the same channels again.
+///
+/// # Example
+/// ```rust,ignore
+/// use async_tungstenite::async_std::connect_async;
+/// use async_tungstenite::tungstenite::Message;
+/// use resilient_rs::config::RetryConfig;
+/// use resilient_rs::websocket::ReconnectingWebSocket;
+///
+/// let mut socket = ReconnectingWebSocket::new(
+///     || async { connect_async("wss://stream.example.com").await.map(|(stream, _)| stream) },
+///     RetryConfig::default(),
+/// )
+/// .with_resubscribe(|| vec![Message::text(r#"{"subscribe":"trades"}"#)]);
+///
+/// while let Ok(message) = socket.recv().await {
+///     println!("{}", message);
+/// }
+/// ```
+pub struct ReconnectingWebSocket<C, M, E> {
+    connection: Option<C>,
+    connect: Box<dyn FnMut() -> ConnectFuture<C, E> + Send>,
+    resubscribe: Option<Box<dyn FnMut() -> Vec<M> + Send>>,
+    retry_config: RetryConfig<E>,
+    reconnects: usize,
+}
+
+impl<C, M, E> ReconnectingWebSocket<C, M, E>
+where
+    C: Stream<Item = Result<M, E>> + Sink<M, Error = E> + Unpin,
+{
+    /// Creates a socket opening its connections with `connect`.
+    ///
+    /// The first connection is opened by the first `recv` or `send`.
+    ///
+    /// # Arguments
+    /// * `connect` - A closure returning a `Future` resolving to a new connection.
+    /// * `retry_config` - The attempts, delays and strategy of every reconnection.
+    pub fn new<F, Fut>(mut connect: F, retry_config: RetryConfig<E>) -> Self
+    where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<C, E>> + Send + 'static,
+    {
+        ReconnectingWebSocket {
+            connection: None,
+            connect: Box::new(move || Box::pin(connect())),
+            resubscribe: None,
+            retry_config,
+            reconnects: 0,
+        }
+    }
+
+    /// Builder-style setter for a hook returning the messages to send on every new connection.
+    ///
+    /// # Arguments
+    /// * `resubscribe` - A closure returning the messages, such as subscription requests, sent
+    ///   before any other message on a new connection.
+    ///
+    /// # Returns
+    /// The updated `ReconnectingWebSocket` with the specified hook.
+    pub fn with_resubscribe<F>(mut self, resubscribe: F) -> Self
+    where
+        F: FnMut() -> Vec<M> + Send + 'static,
+    {
+        self.resubscribe = Some(Box::new(resubscribe));
+        self
+    }
+
+    /// Returns the number of times the socket reconnected after a connection dropped.
+    pub fn reconnects(&self) -> usize {
+        self.reconnects
+    }
+
+    /// Receives the next message, reconnecting if the connection dropped.
+    ///
+    /// # Returns
+    /// - `Ok(M)` with the next message.
+    /// - `Err(E)` if an error is not retryable, or reconnecting ran out of attempts.
+    pub async fn recv(&mut self) -> Result<M, E> {
+        loop {
+            let connection = self.connection().await?;
+            match poll_fn(|cx| Pin::new(&mut *connection).poll_next(cx)).await {
+                Some(Ok(message)) => return Ok(message),
+                Some(Err(err)) => self.drop_connection(err)?,
+                None => {
+                    log_with!(
+                        self.retry_config.log,
+                        self.retry_config.log.level,
+                        "WebSocket closed by the server, reconnecting..."
+                    );
+                    self.connection = None;
+                    self.reconnects += 1;
+                }
+            }
+        }
+    }
+
+    /// Sends a message, reconnecting and sending it again if the connection dropped.
+    ///
+    /// # Returns
+    /// - `Ok(())` once the message was sent and flushed.
+    /// - `Err(E)` if an error is not retryable, or reconnecting ran out of attempts.
+    pub async fn send(&mut self, message: M) -> Result<(), E>
+    where
+        M: Clone,
+    {
+        loop {
+            let connection = self.connection().await?;
+            match send_and_flush(connection, Some(message.clone())).await {
+                Ok(()) => return Ok(()),
+                Err(err) => self.drop_connection(err)?,
+            }
+        }
+    }
+
+    /// Closes the current connection, if any.
+    pub async fn close(&mut self) -> Result<(), E> {
+        match self.connection.take() {
+            Some(mut connection) => poll_fn(|cx| Pin::new(&mut connection).poll_close(cx)).await,
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the current connection, opening a new one if there is none.
+    async fn connection(&mut self) -> Result<&mut C, E> {
+        if self.connection.is_none() {
+            let mut connection = retry(&mut self.connect, &self.retry_config).await?;
+            if let Some(resubscribe) = self.resubscribe.as_mut() {
+                for message in resubscribe() {
+                    send_and_flush(&mut connection, Some(message)).await?;
+                }
+            }
+            self.connection = Some(connection);
+        }
+        Ok(self
+            .connection
+            .as_mut()
+            .expect("the connection was just opened"))
+    }
+
+    /// Drops the connection after `err`, or returns `err` if it is not worth reconnecting for.
+    fn drop_connection(&mut self, err: E) -> Result<(), E> {
+        if !self.retry_config.classify(&err).is_retryable() {
+            return Err(err);
+        }
+        log_with!(
+            self.retry_config.log,
+            self.retry_config.log.level,
+            "WebSocket connection dropped, reconnecting..."
+        );
+        self.connection = None;
+        self.reconnects += 1;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::classifier::ErrorClass;
+    use crate::sim::VirtualClock;
+    use crate::strategies::RetryStrategy;
+    use std::collections::VecDeque;
+    use std::sync::{Arc, Mutex};
+    use std::task::{Context, Poll};
+    use std::time::Duration;
+
+    /// A connection replaying scripted incoming messages and recording the outgoing ones.
+    struct FakeConnection {
+        incoming: VecDeque<Result<String, String>>,
+        sent: Arc<Mutex<Vec<String>>>,
+    }
+
+    impl Stream for FakeConnection {
+        type Item = Result<String, String>;
+
+        fn poll_next(mut self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            Poll::Ready(self.incoming.pop_front())
+        }
+    }
+
+    impl Sink<String> for FakeConnection {
+        type Error = String;
+
+        fn poll_ready(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), String>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn start_send(self: Pin<&mut Self>, message: String) -> Result<(), String> {
+            self.sent.lock().unwrap().push(message);
+            Ok(())
+        }
+
+        fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), String>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<Result<(), String>> {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    /// Returns a factory whose connections replay `scripts` in turn, failing once before each.
+    fn factory(
+        scripts: Vec<Vec<Result<String, String>>>,
+        sent: &Arc<Mutex<Vec<String>>>,
+    ) -> impl FnMut() -> std::future::Ready<Result<FakeConnection, String>> + Send + 'static {
+        let scripts = Arc::new(Mutex::new(VecDeque::from(scripts)));
+        let sent = sent.clone();
+        let mut refused = false;
+        move || {
+            refused = !refused;
+            if refused {
+                return std::future::ready(Err("connection refused".to_string()));
+            }
+            let incoming = scripts.lock().unwrap().pop_front().unwrap_or_default();
+            std::future::ready(Ok(FakeConnection {
+                incoming: incoming.into(),
+                sent: sent.clone(),
+            }))
+        }
+    }
+
+    #[test]
+    fn test_reconnects_and_resubscribes_when_the_connection_drops() {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let scripts = vec![
+            vec![
+                Ok("trade 1".to_string()),
+                Err("connection reset".to_string()),
+            ],
+            vec![Ok("trade 2".to_string())],
+        ];
+        let config = RetryConfig::new(3, Duration::from_secs(1), RetryStrategy::Linear);
+        let mut socket = ReconnectingWebSocket::new(factory(scripts, &sent), config)
+            .with_resubscribe(|| vec!["subscribe trades".to_string()]);
+        let clock = VirtualClock::new();
+
+        assert_eq!(clock.block_on(socket.recv()), Ok("trade 1".to_string()));
+        assert_eq!(clock.block_on(socket.recv()), Ok("trade 2".to_string()));
+        assert_eq!(socket.reconnects(), 1);
+        // Every connection was refused once before it was opened.
+        assert_eq!(clock.elapsed(), Duration::from_secs(2));
+        assert_eq!(
+            *sent.lock().unwrap(),
+            ["subscribe trades", "subscribe trades"]
+        );
+
+        clock.block_on(socket.send("ping".to_string())).unwrap();
+        assert_eq!(sent.lock().unwrap().last().unwrap(), "ping");
+    }
+
+    #[test]
+    fn test_permanent_errors_are_returned() {
+        let sent = Arc::new(Mutex::new(Vec::new()));
+        let scripts = vec![vec![Err("unauthorized".to_string())]];
+        let config = RetryConfig::new(3, Duration::from_secs(1), RetryStrategy::Linear)
+            .with_error_classifier(|err: &String| match err.as_str() {
+                "unauthorized" => ErrorClass::Permanent,
+                _ => ErrorClass::Transient,
+            });
+        let mut socket = ReconnectingWebSocket::new(factory(scripts, &sent), config);
+
+        assert_eq!(
+            VirtualClock::new().block_on(socket.recv()),
+            Err("unauthorized".to_string())
+        );
+        assert_eq!(socket.reconnects(), 0);
+    }
+}
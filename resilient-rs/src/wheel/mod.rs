@@ -0,0 +1,397 @@
+use std::fmt;
+use std::mem;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// The number of slots of each level of the wheel, as a power of two.
+const SLOT_BITS: u32 = 6;
+const SLOTS: usize = 1 << SLOT_BITS;
+/// The number of levels; with a resolution of 1ms, the lowest level spans 64ms and the highest
+/// about 4.6 hours. Later deadlines wait in the highest level and are re-filed on every pass.
+const LEVELS: usize = 4;
+
+/// A hierarchical timer wheel shared by many sleeping futures.
+///
+/// An application retrying tens of thousands of operations at once creates as many sleep timers,
+/// each registered with the runtime's timer and woken on its own. Sleeps on a `TimerWheel` are
+/// instead filed into slots of a few coarse-grained wheels and woken in batches, once per tick,
+/// by a single driver thread. Registering a sleep is constant time, and the wakeups of sleeps
+/// ending within the same tick are coalesced, which reduces timer pressure and jitter for large
+/// fan-out workloads.
+///
+/// Sleeps end on the first tick after their deadline, so they can last up to one `resolution`
+/// longer than requested, but never less. The driver thread is started by the first sleep and
+/// exits once no sleep is pending. Handles are cheap to clone and share the same wheel.
+///
+/// Retry loops use a wheel once it is set on their configuration with
+/// `RetryConfig::with_timer_wheel`; sleeps on a virtual clock (see `sim`) ignore the wheel.
+///
+/// # Example
+/// ```
+/// use std::time::{Duration, Instant};
+/// use async_std::task;
+/// use resilient_rs::wheel::TimerWheel;
+///
+/// let wheel = TimerWheel::new(Duration::from_millis(5));
+/// let start = Instant::now();
+/// let sleeps: Vec<_> = (0..1_000)
+///     .map(|_| {
+///         let wheel = wheel.clone();
+///         task::spawn(async move { wheel.sleep(Duration::from_millis(20)).await })
+///     })
+///     .collect();
+/// task::block_on(async {
+///     for sleep in sleeps {
+///         sleep.await;
+///     }
+/// });
+/// assert!(start.elapsed() >= Duration::from_millis(20));
+/// assert_eq!(wheel.pending(), 0);
+/// ```
+#[derive(Clone)]
+pub struct TimerWheel {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    /// The instant tick `0` started at.
+    origin: Instant,
+    resolution: Duration,
+    state: Mutex<State>,
+}
+
+impl TimerWheel {
+    /// Creates a new `TimerWheel` ticking every `resolution`.
+    ///
+    /// # Arguments
+    /// * `resolution` - The duration of a tick; sleeps are rounded up to a whole number of ticks.
+    ///
+    /// # Returns
+    /// A new `TimerWheel` with no pending sleep.
+    pub fn new(resolution: Duration) -> Self {
+        TimerWheel {
+            inner: Arc::new(Inner {
+                origin: Instant::now(),
+                resolution: resolution.max(Duration::from_micros(1)),
+                state: Mutex::new(State::new()),
+            }),
+        }
+    }
+
+    /// Returns a future completing once `duration` has elapsed, on the first tick after it.
+    ///
+    /// The deadline is computed when this function is called, and the sleep is registered with
+    /// the wheel when the future is first polled. A `duration` too long to represent as an
+    /// `Instant` sleeps until the last tick of the wheel, i.e. forever.
+    pub fn sleep(&self, duration: Duration) -> WheelSleep {
+        let deadline = Instant::now()
+            .checked_add(duration)
+            .map_or(u64::MAX, |deadline| self.inner.tick_at(deadline, true));
+        WheelSleep {
+            wheel: self.inner.clone(),
+            deadline,
+            entry: None,
+        }
+    }
+
+    /// Returns the duration of a tick.
+    pub fn resolution(&self) -> Duration {
+        self.inner.resolution
+    }
+
+    /// Returns the number of sleeps registered with the wheel and not woken yet.
+    pub fn pending(&self) -> usize {
+        self.inner.state().pending
+    }
+
+    /// Returns `true` if both handles refer to the same wheel.
+    pub(crate) fn ptr_eq(&self, other: &TimerWheel) -> bool {
+        Arc::ptr_eq(&self.inner, &other.inner)
+    }
+}
+
+impl Default for TimerWheel {
+    /// Creates a `TimerWheel` with a resolution of 1 millisecond.
+    fn default() -> Self {
+        TimerWheel::new(Duration::from_millis(1))
+    }
+}
+
+impl fmt::Debug for TimerWheel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TimerWheel")
+            .field("resolution", &self.inner.resolution)
+            .field("pending", &self.pending())
+            .finish()
+    }
+}
+
+impl Inner {
+    /// Returns the tick `instant` falls in, or the first tick starting at or after it when
+    /// `round_up` is `true`.
+    fn tick_at(&self, instant: Instant, round_up: bool) -> u64 {
+        let nanos = instant.saturating_duration_since(self.origin).as_nanos();
+        let resolution = self.resolution.as_nanos();
+        let tick = if round_up {
+            nanos.div_ceil(resolution)
+        } else {
+            nanos / resolution
+        };
+        u64::try_from(tick).unwrap_or(u64::MAX)
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Advances the wheel tick by tick with the real time and wakes the sleeps that are due,
+    /// until no sleep is pending.
+    fn drive(self: Arc<Self>) {
+        let mut due = Vec::new();
+        loop {
+            let mut state = self.state();
+            let now = self.tick_at(Instant::now(), false);
+            while state.current < now {
+                state.advance(&mut due);
+            }
+            let idle = state.pending == 0;
+            if idle {
+                state.running = false;
+            }
+            drop(state);
+            // Wake outside the lock, as woken tasks may register their next sleep at once.
+            for entry in due.drain(..) {
+                entry.fire();
+            }
+            if idle {
+                return;
+            }
+            let next = self.origin
+                + Duration::from_nanos((self.resolution.as_nanos() * (now as u128 + 1)) as u64);
+            thread::sleep(next.saturating_duration_since(Instant::now()));
+        }
+    }
+}
+
+/// The slots of the wheel, behind the wheel's lock.
+struct State {
+    /// `levels[l][s]` holds the sleeps due within the `s`th span of `SLOTS^l` ticks of the
+    /// current rotation of level `l`.
+    levels: Vec<Vec<Vec<Arc<Entry>>>>,
+    /// The last tick processed.
+    current: u64,
+    pending: usize,
+    /// Whether the driver thread is running.
+    running: bool,
+}
+
+impl State {
+    fn new() -> Self {
+        State {
+            levels: (0..LEVELS)
+                .map(|_| (0..SLOTS).map(|_| Vec::new()).collect())
+                .collect(),
+            current: 0,
+            pending: 0,
+            running: false,
+        }
+    }
+
+    /// Files `entry` in the lowest level spanning its deadline, or pushes it to `due` if it is
+    /// already due.
+    fn place(&mut self, entry: Arc<Entry>, due: &mut Vec<Arc<Entry>>) {
+        if entry.deadline <= self.current {
+            self.pending -= 1;
+            due.push(entry);
+            return;
+        }
+        let delta = entry.deadline - self.current;
+        let level = (0..LEVELS)
+            .find(|level| delta < 1 << (SLOT_BITS * (*level as u32 + 1)))
+            .unwrap_or(LEVELS - 1);
+        let slot = (entry.deadline >> (SLOT_BITS * level as u32)) as usize % SLOTS;
+        self.levels[level][slot].push(entry);
+    }
+
+    /// Processes the next tick: re-files the sleeps of the higher-level slots starting at this
+    /// tick into lower levels, then pushes the sleeps of the tick to `due`.
+    fn advance(&mut self, due: &mut Vec<Arc<Entry>>) {
+        self.current += 1;
+        let tick = self.current;
+        for level in (1..LEVELS).rev() {
+            let shift = SLOT_BITS * level as u32;
+            if tick.is_multiple_of(1 << shift) {
+                let slot = (tick >> shift) as usize % SLOTS;
+                for entry in mem::take(&mut self.levels[level][slot]) {
+                    self.place(entry, due);
+                }
+            }
+        }
+        let slot = tick as usize % SLOTS;
+        let fired = mem::take(&mut self.levels[0][slot]);
+        self.pending -= fired.len();
+        due.extend(fired);
+    }
+}
+
+/// A sleep registered with the wheel.
+struct Entry {
+    deadline: u64,
+    fired: AtomicBool,
+    waker: Mutex<Option<Waker>>,
+}
+
+impl Entry {
+    fn fire(&self) {
+        self.fired.store(true, Ordering::Release);
+        if let Some(waker) = self.waker().take() {
+            waker.wake();
+        }
+    }
+
+    fn waker(&self) -> MutexGuard<'_, Option<Waker>> {
+        self.waker.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A future completing once the duration given to `TimerWheel::sleep` has elapsed.
+///
+/// Dropping it before it completes leaves its slot in the wheel until the deadline, which only
+/// costs the memory of the slot.
+pub struct WheelSleep {
+    wheel: Arc<Inner>,
+    deadline: u64,
+    entry: Option<Arc<Entry>>,
+}
+
+impl Future for WheelSleep {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        if let Some(entry) = &self.entry {
+            if entry.fired.load(Ordering::Acquire) {
+                return Poll::Ready(());
+            }
+            *entry.waker() = Some(cx.waker().clone());
+            // The driver may have fired the entry before the waker was replaced.
+            return if entry.fired.load(Ordering::Acquire) {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            };
+        }
+        let mut state = self.wheel.state();
+        if !state.running {
+            // Nothing is filed while the driver is stopped, so the wheel can jump to now.
+            state.current = self.wheel.tick_at(Instant::now(), false);
+        }
+        if self.deadline <= state.current {
+            return Poll::Ready(());
+        }
+        let entry = Arc::new(Entry {
+            deadline: self.deadline,
+            fired: AtomicBool::new(false),
+            waker: Mutex::new(Some(cx.waker().clone())),
+        });
+        state.pending += 1;
+        state.place(entry.clone(), &mut Vec::new());
+        if !state.running {
+            state.running = true;
+            let wheel = self.wheel.clone();
+            thread::spawn(move || wheel.drive());
+        }
+        drop(state);
+        self.entry = Some(entry);
+        Poll::Pending
+    }
+}
+
+impl fmt::Debug for WheelSleep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WheelSleep")
+            .field("deadline", &self.deadline)
+            .field("registered", &self.entry.is_some())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::asynchronous::retry;
+    use crate::config::RetryConfig;
+    use crate::strategies::RetryStrategy;
+
+    #[test]
+    fn test_sleeps_are_due_on_their_tick_at_every_level() {
+        let mut state = State::new();
+        let deadlines = [1, 63, 64, 65, 4_095, 4_097, 300_000];
+        for deadline in deadlines {
+            let entry = Arc::new(Entry {
+                deadline,
+                fired: AtomicBool::new(false),
+                waker: Mutex::new(None),
+            });
+            state.pending += 1;
+            state.place(entry, &mut Vec::new());
+        }
+        let mut fired = Vec::new();
+        let mut due = Vec::new();
+        while state.pending > 0 {
+            state.advance(&mut due);
+            fired.extend(due.drain(..).map(|entry| (entry.deadline, state.current)));
+        }
+        assert_eq!(
+            fired,
+            deadlines
+                .iter()
+                .map(|deadline| (*deadline, *deadline))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_unrepresentable_deadlines_saturate() {
+        let wheel = TimerWheel::new(Duration::from_nanos(1));
+        assert_eq!(wheel.sleep(Duration::MAX).deadline, u64::MAX);
+        let far = wheel.sleep(Duration::from_secs(1 << 40)).deadline;
+        assert!(far > wheel.sleep(Duration::from_secs(1)).deadline);
+        assert_eq!(wheel.pending(), 0);
+    }
+
+    #[test]
+    fn test_retries_sleep_on_the_shared_wheel() {
+        let wheel = TimerWheel::new(Duration::from_millis(2));
+        let config = RetryConfig::new(3, Duration::from_millis(10), RetryStrategy::Linear)
+            .with_timer_wheel(wheel.clone());
+        let start = Instant::now();
+        let retries: Vec<_> = (0..200)
+            .map(|_| {
+                let config = config.clone();
+                async_std::task::spawn(async move {
+                    let mut attempts = 0;
+                    let result: Result<(), &str> = retry(
+                        || {
+                            attempts += 1;
+                            async { Err("unavailable") }
+                        },
+                        &config,
+                    )
+                    .await;
+                    (result, attempts)
+                })
+            })
+            .collect();
+        async_std::task::block_on(async {
+            for retry in retries {
+                assert_eq!(retry.await, (Err("unavailable"), 3));
+            }
+        });
+        assert!(start.elapsed() >= Duration::from_millis(20));
+        assert_eq!(wheel.pending(), 0);
+    }
+}
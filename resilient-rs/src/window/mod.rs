@@ -0,0 +1,275 @@
+use crate::config::FailureWindow;
+use crate::time;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// The recent call outcomes of a circuit breaker using a sliding `FailureWindow`.
+///
+/// Only the outcomes needed by the configured window are kept: the last `size` outcomes for a
+/// count-based window, and one aggregate per bucket for a time-based window. Memory therefore
+/// stays bounded by the window configuration regardless of the call rate.
+#[derive(Debug, Default)]
+pub(crate) struct OutcomeWindow {
+    outcomes: VecDeque<bool>,
+    failures: usize,
+    origin: Option<Instant>,
+    buckets: VecDeque<Bucket>,
+}
+
+/// The aggregated outcomes of the calls made during one slice of a time-based window.
+#[derive(Debug)]
+struct Bucket {
+    /// The index of the slice since the window's origin.
+    epoch: u64,
+    calls: usize,
+    failures: usize,
+}
+
+impl OutcomeWindow {
+    /// Records the outcome of a call made now.
+    pub(crate) fn record(&mut self, window: &FailureWindow, failed: bool) {
+        self.record_at(window, failed, time::now());
+    }
+
+    /// Returns `true` if the recorded outcomes reach the window's failure rate threshold now.
+    pub(crate) fn exceeds(&self, window: &FailureWindow) -> bool {
+        self.exceeds_at(window, time::now())
+    }
+
+    fn record_at(&mut self, window: &FailureWindow, failed: bool, now: Instant) {
+        match *window {
+            FailureWindow::Consecutive => {}
+            FailureWindow::Count { size, .. } => {
+                self.outcomes.push_back(failed);
+                if failed {
+                    self.failures += 1;
+                }
+                while self.outcomes.len() > size {
+                    if self.outcomes.pop_front() == Some(true) {
+                        self.failures -= 1;
+                    }
+                }
+            }
+            FailureWindow::Time {
+                duration, buckets, ..
+            } => {
+                let buckets = bucket_count(buckets);
+                self.origin.get_or_insert(now);
+                let epoch = self.epoch(duration, buckets, now);
+                match self.buckets.back_mut() {
+                    Some(bucket) if bucket.epoch == epoch => {
+                        bucket.calls += 1;
+                        bucket.failures += usize::from(failed);
+                    }
+                    _ => self.buckets.push_back(Bucket {
+                        epoch,
+                        calls: 1,
+                        failures: usize::from(failed),
+                    }),
+                }
+                while let Some(oldest) = self.buckets.front()
+                    && oldest.epoch + u64::from(buckets) <= epoch
+                {
+                    self.buckets.pop_front();
+                }
+            }
+        }
+    }
+
+    fn exceeds_at(&self, window: &FailureWindow, now: Instant) -> bool {
+        let (failure_rate_threshold, minimum_calls) = match *window {
+            FailureWindow::Consecutive => return false,
+            FailureWindow::Count {
+                failure_rate_threshold,
+                minimum_calls,
+                ..
+            }
+            | FailureWindow::Time {
+                failure_rate_threshold,
+                minimum_calls,
+                ..
+            } => (failure_rate_threshold, minimum_calls),
+        };
+        let (calls, failures) = self.totals_at(window, now);
+        calls > 0
+            && calls >= minimum_calls
+            && failures as f64 * 100.0 / calls as f64 >= failure_rate_threshold
+    }
+
+    /// Returns the number of calls and failures currently inside the window.
+    pub(crate) fn totals(&self, window: &FailureWindow) -> (usize, usize) {
+        self.totals_at(window, time::now())
+    }
+
+    fn totals_at(&self, window: &FailureWindow, now: Instant) -> (usize, usize) {
+        match *window {
+            FailureWindow::Consecutive => (0, 0),
+            FailureWindow::Count { .. } => (self.outcomes.len(), self.failures),
+            FailureWindow::Time {
+                duration, buckets, ..
+            } => {
+                let buckets = bucket_count(buckets);
+                let epoch = self.epoch(duration, buckets, now);
+                self.buckets
+                    .iter()
+                    .filter(|bucket| bucket.epoch + u64::from(buckets) > epoch)
+                    .fold((0, 0), |(calls, failures), bucket| {
+                        (calls + bucket.calls, failures + bucket.failures)
+                    })
+            }
+        }
+    }
+
+    /// Returns the index of the bucket containing `now`.
+    fn epoch(&self, duration: Duration, buckets: u32, now: Instant) -> u64 {
+        let width = (duration / buckets).as_nanos().max(1);
+        let elapsed = self.origin.map_or(Duration::ZERO, |origin| {
+            now.saturating_duration_since(origin)
+        });
+        u64::try_from(elapsed.as_nanos() / width).unwrap_or(u64::MAX)
+    }
+
+    /// Forgets every recorded outcome, e.g. when the circuit opens.
+    pub(crate) fn clear(&mut self) {
+        self.outcomes.clear();
+        self.failures = 0;
+        self.buckets.clear();
+    }
+}
+
+/// Returns the number of buckets of a time-based window, clamped to at least one so that a
+/// literal configuration skipping `CircuitBreakerConfig::validate` cannot divide by zero.
+fn bucket_count(buckets: usize) -> u32 {
+    u32::try_from(buckets.max(1)).unwrap_or(u32::MAX)
+}
+
+/// The outcomes and latencies of the most recent calls of a circuit breaker, kept for reporting.
+///
+/// Unlike `OutcomeWindow`, which decides when the circuit opens, this history is never cleared by
+/// state transitions and only feeds the breaker's success rate and latency statistics.
+#[derive(Debug, Default)]
+pub(crate) struct CallHistory {
+    samples: VecDeque<(bool, Duration)>,
+    successes: usize,
+    total_latency: Duration,
+}
+
+impl CallHistory {
+    /// Records a call, keeping at most `size` calls.
+    pub(crate) fn record(&mut self, size: usize, success: bool, latency: Duration) {
+        self.samples.push_back((success, latency));
+        self.successes += usize::from(success);
+        self.total_latency += latency;
+        while self.samples.len() > size
+            && let Some((success, latency)) = self.samples.pop_front()
+        {
+            self.successes -= usize::from(success);
+            self.total_latency -= latency;
+        }
+    }
+
+    /// Returns the percentage of successful calls, or `None` without any recorded call.
+    pub(crate) fn success_rate(&self) -> Option<f64> {
+        (!self.samples.is_empty())
+            .then(|| self.successes as f64 * 100.0 / self.samples.len() as f64)
+    }
+
+    /// Returns the mean latency, or `None` without any recorded call.
+    pub(crate) fn mean_latency(&self) -> Option<Duration> {
+        let calls = u32::try_from(self.samples.len()).ok()?;
+        (calls > 0).then(|| self.total_latency / calls)
+    }
+
+    /// Returns the latency below which `percentile` percent of the calls completed, using the
+    /// nearest-rank method, or `None` without any recorded call.
+    pub(crate) fn latency_percentile(&self, percentile: f64) -> Option<Duration> {
+        let mut latencies: Vec<Duration> =
+            self.samples.iter().map(|(_, latency)| *latency).collect();
+        latencies.sort_unstable();
+        let rank = (percentile.clamp(0.0, 100.0) / 100.0 * latencies.len() as f64).ceil() as usize;
+        latencies.get(rank.saturating_sub(1)).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_count_window_evaluates_rate_over_last_calls() {
+        let window = FailureWindow::count(4, 50.0, 4);
+        let mut outcomes = OutcomeWindow::default();
+        for failed in [true, false, true] {
+            outcomes.record(&window, failed);
+        }
+        assert!(!outcomes.exceeds(&window), "minimum calls not reached");
+        outcomes.record(&window, false);
+        assert!(outcomes.exceeds(&window));
+        outcomes.record(&window, false);
+        assert!(!outcomes.exceeds(&window), "oldest failure slid out");
+    }
+
+    #[test]
+    fn test_time_window_expires_old_buckets() {
+        let window = FailureWindow::time(Duration::from_secs(10), 50.0, 2);
+        let mut outcomes = OutcomeWindow::default();
+        let start = Instant::now();
+        outcomes.record_at(&window, true, start);
+        outcomes.record_at(&window, true, start + Duration::from_millis(100));
+        assert!(outcomes.exceeds_at(&window, start + Duration::from_secs(5)));
+
+        // Eleven seconds later both failures are outside the window.
+        let later = start + Duration::from_secs(11);
+        outcomes.record_at(&window, false, later);
+        outcomes.record_at(&window, false, later);
+        assert!(!outcomes.exceeds_at(&window, later));
+        assert_eq!(outcomes.buckets.len(), 1);
+    }
+
+    #[test]
+    fn test_time_window_memory_is_bounded_by_buckets() {
+        let window = FailureWindow::time(Duration::from_secs(1), 50.0, 1);
+        let mut outcomes = OutcomeWindow::default();
+        let start = Instant::now();
+        for i in 0..10_000 {
+            outcomes.record_at(&window, i % 2 == 0, start + Duration::from_millis(i));
+        }
+        assert!(outcomes.buckets.len() <= 10);
+    }
+
+    #[test]
+    fn test_time_window_without_buckets_uses_one() {
+        let window = FailureWindow::Time {
+            duration: Duration::from_secs(10),
+            buckets: 0,
+            failure_rate_threshold: 50.0,
+            minimum_calls: 2,
+        };
+        let mut outcomes = OutcomeWindow::default();
+        let start = Instant::now();
+        outcomes.record_at(&window, true, start);
+        outcomes.record_at(&window, true, start + Duration::from_secs(1));
+        assert!(outcomes.exceeds_at(&window, start + Duration::from_secs(5)));
+        assert!(!outcomes.exceeds_at(&window, start + Duration::from_secs(20)));
+    }
+
+    #[test]
+    fn test_call_history_keeps_last_calls() {
+        let mut history = CallHistory::default();
+        assert_eq!(history.success_rate(), None);
+        assert_eq!(history.latency_percentile(50.0), None);
+        for (i, success) in [false, true, true, true, true].into_iter().enumerate() {
+            history.record(4, success, Duration::from_millis(10 * (i as u64 + 1)));
+        }
+        assert_eq!(history.success_rate(), Some(100.0));
+        assert_eq!(history.mean_latency(), Some(Duration::from_millis(35)));
+        assert_eq!(
+            history.latency_percentile(50.0),
+            Some(Duration::from_millis(30))
+        );
+        assert_eq!(
+            history.latency_percentile(100.0),
+            Some(Duration::from_millis(50))
+        );
+    }
+}